@@ -1,9 +1,10 @@
 
 import os
+import sys
 import bpy
 from glob import glob
 from os.path import dirname, join, isfile
-from os import chmod, add_dll_directory
+from os import chmod
 
 from . import *
 
@@ -37,13 +38,28 @@ bl_info = {
 blender_classes = []
 
 
+if sys.platform == "win32":
+    LIBRARY_PREFIX = ""
+    LIBRARY_SUFFIX = ".dll"
+elif sys.platform == "darwin":
+    LIBRARY_PREFIX = "lib"
+    LIBRARY_SUFFIX = ".dylib"
+else:
+    LIBRARY_PREFIX = "lib"
+    LIBRARY_SUFFIX = ".so"
+
+
+def library_name(filename):
+    return filename.removesuffix(LIBRARY_SUFFIX).removeprefix(LIBRARY_PREFIX)
+
+
 def load_dlls():
     preferences = bpy.context.preferences.addons['INOX'].preferences
 
     if os.path.isdir(preferences.exe_path):
         if len(preferences.libs_to_load) == 0:
-            for file in os.listdir(preferences.exe_path):
-                if file.endswith(".dll") or file.endswith(".so"):
+            for file in sorted(os.listdir(preferences.exe_path)):
+                if file.startswith(LIBRARY_PREFIX) and file.endswith(LIBRARY_SUFFIX):
                     preferences.libs_to_load.append(file)
 
 
@@ -54,10 +70,23 @@ class INOXAddonPreferences(bpy.types.AddonPreferences):
 
     exe_path: bpy.props.StringProperty(
         name="INOX folder",
-        description="Set folder where inox_launcher.exe can be found",
+        description="Set folder where inox_launcher can be found",
         subtype="DIR_PATH",
         default="./bin/")
 
+    launcher_path: bpy.props.StringProperty(
+        name="INOX launcher",
+        description="Set the inox_launcher executable to use (empty to look for it in INOX folder)",
+        subtype="FILE_PATH",
+        default="")
+
+    camera_sync_rate: bpy.props.IntProperty(
+        name="Camera sync rate",
+        description="Max number of viewport camera updates sent to INOX per second",
+        default=30,
+        min=1,
+        max=120)
+
     checkboxes: bpy.props.BoolVectorProperty(
         name="DLLs to load", size=32)
 
@@ -66,13 +95,15 @@ class INOXAddonPreferences(bpy.types.AddonPreferences):
     def draw(self, context):
         layout = self.layout
         layout.prop(self, "exe_path")
+        layout.prop(self, "launcher_path")
+        layout.prop(self, "camera_sync_rate")
 
         box = layout.box()
         split = box.split(factor=.25)
         column = split.column()
         column.label(text="DLLs to load:")
         for i, lib in enumerate(self.libs_to_load):
-            name = lib.removesuffix(".dll").removesuffix(".so")
+            name = library_name(lib)
             column.row().prop(self, 'checkboxes', index=i, text=name)
 
 
@@ -82,7 +113,8 @@ blender_classes.append(INOXAddonPreferences)
 def register():
     # Ensure "Execute" permissions on files in the "bin" dir
     addon_dir = dirname(__spec__.origin)
-    add_dll_directory(addon_dir)
+    if hasattr(os, "add_dll_directory"):
+        os.add_dll_directory(addon_dir)
 
     # Register Blender Classes
     for blender_class in blender_classes:
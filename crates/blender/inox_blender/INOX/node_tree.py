@@ -1,6 +1,7 @@
 import nodeitems_utils
 import bpy
 from bpy.types import NodeTree, Node, NodeSocket, Operator, PropertyGroup
+import copy
 import json
 
 blender_classes = []
@@ -44,6 +45,8 @@ class LogicNodeTree(NodeTree):
             serialized_node["node_type"] = n.bl_idname
             node = n.serialize()
             serialized_node["node"] = node["node"]
+            # Links refer to the node by its name in the tree
+            serialized_node["node"]["name"] = n.name
             nodes.append(serialized_node)
 
         node_tree['nodes'] = nodes
@@ -184,8 +187,9 @@ def create_node_from_data(node_name, base_class, category, description, serializ
         return dictionary
 
     def serialize(self):
+        # Every node of the same type needs its own copy of fields
         output = self.serialize_fields(
-            fields_dictionary)
+            copy.deepcopy(fields_dictionary))
         return output
 
     def init(self, context):
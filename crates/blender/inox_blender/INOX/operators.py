@@ -1,15 +1,52 @@
 import bpy
 
 from glob import glob
-from os.path import join, exists
-from os import chmod, mkdir
-from pathlib import Path
+from mathutils import Matrix
+from os.path import join
+from os import chmod
 
 
 blender_classes = []
 
 inox_engine = None
 
+# Names of objects changed since last export
+changed_objects = set()
+
+# INOX scenes are exported with glTF Y-up axis while Blender is Z-up
+Y_UP_TO_Z_UP = Matrix(((1.0, 0.0, 0.0, 0.0),
+                       (0.0, 0.0, -1.0, 0.0),
+                       (0.0, 1.0, 0.0, 0.0),
+                       (0.0, 0.0, 0.0, 1.0)))
+
+
+def validate_logic(operator):
+    """Report logic trees errors in Blender UI, returning False when any is found"""
+    from INOX import inox_blender
+    errors = inox_blender.validate_logic(inox_engine)
+    for error in errors:
+        operator.report({'ERROR'}, error)
+    if len(errors) > 0:
+        operator.report({'ERROR'}, "%d errors found in logic trees" % len(errors))
+    return len(errors) == 0
+
+
+class INOXValidateLogic(bpy.types.Operator):
+    """Check logic trees against nodes registered in INOX Engine"""
+    bl_idname = "inox.validate_logic"
+    bl_label = "Validate Logic"
+
+    def execute(self, context):
+        if inox_engine is None:
+            return {'CANCELLED'}
+        if not validate_logic(self):
+            return {'CANCELLED'}
+        self.report({'INFO'}, "Logic trees are valid")
+        return {'FINISHED'}
+
+
+blender_classes.append(INOXValidateLogic)
+
 
 class INOXRun(bpy.types.Operator):
     """Run INOX Engine"""
@@ -26,20 +63,21 @@ class INOXRun(bpy.types.Operator):
             context.window_manager.popover(draw_popup)
             return {'FINISHED'}
 
+        if not validate_logic(self):
+            return {'CANCELLED'}
+
         preferences = context.preferences.addons['INOX'].preferences
 
         file_path = join(preferences.exe_path, "*")
         for file_path in glob(file_path):
             chmod(file_path, 0o755)
 
-        path = Path(preferences.exe_path).absolute()
-        last_part = path.parts[-1]
-        if last_part.endswith('debug') or last_part.endswith('release'):
-            path = path.parent.absolute().parent.absolute().parent.absolute()
-#
+        inox_engine.set_launcher_path(bpy.path.abspath(preferences.launcher_path))
+
         from INOX import inox_blender
         inox_blender.start(inox_engine)
         inox_blender.export(inox_engine, str(bpy.data.filepath), True)
+        changed_objects.clear()
 
         # Do NOT wait for the thread to be ended
         return {'FINISHED'}
@@ -48,10 +86,154 @@ class INOXRun(bpy.types.Operator):
 blender_classes.append(INOXRun)
 
 
+class INOXExport(bpy.types.Operator):
+    """Export only part of the scene to INOX, reusing what has been already exported"""
+    bl_idname = "inox.export"
+    bl_label = "Export to INOX"
+
+    mode: bpy.props.EnumProperty(
+        name="Mode",
+        items=[
+            ('SELECTED', "Selected", "Export selected objects"),
+            ('COLLECTION', "Collection", "Export objects of the chosen collection"),
+            ('CHANGED', "Changed", "Export objects changed since last export"),
+        ],
+        default='CHANGED')
+
+    def objects_to_export(self, context):
+        if self.mode == 'SELECTED':
+            return [obj.name for obj in context.selected_objects]
+        if self.mode == 'COLLECTION':
+            collection = context.scene.inox_export_collection
+            if collection is None:
+                return []
+            return [obj.name for obj in collection.all_objects]
+        return list(changed_objects)
+
+    def execute(self, context):
+        if bpy.data.filepath == "":
+            self.report({'WARNING'}, "Save Blend before exporting to INOX")
+            return {'CANCELLED'}
+        if inox_engine is None:
+            return {'CANCELLED'}
+
+        objects = self.objects_to_export(context)
+        if len(objects) == 0:
+            self.report({'INFO'}, "Nothing to export")
+            return {'FINISHED'}
+        if not validate_logic(self):
+            return {'CANCELLED'}
+
+        from INOX import inox_blender
+        inox_blender.export(inox_engine, str(bpy.data.filepath),
+                            inox_engine.is_running(), objects)
+        changed_objects.difference_update(objects)
+        self.report({'INFO'}, "Exported %d objects" % len(objects))
+        return {'FINISHED'}
+
+
+blender_classes.append(INOXExport)
+
+
+@bpy.app.handlers.persistent
+def track_changed_objects(scene, depsgraph):
+    for update in depsgraph.updates:
+        data = update.id.original
+        if isinstance(data, bpy.types.Object):
+            # Selection changes (even the ones done while exporting) are not relevant
+            if update.is_updated_geometry or update.is_updated_transform:
+                changed_objects.add(data.name)
+        elif isinstance(data, bpy.types.Material):
+            for obj in scene.objects:
+                if any(slot.material == data for slot in obj.material_slots):
+                    changed_objects.add(obj.name)
+        else:
+            for obj in scene.objects:
+                if obj.data == data:
+                    changed_objects.add(obj.name)
+
+
+def matrix_to_list(matrix):
+    # column major order as expected by the engine
+    return [matrix[row][col] for col in range(4) for row in range(4)]
+
+
+def find_view3d_region(context):
+    for window in context.window_manager.windows:
+        for area in window.screen.areas:
+            if area.type == 'VIEW_3D':
+                return area.spaces.active.region_3d
+    return None
+
+
+class INOXCameraSync(bpy.types.Operator):
+    """Stream Blender 3D viewport camera into INOX Engine"""
+    bl_idname = "inox.camera_sync"
+    bl_label = "Sync viewport camera"
+
+    is_running = False
+
+    _timer = None
+    _last_sent = None
+
+    def modal(self, context, event):
+        if not INOXCameraSync.is_running:
+            self.stop(context)
+            return {'CANCELLED'}
+
+        if event.type == 'TIMER':
+            region_3d = find_view3d_region(context)
+            if region_3d is not None:
+                # view_matrix is world to view: the engine gets the camera world transform
+                transform = matrix_to_list(
+                    Y_UP_TO_Z_UP.inverted() @ region_3d.view_matrix.inverted())
+                proj = matrix_to_list(region_3d.window_matrix)
+                # Avoid flooding the engine when the viewport is not moving
+                if (transform, proj) != self._last_sent:
+                    from INOX import inox_blender
+                    if inox_blender.sync_camera(inox_engine, transform, proj):
+                        self._last_sent = (transform, proj)
+
+        return {'PASS_THROUGH'}
+
+    def execute(self, context):
+        if INOXCameraSync.is_running:
+            INOXCameraSync.is_running = False
+            return {'FINISHED'}
+
+        if inox_engine is None or not inox_engine.is_running():
+            self.report({'WARNING'}, "Run INOX before syncing the camera")
+            return {'CANCELLED'}
+
+        preferences = context.preferences.addons['INOX'].preferences
+        INOXCameraSync.is_running = True
+        self._last_sent = None
+        self._timer = context.window_manager.event_timer_add(
+            1.0 / preferences.camera_sync_rate, window=context.window)
+        context.window_manager.modal_handler_add(self)
+        return {'RUNNING_MODAL'}
+
+    def stop(self, context):
+        if self._timer is not None:
+            context.window_manager.event_timer_remove(self._timer)
+            self._timer = None
+        if inox_engine is not None:
+            from INOX import inox_blender
+            inox_blender.stop_camera_sync(inox_engine)
+
+
+blender_classes.append(INOXCameraSync)
+
+
 def register():
     for blender_class in blender_classes:
         bpy.utils.register_class(blender_class)
 
+    bpy.types.Scene.inox_export_collection = bpy.props.PointerProperty(
+        name="Collection", description="Collection to export to INOX",
+        type=bpy.types.Collection)
+    bpy.app.handlers.depsgraph_update_post.append(track_changed_objects)
+
     prefs = bpy.context.preferences.addons['INOX'].preferences
     libs_to_load = []
     for i, v in enumerate(prefs.checkboxes):
@@ -64,11 +246,17 @@ def register():
         from INOX import node_tree
 
         inox_engine = inox_blender.INOXEngine(
-            str(prefs.exe_path), libs_to_load)
+            str(prefs.exe_path), libs_to_load, bpy.path.abspath(prefs.launcher_path))
         node_tree.register_nodes(inox_engine)
 
 
 def unregister():
+    INOXCameraSync.is_running = False
+
+    if track_changed_objects in bpy.app.handlers.depsgraph_update_post:
+        bpy.app.handlers.depsgraph_update_post.remove(track_changed_objects)
+    del bpy.types.Scene.inox_export_collection
+
     blender_classes.reverse()
     for blender_class in blender_classes:
         bpy.utils.unregister_class(blender_class)
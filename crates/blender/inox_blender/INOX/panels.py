@@ -1,5 +1,6 @@
 import bpy
 from . import node_tree
+from .operators import INOXCameraSync
 
 blender_classes = []
 
@@ -17,6 +18,24 @@ class INOXRunner(bpy.types.Panel):
 
         row = layout.row()
         row.operator("inox.run", icon='PLAY')
+        row.operator("inox.validate_logic", icon='CHECKMARK')
+
+        box = layout.box()
+        box.label(text="Export")
+        row = box.row(align=True)
+        row.operator("inox.export", text="Changed").mode = 'CHANGED'
+        row.operator("inox.export", text="Selected").mode = 'SELECTED'
+        row = box.row(align=True)
+        row.prop(context.scene, "inox_export_collection", text="")
+        row.operator("inox.export", text="Collection").mode = 'COLLECTION'
+
+        row = layout.row()
+        if INOXCameraSync.is_running:
+            row.operator("inox.camera_sync", icon='PAUSE',
+                         text="Stop viewport camera sync")
+        else:
+            row.operator("inox.camera_sync", icon='VIEW_CAMERA',
+                         text="Sync viewport camera")
 
 
 class INOXPropertiesGroup(bpy.types.PropertyGroup):
@@ -5,7 +5,7 @@ use pyo3::{pyclass, pymethods, PyResult, Python};
 
 use inox_binarizer::{Binarizer, BinarizerParameters};
 use inox_core::App;
-use inox_filesystem::EXE_PATH;
+use inox_filesystem::{executable_filename, library_name, EXE_PATH};
 use inox_nodes::{LogicNodeRegistry, NodeType};
 use inox_platform::PLATFORM_TYPE_PC;
 use inox_resources::{DATA_FOLDER, DATA_RAW_FOLDER};
@@ -14,19 +14,28 @@ use std::{
     env,
     io::Write,
     net::{Shutdown, TcpStream},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::Command,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, RwLock,
     },
     thread::{self, JoinHandle},
+    time::Duration,
 };
 
+const LAUNCHER_NAME: &str = "inox_launcher";
+const SERVER_ADDRESS: &str = "127.0.0.1:1983";
+// Connector reads messages in fixed size chunks padded with zeros
+const MESSAGE_SIZE: usize = 1024;
+const CONNECTION_RETRY_TIME: Duration = Duration::from_millis(500);
+const CLIENT_SLEEP_TIME: Duration = Duration::from_millis(5);
+
 #[derive(Default)]
 struct ThreadData {
     can_continue: Arc<AtomicBool>,
     files_to_load: Vec<PathBuf>,
+    camera_sync: Option<String>,
 }
 
 unsafe impl Send for ThreadData {}
@@ -40,6 +49,7 @@ pub struct INOXEngine {
     app: App,
     app_dir: PathBuf,
     working_dir: PathBuf,
+    launcher_path: PathBuf,
     plugins: Vec<String>,
     thread_data: Arc<RwLock<ThreadData>>,
     process: Option<std::process::Child>,
@@ -49,15 +59,21 @@ pub struct INOXEngine {
 #[pymethods]
 impl INOXEngine {
     #[new]
-    fn new(executable_path: &str, plugins_to_load: Vec<String>) -> Self {
+    #[pyo3(signature = (executable_path, plugins_to_load, launcher_path=None))]
+    pub fn new(
+        executable_path: &str,
+        plugins_to_load: Vec<String>,
+        launcher_path: Option<&str>,
+    ) -> Self {
         let app_dir = PathBuf::from(executable_path);
+        let app_dir = app_dir.canonicalize().unwrap_or(app_dir);
+
+        let working_dir = find_working_dir(app_dir.as_path());
+        let launcher_path = launcher_path
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| app_dir.join(executable_filename(LAUNCHER_NAME)));
 
-        let mut working_dir = app_dir.clone();
-        if working_dir.ends_with("release") || working_dir.ends_with("debug") {
-            working_dir.pop();
-            working_dir.pop();
-            working_dir.pop();
-        }
         env::set_var(EXE_PATH, app_dir.clone());
         env::set_current_dir(&working_dir).ok();
 
@@ -78,14 +94,7 @@ impl INOXEngine {
 
         let mut plugins = Vec::new();
         plugins_to_load.iter().for_each(|plugin| {
-            plugins.push(
-                PathBuf::from(plugin)
-                    .file_stem()
-                    .unwrap()
-                    .to_str()
-                    .unwrap()
-                    .to_string(),
-            );
+            plugins.push(library_name(PathBuf::from(plugin).file_name().unwrap()));
             let mut plugin_path = app_dir.clone();
             plugin_path = plugin_path.join(plugin);
             app.add_dynamic_plugin(plugin_path.as_path());
@@ -94,6 +103,7 @@ impl INOXEngine {
         Self {
             app_dir,
             working_dir,
+            launcher_path,
             is_running: Arc::new(AtomicBool::new(false)),
             thread_data: Arc::new(RwLock::new(ThreadData::default())),
             process: None,
@@ -108,23 +118,51 @@ impl INOXEngine {
     pub fn is_running(&self) -> bool {
         self.is_running.load(Ordering::SeqCst)
     }
+
+    pub fn working_dir(&self) -> String {
+        self.working_dir.to_str().unwrap_or_default().to_string()
+    }
+
+    pub fn launcher_path(&self) -> String {
+        self.launcher_path.to_str().unwrap_or_default().to_string()
+    }
+
+    pub fn set_launcher_path(&mut self, launcher_path: &str) {
+        self.launcher_path = if launcher_path.is_empty() {
+            self.app_dir.join(executable_filename(LAUNCHER_NAME))
+        } else {
+            PathBuf::from(launcher_path)
+        };
+    }
+
     pub fn start(&mut self) -> PyResult<bool> {
+        if !self.launcher_path.is_file() {
+            eprintln!(
+                "[Blender] Unable to find INOX launcher in {:?}",
+                self.launcher_path
+            );
+            return Ok(false);
+        }
         println!("[Blender] INOXEngine started");
 
-        let path = self.app_dir.join("inox_launcher.exe");
-
-        let mut command = Command::new(path.as_path());
+        let mut command = Command::new(self.launcher_path.as_path());
         self.plugins.iter().for_each(|plugin| {
-            let string = "-plugin ".to_string() + plugin;
-            command.arg(string);
+            command.args(["-plugin", plugin]);
         });
         command.args(["-plugin", "inox_connector"]);
         command.args(["-plugin", "inox_viewer"]);
         command.current_dir(self.working_dir.as_path());
 
-        if let Ok(process) = command.spawn() {
-            self.process = Some(process);
-            self.is_running.store(true, Ordering::SeqCst);
+        match command.spawn() {
+            Ok(process) => {
+                self.process = Some(process);
+                // A client thread of a previous run may still be waiting to connect:
+                // it keeps the old flag and ends instead of running along the new one
+                self.is_running = Arc::new(AtomicBool::new(true));
+            }
+            Err(e) => {
+                eprintln!("[Blender] Unable to launch {:?}: {e}", self.launcher_path);
+            }
         }
 
         if self.process.is_some() {
@@ -148,17 +186,20 @@ impl INOXEngine {
         self.is_running.store(false, Ordering::SeqCst);
     }
 
+    #[pyo3(signature = (file_to_export, load_immediately, objects_to_export=None))]
     pub fn export(
         &mut self,
         py: Python,
         file_to_export: &str,
         load_immediately: bool,
+        objects_to_export: Option<Vec<String>>,
     ) -> PyResult<bool> {
         let current_dir = self.working_dir.clone();
         let scenes = self.exporter.process(
             py,
             current_dir.as_path(),
             PathBuf::from(file_to_export).as_path(),
+            objects_to_export,
         )?;
 
         self.binarizer.start();
@@ -179,6 +220,36 @@ impl INOXEngine {
         Ok(true)
     }
 
+    // Transform is the camera world transform, in the engine Y-up space
+    pub fn sync_camera(&self, transform: Vec<f32>, proj: Vec<f32>) -> PyResult<bool> {
+        if transform.len() != 16 || proj.len() != 16 || !self.is_running() {
+            return Ok(false);
+        }
+        // Only the latest camera state matters: older ones not yet sent are replaced
+        self.thread_data.write().unwrap().camera_sync = Some(format!(
+            "-camera_transform {} -camera_proj {}",
+            matrix_to_string(&transform),
+            matrix_to_string(&proj)
+        ));
+        Ok(true)
+    }
+
+    pub fn stop_camera_sync(&self) -> PyResult<bool> {
+        if !self.is_running() {
+            return Ok(false);
+        }
+        self.thread_data.write().unwrap().camera_sync = Some("-camera_sync_stop".to_string());
+        Ok(true)
+    }
+
+    pub fn validate_logic(&self, py: Python) -> PyResult<Vec<String>> {
+        let data = self.app.context().shared_data();
+
+        let registry = LogicNodeRegistry::get(data);
+
+        self.exporter.validate_logic(py, registry)
+    }
+
     pub fn register_nodes(&self, py: Python) -> PyResult<bool> {
         let data = self.app.context().shared_data();
 
@@ -189,6 +260,47 @@ impl INOXEngine {
     }
 }
 
+// The launcher can live in a cargo target folder (target/<profile> or target/<triple>/<profile>)
+// or in a distributed bin folder: the working dir is the first ancestor that contains data_raw
+pub fn find_working_dir(app_dir: &Path) -> PathBuf {
+    if let Some(dir) = app_dir
+        .ancestors()
+        .find(|dir| dir.join(DATA_RAW_FOLDER).is_dir())
+    {
+        return dir.to_path_buf();
+    }
+    if let Some(target_dir) = app_dir.ancestors().find(|dir| dir.ends_with("target")) {
+        if let Some(dir) = target_dir
+            .parent()
+            .and_then(|crates_dir| crates_dir.parent())
+        {
+            return dir.to_path_buf();
+        }
+    }
+    app_dir.to_path_buf()
+}
+
+// Values are enclosed in brackets so that negative numbers are not parsed as commands
+fn matrix_to_string(values: &[f32]) -> String {
+    let values = values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+    format!("[{values}]")
+}
+
+fn send_message(stream: &mut TcpStream, message: &str) -> bool {
+    let bytes = message.as_bytes();
+    if bytes.len() > MESSAGE_SIZE {
+        eprintln!("[Blender] Message too long to be sent: {message}");
+        return true;
+    }
+    let mut buffer = [0u8; MESSAGE_SIZE];
+    buffer[..bytes.len()].copy_from_slice(bytes);
+    stream.write_all(&buffer).is_ok()
+}
+
 fn add_node_in_blender(node: &dyn NodeType, py: Python) {
     let node_name = node.name();
     let category = node.category();
@@ -214,29 +326,44 @@ fn add_node_in_blender(node: &dyn NodeType, py: Python) {
 }
 
 fn client_thread_execution(thread_data: Arc<RwLock<ThreadData>>) {
-    match TcpStream::connect("127.0.0.1:1983") {
-        Ok(mut stream) => {
-            println!("[Blender] Successfully connected to server in port 1983");
-            let is_running = thread_data.read().unwrap().can_continue.clone();
-            while is_running.load(Ordering::SeqCst) {
-                let file = { thread_data.write().unwrap().files_to_load.pop() };
-                if let Some(file) = file {
-                    let file = file.to_str().unwrap_or_default().to_string();
-
-                    println!("[Blender] INOXEngine sending to load {file:?}");
+    let is_running = thread_data.read().unwrap().can_continue.clone();
+    let mut stream = None;
+    let mut is_first_failure = true;
+    // Engine could take a while before starting to listen: only the first failure is logged
+    while stream.is_none() && is_running.load(Ordering::SeqCst) {
+        match TcpStream::connect(SERVER_ADDRESS) {
+            Ok(s) => stream = Some(s),
+            Err(e) => {
+                if is_first_failure {
+                    println!("[Blender] Failed to connect: {e}, retrying until the engine listens");
+                    is_first_failure = false;
+                }
+                thread::sleep(CONNECTION_RETRY_TIME);
+            }
+        }
+    }
+    if let Some(mut stream) = stream {
+        println!("[Blender] Successfully connected to server in {SERVER_ADDRESS}");
+        while is_running.load(Ordering::SeqCst) {
+            let file = { thread_data.write().unwrap().files_to_load.pop() };
+            if let Some(file) = file {
+                let file = file.to_str().unwrap_or_default().to_string();
 
-                    let message = format!("-load_file {file}");
-                    let msg = message.as_bytes();
+                println!("[Blender] INOXEngine sending to load {file:?}");
 
-                    stream.write_all(msg).ok();
+                let message = format!("-load_file {file}");
+                if !send_message(&mut stream, message.as_str()) {
+                    break;
                 }
             }
-            stream
-                .shutdown(Shutdown::Both)
-                .expect("[Blender] Client thread shutdown call failed");
-        }
-        Err(e) => {
-            println!("[Blender] Failed to connect: {e}");
+            let camera_sync = { thread_data.write().unwrap().camera_sync.take() };
+            if let Some(message) = camera_sync {
+                if !send_message(&mut stream, message.as_str()) {
+                    break;
+                }
+            }
+            thread::sleep(CLIENT_SLEEP_TIME);
         }
+        stream.shutdown(Shutdown::Both).ok();
     }
 }
@@ -1,18 +1,32 @@
 use std::{
-    fs::{create_dir_all, File},
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::{create_dir_all, read_dir, remove_file, File},
     io::{BufWriter, Write},
     path::{Path, PathBuf},
 };
 
 use pyo3::{
     prelude::PyAnyMethods,
-    types::{PyDict, PyDictMethods, PyList},
-    IntoPyObjectExt, Py, PyAny, PyResult, Python,
+    types::{PyDict, PyDictMethods, PyList, PyListMethods},
+    Bound, Py, PyAny, PyResult, Python,
 };
 
-use inox_nodes::LogicData;
+use inox_binarizer::{GltfChunkData, GltfIndexData, GLTF_CHUNKS_FOLDER};
+use inox_nodes::{LogicData, LogicNodeRegistry};
 
-use inox_serialize::SerializeFile;
+use inox_serialize::{SerializationType, SerializeFile};
+
+const GLTF_EXTENSION: &str = "gltf";
+const GLTF_BUFFER_EXTENSION: &str = "bin";
+// Object chunks always end with a hash suffix, so they can't be named as the scene chunk
+const SCENE_CHUNK_NAME: &str = "scene";
+
+struct SceneObject {
+    name: String,
+    // Object whose chunk this one is exported with
+    owner: String,
+    parent: Option<String>,
+}
 
 #[derive(Default)]
 pub struct Exporter {
@@ -21,11 +35,16 @@ pub struct Exporter {
 }
 
 impl Exporter {
+    // Visible objects are exported in glTF chunks listed in an index file.
+    // A full export (objects_to_export is None) writes all of them in a single scene chunk.
+    // Otherwise objects_to_export are moved in chunks of their own and only those are re-exported,
+    // so that the binarizer has to process only what actually changed.
     pub fn process(
         &mut self,
         py: Python,
         working_dir: &Path,
         file_to_export: &Path,
+        objects_to_export: Option<Vec<String>>,
     ) -> PyResult<Vec<PathBuf>> {
         println!("Exporting scene from Blender...");
 
@@ -38,64 +57,193 @@ impl Exporter {
                 .join("data_raw")
                 .join("blender_export")
                 .join(scene_name);
+            let chunks_dir = self.export_dir.join(GLTF_CHUNKS_FOLDER);
+
+            if create_dir_all(chunks_dir.as_path()).is_ok() {
+                let context = py.import("bpy")?.getattr("context")?;
+                let objects = context.getattr("scene")?.getattr("objects")?.call_method(
+                    "values",
+                    (),
+                    None,
+                )?;
+                let objects = objects.cast::<PyList>()?;
+
+                let view_layer_objects = context.getattr("view_layer")?.getattr("objects")?;
+                let previous_active = view_layer_objects.getattr("active")?;
+                let mut previously_selected = Vec::new();
+                let mut scene_objects = Vec::new();
+
+                for object in objects.iter() {
+                    if !object.call_method0("visible_get")?.extract::<bool>()? {
+                        continue;
+                    }
+                    let name: String = object.getattr("name")?.extract()?;
+                    if object.call_method0("select_get")?.extract::<bool>()? {
+                        previously_selected.push(name.clone());
+                    }
+                    let parent = object.getattr("parent")?;
+                    scene_objects.push(SceneObject {
+                        name,
+                        owner: chunk_owner(&object)?,
+                        parent: if parent.is_none() {
+                            None
+                        } else {
+                            Some(parent.getattr("name")?.extract()?)
+                        },
+                    });
+                }
+                let by_name: HashMap<&str, &SceneObject> = scene_objects
+                    .iter()
+                    .map(|object| (object.name.as_str(), object))
+                    .collect();
+
+                // Owners exported in their own chunk now or in a previous export
+                let split: HashSet<&str> = match &objects_to_export {
+                    None => HashSet::new(),
+                    Some(names) => scene_objects
+                        .iter()
+                        .filter(|object| {
+                            names.contains(&object.name)
+                                || chunks_dir.join(owner_chunk_name(&object.owner)).exists()
+                        })
+                        .map(|object| object.owner.as_str())
+                        .collect(),
+                };
+                let is_newly_split = split
+                    .iter()
+                    .any(|owner| !chunks_dir.join(owner_chunk_name(owner)).exists());
+
+                // None is the scene chunk, holding objects not split from it
+                let mut chunk_members: BTreeMap<Option<&str>, Vec<&str>> = BTreeMap::new();
+                scene_objects.iter().for_each(|object| {
+                    chunk_members
+                        .entry(split_owner(&object.name, &by_name, &split))
+                        .or_default()
+                        .push(object.name.as_str());
+                });
 
-            if create_dir_all(self.export_dir.as_path()).is_ok() {
-                // Blender data import
-                let export_scene = py.import("bpy")?.getattr("ops")?.getattr("export_scene")?;
-                let scene_path = self.export_dir.join(format!("{}.{}", scene_name, "gltf"));
-                let scene_path = scene_path.to_str().unwrap_or_default().to_string();
-
-                let kwargs = PyDict::new(py);
-                kwargs.set_item("filepath", scene_path.clone())?;
-                kwargs.set_item("check_existing", true)?;
-                kwargs.set_item("export_format", "GLTF_SEPARATE")?;
-                kwargs.set_item("export_apply", true)?;
-                kwargs.set_item("export_materials", "EXPORT")?;
-                kwargs.set_item("export_cameras", true)?;
-                kwargs.set_item("export_yup", true)?;
-                kwargs.set_item("export_lights", true)?;
-                kwargs.set_item("export_extras", true)?;
-                kwargs.set_item("export_texture_dir", "./textures/")?;
-                export_scene.call_method("gltf", (), Some(&kwargs))?;
+                let mut index_data = GltfIndexData::default();
+                for (owner, names) in chunk_members.iter() {
+                    let chunk_name = owner.map_or_else(
+                        || format!("{SCENE_CHUNK_NAME}.{GLTF_EXTENSION}"),
+                        owner_chunk_name,
+                    );
+                    let chunk_path = chunks_dir.join(chunk_name.as_str());
+                    let needs_export = !chunk_path.exists()
+                        || match (owner, &objects_to_export) {
+                            (_, None) => true,
+                            // Objects of the scene chunk change only when moved in their own chunk
+                            (None, Some(_)) => is_newly_split,
+                            (Some(_), Some(requested)) => {
+                                names.iter().any(|name| requested.iter().any(|r| r == name))
+                            }
+                        };
+                    if needs_export {
+                        self.export_chunk(py, objects, names, chunk_path.as_path())?;
+                    }
+                    // Chunks of objects whose parent is in the scene chunk are kept at the root
+                    let parent = owner
+                        .and_then(|owner| by_name.get(owner))
+                        .and_then(|object| object.parent.as_deref())
+                        .and_then(|parent| split_owner(parent, &by_name, &split))
+                        .filter(|parent| Some(*parent) != *owner)
+                        .map(|parent| {
+                            PathBuf::from(GLTF_CHUNKS_FOLDER).join(owner_chunk_name(parent))
+                        });
+                    index_data.chunks.push(GltfChunkData {
+                        path: PathBuf::from(GLTF_CHUNKS_FOLDER).join(chunk_name),
+                        parent,
+                    });
+                }
+
+                // Restoring user selection
+                for object in objects.iter() {
+                    let name: String = object.getattr("name")?.extract()?;
+                    let is_selected = previously_selected.contains(&name);
+                    object.call_method1("select_set", (is_selected,)).ok();
+                }
+                view_layer_objects.setattr("active", previous_active)?;
+
+                remove_stale_chunks(chunks_dir.as_path(), &index_data);
+
+                let index_path =
+                    self.export_dir
+                        .join(format!("{}.{}", scene_name, GltfIndexData::extension()));
+                index_data.save_to_file(index_path.as_path(), SerializationType::Json);
 
                 self.export_custom_data(py, self.export_dir.as_path())?;
 
+                let scene_path = index_path.to_str().unwrap_or_default().to_string();
                 let scene_path = scene_path.replace("data_raw", "data");
-                let scene_path = scene_path.replace(".gltf", ".scene");
+                let scene_path = scene_path.replace(
+                    format!(".{}", GltfIndexData::extension()).as_str(),
+                    ".scene",
+                );
                 scene_paths.push(PathBuf::from(scene_path));
             }
         }
         Ok(scene_paths)
     }
 
-    fn export_custom_data(&self, py: Python, export_dir: &Path) -> PyResult<bool> {
-        let data = py.import("bpy")?.getattr("data")?;
-
-        // For every Blender scene
-        let scenes = data.getattr("scenes")?.call_method("values", (), None)?;
-        let scenes = scenes.cast::<PyList>()?;
-        if let Ok(scene) = scenes.try_iter() {
-            let objects = scene.getattr("objects")?.call_method("values", (), None)?;
-            let objects = objects.cast::<PyList>()?;
-            if let Ok(object) = objects.try_iter() {
-                self.process_object_properties(py, &object.into_py_any(py)?, export_dir)?;
-            }
+    fn export_chunk(
+        &self,
+        py: Python,
+        objects: &Bound<PyList>,
+        object_names: &[&str],
+        chunk_path: &Path,
+    ) -> PyResult<bool> {
+        for object in objects.iter() {
+            let name: String = object.getattr("name")?.extract()?;
+            object
+                .call_method1("select_set", (object_names.contains(&name.as_str()),))
+                .ok();
         }
+
+        let export_scene = py.import("bpy")?.getattr("ops")?.getattr("export_scene")?;
+        let chunk_path = chunk_path.to_str().unwrap_or_default().to_string();
+
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("filepath", chunk_path)?;
+        kwargs.set_item("check_existing", false)?;
+        kwargs.set_item("use_selection", true)?;
+        kwargs.set_item("export_format", "GLTF_SEPARATE")?;
+        kwargs.set_item("export_apply", true)?;
+        kwargs.set_item("export_materials", "EXPORT")?;
+        kwargs.set_item("export_cameras", true)?;
+        kwargs.set_item("export_yup", true)?;
+        kwargs.set_item("export_lights", true)?;
+        kwargs.set_item("export_extras", true)?;
+        kwargs.set_item("export_texture_dir", "./textures/")?;
+        export_scene.call_method("gltf", (), Some(&kwargs))?;
+        Ok(true)
+    }
+
+    fn export_custom_data(&self, py: Python, export_dir: &Path) -> PyResult<bool> {
+        for_each_logic(py, |logic| {
+            self.export_logic(py, logic, export_dir)?;
+            Ok(())
+        })?;
         Ok(true)
     }
 
-    fn process_object_properties(
+    // Logic trees are validated against registered nodes before being exported,
+    // otherwise errors would show up only when the engine tries to run them
+    pub fn validate_logic(
         &self,
         py: Python,
-        object: &Py<PyAny>,
-        path: &Path,
-    ) -> PyResult<bool> {
-        if let Ok(properties) = object.getattr(py, "inox_properties") {
-            if let Ok(logic) = properties.getattr(py, "logic") {
-                self.export_logic(py, &logic, path)?;
-            }
-        }
-        Ok(true)
+        registry: &LogicNodeRegistry,
+    ) -> PyResult<Vec<String>> {
+        let mut errors = Vec::new();
+        for_each_logic(py, |logic| {
+            let name: String = logic.getattr(py, "name")?.extract(py)?;
+            let data: String = logic.call_method(py, "serialize", (), None)?.extract(py)?;
+            registry
+                .validate_tree(data.as_bytes())
+                .into_iter()
+                .for_each(|e| errors.push(format!("{name}: {e}")));
+            Ok(())
+        })?;
+        Ok(errors)
     }
 
     fn export_logic(&self, py: Python, logic: &Py<PyAny>, path: &Path) -> PyResult<bool> {
@@ -118,3 +266,139 @@ impl Exporter {
         Ok(true)
     }
 }
+
+// Calls f once for every logic tree used by objects of any Blender scene
+fn for_each_logic<F>(py: Python, mut f: F) -> PyResult<()>
+where
+    F: FnMut(&Py<PyAny>) -> PyResult<()>,
+{
+    let mut visited = HashSet::new();
+    let data = py.import("bpy")?.getattr("data")?;
+    let scenes = data.getattr("scenes")?.call_method("values", (), None)?;
+    let scenes = scenes.cast::<PyList>()?;
+    for scene in scenes.iter() {
+        let objects = scene.getattr("objects")?.call_method("values", (), None)?;
+        let objects = objects.cast::<PyList>()?;
+        for object in objects.iter() {
+            let Ok(properties) = object.getattr("inox_properties") else {
+                continue;
+            };
+            let Ok(logic) = properties.getattr("logic") else {
+                continue;
+            };
+            if logic.is_none() {
+                continue;
+            }
+            let name: String = logic.getattr("name")?.extract()?;
+            if visited.insert(name) {
+                f(&logic.unbind())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Armatures are exported together with the objects they parent or skin,
+// otherwise skins would reference joints living in another chunk
+fn chunk_owner(object: &Bound<PyAny>) -> PyResult<String> {
+    let mut owner: String = object.getattr("name")?.extract()?;
+    let mut visited = HashSet::new();
+    let mut current = Some(object.clone());
+    while let Some(object) = current {
+        let name: String = object.getattr("name")?.extract()?;
+        if !visited.insert(name.clone()) {
+            break;
+        }
+        if object.getattr("type")?.extract::<String>()? == "ARMATURE" {
+            owner = name;
+        }
+        current = match skinning_armature(&object)? {
+            Some(armature) => Some(armature),
+            None => Some(object.getattr("parent")?).filter(|parent| !parent.is_none()),
+        };
+    }
+    Ok(owner)
+}
+
+fn skinning_armature<'py>(object: &Bound<'py, PyAny>) -> PyResult<Option<Bound<'py, PyAny>>> {
+    for modifier in object.getattr("modifiers")?.try_iter()? {
+        let modifier = modifier?;
+        if modifier.getattr("type")?.extract::<String>()? == "ARMATURE" {
+            let armature = modifier.getattr("object")?;
+            if !armature.is_none() {
+                return Ok(Some(armature));
+            }
+        }
+    }
+    Ok(None)
+}
+
+// Objects go in the chunk of their closest ancestor split from the scene chunk, if any
+fn split_owner<'a>(
+    object_name: &str,
+    objects: &HashMap<&str, &'a SceneObject>,
+    split: &HashSet<&str>,
+) -> Option<&'a str> {
+    let mut visited = HashSet::new();
+    let mut current = objects.get(object_name).copied();
+    while let Some(object) = current {
+        if split.contains(object.owner.as_str()) {
+            return Some(object.owner.as_str());
+        }
+        if !visited.insert(object.name.as_str()) {
+            break;
+        }
+        current = object
+            .parent
+            .as_deref()
+            .and_then(|parent| objects.get(parent).copied());
+    }
+    None
+}
+
+fn owner_chunk_name(owner: &str) -> String {
+    format!("{}.{}", chunk_filename(owner), GLTF_EXTENSION)
+}
+
+// Object names are sanitized to be valid filenames and suffixed with a hash of the original name,
+// so that objects like "Cube.001" and "Cube_001" don't end up in the same chunk file
+pub(crate) fn chunk_filename(object_name: &str) -> String {
+    let name: String = object_name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{name}_{:08x}", name_hash(object_name))
+}
+
+// FNV-1a, stable across runs and platforms unlike std hashers
+fn name_hash(name: &str) -> u32 {
+    name.bytes().fold(0x811c9dc5, |hash, b| {
+        (hash ^ b as u32).wrapping_mul(0x01000193)
+    })
+}
+
+// Chunks of objects that have been removed or hidden in Blender
+fn remove_stale_chunks(chunks_dir: &Path, index_data: &GltfIndexData) {
+    if let Ok(dir) = read_dir(chunks_dir) {
+        dir.flatten().for_each(|entry| {
+            let path = entry.path();
+            let is_chunk_file = path
+                .extension()
+                .is_some_and(|ext| ext == GLTF_EXTENSION || ext == GLTF_BUFFER_EXTENSION);
+            if !is_chunk_file {
+                return;
+            }
+            let chunk = PathBuf::from(GLTF_CHUNKS_FOLDER)
+                .join(path.with_extension(GLTF_EXTENSION).file_name().unwrap());
+            if !index_data.chunks.iter().any(|c| c.path == chunk) {
+                remove_file(path).ok();
+            }
+        });
+    }
+}
@@ -4,6 +4,8 @@ use pyo3::prelude::*;
 
 mod engine;
 mod exporter;
+#[cfg(test)]
+mod tests;
 
 use engine::INOXEngine;
 
@@ -16,6 +18,9 @@ fn inox_blender(_py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(start, m)?)?;
     m.add_function(wrap_pyfunction!(export, m)?)?;
     m.add_function(wrap_pyfunction!(register_nodes, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_logic, m)?)?;
+    m.add_function(wrap_pyfunction!(sync_camera, m)?)?;
+    m.add_function(wrap_pyfunction!(stop_camera_sync, m)?)?;
     Ok(())
 }
 
@@ -34,16 +39,33 @@ fn start(inox_engine: &mut INOXEngine) -> PyResult<bool> {
 }
 
 #[pyfunction]
+#[pyo3(signature = (inox_engine, file_to_export, load_immediately, objects_to_export=None))]
 fn export(
     py: Python,
     inox_engine: &mut INOXEngine,
     file_to_export: &str,
     load_immediately: bool,
+    objects_to_export: Option<Vec<String>>,
 ) -> PyResult<bool> {
-    inox_engine.export(py, file_to_export, load_immediately)
+    inox_engine.export(py, file_to_export, load_immediately, objects_to_export)
 }
 
 #[pyfunction]
 fn register_nodes(py: Python, inox_engine: &INOXEngine) -> PyResult<bool> {
     inox_engine.register_nodes(py)
 }
+
+#[pyfunction]
+fn validate_logic(py: Python, inox_engine: &INOXEngine) -> PyResult<Vec<String>> {
+    inox_engine.validate_logic(py)
+}
+
+#[pyfunction]
+fn sync_camera(inox_engine: &INOXEngine, transform: Vec<f32>, proj: Vec<f32>) -> PyResult<bool> {
+    inox_engine.sync_camera(transform, proj)
+}
+
+#[pyfunction]
+fn stop_camera_sync(inox_engine: &INOXEngine) -> PyResult<bool> {
+    inox_engine.stop_camera_sync()
+}
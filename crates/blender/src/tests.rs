@@ -0,0 +1,141 @@
+use std::{
+    fs::{create_dir_all, remove_dir_all, write},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use inox_filesystem::executable_filename;
+use inox_resources::DATA_RAW_FOLDER;
+
+use crate::{
+    engine::{find_working_dir, INOXEngine},
+    exporter::chunk_filename,
+};
+
+const ARGS_FILE: &str = "launcher_args.txt";
+
+fn create_test_folder(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join("inox_blender_tests").join(name);
+    remove_dir_all(folder.as_path()).ok();
+    create_dir_all(folder.as_path()).unwrap();
+    folder
+}
+
+// Stand-in for inox_launcher that just records the arguments it received
+// in its current (working) directory
+fn create_stand_in_launcher(folder: &Path, name: &str) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = folder.join(name);
+        write(
+            path.as_path(),
+            format!("#!/bin/sh\necho \"$@\" > {ARGS_FILE}\n"),
+        )
+        .unwrap();
+        std::fs::set_permissions(path.as_path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+    #[cfg(windows)]
+    {
+        let path = folder.join(format!("{name}.cmd"));
+        write(path.as_path(), format!("@echo %* > {ARGS_FILE}\r\n")).unwrap();
+        path
+    }
+}
+
+fn wait_for_file(path: &Path) -> String {
+    for _ in 0..100 {
+        if let Ok(content) = std::fs::read_to_string(path) {
+            if !content.is_empty() {
+                return content;
+            }
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    String::new()
+}
+
+#[test]
+fn working_dir_discovery() {
+    let root = create_test_folder("working_dir_discovery");
+    create_dir_all(root.join(DATA_RAW_FOLDER)).unwrap();
+
+    let profile_dir = root.join("crates").join("target").join("debug");
+    let triple_dir = root
+        .join("crates")
+        .join("target")
+        .join("x86_64-unknown-linux-gnu")
+        .join("release");
+    let bin_dir = root.join("bin");
+    create_dir_all(profile_dir.as_path()).unwrap();
+    create_dir_all(triple_dir.as_path()).unwrap();
+    create_dir_all(bin_dir.as_path()).unwrap();
+
+    assert_eq!(find_working_dir(profile_dir.as_path()), root);
+    assert_eq!(find_working_dir(triple_dir.as_path()), root);
+    assert_eq!(find_working_dir(bin_dir.as_path()), root);
+
+    create_dir_all(bin_dir.join(DATA_RAW_FOLDER)).unwrap();
+    assert_eq!(find_working_dir(bin_dir.as_path()), bin_dir);
+
+    remove_dir_all(root).ok();
+}
+
+#[test]
+fn chunk_filenames_are_unique() {
+    let cube = chunk_filename("Cube.001");
+    assert!(cube.starts_with("Cube_001_"), "{cube}");
+    assert_ne!(cube, chunk_filename("Cube_001"));
+    assert_ne!(chunk_filename("a/b"), chunk_filename("a:b"));
+    assert_eq!(cube, chunk_filename("Cube.001"));
+    assert_ne!(chunk_filename("scene"), "scene");
+}
+
+#[test]
+fn engine_starts_stand_in_launcher() {
+    let root = create_test_folder("engine_starts_stand_in_launcher");
+    let bin_dir = root.join("bin");
+    create_dir_all(bin_dir.as_path()).unwrap();
+    create_dir_all(root.join(DATA_RAW_FOLDER)).unwrap();
+    let root = root.canonicalize().unwrap();
+
+    let mut engine = INOXEngine::new(bin_dir.to_str().unwrap(), Vec::new(), None);
+    assert_eq!(PathBuf::from(engine.working_dir()), root);
+    assert_eq!(
+        PathBuf::from(engine.launcher_path()),
+        bin_dir
+            .canonicalize()
+            .unwrap()
+            .join(executable_filename("inox_launcher"))
+    );
+    assert!(!engine.start().unwrap());
+    assert!(!engine.is_running());
+
+    let tools_dir = root.join("tools");
+    create_dir_all(tools_dir.as_path()).unwrap();
+    let launcher = create_stand_in_launcher(tools_dir.as_path(), "stand_in_launcher");
+    engine.set_launcher_path(launcher.to_str().unwrap());
+    assert_eq!(PathBuf::from(engine.launcher_path()), launcher);
+    drop(engine);
+
+    let mut engine = INOXEngine::new(
+        bin_dir.to_str().unwrap(),
+        Vec::new(),
+        Some(launcher.to_str().unwrap()),
+    );
+    assert!(engine.start().unwrap());
+    assert!(engine.is_running());
+
+    let args = wait_for_file(root.join(ARGS_FILE).as_path());
+    assert!(args.contains("-plugin inox_connector"), "{args}");
+    assert!(args.contains("-plugin inox_viewer"), "{args}");
+
+    engine.stop();
+    assert!(!engine.is_running());
+    drop(engine);
+
+    remove_dir_all(root).ok();
+}
@@ -86,11 +86,12 @@ impl BVHTree {
             return start as _;
         }
 
-        let mut indices: Vec<usize> = (start..end).collect();
+        let indices: Vec<usize> = (start..end).collect();
         let axis = Self::choose_split_axis_sah(nodes, &indices);
-        indices.sort_by(|&a, &b| {
-            nodes[a].aabb.center()[axis]
-                .partial_cmp(&nodes[b].aabb.center()[axis])
+        // Leaves are still the only nodes in the range: they're sorted to split them in space
+        nodes[start..end].sort_by(|a, b| {
+            a.aabb.center()[axis]
+                .partial_cmp(&b.aabb.center()[axis])
                 .unwrap_or(Ordering::Equal)
         });
 
@@ -99,8 +100,12 @@ impl BVHTree {
         let right_child = Self::build_tree(nodes, mid, end);
 
         let parent = nodes.len() as u32;
+        let aabb = AABB::compute_aabb(&[
+            nodes[left_child as usize].aabb,
+            nodes[right_child as usize].aabb,
+        ]);
         nodes.push(BVHNode {
-            aabb: AABB::compute_aabb(&nodes.iter().map(|&n| n.aabb).collect::<Vec<_>>()),
+            aabb,
             left: left_child as _,
             right: right_child as _,
             parent: INVALID_NODE,
@@ -138,58 +143,29 @@ impl BVHTree {
 
         split_axis
     }
+    // Nodes are split in two halves by their center: the best axis is the one
+    // where the halves have the lowest surface area heuristic cost
     fn choose_split_axis_sah(nodes: &[BVHNode], indices: &[usize]) -> usize {
+        let mid = indices.len() / 2;
         let mut best_axis = 0;
         let mut best_cost = f32::INFINITY;
-
+        let mut sorted = indices.to_vec();
         for axis in 0..AXIS_COUNT {
-            let min_value = indices
-                .iter()
-                .map(|&i| nodes[i].aabb.min()[axis])
-                .fold(f32::INFINITY, f32::min);
-            let max_value = indices
-                .iter()
-                .map(|&i| nodes[i].aabb.max()[axis])
-                .fold(f32::NEG_INFINITY, f32::max);
-
-            let bin_width = (max_value - min_value) / (indices.len() as f32);
-
-            let mut bins_right = vec![0; indices.len()];
-
-            let mut surface_area_left = 0.0;
-            let mut surface_area_right = 0.0;
-
-            for &i in indices {
-                surface_area_right += nodes[i].aabb.surface_area();
-            }
-
-            let mut right_count = 0;
-
-            for &i in indices {
-                // Calculate bin index ensuring it's within bounds
-                let bin = ((nodes[i].aabb.min()[axis] - min_value) / bin_width)
-                    .clamp(0.0, bins_right.len() as f32 - 1.0) as usize;
-                bins_right[bin] += 1;
-
-                // Rest of the code remains the same
-                surface_area_right -= nodes[i].aabb.surface_area();
-                surface_area_left += nodes[i].aabb.surface_area();
-
-                if right_count < indices.len() - 1 {
-                    right_count += 1;
-                }
-
-                if right_count > 0 {
-                    let cost = (surface_area_left + surface_area_right) * bin_width;
-
-                    if cost < best_cost {
-                        best_cost = cost;
-                        best_axis = axis;
-                    }
-                }
+            sorted.sort_by(|&a, &b| {
+                nodes[a].aabb.center()[axis]
+                    .partial_cmp(&nodes[b].aabb.center()[axis])
+                    .unwrap_or(Ordering::Equal)
+            });
+            let aabbs: Vec<AABB> = sorted.iter().map(|&i| nodes[i].aabb).collect();
+            let left = AABB::compute_aabb(&aabbs[..mid]);
+            let right = AABB::compute_aabb(&aabbs[mid..]);
+            let cost = left.surface_area() * mid as f32
+                + right.surface_area() * (indices.len() - mid) as f32;
+            if cost < best_cost {
+                best_cost = cost;
+                best_axis = axis;
             }
         }
-
         best_axis
     }
 
@@ -206,35 +182,27 @@ impl BVHTree {
         }
         let mut index_map = vec![0; tree.nodes.len()];
         let mut new_bvh = BVHTree::default();
-        let mut nodes_to_visit = [tree.nodes.len() - 1].to_vec();
-        while !nodes_to_visit.is_empty() {
-            let old_index = nodes_to_visit.remove(0);
+        // Depth first, left child right after its parent
+        let mut nodes_to_visit = vec![tree.nodes.len() - 1];
+        while let Some(old_index) = nodes_to_visit.pop() {
             index_map[old_index] = new_bvh.nodes.len();
             new_bvh.nodes.push(tree.nodes[old_index]);
             if !tree.nodes[old_index].is_leaf() {
-                nodes_to_visit.insert(0, tree.nodes[old_index].right() as _);
-                nodes_to_visit.insert(0, tree.nodes[old_index].left() as _);
+                nodes_to_visit.push(tree.nodes[old_index].right() as _);
+                nodes_to_visit.push(tree.nodes[old_index].left() as _);
             }
         }
-        let entries_count = tree.nodes.len();
-        let mut left_modified = vec![false; entries_count];
-        let mut right_modified = vec![false; entries_count];
-        let mut parent_modified = vec![false; entries_count];
-        new_bvh.nodes.iter_mut().enumerate().for_each(|(i, node)| {
-            (0..index_map.len()).for_each(|old_index| {
-                if !left_modified[i] && node.left == old_index as i32 {
-                    node.left = index_map[old_index] as i32;
-                    left_modified[i] = true;
-                }
-                if !right_modified[i] && node.right == old_index as i32 {
-                    node.right = index_map[old_index] as i32;
-                    right_modified[i] = true;
-                }
-                if !parent_modified[i] && node.parent == old_index as i32 {
-                    node.parent = index_map[old_index] as i32;
-                    parent_modified[i] = true;
-                }
-            });
+        let remap = |index: i32| {
+            if index >= 0 {
+                index_map[index as usize] as i32
+            } else {
+                index
+            }
+        };
+        new_bvh.nodes.iter_mut().for_each(|node| {
+            node.left = remap(node.left);
+            node.right = remap(node.right);
+            node.parent = remap(node.parent);
         });
         Self {
             nodes: new_bvh.nodes,
@@ -368,8 +336,90 @@ fn test_bvh_cube() {
     println!("{bvh:?}");
 }
 
+// Unit boxes along x, shuffled so that the build has to sort them
+fn create_row_of_boxes(count: usize) -> Vec<AABB> {
+    (0..count)
+        .map(|i| {
+            let x = ((i * 7) % count) as f32 * 2.;
+            AABB::create(
+                Vector3::new(x, 0., 0.),
+                Vector3::new(x + 1., 1., 1.),
+                i as _,
+            )
+        })
+        .collect()
+}
+
+fn test_bvh_bounds() {
+    let aabbs = create_row_of_boxes(16);
+    let bvh = BVHTree::new(&aabbs);
+    let nodes = bvh.nodes();
+    debug_assert!(nodes.len() == aabbs.len() * 2 - 1);
+
+    let mut references: Vec<i32> = nodes
+        .iter()
+        .filter(|n| n.is_leaf())
+        .map(|n| n.aabb_index())
+        .collect();
+    references.sort();
+    debug_assert!(references == (0..aabbs.len() as i32).collect::<Vec<_>>());
+
+    nodes.iter().enumerate().for_each(|(i, n)| {
+        if n.is_leaf() {
+            let aabb = &aabbs[n.aabb_index() as usize];
+            debug_assert!(n.min() == aabb.min() && n.max() == aabb.max());
+            return;
+        }
+        // Depth first: left child right after its parent
+        debug_assert!(n.left() == i as i32 + 1);
+        let (left, right) = (&nodes[n.left() as usize], &nodes[n.right() as usize]);
+        debug_assert!(left.parent() == i as i32 && right.parent() == i as i32);
+        // Parents are bound by their own children only
+        let aabb = AABB::compute_aabb(&[left.aabb, right.aabb]);
+        debug_assert!(n.min() == aabb.min() && n.max() == aabb.max());
+        // Boxes are split in space, the two halves don't overlap
+        debug_assert!(left.max().x < right.min().x || right.max().x < left.min().x);
+    });
+    debug_assert!(nodes[0].min() == Vector3::new(0., 0., 0.));
+    debug_assert!(nodes[0].max() == Vector3::new(31., 1., 1.));
+}
+
+fn test_bvh_linearized() {
+    use crate::create_linearized_bvh;
+
+    let aabbs = create_row_of_boxes(11);
+    let bvh = BVHTree::new(&aabbs);
+    let linearized = create_linearized_bvh(&bvh);
+    debug_assert!(linearized.len() == bvh.nodes().len());
+
+    // Stackless traversal with miss links finds the boxes containing a point
+    let find = |x: f32| {
+        let mut found = Vec::new();
+        let mut index = 0;
+        while index >= 0 && (index as usize) < linearized.len() {
+            let node = &linearized[index as usize];
+            if x >= node.min[0] && x <= node.max[0] {
+                if node.reference >= 0 {
+                    found.push(node.reference);
+                }
+                index += 1;
+            } else {
+                index = node.miss;
+            }
+        }
+        found
+    };
+    aabbs.iter().for_each(|aabb| {
+        debug_assert!(find(aabb.center().x) == vec![aabb.index()]);
+    });
+    debug_assert!(find(1.5).is_empty());
+    debug_assert!(find(-1.).is_empty());
+}
+
 #[test]
 fn test_bvh() {
     test_bvh_quad();
     test_bvh_cube();
+    test_bvh_bounds();
+    test_bvh_linearized();
 }
@@ -30,6 +30,10 @@ impl CommandParser {
             .any(|c| c.name.as_str() == command_name)
     }
 
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.commands.iter().map(|c| c.name.as_str())
+    }
+
     pub fn get_values_of<T>(&self, command_name: &str) -> Vec<T>
     where
         T: FromStr + Default,
@@ -50,10 +54,14 @@ impl CommandParser {
         let mut commands = Vec::new();
         for a in args {
             if a.starts_with('-') {
-                let mut name = a.clone();
-                name.remove(0);
+                // Both '-name' and '--name' are the command name
+                let name = a.trim_start_matches('-');
                 let mut values: Vec<String> =
                     name.split_whitespace().map(|s| s.to_string()).collect();
+                if values.is_empty() {
+                    eprintln!("Waiting for a command name after {a}");
+                    continue;
+                }
                 let name = values.remove(0);
                 values.retain(|s| !s.is_empty());
                 commands.push(Command { name, values });
@@ -66,3 +74,18 @@ impl CommandParser {
         Self { commands }
     }
 }
+
+#[test]
+fn test_command_names() {
+    let command_parser = CommandParser::from_string("--watch --only *.gltf *.png -jobs 4 --");
+    assert_eq!(
+        command_parser.names().collect::<Vec<_>>(),
+        vec!["watch", "only", "jobs"]
+    );
+    assert!(command_parser.has("watch"));
+    assert_eq!(
+        command_parser.get_values_of::<String>("only"),
+        vec!["*.gltf".to_string(), "*.png".to_string()]
+    );
+    assert_eq!(command_parser.get_values_of::<u32>("jobs"), vec![4]);
+}
@@ -18,6 +18,28 @@ pub fn library_filename<S: AsRef<OsStr>>(name: S) -> OsString {
     string
 }
 
+#[inline]
+pub fn executable_filename<S: AsRef<OsStr>>(name: S) -> OsString {
+    let name = name.as_ref();
+    let mut string = OsString::with_capacity(name.len() + EXE_SUFFIX.len());
+    string.push(name);
+    string.push(EXE_SUFFIX);
+    string
+}
+
+#[inline]
+pub fn library_name<S: AsRef<OsStr>>(filename: S) -> String {
+    let filename = filename.as_ref().to_string_lossy();
+    let mut name = filename.as_ref();
+    if let Some(stripped) = name.strip_suffix(DLL_SUFFIX) {
+        name = stripped;
+    }
+    if let Some(stripped) = name.strip_prefix(DLL_PREFIX) {
+        name = stripped;
+    }
+    name.to_string()
+}
+
 #[inline]
 pub fn compute_folder_and_filename(lib_path: &Path) -> (PathBuf, PathBuf) {
     let mut path = lib_path.to_path_buf();
@@ -56,3 +78,24 @@ impl Library {
         self.0.close();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library_name_roundtrip() {
+        let filename = library_filename("inox_viewer");
+        assert_eq!(library_name(&filename), "inox_viewer");
+        assert_eq!(library_name("inox_viewer"), "inox_viewer");
+    }
+
+    #[test]
+    fn test_executable_filename() {
+        let filename = executable_filename("inox_launcher");
+        assert_eq!(
+            filename,
+            OsString::from(format!("inox_launcher{EXE_SUFFIX}"))
+        );
+    }
+}
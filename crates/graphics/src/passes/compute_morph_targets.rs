@@ -0,0 +1,216 @@
+use std::path::PathBuf;
+
+use inox_render::{
+    BindingData, BindingFlags, BindingInfo, CommandBuffer, ComputePass, ComputePassData, GPUBuffer,
+    GPUMesh, GPUMorph, GPUMorphDelta, GPUMorphVertex, GPUMorphWeight, GPUSkin, GPUSkinVertex,
+    GPUVertexAttributes, GPUVertexPosition, Pass, RenderContext, RenderContextRc, ShaderStage,
+    TextureView,
+};
+
+use inox_core::ContextRc;
+use inox_resources::{DataTypeResource, Resource};
+use inox_uid::generate_random_uid;
+
+pub const MORPH_TARGETS_PIPELINE: &str = "pipelines/ComputeMorphTargets.compute_pipeline";
+pub const MORPH_TARGETS_PASS_NAME: &str = "MorphTargetsPass";
+
+// Applies morph target weights to the vertices of meshes with morph targets.
+// It must run before the skinning pass, that deforms the morphed skin vertices
+pub struct ComputeMorphTargetsPass {
+    compute_pass: Resource<ComputePass>,
+    binding_data: BindingData,
+    morphs: GPUBuffer<GPUMorph>,
+    morph_vertices: GPUBuffer<GPUMorphVertex>,
+    morph_deltas: GPUBuffer<GPUMorphDelta>,
+    morph_weights: GPUBuffer<GPUMorphWeight>,
+    skins: GPUBuffer<GPUSkin>,
+    skin_vertices: GPUBuffer<GPUSkinVertex>,
+    meshes: GPUBuffer<GPUMesh>,
+    vertices_positions: GPUBuffer<GPUVertexPosition>,
+    vertices_attributes: GPUBuffer<GPUVertexAttributes>,
+}
+unsafe impl Send for ComputeMorphTargetsPass {}
+unsafe impl Sync for ComputeMorphTargetsPass {}
+
+impl Pass for ComputeMorphTargetsPass {
+    fn name(&self) -> &str {
+        MORPH_TARGETS_PASS_NAME
+    }
+    fn static_name() -> &'static str {
+        MORPH_TARGETS_PASS_NAME
+    }
+    fn is_active(&self, _render_context: &RenderContext) -> bool {
+        !self.morph_vertices.read().unwrap().is_empty()
+    }
+    fn create(context: &ContextRc, render_context: &RenderContextRc) -> Self
+    where
+        Self: Sized,
+    {
+        let compute_data = ComputePassData {
+            name: MORPH_TARGETS_PASS_NAME.to_string(),
+            pipelines: vec![PathBuf::from(MORPH_TARGETS_PIPELINE)],
+        };
+
+        Self {
+            compute_pass: ComputePass::new_resource(
+                context.shared_data(),
+                context.message_hub(),
+                generate_random_uid(),
+                &compute_data,
+                None,
+            ),
+            morphs: render_context.global_buffers().buffer::<GPUMorph>(),
+            morph_vertices: render_context.global_buffers().buffer::<GPUMorphVertex>(),
+            morph_deltas: render_context.global_buffers().buffer::<GPUMorphDelta>(),
+            morph_weights: render_context.global_buffers().buffer::<GPUMorphWeight>(),
+            skins: render_context.global_buffers().buffer::<GPUSkin>(),
+            skin_vertices: render_context.global_buffers().buffer::<GPUSkinVertex>(),
+            meshes: render_context.global_buffers().buffer::<GPUMesh>(),
+            vertices_positions: render_context
+                .global_buffers()
+                .buffer::<GPUVertexPosition>(),
+            vertices_attributes: render_context
+                .global_buffers()
+                .buffer::<GPUVertexAttributes>(),
+            binding_data: BindingData::new(render_context, MORPH_TARGETS_PASS_NAME),
+        }
+    }
+    fn init(&mut self, render_context: &RenderContext) {
+        inox_profiler::scoped_profile!("morph_targets_pass::init");
+
+        if self.morph_vertices.read().unwrap().is_empty() {
+            return;
+        }
+
+        self.binding_data
+            .add_buffer(
+                &mut *self.morphs.write().unwrap(),
+                Some("Morphs"),
+                BindingInfo {
+                    group_index: 0,
+                    binding_index: 0,
+                    stage: ShaderStage::Compute,
+                    flags: BindingFlags::Storage | BindingFlags::Read,
+                    ..Default::default()
+                },
+            )
+            .add_buffer(
+                &mut *self.morph_vertices.write().unwrap(),
+                Some("MorphVertices"),
+                BindingInfo {
+                    group_index: 0,
+                    binding_index: 1,
+                    stage: ShaderStage::Compute,
+                    flags: BindingFlags::Storage | BindingFlags::Read,
+                    ..Default::default()
+                },
+            )
+            .add_buffer(
+                &mut *self.morph_deltas.write().unwrap(),
+                Some("MorphDeltas"),
+                BindingInfo {
+                    group_index: 0,
+                    binding_index: 2,
+                    stage: ShaderStage::Compute,
+                    flags: BindingFlags::Storage | BindingFlags::Read,
+                    ..Default::default()
+                },
+            )
+            .add_buffer(
+                &mut *self.morph_weights.write().unwrap(),
+                Some("MorphWeights"),
+                BindingInfo {
+                    group_index: 0,
+                    binding_index: 3,
+                    stage: ShaderStage::Compute,
+                    flags: BindingFlags::Storage | BindingFlags::Read,
+                    ..Default::default()
+                },
+            )
+            .add_buffer(
+                &mut *self.skins.write().unwrap(),
+                Some("Skins"),
+                BindingInfo {
+                    group_index: 0,
+                    binding_index: 4,
+                    stage: ShaderStage::Compute,
+                    flags: BindingFlags::Storage | BindingFlags::Read,
+                    ..Default::default()
+                },
+            )
+            .add_buffer(
+                &mut *self.skin_vertices.write().unwrap(),
+                Some("SkinVertices"),
+                BindingInfo {
+                    group_index: 0,
+                    binding_index: 5,
+                    stage: ShaderStage::Compute,
+                    flags: BindingFlags::Storage | BindingFlags::ReadWrite,
+                    ..Default::default()
+                },
+            )
+            .add_buffer(
+                &mut *self.meshes.write().unwrap(),
+                Some("Meshes"),
+                BindingInfo {
+                    group_index: 0,
+                    binding_index: 6,
+                    stage: ShaderStage::Compute,
+                    flags: BindingFlags::Storage | BindingFlags::Read,
+                    ..Default::default()
+                },
+            )
+            .add_buffer(
+                &mut *self.vertices_positions.write().unwrap(),
+                Some("VerticesPositions"),
+                BindingInfo {
+                    group_index: 0,
+                    binding_index: 7,
+                    stage: ShaderStage::Compute,
+                    flags: BindingFlags::Storage | BindingFlags::ReadWrite | BindingFlags::Vertex,
+                    ..Default::default()
+                },
+            )
+            .add_buffer(
+                &mut *self.vertices_attributes.write().unwrap(),
+                Some("VerticesAttributes"),
+                BindingInfo {
+                    group_index: 0,
+                    binding_index: 8,
+                    stage: ShaderStage::Compute,
+                    flags: BindingFlags::Storage | BindingFlags::ReadWrite,
+                    ..Default::default()
+                },
+            );
+
+        let mut pass = self.compute_pass.get_mut();
+        pass.init(render_context, &mut self.binding_data, None);
+    }
+
+    fn update(
+        &mut self,
+        render_context: &RenderContext,
+        _surface_view: &TextureView,
+        command_buffer: &mut CommandBuffer,
+    ) {
+        inox_profiler::scoped_profile!("morph_targets_pass::update");
+
+        let num = self.morph_vertices.read().unwrap().item_count();
+        if num == 0 {
+            return;
+        }
+
+        let workgroup_max_size = 256;
+        let workgroup_size = num.div_ceil(workgroup_max_size);
+
+        let pass = self.compute_pass.get();
+        pass.dispatch(
+            render_context,
+            &mut self.binding_data,
+            command_buffer,
+            workgroup_size as u32,
+            1,
+            1,
+        );
+    }
+}
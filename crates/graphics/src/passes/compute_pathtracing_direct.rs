@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use inox_render::{
-    BindingData, BindingFlags, BindingInfo, CommandBuffer, ComputePass, ComputePassData, ConstantDataRw, DEFAULT_HEIGHT, DEFAULT_WIDTH, GPUBuffer, GPUInstance, GPULight, GPUMaterial, GPUMesh, GPUMeshlet, GPUTexture, GPUTransform, GPUVector, GPUVertexAttributes, GPUVertexIndices, GPUVertexPosition, INSTANCE_DATA_ID, Pass, RenderContext, RenderContextRc, SamplerType, ShaderStage, TextureId, TextureView
+    BindingData, BindingFlags, BindingInfo, CommandBuffer, ComputePass, ComputePassData, ConstantDataRw, CONSTANT_DATA_LIGHTING_KEYWORDS, DEFAULT_HEIGHT, DEFAULT_WIDTH, GPUBuffer, GPUInstance, GPULight, GPUMaterial, GPUMesh, GPUMeshlet, GPUTexture, GPUTransform, GPUVector, GPUVertexAttributes, GPUVertexIndices, GPUVertexPosition, INSTANCE_DATA_ID, Pass, RenderContext, RenderContextRc, SamplerType, ShaderStage, ShaderVariantKey, TextureId, TextureView
 };
 
 use inox_core::ContextRc;
@@ -324,7 +324,17 @@ impl Pass for ComputePathTracingDirectPass {
                 ..Default::default()
             });
 
+        let keywords = self
+            .constant_data
+            .read()
+            .unwrap()
+            .keywords(&CONSTANT_DATA_LIGHTING_KEYWORDS);
         let mut pass = self.compute_pass.get_mut();
+        pass.pipelines().iter().for_each(|pipeline| {
+            pipeline
+                .get_mut()
+                .set_variant(ShaderVariantKey::new(&keywords));
+        });
         pass.init(render_context, &mut self.binding_data, None);
     }
 
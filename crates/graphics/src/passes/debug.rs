@@ -1,11 +1,19 @@
 use std::path::PathBuf;
 
 use inox_render::{
-    BindingData, BindingFlags, BindingInfo, CommandBuffer, ConstantDataRw, GPUBuffer, GPUInstance,
-    GPUMaterial, GPUMesh, GPUMeshlet, GPUTexture, GPUTransform, GPUVector, GPUVertexAttributes,
+    BindingData, BindingFlags, CommandBuffer, ConstantDataRw, GPUBuffer, GPUInstance, GPUMaterial,
+    GPUMesh, GPUMeshlet, GPUTexture, GPUTransform, GPUVector, GPUVertexAttributes,
     GPUVertexIndices, GPUVertexPosition, LoadOperation, Pass, RenderContext, RenderContextRc,
-    RenderPass, RenderPassBeginData, RenderPassData, RenderTarget, SamplerType, ShaderStage,
-    StoreOperation, TextureId, TextureView, DEFAULT_HEIGHT, DEFAULT_WIDTH, INSTANCE_DATA_ID,
+    RenderPass, RenderPassBeginData, RenderPassData, RenderTarget, SamplerType, ShaderVariantKey,
+    StoreOperation, TextureId, TextureView, CONSTANT_DATA_FLAGS_DISPLAY_BASE_COLOR,
+    CONSTANT_DATA_FLAGS_DISPLAY_BITANGENT, CONSTANT_DATA_FLAGS_DISPLAY_DEPTH_BUFFER,
+    CONSTANT_DATA_FLAGS_DISPLAY_MESHLETS, CONSTANT_DATA_FLAGS_DISPLAY_MESHLETS_LOD_LEVEL,
+    CONSTANT_DATA_FLAGS_DISPLAY_METALLIC, CONSTANT_DATA_FLAGS_DISPLAY_NORMALS,
+    CONSTANT_DATA_FLAGS_DISPLAY_PATHTRACE, CONSTANT_DATA_FLAGS_DISPLAY_RADIANCE_BUFFER,
+    CONSTANT_DATA_FLAGS_DISPLAY_ROUGHNESS, CONSTANT_DATA_FLAGS_DISPLAY_TANGENT,
+    CONSTANT_DATA_FLAGS_DISPLAY_UV_0, CONSTANT_DATA_FLAGS_DISPLAY_UV_1,
+    CONSTANT_DATA_FLAGS_DISPLAY_UV_2, CONSTANT_DATA_FLAGS_DISPLAY_UV_3,
+    CONSTANT_DATA_LIGHTING_KEYWORDS, DEFAULT_HEIGHT, DEFAULT_WIDTH, INSTANCE_DATA_ID,
 };
 
 use inox_core::ContextRc;
@@ -18,6 +26,34 @@ use crate::{RadiancePackedData, SIZE_OF_DATA_BUFFER_ELEMENT};
 pub const DEBUG_PIPELINE: &str = "pipelines/Debug.render_pipeline";
 pub const DEBUG_PASS_NAME: &str = "DebugPass";
 
+// Keywords of the debug shader variants, in the same order of its keyword set
+const DEBUG_DISPLAY_KEYWORDS: [(u32, &str); 15] = [
+    (CONSTANT_DATA_FLAGS_DISPLAY_MESHLETS, "DISPLAY_MESHLETS"),
+    (
+        CONSTANT_DATA_FLAGS_DISPLAY_MESHLETS_LOD_LEVEL,
+        "DISPLAY_MESHLETS_LOD_LEVEL",
+    ),
+    (CONSTANT_DATA_FLAGS_DISPLAY_UV_0, "DISPLAY_UV_0"),
+    (CONSTANT_DATA_FLAGS_DISPLAY_UV_1, "DISPLAY_UV_1"),
+    (CONSTANT_DATA_FLAGS_DISPLAY_UV_2, "DISPLAY_UV_2"),
+    (CONSTANT_DATA_FLAGS_DISPLAY_UV_3, "DISPLAY_UV_3"),
+    (CONSTANT_DATA_FLAGS_DISPLAY_NORMALS, "DISPLAY_NORMALS"),
+    (CONSTANT_DATA_FLAGS_DISPLAY_TANGENT, "DISPLAY_TANGENT"),
+    (CONSTANT_DATA_FLAGS_DISPLAY_BITANGENT, "DISPLAY_BITANGENT"),
+    (CONSTANT_DATA_FLAGS_DISPLAY_BASE_COLOR, "DISPLAY_BASE_COLOR"),
+    (CONSTANT_DATA_FLAGS_DISPLAY_METALLIC, "DISPLAY_METALLIC"),
+    (CONSTANT_DATA_FLAGS_DISPLAY_ROUGHNESS, "DISPLAY_ROUGHNESS"),
+    (
+        CONSTANT_DATA_FLAGS_DISPLAY_RADIANCE_BUFFER,
+        "DISPLAY_RADIANCE_BUFFER",
+    ),
+    (
+        CONSTANT_DATA_FLAGS_DISPLAY_DEPTH_BUFFER,
+        "DISPLAY_DEPTH_BUFFER",
+    ),
+    (CONSTANT_DATA_FLAGS_DISPLAY_PATHTRACE, "DISPLAY_PATHTRACE"),
+];
+
 pub struct DebugPass {
     render_pass: Resource<RenderPass>,
     binding_data: BindingData,
@@ -127,177 +163,99 @@ impl Pass for DebugPass {
         }
 
         let mut pass = self.render_pass.get_mut();
+        // Resources are bound to the bindings reflected from the debug shader
+        let Some(bindings) = pass.pipeline().get().bindings() else {
+            return;
+        };
+
+        let keywords = {
+            let constant_data = self.constant_data.read().unwrap();
+            let mut keywords = constant_data.keywords(&DEBUG_DISPLAY_KEYWORDS);
+            keywords.extend(constant_data.keywords(&CONSTANT_DATA_LIGHTING_KEYWORDS));
+            keywords
+        };
+        pass.pipeline()
+            .get_mut()
+            .set_variant(ShaderVariantKey::new(&keywords));
 
         self.binding_data
-            .add_buffer(
+            .set_shader_bindings(bindings)
+            .add_buffer_by_name(
+                "constant_data",
                 &mut *self.constant_data.write().unwrap(),
                 Some("ConstantData"),
-                BindingInfo {
-                    group_index: 0,
-                    binding_index: 0,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::Uniform | BindingFlags::Read,
-                    ..Default::default()
-                },
+                BindingFlags::none(),
             )
-            .add_buffer(
+            .add_buffer_by_name(
+                "indices",
                 &mut *self.indices.write().unwrap(),
                 Some("Indices"),
-                BindingInfo {
-                    group_index: 0,
-                    binding_index: 1,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::Storage | BindingFlags::Read | BindingFlags::Index,
-                    ..Default::default()
-                },
+                BindingFlags::Index,
             )
-            .add_buffer(
+            .add_buffer_by_name(
+                "vertices_positions",
                 &mut *self.vertices_position.write().unwrap(),
                 Some("Vertices Position"),
-                BindingInfo {
-                    group_index: 0,
-                    binding_index: 2,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::Storage | BindingFlags::Read | BindingFlags::Vertex,
-                    ..Default::default()
-                },
+                BindingFlags::Vertex,
             )
-            .add_buffer(
+            .add_buffer_by_name(
+                "vertices_attributes",
                 &mut *self.vertices_attributes.write().unwrap(),
                 Some("Vertices Attributes"),
-                BindingInfo {
-                    group_index: 0,
-                    binding_index: 3,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::Storage | BindingFlags::Read,
-                    ..Default::default()
-                },
+                BindingFlags::none(),
             )
-            .add_buffer(
+            .add_buffer_by_name(
+                "meshes",
                 &mut *self.meshes.write().unwrap(),
                 Some("Meshes"),
-                BindingInfo {
-                    group_index: 0,
-                    binding_index: 4,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::Storage | BindingFlags::Read,
-                    ..Default::default()
-                },
+                BindingFlags::none(),
             )
-            .add_buffer(
+            .add_buffer_by_name(
+                "meshlets",
                 &mut *self.meshlets.write().unwrap(),
                 Some("Meshlets"),
-                BindingInfo {
-                    group_index: 0,
-                    binding_index: 5,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::Storage | BindingFlags::Read,
-                    ..Default::default()
-                },
+                BindingFlags::none(),
             )
-            .add_buffer(
+            .add_buffer_by_name(
+                "instances",
                 &mut *self.instances.write().unwrap(),
                 Some("Instances"),
-                BindingInfo {
-                    group_index: 0,
-                    binding_index: 6,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::Storage | BindingFlags::Read,
-                    ..Default::default()
-                },
+                BindingFlags::none(),
             )
-            .add_buffer(
+            .add_buffer_by_name(
+                "transforms",
                 &mut *self.transforms.write().unwrap(),
                 Some("Transforms"),
-                BindingInfo {
-                    group_index: 0,
-                    binding_index: 7,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::Storage | BindingFlags::Read,
-                    ..Default::default()
-                },
+                BindingFlags::none(),
             )
-            .add_buffer(
+            .add_buffer_by_name(
+                "materials",
                 &mut *self.materials.write().unwrap(),
                 Some("Materials"),
-                BindingInfo {
-                    group_index: 1,
-                    binding_index: 0,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::Uniform | BindingFlags::Read,
-                    ..Default::default()
-                },
+                BindingFlags::none(),
             )
-            .add_buffer(
+            .add_buffer_by_name(
+                "textures",
                 &mut *self.textures.write().unwrap(),
                 Some("Textures"),
-                BindingInfo {
-                    group_index: 1,
-                    binding_index: 1,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::Uniform | BindingFlags::Read,
-                    ..Default::default()
-                },
+                BindingFlags::none(),
             )
-            .add_texture(
-                &self.visibility_texture,
-                0,
-                BindingInfo {
-                    group_index: 1,
-                    binding_index: 2,
-                    stage: ShaderStage::Fragment,
-                    ..Default::default()
-                },
-            )
-            .add_texture(
-                &self.depth_texture,
-                0,
-                BindingInfo {
-                    group_index: 1,
-                    binding_index: 3,
-                    stage: ShaderStage::Fragment,
-                    ..Default::default()
-                },
-            )
-            .add_buffer(
+            .add_texture_by_name("visibility_texture", &self.visibility_texture, 0)
+            .add_texture_by_name("depth_texture", &self.depth_texture, 0)
+            .add_buffer_by_name(
+                "data_buffer_1",
                 &mut *self.data_buffer_1.write().unwrap(),
                 Some("DataBuffer_1"),
-                BindingInfo {
-                    group_index: 1,
-                    binding_index: 4,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::ReadWrite | BindingFlags::Storage,
-                    ..Default::default()
-                },
+                BindingFlags::none(),
             )
-            .add_buffer(
+            .add_buffer_by_name(
+                "data_buffer_debug",
                 &mut *self.data_buffer_debug.write().unwrap(),
                 Some("DataBuffer_Debug"),
-                BindingInfo {
-                    group_index: 1,
-                    binding_index: 5,
-                    stage: ShaderStage::Fragment,
-                    flags: BindingFlags::ReadWrite | BindingFlags::Storage,
-                    ..Default::default()
-                },
-            );
-
-        self.binding_data
-            .add_default_sampler(
-                BindingInfo {
-                    group_index: 2,
-                    binding_index: 0,
-                    stage: ShaderStage::Fragment,
-                    ..Default::default()
-                },
-                SamplerType::Unfiltered,
+                BindingFlags::none(),
             )
-            .add_material_textures(BindingInfo {
-                group_index: 2,
-                binding_index: 1,
-                stage: ShaderStage::Fragment,
-                ..Default::default()
-            });
+            .add_default_sampler_by_name("default_sampler", SamplerType::Unfiltered)
+            .add_material_textures_by_name();
         pass.init(render_context, &mut self.binding_data, None, None);
     }
     fn update(
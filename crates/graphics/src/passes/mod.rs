@@ -4,8 +4,10 @@ pub use self::compute_culling::*;
 pub use self::compute_depth_first::*;
 pub use self::compute_depth_pyramid::*;
 pub use self::compute_instances::*;
+pub use self::compute_morph_targets::*;
 pub use self::compute_pathtracing_direct::*;
 pub use self::compute_pathtracing_indirect::*;
+pub use self::compute_skinning::*;
 pub use self::debug::*;
 pub use self::finalize::*;
 pub use self::visibility::*;
@@ -17,8 +19,10 @@ pub mod compute_culling;
 pub mod compute_depth_first;
 pub mod compute_depth_pyramid;
 pub mod compute_instances;
+pub mod compute_morph_targets;
 pub mod compute_pathtracing_direct;
 pub mod compute_pathtracing_indirect;
+pub mod compute_skinning;
 pub mod debug;
 pub mod finalize;
 pub mod visibility;
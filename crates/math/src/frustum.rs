@@ -129,16 +129,42 @@ pub fn compute_frustum(
     //  /                      \
     // fbl                     fbr
     ////////////////////////////////
+    let tang = (fov * 0.5).0.to_radians().tan();
+    let near_half_size = Vector2::new(near * tang * aspect_ratio, near * tang);
+    let far_half_size = Vector2::new(far * tang * aspect_ratio, far * tang);
+    frustum_from_half_sizes(&mut frustum, view, near, far, near_half_size, far_half_size);
+    frustum
+}
+
+// xmag and ymag are half width and half height of the orthographic view volume
+pub fn compute_orthographic_frustum(
+    view: &Matrix4,
+    near: f32,
+    far: f32,
+    xmag: f32,
+    ymag: f32,
+) -> Frustum {
+    let mut frustum = Frustum::default();
+    let half_size = Vector2::new(xmag, ymag);
+    frustum_from_half_sizes(&mut frustum, view, near, far, half_size, half_size);
+    frustum
+}
+
+fn frustum_from_half_sizes(
+    frustum: &mut Frustum,
+    view: &Matrix4,
+    near: f32,
+    far: f32,
+    near_half_size: Vector2,
+    far_half_size: Vector2,
+) {
     let inv_view = view.inverse();
     let position = inv_view.translation();
     let facing = -inv_view.forward().normalized();
     let up = inv_view.up().normalized();
     let right = inv_view.right().normalized();
-    let tang = (fov * 0.5).0.to_radians().tan();
-    let nh = near * tang;
-    let nw = nh * aspect_ratio;
-    let fh = far * tang;
-    let fw = fh * aspect_ratio;
+    let (nw, nh) = (near_half_size.x, near_half_size.y);
+    let (fw, fh) = (far_half_size.x, far_half_size.y);
     let nc = position + facing * near;
     let fc = position + facing * far;
 
@@ -162,30 +188,68 @@ pub fn compute_frustum(
     frustum.faces[Faces::Far as usize].distance =
         frustum.faces[Faces::Far as usize].normal.dot_product(fc);
 
+    // side planes pass through the near and far edges: they are parallel to facing when orthographic
     let aux = nc + up * nh;
-    frustum.faces[Faces::Top as usize].normal =
-        ((aux - position).normalized()).cross(right).normalized();
+    let edge = (fc + up * fh) - aux;
+    frustum.faces[Faces::Top as usize].normal = (edge.normalized()).cross(right).normalized();
     frustum.faces[Faces::Top as usize].distance =
         frustum.faces[Faces::Top as usize].normal.dot_product(aux);
 
     let aux = nc - up * nh;
-    frustum.faces[Faces::Bottom as usize].normal =
-        (right).cross((aux - position).normalized()).normalized();
+    let edge = (fc - up * fh) - aux;
+    frustum.faces[Faces::Bottom as usize].normal = (right).cross(edge.normalized()).normalized();
     frustum.faces[Faces::Bottom as usize].distance = frustum.faces[Faces::Bottom as usize]
         .normal
         .dot_product(aux);
 
     let aux = nc - right * nw;
-    frustum.faces[Faces::Left as usize].normal =
-        ((aux - position).normalized()).cross(up).normalized();
+    let edge = (fc - right * fw) - aux;
+    frustum.faces[Faces::Left as usize].normal = (edge.normalized()).cross(up).normalized();
     frustum.faces[Faces::Left as usize].distance =
         frustum.faces[Faces::Left as usize].normal.dot_product(aux);
 
     let aux = nc + right * nw;
-    frustum.faces[Faces::Right as usize].normal =
-        (up).cross((aux - position).normalized()).normalized();
+    let edge = (fc + right * fw) - aux;
+    frustum.faces[Faces::Right as usize].normal = (up).cross(edge.normalized()).normalized();
     frustum.faces[Faces::Right as usize].distance =
-        frustum.faces[Faces::Left as usize].normal.dot_product(aux);
+        frustum.faces[Faces::Right as usize].normal.dot_product(aux);
+}
 
-    frustum
+#[test]
+fn orthographic_frustum_test() {
+    let epsilon = 0.0001;
+    let is_near = |a: Vector3, b: Vector3| (a - b).length() < epsilon;
+    // Camera at (1, 0, 5) looking along -z
+    let view = Matrix4::from_translation(Vector3::new(-1., 0., -5.));
+    let frustum = compute_orthographic_frustum(&view, 1., 10., 2., 3.);
+
+    // Near and far planes have the same size
+    assert!(is_near(frustum.ntl, Vector3::new(-1., 3., 4.)));
+    assert!(is_near(frustum.nbr, Vector3::new(3., -3., 4.)));
+    assert!(is_near(frustum.ftl, Vector3::new(-1., 3., -5.)));
+    assert!(is_near(frustum.fbr, Vector3::new(3., -3., -5.)));
+
+    // Side planes are parallel to the view direction
+    let facing = Vector3::new(0., 0., -1.);
+    assert!(is_near(frustum.faces[Faces::Near as usize].normal, facing));
+    assert!(is_near(frustum.faces[Faces::Far as usize].normal, -facing));
+    for face in [Faces::Top, Faces::Bottom, Faces::Left, Faces::Right] {
+        let normal = frustum.faces[face as usize].normal;
+        assert!(normal.dot_product(facing).abs() < epsilon);
+    }
+
+    let is_inside = |p: Vector3| {
+        frustum
+            .faces
+            .iter()
+            .all(|f| f.normal.dot_product(p) >= f.distance)
+    };
+    assert!(is_inside(Vector3::new(2., 2., 0.)));
+    assert!(is_inside(Vector3::new(-0.9, -2.9, -4.9)));
+    assert!(!is_inside(Vector3::new(3.5, 0., 0.)));
+    assert!(!is_inside(Vector3::new(1., -3.5, 0.)));
+    assert!(!is_inside(Vector3::new(1., 0., 4.5)));
+    assert!(!is_inside(Vector3::new(1., 0., -5.5)));
+    // Unlike a perspective frustum it doesn't get larger with distance
+    assert!(!is_inside(Vector3::new(3.5, 3.5, -4.9)));
 }
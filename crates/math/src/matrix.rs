@@ -239,3 +239,8 @@ pub fn unproject(position: Vector3, view: Matrix4, projection: Matrix4) -> Vecto
 pub fn perspective(fovy: Deg<f32>, aspect: f32, near: f32, far: f32) -> Matrix4 {
     cgmath::perspective(fovy, aspect, near, far)
 }
+
+// xmag and ymag are half width and half height of the view volume
+pub fn orthographic(xmag: f32, ymag: f32, near: f32, far: f32) -> Matrix4 {
+    cgmath::ortho(-xmag, xmag, -ymag, ymag, near, far)
+}
@@ -1,21 +1,22 @@
 use crate::{
-    VecBase, Vector2, Vector2h, Vector2u, Vector3, Vector3h, Vector3u, Vector4, Vector4h, Vector4u,
+    Matrix4, VecBase, Vector2, Vector2h, Vector2u, Vector3, Vector3h, Vector3u, Vector4, Vector4b,
+    Vector4h, Vector4u,
 };
-use std::{fs::File, io::Read, mem::size_of};
+use std::{io::Read, mem::size_of};
 
 pub trait Parser {
     fn size() -> usize;
-    fn parse(file: &mut File) -> Self;
+    fn parse<R: Read>(reader: &mut R) -> Self;
 }
 
 impl Parser for f32 {
     fn size() -> usize {
         size_of::<f32>()
     }
-    fn parse(file: &mut File) -> f32 {
+    fn parse<R: Read>(reader: &mut R) -> f32 {
         debug_assert!(Self::size() == 4);
         let mut bytes = [0u8; 4];
-        if file.read_exact(&mut bytes).is_ok() {
+        if reader.read_exact(&mut bytes).is_ok() {
             return f32::from_le_bytes(bytes);
         }
         0.
@@ -26,10 +27,10 @@ impl Parser for u8 {
     fn size() -> usize {
         size_of::<u8>()
     }
-    fn parse(file: &mut File) -> u8 {
+    fn parse<R: Read>(reader: &mut R) -> u8 {
         debug_assert!(Self::size() == 1);
         let mut bytes = [0u8; 1];
-        if file.read_exact(&mut bytes).is_ok() {
+        if reader.read_exact(&mut bytes).is_ok() {
             return u8::from_le_bytes(bytes);
         }
         0
@@ -40,10 +41,10 @@ impl Parser for u16 {
     fn size() -> usize {
         size_of::<u16>()
     }
-    fn parse(file: &mut File) -> u16 {
+    fn parse<R: Read>(reader: &mut R) -> u16 {
         debug_assert!(Self::size() == 2);
         let mut bytes = [0u8; 2];
-        if file.read_exact(&mut bytes).is_ok() {
+        if reader.read_exact(&mut bytes).is_ok() {
             return u16::from_le_bytes(bytes);
         }
         0
@@ -54,10 +55,10 @@ impl Parser for u32 {
     fn size() -> usize {
         size_of::<u32>()
     }
-    fn parse(file: &mut File) -> u32 {
+    fn parse<R: Read>(reader: &mut R) -> u32 {
         debug_assert!(Self::size() == 4);
         let mut bytes = [0u8; 4];
-        if file.read_exact(&mut bytes).is_ok() {
+        if reader.read_exact(&mut bytes).is_ok() {
             return u32::from_le_bytes(bytes);
         }
         0
@@ -68,10 +69,10 @@ impl Parser for Vector2 {
     fn size() -> usize {
         2 * size_of::<f32>()
     }
-    fn parse(file: &mut File) -> Vector2 {
+    fn parse<R: Read>(reader: &mut R) -> Vector2 {
         let mut v = Vector2::default_zero();
-        v.x = f32::parse(file);
-        v.y = f32::parse(file);
+        v.x = f32::parse(reader);
+        v.y = f32::parse(reader);
         v
     }
 }
@@ -80,11 +81,11 @@ impl Parser for Vector3 {
     fn size() -> usize {
         3 * size_of::<f32>()
     }
-    fn parse(file: &mut File) -> Vector3 {
+    fn parse<R: Read>(reader: &mut R) -> Vector3 {
         let mut v = Vector3::default_zero();
-        v.x = f32::parse(file);
-        v.y = f32::parse(file);
-        v.z = f32::parse(file);
+        v.x = f32::parse(reader);
+        v.y = f32::parse(reader);
+        v.z = f32::parse(reader);
         v
     }
 }
@@ -93,12 +94,12 @@ impl Parser for Vector4 {
     fn size() -> usize {
         4 * size_of::<f32>()
     }
-    fn parse(file: &mut File) -> Vector4 {
+    fn parse<R: Read>(reader: &mut R) -> Vector4 {
         let mut v = Vector4::default_zero();
-        v.x = f32::parse(file);
-        v.y = f32::parse(file);
-        v.z = f32::parse(file);
-        v.w = f32::parse(file);
+        v.x = f32::parse(reader);
+        v.y = f32::parse(reader);
+        v.z = f32::parse(reader);
+        v.w = f32::parse(reader);
         v
     }
 }
@@ -107,10 +108,10 @@ impl Parser for Vector2u {
     fn size() -> usize {
         2 * size_of::<u32>()
     }
-    fn parse(file: &mut File) -> Vector2u {
+    fn parse<R: Read>(reader: &mut R) -> Vector2u {
         let mut v = Vector2u::default_zero();
-        v.x = u32::parse(file);
-        v.y = u32::parse(file);
+        v.x = u32::parse(reader);
+        v.y = u32::parse(reader);
         v
     }
 }
@@ -119,11 +120,11 @@ impl Parser for Vector3u {
     fn size() -> usize {
         3 * size_of::<u32>()
     }
-    fn parse(file: &mut File) -> Vector3u {
+    fn parse<R: Read>(reader: &mut R) -> Vector3u {
         let mut v = Vector3u::default_zero();
-        v.x = u32::parse(file);
-        v.y = u32::parse(file);
-        v.z = u32::parse(file);
+        v.x = u32::parse(reader);
+        v.y = u32::parse(reader);
+        v.z = u32::parse(reader);
         v
     }
 }
@@ -132,12 +133,12 @@ impl Parser for Vector4u {
     fn size() -> usize {
         4 * size_of::<u32>()
     }
-    fn parse(file: &mut File) -> Vector4u {
+    fn parse<R: Read>(reader: &mut R) -> Vector4u {
         let mut v = Vector4u::default_zero();
-        v.x = u32::parse(file);
-        v.y = u32::parse(file);
-        v.z = u32::parse(file);
-        v.w = u32::parse(file);
+        v.x = u32::parse(reader);
+        v.y = u32::parse(reader);
+        v.z = u32::parse(reader);
+        v.w = u32::parse(reader);
         v
     }
 }
@@ -146,10 +147,10 @@ impl Parser for Vector2h {
     fn size() -> usize {
         2 * size_of::<u16>()
     }
-    fn parse(file: &mut File) -> Vector2h {
+    fn parse<R: Read>(reader: &mut R) -> Vector2h {
         let mut v = Vector2h::default_zero();
-        v.x = u16::parse(file);
-        v.y = u16::parse(file);
+        v.x = u16::parse(reader);
+        v.y = u16::parse(reader);
         v
     }
 }
@@ -158,11 +159,11 @@ impl Parser for Vector3h {
     fn size() -> usize {
         3 * size_of::<u16>()
     }
-    fn parse(file: &mut File) -> Vector3h {
+    fn parse<R: Read>(reader: &mut R) -> Vector3h {
         let mut v = Vector3h::default_zero();
-        v.x = u16::parse(file);
-        v.y = u16::parse(file);
-        v.z = u16::parse(file);
+        v.x = u16::parse(reader);
+        v.y = u16::parse(reader);
+        v.z = u16::parse(reader);
         v
     }
 }
@@ -171,12 +172,40 @@ impl Parser for Vector4h {
     fn size() -> usize {
         4 * size_of::<u16>()
     }
-    fn parse(file: &mut File) -> Vector4h {
+    fn parse<R: Read>(reader: &mut R) -> Vector4h {
         let mut v = Vector4h::default_zero();
-        v.x = u16::parse(file);
-        v.y = u16::parse(file);
-        v.z = u16::parse(file);
-        v.w = u16::parse(file);
+        v.x = u16::parse(reader);
+        v.y = u16::parse(reader);
+        v.z = u16::parse(reader);
+        v.w = u16::parse(reader);
         v
     }
 }
+
+impl Parser for Vector4b {
+    fn size() -> usize {
+        4 * size_of::<u8>()
+    }
+    fn parse<R: Read>(reader: &mut R) -> Vector4b {
+        Vector4b::new(
+            u8::parse(reader),
+            u8::parse(reader),
+            u8::parse(reader),
+            u8::parse(reader),
+        )
+    }
+}
+
+// Column major, as stored in glTF accessors
+impl Parser for Matrix4 {
+    fn size() -> usize {
+        4 * Vector4::size()
+    }
+    fn parse<R: Read>(reader: &mut R) -> Matrix4 {
+        let x = Vector4::parse(reader);
+        let y = Vector4::parse(reader);
+        let z = Vector4::parse(reader);
+        let w = Vector4::parse(reader);
+        Matrix4::from_cols(x, y, z, w)
+    }
+}
@@ -14,6 +14,7 @@ pub type Vector4u = cgmath::Vector4<u32>;
 pub type Vector2h = cgmath::Vector2<u16>;
 pub type Vector3h = cgmath::Vector3<u16>;
 pub type Vector4h = cgmath::Vector4<u16>;
+pub type Vector4b = cgmath::Vector4<u8>;
 
 pub trait VecBase<T> {
     fn default_zero() -> Self;
@@ -6,6 +6,7 @@ pub mod macros;
 pub mod node;
 pub mod node_registry;
 pub mod node_tree;
+pub mod node_validation;
 pub mod pin;
 
 pub use link::*;
@@ -15,6 +16,7 @@ pub use logic_nodes::*;
 pub use node::*;
 pub use node_registry::*;
 pub use node_tree::*;
+pub use node_validation::*;
 pub use pin::*;
 
 use inox_resources::SharedDataRc;
@@ -8,6 +8,7 @@ pub trait NodeType: Send + Sync + 'static {
     fn name(&self) -> &str;
     fn category(&self) -> &str;
     fn description(&self) -> &str;
+    fn node(&self) -> &Node;
     fn serialize_node(&self) -> Vec<u8>;
     fn deserialize_node(&self, data: &[u8]) -> Option<Box<dyn NodeTrait + Send + Sync>>;
 }
@@ -31,6 +32,9 @@ where
     fn description(&self) -> &str {
         &self.description
     }
+    fn node(&self) -> &Node {
+        self.n.node()
+    }
     fn serialize_node(&self) -> Vec<u8> {
         self.n.serialize_node()
     }
@@ -108,6 +112,12 @@ impl LogicNodeRegistry {
             f(node.as_ref());
         }
     }
+    pub fn find_node_type(&self, name: &str) -> Option<&dyn NodeType> {
+        self.node_types
+            .iter()
+            .find(|n| n.name() == name)
+            .map(|n| n.as_ref())
+    }
     pub fn deserialize_node(&self, data: &[u8]) -> Option<Box<dyn NodeTrait + Send + Sync>> {
         for node in &self.node_types {
             if let Some(n) = node.deserialize_node(data) {
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use inox_serialize::{deserialize_from_text, Deserialize};
+
+use crate::{LogicNodeRegistry, Node, NodeLink, Pin, PinId};
+
+// Node trees are validated in their serialized form, so that also trees
+// with unknown node types or pins can be checked without being deserialized
+#[derive(Deserialize)]
+#[serde(crate = "inox_serialize")]
+struct PinDescription {
+    pin_type: String,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "inox_serialize")]
+struct NodeDescription {
+    name: String,
+    #[serde(default)]
+    inputs: HashMap<String, PinDescription>,
+    #[serde(default)]
+    outputs: HashMap<String, PinDescription>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "inox_serialize")]
+struct NodeEntry {
+    node_type: String,
+    node: NodeDescription,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "inox_serialize")]
+struct NodeTreeDescription {
+    #[serde(default)]
+    nodes: Vec<NodeEntry>,
+    #[serde(default)]
+    links: Vec<NodeLink>,
+}
+
+impl LogicNodeRegistry {
+    // Returns a message for each problem found in a serialized (json) node tree:
+    // unknown node types, unknown or mistyped pins and dangling links
+    pub fn validate_tree(&self, data: &[u8]) -> Vec<String> {
+        let mut errors = Vec::new();
+        let tree = match deserialize_from_text::<NodeTreeDescription>(data) {
+            Some(tree) => tree,
+            None => {
+                errors.push("Unable to read node tree data".to_string());
+                return errors;
+            }
+        };
+
+        let mut nodes = HashMap::new();
+        tree.nodes.iter().for_each(|entry| {
+            let node = &entry.node;
+            if nodes.insert(node.name.as_str(), node).is_some() {
+                errors.push(format!("Node {} is defined more than once", node.name));
+            }
+            match self.find_node_type(&entry.node_type) {
+                Some(node_type) => {
+                    validate_pins(node, node_type.node(), &entry.node_type, &mut errors)
+                }
+                None => errors.push(format!(
+                    "Node {} has unknown type {}",
+                    node.name, entry.node_type
+                )),
+            }
+        });
+
+        tree.links.iter().for_each(|l| {
+            let from_pin = nodes
+                .get(l.from_node())
+                .map(|n| n.outputs.get(l.from_pin()));
+            let to_pin = nodes.get(l.to_node()).map(|n| n.inputs.get(l.to_pin()));
+            let link = format!(
+                "Link from {}.{} to {}.{}",
+                l.from_node(),
+                l.from_pin(),
+                l.to_node(),
+                l.to_pin()
+            );
+            match (from_pin, to_pin) {
+                (None, _) => errors.push(format!(
+                    "{link} is dangling: node {} doesn't exist",
+                    l.from_node()
+                )),
+                (_, None) => errors.push(format!(
+                    "{link} is dangling: node {} doesn't exist",
+                    l.to_node()
+                )),
+                (Some(None), _) => errors.push(format!(
+                    "{link} is dangling: output pin {} doesn't exist",
+                    l.from_pin()
+                )),
+                (_, Some(None)) => errors.push(format!(
+                    "{link} is dangling: input pin {} doesn't exist",
+                    l.to_pin()
+                )),
+                (Some(Some(from)), Some(Some(to))) => {
+                    if from.pin_type != to.pin_type {
+                        errors.push(format!(
+                            "{link} connects pin type {} to {}",
+                            from.pin_type, to.pin_type
+                        ));
+                    }
+                }
+            }
+        });
+        errors
+    }
+}
+
+fn validate_pins(
+    node: &NodeDescription,
+    expected: &Node,
+    node_type: &str,
+    errors: &mut Vec<String>,
+) {
+    let mut check = |pins: &HashMap<String, PinDescription>,
+                     expected_pins: &HashMap<PinId, Box<dyn Pin>>,
+                     kind: &str| {
+        pins.iter().for_each(|(name, pin)| {
+            match expected_pins
+                .iter()
+                .find(|(id, _)| id.name() == name)
+                .map(|(_, p)| p.get_type_name())
+            {
+                Some(type_name) if type_name != pin.pin_type => errors.push(format!(
+                    "Node {} {kind} pin {name} is {} while {node_type} expects {type_name}",
+                    node.name, pin.pin_type
+                )),
+                Some(_) => {}
+                None => errors.push(format!(
+                    "Node {} has {kind} pin {name} that doesn't exist in {node_type}",
+                    node.name
+                )),
+            }
+        });
+        expected_pins.keys().for_each(|id| {
+            if !pins.contains_key(id.name()) {
+                errors.push(format!(
+                    "Node {} is missing {kind} pin {} of {node_type}",
+                    node.name,
+                    id.name()
+                ));
+            }
+        });
+    };
+    check(&node.inputs, expected.inputs(), "input");
+    check(&node.outputs, expected.outputs(), "output");
+}
+
+#[test]
+fn test_validate_tree() {
+    use crate::{RustExampleNode, ScriptInitNode};
+
+    let mut registry = LogicNodeRegistry::default();
+    registry.register_node::<ScriptInitNode>();
+    registry.register_node::<RustExampleNode>();
+
+    let example_node = r#"{"node_type":"RustExampleNode","node":{"name":"NodeA","inputs":{
+        "in_int":{"pin_type":"i32","value":0},"in_float":{"pin_type":"f32","value":0.0},
+        "in_string":{"pin_type":"String","value":""},"in_bool":{"pin_type":"bool","value":false},
+        "in_execute":{"pin_type":"LogicExecution"}},"outputs":{
+        "out_execute":{"pin_type":"LogicExecution"},"out_int":{"pin_type":"i32","value":0},
+        "out_float":{"pin_type":"f32","value":0.0},"out_string":{"pin_type":"String","value":""},
+        "out_bool":{"pin_type":"bool","value":false}}}}"#;
+    let init_node = r#"{"node_type":"ScriptInitNode","node":{"name":"Init","inputs":{},
+        "outputs":{"Execute":{"pin_type":"LogicExecution"}}}}"#;
+    let tree = |nodes: &[&str], links: &str| {
+        format!(r#"{{"nodes":[{}],"links":[{links}]}}"#, nodes.join(","))
+    };
+    let link = |from_node: &str, to_node: &str, from_pin: &str, to_pin: &str| {
+        format!(
+            r#"{{"from_node":"{from_node}","to_node":"{to_node}","from_pin":"{from_pin}","to_pin":"{to_pin}"}}"#
+        )
+    };
+
+    let valid = tree(
+        &[init_node, example_node],
+        &link("Init", "NodeA", "Execute", "in_execute"),
+    );
+    assert!(registry.validate_tree(valid.as_bytes()).is_empty());
+
+    let unknown_type = tree(
+        &[init_node.replace("ScriptInitNode", "MissingNode").as_str()],
+        "",
+    );
+    assert_eq!(registry.validate_tree(unknown_type.as_bytes()).len(), 1);
+
+    let renamed_pin = tree(&[init_node.replace("Execute", "Run").as_str()], "");
+    assert_eq!(registry.validate_tree(renamed_pin.as_bytes()).len(), 2);
+
+    let mistyped_pin = tree(
+        &[example_node
+            .replace(
+                r#""in_int":{"pin_type":"i32""#,
+                r#""in_int":{"pin_type":"u32""#,
+            )
+            .as_str()],
+        "",
+    );
+    assert_eq!(registry.validate_tree(mistyped_pin.as_bytes()).len(), 1);
+
+    let dangling_links = tree(
+        &[init_node, example_node],
+        &[
+            link("Init", "NodeB", "Execute", "in_execute"),
+            link("Init", "NodeA", "Run", "in_execute"),
+            link("NodeA", "NodeA", "out_int", "in_float"),
+        ]
+        .join(","),
+    );
+    assert_eq!(registry.validate_tree(dangling_links.as_bytes()).len(), 3);
+
+    assert_eq!(registry.validate_tree(b"not a tree").len(), 1);
+}
@@ -4,9 +4,6 @@ use inox_math::{compute_hash_position, Vector3};
 use inox_render::MeshletData;
 use meshopt::DecodePosition;
 
-pub const MESHLETS_GROUP_SIZE: usize = 4;
-pub const HALF_MESHLETS_GROUP_SIZE: usize = MESHLETS_GROUP_SIZE / 2;
-
 #[derive(Default, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Clone)]
 struct Edge {
     v1: u32,
@@ -219,7 +216,10 @@ fn fill_with_info_and_adjacency(
     }
 }
 
-pub fn group_meshlets_with_metis(meshlets_info: &[MeshletAdjacency]) -> Vec<Vec<u32>> {
+pub fn group_meshlets_with_metis(
+    meshlets_info: &[MeshletAdjacency],
+    group_size: usize,
+) -> Vec<Vec<u32>> {
     let mut xadj = Vec::new();
     let mut adjncy = Vec::new();
     let mut adjwgt = Vec::new();
@@ -234,7 +234,7 @@ pub fn group_meshlets_with_metis(meshlets_info: &[MeshletAdjacency]) -> Vec<Vec<
     });
     xadj.push(adjncy.len() as i32);
 
-    let num_groups = meshlets_info.len().div_ceil(MESHLETS_GROUP_SIZE);
+    let num_groups = meshlets_info.len().div_ceil(group_size);
     let mut meshlets_groups = Vec::new();
     if let Ok(graph) = metis::Graph::new(1, num_groups as _, &xadj, &adjncy) {
         let mut part = vec![0; meshlets_info.len()];
@@ -262,15 +262,15 @@ pub fn group_meshlets_with_metis(meshlets_info: &[MeshletAdjacency]) -> Vec<Vec<
     meshlets_groups
 }
 
-#[allow(dead_code)]
-pub fn group_meshlets(meshlets_info: &[MeshletAdjacency]) -> Vec<Vec<u32>> {
+pub fn group_meshlets(meshlets_info: &[MeshletAdjacency], group_size: usize) -> Vec<Vec<u32>> {
+    let half_group_size = group_size / 2;
     let mut available_meshlets = meshlets_info.to_vec();
     let mut meshlets_groups = Vec::new();
     while !available_meshlets.is_empty() {
         let mut meshlet_group = Vec::new();
         meshlet_group.push(available_meshlets.remove(0));
         let mut meshlet_current_index = 0;
-        while meshlet_group.len() < MESHLETS_GROUP_SIZE {
+        while meshlet_group.len() < group_size {
             let mut max_adjacency_value = -1;
             let mut adjacent_index = -1;
             meshlet_group.iter().enumerate().for_each(|(i, m)| {
@@ -312,7 +312,7 @@ pub fn group_meshlets(meshlets_info: &[MeshletAdjacency]) -> Vec<Vec<u32>> {
             }
         }
         let mut should_retry = meshlet_group.is_empty();
-        should_retry |= meshlet_group.len() == 1 && available_meshlets.len() > MESHLETS_GROUP_SIZE;
+        should_retry |= meshlet_group.len() == 1 && available_meshlets.len() > group_size;
         should_retry &= !meshlet_group.iter().all(|mi| {
             meshlets_info[mi.meshlet_index as usize]
                 .adjacent_meshlets
@@ -330,10 +330,10 @@ pub fn group_meshlets(meshlets_info: &[MeshletAdjacency]) -> Vec<Vec<u32>> {
                 {
                     let original = &meshlets_info[p];
                     let mut a = original.adjacent_meshlets.len() as i32 - 1;
-                    while a >= 0 && stealed.len() < HALF_MESHLETS_GROUP_SIZE {
+                    while a >= 0 && stealed.len() < half_group_size {
                         let mut j = meshlets_groups.len() as i32 - 1;
-                        while a >= 0 && j >= 0 && stealed.len() < HALF_MESHLETS_GROUP_SIZE {
-                            if meshlets_groups[j as usize].len() > HALF_MESHLETS_GROUP_SIZE {
+                        while a >= 0 && j >= 0 && stealed.len() < half_group_size {
+                            if meshlets_groups[j as usize].len() > half_group_size {
                                 if let Some(i) = meshlets_groups[j as usize].iter().position(|m| {
                                     m.meshlet_index == original.adjacent_meshlets[a as usize].0
                                 }) {
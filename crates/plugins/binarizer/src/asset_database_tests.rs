@@ -0,0 +1,289 @@
+use std::{
+    fs::{self, create_dir_all},
+    path::PathBuf,
+};
+
+use inox_resources::{AssetResult, AssetStatus, BuildReport, DiagnosticSeverity, MeshStats};
+
+use crate::{
+    abort_asset, add_asset_dependency, add_asset_mesh_stats, asset_dependents, begin_asset,
+    clean_asset_database, end_asset, need_to_binarize, open_asset_database, read_build_report,
+    report_asset_diagnostic, save_asset_database, stale_assets, take_asset_results,
+    track_asset_output, write_build_report, ASSET_DATABASE_FILENAME,
+};
+
+#[allow(dead_code)]
+fn asset_database_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_asset_database_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    let data_raw_folder = test_folder.join("data_raw");
+    let data_folder = test_folder.join("data");
+    create_dir_all(data_raw_folder.as_path()).unwrap();
+    create_dir_all(data_folder.as_path()).unwrap();
+    let data_raw_folder = data_raw_folder.canonicalize().unwrap();
+    let data_folder = data_folder.canonicalize().unwrap();
+
+    let source = data_raw_folder.join("main.wgsl");
+    let dependency = data_raw_folder.join("common.inc");
+    let output = data_folder.join("main.shader");
+    fs::write(source.as_path(), "#import \"common.inc\"").unwrap();
+    fs::write(dependency.as_path(), "const A: u32 = 1u;").unwrap();
+
+    let compile = |version: u32| {
+        if !begin_asset(
+            source.as_path(),
+            "test",
+            version,
+            data_raw_folder.as_path(),
+            data_folder.as_path(),
+        ) {
+            return false;
+        }
+        add_asset_dependency(source.as_path(), "test", dependency.as_path());
+        // Outputs of assets in building are always written and recorded only once written
+        assert!(need_to_binarize(source.as_path(), output.as_path()));
+        assert!(need_to_binarize(
+            source.as_path(),
+            data_folder.join("unused.shader").as_path()
+        ));
+        fs::write(output.as_path(), "compiled").unwrap();
+        track_asset_output(source.as_path(), "test", output.as_path());
+        end_asset(
+            source.as_path(),
+            "test",
+            data_raw_folder.as_path(),
+            data_folder.as_path(),
+        );
+        true
+    };
+
+    open_asset_database(data_raw_folder.as_path(), data_folder.as_path(), false);
+    assert!(compile(1));
+    // The database is saved once for all the assets compiled
+    assert!(!data_folder.join(ASSET_DATABASE_FILENAME).exists());
+    save_asset_database(data_raw_folder.as_path(), data_folder.as_path());
+    assert!(data_folder.join(ASSET_DATABASE_FILENAME).exists());
+    assert!(!compile(1));
+
+    let results = take_asset_results(data_raw_folder.as_path(), data_folder.as_path());
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].status, AssetStatus::Produced);
+    assert_eq!(results[0].outputs, vec![PathBuf::from("main.shader")]);
+    assert_eq!(results[1].status, AssetStatus::Skipped);
+
+    // Same content with a newer modification time is still up to date
+    fs::write(source.as_path(), "#import \"common.inc\"").unwrap();
+    assert!(!compile(1));
+
+    // Changed imports and compilers rebuild the asset
+    fs::write(dependency.as_path(), "const A: u32 = 22u;").unwrap();
+    assert_eq!(asset_dependents(dependency.as_path()), vec![source.clone()]);
+    assert!(compile(1));
+    assert!(!compile(1));
+    assert!(compile(2));
+    fs::remove_file(output.as_path()).unwrap();
+    assert!(compile(2));
+
+    // Verify only reports what is stale
+    open_asset_database(data_raw_folder.as_path(), data_folder.as_path(), true);
+    assert!(!compile(2));
+    assert!(stale_assets(data_raw_folder.as_path(), data_folder.as_path()).is_empty());
+    fs::write(source.as_path(), "#import \"common.inc\"\n").unwrap();
+    assert!(!compile(2));
+    let stale = stale_assets(data_raw_folder.as_path(), data_folder.as_path());
+    assert_eq!(stale.len(), 1);
+    assert!(stale[0].starts_with("main.wgsl (test)"));
+
+    // Clean removes the outputs and the database
+    clean_asset_database(data_raw_folder.as_path(), data_folder.as_path());
+    assert!(!output.exists());
+    assert!(!data_folder.join(ASSET_DATABASE_FILENAME).exists());
+    open_asset_database(data_raw_folder.as_path(), data_folder.as_path(), false);
+    assert!(compile(2));
+
+    // Failed assets are reported and compiled again next time
+    let compile_failing = || {
+        let is_compiling = begin_asset(
+            source.as_path(),
+            "failing",
+            1,
+            data_raw_folder.as_path(),
+            data_folder.as_path(),
+        );
+        abort_asset(
+            source.as_path(),
+            "failing",
+            data_raw_folder.as_path(),
+            data_folder.as_path(),
+        );
+        is_compiling
+    };
+    assert!(compile_failing());
+    assert!(compile_failing());
+    let results = take_asset_results(data_raw_folder.as_path(), data_folder.as_path());
+    assert_eq!(results.len(), 3);
+    assert!(results[1..]
+        .iter()
+        .all(|r| r.status == AssetStatus::Failed && r.compiler == "failing"));
+
+    // Compilers of the same source build it together without mixing what they record
+    let begin = |compiler: &str| {
+        begin_asset(
+            source.as_path(),
+            compiler,
+            1,
+            data_raw_folder.as_path(),
+            data_folder.as_path(),
+        )
+    };
+    let end = |compiler: &str| {
+        end_asset(
+            source.as_path(),
+            compiler,
+            data_raw_folder.as_path(),
+            data_folder.as_path(),
+        )
+    };
+    assert!(begin("first"));
+    assert!(begin("second"));
+    let first_output = data_folder.join("first.bin");
+    fs::write(first_output.as_path(), "first").unwrap();
+    track_asset_output(source.as_path(), "first", first_output.as_path());
+    report_asset_diagnostic(
+        source.as_path(),
+        "second",
+        DiagnosticSeverity::Warning,
+        "",
+        "second only",
+    );
+    end("second");
+    end("first");
+    let results = take_asset_results(data_raw_folder.as_path(), data_folder.as_path());
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].compiler, "second");
+    assert!(results[0].outputs.is_empty());
+    assert_eq!(results[0].diagnostics.len(), 1);
+    assert_eq!(results[1].compiler, "first");
+    assert_eq!(results[1].outputs, vec![PathBuf::from("first.bin")]);
+    assert!(results[1].diagnostics.is_empty());
+
+    // Removed sources and outputs not written yet are binarized instead of failing
+    assert!(!need_to_binarize(source.as_path(), output.as_path()));
+    assert!(need_to_binarize(
+        data_raw_folder.join("removed.wgsl").as_path(),
+        output.as_path()
+    ));
+    assert!(need_to_binarize(
+        source.as_path(),
+        data_folder.join("removed.shader").as_path()
+    ));
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[allow(dead_code)]
+fn build_report_test() {
+    let result = |source: &str, status: AssetStatus| AssetResult::new(source, "test", status);
+    let mut report = BuildReport::default();
+    report.add_results(vec![
+        result("a.wgsl", AssetStatus::Failed),
+        result("b.wgsl", AssetStatus::Skipped),
+    ]);
+    assert!(!report.is_successful());
+
+    // Only the last result of an asset is kept
+    report.add_results(vec![result("a.wgsl", AssetStatus::Produced)]);
+    assert!(report.is_successful());
+    assert_eq!(report.produced.len(), 1);
+    assert_eq!(report.skipped.len(), 1);
+    assert!(report.failed.is_empty());
+}
+
+#[allow(dead_code)]
+fn diagnostics_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_diagnostics_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    let data_raw_folder = test_folder.join("data_raw");
+    let data_folder = test_folder.join("data");
+    create_dir_all(data_raw_folder.as_path()).unwrap();
+    create_dir_all(data_folder.as_path()).unwrap();
+    let data_raw_folder = data_raw_folder.canonicalize().unwrap();
+    let data_folder = data_folder.canonicalize().unwrap();
+
+    let source = data_raw_folder.join("model.gltf");
+    fs::write(source.as_path(), "{}").unwrap();
+
+    let compile = |version: u32, severity: DiagnosticSeverity| {
+        if !begin_asset(
+            source.as_path(),
+            "test",
+            version,
+            data_raw_folder.as_path(),
+            data_folder.as_path(),
+        ) {
+            return false;
+        }
+        report_asset_diagnostic(
+            source.as_path(),
+            "test",
+            severity,
+            "Mesh_Primitive_1",
+            "skipped",
+        );
+        add_asset_mesh_stats(
+            source.as_path(),
+            "test",
+            MeshStats {
+                name: "Mesh_Primitive_0".to_string(),
+                vertices: 3,
+                triangles: 1,
+                meshlets_per_lod: vec![1],
+                ..Default::default()
+            },
+        );
+        end_asset(
+            source.as_path(),
+            "test",
+            data_raw_folder.as_path(),
+            data_folder.as_path(),
+        );
+        true
+    };
+
+    open_asset_database(data_raw_folder.as_path(), data_folder.as_path(), false);
+    assert!(compile(1, DiagnosticSeverity::Warning));
+    let results = take_asset_results(data_raw_folder.as_path(), data_folder.as_path());
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].status, AssetStatus::Produced);
+    assert_eq!(results[0].diagnostics.len(), 1);
+    assert_eq!(results[0].diagnostics[0].asset, "model.gltf");
+    assert_eq!(results[0].diagnostics[0].location, "Mesh_Primitive_1");
+    assert_eq!(results[0].stats.meshes.len(), 1);
+
+    // Errors make the asset fail, so that it's compiled again next time
+    assert!(compile(2, DiagnosticSeverity::Error));
+    assert!(compile(2, DiagnosticSeverity::Error));
+    let results = take_asset_results(data_raw_folder.as_path(), data_folder.as_path());
+    assert!(results.iter().all(|r| r.status == AssetStatus::Failed));
+
+    // Diagnostics are read back from the report in the data folder
+    let mut report = BuildReport::new("pc", data_folder.as_path());
+    report.add_results(results);
+    assert!(write_build_report(&report));
+    let read_report = read_build_report(data_folder.as_path()).unwrap();
+    assert_eq!(read_report, report);
+    assert_eq!(read_report.count_diagnostics(DiagnosticSeverity::Error), 1);
+    assert_eq!(
+        read_report.count_diagnostics(DiagnosticSeverity::Warning),
+        0
+    );
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[test]
+fn tests() {
+    asset_database_test();
+    build_report_test();
+    diagnostics_test();
+}
@@ -0,0 +1,321 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Receiver,
+        Arc, RwLock,
+    },
+    thread,
+    time::Duration,
+};
+
+use inox_binarizer::{
+    config::Config, spawn_binarizer_workers, write_build_reports, Binarizer, BinarizerParameters,
+};
+use inox_commands::CommandParser;
+use inox_core::{ContextRc, System};
+use inox_platform::{
+    PLATFORM_TYPE_PC, PLATFORM_TYPE_PC_NAME, PLATFORM_TYPE_WEB, PLATFORM_TYPE_WEB_NAME,
+};
+use inox_resources::{
+    BuildReport, ConfigBase, Data, DiagnosticSeverity, CONFIG_FOLDER, PC_FOLDER, WEB_FOLDER,
+};
+use inox_serialize::deserialize_from_text;
+
+const USAGE: &str = "Binarizes the assets of a data_raw folder into a data folder
+
+Usage: inox_binarizer_cli [options]
+
+Options:
+  --input <folder>        Folder of the source assets [default: data_raw]
+  --output <folder>       Folder of the binarized assets, with a subfolder for each platform [default: data]
+  --platform <name>...    Platforms to binarize for: pc, web [default: pc]
+  --watch                 Keeps binarizing the files that change until stopped
+  --only <glob>...        Binarizes only the files matching one of the globs
+  --jobs <count>          Files and meshes binarized in parallel [default: number of cores]
+  --no_optimize_meshes    Keeps vertices and indices of meshes in their original order
+  --no_mesh_lods          Builds only the meshlets of full detail
+  --bake_lightmaps        Bakes lightmaps of static glTF meshes on the CPU
+  --clean                 Removes everything binarized before
+  --verify                Only checks that the binarized assets are up to date
+  --report <file>         Writes a json report of what has been produced, skipped or failed
+  --help                  Prints this message
+
+Options can be given with a single dash too, as -watch.
+Options not given on the command line are read from config/inox_binarizer/binarizer.cfg
+in the input folder.
+Exits with an error when an asset fails to compile or, with --verify, when one is stale";
+
+const OPTIONS: [&str; 13] = [
+    "input",
+    "output",
+    "platform",
+    "watch",
+    "only",
+    "jobs",
+    "no_optimize_meshes",
+    "no_mesh_lods",
+    "bake_lightmaps",
+    "clean",
+    "verify",
+    "report",
+    "help",
+];
+
+const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+const CONFIG_PLUGIN_NAME: &str = "inox_binarizer";
+
+// Binarizer of a platform together with the last report it has sent
+struct Target {
+    binarizer: Box<dyn System>,
+    reports: Receiver<BuildReport>,
+    report: BuildReport,
+}
+
+impl Target {
+    fn new(
+        context: &ContextRc,
+        platform: &str,
+        data_raw_folder: &Path,
+        output_folder: &Path,
+        info: &BinarizerParameters,
+    ) -> Option<Self> {
+        let (data_folder, binarizer, reports): (PathBuf, Box<dyn System>, _) = match platform {
+            PLATFORM_TYPE_PC_NAME => {
+                let data_folder = output_folder.join(PC_FOLDER);
+                let mut binarizer = Binarizer::<PLATFORM_TYPE_PC>::new(
+                    context,
+                    data_raw_folder.to_path_buf(),
+                    data_folder.clone(),
+                    info,
+                );
+                let reports = binarizer.report_receiver();
+                (data_folder, Box::new(binarizer), reports)
+            }
+            PLATFORM_TYPE_WEB_NAME => {
+                let data_folder = output_folder.join(WEB_FOLDER);
+                let mut binarizer = Binarizer::<PLATFORM_TYPE_WEB>::new(
+                    context,
+                    data_raw_folder.to_path_buf(),
+                    data_folder.clone(),
+                    info,
+                );
+                let reports = binarizer.report_receiver();
+                (data_folder, Box::new(binarizer), reports)
+            }
+            _ => return None,
+        };
+        Some(Self {
+            report: BuildReport::new(platform, data_folder.as_path()),
+            binarizer,
+            reports,
+        })
+    }
+
+    // Returns true when the binarizer has sent a new report
+    fn update_report(&mut self) -> bool {
+        match self.reports.try_iter().last() {
+            Some(report) => {
+                self.report = report;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+// Config of the binarizer in the input folder, None when there is none
+fn read_config(data_raw_folder: &Path) -> Result<Option<Config>, String> {
+    let config = Config::default();
+    let path = data_raw_folder
+        .join(CONFIG_FOLDER)
+        .join(CONFIG_PLUGIN_NAME)
+        .join(config.get_filename());
+    if !path.is_file() {
+        return Ok(None);
+    }
+    fs::read(path.as_path())
+        .ok()
+        .and_then(|bytes| deserialize_from_text::<Config>(&bytes))
+        .map(Some)
+        .ok_or(format!("Unable to read config {path:?}"))
+}
+
+fn first_value<T>(command_parser: &CommandParser, name: &str) -> Option<T>
+where
+    T: std::str::FromStr + Default,
+{
+    command_parser.get_values_of::<T>(name).into_iter().next()
+}
+
+// Unknown options and unparsable values are errors instead of being ignored
+fn check_options(command_parser: &CommandParser) -> Result<(), String> {
+    if let Some(name) = command_parser.names().find(|n| !OPTIONS.contains(n)) {
+        return Err(format!("Unknown option --{name}, see --help"));
+    }
+    jobs_count(command_parser).map(|_| ())
+}
+
+// Jobs given on the command line, None to use the default
+fn jobs_count(command_parser: &CommandParser) -> Result<Option<usize>, String> {
+    if !command_parser.has("jobs") {
+        return Ok(None);
+    }
+    let jobs = first_value::<String>(command_parser, "jobs").unwrap_or_default();
+    match jobs.parse::<usize>() {
+        Ok(jobs) if jobs > 0 => Ok(Some(jobs)),
+        _ => Err(format!(
+            "Invalid --jobs value '{jobs}': a count greater than zero is expected"
+        )),
+    }
+}
+
+fn write_reports(report_path: Option<&Path>, targets: &[Target]) {
+    if let Some(path) = report_path {
+        let reports = targets.iter().map(|t| t.report.clone()).collect::<Vec<_>>();
+        write_build_reports(path, &reports);
+    }
+}
+
+fn main() -> ExitCode {
+    let command_parser = CommandParser::from_command_line();
+    if command_parser.has("help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    if let Err(e) = check_options(&command_parser) {
+        eprintln!("{e}");
+        return ExitCode::from(2);
+    }
+
+    let data_raw_folder = first_value::<String>(&command_parser, "input")
+        .map(PathBuf::from)
+        .unwrap_or_else(Data::data_raw_folder);
+    if !data_raw_folder.is_dir() {
+        eprintln!("Input folder {data_raw_folder:?} not found");
+        return ExitCode::from(2);
+    }
+    let output_folder = first_value::<String>(&command_parser, "output")
+        .map(PathBuf::from)
+        .unwrap_or_else(Data::data_folder);
+    let mut platforms = command_parser.get_values_of::<String>("platform");
+    if platforms.is_empty() {
+        platforms.push(PLATFORM_TYPE_PC_NAME.to_string());
+    }
+    let jobs = jobs_count(&command_parser)
+        .unwrap_or_default()
+        .unwrap_or_else(|| thread::available_parallelism().map_or(4, |n| n.get()));
+    let report_path = first_value::<String>(&command_parser, "report").map(PathBuf::from);
+    let is_watching = command_parser.has("watch");
+    let config = match read_config(data_raw_folder.as_path()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    // Command line flags win over the config, that has the other options
+    let optimize_meshes = config.as_ref().map(|c| c.optimize_meshes).unwrap_or(true);
+    let config = config.unwrap_or_default();
+    let info = BinarizerParameters {
+        should_end_on_completion: AtomicBool::new(!is_watching),
+        optimize_meshes: AtomicBool::new(
+            optimize_meshes && !command_parser.has("no_optimize_meshes"),
+        ),
+        preprocess_shaders_paths: Vec::new(),
+        clean: command_parser.has("clean"),
+        verify: command_parser.has("verify"),
+        only: command_parser.get_values_of::<String>("only"),
+        jobs,
+        skip_mesh_lods: command_parser.has("no_mesh_lods"),
+        font_options: RwLock::new(config.font),
+        meshlet_options: RwLock::new(config.meshlets),
+        meshlet_options_overrides: RwLock::new(config.meshlets_overrides),
+        bake_lightmaps: command_parser.has("bake_lightmaps"),
+        lightmap_options: RwLock::new(config.lightmaps),
+    };
+
+    // No window and no renderer: only the job handler of the context is used
+    let context = ContextRc::default();
+    let mut targets = Vec::new();
+    for platform in platforms.iter() {
+        match Target::new(&context, platform, &data_raw_folder, &output_folder, &info) {
+            Some(target) => targets.push(target),
+            None => {
+                eprintln!("Binarization for platform {platform} is not supported");
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    // Each binarizer thread executes jobs too while waiting for them
+    let can_continue = Arc::new(AtomicBool::new(true));
+    let workers = spawn_binarizer_workers(context.job_handler(), jobs - 1, &can_continue);
+
+    targets.iter_mut().for_each(|t| t.binarizer.init());
+    let mut is_running = true;
+    while is_running {
+        thread::sleep(UPDATE_INTERVAL);
+        is_running = targets
+            .iter_mut()
+            .fold(false, |is_running, t| t.binarizer.run() || is_running);
+        let has_results = targets
+            .iter_mut()
+            .fold(false, |has_results, t| t.update_report() || has_results);
+        if is_watching && has_results {
+            write_reports(report_path.as_deref(), &targets);
+        }
+    }
+
+    can_continue.store(false, Ordering::SeqCst);
+    workers.into_iter().for_each(|w| w.join().unwrap());
+
+    for target in targets.iter_mut() {
+        target.update_report();
+        let report = &target.report;
+        println!(
+            "{}: {} produced, {} skipped, {} failed, {} warnings",
+            report.platform,
+            report.produced.len(),
+            report.skipped.len(),
+            report.failed.len(),
+            report.count_diagnostics(DiagnosticSeverity::Warning)
+        );
+        report
+            .failed
+            .iter()
+            .for_each(|r| eprintln!("Failed to compile {} with {}", r.source, r.compiler));
+    }
+    write_reports(report_path.as_deref(), &targets);
+
+    if targets.iter().all(|t| t.report.is_successful()) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[test]
+fn test_options() {
+    let command_parser = CommandParser::from_string("--watch --only *.gltf --jobs 2");
+    assert!(check_options(&command_parser).is_ok());
+    assert!(command_parser.has("watch"));
+    assert_eq!(
+        command_parser.get_values_of::<String>("only"),
+        vec!["*.gltf"]
+    );
+    assert_eq!(jobs_count(&command_parser), Ok(Some(2)));
+    assert_eq!(
+        jobs_count(&CommandParser::from_string("-jobs 3")),
+        Ok(Some(3))
+    );
+
+    assert!(check_options(&CommandParser::from_string("--watchh")).is_err());
+    assert!(check_options(&CommandParser::from_string("--jobs four")).is_err());
+    assert!(check_options(&CommandParser::from_string("--jobs 0")).is_err());
+    assert!(check_options(&CommandParser::from_string("--jobs")).is_err());
+}
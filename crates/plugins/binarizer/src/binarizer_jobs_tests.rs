@@ -0,0 +1,96 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc, RwLock,
+};
+
+use inox_core::{JobHandler, JobHandlerRw, JobHandlerTrait};
+
+use crate::BinarizerJobs;
+
+const NUM_JOBS: usize = 64;
+const NUM_CHILD_JOBS: usize = 4;
+
+#[allow(dead_code)]
+fn inline_jobs_test() {
+    let jobs = BinarizerJobs::default();
+    let counter = Arc::new(AtomicUsize::new(0));
+    for _ in 0..NUM_JOBS {
+        let counter = counter.clone();
+        jobs.add_job("Inline", move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    // Without a job handler everything has been executed already
+    assert_eq!(counter.load(Ordering::SeqCst), NUM_JOBS);
+    jobs.wait();
+    assert_eq!(jobs.progress(), (NUM_JOBS, NUM_JOBS));
+}
+
+#[allow(dead_code)]
+fn nested_jobs_test(start_workers: bool) {
+    let job_handler: JobHandlerRw = Arc::new(RwLock::new(JobHandler::default()));
+    let can_continue = Arc::new(AtomicBool::new(true));
+    if start_workers {
+        job_handler.start(&can_continue);
+    }
+
+    let jobs = BinarizerJobs::new(&job_handler);
+    let counter = Arc::new(AtomicUsize::new(0));
+    for i in 0..NUM_JOBS {
+        let counter = counter.clone();
+        let child_jobs = jobs.child();
+        jobs.add_job(format!("File {i}").as_str(), move || {
+            // Like a file scheduling its meshes and waiting for them
+            for j in 0..NUM_CHILD_JOBS {
+                let counter = counter.clone();
+                child_jobs.add_job(format!("File {i} Mesh {j}").as_str(), move || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+            child_jobs.wait();
+            assert_eq!(child_jobs.progress(), (NUM_CHILD_JOBS, NUM_CHILD_JOBS));
+        });
+    }
+    jobs.wait();
+    assert_eq!(counter.load(Ordering::SeqCst), NUM_JOBS * NUM_CHILD_JOBS);
+    assert_eq!(jobs.progress(), (NUM_JOBS, NUM_JOBS));
+
+    can_continue.store(false, Ordering::SeqCst);
+    job_handler.stop();
+}
+
+#[allow(dead_code)]
+fn own_jobs_test() {
+    let job_handler: JobHandlerRw = Arc::new(RwLock::new(JobHandler::default()));
+    let jobs = BinarizerJobs::new(&job_handler);
+    let other_jobs = jobs.child();
+    let child_jobs = jobs.child();
+    let other_counter = Arc::new(AtomicUsize::new(0));
+    let child_counter = Arc::new(AtomicUsize::new(0));
+    {
+        let other_counter = other_counter.clone();
+        other_jobs.add_job("Other", move || {
+            other_counter.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    {
+        let child_counter = child_counter.clone();
+        child_jobs.add_job("Child", move || {
+            child_counter.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    // Without workers, waiting executes only the jobs scheduled by the same child
+    child_jobs.wait();
+    assert_eq!(child_counter.load(Ordering::SeqCst), 1);
+    assert_eq!(other_counter.load(Ordering::SeqCst), 0);
+    other_jobs.wait();
+    assert_eq!(other_counter.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn tests() {
+    inline_jobs_test();
+    nested_jobs_test(false);
+    nested_jobs_test(true);
+    own_jobs_test();
+}
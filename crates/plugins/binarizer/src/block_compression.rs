@@ -0,0 +1,250 @@
+// Encoders of a single 4x4 block of rgba8 pixels:
+// endpoints come from the principal axis of the block values and each pixel picks its closest palette entry
+
+pub type PixelsBlock = [[u8; 4]; 16];
+
+const BC7_WEIGHTS: [i32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+const ASTC_WEIGHTS: [i32; 4] = [0, 21, 43, 64];
+// 4x4 weights grid with 2 bits each, single plane
+const ASTC_BLOCK_MODE: u32 = 0x42;
+// LDR RGBA direct
+const ASTC_COLOR_ENDPOINT_MODE: u32 = 12;
+
+#[derive(Default)]
+struct BitWriter {
+    bits: u128,
+    offset: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, count: u32) {
+        self.bits |= ((value as u128) & ((1u128 << count) - 1)) << self.offset;
+        self.offset += count;
+    }
+}
+
+fn principal_endpoints<const C: usize>(values: &[[f32; C]; 16]) -> ([f32; C], [f32; C]) {
+    let mut mean = [0f32; C];
+    values
+        .iter()
+        .for_each(|v| (0..C).for_each(|c| mean[c] += v[c] / 16.));
+    let mut covariance = [[0f32; C]; C];
+    values.iter().for_each(|v| {
+        (0..C).for_each(|i| {
+            (0..C).for_each(|j| covariance[i][j] += (v[i] - mean[i]) * (v[j] - mean[j]))
+        })
+    });
+    // Power iteration starting from the channel with the biggest variance
+    let mut axis = (0..C)
+        .max_by(|a, b| covariance[*a][*a].total_cmp(&covariance[*b][*b]))
+        .map(|c| covariance[c])
+        .unwrap();
+    for _ in 0..8 {
+        let mut next = [0f32; C];
+        (0..C).for_each(|i| (0..C).for_each(|j| next[i] += covariance[i][j] * axis[j]));
+        let length = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if length <= f32::EPSILON {
+            break;
+        }
+        (0..C).for_each(|c| axis[c] = next[c] / length);
+    }
+    let length = axis.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if length <= f32::EPSILON {
+        return (mean, mean);
+    }
+    (0..C).for_each(|c| axis[c] /= length);
+    let (mut min_t, mut max_t) = (f32::MAX, f32::MIN);
+    values.iter().for_each(|v| {
+        let t = (0..C).map(|c| (v[c] - mean[c]) * axis[c]).sum::<f32>();
+        min_t = min_t.min(t);
+        max_t = max_t.max(t);
+    });
+    let mut start = [0f32; C];
+    let mut end = [0f32; C];
+    (0..C).for_each(|c| {
+        start[c] = (mean[c] + axis[c] * min_t).clamp(0., 255.);
+        end[c] = (mean[c] + axis[c] * max_t).clamp(0., 255.);
+    });
+    (start, end)
+}
+
+fn closest_index<const C: usize>(palette: &[[i32; C]], value: &[u8]) -> u32 {
+    let mut best = (0, i32::MAX);
+    palette.iter().enumerate().for_each(|(i, p)| {
+        let distance = (0..C)
+            .map(|c| (p[c] - value[c] as i32) * (p[c] - value[c] as i32))
+            .sum::<i32>();
+        if distance < best.1 {
+            best = (i, distance);
+        }
+    });
+    best.0 as u32
+}
+
+fn to_565(color: &[f32; 3]) -> u16 {
+    let r = (color[0] * 31. / 255.).round() as u16;
+    let g = (color[1] * 63. / 255.).round() as u16;
+    let b = (color[2] * 31. / 255.).round() as u16;
+    (r << 11) | (g << 5) | b
+}
+
+fn from_565(value: u16) -> [i32; 3] {
+    let r = ((value >> 11) & 31) as i32;
+    let g = ((value >> 5) & 63) as i32;
+    let b = (value & 31) as i32;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+}
+
+// Always uses the four colors mode, so that it can be used also for the color part of BC3
+pub fn encode_bc1(block: &PixelsBlock) -> [u8; 8] {
+    let colors = block.map(|p| [p[0] as f32, p[1] as f32, p[2] as f32]);
+    let (start, end) = principal_endpoints(&colors);
+    let (mut c0, mut c1) = (to_565(&end), to_565(&start));
+    if c0 < c1 {
+        std::mem::swap(&mut c0, &mut c1);
+    }
+    let mut indices = 0u32;
+    if c0 != c1 {
+        let (p0, p1) = (from_565(c0), from_565(c1));
+        let mut palette = [p0, p1, [0; 3], [0; 3]];
+        (0..3).for_each(|c| {
+            palette[2][c] = (2 * p0[c] + p1[c]) / 3;
+            palette[3][c] = (p0[c] + 2 * p1[c]) / 3;
+        });
+        block
+            .iter()
+            .enumerate()
+            .for_each(|(i, p)| indices |= closest_index(&palette, p) << (2 * i));
+    }
+    let mut result = [0u8; 8];
+    result[0..2].copy_from_slice(&c0.to_le_bytes());
+    result[2..4].copy_from_slice(&c1.to_le_bytes());
+    result[4..8].copy_from_slice(&indices.to_le_bytes());
+    result
+}
+
+pub fn encode_bc4(values: &[u8; 16]) -> [u8; 8] {
+    let max = *values.iter().max().unwrap();
+    let min = *values.iter().min().unwrap();
+    let mut indices = 0u64;
+    if max > min {
+        let (max, min) = (max as i32, min as i32);
+        let mut palette = [[max], [min], [0], [0], [0], [0], [0], [0]];
+        (1..7).for_each(|i| palette[i + 1][0] = ((7 - i as i32) * max + i as i32 * min) / 7);
+        values
+            .iter()
+            .enumerate()
+            .for_each(|(i, v)| indices |= (closest_index(&palette, &[*v]) as u64) << (3 * i));
+    }
+    let mut result = [0u8; 8];
+    result[0] = max;
+    result[1] = min;
+    result[2..8].copy_from_slice(&indices.to_le_bytes()[0..6]);
+    result
+}
+
+pub fn encode_bc3(block: &PixelsBlock) -> [u8; 16] {
+    let mut result = [0u8; 16];
+    result[0..8].copy_from_slice(&encode_bc4(&block.map(|p| p[3])));
+    result[8..16].copy_from_slice(&encode_bc1(block));
+    result
+}
+
+pub fn encode_bc5(block: &PixelsBlock) -> [u8; 16] {
+    let mut result = [0u8; 16];
+    result[0..8].copy_from_slice(&encode_bc4(&block.map(|p| p[0])));
+    result[8..16].copy_from_slice(&encode_bc4(&block.map(|p| p[1])));
+    result
+}
+
+// Endpoints have 7 bits for each channel and a shared lowest bit
+fn quantize_bc7_endpoint(endpoint: &[f32; 4]) -> ([u32; 4], u32) {
+    let mut best = ([0u32; 4], 0u32, f32::MAX);
+    (0..2).for_each(|p| {
+        let mut quantized = [0u32; 4];
+        let mut error = 0.;
+        (0..4).for_each(|c| {
+            quantized[c] = ((endpoint[c] - p as f32) / 2.).round().clamp(0., 127.) as u32;
+            let decoded = ((quantized[c] << 1) | p) as f32;
+            error += (decoded - endpoint[c]) * (decoded - endpoint[c]);
+        });
+        if error < best.2 {
+            best = (quantized, p, error);
+        }
+    });
+    (best.0, best.1)
+}
+
+// Mode 6: a single subset with rgba endpoints and 4 bits indices
+pub fn encode_bc7(block: &PixelsBlock) -> [u8; 16] {
+    let values = block.map(|p| p.map(|v| v as f32));
+    let (start, end) = principal_endpoints(&values);
+    let (mut q0, mut p0) = quantize_bc7_endpoint(&start);
+    let (mut q1, mut p1) = quantize_bc7_endpoint(&end);
+    let mut palette = [[0i32; 4]; 16];
+    palette.iter_mut().enumerate().for_each(|(i, color)| {
+        (0..4).for_each(|c| {
+            let e0 = ((q0[c] << 1) | p0) as i32;
+            let e1 = ((q1[c] << 1) | p1) as i32;
+            color[c] = ((64 - BC7_WEIGHTS[i]) * e0 + BC7_WEIGHTS[i] * e1 + 32) >> 6;
+        })
+    });
+    let mut indices = block.map(|p| closest_index(&palette, &p));
+    // The highest bit of the first index is implicit and has to be zero
+    if indices[0] >= 8 {
+        std::mem::swap(&mut q0, &mut q1);
+        std::mem::swap(&mut p0, &mut p1);
+        indices.iter_mut().for_each(|i| *i = 15 - *i);
+    }
+    let mut writer = BitWriter::default();
+    writer.write(1 << 6, 7);
+    (0..4).for_each(|c| {
+        writer.write(q0[c], 7);
+        writer.write(q1[c], 7);
+    });
+    writer.write(p0, 1);
+    writer.write(p1, 1);
+    writer.write(indices[0], 3);
+    indices[1..].iter().for_each(|i| writer.write(*i, 4));
+    writer.bits.to_le_bytes()
+}
+
+// Single partition with 8 bits endpoints and 2 bits weights: no integer sequence encoding is needed
+pub fn encode_astc_4x4(block: &PixelsBlock) -> [u8; 16] {
+    let values = block.map(|p| p.map(|v| v as f32));
+    let (start, end) = principal_endpoints(&values);
+    let mut e0 = start.map(|v| v.round() as u32);
+    let mut e1 = end.map(|v| v.round() as u32);
+    // Decoder applies blue contraction when the second endpoint is the darkest one
+    if e1[0] + e1[1] + e1[2] < e0[0] + e0[1] + e0[2] {
+        std::mem::swap(&mut e0, &mut e1);
+    }
+    let mut palette = [[0i32; 4]; 4];
+    palette.iter_mut().enumerate().for_each(|(i, color)| {
+        (0..4).for_each(|c| {
+            let c0 = (e0[c] * 257) as i32;
+            let c1 = (e1[c] * 257) as i32;
+            color[c] = (((64 - ASTC_WEIGHTS[i]) * c0 + ASTC_WEIGHTS[i] * c1 + 32) >> 6) >> 8;
+        })
+    });
+    let mut writer = BitWriter::default();
+    writer.write(ASTC_BLOCK_MODE, 11);
+    writer.write(0, 2);
+    writer.write(ASTC_COLOR_ENDPOINT_MODE, 4);
+    (0..4).for_each(|c| {
+        writer.write(e0[c], 8);
+        writer.write(e1[c], 8);
+    });
+    // Weights are stored in reverse order starting from the top of the block
+    let mut bits = writer.bits;
+    block.iter().enumerate().for_each(|(i, p)| {
+        let weight = closest_index(&palette, p) as u128;
+        bits |= (weight & 1) << (127 - 2 * i);
+        bits |= ((weight >> 1) & 1) << (126 - 2 * i);
+    });
+    bits.to_le_bytes()
+}
@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use crate::{copy_into_data_folder, ExtensionHandler};
+use crate::{abort_asset, begin_asset, copy_into_data_folder, end_asset, ExtensionHandler};
 use inox_log::debug_log;
 use inox_messenger::MessageHubRc;
 use inox_nodes::NodeTree;
@@ -10,6 +10,8 @@ use inox_scene::{Camera, Object, Scene, Script};
 use inox_serialize::SerializeFile;
 
 const CONFIG_EXTENSION: &str = "cfg";
+const COMPILER_NAME: &str = "copy";
+const COMPILER_VERSION: u32 = 1;
 
 pub struct CopyCompiler {
     message_hub: MessageHubRc,
@@ -28,7 +30,7 @@ impl CopyCompiler {
 }
 
 impl ExtensionHandler for CopyCompiler {
-    fn on_changed(&mut self, path: &Path) {
+    fn on_changed(&self, path: &Path) {
         if let Some(ext) = path.extension() {
             let ext = ext.to_str().unwrap().to_string();
             if (ext.as_str() == CONFIG_EXTENSION
@@ -42,14 +44,36 @@ impl ExtensionHandler for CopyCompiler {
                 || ext.as_str() == Camera::extension()
                 || ext.as_str() == Light::extension()
                 || ext.as_str() == Script::extension())
-                && copy_into_data_folder(
-                    &self.message_hub,
+                && begin_asset(
                     path,
+                    COMPILER_NAME,
+                    COMPILER_VERSION,
                     self.data_raw_folder.as_path(),
                     self.data_folder.as_path(),
                 )
             {
-                debug_log!("Serializing {:?}", path);
+                if copy_into_data_folder(
+                    &self.message_hub,
+                    path,
+                    COMPILER_NAME,
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                ) {
+                    debug_log!("Serializing {:?}", path);
+                    end_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                } else {
+                    abort_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                }
             }
         }
     }
@@ -0,0 +1,144 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    abort_asset, add_asset_texture_stats, begin_asset, compute_compiled_path, create_compiled_file,
+    end_asset,
+    environment::{compile_environment, compute_brdf_luts, LUT_SIZE},
+    need_to_binarize, report_asset_diagnostic,
+    texture::compute_texture_stats,
+    to_local_path, ExtensionHandler,
+};
+use inox_log::debug_log;
+use inox_render::EnvironmentData;
+use inox_resources::DiagnosticSeverity;
+use inox_serialize::{SerializationType, Serialize, SerializeFile};
+
+const ENVIRONMENT_HDR_EXTENSION: &str = "hdr";
+const ENVIRONMENT_EXR_EXTENSION: &str = "exr";
+const COMPILER_NAME: &str = "environment";
+const COMPILER_VERSION: u32 = 1;
+
+// Equirectangular hdr images become a prefiltered environment with its irradiance and BRDF LUTs
+pub struct EnvironmentCompiler {
+    data_raw_folder: PathBuf,
+    data_folder: PathBuf,
+}
+
+impl EnvironmentCompiler {
+    pub fn new(data_raw_folder: &Path, data_folder: &Path) -> Self {
+        Self {
+            data_raw_folder: data_raw_folder.to_path_buf(),
+            data_folder: data_folder.to_path_buf(),
+        }
+    }
+
+    pub fn compile_environment(&self, path: &Path) -> bool {
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let environment_path = compute_compiled_path(
+            path,
+            name,
+            EnvironmentData::extension(),
+            "",
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        );
+        if !need_to_binarize(path, environment_path.as_path()) {
+            return false;
+        }
+        let image = match image::open(path) {
+            Ok(image) => image.into_rgb32f(),
+            Err(e) => {
+                report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Error,
+                    "",
+                    &format!("Unable to read environment: {e}"),
+                );
+                return false;
+            }
+        };
+        let (specular_map, irradiance) = compile_environment(&image);
+        let (lut_pbr_ggx, lut_pbr_charlie) = compute_brdf_luts(LUT_SIZE);
+        let specular_name = format!("{name}_specular");
+        let ggx_name = format!("{name}_ggx_LUT");
+        let charlie_name = format!("{name}_charlie_LUT");
+        [
+            (&specular_name, &specular_map),
+            (&ggx_name, &lut_pbr_ggx),
+            (&charlie_name, &lut_pbr_charlie),
+        ]
+        .into_iter()
+        .for_each(|(name, texture_data)| {
+            add_asset_texture_stats(
+                path,
+                COMPILER_NAME,
+                compute_texture_stats(name, texture_data),
+            )
+        });
+        let environment_data = EnvironmentData {
+            specular_map: self.create_file(path, &specular_map, &specular_name),
+            lut_pbr_ggx: self.create_file(path, &lut_pbr_ggx, &ggx_name),
+            lut_pbr_charlie: self.create_file(path, &lut_pbr_charlie, &charlie_name),
+            irradiance,
+        };
+        self.create_file(path, &environment_data, name);
+        true
+    }
+
+    // Returns the local path of the created file
+    fn create_file<T>(&self, path: &Path, data: &T, new_name: &str) -> PathBuf
+    where
+        T: Serialize + SerializeFile + Clone + 'static,
+    {
+        let new_path = create_compiled_file(
+            path,
+            COMPILER_NAME,
+            data,
+            new_name,
+            "",
+            SerializationType::Binary,
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        );
+        to_local_path(
+            new_path.as_path(),
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        )
+    }
+}
+
+impl ExtensionHandler for EnvironmentCompiler {
+    fn on_changed(&self, path: &Path) {
+        if let Some(ext) = path.extension() {
+            let extension = ext.to_str().unwrap();
+            if (extension == ENVIRONMENT_HDR_EXTENSION || extension == ENVIRONMENT_EXR_EXTENSION)
+                && begin_asset(
+                    path,
+                    COMPILER_NAME,
+                    COMPILER_VERSION,
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                )
+            {
+                if self.compile_environment(path) {
+                    debug_log!("Serializing {:?}", path);
+                    end_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                } else {
+                    abort_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,15 +1,25 @@
 use std::path::{Path, PathBuf};
 
-use crate::{copy_into_data_folder, ExtensionHandler};
+use crate::{
+    abort_asset, add_asset_texture_stats, begin_asset, compute_compiled_path, create_compiled_file,
+    end_asset, need_to_binarize, report_asset_diagnostic, send_reloaded_event, ExtensionHandler,
+};
 use inox_log::debug_log;
 use inox_messenger::MessageHubRc;
+use inox_render::{FontData, FontOptions};
+use inox_resources::{DiagnosticSeverity, TextureStats};
+use inox_serialize::{SerializationType, SerializeFile};
 
 const FONT_EXTENSION: &str = "ttf";
+const COMPILER_NAME: &str = "font";
+const COMPILER_VERSION: u32 = 2;
 
+// Glyphs of the configured unicode ranges are rendered in a distance field atlas
 pub struct FontCompiler {
     message_hub: MessageHubRc,
     data_raw_folder: PathBuf,
     data_folder: PathBuf,
+    font_options: FontOptions,
 }
 
 impl FontCompiler {
@@ -18,22 +28,91 @@ impl FontCompiler {
             message_hub,
             data_raw_folder: data_raw_folder.to_path_buf(),
             data_folder: data_folder.to_path_buf(),
+            font_options: FontOptions::default(),
         }
     }
+
+    pub fn set_font_options(&mut self, font_options: &FontOptions) -> &mut Self {
+        self.font_options = font_options.clone();
+        self
+    }
+
+    pub fn compile_font(&self, path: &Path) -> bool {
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let font_path = compute_compiled_path(
+            path,
+            name,
+            FontData::extension(),
+            "",
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        );
+        if !need_to_binarize(path, font_path.as_path()) {
+            return false;
+        }
+        let font_data = match FontData::new(path, &self.font_options) {
+            Ok(font_data) => font_data,
+            Err(e) => {
+                report_asset_diagnostic(path, COMPILER_NAME, DiagnosticSeverity::Error, "", &e);
+                return false;
+            }
+        };
+        let (width, height) = font_data.atlas_size();
+        add_asset_texture_stats(
+            path,
+            COMPILER_NAME,
+            TextureStats {
+                name: name.to_string(),
+                width,
+                height,
+                mips: 1,
+                format: format!("{:?}", font_data.atlas_type()),
+                bytes: font_data.atlas().len() as _,
+            },
+        );
+        create_compiled_file(
+            path,
+            COMPILER_NAME,
+            &font_data,
+            name,
+            "",
+            SerializationType::Binary,
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        );
+        send_reloaded_event(&self.message_hub, font_path.as_path());
+        true
+    }
 }
 
 impl ExtensionHandler for FontCompiler {
-    fn on_changed(&mut self, path: &Path) {
+    fn on_changed(&self, path: &Path) {
         if let Some(ext) = path.extension() {
             if ext.to_str().unwrap().to_string().as_str() == FONT_EXTENSION
-                && copy_into_data_folder(
-                    &self.message_hub,
+                && begin_asset(
                     path,
+                    COMPILER_NAME,
+                    COMPILER_VERSION,
                     self.data_raw_folder.as_path(),
                     self.data_folder.as_path(),
                 )
             {
-                debug_log!("Serializing {:?}", path);
+                if self.compile_font(path) {
+                    debug_log!("Serializing {:?}", path);
+                    end_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                } else {
+                    abort_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                }
             }
         }
     }
@@ -1,40 +1,91 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs::{self, create_dir_all, File},
-    io::{Seek, SeekFrom},
+    io::{Cursor, Read, Seek, SeekFrom},
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 
 use crate::{
-    adjacency::{build_meshlets_adjacency, group_meshlets_with_metis},
-    mesh::{compute_clusters, compute_meshlets, create_mesh_data, optimize_mesh, MeshVertex},
-    need_to_binarize, to_local_path, ExtensionHandler,
+    abort_asset, add_asset_dependency, add_asset_mesh_stats, add_asset_texture_stats, begin_asset,
+    compute_compiled_path, create_compiled_file, decode_data_uri, end_asset, is_data_uri,
+    lightmap::{
+        bake_lightmap, compute_lightmap_size, encode_lightmap, generate_lightmap_uvs,
+        uv_set_layout, LightmapOptions, LightmapScene, LightmapTriangle,
+    },
+    mesh::{
+        compute_mesh_stats, compute_meshlets_bvh, compute_meshlets_lods, create_mesh_data,
+        expand_meshlets_with_morph_targets, optimize_mesh_with_remap, MeshOptions, MeshVertex,
+    },
+    need_to_binarize, report_asset_diagnostic,
+    texture::compile_material_texture,
+    to_local_path, track_asset_output, ExtensionHandler,
 };
 use gltf::{
-    accessor::{DataType, Dimensions},
+    accessor::{sparse::IndexType, DataType, Dimensions},
+    animation::{Interpolation, Property},
     buffer::{Source, View},
     camera::Projection,
-    image::Source as ImageSource,
+    image::{Image, Source as ImageSource},
+    json::Value,
     khr_lights_punctual::{Kind, Light},
     material::AlphaMode,
     mesh::Mode,
-    Accessor, Camera, Gltf, Node, Primitive, Semantic, Texture,
+    texture::TextureTransform,
+    Accessor, Camera, Document, Gltf, Node, Primitive, Semantic, Texture,
 };
 
-use inox_bvh::{create_linearized_bvh, BVHTree, AABB};
-use inox_log::debug_log;
-use inox_math::{Mat4Ops, Matrix4, NewAngle, Parser, Radians, Vector2, Vector3, Vector4, Vector4h};
+use image::RgbaImage;
+use inox_math::{
+    Mat4Ops, MatBase, Matrix4, NewAngle, Parser, Radians, VecBase, VecBaseFloat, Vector2, Vector3,
+    Vector4, Vector4b, Vector4h,
+};
 use inox_render::{
-    GPULight, LightType, MaterialData, MaterialFlags, MeshData, TextureType, VertexAttributeLayout,
-    MAX_LOD_LEVELS, MAX_TEXTURE_COORDS_SETS,
+    GPULight, LightType, MaterialData, MaterialFlags, MeshData, MeshMorphData, TextureType,
+    VertexAttributeLayout, MAX_TEXTURE_COORDS_SETS,
 };
 
 use inox_nodes::LogicData;
-use inox_scene::{CameraData, ObjectData, SceneData};
-use inox_serialize::{deserialize, Deserialize, SerializationType, Serialize, SerializeFile};
+use inox_platform::PlatformType;
+use inox_resources::{DiagnosticSeverity, TextureStats};
+use inox_scene::{
+    AnimationChannelData, AnimationData, AnimationInterpolation, AnimationProperty, CameraData,
+    ObjectData, SceneData, SkinData,
+};
+use inox_serialize::{
+    deserialize, deserialize_from_text, Deserialize, SerializationType, Serialize, SerializeFile,
+};
 use mikktspace::{generate_tangents, Geometry};
 
 const GLTF_EXTENSION: &str = "gltf";
+const GLB_EXTENSION: &str = "glb";
+const COMPILER_NAME: &str = "gltf";
+const COMPILER_VERSION: u32 = 6;
+// Folder of the chunks written by the exporter, next to their index file
+pub const GLTF_CHUNKS_FOLDER: &str = "chunks";
+
+// Chunk of an index, with the chunk of the object it is a child of
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "inox_serialize", default)]
+pub struct GltfChunkData {
+    pub path: PathBuf,
+    pub parent: Option<PathBuf>,
+}
+
+// Scene exported as a set of glTF chunks (one for each object, in world space).
+// A glTF listed by an index is compiled as a chunk, that produces only its objects.
+// Chunks paths are relative to the index file folder
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct GltfIndexData {
+    pub chunks: Vec<GltfChunkData>,
+}
+
+impl SerializeFile for GltfIndexData {
+    fn extension() -> &'static str {
+        "gltf_index"
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(crate = "inox_serialize")]
@@ -97,37 +148,113 @@ impl Geometry for GltfGeometry {
     }
 }
 
+// Lightmap of a primitive, in a variant of its material
+struct PrimitiveLightmap {
+    uv_set: usize,
+    size: u32,
+    material_data: MaterialData,
+}
+
+// Static primitive in local space, kept to bake lightmaps once every mesh is done
+struct LightmapGeometry {
+    vertices: Vec<MeshVertex>,
+    indices: Vec<u32>,
+    albedo: Vector3,
+    emissive: Vector3,
+    // Primitives without it are only occluders
+    lightmap: Option<PrimitiveLightmap>,
+}
+
+// Static nodes of the scenes, gathered before mesh jobs to know which primitives get a lightmap
 #[derive(Default)]
-pub struct GltfCompiler {
+struct LightmapInstances {
+    // World transforms by primitive name
+    transforms: HashMap<String, Vec<Matrix4>>,
+    // Primitives of nodes moved by animations, their lighting can't be baked
+    animated: HashSet<String>,
+    lights: Vec<GPULight>,
+}
+
+#[derive(Default, Clone)]
+pub struct GltfCompiler<const PLATFORM_TYPE: PlatformType> {
     data_raw_folder: PathBuf,
     data_folder: PathBuf,
-    node_index: usize,
-    material_index: usize,
+    jobs: BinarizerJobs,
+    mesh_options: MeshOptions,
     meshes_paths: HashMap<String, PathBuf>,
+    animations_paths: HashMap<usize, Vec<PathBuf>>,
+    // Objects local paths by node index, used to reference skin joints
+    nodes_paths: HashMap<usize, PathBuf>,
+    // Buffers not stored in a file: the .glb binary chunk and data uris, by buffer index
+    buffers: Arc<HashMap<usize, Vec<u8>>>,
+    lightmap_options: LightmapOptions,
+    // Filled by mesh jobs with primitives names as keys
+    lightmap_geometries: Arc<Mutex<HashMap<String, LightmapGeometry>>>,
+    lightmap_instances: Arc<LightmapInstances>,
+    // Set only while processing a root node of a chunk
+    chunk_hierarchy: Option<ChunkHierarchy>,
+    // Compiled textures by image path and texture type
+    compiled_textures: HashMap<(PathBuf, usize), PathBuf>,
 }
 
-impl GltfCompiler {
+// Parent objects of chunks are in other chunks
+#[derive(Clone)]
+struct ChunkHierarchy {
+    // Inverse world transform of the parent chunk, identity for chunks without parent
+    parent_inverse: Matrix4,
+    // Objects of the chunks that have this one as parent
+    children: Vec<PathBuf>,
+}
+
+impl<const PLATFORM_TYPE: PlatformType> GltfCompiler<PLATFORM_TYPE> {
     pub fn new(data_raw_folder: &Path, data_folder: &Path) -> Self {
         Self {
             data_raw_folder: data_raw_folder.to_path_buf(),
             data_folder: data_folder.to_path_buf(),
-            node_index: 0,
-            material_index: 0,
+            jobs: BinarizerJobs::default(),
+            mesh_options: MeshOptions::default(),
             meshes_paths: HashMap::default(),
+            animations_paths: HashMap::default(),
+            nodes_paths: HashMap::default(),
+            buffers: Arc::default(),
+            lightmap_options: LightmapOptions::default(),
+            lightmap_geometries: Arc::default(),
+            lightmap_instances: Arc::default(),
+            chunk_hierarchy: None,
+            compiled_textures: HashMap::default(),
         }
     }
 
-    fn num_from_type(&mut self, accessor: &Accessor) -> usize {
-        match accessor.dimensions() {
-            Dimensions::Vec2 => 2,
-            Dimensions::Vec3 => 3,
-            Dimensions::Vec4 => 4,
-            Dimensions::Mat2 => 4,
-            Dimensions::Mat3 => 9,
-            Dimensions::Mat4 => 16,
-            _ => 1,
+    // Mesh primitives are processed as jobs when set
+    pub fn set_jobs(&mut self, jobs: &BinarizerJobs) -> &mut Self {
+        self.jobs = jobs.clone();
+        self
+    }
+
+    pub fn set_mesh_options(&mut self, mesh_options: &MeshOptions) -> &mut Self {
+        self.mesh_options = mesh_options.clone();
+        self
+    }
+
+    pub fn set_lightmap_options(&mut self, lightmap_options: &LightmapOptions) -> &mut Self {
+        self.lightmap_options = lightmap_options.clone();
+        self
+    }
+
+    // Copy of the compiler that shares only what is needed to process a mesh
+    fn mesh_compiler(&self) -> Self {
+        Self {
+            data_raw_folder: self.data_raw_folder.clone(),
+            data_folder: self.data_folder.clone(),
+            mesh_options: self.mesh_options.clone(),
+            buffers: self.buffers.clone(),
+            lightmap_options: self.lightmap_options.clone(),
+            lightmap_geometries: self.lightmap_geometries.clone(),
+            lightmap_instances: self.lightmap_instances.clone(),
+            ..Default::default()
         }
     }
+
     fn bytes_from_dimension(&mut self, accessor: &Accessor) -> usize {
         match accessor.data_type() {
             DataType::F32 | DataType::U32 => 4,
@@ -140,37 +267,100 @@ impl GltfCompiler {
     where
         T: Parser,
     {
-        let view = if let Some(sparse) = accessor.sparse() {
-            Some(sparse.values().view())
-        } else {
-            accessor.view()
+        let mut result = match accessor.view() {
+            Some(view) => {
+                self.read_view_values::<T>(path, &view, accessor.offset(), accessor.count())?
+            }
+            // Sparse accessors without a view are initialized with zeros
+            None => (0..accessor.count())
+                .map(|_| T::parse(&mut std::io::repeat(0)))
+                .collect(),
         };
-        if let Some(view) = view {
-            if let Some(parent_folder) = path.parent() {
-                match view.buffer().source() {
-                    Source::Uri(local_path) => {
-                        let filepath = parent_folder.to_path_buf().join(local_path);
-                        if let Ok(mut file) = fs::File::open(filepath) {
-                            return Some(self.read_from_file::<T>(&mut file, &view, accessor));
-                        } else {
-                            eprintln!("Unable to open file: {local_path}");
-                        }
+        if let Some(sparse) = accessor.sparse() {
+            let indices = sparse.indices();
+            let view = indices.view();
+            let (offset, count) = (indices.offset(), sparse.count());
+            let indices: Vec<usize> = match indices.index_type() {
+                IndexType::U8 => self
+                    .read_view_values::<u8>(path, &view, offset, count)?
+                    .into_iter()
+                    .map(|i| i as usize)
+                    .collect(),
+                IndexType::U16 => self
+                    .read_view_values::<u16>(path, &view, offset, count)?
+                    .into_iter()
+                    .map(|i| i as usize)
+                    .collect(),
+                IndexType::U32 => self
+                    .read_view_values::<u32>(path, &view, offset, count)?
+                    .into_iter()
+                    .map(|i| i as usize)
+                    .collect(),
+            };
+            let values = sparse.values();
+            let values =
+                self.read_view_values::<T>(path, &values.view(), values.offset(), count)?;
+            indices.into_iter().zip(values).for_each(|(i, v)| {
+                if let Some(r) = result.get_mut(i) {
+                    *r = v;
+                }
+            });
+        }
+        Some(result)
+    }
+
+    fn read_view_values<T>(
+        &mut self,
+        path: &Path,
+        view: &View,
+        offset: usize,
+        count: usize,
+    ) -> Option<Vec<T>>
+    where
+        T: Parser,
+    {
+        if let Some(data) = self.buffers.get(&view.buffer().index()) {
+            let mut reader = Cursor::new(data.as_slice());
+            return Some(self.read_from_file::<T, _>(&mut reader, view, offset, count));
+        }
+        if let Some(parent_folder) = path.parent() {
+            match view.buffer().source() {
+                Source::Uri(local_path) => {
+                    let filepath = parent_folder.to_path_buf().join(local_path);
+                    add_asset_dependency(path, COMPILER_NAME, filepath.as_path());
+                    if let Ok(mut file) = fs::File::open(filepath) {
+                        return Some(self.read_from_file::<T, _>(&mut file, view, offset, count));
+                    } else {
+                        report_asset_diagnostic(
+                            path,
+                            COMPILER_NAME,
+                            DiagnosticSeverity::Error,
+                            &format!("Buffer {}", view.buffer().index()),
+                            &format!("Unable to open file {local_path}"),
+                        );
                     }
-                    Source::Bin => {}
+                }
+                Source::Bin => {
+                    report_asset_diagnostic(
+                        path,
+                        COMPILER_NAME,
+                        DiagnosticSeverity::Error,
+                        &format!("Buffer {}", view.buffer().index()),
+                        "Missing binary chunk",
+                    );
                 }
             }
         }
         None
     }
 
-    fn read_from_file<T>(&mut self, file: &mut File, view: &View, accessor: &Accessor) -> Vec<T>
+    fn read_from_file<T, R>(&self, file: &mut R, view: &View, offset: usize, count: usize) -> Vec<T>
     where
         T: Parser,
+        R: Read + Seek,
     {
-        let count = accessor.count();
         let view_offset = view.offset();
-        let accessor_offset = accessor.offset();
-        let starting_offset = view_offset + accessor_offset;
+        let starting_offset = view_offset + offset;
         let view_stride = view.stride().unwrap_or(0);
         let type_stride = T::size();
         let stride = view_stride.saturating_sub(type_stride);
@@ -184,13 +374,100 @@ impl GltfCompiler {
         result
     }
 
-    fn extract_indices(&mut self, path: &Path, primitive: &Primitive) -> Vec<u32> {
-        debug_assert!(primitive.mode() == Mode::Triangles);
+    fn read_view_from_path(&self, path: &Path, view: &View) -> Option<Vec<u8>> {
+        let range = view.offset()..view.offset() + view.length();
+        if let Some(data) = self.buffers.get(&view.buffer().index()) {
+            return data.get(range).map(|b| b.to_vec());
+        }
+        match view.buffer().source() {
+            Source::Uri(local_path) => {
+                let filepath = path.parent()?.join(local_path);
+                add_asset_dependency(path, COMPILER_NAME, filepath.as_path());
+                match fs::read(filepath) {
+                    Ok(bytes) => bytes.get(range).map(|b| b.to_vec()),
+                    Err(e) => {
+                        report_asset_diagnostic(
+                            path,
+                            COMPILER_NAME,
+                            DiagnosticSeverity::Error,
+                            &format!("Buffer {}", view.buffer().index()),
+                            &format!("Unable to open file {local_path}: {e}"),
+                        );
+                        None
+                    }
+                }
+            }
+            Source::Bin => None,
+        }
+    }
+
+    fn load_buffers(&mut self, path: &Path, gltf: &mut Gltf) {
+        let mut buffers = HashMap::new();
+        let mut blob = gltf.blob.take();
+        for buffer in gltf.buffers() {
+            match buffer.source() {
+                Source::Bin => {
+                    if let Some(blob) = blob.take() {
+                        buffers.insert(buffer.index(), blob);
+                    }
+                }
+                Source::Uri(uri) if is_data_uri(uri) => match decode_data_uri(uri) {
+                    Some((_, data)) => {
+                        buffers.insert(buffer.index(), data);
+                    }
+                    None => report_asset_diagnostic(
+                        path,
+                        COMPILER_NAME,
+                        DiagnosticSeverity::Error,
+                        &format!("Buffer {}", buffer.index()),
+                        "Unable to decode data uri",
+                    ),
+                },
+                Source::Uri(_) => {}
+            }
+        }
+        self.buffers = Arc::new(buffers);
+    }
+
+    // Accessors of types not expected for their semantic are reported and skipped
+    fn is_accessor_supported(
+        path: &Path,
+        location: &str,
+        accessor: &Accessor,
+        dimensions: &[Dimensions],
+        data_types: &[DataType],
+    ) -> bool {
+        let is_supported = dimensions.contains(&accessor.dimensions())
+            && data_types.contains(&accessor.data_type());
+        if !is_supported {
+            report_asset_diagnostic(
+                path,
+                COMPILER_NAME,
+                DiagnosticSeverity::Warning,
+                location,
+                &format!(
+                    "Unsupported accessor {} of type {:?} {:?}: skipped",
+                    accessor.index(),
+                    accessor.dimensions(),
+                    accessor.data_type()
+                ),
+            );
+        }
+        is_supported
+    }
+
+    fn extract_indices(&mut self, path: &Path, mesh_name: &str, primitive: &Primitive) -> Vec<u32> {
         let mut indices = Vec::new();
-        if let Some(accessor) = primitive.indices() {
-            let num = self.num_from_type(&accessor);
+        if let Some(accessor) = primitive.indices().filter(|accessor| {
+            Self::is_accessor_supported(
+                path,
+                &format!("{mesh_name} indices"),
+                accessor,
+                &[Dimensions::Scalar],
+                &[DataType::U8, DataType::U16, DataType::U32],
+            )
+        }) {
             let num_bytes = self.bytes_from_dimension(&accessor);
-            debug_assert!(num == 1);
             if num_bytes == 1 {
                 if let Some(ind) = self.read_accessor_from_path::<u8>(path, &accessor) {
                     indices = ind.iter().map(|e| *e as u32).collect();
@@ -209,6 +486,7 @@ impl GltfCompiler {
     fn extract_vertices(
         &mut self,
         path: &Path,
+        mesh_name: &str,
         primitive: &Primitive,
     ) -> (VertexAttributeLayout, Vec<MeshVertex>) {
         let mut vertex_layout = VertexAttributeLayout::default();
@@ -216,11 +494,25 @@ impl GltfCompiler {
 
         primitive.attributes().for_each(| (semantic, accessor)| {
             //debug_log!("Attribute[{}]: {:?}", _attribute_index, semantic);
+            let location = format!("{mesh_name} {semantic:?}");
+            let (dimensions, data_types): (&[Dimensions], &[DataType]) = match semantic {
+                Semantic::Positions | Semantic::Normals => (&[Dimensions::Vec3], &[DataType::F32]),
+                Semantic::Tangents | Semantic::Colors(_) => {
+                    (&[Dimensions::Vec4], &[DataType::F32, DataType::U16])
+                }
+                Semantic::TexCoords(_) => (&[Dimensions::Vec2], &[DataType::F32]),
+                Semantic::Joints(_) => (&[Dimensions::Vec4], &[DataType::U8, DataType::U16]),
+                Semantic::Weights(_) => (
+                    &[Dimensions::Vec4],
+                    &[DataType::F32, DataType::U8, DataType::U16],
+                ),
+                _ => return,
+            };
+            if !Self::is_accessor_supported(path, &location, &accessor, dimensions, data_types) {
+                return;
+            }
             match semantic {
                 Semantic::Positions => {
-                    let num = self.num_from_type(&accessor);
-                    let num_bytes = self.bytes_from_dimension(&accessor);
-                    debug_assert!(num == 3 && num_bytes == 4);
                     if let Some(pos) = self.read_accessor_from_path::<Vector3>(path, &accessor) {
                         vertex_layout |= VertexAttributeLayout::HasPosition;
                         if vertices.is_empty() {
@@ -232,9 +524,6 @@ impl GltfCompiler {
                     }
                 }
                 Semantic::Normals => {
-                    let num = self.num_from_type(&accessor);
-                    let num_bytes = self.bytes_from_dimension(&accessor);
-                    debug_assert!(num == 3 && num_bytes == 4);
                     if let Some(norm) = self.read_accessor_from_path::<Vector3>(path, &accessor) {
                         vertex_layout |= VertexAttributeLayout::HasNormal;
                         if vertices.is_empty() {
@@ -246,11 +535,8 @@ impl GltfCompiler {
                     }
                 }
                 Semantic::Tangents => {
-                    let num = self.num_from_type(&accessor);
                     let num_bytes = self.bytes_from_dimension(&accessor);
-                    debug_assert!(num == 4);
                     if num_bytes == 2 {
-                        debug_assert!(num_bytes == 2);
                         if let Some(tan) = self.read_accessor_from_path::<Vector4h>(path, &accessor)
                         {
                             vertex_layout |= VertexAttributeLayout::HasTangent;
@@ -262,23 +548,18 @@ impl GltfCompiler {
                                     [v.x as f32, v.y as f32, v.z as f32, v.z as f32].into();
                             });
                         }
-                    } else {
-                        debug_assert!(num_bytes == 4);
-                        if let Some(tan) = self.read_accessor_from_path::<Vector4>(path, &accessor)
-                        {
-                            vertex_layout |= VertexAttributeLayout::HasTangent;
-                            tan.iter().enumerate().for_each(|(i, v)| {
-                                vertices[i].tangent = *v;
-                            });
-                        }
+                    } else if let Some(tan) =
+                        self.read_accessor_from_path::<Vector4>(path, &accessor)
+                    {
+                        vertex_layout |= VertexAttributeLayout::HasTangent;
+                        tan.iter().enumerate().for_each(|(i, v)| {
+                            vertices[i].tangent = *v;
+                        });
                     }
                 }
                 Semantic::Colors(_color_index) => {
-                    let num = self.num_from_type(&accessor);
                     let num_bytes = self.bytes_from_dimension(&accessor);
-                    debug_assert!(num == 4);
                     if num_bytes == 2 {
-                        debug_assert!(num_bytes == 2);
                         if let Some(col) = self.read_accessor_from_path::<Vector4h>(path, &accessor)
                         {
                             vertex_layout |= VertexAttributeLayout::HasColor;
@@ -290,20 +571,16 @@ impl GltfCompiler {
                                     [v.x as f32, v.y as f32, v.z as f32, v.z as f32].into();
                             });
                         }
-                    } else {
-                        debug_assert!(num_bytes == 4);
-                        if let Some(col) = self.read_accessor_from_path::<Vector4>(path, &accessor)
-                        {
-                            vertex_layout |= VertexAttributeLayout::HasColor;
-                            col.iter().enumerate().for_each(|(i, v)| {
-                                vertices[i].color = *v;
-                            });
-                        }
+                    } else if let Some(col) =
+                        self.read_accessor_from_path::<Vector4>(path, &accessor)
+                    {
+                        vertex_layout |= VertexAttributeLayout::HasColor;
+                        col.iter().enumerate().for_each(|(i, v)| {
+                            vertices[i].color = *v;
+                        });
                     }
                 }
                 Semantic::TexCoords(texture_index) => {
-                    let num = self.num_from_type(&accessor);
-                    let num_bytes = self.bytes_from_dimension(&accessor);
                     let max = if let Some(max) = accessor.max() {
                         max.as_array().unwrap().iter().map(|v| v.as_f64().unwrap() as f32).collect()
                     } else {
@@ -316,7 +593,6 @@ impl GltfCompiler {
                     };
                     let min: Vector2 = Vector2::new(min[0], min[1]);
                     let max: Vector2 = Vector2::new(max[0], max[1]);
-                    debug_assert!(num == 2 && num_bytes == 4);
                     if let Some(tex) = self.read_accessor_from_path::<Vector2>(path, &accessor) {
                         if vertices.is_empty() {
                             vertices.resize(tex.len(), MeshVertex::default());
@@ -347,142 +623,528 @@ impl GltfCompiler {
                                 });
                             }
                             _ => {
-                                eprintln!(
-                                "ERROR: Texture coordinate set {texture_index} is out of range (max {MAX_TEXTURE_COORDS_SETS})",
-                            );
+                                report_asset_diagnostic(
+                                    path,
+                                    COMPILER_NAME,
+                                    DiagnosticSeverity::Warning,
+                                    &location,
+                                    &format!("Texture coordinate set {texture_index} is out of range (max {MAX_TEXTURE_COORDS_SETS}): skipped"),
+                                );
                             }
                         }
                     }
                 }
+                Semantic::Joints(0) => {
+                    let joints = match accessor.data_type() {
+                        DataType::U8 => self
+                            .read_accessor_from_path::<Vector4b>(path, &accessor)
+                            .map(|j| j.iter().map(|v| v.cast::<f32>().unwrap()).collect::<Vec<_>>()),
+                        DataType::U16 => self
+                            .read_accessor_from_path::<Vector4h>(path, &accessor)
+                            .map(|j| j.iter().map(|v| v.cast::<f32>().unwrap()).collect::<Vec<_>>()),
+                        _ => None,
+                    };
+                    if let Some(joints) = joints {
+                        if vertices.is_empty() {
+                            vertices.resize(joints.len(), MeshVertex::default());
+                        }
+                        joints.iter().enumerate().for_each(|(i, v)| {
+                            vertices[i].joints = *v;
+                        });
+                    }
+                }
+                Semantic::Weights(0) => {
+                    let weights = match accessor.data_type() {
+                        DataType::F32 => self.read_accessor_from_path::<Vector4>(path, &accessor),
+                        DataType::U8 => self
+                            .read_accessor_from_path::<Vector4b>(path, &accessor)
+                            .map(|w| {
+                                w.iter()
+                                    .map(|v| v.cast::<f32>().unwrap() / u8::MAX as f32)
+                                    .collect::<Vec<_>>()
+                            }),
+                        DataType::U16 => self
+                            .read_accessor_from_path::<Vector4h>(path, &accessor)
+                            .map(|w| {
+                                w.iter()
+                                    .map(|v| v.cast::<f32>().unwrap() / u16::MAX as f32)
+                                    .collect::<Vec<_>>()
+                            }),
+                        _ => None,
+                    };
+                    if let Some(weights) = weights {
+                        if vertices.is_empty() {
+                            vertices.resize(weights.len(), MeshVertex::default());
+                        }
+                        weights.iter().enumerate().for_each(|(i, v)| {
+                            vertices[i].weights = *v;
+                        });
+                    }
+                }
                 _ => {}
             }
         });
         (vertex_layout, vertices)
     }
 
+    // Position and normal deltas of every target, in the original vertices order
+    fn extract_morph_targets(
+        &mut self,
+        path: &Path,
+        primitive: &Primitive,
+    ) -> Vec<(Vec<Vector3>, Vec<Vector3>)> {
+        primitive
+            .morph_targets()
+            .map(|target| {
+                let positions = target
+                    .positions()
+                    .and_then(|a| self.read_accessor_from_path::<Vector3>(path, &a))
+                    .unwrap_or_default();
+                let normals = target
+                    .normals()
+                    .and_then(|a| self.read_accessor_from_path::<Vector3>(path, &a))
+                    .unwrap_or_default();
+                (positions, normals)
+            })
+            .collect()
+    }
+
+    // Deltas follow the vertices order after the optimization, given by their source indices
+    fn remap_morph_targets(
+        targets: &[(Vec<Vector3>, Vec<Vector3>)],
+        weights: &[f32],
+        weights_range: &[[f32; 2]],
+        source_indices: &[u32],
+    ) -> MeshMorphData {
+        let mut morph_targets = MeshMorphData::default();
+        if targets.is_empty() {
+            return morph_targets;
+        }
+        let has_normals = targets.iter().any(|(_, normals)| !normals.is_empty());
+        let zero = Vector3::new(0., 0., 0.);
+        targets
+            .iter()
+            .enumerate()
+            .for_each(|(t, (positions, normals))| {
+                source_indices.iter().for_each(|&source_index| {
+                    let source_index = source_index as usize;
+                    morph_targets
+                        .position_deltas
+                        .push(positions.get(source_index).copied().unwrap_or(zero));
+                    if has_normals {
+                        morph_targets
+                            .normal_deltas
+                            .push(normals.get(source_index).copied().unwrap_or(zero));
+                    }
+                });
+                morph_targets
+                    .weights
+                    .push(weights.get(t).copied().unwrap_or(0.));
+                morph_targets
+                    .weights_range
+                    .push(weights_range.get(t).copied().unwrap_or([0., 1.]));
+            });
+        morph_targets
+    }
+
     fn process_mesh_data(
         &mut self,
         path: &Path,
         mesh_name: &str,
         primitive: &Primitive,
         material_path: &Path,
+        morph_weights: &[f32],
+        morph_weights_range: &[[f32; 2]],
     ) -> PathBuf {
         let new_path = self.compute_path_name::<MeshData>(path, mesh_name, "mesh");
-        if need_to_binarize(path, new_path.as_path()) {
-            let (vertex_layout, vertices) = self.extract_vertices(path, primitive);
-            let indices = self.extract_indices(path, primitive);
-            let mut geometry = GltfGeometry { vertices, indices };
-            generate_tangents(&mut geometry);
-
-            let (geometry_vertices, geometry_indices) =
-                optimize_mesh(&geometry.vertices, &geometry.indices);
-
-            let mut mesh_indices_offset = 0;
-            let mut previous_meshlets_starting_offset = 0;
-            let mut meshlets_per_lod = Vec::new();
-            let mut all_meshlets = Vec::new();
-            let (meshlets, mut mesh_indices) =
-                compute_meshlets(&geometry_vertices, &geometry_indices, 0);
-
-            inox_log::debug_log!(
-                "LOD 0 has {} meshlets and {} triangles",
-                meshlets.len(),
-                geometry_indices.len() / 3
+        let need_to_build = need_to_binarize(path, new_path.as_path());
+        // Geometry of up to date meshes is still needed to occlude the lightmaps bake
+        if !need_to_build && !self.lightmap_options.enabled {
+            return new_path;
+        }
+        let (mut vertex_layout, vertices) = self.extract_vertices(path, mesh_name, primitive);
+        if vertices.is_empty() {
+            report_asset_diagnostic(
+                path,
+                COMPILER_NAME,
+                DiagnosticSeverity::Error,
+                mesh_name,
+                "Primitive without vertices",
             );
+            return new_path;
+        }
+        if !vertex_layout.intersects(VertexAttributeLayout::HasUV1)
+            && Self::has_textures(&primitive.material())
+        {
+            report_asset_diagnostic(
+                path,
+                COMPILER_NAME,
+                DiagnosticSeverity::Warning,
+                mesh_name,
+                "Missing texture coordinates: textures of its material can't be mapped",
+            );
+        }
+        let indices = self.extract_indices(path, mesh_name, primitive);
+        let mut geometry = GltfGeometry { vertices, indices };
+        generate_tangents(&mut geometry);
+        let lightmap_material_path = self.prepare_lightmap(
+            path,
+            mesh_name,
+            primitive,
+            &mut vertex_layout,
+            &mut geometry,
+        );
+        if !need_to_build {
+            return new_path;
+        }
 
-            let mut is_meshlet_tree_created = meshlets.len() <= 1;
-            all_meshlets.push(meshlets.clone());
-            meshlets_per_lod.push(meshlets);
-            mesh_indices_offset += mesh_indices.len();
-
-            let mut level: usize = 0;
-            while !is_meshlet_tree_created {
-                let previous_lod_meshlets = meshlets_per_lod.last_mut().unwrap();
-                let meshlets_adjacency = build_meshlets_adjacency(
-                    previous_lod_meshlets,
-                    &geometry_vertices,
-                    &mesh_indices,
-                );
-                let groups = group_meshlets_with_metis(&meshlets_adjacency);
-
-                let (mut cluster_indices, cluster_meshlets) = compute_clusters(
-                    &groups,
-                    previous_lod_meshlets,
-                    previous_meshlets_starting_offset,
-                    mesh_indices_offset,
-                    &geometry_vertices,
-                    &mesh_indices,
-                    level as _,
-                );
+        let morph_targets = self.extract_morph_targets(path, primitive);
+        let mesh_options = self
+            .mesh_options
+            .for_asset(path, self.data_raw_folder.as_path());
+        let (geometry_vertices, geometry_indices, source_indices) = if mesh_options.optimize {
+            optimize_mesh_with_remap(&geometry.vertices, &geometry.indices)
+        } else {
+            let source_indices = (0..geometry.vertices.len() as u32).collect();
+            (geometry.vertices, geometry.indices, source_indices)
+        };
+        let morph_targets = Self::remap_morph_targets(
+            &morph_targets,
+            morph_weights,
+            morph_weights_range,
+            &source_indices,
+        );
 
-                inox_log::debug_log!(
-                    "LOD {} has {} meshlets and {} triangles",
-                    level,
-                    cluster_meshlets.len(),
-                    cluster_indices.len() / 3
-                );
+        let (meshlets_per_lod, mesh_indices, lods_stats) =
+            compute_meshlets_lods(&geometry_vertices, &geometry_indices, &mesh_options);
 
-                mesh_indices_offset += cluster_indices.len();
-                mesh_indices.append(&mut cluster_indices);
-                previous_meshlets_starting_offset += meshlets_per_lod[level].len();
-                all_meshlets.push(cluster_meshlets.clone());
-                meshlets_per_lod.push(cluster_meshlets);
-                level += 1;
+        let is_skinned = primitive.get(&Semantic::Joints(0)).is_some()
+            && primitive.get(&Semantic::Weights(0)).is_some();
+        let mut mesh_data = create_mesh_data(
+            vertex_layout,
+            &geometry_vertices,
+            &mesh_indices,
+            is_skinned,
+            morph_targets,
+        );
+        mesh_data.meshlets = meshlets_per_lod;
+        mesh_data.material = lightmap_material_path.unwrap_or(material_path.to_path_buf());
+        if mesh_data.has_morph_targets() {
+            expand_meshlets_with_morph_targets(&mut mesh_data, &geometry_vertices);
+        }
+        compute_meshlets_bvh(&mut mesh_data);
+        add_asset_mesh_stats(
+            path,
+            COMPILER_NAME,
+            compute_mesh_stats(mesh_name, &mesh_data, lods_stats),
+        );
 
-                is_meshlet_tree_created = groups.len() == 1 || level >= (MAX_LOD_LEVELS - 1);
-            }
+        self.create_file(
+            path,
+            &mesh_data,
+            mesh_name,
+            "mesh",
+            SerializationType::Binary,
+        )
+    }
 
-            let mut mesh_data = create_mesh_data(vertex_layout, &geometry_vertices, &mesh_indices);
-            mesh_data.meshlets = meshlets_per_lod;
-            mesh_data.meshlets_bvh.clear();
-            mesh_data.material = material_path.to_path_buf();
+    fn primitive_name(mesh: &gltf::Mesh, primitive_index: usize) -> String {
+        format!(
+            "{}_Primitive_{primitive_index}",
+            mesh.name().unwrap_or("Mesh")
+        )
+    }
 
-            mesh_data.meshlets.iter_mut().for_each(|meshlets| {
-                //println!("LOD {} has {} meshlets", _lod_level, meshlets.len());
+    // Compiled material of the primitive, the default one when it can't be read
+    fn read_material_data(&self, path: &Path, material: &gltf::Material) -> MaterialData {
+        let material_path = self.compute_path_name::<MaterialData>(
+            path,
+            &Self::material_name(material),
+            "material",
+        );
+        fs::read(material_path)
+            .ok()
+            .and_then(|bytes| deserialize::<MaterialData>(&bytes))
+            .unwrap_or_default()
+    }
 
-                let mut meshlets_aabbs = Vec::new();
-                meshlets_aabbs.resize_with(meshlets.len(), AABB::empty);
-                meshlets.iter_mut().enumerate().for_each(|(i, m)| {
-                    meshlets_aabbs[i] = AABB::create(m.aabb_min, m.aabb_max, i as _);
-                    m.bvh_offset = i as _;
-                });
-                let bvh = BVHTree::new(&meshlets_aabbs);
-                mesh_data.meshlets_bvh.push(create_linearized_bvh(&bvh));
-            });
+    // First uv set after the base one that no texture of the material uses
+    fn lightmap_uv_set(material_data: &MaterialData) -> Option<usize> {
+        (1..MAX_TEXTURE_COORDS_SETS).find(|&uv_set| {
+            !material_data
+                .textures
+                .iter()
+                .zip(material_data.texcoords_set.iter())
+                .any(|(texture, &texcoords_set)| {
+                    !texture.as_os_str().is_empty() && texcoords_set == uv_set
+                })
+        })
+    }
 
-            self.create_file(
+    // Static primitives are kept for the lightmaps bake. The ones that get a lightmap use
+    // an authored uv_1 when it's free, otherwise a new set is generated for it.
+    // Returns the material variant with the lightmap that the mesh has to use.
+    fn prepare_lightmap(
+        &self,
+        path: &Path,
+        mesh_name: &str,
+        primitive: &Primitive,
+        vertex_layout: &mut VertexAttributeLayout,
+        geometry: &mut GltfGeometry,
+    ) -> Option<PathBuf> {
+        if !self.lightmap_options.enabled
+            || primitive.get(&Semantic::Joints(0)).is_some()
+            || primitive.morph_targets().next().is_some()
+        {
+            return None;
+        }
+        let material = primitive.material();
+        let instances_count = self
+            .lightmap_instances
+            .transforms
+            .get(mesh_name)
+            .map_or(0, Vec::len);
+        let mut lightmap = None;
+        if self.lightmap_instances.animated.contains(mesh_name) {
+            report_asset_diagnostic(
                 path,
-                &mesh_data,
+                COMPILER_NAME,
+                DiagnosticSeverity::Info,
                 mesh_name,
-                "mesh",
-                SerializationType::Binary,
-            )
-        } else {
-            new_path
+                "Moved by animations: not lightmapped",
+            );
+        } else if instances_count > 1 {
+            // A single lightmap can't hold the lighting of every node using the primitive
+            report_asset_diagnostic(
+                path,
+                COMPILER_NAME,
+                DiagnosticSeverity::Warning,
+                mesh_name,
+                &format!("Used by {instances_count} nodes: not lightmapped"),
+            );
+        } else if !material.unlit() {
+            let material_data = self.read_material_data(path, &material);
+            match Self::lightmap_uv_set(&material_data) {
+                Some(1) if vertex_layout.intersects(VertexAttributeLayout::HasUV2) => {
+                    let size = compute_lightmap_size(
+                        &geometry.vertices,
+                        &geometry.indices,
+                        &self.lightmap_options,
+                    );
+                    lightmap = Some((1, size, material_data));
+                }
+                Some(uv_set) => match generate_lightmap_uvs(
+                    &geometry.vertices,
+                    &geometry.indices,
+                    uv_set,
+                    &self.lightmap_options,
+                ) {
+                    Some((vertices, indices, size)) => {
+                        geometry.vertices = vertices;
+                        geometry.indices = indices;
+                        *vertex_layout |= uv_set_layout(uv_set);
+                        lightmap = Some((uv_set, size, material_data));
+                    }
+                    None => report_asset_diagnostic(
+                        path,
+                        COMPILER_NAME,
+                        DiagnosticSeverity::Warning,
+                        mesh_name,
+                        "Lightmap charts don't fit in the max lightmap size: not lightmapped",
+                    ),
+                },
+                None => report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Warning,
+                    mesh_name,
+                    "No free texture coordinates set for the lightmap: not lightmapped",
+                ),
+            }
+        }
+        let base_color = material.pbr_metallic_roughness().base_color_factor();
+        let emissive = material.emissive_factor();
+        let geometry = LightmapGeometry {
+            vertices: geometry.vertices.clone(),
+            indices: geometry.indices.clone(),
+            albedo: Vector3::new(base_color[0], base_color[1], base_color[2]),
+            emissive: emissive.into(),
+            lightmap: lightmap.map(|(uv_set, size, material_data)| PrimitiveLightmap {
+                uv_set,
+                size,
+                material_data,
+            }),
+        };
+        let has_lightmap = geometry.lightmap.is_some();
+        self.lightmap_geometries
+            .lock()
+            .unwrap()
+            .insert(mesh_name.to_string(), geometry);
+        if !has_lightmap {
+            return None;
         }
+        let material_path = self.compute_path_name::<MaterialData>(
+            path,
+            &format!("{mesh_name}_Lightmap"),
+            "material",
+        );
+        Some(to_local_path(
+            material_path.as_path(),
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        ))
     }
-    fn process_texture(&mut self, path: &Path, texture: Texture) -> PathBuf {
-        if let ImageSource::Uri {
-            uri,
-            mime_type: _, /* fields */
-        } = texture.source().source()
-        {
-            if let Some(parent_folder) = path.parent() {
-                let parent_path = parent_folder.to_str().unwrap().to_string();
-                let filepath = PathBuf::from(parent_path).join(uri);
-                let path = to_local_path(
-                    filepath.as_path(),
+
+    // Textures are compiled for the kind of slot that uses them, once for each gltf
+    fn process_texture(
+        &mut self,
+        path: &Path,
+        texture: Texture,
+        texture_type: TextureType,
+    ) -> PathBuf {
+        let Some(image_path) = self.process_image(path, texture) else {
+            return PathBuf::new();
+        };
+        let key = (image_path.clone(), texture_type as usize);
+        let texture_path = match self.compiled_textures.get(&key) {
+            Some(texture_path) => texture_path.clone(),
+            None => {
+                let texture_path = compile_material_texture::<PLATFORM_TYPE>(
+                    path,
+                    COMPILER_NAME,
+                    image_path.as_path(),
+                    texture_type,
                     self.data_raw_folder.as_path(),
                     self.data_folder.as_path(),
-                );
-                return path;
+                )
+                .unwrap_or(image_path);
+                self.compiled_textures.insert(key, texture_path.clone());
+                texture_path
+            }
+        };
+        to_local_path(
+            texture_path.as_path(),
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        )
+    }
+
+    fn process_image(&mut self, path: &Path, texture: Texture) -> Option<PathBuf> {
+        let image = texture.source();
+        match image.source() {
+            ImageSource::Uri { uri, mime_type } if is_data_uri(uri) => {
+                let data = decode_data_uri(uri);
+                let mime_type = mime_type
+                    .map(|m| m.to_string())
+                    .or(data.as_ref().map(|(m, _)| m.clone()))
+                    .unwrap_or_default();
+                return Some(self.write_embedded_image(path, &image, &mime_type, || {
+                    data.map(|(_, bytes)| bytes)
+                }));
+            }
+            ImageSource::Uri {
+                uri,
+                mime_type: _, /* fields */
+            } => {
+                if let Some(parent_folder) = path.parent() {
+                    let parent_path = parent_folder.to_str().unwrap().to_string();
+                    let filepath = PathBuf::from(parent_path).join(uri);
+                    add_asset_dependency(path, COMPILER_NAME, filepath.as_path());
+                    if !filepath.exists() {
+                        report_asset_diagnostic(
+                            path,
+                            COMPILER_NAME,
+                            DiagnosticSeverity::Warning,
+                            &format!("Texture {}", texture.index()),
+                            &format!("Missing image {uri}"),
+                        );
+                    }
+                    return Some(filepath);
+                }
+            }
+            ImageSource::View { view, mime_type } => {
+                return Some(self.write_embedded_image(path, &image, mime_type, || {
+                    self.read_view_from_path(path, &view)
+                }));
+            }
+        }
+        None
+    }
+
+    // Embedded images are written as they are in the data folder,
+    // so that they can be loaded like any other texture file
+    fn write_embedded_image<F>(
+        &self,
+        path: &Path,
+        image: &Image,
+        mime_type: &str,
+        read_bytes: F,
+    ) -> PathBuf
+    where
+        F: FnOnce() -> Option<Vec<u8>>,
+    {
+        let name = image
+            .name()
+            .map(|n| n.to_string())
+            .unwrap_or(format!("Image_{}", image.index()));
+        let extension = match mime_type {
+            "image/jpeg" => "jpg",
+            _ => mime_type.rsplit('/').next().unwrap_or_default(),
+        };
+        let new_path = self.compute_path_name_with_extension(path, &name, extension, "texture");
+        if need_to_binarize(path, new_path.as_path()) {
+            match read_bytes() {
+                Some(bytes) => {
+                    create_dir_all(new_path.parent().unwrap()).ok();
+                    match fs::write(new_path.as_path(), bytes) {
+                        Ok(()) => track_asset_output(path, COMPILER_NAME, new_path.as_path()),
+                        Err(e) => report_asset_diagnostic(
+                            path,
+                            COMPILER_NAME,
+                            DiagnosticSeverity::Error,
+                            &format!("Image {name}"),
+                            &format!("Unable to write texture {new_path:?}: {e}"),
+                        ),
+                    }
+                }
+                None => report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Warning,
+                    &format!("Image {name}"),
+                    "Unable to read embedded image",
+                ),
             }
         }
-        PathBuf::new()
+        new_path
+    }
+    // Materials without a name are identified by their index in the document
+    fn has_textures(material: &gltf::Material) -> bool {
+        let pbr = material.pbr_metallic_roughness();
+        pbr.base_color_texture().is_some()
+            || pbr.metallic_roughness_texture().is_some()
+            || material.normal_texture().is_some()
+            || material.emissive_texture().is_some()
+            || material.occlusion_texture().is_some()
     }
-    fn process_material_data(&mut self, path: &Path, primitive: &Primitive) -> PathBuf {
-        let name = format!("Material_{}", self.material_index);
-        let new_path = self.compute_path_name::<MeshData>(path, &name, "material");
+
+    // Materials are deduplicated by index: different materials with the same name get their own file
+    fn material_name(material: &gltf::Material) -> String {
+        match (material.name(), material.index()) {
+            (Some(name), Some(index)) => format!("{name}_{index}"),
+            (Some(name), None) => name.to_string(),
+            (None, Some(index)) => format!("Material_{index}"),
+            (None, None) => "Material_Default".to_string(),
+        }
+    }
+    fn process_material_data(
+        &mut self,
+        path: &Path,
+        document: &Document,
+        primitive: &Primitive,
+    ) -> PathBuf {
+        let name = Self::material_name(&primitive.material());
+        let new_path = self.compute_path_name::<MaterialData>(path, &name, "material");
         if need_to_binarize(path, new_path.as_path()) {
             let mut material_data = MaterialData::default();
 
@@ -494,15 +1156,25 @@ impl GltfCompiler {
             material_data.metallic_factor = material.metallic_factor();
             if let Some(info) = material.base_color_texture() {
                 material_data.textures[TextureType::BaseColor as usize] =
-                    self.process_texture(path, info.texture());
+                    self.process_texture(path, info.texture(), TextureType::BaseColor);
                 material_data.texcoords_set[TextureType::BaseColor as usize] =
                     info.tex_coord() as _;
+                Self::process_texture_transform(
+                    &mut material_data,
+                    TextureType::BaseColor,
+                    info.texture_transform(),
+                );
             }
             if let Some(info) = material.metallic_roughness_texture() {
                 material_data.textures[TextureType::MetallicRoughness as usize] =
-                    self.process_texture(path, info.texture());
+                    self.process_texture(path, info.texture(), TextureType::MetallicRoughness);
                 material_data.texcoords_set[TextureType::MetallicRoughness as usize] =
                     info.tex_coord() as _;
+                Self::process_texture_transform(
+                    &mut material_data,
+                    TextureType::MetallicRoughness,
+                    info.texture_transform(),
+                );
             }
 
             let material = primitive.material();
@@ -512,19 +1184,24 @@ impl GltfCompiler {
             }
             if let Some(texture) = material.normal_texture() {
                 material_data.textures[TextureType::Normal as usize] =
-                    self.process_texture(path, texture.texture());
+                    self.process_texture(path, texture.texture(), TextureType::Normal);
                 material_data.texcoords_set[TextureType::Normal as usize] =
                     texture.tex_coord() as _;
             }
             if let Some(texture) = material.emissive_texture() {
                 material_data.textures[TextureType::Emissive as usize] =
-                    self.process_texture(path, texture.texture());
+                    self.process_texture(path, texture.texture(), TextureType::Emissive);
                 material_data.texcoords_set[TextureType::Emissive as usize] =
                     texture.tex_coord() as _;
+                Self::process_texture_transform(
+                    &mut material_data,
+                    TextureType::Emissive,
+                    texture.texture_transform(),
+                );
             }
             if let Some(texture) = material.occlusion_texture() {
                 material_data.textures[TextureType::Occlusion as usize] =
-                    self.process_texture(path, texture.texture());
+                    self.process_texture(path, texture.texture(), TextureType::Occlusion);
                 material_data.texcoords_set[TextureType::Occlusion as usize] =
                     texture.tex_coord() as _;
                 material_data.occlusion_strength = texture.strength();
@@ -556,14 +1233,14 @@ impl GltfCompiler {
                 material_data.flags |= MaterialFlags::SpecularGlossiness;
                 //println!("Flags |= SpecularGlossiness");
                 if let Some(texture) = pbr.specular_glossiness_texture() {
-                    material_data.textures[TextureType::SpecularGlossiness as usize] =
-                        self.process_texture(path, texture.texture());
+                    material_data.textures[TextureType::SpecularGlossiness as usize] = self
+                        .process_texture(path, texture.texture(), TextureType::SpecularGlossiness);
                     material_data.texcoords_set[TextureType::SpecularGlossiness as usize] =
                         texture.tex_coord() as _;
                 }
                 if let Some(texture) = pbr.diffuse_texture() {
                     material_data.textures[TextureType::Diffuse as usize] =
-                        self.process_texture(path, texture.texture());
+                        self.process_texture(path, texture.texture(), TextureType::Diffuse);
                     material_data.texcoords_set[TextureType::Diffuse as usize] =
                         texture.tex_coord() as _;
                 }
@@ -596,13 +1273,13 @@ impl GltfCompiler {
                 .into();
                 if let Some(texture) = specular.specular_texture() {
                     material_data.textures[TextureType::Specular as usize] =
-                        self.process_texture(path, texture.texture());
+                        self.process_texture(path, texture.texture(), TextureType::Specular);
                     material_data.texcoords_set[TextureType::Specular as usize] =
                         texture.tex_coord() as _;
                 }
                 if let Some(texture) = specular.specular_color_texture() {
                     material_data.textures[TextureType::SpecularColor as usize] =
-                        self.process_texture(path, texture.texture());
+                        self.process_texture(path, texture.texture(), TextureType::SpecularColor);
                     material_data.texcoords_set[TextureType::SpecularColor as usize] =
                         texture.tex_coord() as _;
                 }
@@ -613,7 +1290,7 @@ impl GltfCompiler {
                 material_data.transmission_factor = transmission.transmission_factor();
                 if let Some(texture) = transmission.transmission_texture() {
                     material_data.textures[TextureType::Transmission as usize] =
-                        self.process_texture(path, texture.texture());
+                        self.process_texture(path, texture.texture(), TextureType::Transmission);
                     material_data.texcoords_set[TextureType::Transmission as usize] =
                         texture.tex_coord() as _;
                 }
@@ -628,20 +1305,23 @@ impl GltfCompiler {
                     volume.attenuation_distance(),
                 ]
                 .into();
+                material_data.thickness_factor = volume.thickness_factor();
                 if let Some(texture) = volume.thickness_texture() {
                     material_data.textures[TextureType::Thickness as usize] =
-                        self.process_texture(path, texture.texture());
+                        self.process_texture(path, texture.texture(), TextureType::Thickness);
                     material_data.texcoords_set[TextureType::Thickness as usize] =
                         texture.tex_coord() as _;
                 }
             }
 
+            self.process_material_extensions(path, document, &material, &mut material_data);
+
             //let flags: u32 = material_data.flags.into();
             //println!("Flags = {} = {:b}", flags, material_data.flags);
             self.create_file(
                 path,
                 &material_data,
-                primitive.material().name().unwrap_or(&name),
+                &name,
                 "material",
                 SerializationType::Binary,
             )
@@ -650,6 +1330,176 @@ impl GltfCompiler {
         }
     }
 
+    fn process_texture_transform(
+        material_data: &mut MaterialData,
+        texture_type: TextureType,
+        transform: Option<TextureTransform>,
+    ) {
+        if let Some(transform) = transform {
+            Self::apply_texture_transform(
+                material_data,
+                texture_type,
+                transform.offset(),
+                transform.rotation(),
+                transform.scale(),
+                transform.tex_coord(),
+            );
+        }
+    }
+
+    fn apply_texture_transform(
+        material_data: &mut MaterialData,
+        texture_type: TextureType,
+        offset: [f32; 2],
+        rotation: f32,
+        scale: [f32; 2],
+        tex_coord: Option<u32>,
+    ) {
+        if let Some(tex_coord) = tex_coord {
+            material_data.texcoords_set[texture_type as usize] = tex_coord as _;
+        }
+        material_data.flags |= MaterialFlags::TextureTransform;
+        material_data.texture_offset_and_scale[texture_type as usize] =
+            [offset[0], offset[1], scale[0], scale[1]].into();
+        material_data.texture_rotation[texture_type as usize] = rotation;
+    }
+
+    // Texture info of material extensions not parsed by gltf crate
+    fn process_extension_texture(
+        &mut self,
+        path: &Path,
+        document: &Document,
+        material_data: &mut MaterialData,
+        texture_type: TextureType,
+        info: Option<&Value>,
+    ) {
+        let Some(info) = info else {
+            return;
+        };
+        let Some(texture) = info["index"]
+            .as_u64()
+            .and_then(|i| document.textures().nth(i as _))
+        else {
+            return;
+        };
+        material_data.textures[texture_type as usize] =
+            self.process_texture(path, texture, texture_type);
+        material_data.texcoords_set[texture_type as usize] =
+            info["texCoord"].as_u64().unwrap_or(0) as _;
+        if let Some(transform) = info
+            .get("extensions")
+            .and_then(|e| e.get("KHR_texture_transform"))
+        {
+            let vec2 = |v: &Value, default: [f32; 2]| {
+                [
+                    v[0].as_f64().map_or(default[0], |v| v as f32),
+                    v[1].as_f64().map_or(default[1], |v| v as f32),
+                ]
+            };
+            Self::apply_texture_transform(
+                material_data,
+                texture_type,
+                vec2(&transform["offset"], [0., 0.]),
+                transform["rotation"].as_f64().unwrap_or(0.) as f32,
+                vec2(&transform["scale"], [1., 1.]),
+                transform["texCoord"].as_u64().map(|v| v as u32),
+            );
+        }
+    }
+
+    // KHR_materials_clearcoat, sheen, iridescence and anisotropy are read from the raw json
+    fn process_material_extensions(
+        &mut self,
+        path: &Path,
+        document: &Document,
+        material: &gltf::Material,
+        material_data: &mut MaterialData,
+    ) {
+        let Some(extensions) = material.extensions() else {
+            return;
+        };
+        let float = |v: &Value, default: f32| v.as_f64().map_or(default, |v| v as f32);
+        if let Some(clearcoat) = extensions.get("KHR_materials_clearcoat") {
+            material_data.flags |= MaterialFlags::Clearcoat;
+            material_data.clearcoat_factor = float(&clearcoat["clearcoatFactor"], 0.);
+            material_data.clearcoat_roughness_factor =
+                float(&clearcoat["clearcoatRoughnessFactor"], 0.);
+            for (texture_type, name) in [
+                (TextureType::Clearcoat, "clearcoatTexture"),
+                (TextureType::ClearcoatRoughness, "clearcoatRoughnessTexture"),
+                (TextureType::ClearcoatNormal, "clearcoatNormalTexture"),
+            ] {
+                self.process_extension_texture(
+                    path,
+                    document,
+                    material_data,
+                    texture_type,
+                    clearcoat.get(name),
+                );
+            }
+        }
+        if let Some(sheen) = extensions.get("KHR_materials_sheen") {
+            material_data.flags |= MaterialFlags::Sheen;
+            let color = &sheen["sheenColorFactor"];
+            material_data.sheen_color_and_roughness_factor = [
+                float(&color[0], 0.),
+                float(&color[1], 0.),
+                float(&color[2], 0.),
+                float(&sheen["sheenRoughnessFactor"], 0.),
+            ]
+            .into();
+            for (texture_type, name) in [
+                (TextureType::SheenColor, "sheenColorTexture"),
+                (TextureType::SheenRoughness, "sheenRoughnessTexture"),
+            ] {
+                self.process_extension_texture(
+                    path,
+                    document,
+                    material_data,
+                    texture_type,
+                    sheen.get(name),
+                );
+            }
+        }
+        if let Some(iridescence) = extensions.get("KHR_materials_iridescence") {
+            material_data.flags |= MaterialFlags::Iridescence;
+            material_data.iridescence_factor = float(&iridescence["iridescenceFactor"], 0.);
+            material_data.iridescence_ior = float(&iridescence["iridescenceIor"], 1.3);
+            material_data.iridescence_thickness_range = [
+                float(&iridescence["iridescenceThicknessMinimum"], 100.),
+                float(&iridescence["iridescenceThicknessMaximum"], 400.),
+            ]
+            .into();
+            for (texture_type, name) in [
+                (TextureType::Iridescence, "iridescenceTexture"),
+                (
+                    TextureType::IridescenceThickness,
+                    "iridescenceThicknessTexture",
+                ),
+            ] {
+                self.process_extension_texture(
+                    path,
+                    document,
+                    material_data,
+                    texture_type,
+                    iridescence.get(name),
+                );
+            }
+        }
+        if let Some(anisotropy) = extensions.get("KHR_materials_anisotropy") {
+            material_data.flags |= MaterialFlags::Anisotropy;
+            material_data.anisotropy_strength = float(&anisotropy["anisotropyStrength"], 0.);
+            material_data.anisotropy_rotation = float(&anisotropy["anisotropyRotation"], 0.);
+            self.process_extension_texture(
+                path,
+                document,
+                material_data,
+                TextureType::Anisotropy,
+                anisotropy.get("anisotropyTexture"),
+            );
+        }
+    }
+
     fn process_node(
         &mut self,
         path: &Path,
@@ -657,28 +1507,42 @@ impl GltfCompiler {
         node_name: &str,
     ) -> Option<(NodeType, PathBuf)> {
         let (node_type, node_path) = self.process_object(path, node, node_name);
-        self.node_index += 1;
         Some((node_type, node_path))
     }
 
     fn process_object(&mut self, path: &Path, node: &Node, node_name: &str) -> (NodeType, PathBuf) {
+        let chunk_hierarchy = self.chunk_hierarchy.take();
         let mut object_data = ObjectData::default();
         let object_transform: Matrix4 = Matrix4::from(node.transform().matrix());
         object_data.transform = object_transform;
+        if let Some(hierarchy) = &chunk_hierarchy {
+            object_data.transform = hierarchy.parent_inverse * object_transform;
+        }
 
         if let Some(mesh) = node.mesh() {
             for (primitive_index, _primitive) in mesh.primitives().enumerate() {
-                let name = format!(
-                    "{}_Primitive_{primitive_index}",
-                    mesh.name().unwrap_or("Mesh")
-                );
-                let mesh_path = self.meshes_paths.get(&name).unwrap();
-                object_data.components.push(mesh_path.clone());
+                let name = Self::primitive_name(&mesh, primitive_index);
+                // Primitives skipped as not supported have no mesh
+                if let Some(mesh_path) = self.meshes_paths.get(&name) {
+                    object_data.components.push(mesh_path.clone());
+                }
             }
         }
+        if let Some(animations) = self.animations_paths.get(&node.index()) {
+            object_data.components.extend(animations.iter().cloned());
+        }
+        if let Some(skin) = node.skin() {
+            let skin_path =
+                self.compute_path_name::<SkinData>(path, &Self::skin_name(&skin), "skin");
+            object_data.components.push(to_local_path(
+                skin_path.as_path(),
+                self.data_raw_folder.as_path(),
+                self.data_folder.as_path(),
+            ));
+        }
         if let Some(camera) = node.camera() {
             object_data.transform = object_data.transform.inverse();
-            let (_, camera_path) = self.process_camera(path, &camera);
+            let (_, camera_path) = self.process_camera(path, &camera, node.index());
             object_data.components.push(to_local_path(
                 camera_path.as_path(),
                 self.data_raw_folder.as_path(),
@@ -686,7 +1550,7 @@ impl GltfCompiler {
             ));
         }
         if let Some(light) = node.light() {
-            let (_, light_path) = self.process_light(path, &light, &object_transform);
+            let (_, light_path) = self.process_light(path, &light, &object_transform, node.index());
             object_data.components.push(to_local_path(
                 light_path.as_path(),
                 self.data_raw_folder.as_path(),
@@ -721,11 +1585,11 @@ impl GltfCompiler {
         }
 
         for (child_index, child) in node.children().enumerate() {
-            let name = format!("Node_{}_Child_{}", self.node_index, child_index);
+            let name = format!("Node_{}_Child_{}", node.index(), child_index);
             if let Some(camera) = child.camera() {
                 object_data.transform =
                     object_data.transform * Matrix4::from(child.transform().matrix());
-                let (_, camera_path) = self.process_camera(path, &camera);
+                let (_, camera_path) = self.process_camera(path, &camera, child.index());
                 object_data.components.push(to_local_path(
                     camera_path.as_path(),
                     self.data_raw_folder.as_path(),
@@ -744,17 +1608,26 @@ impl GltfCompiler {
                 }
             }
         }
+        if let Some(hierarchy) = chunk_hierarchy {
+            object_data.children.extend(hierarchy.children);
+        }
 
-        (
-            NodeType::Object,
-            self.create_file(
-                path,
-                &object_data,
-                node_name,
-                "object",
-                SerializationType::Binary,
+        let object_path = self.create_file(
+            path,
+            &object_data,
+            node_name,
+            "object",
+            SerializationType::Binary,
+        );
+        self.nodes_paths.insert(
+            node.index(),
+            to_local_path(
+                object_path.as_path(),
+                self.data_raw_folder.as_path(),
+                self.data_folder.as_path(),
             ),
-        )
+        );
+        (NodeType::Object, object_path)
     }
 
     fn process_light(
@@ -762,7 +1635,17 @@ impl GltfCompiler {
         path: &Path,
         light: &Light,
         transform: &Matrix4,
+        node_index: usize,
     ) -> (NodeType, PathBuf) {
+        let light_data = Self::light_data(light, transform);
+        let name = format!("Node_{}_Light_{}", node_index, light.index());
+        (
+            NodeType::Light,
+            self.create_file(path, &light_data, &name, "light", SerializationType::Binary),
+        )
+    }
+
+    fn light_data(light: &Light, transform: &Matrix4) -> GPULight {
         let mut light_data = GPULight {
             color: [light.color()[0], light.color()[1], light.color()[2]],
             direction: (-transform.forward()).into(),
@@ -788,15 +1671,15 @@ impl GltfCompiler {
                 light_data.outer_cone_angle = outer_cone_angle.cos();
             }
         }
-
-        let name = format!("Node_{}_Light_{}", self.node_index, light.index());
-        (
-            NodeType::Light,
-            self.create_file(path, &light_data, &name, "light", SerializationType::Binary),
-        )
+        light_data
     }
 
-    fn process_camera(&mut self, path: &Path, camera: &Camera) -> (NodeType, PathBuf) {
+    fn process_camera(
+        &mut self,
+        path: &Path,
+        camera: &Camera,
+        node_index: usize,
+    ) -> (NodeType, PathBuf) {
         let mut camera_data = CameraData::default();
         match camera.projection() {
             Projection::Perspective(p) => {
@@ -806,11 +1689,15 @@ impl GltfCompiler {
                 camera_data.fov = Radians::new(p.yfov()).into();
             }
             Projection::Orthographic(o) => {
+                camera_data.is_orthographic = true;
+                camera_data.xmag = o.xmag();
+                camera_data.ymag = o.ymag();
+                camera_data.aspect_ratio = o.xmag() / o.ymag();
                 camera_data.near = o.znear();
                 camera_data.far = o.zfar();
             }
         }
-        let name = format!("Node_{}_Camera_{}", self.node_index, camera.index());
+        let name = format!("Node_{}_Camera_{}", node_index, camera.index());
 
         (
             NodeType::Camera,
@@ -824,100 +1711,728 @@ impl GltfCompiler {
         )
     }
 
-    pub fn process_path(&mut self, path: &Path) {
+    // Channels of an animation are split by target node: every node gets its own clip,
+    // all with the duration of the whole animation so that they loop in sync
+    fn process_animation(&mut self, path: &Path, animation: &gltf::Animation) {
+        let animation_name = animation
+            .name()
+            .map(|n| n.to_string())
+            .unwrap_or(format!("Animation_{}", animation.index()));
+        let mut nodes_data: HashMap<usize, AnimationData> = HashMap::new();
+        for channel in animation.channels() {
+            // Morph target weights are split in one channel per target
+            let property = match channel.target().property() {
+                Property::Translation => Some(AnimationProperty::Translation),
+                Property::Rotation => Some(AnimationProperty::Rotation),
+                Property::Scale => Some(AnimationProperty::Scale),
+                Property::MorphTargetWeights => None,
+            };
+            let sampler = channel.sampler();
+            let interpolation = match sampler.interpolation() {
+                Interpolation::Linear => AnimationInterpolation::Linear,
+                Interpolation::Step => AnimationInterpolation::Step,
+                Interpolation::CubicSpline => AnimationInterpolation::CubicSpline,
+            };
+            let output = sampler.output();
+            if output.data_type() != DataType::F32 {
+                report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Warning,
+                    &format!("Animation {animation_name}"),
+                    "Unsupported normalized values: channel skipped",
+                );
+                continue;
+            }
+            let times = self.read_accessor_from_path::<f32>(path, &sampler.input());
+            let values = match output.dimensions() {
+                Dimensions::Vec3 => self
+                    .read_accessor_from_path::<Vector3>(path, &output)
+                    .map(|v| v.iter().map(|v| v.extend(0.)).collect()),
+                Dimensions::Vec4 => self.read_accessor_from_path::<Vector4>(path, &output),
+                Dimensions::Scalar => self
+                    .read_accessor_from_path::<f32>(path, &output)
+                    .map(|v| v.iter().map(|v| Vector4::new(*v, 0., 0., 0.)).collect()),
+                _ => None,
+            };
+            if let (Some(times), Some(values)) = (times, values) {
+                let channels = match property {
+                    Some(property) => vec![(property, values)],
+                    None => Self::split_morph_weights(&times, &values, interpolation),
+                };
+                let node = channel.target().node();
+                let data = nodes_data.entry(node.index()).or_insert_with(|| {
+                    let node_name = node
+                        .name()
+                        .map(|n| n.to_string())
+                        .unwrap_or(format!("Node_{}", node.index()));
+                    AnimationData {
+                        name: format!("{animation_name}_{node_name}"),
+                        ..Default::default()
+                    }
+                });
+                data.duration = times.iter().fold(data.duration, |d, t| d.max(*t));
+                channels.into_iter().for_each(|(property, values)| {
+                    data.channels.push(AnimationChannelData {
+                        property,
+                        interpolation,
+                        times: times.clone(),
+                        values,
+                    });
+                });
+            }
+        }
+        let duration = nodes_data
+            .values()
+            .fold(0., |d: f32, data| d.max(data.duration));
+        for (node_index, mut data) in nodes_data {
+            data.duration = duration;
+            let animation_path = self.create_file(
+                path,
+                &data,
+                &data.name,
+                "animation",
+                SerializationType::Binary,
+            );
+            self.animations_paths
+                .entry(node_index)
+                .or_default()
+                .push(to_local_path(
+                    animation_path.as_path(),
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                ));
+        }
+    }
+
+    // Weights of the morph targets of each mesh by mesh index, from 0..1 widened by the
+    // default ones and the animated ones, so that mesh bounds contain the morphed vertices
+    fn morph_weights_ranges(
+        &mut self,
+        path: &Path,
+        document: &Document,
+    ) -> HashMap<usize, Vec<[f32; 2]>> {
+        let mut ranges: HashMap<usize, Vec<[f32; 2]>> = HashMap::new();
+        for mesh in document.meshes() {
+            if let Some(weights) = mesh.weights() {
+                let range = ranges.entry(mesh.index()).or_default();
+                weights
+                    .iter()
+                    .enumerate()
+                    .for_each(|(t, w)| Self::widen_weight_range(range, t, *w));
+            }
+        }
+        for animation in document.animations() {
+            for channel in animation.channels() {
+                let Some(mesh) = channel.target().node().mesh() else {
+                    continue;
+                };
+                let sampler = channel.sampler();
+                if !matches!(channel.target().property(), Property::MorphTargetWeights)
+                    || sampler.output().data_type() != DataType::F32
+                {
+                    continue;
+                }
+                let Some(values) = self.read_accessor_from_path::<f32>(path, &sampler.output())
+                else {
+                    continue;
+                };
+                // Tangents of cubic splines are skipped, only the values are keys
+                let is_cubic = matches!(sampler.interpolation(), Interpolation::CubicSpline);
+                let elements_count = sampler.input().count() * if is_cubic { 3 } else { 1 };
+                if elements_count == 0 {
+                    continue;
+                }
+                let targets_count = values.len() / elements_count;
+                if targets_count == 0 {
+                    continue;
+                }
+                let range = ranges.entry(mesh.index()).or_default();
+                values
+                    .iter()
+                    .enumerate()
+                    .filter(|(k, _)| !is_cubic || (k / targets_count) % 3 == 1)
+                    .for_each(|(k, w)| Self::widen_weight_range(range, k % targets_count, *w));
+            }
+        }
+        ranges
+    }
+
+    fn widen_weight_range(range: &mut Vec<[f32; 2]>, target: usize, weight: f32) {
+        if range.len() <= target {
+            range.resize(target + 1, [0., 1.]);
+        }
+        range[target] = [range[target][0].min(weight), range[target][1].max(weight)];
+    }
+
+    // Morph weights output has the values of all the targets for every key
+    // (for every in tangent, value and out tangent with cubic splines)
+    fn split_morph_weights(
+        times: &[f32],
+        values: &[Vector4],
+        interpolation: AnimationInterpolation,
+    ) -> Vec<(AnimationProperty, Vec<Vector4>)> {
+        let elements_count = if interpolation == AnimationInterpolation::CubicSpline {
+            times.len() * 3
+        } else {
+            times.len()
+        };
+        if elements_count == 0 {
+            return Vec::new();
+        }
+        let targets_count = values.len() / elements_count;
+        (0..targets_count)
+            .map(|t| {
+                let values = (0..elements_count)
+                    .map(|k| values[k * targets_count + t])
+                    .collect();
+                (AnimationProperty::MorphWeight(t as _), values)
+            })
+            .collect()
+    }
+
+    fn skin_name(skin: &gltf::Skin) -> String {
+        skin.name()
+            .map(|n| n.to_string())
+            .unwrap_or(format!("Skin_{}", skin.index()))
+    }
+
+    // Skins are written after the scene traversal, when all the joint objects paths are known.
+    // A skin with missing joints is kept but never animated
+    fn process_skin(&mut self, path: &Path, skin: &gltf::Skin) {
+        let skin_name = Self::skin_name(skin);
+        let new_path = self.compute_path_name::<SkinData>(path, &skin_name, "skin");
+        if !need_to_binarize(path, new_path.as_path()) {
+            return;
+        }
+        let mut skin_data = SkinData::default();
+        for joint in skin.joints() {
+            match self.nodes_paths.get(&joint.index()) {
+                Some(joint_path) => skin_data.joints.push(joint_path.clone()),
+                None => {
+                    report_asset_diagnostic(
+                        path,
+                        COMPILER_NAME,
+                        DiagnosticSeverity::Warning,
+                        &format!("Skin {skin_name}"),
+                        &format!("Joint {} is not in a scene", joint.index()),
+                    );
+                    skin_data.joints.push(PathBuf::new());
+                }
+            }
+        }
+        if let Some(accessor) = skin.inverse_bind_matrices() {
+            if let Some(matrices) = self.read_accessor_from_path::<Matrix4>(path, &accessor) {
+                skin_data.inverse_bind_matrices = matrices;
+            }
+        }
+        self.create_file(
+            path,
+            &skin_data,
+            &skin_name,
+            "skin",
+            SerializationType::Binary,
+        );
+    }
+
+    // Static nodes of the scenes with their lights. Nodes targeted by animations move
+    // with their children, so their primitives are neither lightmapped nor occluders.
+    fn collect_lightmap_instances(document: &Document) -> LightmapInstances {
+        let mut animated_nodes = HashSet::new();
+        for animation in document.animations() {
+            for channel in animation.channels() {
+                animated_nodes.insert(channel.target().node().index());
+            }
+        }
+        let mut instances = LightmapInstances::default();
+        for scene in document.scenes() {
+            for node in scene.nodes() {
+                Self::collect_node_lightmap_instances(
+                    &node,
+                    &Matrix4::default_identity(),
+                    &animated_nodes,
+                    &mut instances,
+                );
+            }
+        }
+        instances
+    }
+
+    fn collect_node_lightmap_instances(
+        node: &Node,
+        parent_transform: &Matrix4,
+        animated_nodes: &HashSet<usize>,
+        instances: &mut LightmapInstances,
+    ) {
+        if animated_nodes.contains(&node.index()) {
+            Self::collect_animated_primitives(node, instances);
+            return;
+        }
+        let transform = parent_transform * Matrix4::from(node.transform().matrix());
+        if let Some(mesh) = node.mesh() {
+            for primitive_index in 0..mesh.primitives().len() {
+                instances
+                    .transforms
+                    .entry(Self::primitive_name(&mesh, primitive_index))
+                    .or_default()
+                    .push(transform);
+            }
+        }
+        if let Some(light) = node.light() {
+            instances.lights.push(Self::light_data(&light, &transform));
+        }
+        for child in node.children() {
+            Self::collect_node_lightmap_instances(&child, &transform, animated_nodes, instances);
+        }
+    }
+
+    fn collect_animated_primitives(node: &Node, instances: &mut LightmapInstances) {
+        if let Some(mesh) = node.mesh() {
+            for primitive_index in 0..mesh.primitives().len() {
+                instances
+                    .animated
+                    .insert(Self::primitive_name(&mesh, primitive_index));
+            }
+        }
+        for child in node.children() {
+            Self::collect_animated_primitives(&child, instances);
+        }
+    }
+
+    // Bakes the lightmaps of the primitives gathered by mesh jobs, each one as a job.
+    // The whole scene occludes and bounces the light of its punctual lights.
+    fn process_lightmaps(&mut self, path: &Path) {
+        let geometries = std::mem::take(&mut *self.lightmap_geometries.lock().unwrap());
+        if !geometries.values().any(|g| g.lightmap.is_some()) {
+            return;
+        }
+        let instances = self.lightmap_instances.clone();
+        // Primitives not in a scene are at the origin
+        let identity = [Matrix4::default_identity()];
+        let transforms_of = |name: &str| match instances.transforms.get(name) {
+            Some(transforms) => transforms.as_slice(),
+            None if instances.animated.contains(name) => &[],
+            None => identity.as_slice(),
+        };
+
+        let mut triangles = Vec::new();
+        geometries.iter().for_each(|(name, geometry)| {
+            transforms_of(name).iter().for_each(|transform| {
+                geometry.indices.chunks(3).for_each(|t| {
+                    triangles.push(LightmapTriangle {
+                        positions: [t[0], t[1], t[2]].map(|i| {
+                            transform.rotate_point(geometry.vertices[i as usize].pos.xyz())
+                        }),
+                        albedo: geometry.albedo,
+                        emissive: geometry.emissive,
+                    });
+                });
+            });
+        });
+        let scene = Arc::new(LightmapScene::new(
+            triangles,
+            instances.lights.clone(),
+            &self.lightmap_options,
+        ));
+
+        let jobs = self.jobs.child();
+        geometries.into_iter().for_each(|(name, geometry)| {
+            let Some(lightmap) = geometry.lightmap else {
+                return;
+            };
+            let transforms = transforms_of(&name);
+            let lightmap_name = format!("{name}_Lightmap");
+            let texture_path =
+                self.compute_path_name_with_extension(path, &lightmap_name, "png", "texture");
+            let mut material_data = lightmap.material_data;
+            material_data.textures[TextureType::Lightmap as usize] = to_local_path(
+                texture_path.as_path(),
+                self.data_raw_folder.as_path(),
+                self.data_folder.as_path(),
+            );
+            material_data.texcoords_set[TextureType::Lightmap as usize] = lightmap.uv_set;
+            self.create_file(
+                path,
+                &material_data,
+                &lightmap_name,
+                "material",
+                SerializationType::Binary,
+            );
+
+            // Lightmapped primitives have a single node at most.
+            // Normals use the inverse transpose, that is the transform with inverted scale
+            let transform = transforms[0];
+            let scale = transform.scale();
+            let vertices: Vec<MeshVertex> = geometry
+                .vertices
+                .iter()
+                .map(|v| {
+                    let p = transform.rotate_point(v.pos.xyz());
+                    let n = transform
+                        .rotate_vector(v.normal.xyz().div(scale.mul(scale)))
+                        .normalized();
+                    MeshVertex {
+                        pos: Vector4::new(p.x, p.y, p.z, 1.),
+                        normal: Vector4::new(n.x, n.y, n.z, 0.),
+                        ..*v
+                    }
+                })
+                .collect();
+            let (uv_set, size) = (lightmap.uv_set, lightmap.size);
+            let scene = scene.clone();
+            let path = path.to_path_buf();
+            jobs.add_job(&format!("Lightmap {name}"), move || {
+                let texels = bake_lightmap(&scene, &vertices, &geometry.indices, uv_set, size);
+                let bytes = encode_lightmap(&texels);
+                let bytes_count = bytes.len();
+                create_dir_all(texture_path.parent().unwrap()).ok();
+                let result = RgbaImage::from_raw(size, size, bytes)
+                    .ok_or("Invalid size".to_string())
+                    .and_then(|image| image.save(&texture_path).map_err(|e| e.to_string()));
+                match result {
+                    Ok(()) => {
+                        track_asset_output(path.as_path(), COMPILER_NAME, texture_path.as_path());
+                        add_asset_texture_stats(
+                            path.as_path(),
+                            COMPILER_NAME,
+                            TextureStats {
+                                name: lightmap_name,
+                                width: size,
+                                height: size,
+                                mips: 1,
+                                format: "Rgba8Unorm".to_string(),
+                                bytes: bytes_count as _,
+                            },
+                        )
+                    }
+                    Err(e) => report_asset_diagnostic(
+                        path.as_path(),
+                        COMPILER_NAME,
+                        DiagnosticSeverity::Error,
+                        &name,
+                        &format!("Unable to write lightmap {texture_path:?}: {e}"),
+                    ),
+                }
+            });
+        });
+        jobs.wait();
+    }
+
+    // Returns false when the file can't be read
+    pub fn process_path(&mut self, path: &Path) -> bool {
         self.meshes_paths.clear();
+        self.animations_paths.clear();
+        self.nodes_paths.clear();
+        // Compilers cloned to process other files in parallel share the same map otherwise
+        self.lightmap_geometries = Arc::default();
+        self.lightmap_instances = Arc::default();
 
-        if let Ok(gltf) = Gltf::open(path) {
-            for mesh in gltf.meshes() {
-                for (primitive_index, primitive) in mesh.primitives().enumerate() {
-                    let name = format!(
-                        "{}_Primitive_{primitive_index}",
-                        mesh.name().unwrap_or("Mesh")
+        let mut gltf = match Gltf::open(path) {
+            Ok(gltf) => gltf,
+            Err(e) => {
+                report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Error,
+                    "",
+                    &format!("Unable to open glTF: {e}"),
+                );
+                return false;
+            }
+        };
+        self.load_buffers(path, &mut gltf);
+        let document = Arc::new(gltf.document);
+        if self.lightmap_options.enabled {
+            self.lightmap_instances = Arc::new(Self::collect_lightmap_instances(&document));
+        }
+        let jobs = self.jobs.child();
+        // Materials are shared between primitives so they're processed only once,
+        // while each primitive is a job that computes its mesh, meshlets and LODs
+        let mut materials_paths = HashMap::new();
+        let morph_weights_ranges = self.morph_weights_ranges(path, &document);
+        for mesh in document.meshes() {
+            for (primitive_index, primitive) in mesh.primitives().enumerate() {
+                let name = Self::primitive_name(&mesh, primitive_index);
+                if primitive.mode() != Mode::Triangles {
+                    report_asset_diagnostic(
+                        path,
+                        COMPILER_NAME,
+                        DiagnosticSeverity::Warning,
+                        &name,
+                        &format!("Unsupported {:?} primitive mode: skipped", primitive.mode()),
                     );
-                    let material_path = self.process_material_data(path, &primitive);
-                    let material_path = to_local_path(
+                    continue;
+                }
+                let material_path = materials_paths
+                    .entry(primitive.material().index())
+                    .or_insert_with(|| {
+                        let material_path = self.process_material_data(path, &document, &primitive);
+                        to_local_path(
+                            material_path.as_path(),
+                            self.data_raw_folder.as_path(),
+                            self.data_folder.as_path(),
+                        )
+                    })
+                    .clone();
+                let mesh_path = self.compute_path_name::<MeshData>(path, &name, "mesh");
+                let mesh_path = to_local_path(
+                    mesh_path.as_path(),
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                );
+                self.meshes_paths.insert(name.clone(), mesh_path);
+
+                let compiler = self.mesh_compiler();
+                let document = document.clone();
+                let path = path.to_path_buf();
+                let mesh_index = mesh.index();
+                let morph_weights_range = morph_weights_ranges
+                    .get(&mesh_index)
+                    .cloned()
+                    .unwrap_or_default();
+                jobs.add_job(&format!("Mesh {name}"), move || {
+                    let mut compiler = compiler;
+                    let mesh = document.meshes().nth(mesh_index).unwrap();
+                    let primitive = mesh.primitives().nth(primitive_index).unwrap();
+                    compiler.process_mesh_data(
+                        path.as_path(),
+                        &name,
+                        &primitive,
                         material_path.as_path(),
-                        self.data_raw_folder.as_path(),
-                        self.data_folder.as_path(),
+                        mesh.weights().unwrap_or_default(),
+                        &morph_weights_range,
                     );
-                    let mesh_path =
-                        self.process_mesh_data(path, &name, &primitive, material_path.as_path());
-                    let mesh_path = to_local_path(
-                        mesh_path.as_path(),
-                        self.data_raw_folder.as_path(),
-                        self.data_folder.as_path(),
-                    );
-                    self.meshes_paths.insert(name, mesh_path);
-                }
-            }
-            for scene in gltf.scenes() {
-                let mut scene_data = SceneData::default();
-                let scene_name = path
-                    .parent()
-                    .unwrap()
-                    .file_stem()
-                    .unwrap()
-                    .to_str()
-                    .unwrap();
-
-                let new_path = self.compute_path_name::<SceneData>(path, scene_name, "");
-                if need_to_binarize(path, new_path.as_path()) {
-                    self.material_index = 0;
-                    self.node_index = 0;
-                    for node in scene.nodes() {
-                        let name = format!("Node_{}", self.node_index);
-                        if let Some((node_type, node_path)) =
-                            self.process_node(path, &node, node.name().unwrap_or(&name))
-                        {
-                            let node_path = to_local_path(
-                                node_path.as_path(),
-                                self.data_raw_folder.as_path(),
-                                self.data_folder.as_path(),
-                            );
-                            match node_type {
-                                NodeType::Camera => {
-                                    scene_data.cameras.push(node_path);
-                                }
-                                NodeType::Object => {
-                                    scene_data.objects.push(node_path);
-                                }
-                                NodeType::Light => {
-                                    scene_data.lights.push(node_path);
-                                }
+                });
+            }
+        }
+        for animation in document.animations() {
+            self.process_animation(path, &animation);
+        }
+        // Chunks only produce objects, their scene is created from the index file
+        let chunk_hierarchy = self.find_chunk_index(path).map(|(index_path, index_data)| {
+            add_asset_dependency(path, COMPILER_NAME, index_path.as_path());
+            self.compute_chunk_hierarchy(path, index_path.as_path(), &index_data)
+        });
+        for scene in document.scenes() {
+            if let Some(hierarchy) = &chunk_hierarchy {
+                for node in scene.nodes() {
+                    // Cameras stay at the root of the scene, with their view transform
+                    if node.camera().is_none() {
+                        self.chunk_hierarchy = Some(hierarchy.clone());
+                    }
+                    let name = format!("Node_{}", node.index());
+                    self.process_node(path, &node, node.name().unwrap_or(&name));
+                    self.chunk_hierarchy = None;
+                }
+                continue;
+            }
+            let mut scene_data = SceneData::default();
+            let scene_name = path
+                .parent()
+                .unwrap()
+                .file_stem()
+                .unwrap()
+                .to_str()
+                .unwrap();
+
+            let new_path = self.compute_path_name::<SceneData>(path, scene_name, "");
+            if need_to_binarize(path, new_path.as_path()) {
+                for node in scene.nodes() {
+                    let name = format!("Node_{}", node.index());
+                    if let Some((node_type, node_path)) =
+                        self.process_node(path, &node, node.name().unwrap_or(&name))
+                    {
+                        let node_path = to_local_path(
+                            node_path.as_path(),
+                            self.data_raw_folder.as_path(),
+                            self.data_folder.as_path(),
+                        );
+                        match node_type {
+                            NodeType::Camera => {
+                                scene_data.cameras.push(node_path);
+                            }
+                            NodeType::Object => {
+                                scene_data.objects.push(node_path);
+                            }
+                            NodeType::Light => {
+                                scene_data.lights.push(node_path);
                             }
                         }
                     }
+                }
 
-                    self.create_file(path, &scene_data, scene_name, "", SerializationType::Binary);
+                self.create_file(path, &scene_data, scene_name, "", SerializationType::Binary);
+            }
+        }
+        for skin in document.skins() {
+            self.process_skin(path, &skin);
+        }
+        jobs.wait();
+        self.process_lightmaps(path);
+        self.buffers = Arc::default();
+        true
+    }
+
+    // Scene referencing the objects of every chunk, even the ones not re-exported
+    pub fn process_index(&mut self, path: &Path) -> bool {
+        let scene_name = path.file_stem().unwrap().to_str().unwrap();
+        let new_path = self.compute_path_name::<SceneData>(path, scene_name, "");
+        if !need_to_binarize(path, new_path.as_path()) {
+            return true;
+        }
+        let Some(index_data) = read_gltf_index(path) else {
+            report_asset_diagnostic(
+                path,
+                COMPILER_NAME,
+                DiagnosticSeverity::Error,
+                "",
+                "Unable to read glTF index",
+            );
+            return false;
+        };
+        let mut scene_data = SceneData::default();
+        let parent_folder = path.parent().unwrap();
+        index_data.chunks.iter().for_each(|chunk| {
+            let chunk_path = parent_folder.join(chunk.path.as_path());
+            add_asset_dependency(path, COMPILER_NAME, chunk_path.as_path());
+            // Children are referenced by the objects of their parent, cameras excepted
+            let has_parent = chunk_parent(&index_data, chunk).is_some();
+            match self.chunk_root_objects(chunk_path.as_path()) {
+                Ok(objects) => objects
+                    .into_iter()
+                    .filter(|(_, is_camera)| !has_parent || *is_camera)
+                    .for_each(|(object_path, _)| scene_data.objects.push(object_path)),
+                Err(e) => {
+                    report_asset_diagnostic(
+                        path,
+                        COMPILER_NAME,
+                        DiagnosticSeverity::Error,
+                        &format!("Chunk {:?}", chunk.path),
+                        &format!("Unable to open glTF chunk: {e}"),
+                    );
                 }
             }
+        });
+        self.create_file(path, &scene_data, scene_name, "", SerializationType::Binary);
+        true
+    }
+
+    // Index listing the glTF as one of its chunks, searched in the folders containing it.
+    // Indices are named after their folder, as the Blender exporter writes them,
+    // so that no folder has to be listed to find them
+    fn find_chunk_index(&self, path: &Path) -> Option<(PathBuf, GltfIndexData)> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let data_raw_folder = self
+            .data_raw_folder
+            .canonicalize()
+            .unwrap_or_else(|_| self.data_raw_folder.clone());
+        path.ancestors()
+            .skip(1)
+            .take_while(|folder| folder.starts_with(data_raw_folder.as_path()))
+            .filter_map(|folder| {
+                let name = folder.file_name()?.to_str()?;
+                Some(folder.join(format!("{name}.{}", GltfIndexData::extension())))
+            })
+            .filter(|index_path| index_path.exists())
+            .find_map(|index_path| {
+                let index_data = read_gltf_index(index_path.as_path())?;
+                let folder = index_path.parent()?;
+                let is_listed = index_data
+                    .chunks
+                    .iter()
+                    .any(|c| is_same_file(folder.join(c.path.as_path()).as_path(), &path));
+                is_listed.then_some((index_path, index_data))
+            })
+    }
+
+    fn compute_chunk_hierarchy(
+        &self,
+        path: &Path,
+        index_path: &Path,
+        index_data: &GltfIndexData,
+    ) -> ChunkHierarchy {
+        let folder = index_path.parent().unwrap();
+        let is_this_chunk = |chunk: &Path| is_same_file(folder.join(chunk).as_path(), path);
+        let parent_inverse = index_data
+            .chunks
+            .iter()
+            .find(|c| is_this_chunk(c.path.as_path()))
+            .and_then(|c| chunk_parent(index_data, c))
+            .and_then(|parent| {
+                let parent_path = folder.join(parent);
+                add_asset_dependency(path, COMPILER_NAME, parent_path.as_path());
+                chunk_root_transform(parent_path.as_path())
+            })
+            .map_or(Matrix4::default_identity(), |transform| transform.inverse());
+        let mut children = Vec::new();
+        index_data
+            .chunks
+            .iter()
+            .filter(|c| chunk_parent(index_data, c).is_some_and(&is_this_chunk))
+            .for_each(|c| {
+                let child_path = folder.join(c.path.as_path());
+                add_asset_dependency(path, COMPILER_NAME, child_path.as_path());
+                if let Ok(objects) = self.chunk_root_objects(child_path.as_path()) {
+                    children.extend(
+                        objects
+                            .into_iter()
+                            .filter(|(_, is_camera)| !is_camera)
+                            .map(|(object_path, _)| object_path),
+                    );
+                }
+            });
+        ChunkHierarchy {
+            parent_inverse,
+            children,
         }
     }
 
+    // Objects of the root nodes of a chunk, with true for cameras
+    fn chunk_root_objects(&self, chunk_path: &Path) -> Result<Vec<(PathBuf, bool)>, gltf::Error> {
+        let gltf = Gltf::open(chunk_path)?;
+        let objects = gltf
+            .scenes()
+            .flat_map(|scene| scene.nodes())
+            .map(|node| {
+                let name = format!("Node_{}", node.index());
+                let object_path = self.compute_path_name::<ObjectData>(
+                    chunk_path,
+                    node.name().unwrap_or(&name),
+                    "object",
+                );
+                let object_path = to_local_path(
+                    object_path.as_path(),
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                );
+                (object_path, node.camera().is_some())
+            })
+            .collect();
+        Ok(objects)
+    }
+
     fn compute_path_name<T>(&self, path: &Path, new_name: &str, folder: &str) -> PathBuf
     where
         T: Serialize + SerializeFile + Clone + 'static,
     {
-        let filename = path.file_name().unwrap().to_str().unwrap();
-        let destination_ext = format!("{}.{}", new_name, T::extension());
-        let mut filepath = path.parent().unwrap().to_path_buf();
-        if !folder.is_empty() {
-            filepath = filepath.join(folder);
-        }
-        filepath = filepath.join(filename);
-        let mut from_source_to_compiled = filepath.to_str().unwrap().to_string();
-        from_source_to_compiled = from_source_to_compiled.replace(
-            self.data_raw_folder
-                .canonicalize()
-                .unwrap()
-                .to_str()
-                .unwrap(),
-            self.data_folder.canonicalize().unwrap().to_str().unwrap(),
-        );
-        from_source_to_compiled =
-            from_source_to_compiled.replace(filename, destination_ext.as_str());
+        self.compute_path_name_with_extension(path, new_name, T::extension(), folder)
+    }
 
-        PathBuf::from(from_source_to_compiled)
+    fn compute_path_name_with_extension(
+        &self,
+        path: &Path,
+        new_name: &str,
+        extension: &str,
+        folder: &str,
+    ) -> PathBuf {
+        compute_compiled_path(
+            path,
+            new_name,
+            extension,
+            folder,
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        )
     }
 
     fn create_file<T>(
@@ -931,25 +2446,88 @@ impl GltfCompiler {
     where
         T: Serialize + SerializeFile + Clone + 'static,
     {
-        let new_path = self.compute_path_name::<T>(path, new_name, folder);
-        if !new_path.exists() {
-            let result = create_dir_all(new_path.parent().unwrap());
-            debug_assert!(result.is_ok());
-        }
-        if need_to_binarize(path, new_path.as_path()) {
-            debug_log!("Serializing {:?}", new_path);
-            data.save_to_file(new_path.as_path(), serialization_type);
-        }
-        new_path
+        create_compiled_file(
+            path,
+            COMPILER_NAME,
+            data,
+            new_name,
+            folder,
+            serialization_type,
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        )
     }
 }
 
-impl ExtensionHandler for GltfCompiler {
-    fn on_changed(&mut self, path: &Path) {
+fn read_gltf_index(path: &Path) -> Option<GltfIndexData> {
+    fs::read(path)
+        .ok()
+        .and_then(|bytes| deserialize_from_text::<GltfIndexData>(&bytes))
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    a == b
+        || a.canonicalize()
+            .is_ok_and(|a| b.canonicalize().is_ok_and(|b| a == b))
+}
+
+// Parent of the chunk, when it is listed in the index too
+fn chunk_parent<'a>(index_data: &GltfIndexData, chunk: &'a GltfChunkData) -> Option<&'a Path> {
+    chunk
+        .parent
+        .as_deref()
+        .filter(|parent| index_data.chunks.iter().any(|c| c.path == *parent))
+}
+
+// World transform of the first root node of a chunk that isn't a camera
+fn chunk_root_transform(chunk_path: &Path) -> Option<Matrix4> {
+    let gltf = Gltf::open(chunk_path).ok()?;
+    let transform = gltf
+        .scenes()
+        .flat_map(|scene| scene.nodes())
+        .find(|node| node.camera().is_none())
+        .map(|node| Matrix4::from(node.transform().matrix()));
+    transform
+}
+
+impl<const PLATFORM_TYPE: PlatformType> ExtensionHandler for GltfCompiler<PLATFORM_TYPE> {
+    fn on_changed(&self, path: &Path) {
         if let Some(ext) = path.extension() {
             let extension = ext.to_str().unwrap().to_string();
-            if extension.as_str() == GLTF_EXTENSION {
-                self.process_path(path);
+            let is_scene =
+                extension.as_str() == GLTF_EXTENSION || extension.as_str() == GLB_EXTENSION;
+            let is_index = extension.as_str() == GltfIndexData::extension();
+            if (is_scene || is_index)
+                && begin_asset(
+                    path,
+                    COMPILER_NAME,
+                    COMPILER_VERSION,
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                )
+            {
+                // Each file is processed by its own compiler, as more of them can run in parallel
+                let mut compiler = self.clone();
+                let is_processed = if is_scene {
+                    compiler.process_path(path)
+                } else {
+                    compiler.process_index(path)
+                };
+                if is_processed {
+                    end_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                } else {
+                    abort_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                }
             }
         }
     }
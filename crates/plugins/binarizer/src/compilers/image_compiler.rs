@@ -1,8 +1,13 @@
 use std::path::{Path, PathBuf};
 
-use crate::{copy_into_data_folder, ExtensionHandler};
+use crate::{
+    abort_asset, begin_asset, compute_compiled_path, copy_into_data_folder, end_asset,
+    need_to_binarize, send_reloaded_event, texture::compile_image_texture, track_asset_output,
+    ExtensionHandler,
+};
 use inox_log::debug_log;
 use inox_messenger::MessageHubRc;
+use inox_platform::PlatformType;
 
 const IMAGE_PNG_EXTENSION: &str = "png";
 const IMAGE_JPG_EXTENSION: &str = "jpg";
@@ -11,17 +16,18 @@ const IMAGE_BMP_EXTENSION: &str = "bmp";
 const IMAGE_TGA_EXTENSION: &str = "tga";
 const IMAGE_DDS_EXTENSION: &str = "dds";
 const IMAGE_TIFF_EXTENSION: &str = "tiff";
-const IMAGE_GIF_EXTENSION: &str = "bmp";
+const IMAGE_GIF_EXTENSION: &str = "gif";
 const IMAGE_ICO_EXTENSION: &str = "ico";
-const IMAGE_EXR_EXTENSION: &str = "exr";
+const COMPILER_NAME: &str = "image";
+const COMPILER_VERSION: u32 = 3;
 
-pub struct ImageCompiler {
+pub struct ImageCompiler<const PLATFORM_TYPE: PlatformType> {
     message_hub: MessageHubRc,
     data_raw_folder: PathBuf,
     data_folder: PathBuf,
 }
 
-impl ImageCompiler {
+impl<const PLATFORM_TYPE: PlatformType> ImageCompiler<PLATFORM_TYPE> {
     pub fn new(message_hub: MessageHubRc, data_raw_folder: &Path, data_folder: &Path) -> Self {
         Self {
             message_hub,
@@ -29,12 +35,64 @@ impl ImageCompiler {
             data_folder: data_folder.to_path_buf(),
         }
     }
+
+    // The copy is kept even when up to date, as an output of this build
+    fn copy_image(&self, path: &Path) -> bool {
+        if copy_into_data_folder(
+            &self.message_hub,
+            path,
+            COMPILER_NAME,
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        ) {
+            return true;
+        }
+        let image_path = self.image_path(path);
+        if !need_to_binarize(path, image_path.as_path()) {
+            track_asset_output(path, COMPILER_NAME, image_path.as_path());
+            return true;
+        }
+        false
+    }
+
+    fn image_path(&self, path: &Path) -> PathBuf {
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let extension = path.extension().unwrap().to_str().unwrap();
+        compute_compiled_path(
+            path,
+            name,
+            extension,
+            "",
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        )
+    }
+
+    // Images are compiled with mips and block compression.
+    // They're copied as they are too when they can't be decoded or when their
+    // compressed format isn't supported by the GPU, as the renderer falls back to them
+    pub fn process_path(&self, path: &Path) -> bool {
+        let Some(texture_data) = compile_image_texture::<PLATFORM_TYPE>(
+            path,
+            COMPILER_NAME,
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        ) else {
+            return self.copy_image(path);
+        };
+        if texture_data.format.block_dimensions() != (1, 1) {
+            return self.copy_image(path);
+        }
+        send_reloaded_event(&self.message_hub, self.image_path(path).as_path());
+        true
+    }
 }
 
-impl ExtensionHandler for ImageCompiler {
-    fn on_changed(&mut self, path: &Path) {
+impl<const PLATFORM_TYPE: PlatformType> ExtensionHandler for ImageCompiler<PLATFORM_TYPE> {
+    fn on_changed(&self, path: &Path) {
         if let Some(ext) = path.extension() {
             let extension = ext.to_str().unwrap().to_string();
+            // Exr images are environments, compiled by the EnvironmentCompiler
             if (extension.as_str() == IMAGE_PNG_EXTENSION
                 || extension.as_str() == IMAGE_JPG_EXTENSION
                 || extension.as_str() == IMAGE_JPEG_EXTENSION
@@ -43,16 +101,31 @@ impl ExtensionHandler for ImageCompiler {
                 || extension.as_str() == IMAGE_TIFF_EXTENSION
                 || extension.as_str() == IMAGE_GIF_EXTENSION
                 || extension.as_str() == IMAGE_ICO_EXTENSION
-                || extension.as_str() == IMAGE_DDS_EXTENSION
-                || extension.as_str() == IMAGE_EXR_EXTENSION)
-                && copy_into_data_folder(
-                    &self.message_hub,
+                || extension.as_str() == IMAGE_DDS_EXTENSION)
+                && begin_asset(
                     path,
+                    COMPILER_NAME,
+                    COMPILER_VERSION,
                     self.data_raw_folder.as_path(),
                     self.data_folder.as_path(),
                 )
             {
-                debug_log!("Serializing {:?}", path);
+                if self.process_path(path) {
+                    debug_log!("Serializing {:?}", path);
+                    end_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                } else {
+                    abort_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                }
             }
         }
     }
@@ -1,11 +1,19 @@
 pub use crate::copy_compiler::*;
+pub use crate::environment_compiler::*;
 pub use crate::font_compiler::*;
 pub use crate::gltf_compiler::*;
 pub use crate::image_compiler::*;
+pub use crate::obj_compiler::*;
+pub use crate::ply_compiler::*;
 pub use crate::shader_compiler::*;
+pub use crate::stl_compiler::*;
 
 pub mod copy_compiler;
+pub mod environment_compiler;
 pub mod font_compiler;
 pub mod gltf_compiler;
 pub mod image_compiler;
+pub mod obj_compiler;
+pub mod ply_compiler;
 pub mod shader_compiler;
+pub mod stl_compiler;
@@ -0,0 +1,699 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    abort_asset, add_asset_dependency, add_asset_mesh_stats, begin_asset, compute_compiled_path,
+    create_compiled_file, end_asset,
+    mesh::{build_mesh_data, compute_mesh_stats, default_material_data, MeshOptions, MeshVertex},
+    need_to_binarize, report_asset_diagnostic,
+    texture::compile_material_texture,
+    to_local_path, ExtensionHandler,
+};
+use inox_math::{VecBase, VecBaseFloat, Vector2, Vector3, Vector4};
+use inox_platform::PlatformType;
+use inox_render::{MaterialData, MaterialFlags, MeshData, TextureType, VertexAttributeLayout};
+use inox_resources::DiagnosticSeverity;
+use inox_scene::{ObjectData, SceneData};
+use inox_serialize::{SerializationType, Serialize, SerializeFile};
+
+const OBJ_EXTENSION: &str = "obj";
+const COMPILER_NAME: &str = "obj";
+const COMPILER_VERSION: u32 = 3;
+const DEFAULT_GROUP_NAME: &str = "Default";
+const DEFAULT_MATERIAL_NAME: &str = "DefaultMaterial";
+
+// Position, texture coordinate and normal indices of a face vertex, starting from zero
+type ObjIndex = (usize, Option<usize>, Option<usize>);
+
+// Triangles of a group that use the same material
+struct ObjSubMesh {
+    material: String,
+    triangles: Vec<[ObjIndex; 3]>,
+}
+
+struct ObjGroup {
+    name: String,
+    submeshes: Vec<ObjSubMesh>,
+}
+
+#[derive(Default)]
+struct ObjModel {
+    positions: Vec<Vector3>,
+    colors: Vec<Vector4>,
+    uvs: Vec<Vector2>,
+    normals: Vec<Vector3>,
+    groups: Vec<ObjGroup>,
+    current_group: usize,
+    material_libraries: Vec<String>,
+}
+
+// Roughness comes from Pr if present, otherwise from the Ns specular exponent
+struct MtlMaterial {
+    name: String,
+    data: MaterialData,
+    roughness: Option<f32>,
+    shininess: Option<f32>,
+}
+
+// Group and material names are free text: anything but letters, digits, '-' and '_'
+// is replaced so that they can't escape or break the output filenames
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn parse_floats<'a>(tokens: impl Iterator<Item = &'a str>) -> Vec<f32> {
+    tokens.map_while(|t| t.parse::<f32>().ok()).collect()
+}
+
+// Indices are 1 based, negative ones are relative to the last element read
+fn resolve_index(token: &str, count: usize) -> Option<usize> {
+    let index = token.parse::<i64>().ok()?;
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        count as i64 + index
+    };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        None
+    } else {
+        Some(resolved as usize)
+    }
+}
+
+impl ObjModel {
+    fn parse(path: &Path, content: &str) -> Self {
+        let mut model = Self::default();
+        let mut material = DEFAULT_MATERIAL_NAME.to_string();
+        for (line_index, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut tokens = line.split_whitespace();
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+            match keyword {
+                "v" => {
+                    let values = parse_floats(tokens);
+                    if values.len() < 3 {
+                        report_asset_diagnostic(
+                            path,
+                            COMPILER_NAME,
+                            DiagnosticSeverity::Warning,
+                            &format!("Line {}", line_index + 1),
+                            "Invalid vertex: skipped",
+                        );
+                        continue;
+                    }
+                    model
+                        .positions
+                        .push(Vector3::new(values[0], values[1], values[2]));
+                    // Vertex colors are an extension that follows the position with rgb values
+                    if values.len() >= 6 {
+                        model
+                            .colors
+                            .resize(model.positions.len() - 1, Vector4::new(1., 1., 1., 1.));
+                        model
+                            .colors
+                            .push(Vector4::new(values[3], values[4], values[5], 1.));
+                    }
+                }
+                "vt" => {
+                    let values = parse_floats(tokens);
+                    let u = values.first().copied().unwrap_or_default();
+                    let v = values.get(1).copied().unwrap_or_default();
+                    model.uvs.push(Vector2::new(u, 1. - v));
+                }
+                "vn" => {
+                    let values = parse_floats(tokens);
+                    if values.len() < 3 {
+                        report_asset_diagnostic(
+                            path,
+                            COMPILER_NAME,
+                            DiagnosticSeverity::Warning,
+                            &format!("Line {}", line_index + 1),
+                            "Invalid normal: skipped",
+                        );
+                        continue;
+                    }
+                    model
+                        .normals
+                        .push(Vector3::new(values[0], values[1], values[2]));
+                }
+                "f" => {
+                    let face = tokens
+                        .map(|t| model.parse_face_vertex(t))
+                        .collect::<Option<Vec<_>>>();
+                    match face {
+                        Some(face) if face.len() >= 3 => {
+                            let submesh = model.current_submesh(&material);
+                            // N-gons are triangulated as a fan around their first vertex
+                            for i in 1..face.len() - 1 {
+                                submesh.triangles.push([face[0], face[i], face[i + 1]]);
+                            }
+                        }
+                        _ => report_asset_diagnostic(
+                            path,
+                            COMPILER_NAME,
+                            DiagnosticSeverity::Warning,
+                            &format!("Line {}", line_index + 1),
+                            "Invalid face: skipped",
+                        ),
+                    }
+                }
+                "g" | "o" => {
+                    let name = tokens.collect::<Vec<_>>().join(" ");
+                    model.set_current_group(if name.is_empty() {
+                        DEFAULT_GROUP_NAME
+                    } else {
+                        &name
+                    });
+                }
+                "usemtl" => {
+                    let name = tokens.collect::<Vec<_>>().join(" ");
+                    material = if name.is_empty() {
+                        DEFAULT_MATERIAL_NAME.to_string()
+                    } else {
+                        name
+                    };
+                }
+                "mtllib" => {
+                    model.material_libraries.extend(tokens.map(String::from));
+                }
+                "l" | "p" => report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Warning,
+                    &format!("Line {}", line_index + 1),
+                    "Lines and points are not supported: skipped",
+                ),
+                // Smoothing groups and free-form geometry are not supported
+                _ => {}
+            }
+        }
+        if !model.colors.is_empty() {
+            model
+                .colors
+                .resize(model.positions.len(), Vector4::new(1., 1., 1., 1.));
+        }
+        model.groups.retain(|g| !g.submeshes.is_empty());
+        model
+    }
+
+    fn parse_face_vertex(&self, token: &str) -> Option<ObjIndex> {
+        let mut parts = token.split('/');
+        let position = resolve_index(parts.next()?, self.positions.len())?;
+        let mut optional_index = |count| match parts.next() {
+            Some(t) if !t.is_empty() => resolve_index(t, count).map(Some),
+            _ => Some(None),
+        };
+        let uv = optional_index(self.uvs.len())?;
+        let normal = optional_index(self.normals.len())?;
+        Some((position, uv, normal))
+    }
+
+    // Groups with the same name are merged together
+    fn set_current_group(&mut self, name: &str) {
+        if let Some(index) = self.groups.iter().position(|g| g.name == name) {
+            self.current_group = index;
+            return;
+        }
+        self.current_group = self.groups.len();
+        self.groups.push(ObjGroup {
+            name: name.to_string(),
+            submeshes: Vec::new(),
+        });
+    }
+
+    fn current_submesh(&mut self, material: &str) -> &mut ObjSubMesh {
+        if self.groups.is_empty() {
+            self.set_current_group(DEFAULT_GROUP_NAME);
+        }
+        let group = &mut self.groups[self.current_group];
+        let index = match group.submeshes.iter().position(|s| s.material == material) {
+            Some(index) => index,
+            None => {
+                group.submeshes.push(ObjSubMesh {
+                    material: material.to_string(),
+                    triangles: Vec::new(),
+                });
+                group.submeshes.len() - 1
+            }
+        };
+        &mut group.submeshes[index]
+    }
+
+    // Vertices are shared between faces only if they have the same position, uv and normal.
+    // Missing normals are computed averaging the normals of the faces around a position
+    fn extract_vertices(
+        &self,
+        submesh: &ObjSubMesh,
+    ) -> (VertexAttributeLayout, Vec<MeshVertex>, Vec<u32>) {
+        let mut vertex_layout =
+            VertexAttributeLayout::HasPosition | VertexAttributeLayout::HasNormal;
+        if !self.colors.is_empty() {
+            vertex_layout |= VertexAttributeLayout::HasColor;
+        }
+        if submesh.triangles.iter().flatten().any(|i| i.1.is_some()) {
+            vertex_layout |= VertexAttributeLayout::HasUV1;
+        }
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut vertices_map: HashMap<ObjIndex, u32> = HashMap::new();
+        submesh.triangles.iter().flatten().for_each(|index| {
+            let i = *vertices_map.entry(*index).or_insert_with(|| {
+                let (p, uv, n) = *index;
+                let mut vertex = MeshVertex {
+                    pos: self.positions[p].extend(1.),
+                    ..Default::default()
+                };
+                if let Some(color) = self.colors.get(p) {
+                    vertex.color = *color;
+                }
+                if let Some(uv) = uv {
+                    vertex.uv_0 = self.uvs[uv];
+                }
+                if let Some(n) = n {
+                    vertex.normal = self.normals[n].extend(1.);
+                }
+                vertices.push(vertex);
+                (vertices.len() - 1) as u32
+            });
+            indices.push(i);
+        });
+
+        if submesh.triangles.iter().flatten().any(|i| i.2.is_none()) {
+            let mut normals = vec![Vector3::default_zero(); self.positions.len()];
+            submesh.triangles.iter().for_each(|t| {
+                let a = self.positions[t[0].0];
+                let b = self.positions[t[1].0];
+                let c = self.positions[t[2].0];
+                // Not normalized: bigger faces weight more
+                let n = (b - a).cross(c - a);
+                t.iter().for_each(|i| normals[i.0] += n);
+            });
+            vertices_map.iter().for_each(|(index, &i)| {
+                if index.2.is_none() {
+                    let n = normals[index.0];
+                    let n = if n.length() > 0. {
+                        n.normalized()
+                    } else {
+                        Vector3::new(0., 0., 1.)
+                    };
+                    vertices[i as usize].normal = n.extend(1.);
+                }
+            });
+        }
+        (vertex_layout, vertices, indices)
+    }
+}
+
+#[derive(Default)]
+pub struct ObjCompiler<const PLATFORM_TYPE: PlatformType> {
+    data_raw_folder: PathBuf,
+    data_folder: PathBuf,
+    mesh_options: MeshOptions,
+}
+
+impl<const PLATFORM_TYPE: PlatformType> ObjCompiler<PLATFORM_TYPE> {
+    pub fn new(data_raw_folder: &Path, data_folder: &Path) -> Self {
+        Self {
+            data_raw_folder: data_raw_folder.to_path_buf(),
+            data_folder: data_folder.to_path_buf(),
+            mesh_options: MeshOptions::default(),
+        }
+    }
+
+    pub fn set_mesh_options(&mut self, mesh_options: &MeshOptions) -> &mut Self {
+        self.mesh_options = mesh_options.clone();
+        self
+    }
+
+    // Texture options are skipped: the file is the last token of the statement.
+    // The texture is compiled for the kind of slot that uses it
+    fn texture_path(
+        &self,
+        path: &Path,
+        mtl_path: &Path,
+        material_name: &str,
+        texture_type: TextureType,
+        tokens: &[&str],
+    ) -> PathBuf {
+        let Some(filename) = tokens.last() else {
+            return PathBuf::new();
+        };
+        let image_path = mtl_path.parent().unwrap().join(filename.replace('\\', "/"));
+        if !image_path.exists() {
+            report_asset_diagnostic(
+                path,
+                COMPILER_NAME,
+                DiagnosticSeverity::Warning,
+                &format!("Material {material_name}"),
+                &format!("Missing texture {filename}"),
+            );
+        }
+        let texture_path = compile_material_texture::<PLATFORM_TYPE>(
+            path,
+            COMPILER_NAME,
+            image_path.as_path(),
+            texture_type,
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        )
+        .unwrap_or(image_path);
+        to_local_path(
+            texture_path.as_path(),
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        )
+    }
+
+    fn parse_mtl(&self, path: &Path, library: &str) -> HashMap<String, MaterialData> {
+        let library_path = path.parent().unwrap().join(library);
+        let mtl_path = library_path.as_path();
+        add_asset_dependency(path, COMPILER_NAME, mtl_path);
+        let mut materials: Vec<MtlMaterial> = Vec::new();
+        let Ok(content) = fs::read_to_string(mtl_path) else {
+            report_asset_diagnostic(
+                path,
+                COMPILER_NAME,
+                DiagnosticSeverity::Warning,
+                &format!("Material library {library}"),
+                if mtl_path.exists() {
+                    "Unable to read MTL file"
+                } else {
+                    "Missing MTL file"
+                },
+            );
+            return HashMap::new();
+        };
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            let Some((keyword, values)) = tokens.split_first() else {
+                continue;
+            };
+            if *keyword == "newmtl" {
+                materials.push(MtlMaterial {
+                    name: values.join(" "),
+                    data: default_material_data(),
+                    roughness: None,
+                    shininess: None,
+                });
+                continue;
+            }
+            let Some(material) = materials.last_mut() else {
+                continue;
+            };
+            let floats = parse_floats(values.iter().copied());
+            let value = floats.first().copied().unwrap_or_default();
+            let color = || {
+                Vector3::new(
+                    value,
+                    floats.get(1).copied().unwrap_or(value),
+                    floats.get(2).copied().unwrap_or(value),
+                )
+            };
+            let data = &mut material.data;
+            match *keyword {
+                "Kd" => {
+                    let c = color();
+                    data.base_color = Vector4::new(c.x, c.y, c.z, data.base_color.w);
+                }
+                "Ke" => data.emissive_color = color(),
+                "d" => data.base_color.w = value,
+                "Tr" => data.base_color.w = 1. - value,
+                "Ni" => {
+                    data.ior = value;
+                    data.flags |= MaterialFlags::Ior;
+                }
+                "Ns" => material.shininess = Some(value),
+                "Pr" => material.roughness = Some(value),
+                "Pm" => data.metallic_factor = value,
+                "map_Kd" | "map_Ke" | "map_Bump" | "map_bump" | "bump" | "norm" => {
+                    let texture_type = match *keyword {
+                        "map_Kd" => TextureType::BaseColor,
+                        "map_Ke" => TextureType::Emissive,
+                        _ => TextureType::Normal,
+                    };
+                    let is_normal = texture_type == TextureType::Normal;
+                    data.textures[texture_type.clone() as usize] =
+                        self.texture_path(path, mtl_path, &material.name, texture_type, values);
+                    if let Some(i) = values
+                        .iter()
+                        .position(|v| *v == "-bm")
+                        .filter(|_| is_normal)
+                    {
+                        data.normal_scale =
+                            values.get(i + 1).and_then(|v| v.parse().ok()).unwrap_or(1.);
+                    }
+                }
+                // Separated roughness and metallic maps can't be packed in a single texture here
+                _ => {}
+            }
+        }
+        materials
+            .into_iter()
+            .map(|mut m| {
+                m.data.roughness_factor = m
+                    .roughness
+                    .or(m.shininess.map(|ns| (2. / (ns.max(0.) + 2.)).sqrt()))
+                    .unwrap_or(1.);
+                if m.data.base_color.w < 1. {
+                    m.data.flags &= !MaterialFlags::AlphaModeOpaque;
+                    m.data.flags |= MaterialFlags::AlphaModeBlend;
+                }
+                if !m.data.textures[TextureType::Emissive as usize]
+                    .as_os_str()
+                    .is_empty()
+                    && m.data.emissive_color == Vector3::default_zero()
+                {
+                    m.data.emissive_color = Vector3::new(1., 1., 1.);
+                }
+                (m.name, m.data)
+            })
+            .collect()
+    }
+
+    fn process_mesh_data(
+        &self,
+        path: &Path,
+        mesh_name: &str,
+        model: &ObjModel,
+        submesh: &ObjSubMesh,
+        material_path: &Path,
+    ) -> PathBuf {
+        let new_path = self.compute_path_name::<MeshData>(path, mesh_name, "mesh");
+        if !need_to_binarize(path, new_path.as_path()) {
+            return new_path;
+        }
+        let (vertex_layout, vertices, indices) = model.extract_vertices(submesh);
+        let mesh_options = self
+            .mesh_options
+            .for_asset(path, self.data_raw_folder.as_path());
+        let (mut mesh_data, lods_stats) =
+            build_mesh_data(vertex_layout, &vertices, &indices, &mesh_options);
+        mesh_data.material = material_path.to_path_buf();
+        add_asset_mesh_stats(
+            path,
+            COMPILER_NAME,
+            compute_mesh_stats(mesh_name, &mesh_data, lods_stats),
+        );
+
+        self.create_file(
+            path,
+            &mesh_data,
+            mesh_name,
+            "mesh",
+            SerializationType::Binary,
+        )
+    }
+
+    // Every group becomes an object with a mesh for each material it uses
+    pub fn process_path(&self, path: &Path) -> bool {
+        let scene_name = path.file_stem().unwrap().to_str().unwrap();
+        let scene_path = self.compute_path_name::<SceneData>(path, scene_name, "");
+        if !need_to_binarize(path, scene_path.as_path()) {
+            return true;
+        }
+        let Ok(content) = fs::read_to_string(path) else {
+            report_asset_diagnostic(
+                path,
+                COMPILER_NAME,
+                DiagnosticSeverity::Error,
+                "",
+                "Unable to read OBJ",
+            );
+            return false;
+        };
+        let model = ObjModel::parse(path, &content);
+
+        let mut materials = HashMap::new();
+        model.material_libraries.iter().for_each(|library| {
+            materials.extend(self.parse_mtl(path, library));
+        });
+
+        let mut materials_paths: HashMap<String, PathBuf> = HashMap::new();
+        let mut scene_data = SceneData::default();
+        model.groups.iter().for_each(|group| {
+            let mut object_data = ObjectData::default();
+            group
+                .submeshes
+                .iter()
+                .enumerate()
+                .for_each(|(submesh_index, submesh)| {
+                    let material_path = materials_paths
+                        .entry(submesh.material.clone())
+                        .or_insert_with(|| {
+                            let material_data = materials.get(&submesh.material).cloned();
+                            if material_data.is_none() && submesh.material != DEFAULT_MATERIAL_NAME
+                            {
+                                report_asset_diagnostic(
+                                    path,
+                                    COMPILER_NAME,
+                                    DiagnosticSeverity::Warning,
+                                    &format!("Material {}", submesh.material),
+                                    "Material not found: default one used",
+                                );
+                            }
+                            // Default materials of models in the same folder don't overwrite each other
+                            let material_name = if submesh.material == DEFAULT_MATERIAL_NAME {
+                                format!("{scene_name}_{DEFAULT_MATERIAL_NAME}")
+                            } else {
+                                submesh.material.clone()
+                            };
+                            let material_path = self.create_file(
+                                path,
+                                &material_data.unwrap_or_else(default_material_data),
+                                &file_name(&material_name),
+                                "material",
+                                SerializationType::Binary,
+                            );
+                            to_local_path(
+                                material_path.as_path(),
+                                self.data_raw_folder.as_path(),
+                                self.data_folder.as_path(),
+                            )
+                        })
+                        .clone();
+                    let mesh_name = format!("{}_Primitive_{submesh_index}", file_name(&group.name));
+                    let has_textures = materials
+                        .get(&submesh.material)
+                        .is_some_and(|m| m.textures.iter().any(|t| !t.as_os_str().is_empty()));
+                    if has_textures && !submesh.triangles.iter().flatten().any(|i| i.1.is_some()) {
+                        report_asset_diagnostic(
+                            path,
+                            COMPILER_NAME,
+                            DiagnosticSeverity::Warning,
+                            &mesh_name,
+                            "Missing texture coordinates: textures of its material can't be mapped",
+                        );
+                    }
+                    let mesh_path = self.process_mesh_data(
+                        path,
+                        &mesh_name,
+                        &model,
+                        submesh,
+                        material_path.as_path(),
+                    );
+                    object_data.components.push(to_local_path(
+                        mesh_path.as_path(),
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    ));
+                });
+            let object_path = self.create_file(
+                path,
+                &object_data,
+                &file_name(&group.name),
+                "object",
+                SerializationType::Binary,
+            );
+            scene_data.objects.push(to_local_path(
+                object_path.as_path(),
+                self.data_raw_folder.as_path(),
+                self.data_folder.as_path(),
+            ));
+        });
+        self.create_file(path, &scene_data, scene_name, "", SerializationType::Binary);
+        true
+    }
+
+    fn compute_path_name<T>(&self, path: &Path, new_name: &str, folder: &str) -> PathBuf
+    where
+        T: SerializeFile,
+    {
+        compute_compiled_path(
+            path,
+            new_name,
+            T::extension(),
+            folder,
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        )
+    }
+
+    fn create_file<T>(
+        &self,
+        path: &Path,
+        data: &T,
+        new_name: &str,
+        folder: &str,
+        serialization_type: SerializationType,
+    ) -> PathBuf
+    where
+        T: Serialize + SerializeFile + Clone + 'static,
+    {
+        create_compiled_file(
+            path,
+            COMPILER_NAME,
+            data,
+            new_name,
+            folder,
+            serialization_type,
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        )
+    }
+}
+
+impl<const PLATFORM_TYPE: PlatformType> ExtensionHandler for ObjCompiler<PLATFORM_TYPE> {
+    fn on_changed(&self, path: &Path) {
+        if let Some(ext) = path.extension() {
+            if ext.to_str().unwrap() == OBJ_EXTENSION
+                && begin_asset(
+                    path,
+                    COMPILER_NAME,
+                    COMPILER_VERSION,
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                )
+            {
+                if self.process_path(path) {
+                    end_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                } else {
+                    abort_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,438 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    abort_asset, add_asset_mesh_stats, begin_asset, compute_compiled_path, create_compiled_file,
+    end_asset,
+    mesh::{
+        build_mesh_data, compute_mesh_stats, compute_normals, compute_tangents,
+        default_material_data, MeshOptions, MeshVertex,
+    },
+    need_to_binarize, report_asset_diagnostic, to_local_path, ExtensionHandler,
+};
+use inox_math::{VecBase, Vector4};
+use inox_render::VertexAttributeLayout;
+use inox_resources::DiagnosticSeverity;
+use inox_scene::{ObjectData, SceneData};
+use inox_serialize::{SerializationType, Serialize, SerializeFile};
+
+const PLY_EXTENSION: &str = "ply";
+const COMPILER_NAME: &str = "ply";
+const COMPILER_VERSION: u32 = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Clone, Copy)]
+enum PlyType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl PlyType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(Self::I8),
+            "uchar" | "uint8" => Some(Self::U8),
+            "short" | "int16" => Some(Self::I16),
+            "ushort" | "uint16" => Some(Self::U16),
+            "int" | "int32" => Some(Self::I32),
+            "uint" | "uint32" => Some(Self::U32),
+            "float" | "float32" => Some(Self::F32),
+            "double" | "float64" => Some(Self::F64),
+            _ => None,
+        }
+    }
+    fn size(&self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+    // Integer colors are normalized with the max value of their type
+    fn color_scale(&self) -> f32 {
+        match self {
+            Self::U8 | Self::I8 => 1. / 255.,
+            Self::U16 | Self::I16 => 1. / 65535.,
+            _ => 1.,
+        }
+    }
+}
+
+struct PlyProperty {
+    name: String,
+    data_type: PlyType,
+    // Type of the elements count when the property is a list
+    count_type: Option<PlyType>,
+}
+
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+// Values are read one by one from the file: in ascii format lines are split in tokens on demand
+struct PlyReader<R: BufRead> {
+    reader: R,
+    format: PlyFormat,
+    tokens: VecDeque<String>,
+}
+
+impl<R: BufRead> PlyReader<R> {
+    fn read_header(reader: &mut R) -> Result<(PlyFormat, Vec<PlyElement>), String> {
+        let mut format = None;
+        let mut elements: Vec<PlyElement> = Vec::new();
+        let mut line = String::new();
+        let mut is_first_line = true;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+                return Err("Unexpected end of header".to_string());
+            }
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            if is_first_line {
+                if tokens.first() != Some(&"ply") {
+                    return Err("Not a PLY file".to_string());
+                }
+                is_first_line = false;
+                continue;
+            }
+            match tokens.as_slice() {
+                ["format", "ascii", ..] => format = Some(PlyFormat::Ascii),
+                ["format", "binary_little_endian", ..] => {
+                    format = Some(PlyFormat::BinaryLittleEndian)
+                }
+                ["format", "binary_big_endian", ..] => format = Some(PlyFormat::BinaryBigEndian),
+                ["element", name, count] => elements.push(PlyElement {
+                    name: name.to_string(),
+                    count: count
+                        .parse()
+                        .map_err(|_| format!("Invalid count {count}"))?,
+                    properties: Vec::new(),
+                }),
+                ["property", "list", count_type, data_type, name] => {
+                    let element = elements.last_mut().ok_or("Property without element")?;
+                    element.properties.push(PlyProperty {
+                        name: name.to_string(),
+                        data_type: PlyType::parse(data_type)
+                            .ok_or(format!("Invalid type {data_type}"))?,
+                        count_type: Some(
+                            PlyType::parse(count_type)
+                                .ok_or(format!("Invalid type {count_type}"))?,
+                        ),
+                    });
+                }
+                ["property", data_type, name] => {
+                    let element = elements.last_mut().ok_or("Property without element")?;
+                    element.properties.push(PlyProperty {
+                        name: name.to_string(),
+                        data_type: PlyType::parse(data_type)
+                            .ok_or(format!("Invalid type {data_type}"))?,
+                        count_type: None,
+                    });
+                }
+                ["end_header"] => break,
+                // Comments and obj_info
+                _ => {}
+            }
+        }
+        Ok((format.ok_or("Missing format")?, elements))
+    }
+
+    fn read_value(&mut self, data_type: PlyType) -> Result<f64, String> {
+        if self.format == PlyFormat::Ascii {
+            while self.tokens.is_empty() {
+                let mut line = String::new();
+                if self
+                    .reader
+                    .read_line(&mut line)
+                    .map_err(|e| e.to_string())?
+                    == 0
+                {
+                    return Err("Unexpected end of file".to_string());
+                }
+                self.tokens
+                    .extend(line.split_whitespace().map(String::from));
+            }
+            let token = self.tokens.pop_front().unwrap();
+            return token
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid value {token}"));
+        }
+        let mut bytes = [0u8; 8];
+        let size = data_type.size();
+        self.reader
+            .read_exact(&mut bytes[..size])
+            .map_err(|e| e.to_string())?;
+        if self.format == PlyFormat::BinaryBigEndian {
+            bytes[..size].reverse();
+        }
+        let b = bytes;
+        Ok(match data_type {
+            PlyType::I8 => b[0] as i8 as f64,
+            PlyType::U8 => b[0] as f64,
+            PlyType::I16 => i16::from_le_bytes([b[0], b[1]]) as f64,
+            PlyType::U16 => u16::from_le_bytes([b[0], b[1]]) as f64,
+            PlyType::I32 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            PlyType::U32 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            PlyType::F32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+            PlyType::F64 => f64::from_le_bytes(b),
+        })
+    }
+
+    fn read_list(
+        &mut self,
+        property: &PlyProperty,
+        count_type: PlyType,
+    ) -> Result<Vec<f64>, String> {
+        let count = self.read_value(count_type)? as usize;
+        (0..count)
+            .map(|_| self.read_value(property.data_type))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct PlyCompiler {
+    data_raw_folder: PathBuf,
+    data_folder: PathBuf,
+    mesh_options: MeshOptions,
+}
+
+impl PlyCompiler {
+    pub fn new(data_raw_folder: &Path, data_folder: &Path) -> Self {
+        Self {
+            data_raw_folder: data_raw_folder.to_path_buf(),
+            data_folder: data_folder.to_path_buf(),
+            mesh_options: MeshOptions::default(),
+        }
+    }
+
+    pub fn set_mesh_options(&mut self, mesh_options: &MeshOptions) -> &mut Self {
+        self.mesh_options = mesh_options.clone();
+        self
+    }
+
+    // Vertices and faces are read while streaming the file, other elements are skipped
+    fn read_mesh(
+        path: &Path,
+    ) -> Result<(VertexAttributeLayout, Vec<MeshVertex>, Vec<u32>), String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let file_size = file.metadata().map_err(|e| e.to_string())?.len();
+        let mut reader = BufReader::new(file);
+        let (format, elements) = PlyReader::read_header(&mut reader)?;
+        let mut reader = PlyReader {
+            reader,
+            format,
+            tokens: VecDeque::new(),
+        };
+        let mut vertex_layout = VertexAttributeLayout::HasPosition
+            | VertexAttributeLayout::HasNormal
+            | VertexAttributeLayout::HasTangent;
+        let mut has_normals = false;
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for element in elements.iter() {
+            if element.name == "vertex" {
+                element
+                    .properties
+                    .iter()
+                    .for_each(|p| match p.name.as_str() {
+                        "nx" => has_normals = true,
+                        "red" | "r" | "diffuse_red" => {
+                            vertex_layout |= VertexAttributeLayout::HasColor
+                        }
+                        "s" | "u" | "texture_u" => vertex_layout |= VertexAttributeLayout::HasUV1,
+                        _ => {}
+                    });
+                // Every vertex takes at least a byte: a wrong count can't make it run out of memory
+                vertices.reserve(element.count.min(file_size as usize));
+            }
+            for _ in 0..element.count {
+                let mut vertex = MeshVertex {
+                    pos: Vector4::new(0., 0., 0., 1.),
+                    color: Vector4::default_one(),
+                    ..Default::default()
+                };
+                for property in element.properties.iter() {
+                    if let Some(count_type) = property.count_type {
+                        let values = reader.read_list(property, count_type)?;
+                        if element.name == "face"
+                            && (property.name == "vertex_indices"
+                                || property.name == "vertex_index")
+                        {
+                            if values
+                                .iter()
+                                .any(|&i| i < 0. || i as usize >= vertices.len())
+                            {
+                                return Err("Face with invalid vertex index".to_string());
+                            }
+                            // Polygons are triangulated as a fan around their first vertex
+                            for i in 1..values.len().saturating_sub(1) {
+                                indices.push(values[0] as u32);
+                                indices.push(values[i] as u32);
+                                indices.push(values[i + 1] as u32);
+                            }
+                        }
+                        continue;
+                    }
+                    let value = reader.read_value(property.data_type)? as f32;
+                    if element.name != "vertex" {
+                        continue;
+                    }
+                    let color_scale = property.data_type.color_scale();
+                    match property.name.as_str() {
+                        "x" => vertex.pos.x = value,
+                        "y" => vertex.pos.y = value,
+                        "z" => vertex.pos.z = value,
+                        "nx" => vertex.normal.x = value,
+                        "ny" => vertex.normal.y = value,
+                        "nz" => vertex.normal.z = value,
+                        "red" | "r" | "diffuse_red" => vertex.color.x = value * color_scale,
+                        "green" | "g" | "diffuse_green" => vertex.color.y = value * color_scale,
+                        "blue" | "b" | "diffuse_blue" => vertex.color.z = value * color_scale,
+                        "alpha" | "a" => vertex.color.w = value * color_scale,
+                        "s" | "u" | "texture_u" => vertex.uv_0.x = value,
+                        "t" | "v" | "texture_v" => vertex.uv_0.y = 1. - value,
+                        _ => {}
+                    }
+                }
+                if element.name == "vertex" {
+                    vertex.normal.w = 1.;
+                    vertices.push(vertex);
+                }
+            }
+        }
+        if indices.is_empty() {
+            return Err("No faces found: point clouds are not supported".to_string());
+        }
+        if !has_normals {
+            compute_normals(&mut vertices, &indices);
+        }
+        compute_tangents(&mut vertices, &indices);
+        Ok((vertex_layout, vertices, indices))
+    }
+
+    pub fn process_path(&self, path: &Path) -> bool {
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let scene_path = compute_compiled_path(
+            path,
+            name,
+            SceneData::extension(),
+            "",
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        );
+        if !need_to_binarize(path, scene_path.as_path()) {
+            return true;
+        }
+        let (vertex_layout, vertices, indices) = match Self::read_mesh(path) {
+            Ok(mesh) => mesh,
+            Err(e) => {
+                report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Error,
+                    "",
+                    &format!("Unable to read PLY: {e}"),
+                );
+                return false;
+            }
+        };
+        let material_path = self.create_file(path, &default_material_data(), name, "material");
+        let mesh_options = self
+            .mesh_options
+            .for_asset(path, self.data_raw_folder.as_path());
+        let (mut mesh_data, lods_stats) =
+            build_mesh_data(vertex_layout, &vertices, &indices, &mesh_options);
+        mesh_data.material = material_path;
+        add_asset_mesh_stats(
+            path,
+            COMPILER_NAME,
+            compute_mesh_stats(name, &mesh_data, lods_stats),
+        );
+        let mesh_path = self.create_file(path, &mesh_data, name, "mesh");
+        let object_data = ObjectData {
+            components: vec![mesh_path],
+            ..Default::default()
+        };
+        let object_path = self.create_file(path, &object_data, name, "object");
+        let scene_data = SceneData {
+            objects: vec![object_path],
+            ..Default::default()
+        };
+        self.create_file(path, &scene_data, name, "");
+        true
+    }
+
+    // Returns the local path of the created file
+    fn create_file<T>(&self, path: &Path, data: &T, new_name: &str, folder: &str) -> PathBuf
+    where
+        T: Serialize + SerializeFile + Clone + 'static,
+    {
+        let new_path = create_compiled_file(
+            path,
+            COMPILER_NAME,
+            data,
+            new_name,
+            folder,
+            SerializationType::Binary,
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        );
+        to_local_path(
+            new_path.as_path(),
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        )
+    }
+}
+
+impl ExtensionHandler for PlyCompiler {
+    fn on_changed(&self, path: &Path) {
+        if let Some(ext) = path.extension() {
+            if ext.to_str().unwrap() == PLY_EXTENSION
+                && begin_asset(
+                    path,
+                    COMPILER_NAME,
+                    COMPILER_VERSION,
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                )
+            {
+                if self.process_path(path) {
+                    end_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                } else {
+                    abort_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                }
+            }
+        }
+    }
+}
@@ -3,23 +3,29 @@
 use std::{
     env,
     fs::create_dir_all,
-    io::Read,
     path::{Path, PathBuf},
     process::Command,
 };
 
-use crate::{clean_unused_definitions, need_to_binarize, send_reloaded_event, ExtensionHandler};
+use crate::{
+    abort_asset, add_asset_dependency, begin_asset, clean_unused_definitions, end_asset,
+    need_to_binarize, report_asset_diagnostic, send_reloaded_event,
+    shader_preprocessor::{keyword_permutations, PreprocessedShader, ShaderPreprocessor},
+    shader_reflection::reflect_bindings,
+    track_asset_output, ExtensionHandler,
+};
 use inox_filesystem::{convert_from_local_path, delete_file};
 use inox_log::debug_log;
 use inox_messenger::MessageHubRc;
 use inox_platform::PlatformType;
 use inox_render::{
-    platform::shader_preprocessor_defs, read_spirv_from_bytes, ShaderData, SHADER_EXTENSION,
+    merge_shader_bindings, platform::shader_preprocessor_defs, read_spirv_from_bytes,
+    ShaderBinding, ShaderData, ShaderVariantData, ShaderVariantKey, ENVIRONMENT_ROUGHNESS_LEVELS,
+    SHADER_EXTENSION,
 };
-use inox_resources::SharedDataRc;
+use inox_resources::{DiagnosticSeverity, SharedDataRc};
 use inox_serialize::{SerializationType, SerializeFile};
 use inox_uid::generate_random_uid;
-use regex::Regex;
 
 const DEBUG_SHADER_GENERATED_CODE: bool = false;
 
@@ -31,6 +37,9 @@ const SPV_EXTENSION: &str = "spv";
 const VERTEX_SHADER_EXTENSION: &str = "vert";
 const FRAGMENT_SHADER_EXTENSION: &str = "frag";
 const GEOMETRY_SHADER_EXTENSION: &str = "geom";
+const COMPILER_NAME: &str = "shader";
+const COMPILER_VERSION: u32 = 5;
+const MAX_SHADER_VARIANTS: usize = 64;
 
 pub struct ShaderCompiler<const PLATFORM_TYPE: PlatformType> {
     shared_data: SharedDataRc,
@@ -141,7 +150,10 @@ impl<const PLATFORM_TYPE: PlatformType> ShaderCompiler<PLATFORM_TYPE> {
             from_source_to_compiled.replace(source_ext.as_str(), destination_ext.as_str());
         let temp_path = PathBuf::from(from_source_to_temp);
         let new_path = PathBuf::from(from_source_to_compiled);
-        if need_to_binarize(path, new_path.as_path()) {
+        if !need_to_binarize(path, new_path.as_path()) {
+            return true;
+        }
+        {
             debug_log!("Serializing {:?}", path);
             if let Ok(mut command) = Command::new(self.glsl_validator.to_str().unwrap())
                 .args([
@@ -174,6 +186,7 @@ impl<const PLATFORM_TYPE: PlatformType> ShaderCompiler<PLATFORM_TYPE> {
                         debug_assert!(result.is_ok());
                     }
                     shader_data.save_to_file(new_path.as_path(), SerializationType::Binary);
+                    track_asset_output(path, COMPILER_NAME, new_path.as_path());
                     send_reloaded_event(&self.message_hub, new_path.as_path());
                 }
                 delete_file(temp_path);
@@ -194,25 +207,45 @@ impl<const PLATFORM_TYPE: PlatformType> ShaderCompiler<PLATFORM_TYPE> {
             from_source_to_preprocessed.replace(source_ext.as_str(), destination_ext.as_str());
         let new_path = PathBuf::from(from_source_to_preprocessed);
 
-        let mut file = std::fs::File::open(path.to_str().unwrap()).unwrap();
-        let mut data = Vec::new();
-        file.read_to_end(&mut data).unwrap();
-        let shader_code = String::from_utf8(data).unwrap();
-        let preprocessed_code = Self::preprocess_code(&path, shader_code);
-
-        let preprocessed_code = Self::remove_comments(&preprocessed_code);
-        let preprocessed_code = clean_unused_definitions(&preprocessed_code).unwrap();
+        let Some(shader) = self.preprocess_code(&path, &[]) else {
+            return;
+        };
+        let preprocessed_code =
+            clean_unused_definitions(&shader.code).unwrap_or_else(|_| shader.code.clone());
 
         debug_log!("into shader {:?}", new_path);
         std::fs::write(&new_path, &preprocessed_code).unwrap();
 
-        if self.validate_shader(&preprocessed_code, &path, &new_path) {
+        if self
+            .validate_shader(&shader, &[], &path, &new_path)
+            .is_some()
+        {
             debug_log!("and it's validated as well");
         }
     }
 
-    fn validate_shader(&self, preprocessed_code: &str, path: &Path, new_path: &Path) -> bool {
-        let mut validated = false;
+    // Errors point at the file and line the preprocessed code comes from.
+    // Returns the bindings reflected from a valid shader
+    fn validate_shader(
+        &self,
+        shader: &PreprocessedShader,
+        keywords: &[String],
+        path: &Path,
+        new_path: &Path,
+    ) -> Option<Vec<ShaderBinding>> {
+        let mut bindings = None;
+        let preprocessed_code = shader.code.as_str();
+        let variant = if keywords.is_empty() {
+            String::new()
+        } else {
+            format!(" variant {keywords:?}")
+        };
+        let source_location = |location: Option<naga::SourceLocation>| {
+            location
+                .and_then(|l| shader.source_map.location(l.line_number))
+                .map(|l| l.to_string())
+                .unwrap_or_default()
+        };
 
         let result = naga::front::wgsl::parse_str(preprocessed_code);
         match result {
@@ -264,29 +297,42 @@ impl<const PLATFORM_TYPE: PlatformType> ShaderCompiler<PLATFORM_TYPE> {
                             });
                         }
 
-                        validated = true;
+                        bindings = Some(reflect_bindings(&module, &info));
                     }
                     Err(e) => {
-                        println!(
-                            "Unable to compile shader {path:?} with error: \n{}",
-                            e.emit_to_string(preprocessed_code),
+                        report_asset_diagnostic(
+                            path,
+                            COMPILER_NAME,
+                            DiagnosticSeverity::Error,
+                            &source_location(e.location(preprocessed_code)),
+                            &format!(
+                                "Unable to validate shader{variant}:\n{}",
+                                e.emit_to_string(preprocessed_code)
+                            ),
                         );
                     }
                 };
             }
             Err(ref e) => {
-                println!(
-                    "Unable to compile shader {path:?} with error: \n{}\n{}",
-                    e.message(),
-                    e.emit_to_string(preprocessed_code),
+                report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Error,
+                    &source_location(e.location(preprocessed_code)),
+                    &format!(
+                        "Unable to parse shader{variant}: {}\n{}",
+                        e.message(),
+                        e.emit_to_string(preprocessed_code)
+                    ),
                 );
             }
         }
 
-        validated
+        bindings
     }
 
-    fn create_wgsl_shader_data(&self, path: &Path) {
+    // Returns false when the shader is not valid
+    fn create_wgsl_shader_data(&self, path: &Path) -> bool {
         let extension = path.extension().unwrap().to_str().unwrap();
         let source_ext = format!(".{extension}");
         let destination_ext = format!(".{SHADER_EXTENSION}");
@@ -303,108 +349,157 @@ impl<const PLATFORM_TYPE: PlatformType> ShaderCompiler<PLATFORM_TYPE> {
             from_source_to_compiled.replace(source_ext.as_str(), destination_ext.as_str());
         let new_path = PathBuf::from(from_source_to_compiled);
 
-        if need_to_binarize(path, new_path.as_path()) {
-            debug_log!("Serializing {:?}", path);
-            let mut file = std::fs::File::open(path.to_str().unwrap()).unwrap();
-            let mut data = Vec::new();
-            file.read_to_end(&mut data).unwrap();
-            let shader_code = String::from_utf8(data).unwrap();
-            let mut preprocessed_code = Self::preprocess_code(path, shader_code);
-            preprocessed_code = Self::remove_comments(&preprocessed_code);
-
-            let result = clean_unused_definitions(&preprocessed_code);
-            match result {
-                Ok(code) => {
-                    preprocessed_code = code;
-                }
-                Err(e) => {
-                    println!("Unable to optimize shader {path:?} with error: \n{}", e,);
-                }
+        if !need_to_binarize(path, new_path.as_path()) {
+            return true;
+        }
+        debug_log!("Serializing {:?}", path);
+        let Some(shader) = self.preprocess_code(path, &[]) else {
+            return false;
+        };
+        let keyword_sets = shader.keyword_sets.clone();
+        let variants = if keyword_sets.is_empty() {
+            vec![(Vec::new(), shader)]
+        } else {
+            let permutations = keyword_permutations(&keyword_sets);
+            if permutations.len() > MAX_SHADER_VARIANTS {
+                report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Error,
+                    "",
+                    &format!(
+                        "Too many shader variants: {} while max is {MAX_SHADER_VARIANTS}",
+                        permutations.len()
+                    ),
+                );
+                return false;
             }
-
-            if self.validate_shader(&preprocessed_code, path, &new_path) {
-                let shader_data = ShaderData {
-                    wgsl_code: preprocessed_code,
-                    ..Default::default()
+            let mut variants = Vec::new();
+            for keywords in permutations {
+                let Some(variant) = self.preprocess_code(path, &keywords) else {
+                    return false;
                 };
-                if !new_path.exists() {
-                    let result = create_dir_all(new_path.parent().unwrap());
-                    debug_assert!(result.is_ok());
-                }
-                shader_data.save_to_file(new_path.as_path(), SerializationType::Binary);
-                send_reloaded_event(&self.message_hub, new_path.as_path());
+                variants.push((keywords, variant));
+            }
+            variants
+        };
+
+        // First variant is the default one
+        let mut shader_data = ShaderData {
+            keyword_sets,
+            ..Default::default()
+        };
+        for (i, (keywords, variant)) in variants.into_iter().enumerate() {
+            let Some(bindings) = self.validate_shader(&variant, &keywords, path, &new_path) else {
+                return false;
+            };
+            if !merge_shader_bindings(&mut shader_data.bindings, &bindings) {
+                report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Warning,
+                    "",
+                    &format!(
+                        "Variant {keywords:?} declares other resources with the same bindings"
+                    ),
+                );
+            }
+            let wgsl_code = Self::clean_code(path, variant.code);
+            if i == 0 {
+                shader_data.wgsl_code = wgsl_code;
+            } else {
+                shader_data.variants.push(ShaderVariantData {
+                    key: ShaderVariantKey::new(&keywords),
+                    wgsl_code,
+                });
             }
         }
+        if !new_path.exists() {
+            let result = create_dir_all(new_path.parent().unwrap());
+            debug_assert!(result.is_ok());
+        }
+        shader_data.save_to_file(new_path.as_path(), SerializationType::Binary);
+        track_asset_output(path, COMPILER_NAME, new_path.as_path());
+        send_reloaded_event(&self.message_hub, new_path.as_path());
+        true
     }
 
-    fn remove_comments(code: &str) -> String {
-        let single_line_comment = Regex::new(r"//.*").unwrap();
-        let multi_line_comment = Regex::new(r"/\*.*?\*/").unwrap();
-        let code = single_line_comment.replace_all(code, "");
-        let code = multi_line_comment.replace_all(&code, "");
-
-        code.lines()
-            .filter(|line| !line.trim().is_empty())
-            .map(|line| line.trim())
-            .collect::<Vec<_>>()
-            .join("\n")
+    fn clean_code(path: &Path, code: String) -> String {
+        match clean_unused_definitions(&code) {
+            Ok(code) => code,
+            Err(e) => {
+                report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Warning,
+                    "",
+                    &format!("Unable to remove unused definitions: {e}"),
+                );
+                code
+            }
+        }
     }
 
-    fn preprocess_code(path: &Path, code: String) -> String {
-        let available_defs = shader_preprocessor_defs::<PLATFORM_TYPE>();
-        let mut string = String::new();
-        let ifdef_regex = Regex::new(r"^\s*#\s*ifdef\s*([\w|\d|_]+)").unwrap();
-        let else_regex = Regex::new(r"^\s*#\s*else").unwrap();
-        let endif_regex = Regex::new(r"^\s*#\s*endif").unwrap();
-        let import_regex = Regex::new(r"^\s*#\s*import").unwrap();
-        let mut should_skip = false;
-        for line in code.lines() {
-            if let Some(cap) = ifdef_regex.captures(line) {
-                let def = cap.get(1).unwrap().as_str().to_string();
-                should_skip = !available_defs.contains(&def);
-                continue;
-            } else if else_regex.is_match(line) {
-                should_skip = !should_skip;
-                continue;
-            } else if endif_regex.is_match(line) {
-                should_skip = false;
-                continue;
-            } else if import_regex.is_match(line) {
-                if let Some(first) = line.find('"') {
-                    if let Some(end) = line.rfind('"') {
-                        let import_path = line[first + 1..end].to_string();
-                        let import_path = PathBuf::from(path.parent().unwrap()).join(import_path);
-                        let import_path = import_path.canonicalize().unwrap();
-                        let shader_code = std::fs::read_to_string(import_path).unwrap();
-                        let import_code = Self::preprocess_code(path, shader_code);
-                        if !import_code.trim().is_empty() {
-                            string.push_str(&import_code);
-                        }
-                        continue;
-                    }
-                }
+    // Imported files are dependencies of the shader, so it's compiled again when they change
+    fn preprocess_code(&self, path: &Path, keywords: &[String]) -> Option<PreprocessedShader> {
+        let mut defines = shader_preprocessor_defs::<PLATFORM_TYPE>();
+        // Constants shared with the Rust code, so that they have a single source
+        defines.push(format!(
+            "ENVIRONMENT_ROUGHNESS_LEVELS={ENVIRONMENT_ROUGHNESS_LEVELS}u"
+        ));
+        defines.extend_from_slice(keywords);
+        match ShaderPreprocessor::new(&defines).preprocess(path) {
+            Ok(shader) => {
+                shader
+                    .dependencies
+                    .iter()
+                    .for_each(|d| add_asset_dependency(path, COMPILER_NAME, d));
+                Some(shader)
             }
-            if !should_skip && !line.trim().is_empty() {
-                string.push_str(line);
-                string.push('\n');
+            Err(e) => {
+                report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Error,
+                    &e.location.to_string(),
+                    &format!("Unable to preprocess shader: {}", e.message),
+                );
+                None
             }
         }
-        string
     }
 }
 
 impl<const PLATFORM_TYPE: PlatformType> ExtensionHandler for ShaderCompiler<PLATFORM_TYPE> {
-    fn on_changed(&mut self, path: &Path) {
+    fn on_changed(&self, path: &Path) {
         if let Some(ext) = path.extension() {
-            match ext.to_str().unwrap().to_string().as_str() {
-                WGSL_EXTENSION => {
-                    self.create_wgsl_shader_data(path);
-                }
+            let extension = ext.to_str().unwrap().to_string();
+            let is_shader = [
+                WGSL_EXTENSION,
+                VERTEX_SHADER_EXTENSION,
+                GEOMETRY_SHADER_EXTENSION,
+                FRAGMENT_SHADER_EXTENSION,
+            ]
+            .contains(&extension.as_str());
+            if !is_shader
+                || !begin_asset(
+                    path,
+                    COMPILER_NAME,
+                    COMPILER_VERSION,
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                )
+            {
+                return;
+            }
+            let is_compiled = match extension.as_str() {
+                WGSL_EXTENSION => self.create_wgsl_shader_data(path),
                 VERTEX_SHADER_EXTENSION => {
                     let result = self.convert_in_spirv(path);
                     if !result {
                         eprintln!("Failed to process VERTEX shader {}", path.to_str().unwrap());
                     }
+                    result
                 }
                 GEOMETRY_SHADER_EXTENSION => {
                     let result = self.convert_in_spirv(path);
@@ -414,6 +509,7 @@ impl<const PLATFORM_TYPE: PlatformType> ExtensionHandler for ShaderCompiler<PLAT
                             path.to_str().unwrap(),
                         );
                     }
+                    result
                 }
                 FRAGMENT_SHADER_EXTENSION => {
                     let result = self.convert_in_spirv(path);
@@ -423,8 +519,24 @@ impl<const PLATFORM_TYPE: PlatformType> ExtensionHandler for ShaderCompiler<PLAT
                             path.to_str().unwrap(),
                         );
                     }
+                    result
                 }
-                _ => {}
+                _ => false,
+            };
+            if is_compiled {
+                end_asset(
+                    path,
+                    COMPILER_NAME,
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                );
+            } else {
+                abort_asset(
+                    path,
+                    COMPILER_NAME,
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                );
             }
         }
     }
@@ -0,0 +1,267 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    abort_asset, add_asset_mesh_stats, begin_asset, compute_compiled_path, create_compiled_file,
+    end_asset,
+    mesh::{
+        build_mesh_data, compute_mesh_stats, compute_tangents, default_material_data, MeshOptions,
+        MeshVertex,
+    },
+    need_to_binarize, report_asset_diagnostic, to_local_path, ExtensionHandler,
+};
+use inox_math::{VecBaseFloat, Vector3};
+use inox_render::VertexAttributeLayout;
+use inox_resources::DiagnosticSeverity;
+use inox_scene::{ObjectData, SceneData};
+use inox_serialize::{SerializationType, Serialize, SerializeFile};
+
+const STL_EXTENSION: &str = "stl";
+const COMPILER_NAME: &str = "stl";
+const COMPILER_VERSION: u32 = 2;
+const STL_HEADER_SIZE: u64 = 84;
+const STL_TRIANGLE_SIZE: u64 = 50;
+
+// CAD meshes have hard edges: vertices are shared only by adjacent triangles on the same plane
+#[derive(Default)]
+struct StlMesh {
+    vertices: Vec<MeshVertex>,
+    indices: Vec<u32>,
+    vertices_map: HashMap<[u32; 6], u32>,
+}
+
+impl StlMesh {
+    fn add_triangle(&mut self, positions: [Vector3; 3]) {
+        let n = (positions[1] - positions[0]).cross(positions[2] - positions[0]);
+        if n.length() <= 0. {
+            return;
+        }
+        let n = n.normalized();
+        positions.iter().for_each(|p| {
+            let key = [
+                p.x.to_bits(),
+                p.y.to_bits(),
+                p.z.to_bits(),
+                n.x.to_bits(),
+                n.y.to_bits(),
+                n.z.to_bits(),
+            ];
+            let vertices = &mut self.vertices;
+            let i = *self.vertices_map.entry(key).or_insert_with(|| {
+                vertices.push(MeshVertex {
+                    pos: p.extend(1.),
+                    normal: n.extend(1.),
+                    ..Default::default()
+                });
+                (vertices.len() - 1) as u32
+            });
+            self.indices.push(i);
+        });
+    }
+}
+
+#[derive(Default)]
+pub struct StlCompiler {
+    data_raw_folder: PathBuf,
+    data_folder: PathBuf,
+    mesh_options: MeshOptions,
+}
+
+impl StlCompiler {
+    pub fn new(data_raw_folder: &Path, data_folder: &Path) -> Self {
+        Self {
+            data_raw_folder: data_raw_folder.to_path_buf(),
+            data_folder: data_folder.to_path_buf(),
+            mesh_options: MeshOptions::default(),
+        }
+    }
+
+    pub fn set_mesh_options(&mut self, mesh_options: &MeshOptions) -> &mut Self {
+        self.mesh_options = mesh_options.clone();
+        self
+    }
+
+    // Binary files are recognized by their size: ascii ones could start with "solid" too.
+    // Some exporters add data after the triangles, so larger files are binary when they
+    // don't start with "solid"
+    fn read_mesh(path: &Path) -> Result<StlMesh, String> {
+        let mut file = File::open(path).map_err(|e| e.to_string())?;
+        let file_size = file.metadata().map_err(|e| e.to_string())?.len();
+        let mut header = [0u8; STL_HEADER_SIZE as usize];
+        let mut is_binary = false;
+        let mut triangles_count = 0;
+        if file.read_exact(&mut header).is_ok() {
+            triangles_count = u32::from_le_bytes([header[80], header[81], header[82], header[83]]);
+            let size = STL_HEADER_SIZE + STL_TRIANGLE_SIZE * triangles_count as u64;
+            is_binary = file_size == size || (file_size > size && !header.starts_with(b"solid"));
+        }
+        let mut mesh = StlMesh::default();
+        if is_binary {
+            let mut reader = BufReader::new(file);
+            let mut triangle = [0u8; STL_TRIANGLE_SIZE as usize];
+            for _ in 0..triangles_count {
+                reader
+                    .read_exact(&mut triangle)
+                    .map_err(|e| e.to_string())?;
+                // Normal is computed from the vertices: the stored one is often wrong
+                let value = |i: usize| {
+                    let offset = 12 + i * 4;
+                    f32::from_le_bytes([
+                        triangle[offset],
+                        triangle[offset + 1],
+                        triangle[offset + 2],
+                        triangle[offset + 3],
+                    ])
+                };
+                let vertex =
+                    |v: usize| Vector3::new(value(v * 3), value(v * 3 + 1), value(v * 3 + 2));
+                mesh.add_triangle([vertex(0), vertex(1), vertex(2)]);
+            }
+        } else {
+            file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+            let reader = BufReader::new(file);
+            let mut polygon = Vec::new();
+            for line in reader.lines() {
+                let line = line.map_err(|e| e.to_string())?;
+                let mut tokens = line.split_whitespace();
+                match tokens.next() {
+                    Some("vertex") => {
+                        let values = tokens
+                            .map(|t| t.parse::<f32>().map_err(|_| format!("Invalid value {t}")))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        if values.len() < 3 {
+                            return Err("Invalid vertex".to_string());
+                        }
+                        polygon.push(Vector3::new(values[0], values[1], values[2]));
+                    }
+                    Some("endloop") => {
+                        // Loops with more than 3 vertices are triangulated as a fan
+                        for i in 1..polygon.len().saturating_sub(1) {
+                            mesh.add_triangle([polygon[0], polygon[i], polygon[i + 1]]);
+                        }
+                        polygon.clear();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if mesh.indices.is_empty() {
+            return Err("No triangles found".to_string());
+        }
+        compute_tangents(&mut mesh.vertices, &mesh.indices);
+        Ok(mesh)
+    }
+
+    pub fn process_path(&self, path: &Path) -> bool {
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let scene_path = compute_compiled_path(
+            path,
+            name,
+            SceneData::extension(),
+            "",
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        );
+        if !need_to_binarize(path, scene_path.as_path()) {
+            return true;
+        }
+        let mesh = match Self::read_mesh(path) {
+            Ok(mesh) => mesh,
+            Err(e) => {
+                report_asset_diagnostic(
+                    path,
+                    COMPILER_NAME,
+                    DiagnosticSeverity::Error,
+                    "",
+                    &format!("Unable to read STL: {e}"),
+                );
+                return false;
+            }
+        };
+        let vertex_layout = VertexAttributeLayout::HasPosition
+            | VertexAttributeLayout::HasNormal
+            | VertexAttributeLayout::HasTangent;
+        let material_path = self.create_file(path, &default_material_data(), name, "material");
+        let mesh_options = self
+            .mesh_options
+            .for_asset(path, self.data_raw_folder.as_path());
+        let (mut mesh_data, lods_stats) =
+            build_mesh_data(vertex_layout, &mesh.vertices, &mesh.indices, &mesh_options);
+        mesh_data.material = material_path;
+        add_asset_mesh_stats(
+            path,
+            COMPILER_NAME,
+            compute_mesh_stats(name, &mesh_data, lods_stats),
+        );
+        let mesh_path = self.create_file(path, &mesh_data, name, "mesh");
+        let object_data = ObjectData {
+            components: vec![mesh_path],
+            ..Default::default()
+        };
+        let object_path = self.create_file(path, &object_data, name, "object");
+        let scene_data = SceneData {
+            objects: vec![object_path],
+            ..Default::default()
+        };
+        self.create_file(path, &scene_data, name, "");
+        true
+    }
+
+    // Returns the local path of the created file
+    fn create_file<T>(&self, path: &Path, data: &T, new_name: &str, folder: &str) -> PathBuf
+    where
+        T: Serialize + SerializeFile + Clone + 'static,
+    {
+        let new_path = create_compiled_file(
+            path,
+            COMPILER_NAME,
+            data,
+            new_name,
+            folder,
+            SerializationType::Binary,
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        );
+        to_local_path(
+            new_path.as_path(),
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+        )
+    }
+}
+
+impl ExtensionHandler for StlCompiler {
+    fn on_changed(&self, path: &Path) {
+        if let Some(ext) = path.extension() {
+            if ext.to_str().unwrap() == STL_EXTENSION
+                && begin_asset(
+                    path,
+                    COMPILER_NAME,
+                    COMPILER_VERSION,
+                    self.data_raw_folder.as_path(),
+                    self.data_folder.as_path(),
+                )
+            {
+                if self.process_path(path) {
+                    end_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                } else {
+                    abort_asset(
+                        path,
+                        COMPILER_NAME,
+                        self.data_raw_folder.as_path(),
+                        self.data_folder.as_path(),
+                    );
+                }
+            }
+        }
+    }
+}
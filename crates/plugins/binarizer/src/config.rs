@@ -1,11 +1,23 @@
+use inox_render::FontOptions;
 use inox_resources::ConfigBase;
 use inox_serialize::{Deserialize, Serialize, SerializeFile};
 
-#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+use crate::{LightmapOptions, MeshletOptions, MeshletOptionsOverride};
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(crate = "inox_serialize")]
 pub struct Config {
     pub optimize_meshes: bool,
     pub end_on_completion: bool,
+    #[serde(default)]
+    pub font: FontOptions,
+    #[serde(default)]
+    pub meshlets: MeshletOptions,
+    // Meshlet options of the assets matching a glob, the last matching one wins
+    #[serde(default)]
+    pub meshlets_overrides: Vec<MeshletOptionsOverride>,
+    #[serde(default)]
+    pub lightmaps: LightmapOptions,
 }
 
 impl SerializeFile for Config {
@@ -0,0 +1,477 @@
+use std::f32::consts::PI;
+
+use image::Rgb32FImage;
+use inox_math::quantize_half;
+use inox_render::{
+    CompiledTextureData, TextureFormat, ENVIRONMENT_ROUGHNESS_LEVELS, ENVIRONMENT_SH_COEFFICIENTS,
+};
+
+// Smallest roughness level has to be at least one texel wide
+const MIN_FACE_SIZE: u32 = 1 << (ENVIRONMENT_ROUGHNESS_LEVELS - 1);
+const MAX_FACE_SIZE: u32 = 256;
+const SPECULAR_SAMPLES: u32 = 64;
+const LUT_SAMPLES: u32 = 256;
+pub const LUT_SIZE: u32 = 128;
+
+type Color = [f32; 3];
+
+fn dot(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: &[f32; 3], b: &[f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = dot(&v, &v).sqrt();
+    if length <= f32::EPSILON {
+        return [0., 0., 1.];
+    }
+    v.map(|c| c / length)
+}
+
+fn hammersley(i: u32, count: u32) -> (f32, f32) {
+    (
+        (i as f32 + 0.5) / count as f32,
+        i.reverse_bits() as f32 / 4_294_967_296.,
+    )
+}
+
+// Half vector around the z axis distributed as the GGX normals
+fn importance_sample_ggx(u: (f32, f32), alpha: f32) -> [f32; 3] {
+    let phi = 2. * PI * u.0;
+    let cos_theta = ((1. - u.1) / (1. + (alpha * alpha - 1.) * u.1)).sqrt();
+    let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+    [sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta]
+}
+
+fn d_ggx(n_dot_h: f32, alpha: f32) -> f32 {
+    let alpha_sq = alpha * alpha;
+    let f = (n_dot_h * n_dot_h) * (alpha_sq - 1.) + 1.;
+    alpha_sq / (PI * f * f)
+}
+
+// Same visibility used by the shaders
+fn v_ggx(n_dot_l: f32, n_dot_v: f32, alpha: f32) -> f32 {
+    let alpha_sq = alpha * alpha;
+    let ggx_v = n_dot_l * (n_dot_v * n_dot_v * (1. - alpha_sq) + alpha_sq).sqrt();
+    let ggx_l = n_dot_v * (n_dot_l * n_dot_l * (1. - alpha_sq) + alpha_sq).sqrt();
+    let ggx = ggx_v + ggx_l;
+    if ggx > 0. {
+        0.5 / ggx
+    } else {
+        0.
+    }
+}
+
+fn d_charlie(sheen_roughness: f32, n_dot_h: f32) -> f32 {
+    let alpha = sheen_roughness.max(0.000001).powi(2);
+    let inv_r = 1. / alpha;
+    let sin2h = 1. - n_dot_h * n_dot_h;
+    (2. + inv_r) * sin2h.max(0.).powf(inv_r * 0.5) / (2. * PI)
+}
+
+fn v_ashikhmin(n_dot_l: f32, n_dot_v: f32) -> f32 {
+    (1. / (4. * (n_dot_l + n_dot_v - n_dot_l * n_dot_v))).clamp(0., 1.)
+}
+
+// Inverse of the face selection done by the shaders
+fn face_direction(face: usize, u: f32, v: f32) -> [f32; 3] {
+    let a = 2. * u - 1.;
+    let b = 2. * v - 1.;
+    normalize(match face {
+        0 => [1., -b, -a],
+        1 => [-1., -b, a],
+        2 => [a, 1., b],
+        3 => [a, -1., -b],
+        4 => [a, -b, 1.],
+        _ => [-a, -b, -1.],
+    })
+}
+
+fn direction_to_face(direction: &[f32; 3]) -> (usize, f32, f32) {
+    let [x, y, z] = *direction;
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+    let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+        if x > 0. {
+            (0, -z, -y, ax)
+        } else {
+            (1, z, -y, ax)
+        }
+    } else if ay >= az {
+        if y > 0. {
+            (2, x, z, ay)
+        } else {
+            (3, x, -z, ay)
+        }
+    } else if z > 0. {
+        (4, x, -y, az)
+    } else {
+        (5, -x, -y, az)
+    };
+    let ma = ma.max(f32::EPSILON);
+    (face, (sc / ma + 1.) * 0.5, (tc / ma + 1.) * 0.5)
+}
+
+// Solid angle of the texel at (x, y) of a face of the given size
+fn texel_solid_angle(size: u32, x: u32, y: u32) -> f32 {
+    let area = |x: f32, y: f32| (x * y).atan2((x * x + y * y + 1.).sqrt());
+    let texel = 2. / size as f32;
+    let x0 = x as f32 * texel - 1.;
+    let y0 = y as f32 * texel - 1.;
+    let (x1, y1) = (x0 + texel, y0 + texel);
+    area(x0, y0) - area(x0, y1) - area(x1, y0) + area(x1, y1)
+}
+
+struct Equirectangular<'a> {
+    image: &'a Rgb32FImage,
+}
+
+impl Equirectangular<'_> {
+    fn pixel(&self, x: i64, y: i64) -> Color {
+        let (width, height) = (self.image.width() as i64, self.image.height() as i64);
+        let x = x.rem_euclid(width) as u32;
+        let y = y.clamp(0, height - 1) as u32;
+        self.image.get_pixel(x, y).0
+    }
+
+    // Same mapping used by the shaders when sampling an equirectangular map
+    fn sample(&self, direction: &[f32; 3]) -> Color {
+        let u = (direction[2].atan2(direction[0]) + PI) / (2. * PI);
+        let v = direction[1].clamp(-1., 1.).acos() / PI;
+        let x = u * self.image.width() as f32 - 0.5;
+        let y = v * self.image.height() as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let mut color = [0.; 3];
+        [
+            (0, 0, (1. - fx) * (1. - fy)),
+            (1, 0, fx * (1. - fy)),
+            (0, 1, (1. - fx) * fy),
+            (1, 1, fx * fy),
+        ]
+        .iter()
+        .for_each(|(i, j, w)| {
+            let p = self.pixel(x0 + i, y0 + j);
+            (0..3).for_each(|c| color[c] += p[c] * w);
+        });
+        color
+    }
+}
+
+struct CubeMap {
+    size: u32,
+    faces: Vec<Vec<Color>>,
+}
+
+impl CubeMap {
+    fn from_equirectangular(equirectangular: &Equirectangular, size: u32) -> Self {
+        // Each texel averages 2x2 samples to reduce aliasing of bigger source images
+        let faces = (0..6)
+            .map(|face| {
+                let mut pixels = Vec::with_capacity((size * size) as usize);
+                for y in 0..size {
+                    for x in 0..size {
+                        let mut color = [0.; 3];
+                        [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+                            .iter()
+                            .for_each(|(i, j)| {
+                                let u = (x as f32 + i) / size as f32;
+                                let v = (y as f32 + j) / size as f32;
+                                let p = equirectangular.sample(&face_direction(face, u, v));
+                                (0..3).for_each(|c| color[c] += p[c] * 0.25);
+                            });
+                        pixels.push(color);
+                    }
+                }
+                pixels
+            })
+            .collect();
+        Self { size, faces }
+    }
+
+    fn downsample(&self) -> Self {
+        let size = (self.size / 2).max(1);
+        let faces = self
+            .faces
+            .iter()
+            .map(|pixels| {
+                let mut result = Vec::with_capacity((size * size) as usize);
+                for y in 0..size {
+                    for x in 0..size {
+                        let mut color = [0.; 3];
+                        [(0, 0), (1, 0), (0, 1), (1, 1)].iter().for_each(|(i, j)| {
+                            let px = (x * 2 + i).min(self.size - 1);
+                            let py = (y * 2 + j).min(self.size - 1);
+                            let p = pixels[(py * self.size + px) as usize];
+                            (0..3).for_each(|c| color[c] += p[c] * 0.25);
+                        });
+                        result.push(color);
+                    }
+                }
+                result
+            })
+            .collect();
+        Self { size, faces }
+    }
+
+    // Bilinear filtering clamped inside the face
+    fn sample(&self, direction: &[f32; 3]) -> Color {
+        let (face, u, v) = direction_to_face(direction);
+        let max = (self.size - 1) as f32;
+        let x = (u * self.size as f32 - 0.5).clamp(0., max);
+        let y = (v * self.size as f32 - 0.5).clamp(0., max);
+        let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+        let (x1, y1) = ((x0 + 1).min(self.size - 1), (y0 + 1).min(self.size - 1));
+        let (fx, fy) = (x.fract(), y.fract());
+        let pixels = &self.faces[face];
+        let mut color = [0.; 3];
+        [
+            (x0, y0, (1. - fx) * (1. - fy)),
+            (x1, y0, fx * (1. - fy)),
+            (x0, y1, (1. - fx) * fy),
+            (x1, y1, fx * fy),
+        ]
+        .iter()
+        .for_each(|(px, py, w)| {
+            let p = pixels[(py * self.size + px) as usize];
+            (0..3).for_each(|c| color[c] += p[c] * w);
+        });
+        color
+    }
+}
+
+fn sh_basis(direction: &[f32; 3]) -> [f32; ENVIRONMENT_SH_COEFFICIENTS] {
+    let [x, y, z] = *direction;
+    [
+        0.282095,
+        0.488603 * y,
+        0.488603 * z,
+        0.488603 * x,
+        1.092548 * x * y,
+        1.092548 * y * z,
+        0.315392 * (3. * z * z - 1.),
+        1.092548 * x * z,
+        0.546274 * (x * x - y * y),
+    ]
+}
+
+// Radiance projected on L2 spherical harmonics and convolved with the cosine lobe.
+// Bands are divided by PI so that shaders get directly the lambertian irradiance
+fn compute_irradiance_sh(cube_map: &CubeMap) -> [[f32; 4]; ENVIRONMENT_SH_COEFFICIENTS] {
+    const BAND_FACTORS: [f32; ENVIRONMENT_SH_COEFFICIENTS] =
+        [1., 2. / 3., 2. / 3., 2. / 3., 0.25, 0.25, 0.25, 0.25, 0.25];
+    let mut coefficients = [[0f32; 4]; ENVIRONMENT_SH_COEFFICIENTS];
+    let mut total_weight = 0.;
+    let size = cube_map.size;
+    cube_map
+        .faces
+        .iter()
+        .enumerate()
+        .for_each(|(face, pixels)| {
+            for y in 0..size {
+                for x in 0..size {
+                    let u = (x as f32 + 0.5) / size as f32;
+                    let v = (y as f32 + 0.5) / size as f32;
+                    let basis = sh_basis(&face_direction(face, u, v));
+                    let weight = texel_solid_angle(size, x, y);
+                    let color = pixels[(y * size + x) as usize];
+                    total_weight += weight;
+                    coefficients
+                        .iter_mut()
+                        .enumerate()
+                        .for_each(|(i, coefficient)| {
+                            (0..3).for_each(|c| coefficient[c] += color[c] * basis[i] * weight);
+                        });
+                }
+            }
+        });
+    // Texels solid angles have to cover the whole sphere
+    let normalization = 4. * PI / total_weight;
+    coefficients
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, coefficient)| {
+            (0..3).for_each(|c| coefficient[c] *= normalization * BAND_FACTORS[i]);
+        });
+    coefficients
+}
+
+pub fn evaluate_irradiance_sh(
+    coefficients: &[[f32; 4]; ENVIRONMENT_SH_COEFFICIENTS],
+    normal: &[f32; 3],
+) -> Color {
+    let basis = sh_basis(normal);
+    let mut color = [0.; 3];
+    coefficients
+        .iter()
+        .enumerate()
+        .for_each(|(i, coefficient)| {
+            (0..3).for_each(|c| color[c] += coefficient[c] * basis[i]);
+        });
+    color.map(|c| c.max(0.))
+}
+
+// Split sum approximation: normal, view and reflection directions are the same
+fn prefilter_ggx(mips: &[CubeMap], direction: &[f32; 3], roughness: f32) -> Color {
+    let alpha = roughness * roughness;
+    let up = if direction[2].abs() < 0.999 {
+        [0., 0., 1.]
+    } else {
+        [1., 0., 0.]
+    };
+    let tangent = normalize(cross(&up, direction));
+    let bitangent = cross(direction, &tangent);
+    let texel_solid_angle = 4. * PI / (6. * (mips[0].size * mips[0].size) as f32);
+    let mut color = [0.; 3];
+    let mut total_weight = 0.;
+    for i in 0..SPECULAR_SAMPLES {
+        let h = importance_sample_ggx(hammersley(i, SPECULAR_SAMPLES), alpha);
+        let h = normalize([
+            tangent[0] * h[0] + bitangent[0] * h[1] + direction[0] * h[2],
+            tangent[1] * h[0] + bitangent[1] * h[1] + direction[1] * h[2],
+            tangent[2] * h[0] + bitangent[2] * h[1] + direction[2] * h[2],
+        ]);
+        let v_dot_h = dot(direction, &h);
+        let l = normalize([
+            2. * v_dot_h * h[0] - direction[0],
+            2. * v_dot_h * h[1] - direction[1],
+            2. * v_dot_h * h[2] - direction[2],
+        ]);
+        let n_dot_l = dot(direction, &l);
+        if n_dot_l <= 0. {
+            continue;
+        }
+        // Filtered importance sampling: samples with a low probability read from smaller mips
+        let pdf = d_ggx(v_dot_h.max(0.), alpha) * 0.25;
+        let sample_solid_angle = 1. / (SPECULAR_SAMPLES as f32 * pdf + f32::EPSILON);
+        let lod = (0.5 * (sample_solid_angle / texel_solid_angle).log2() + 1.)
+            .clamp(0., (mips.len() - 1) as f32);
+        let p = mips[lod.round() as usize].sample(&l);
+        (0..3).for_each(|c| color[c] += p[c] * n_dot_l);
+        total_weight += n_dot_l;
+    }
+    if total_weight > 0. {
+        color.map(|c| c / total_weight)
+    } else {
+        mips[0].sample(direction)
+    }
+}
+
+fn push_texel(data: &mut Vec<u8>, color: [f32; 4]) {
+    color
+        .iter()
+        .for_each(|c| data.extend_from_slice(&quantize_half(*c).to_le_bytes()));
+}
+
+// Prefiltered map and irradiance computed from an equirectangular hdr image.
+// Every roughness level has the six faces side by side and is placed under the previous one
+pub fn compile_environment(
+    image: &Rgb32FImage,
+) -> (CompiledTextureData, [[f32; 4]; ENVIRONMENT_SH_COEFFICIENTS]) {
+    let face_size = (image.width() / 4)
+        .next_power_of_two()
+        .clamp(MIN_FACE_SIZE, MAX_FACE_SIZE);
+    let equirectangular = Equirectangular { image };
+    let mut mips = vec![CubeMap::from_equirectangular(&equirectangular, face_size)];
+    while mips.last().unwrap().size > 1 {
+        let mip = mips.last().unwrap().downsample();
+        mips.push(mip);
+    }
+    let irradiance = compute_irradiance_sh(&mips[0]);
+
+    let width = face_size * 6;
+    let height = (0..ENVIRONMENT_ROUGHNESS_LEVELS)
+        .map(|l| face_size >> l)
+        .sum::<u32>();
+    let mut rows: Vec<Vec<u8>> = Vec::with_capacity(height as usize);
+    for level in 0..ENVIRONMENT_ROUGHNESS_LEVELS {
+        let level_size = face_size >> level;
+        let roughness = level as f32 / (ENVIRONMENT_ROUGHNESS_LEVELS - 1) as f32;
+        for y in 0..level_size {
+            let mut row = Vec::with_capacity((width * 8) as usize);
+            for face in 0..6 {
+                for x in 0..level_size {
+                    let color = if level == 0 {
+                        mips[0].faces[face][(y * level_size + x) as usize]
+                    } else {
+                        let u = (x as f32 + 0.5) / level_size as f32;
+                        let v = (y as f32 + 0.5) / level_size as f32;
+                        prefilter_ggx(&mips, &face_direction(face, u, v), roughness)
+                    };
+                    push_texel(&mut row, [color[0], color[1], color[2], 1.]);
+                }
+            }
+            // Smaller levels leave the rest of the row empty
+            row.resize((width * 8) as usize, 0);
+            rows.push(row);
+        }
+    }
+    let texture = CompiledTextureData {
+        width,
+        height,
+        format: TextureFormat::Rgba16Float,
+        mips: vec![rows.concat()],
+    };
+    (texture, irradiance)
+}
+
+// Columns are NdotV and rows are roughness.
+// GGX LUT has scale and bias of F0 in red and green and the sheen albedo in blue
+pub fn compute_brdf_luts(size: u32) -> (CompiledTextureData, CompiledTextureData) {
+    let mut ggx = Vec::with_capacity((size * size * 8) as usize);
+    let mut charlie = Vec::with_capacity((size * size * 8) as usize);
+    for y in 0..size {
+        let roughness = (y as f32 + 0.5) / size as f32;
+        let alpha = roughness * roughness;
+        for x in 0..size {
+            let n_dot_v = (x as f32 + 0.5) / size as f32;
+            let v = [(1. - n_dot_v * n_dot_v).sqrt(), 0., n_dot_v];
+            let (mut a, mut b, mut c) = (0., 0., 0.);
+            for i in 0..LUT_SAMPLES {
+                let u = hammersley(i, LUT_SAMPLES);
+                let h = importance_sample_ggx(u, alpha);
+                let v_dot_h = dot(&v, &h);
+                let l = [
+                    2. * v_dot_h * h[0] - v[0],
+                    2. * v_dot_h * h[1] - v[1],
+                    2. * v_dot_h * h[2] - v[2],
+                ];
+                let n_dot_l = l[2];
+                if n_dot_l > 0. && h[2] > 0. {
+                    let visibility = v_ggx(n_dot_l, n_dot_v, alpha);
+                    let weight = visibility * 4. * v_dot_h.max(0.) * n_dot_l / h[2];
+                    let fresnel = (1. - v_dot_h.max(0.)).powi(5);
+                    a += (1. - fresnel) * weight;
+                    b += fresnel * weight;
+                }
+                // Sheen lobe is too wide to be importance sampled: uniform hemisphere directions
+                let cos_theta = u.0;
+                let sin_theta = (1. - cos_theta * cos_theta).sqrt();
+                let phi = 2. * PI * u.1;
+                let l = [sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta];
+                let h = normalize([v[0] + l[0], v[1] + l[1], v[2] + l[2]]);
+                c += d_charlie(roughness, h[2]) * v_ashikhmin(l[2], n_dot_v) * l[2];
+            }
+            let a = a / LUT_SAMPLES as f32;
+            let b = b / LUT_SAMPLES as f32;
+            // Sampling noise at grazing angles could make the albedo bigger than one
+            let c = (c * 2. * PI / LUT_SAMPLES as f32).min(1.);
+            push_texel(&mut ggx, [a, b, c, 1.]);
+            push_texel(&mut charlie, [c, c, c, 1.]);
+        }
+    }
+    let lut = |data: Vec<u8>| CompiledTextureData {
+        width: size,
+        height: size,
+        format: TextureFormat::Rgba16Float,
+        mips: vec![data],
+    };
+    (lut(ggx), lut(charlie))
+}
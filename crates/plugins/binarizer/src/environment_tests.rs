@@ -0,0 +1,135 @@
+use std::{
+    fs::{self, create_dir_all},
+    path::Path,
+};
+
+use image::{Rgb, Rgb32FImage};
+use inox_math::decode_half;
+use inox_render::{CompiledTextureData, EnvironmentData, TextureFormat};
+use inox_serialize::deserialize;
+
+use crate::{
+    environment::{compile_environment, compute_brdf_luts, evaluate_irradiance_sh},
+    EnvironmentCompiler,
+};
+
+fn texel(texture: &CompiledTextureData, x: u32, y: u32) -> [f32; 4] {
+    let offset = ((y * texture.width + x) * 8) as usize;
+    let data = &texture.mips[0][offset..offset + 8];
+    std::array::from_fn(|c| decode_half(u16::from_le_bytes([data[c * 2], data[c * 2 + 1]])))
+}
+
+#[allow(dead_code)]
+fn environment_irradiance_test() {
+    // Constant radiance gives the same irradiance and the same prefiltered value everywhere
+    let color = [0.5, 1., 2.];
+    let image = Rgb32FImage::from_pixel(64, 32, Rgb(color));
+    let (specular_map, irradiance) = compile_environment(&image);
+    assert_eq!(specular_map.format, TextureFormat::Rgba16Float);
+    assert_eq!(specular_map.width, 6 * 32);
+    assert_eq!(specular_map.height, 32 + 16 + 8 + 4 + 2 + 1);
+    [[0., 1., 0.], [1., 0., 0.], [0., 0., -1.]]
+        .iter()
+        .for_each(|n| {
+            let e = evaluate_irradiance_sh(&irradiance, n);
+            (0..3).for_each(|c| assert!((e[c] - color[c]).abs() < 0.01 * color[c]));
+        });
+    // First texel of the face 3 in every roughness level
+    let mut y = 0;
+    (0..6).for_each(|level| {
+        let level_size = 32 >> level;
+        let t = texel(&specular_map, 3 * level_size, y);
+        (0..3).for_each(|c| assert!((t[c] - color[c]).abs() < 0.01 * color[c]));
+        y += level_size;
+    });
+
+    // Sky above and black ground
+    let image = Rgb32FImage::from_fn(64, 32, |_, y| {
+        if y < 16 {
+            Rgb([1., 1., 1.])
+        } else {
+            Rgb([0., 0., 0.])
+        }
+    });
+    let (_, irradiance) = compile_environment(&image);
+    let up = evaluate_irradiance_sh(&irradiance, &[0., 1., 0.]);
+    let down = evaluate_irradiance_sh(&irradiance, &[0., -1., 0.]);
+    let side = evaluate_irradiance_sh(&irradiance, &[1., 0., 0.]);
+    assert!(up[0] > 0.9 && down[0] < 0.1);
+    assert!((side[0] - 0.5).abs() < 0.05);
+}
+
+#[allow(dead_code)]
+fn brdf_luts_test() {
+    let (ggx, charlie) = compute_brdf_luts(16);
+    assert_eq!((ggx.width, ggx.height), (16, 16));
+    assert_eq!(charlie.mips[0].len(), 16 * 16 * 8);
+    for y in 0..16 {
+        for x in 0..16 {
+            let t = texel(&ggx, x, y);
+            assert!(t[0] >= 0. && t[1] >= 0. && t[0] + t[1] <= 1.01);
+            assert!(t[2] >= 0. && t[2] <= 1.01);
+        }
+    }
+    // Smooth surfaces seen from the front reflect almost everything
+    let t = texel(&ggx, 15, 0);
+    assert!(t[0] + t[1] > 0.95);
+    // Roughness makes energy lost at grazing angles
+    let rough_grazing = texel(&ggx, 0, 15);
+    assert!(rough_grazing[0] + rough_grazing[1] < t[0] + t[1]);
+}
+
+#[allow(dead_code)]
+fn environment_compiler_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_environment_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    let data_raw_folder = test_folder.join("data_raw");
+    let data_folder = test_folder.join("data");
+    let textures_folder = data_raw_folder.join("textures");
+    create_dir_all(textures_folder.as_path()).unwrap();
+    create_dir_all(data_folder.as_path()).unwrap();
+
+    Rgb32FImage::from_pixel(64, 32, Rgb([0.25, 0.25, 0.25]))
+        .save(textures_folder.join("studio.hdr"))
+        .unwrap();
+
+    let compiler = EnvironmentCompiler::new(data_raw_folder.as_path(), data_folder.as_path());
+    let path = textures_folder.join("studio.hdr").canonicalize().unwrap();
+    assert!(compiler.compile_environment(path.as_path()));
+    // Already compiled environments are skipped
+    assert!(!compiler.compile_environment(path.as_path()));
+
+    let data_folder = data_folder.canonicalize().unwrap();
+    let environment = deserialize::<EnvironmentData>(
+        &fs::read(data_folder.join("textures").join("studio.environment")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        environment.specular_map.as_path(),
+        Path::new("textures").join("studio_specular.texture")
+    );
+    [
+        &environment.specular_map,
+        &environment.lut_pbr_ggx,
+        &environment.lut_pbr_charlie,
+    ]
+    .iter()
+    .for_each(|p| assert!(data_folder.join(p).exists()));
+    assert!(environment
+        .lut_pbr_ggx
+        .file_stem()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .ends_with("_LUT"));
+    assert!((environment.irradiance[0][0] - 0.25 * 3.5449).abs() < 0.01);
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[test]
+fn tests() {
+    environment_irradiance_test();
+    brdf_luts_test();
+    environment_compiler_test();
+}
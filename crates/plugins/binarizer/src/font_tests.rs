@@ -0,0 +1,123 @@
+use std::{
+    fs::{self, create_dir_all},
+    path::Path,
+    sync::Arc,
+};
+
+use inox_math::{Vector2, Vector4};
+use inox_messenger::MessageHub;
+use inox_render::{FontAtlasType, FontData, FontOptions, TextData, FONT_PT_TO_PIXEL};
+use inox_serialize::deserialize;
+
+use crate::FontCompiler;
+
+#[allow(dead_code)]
+fn font_compiler_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_font_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    let data_raw_folder = test_folder.join("data_raw");
+    let data_folder = test_folder.join("data");
+    let fonts_folder = data_raw_folder.join("fonts");
+    create_dir_all(fonts_folder.as_path()).unwrap();
+    create_dir_all(data_folder.as_path()).unwrap();
+    fs::copy(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../../data_raw/fonts/Roboto-Regular.ttf")
+            .as_path(),
+        fonts_folder.join("Roboto-Regular.ttf"),
+    )
+    .unwrap();
+
+    let mut compiler = FontCompiler::new(
+        Arc::new(MessageHub::default()),
+        data_raw_folder.as_path(),
+        data_folder.as_path(),
+    );
+    compiler.set_font_options(&FontOptions {
+        glyph_size: 32,
+        unicode_ranges: vec![[0x20, 0x20], [0x41, 0x5A], [0x3F, 0x3F]],
+        ..Default::default()
+    });
+    let path = fonts_folder
+        .join("Roboto-Regular.ttf")
+        .canonicalize()
+        .unwrap();
+    assert!(compiler.compile_font(path.as_path()));
+    // Already compiled fonts are skipped
+    assert!(!compiler.compile_font(path.as_path()));
+
+    let font = deserialize::<FontData>(
+        &fs::read(
+            data_folder
+                .canonicalize()
+                .unwrap()
+                .join("fonts")
+                .join("Roboto-Regular.font"),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(font.atlas_type(), FontAtlasType::Msdf);
+    assert_eq!(font.glyphs().len(), 28);
+    let (width, height) = font.atlas_size();
+    assert!(width.is_power_of_two() && height.is_power_of_two() && height <= width);
+    assert_eq!(font.atlas().len(), (width * height * 4) as usize);
+
+    let space = font.glyph(' ').unwrap();
+    assert!(space.is_empty() && space.advance > 0.);
+    assert!(font.glyph('a').is_none());
+    let a = font.glyph('A').unwrap();
+    assert!(!a.is_empty());
+    assert!(a.plane_bounds.w > font.get_metrics().ascender * 0.5);
+    assert!(a.texture_coord.x < a.texture_coord.z && a.texture_coord.z <= 1.);
+    assert!(font.get_metrics().line_height > font.get_metrics().ascender);
+    // Roboto has pair kerning in GPOS only
+    assert!(font.kerning('A', 'V') < 0.);
+    assert_eq!(font.kerning('V', ' '), 0.);
+    let pairs = font.kerning_pairs();
+    assert!(pairs
+        .windows(2)
+        .all(|p| (p[0].left, p[0].right) < (p[1].left, p[1].right)));
+    assert!(pairs.iter().all(|p| p.advance != 0.));
+
+    // The middle of the I bar is inside at any size, the padding around it outside
+    let i = font.glyph('I').unwrap().texture_coord;
+    let middle = Vector2::new((i.x + i.z) * 0.5, (i.y + i.w) * 0.5);
+    let corner = Vector2::new(i.x, i.y);
+    [1., 16., 256.].iter().for_each(|em_pixels| {
+        let range = font.screen_px_range(*em_pixels);
+        assert!(font.coverage(font.sample(middle), range) > 0.5);
+        assert_eq!(font.coverage(font.sample(corner), range), 0.);
+    });
+    // Edges get sharper as the text grows
+    assert_eq!(
+        font.coverage(font.sample(middle), font.screen_px_range(256.)),
+        1.
+    );
+
+    // Kerning moves V toward A, unknown characters are drawn as '?'
+    let text = TextData {
+        text: "AV\nA~".to_string(),
+        position: Vector2::new(0., 0.),
+        scale: 1. / FONT_PT_TO_PIXEL,
+        color: Vector4::new(1., 1., 1., 1.),
+        spacing: Vector2::new(0., 0.),
+    };
+    let mesh_data = font.create_mesh_from_text(&text);
+    assert_eq!(mesh_data.vertex_count(), 16);
+    assert_eq!(mesh_data.index_count(), 24);
+    let v = font.glyph('V').unwrap();
+    let v_left = a.advance + font.kerning('A', 'V') + v.plane_bounds.x;
+    let size = mesh_data.aabb_max() - mesh_data.aabb_min();
+    assert!((mesh_data.position(4).x - v_left).abs() <= size.x / 512.);
+    let second_line_top = font.get_metrics().line_height + font.get_metrics().ascender
+        - font.glyph('?').unwrap().plane_bounds.w;
+    assert!((mesh_data.position(12).y - second_line_top).abs() <= size.y / 512.);
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[test]
+fn tests() {
+    font_compiler_test();
+}
@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use crate::matches_glob;
+
+#[allow(dead_code)]
+fn glob_test() {
+    let path = Path::new("models/Sponza/glTF/Sponza.gltf");
+    assert!(matches_glob("*.gltf", path));
+    assert!(matches_glob("Sponza.*", path));
+    assert!(matches_glob("Spon?a.gltf", path));
+    assert!(!matches_glob("*.glb", path));
+
+    assert!(matches_glob("models/**", path));
+    assert!(matches_glob("models/**/*.gltf", path));
+    assert!(matches_glob("**/glTF/*.gltf", path));
+    assert!(matches_glob("./models/Sponza/glTF/Sponza.gltf", path));
+    assert!(!matches_glob("models/*.gltf", path));
+    assert!(!matches_glob("models/*/Sponza.gltf", path));
+    assert!(!matches_glob("shaders/**", path));
+
+    // '**/' matches no folder as well
+    assert!(matches_glob("**/Sponza.gltf", Path::new("Sponza.gltf")));
+    assert!(matches_glob(
+        "models/**/Sponza.gltf",
+        Path::new("models/Sponza.gltf")
+    ));
+    assert!(matches_glob(
+        "models/**/*.gltf",
+        Path::new("models\\Sponza\\Sponza.gltf")
+    ));
+}
+
+#[test]
+fn tests() {
+    glob_test();
+}
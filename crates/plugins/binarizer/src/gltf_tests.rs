@@ -0,0 +1,411 @@
+use std::{
+    fs::{self, create_dir_all},
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use inox_core::{JobHandler, JobHandlerRw};
+
+use inox_math::{Mat4Ops, VecBaseFloat, Vector3, Vector4};
+use inox_platform::PLATFORM_TYPE_PC;
+use inox_render::{MaterialData, MaterialFlags, MeshData, TextureType};
+use inox_scene::{ObjectData, SceneData};
+use inox_serialize::{deserialize, SerializationType, SerializeFile};
+
+use crate::{BinarizerJobs, GltfChunkData, GltfCompiler, GltfIndexData};
+
+#[rustfmt::skip]
+const PNG_1X1: [u8; 67] = [
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F, 0x15, 0xC4,
+    0x89, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x00, 0x01, 0x00, 0x00,
+    0x05, 0x00, 0x01, 0x0D, 0x0A, 0x2D, 0xB4, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE,
+    0x42, 0x60, 0x82,
+];
+
+fn align_to_4(data: &mut Vec<u8>, padding: u8) {
+    while data.len() % 4 != 0 {
+        data.push(padding);
+    }
+}
+
+fn encode_base64(data: &[u8]) -> String {
+    const TABLE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut result = String::new();
+    data.chunks(3).for_each(|chunk| {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |bits, (i, b)| bits | ((*b as u32) << (16 - 8 * i)));
+        (0..4).for_each(|i| {
+            if i <= chunk.len() {
+                result.push(TABLE[((bits >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                result.push('=');
+            }
+        });
+    });
+    result
+}
+
+// Quad mesh vertices and indices followed by a png image
+fn create_test_buffer() -> (Vec<u8>, usize) {
+    #[rustfmt::skip]
+    let positions: [f32; 12] = [
+        -1., -1., 0.,
+        1., -1., 0.,
+        1., 1., 0.,
+        -1., 1., 0.,
+    ];
+    #[rustfmt::skip]
+    let normals: [f32; 12] = [
+        0., 0., 1.,
+        0., 0., 1.,
+        0., 0., 1.,
+        0., 0., 1.,
+    ];
+    #[rustfmt::skip]
+    let uvs: [f32; 8] = [
+        0., 1.,
+        1., 1.,
+        1., 0.,
+        0., 0.,
+    ];
+    let indices: [u16; 6] = [0, 1, 2, 2, 3, 0];
+
+    let mut bin = Vec::new();
+    positions
+        .iter()
+        .chain(normals.iter())
+        .chain(uvs.iter())
+        .for_each(|v| bin.extend_from_slice(&v.to_le_bytes()));
+    indices
+        .iter()
+        .for_each(|i| bin.extend_from_slice(&i.to_le_bytes()));
+    align_to_4(&mut bin, 0);
+    let image_offset = bin.len();
+    bin.extend_from_slice(&PNG_1X1);
+    (bin, image_offset)
+}
+
+// Quad mesh with a png texture, a camera and a point light.
+// buffer and image are the json fields used to reference their data
+fn create_test_json(
+    buffer: &str,
+    image: &str,
+    image_offset: usize,
+    buffer_length: usize,
+) -> String {
+    format!(
+        r#"{{
+        "asset": {{ "version": "2.0" }},
+        "extensionsUsed": ["KHR_lights_punctual", "KHR_materials_clearcoat",
+            "KHR_texture_transform"],
+        "extensions": {{ "KHR_lights_punctual": {{ "lights": [
+            {{ "type": "point", "color": [1.0, 1.0, 1.0], "intensity": 10.0 }}
+        ] }} }},
+        "scene": 0,
+        "scenes": [{{ "nodes": [0, 1, 2] }}],
+        "nodes": [
+            {{ "name": "Quad", "mesh": 0 }},
+            {{ "name": "Camera", "camera": 0, "translation": [0.0, 0.0, 5.0] }},
+            {{ "name": "Light", "translation": [0.0, 2.0, 0.0],
+              "extensions": {{ "KHR_lights_punctual": {{ "light": 0 }} }} }}
+        ],
+        "cameras": [{{ "type": "perspective",
+            "perspective": {{ "yfov": 0.8, "znear": 0.1, "zfar": 100.0 }} }}],
+        "meshes": [{{ "name": "Quad", "primitives": [{{
+            "attributes": {{ "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }},
+            "indices": 3, "material": 0 }}] }}],
+        "materials": [{{ "name": "QuadMaterial",
+            "pbrMetallicRoughness": {{ "baseColorTexture": {{ "index": 0, "extensions": {{
+                "KHR_texture_transform": {{ "offset": [0.5, 0.0], "scale": [2.0, 2.0] }} }} }} }},
+            "extensions": {{ "KHR_materials_clearcoat": {{ "clearcoatFactor": 0.5,
+                "clearcoatTexture": {{ "index": 0, "texCoord": 0, "extensions": {{
+                    "KHR_texture_transform": {{ "rotation": 1.0, "texCoord": 1 }} }} }} }} }} }}],
+        "textures": [{{ "source": 0 }}],
+        "images": [{{ "name": "QuadImage", {image} }}],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3",
+              "min": [-1.0, -1.0, 0.0], "max": [1.0, 1.0, 0.0] }},
+            {{ "bufferView": 1, "componentType": 5126, "count": 4, "type": "VEC3" }},
+            {{ "bufferView": 2, "componentType": 5126, "count": 4, "type": "VEC2" }},
+            {{ "bufferView": 3, "componentType": 5123, "count": 6, "type": "SCALAR" }}
+        ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": 48 }},
+            {{ "buffer": 0, "byteOffset": 48, "byteLength": 48 }},
+            {{ "buffer": 0, "byteOffset": 96, "byteLength": 32 }},
+            {{ "buffer": 0, "byteOffset": 128, "byteLength": 12 }},
+            {{ "buffer": 0, "byteOffset": {image_offset}, "byteLength": {} }}
+        ],
+        "buffers": [{{ {buffer}"byteLength": {buffer_length} }}]
+    }}"#,
+        PNG_1X1.len()
+    )
+}
+
+fn create_test_glb(path: &Path) {
+    let (mut bin, image_offset) = create_test_buffer();
+    let buffer_length = bin.len();
+    align_to_4(&mut bin, 0);
+
+    let json = create_test_json(
+        "",
+        r#""bufferView": 4, "mimeType": "image/png""#,
+        image_offset,
+        buffer_length,
+    );
+    let mut json = json.into_bytes();
+    align_to_4(&mut json, b' ');
+
+    let total_length = 12 + 8 + json.len() + 8 + bin.len();
+    let mut glb = Vec::new();
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(total_length as u32).to_le_bytes());
+    glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&json);
+    glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"BIN\0");
+    glb.extend_from_slice(&bin);
+
+    fs::write(path, glb).unwrap();
+}
+
+fn create_test_gltf_with_data_uris(path: &Path) {
+    let (bin, image_offset) = create_test_buffer();
+    let json = create_test_json(
+        &format!(
+            r#""uri": "data:application/octet-stream;base64,{}", "#,
+            encode_base64(&bin)
+        ),
+        &format!(
+            r#""uri": "data:image/png;base64,{}""#,
+            encode_base64(&PNG_1X1)
+        ),
+        image_offset,
+        bin.len(),
+    );
+    fs::write(path, json).unwrap();
+}
+
+fn compile_test_model(
+    test_name: &str,
+    filename: &str,
+    create_model: fn(&Path),
+    jobs: &BinarizerJobs,
+) {
+    let test_folder = std::env::temp_dir().join(test_name);
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    let data_raw_folder = test_folder.join("data_raw");
+    let data_folder = test_folder.join("data");
+    let model_folder = data_raw_folder.join("models").join("Quad");
+    create_dir_all(model_folder.as_path()).unwrap();
+    create_dir_all(data_folder.as_path()).unwrap();
+
+    let model_path = model_folder.join(filename);
+    create_model(model_path.as_path());
+    let model_path = model_path.canonicalize().unwrap();
+
+    let mut compiler =
+        GltfCompiler::<PLATFORM_TYPE_PC>::new(data_raw_folder.as_path(), data_folder.as_path());
+    compiler.set_jobs(jobs);
+    assert!(compiler.process_path(model_path.as_path()));
+
+    let output_folder = data_folder
+        .canonicalize()
+        .unwrap()
+        .join("models")
+        .join("Quad");
+
+    let texture = fs::read(output_folder.join("texture").join("QuadImage.png")).unwrap();
+    assert_eq!(texture, PNG_1X1.to_vec(), "Embedded image not extracted");
+
+    let mesh = deserialize::<MeshData>(
+        &fs::read(output_folder.join("mesh").join("Quad_Primitive_0.mesh")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(mesh.vertex_count(), 4);
+    assert_eq!(
+        mesh.meshlets[0]
+            .iter()
+            .map(|m| m.indices_count)
+            .sum::<u32>(),
+        6
+    );
+
+    let material = deserialize::<MaterialData>(
+        &fs::read(
+            output_folder
+                .join("material")
+                .join("QuadMaterial_0.material"),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    // Each slot uses the image compiled for its kind
+    assert!(
+        material.textures[TextureType::BaseColor as usize].ends_with("QuadImage.png.color.texture")
+    );
+    assert!(material
+        .flags
+        .contains(MaterialFlags::Clearcoat | MaterialFlags::TextureTransform));
+    assert_eq!(material.clearcoat_factor, 0.5);
+    assert!(
+        material.textures[TextureType::Clearcoat as usize].ends_with("QuadImage.png.data.texture")
+    );
+    assert!(output_folder
+        .join("texture")
+        .join("QuadImage.png.color.texture")
+        .exists());
+    // Each texture keeps its own KHR_texture_transform
+    assert_eq!(
+        material.texture_offset_and_scale[TextureType::BaseColor as usize],
+        Vector4::new(0.5, 0., 2., 2.)
+    );
+    assert_eq!(
+        material.texture_rotation[TextureType::BaseColor as usize],
+        0.
+    );
+    assert_eq!(
+        material.texture_offset_and_scale[TextureType::Clearcoat as usize],
+        Vector4::new(0., 0., 1., 1.)
+    );
+    assert_eq!(
+        material.texture_rotation[TextureType::Clearcoat as usize],
+        1.
+    );
+    assert_eq!(material.texcoords_set[TextureType::BaseColor as usize], 0);
+    assert_eq!(material.texcoords_set[TextureType::Clearcoat as usize], 1);
+
+    assert!(output_folder.join("object").join("Quad.object").exists());
+    assert!(output_folder
+        .join("camera")
+        .join("Node_1_Camera_0.camera")
+        .exists());
+    assert!(output_folder
+        .join("light")
+        .join("Node_2_Light_0.light")
+        .exists());
+
+    let scene =
+        deserialize::<SceneData>(&fs::read(output_folder.join("Quad.scene")).unwrap()).unwrap();
+    assert_eq!(scene.objects.len(), 3);
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[allow(dead_code)]
+fn glb_test() {
+    compile_test_model(
+        "inox_binarizer_glb_test",
+        "quad.glb",
+        create_test_glb,
+        &BinarizerJobs::default(),
+    );
+}
+
+#[allow(dead_code)]
+fn data_uri_test() {
+    compile_test_model(
+        "inox_binarizer_data_uri_test",
+        "quad.gltf",
+        create_test_gltf_with_data_uris,
+        &BinarizerJobs::default(),
+    );
+}
+
+#[allow(dead_code)]
+fn jobs_test() {
+    // Primitives scheduled as jobs produce the same files
+    let job_handler: JobHandlerRw = Arc::new(RwLock::new(JobHandler::default()));
+    compile_test_model(
+        "inox_binarizer_gltf_jobs_test",
+        "quad.glb",
+        create_test_glb,
+        &BinarizerJobs::new(&job_handler),
+    );
+}
+
+// Chunk with a single node, with its world translation
+fn create_test_chunk(path: &Path, name: &str, translation: [f32; 3]) {
+    let json = format!(
+        r#"{{
+        "asset": {{ "version": "2.0" }},
+        "scene": 0,
+        "scenes": [{{ "nodes": [0] }}],
+        "nodes": [{{ "name": "{name}", "translation": {translation:?} }}]
+    }}"#
+    );
+    fs::write(path, json).unwrap();
+}
+
+#[allow(dead_code)]
+fn index_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_gltf_index_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    let data_raw_folder = test_folder.join("data_raw");
+    let data_folder = test_folder.join("data");
+    // Chunks are found through the index, whatever their folder is
+    let scene_folder = data_raw_folder.join("Scene");
+    let chunks_folder = scene_folder.join("parts");
+    create_dir_all(chunks_folder.as_path()).unwrap();
+    create_dir_all(data_folder.as_path()).unwrap();
+
+    create_test_chunk(&chunks_folder.join("Parent.gltf"), "Parent", [1., 0., 0.]);
+    create_test_chunk(&chunks_folder.join("Child.gltf"), "Child", [1., 2., 0.]);
+    let index_data = GltfIndexData {
+        chunks: vec![
+            GltfChunkData {
+                path: "parts/Parent.gltf".into(),
+                parent: None,
+            },
+            GltfChunkData {
+                path: "parts/Child.gltf".into(),
+                parent: Some("parts/Parent.gltf".into()),
+            },
+        ],
+    };
+    let index_path = scene_folder.join(format!("Scene.{}", GltfIndexData::extension()));
+    index_data.save_to_file(index_path.as_path(), SerializationType::Json);
+
+    let mut compiler =
+        GltfCompiler::<PLATFORM_TYPE_PC>::new(data_raw_folder.as_path(), data_folder.as_path());
+    for chunk in ["Parent.gltf", "Child.gltf"] {
+        let chunk_path = chunks_folder.join(chunk).canonicalize().unwrap();
+        assert!(compiler.process_path(chunk_path.as_path()));
+    }
+    assert!(compiler.process_index(index_path.canonicalize().unwrap().as_path()));
+
+    let output_folder = data_folder.canonicalize().unwrap().join("Scene");
+    // Chunks don't produce scenes, only the index does with the root objects
+    assert!(!output_folder.join("parts").join("Scene.scene").exists());
+    let scene =
+        deserialize::<SceneData>(&fs::read(output_folder.join("Scene.scene")).unwrap()).unwrap();
+    assert_eq!(scene.objects.len(), 1);
+    assert!(scene.objects[0].ends_with("Parent.object"));
+
+    let read_object = |name: &str| {
+        let path = output_folder.join("parts").join("object").join(name);
+        deserialize::<ObjectData>(&fs::read(path).unwrap()).unwrap()
+    };
+    let parent = read_object("Parent.object");
+    assert_eq!(parent.children.len(), 1);
+    assert!(parent.children[0].ends_with("Child.object"));
+    assert_eq!(parent.transform.translation(), Vector3::new(1., 0., 0.));
+    // Chunks are in world space, children are moved in the space of their parent
+    let child = read_object("Child.object");
+    assert!((child.transform.translation() - Vector3::new(0., 2., 0.)).length() < 1e-5);
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[test]
+fn tests() {
+    glb_test();
+    data_uri_test();
+    jobs_test();
+    index_test();
+}
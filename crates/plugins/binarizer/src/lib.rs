@@ -2,15 +2,36 @@
 #![warn(clippy::all)]
 
 pub use crate::compilers::*;
+pub use crate::lightmap::LightmapOptions;
+pub use crate::mesh::{MeshOptions, MeshletGrouping, MeshletOptions, MeshletOptionsOverride};
 pub use crate::plugin::*;
 pub use crate::system::*;
 pub use crate::utils::*;
 
 mod adjacency;
+mod asset_database_tests;
+mod binarizer_jobs_tests;
+mod block_compression;
 mod compilers;
-mod config;
+pub mod config;
+mod environment;
+mod environment_tests;
+mod font_tests;
+mod glob_tests;
+mod gltf_tests;
+mod lightmap;
+mod lightmap_tests;
 mod mesh;
+mod meshlet_lods_tests;
+mod obj_tests;
 pub mod plugin;
+mod ply_stl_tests;
+mod shader_preprocessor;
+mod shader_preprocessor_tests;
+mod shader_reflection;
+mod shader_reflection_tests;
 mod system;
 mod tests;
+mod texture;
+mod texture_tests;
 mod utils;
@@ -0,0 +1,694 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use inox_bvh::{create_linearized_bvh, BVHTree, GPUBVHNode, AABB};
+use inox_math::{VecBase, VecBaseFloat, Vector2, Vector3};
+use inox_render::{GPULight, LightType, VertexAttributeLayout};
+use inox_serialize::{Deserialize, Serialize};
+
+use crate::mesh::MeshVertex;
+
+// Positions closer than this are the same one when looking for shared edges
+const POSITION_QUANTIZATION: f32 = 1e4;
+// Charts scale at each failed packing once the lightmap has its max size
+const PACKING_SHRINK: f32 = 0.9;
+// Ray origins are moved away from surfaces by this fraction of the scene size
+const RAY_BIAS_SCALE: f32 = 1e-4;
+
+// How lightmaps of static meshes are generated and baked
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "inox_serialize", default)]
+pub struct LightmapOptions {
+    pub enabled: bool,
+    // Texels for each world unit, before clamping the size of the lightmap
+    pub texels_per_unit: f32,
+    pub min_size: u32,
+    pub max_size: u32,
+    // Texels between charts, filled dilating them to avoid bleeding
+    pub padding: u32,
+    // Max angle in degrees between faces of the same generated chart
+    pub chart_angle: f32,
+    // Hemisphere rays for each texel
+    pub samples: u32,
+    // Indirect bounces, with zero only sky and emissive surfaces are gathered
+    pub bounces: u32,
+    // Bake the scene lights too, disable it when they're evaluated at runtime
+    pub direct_light: bool,
+    // Occluders further than this don't darken ambient occlusion
+    pub ao_distance: f32,
+    // Radiance of rays leaving the scene
+    pub sky_color: [f32; 3],
+}
+
+impl Default for LightmapOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            texels_per_unit: 16.,
+            min_size: 32,
+            max_size: 1024,
+            padding: 2,
+            chart_angle: 60.,
+            samples: 64,
+            bounces: 1,
+            direct_light: true,
+            ao_distance: 1.,
+            sky_color: [0.; 3],
+        }
+    }
+}
+
+pub fn vertex_uv(vertex: &MeshVertex, uv_set: usize) -> Vector2 {
+    match uv_set {
+        0 => vertex.uv_0,
+        1 => vertex.uv_1,
+        2 => vertex.uv_2,
+        _ => vertex.uv_3,
+    }
+}
+
+pub fn uv_set_layout(uv_set: usize) -> VertexAttributeLayout {
+    match uv_set {
+        0 => VertexAttributeLayout::HasUV1,
+        1 => VertexAttributeLayout::HasUV2,
+        2 => VertexAttributeLayout::HasUV3,
+        _ => VertexAttributeLayout::HasUV4,
+    }
+}
+
+pub fn set_vertex_uv(vertex: &mut MeshVertex, uv_set: usize, uv: Vector2) {
+    match uv_set {
+        0 => vertex.uv_0 = uv,
+        1 => vertex.uv_1 = uv,
+        2 => vertex.uv_2 = uv,
+        _ => vertex.uv_3 = uv,
+    }
+}
+
+fn triangle_positions(vertices: &[MeshVertex], indices: &[u32], triangle: usize) -> [Vector3; 3] {
+    [
+        vertices[indices[triangle * 3] as usize].pos.xyz(),
+        vertices[indices[triangle * 3 + 1] as usize].pos.xyz(),
+        vertices[indices[triangle * 3 + 2] as usize].pos.xyz(),
+    ]
+}
+
+fn lightmap_size(area: f32, texels_per_unit: f32, options: &LightmapOptions) -> u32 {
+    let max_size = options.max_size.max(1);
+    let texels = (area.sqrt() * texels_per_unit).ceil().max(1.) as u32;
+    texels
+        .next_power_of_two()
+        .clamp(options.min_size.min(max_size), max_size)
+}
+
+// Size of the lightmap of an authored uv set, from the surface of the mesh
+pub fn compute_lightmap_size(
+    vertices: &[MeshVertex],
+    indices: &[u32],
+    options: &LightmapOptions,
+) -> u32 {
+    let area = (0..indices.len() / 3).fold(0., |area, t| {
+        let [a, b, c] = triangle_positions(vertices, indices, t);
+        area + (b - a).cross(c - a).length() * 0.5
+    });
+    lightmap_size(area, options.texels_per_unit, options)
+}
+
+// Axes of the plane orthogonal to the normal
+fn tangent_basis(normal: Vector3) -> (Vector3, Vector3) {
+    let axis = if normal.x.abs() < 0.9 {
+        Vector3::new(1., 0., 0.)
+    } else {
+        Vector3::new(0., 1., 0.)
+    };
+    let u = normal.cross(axis).normalized();
+    (u, normal.cross(u))
+}
+
+struct Chart {
+    normal: Vector3,
+    triangles: Vec<usize>,
+    min: Vector2,
+    max: Vector2,
+}
+
+impl Chart {
+    // One more texel than the chart size, so that texel centers cover its borders
+    fn texels(&self, texels_per_unit: f32) -> (u32, u32) {
+        let size = (self.max - self.min) * texels_per_unit;
+        (size.x.ceil() as u32 + 1, size.y.ceil() as u32 + 1)
+    }
+}
+
+// Shelf packing of charts sorted by height, returns the texel offset of each one
+fn pack_charts(sizes: &[(u32, u32)], size: u32, padding: u32) -> Option<Vec<(u32, u32)>> {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by(|&a, &b| sizes[b].1.cmp(&sizes[a].1));
+    let mut offsets = vec![(0, 0); sizes.len()];
+    let (mut x, mut y, mut row_height) = (padding, padding, 0);
+    for c in order {
+        let (width, height) = sizes[c];
+        if x + width + padding > size {
+            x = padding;
+            y += row_height + padding;
+            row_height = 0;
+        }
+        if x + width + padding > size || y + height + padding > size {
+            return None;
+        }
+        offsets[c] = (x, y);
+        x += width + padding;
+        row_height = row_height.max(height);
+    }
+    Some(offsets)
+}
+
+// Splits the mesh in charts of faces with similar normals, projects them on their plane
+// and packs them in a square lightmap. Vertices on chart borders are duplicated.
+// Returns the new vertices and indices with the uv set filled and the lightmap size.
+pub fn generate_lightmap_uvs(
+    vertices: &[MeshVertex],
+    indices: &[u32],
+    uv_set: usize,
+    options: &LightmapOptions,
+) -> Option<(Vec<MeshVertex>, Vec<u32>, u32)> {
+    let triangles_count = indices.len() / 3;
+    let faces: Vec<(Vector3, f32)> = (0..triangles_count)
+        .map(|t| {
+            let [a, b, c] = triangle_positions(vertices, indices, t);
+            let n = (b - a).cross(c - a);
+            let length = n.length();
+            if length > 0. {
+                (n / length, length * 0.5)
+            } else {
+                (Vector3::default_zero(), 0.)
+            }
+        })
+        .collect();
+
+    // Edges are found by position to ignore seams of normals and uvs
+    let key = |i: u32| {
+        let p = vertices[i as usize].pos.xyz() * POSITION_QUANTIZATION;
+        [p.x.round() as i32, p.y.round() as i32, p.z.round() as i32]
+    };
+    let mut edges: HashMap<([i32; 3], [i32; 3]), Vec<usize>> = HashMap::new();
+    (0..triangles_count).for_each(|t| {
+        (0..3).for_each(|e| {
+            let a = key(indices[t * 3 + e]);
+            let b = key(indices[t * 3 + (e + 1) % 3]);
+            edges
+                .entry(if a < b { (a, b) } else { (b, a) })
+                .or_default()
+                .push(t);
+        });
+    });
+    let mut neighbours = vec![Vec::new(); triangles_count];
+    edges.values().for_each(|triangles| {
+        triangles.iter().for_each(|&t| {
+            triangles
+                .iter()
+                .filter(|&&other| other != t)
+                .for_each(|&other| neighbours[t].push(other));
+        });
+    });
+
+    // Charts grow from a seed while faces are close enough to its normal
+    let min_dot = options.chart_angle.to_radians().cos();
+    let mut charts: Vec<Chart> = Vec::new();
+    let mut chart_of = vec![usize::MAX; triangles_count];
+    for seed in 0..triangles_count {
+        if chart_of[seed] != usize::MAX {
+            continue;
+        }
+        let normal = if faces[seed].1 > 0. {
+            faces[seed].0
+        } else {
+            Vector3::new(0., 0., 1.)
+        };
+        let chart = charts.len();
+        chart_of[seed] = chart;
+        let mut triangles = vec![seed];
+        let mut i = 0;
+        while i < triangles.len() {
+            for &n in &neighbours[triangles[i]] {
+                if chart_of[n] == usize::MAX
+                    && (faces[n].1 <= 0. || faces[n].0.dot_product(normal) >= min_dot)
+                {
+                    chart_of[n] = chart;
+                    triangles.push(n);
+                }
+            }
+            i += 1;
+        }
+        charts.push(Chart {
+            normal,
+            triangles,
+            min: Vector2::new(f32::MAX, f32::MAX),
+            max: Vector2::new(f32::MIN, f32::MIN),
+        });
+    }
+
+    let mut new_vertices = Vec::new();
+    let mut vertices_chart = Vec::new();
+    let mut chart_uvs = Vec::new();
+    let mut new_indices = vec![0; triangles_count * 3];
+    for (c, chart) in charts.iter_mut().enumerate() {
+        let (u, v) = tangent_basis(chart.normal);
+        let (mut min, mut max) = (chart.min, chart.max);
+        let mut remap = HashMap::new();
+        for &t in &chart.triangles {
+            for k in 0..3 {
+                let index = indices[t * 3 + k];
+                new_indices[t * 3 + k] = *remap.entry(index).or_insert_with(|| {
+                    let p = vertices[index as usize].pos.xyz();
+                    let uv = Vector2::new(p.dot_product(u), p.dot_product(v));
+                    min = min.min(uv);
+                    max = max.max(uv);
+                    new_vertices.push(vertices[index as usize]);
+                    vertices_chart.push(c);
+                    chart_uvs.push(uv);
+                    (new_vertices.len() - 1) as u32
+                });
+            }
+        }
+        chart.min = min;
+        chart.max = max;
+    }
+
+    let area = charts.iter().fold(0., |area, chart| {
+        let size = chart.max - chart.min;
+        area + size.x * size.y
+    });
+    let mut texels_per_unit = options.texels_per_unit.max(f32::EPSILON);
+    let mut size = lightmap_size(area, texels_per_unit, options);
+    loop {
+        let sizes: Vec<(u32, u32)> = charts.iter().map(|c| c.texels(texels_per_unit)).collect();
+        if let Some(offsets) = pack_charts(&sizes, size, options.padding) {
+            new_vertices.iter_mut().enumerate().for_each(|(i, vertex)| {
+                let c = vertices_chart[i];
+                let offset = Vector2::new(offsets[c].0 as f32 + 0.5, offsets[c].1 as f32 + 0.5);
+                let texel = offset + (chart_uvs[i] - charts[c].min) * texels_per_unit;
+                set_vertex_uv(vertex, uv_set, texel / size as f32);
+            });
+            return Some((new_vertices, new_indices, size));
+        }
+        if size < options.max_size {
+            size = (size * 2).min(options.max_size);
+        } else if sizes
+            .iter()
+            .all(|&(width, height)| width <= 2 && height <= 2)
+        {
+            // Too many charts even at their smallest
+            return None;
+        } else {
+            texels_per_unit *= PACKING_SHRINK;
+        }
+    }
+}
+
+// Triangle of the scene in world space, with the material values seen by bounces
+#[derive(Debug, Clone, Copy)]
+pub struct LightmapTriangle {
+    pub positions: [Vector3; 3],
+    pub albedo: Vector3,
+    pub emissive: Vector3,
+}
+
+impl LightmapTriangle {
+    fn normal(&self) -> Vector3 {
+        let [a, b, c] = self.positions;
+        (b - a).cross(c - a).normalized()
+    }
+}
+
+fn intersect_aabb(node: &GPUBVHNode, origin: Vector3, inv_direction: Vector3, max: f32) -> bool {
+    let mut t_min = 0f32;
+    let mut t_max = max;
+    (0..3).for_each(|axis| {
+        let t1 = (node.min[axis] - origin[axis]) * inv_direction[axis];
+        let t2 = (node.max[axis] - origin[axis]) * inv_direction[axis];
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+    });
+    t_min <= t_max
+}
+
+// Möller–Trumbore, faces are hit from both sides
+fn intersect_triangle(
+    triangle: &LightmapTriangle,
+    origin: Vector3,
+    direction: Vector3,
+) -> Option<f32> {
+    let [p0, p1, p2] = triangle.positions;
+    let e1 = p1 - p0;
+    let e2 = p2 - p0;
+    let p = direction.cross(e2);
+    let det = e1.dot_product(p);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1. / det;
+    let s = origin - p0;
+    let u = s.dot_product(p) * inv_det;
+    if !(0. ..=1.).contains(&u) {
+        return None;
+    }
+    let q = s.cross(e1);
+    let v = direction.dot_product(q) * inv_det;
+    if v < 0. || u + v > 1. {
+        return None;
+    }
+    let t = e2.dot_product(q) * inv_det;
+    if t > 0. {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+// PCG hash, returns a value in [0, 1)
+fn random(seed: &mut u32) -> f32 {
+    *seed = seed.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((*seed >> ((*seed >> 28) + 4)) ^ *seed).wrapping_mul(277803737);
+    (((word >> 22) ^ word) >> 8) as f32 / (1u32 << 24) as f32
+}
+
+fn cosine_weighted_direction(normal: Vector3, seed: &mut u32) -> Vector3 {
+    let (u, v) = tangent_basis(normal);
+    let r1 = random(seed);
+    let r2 = random(seed);
+    let radius = r1.sqrt();
+    let phi = 2. * PI * r2;
+    (u * (radius * phi.cos()) + v * (radius * phi.sin()) + normal * (1. - r1).sqrt()).normalized()
+}
+
+// Scene triangles and lights with a bvh to trace rays on the cpu
+pub struct LightmapScene {
+    triangles: Vec<LightmapTriangle>,
+    lights: Vec<GPULight>,
+    bvh: Vec<GPUBVHNode>,
+    bias: f32,
+    options: LightmapOptions,
+}
+
+impl LightmapScene {
+    pub fn new(
+        triangles: Vec<LightmapTriangle>,
+        lights: Vec<GPULight>,
+        options: &LightmapOptions,
+    ) -> Self {
+        let aabbs: Vec<AABB> = triangles
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let [a, b, c] = t.positions;
+                AABB::create(a.min(b).min(c), a.max(b).max(c), i as _)
+            })
+            .collect();
+        let bvh = create_linearized_bvh(&BVHTree::new(&aabbs));
+        let bias = if aabbs.is_empty() {
+            RAY_BIAS_SCALE
+        } else {
+            (AABB::compute_aabb(&aabbs).size().length() * RAY_BIAS_SCALE).max(RAY_BIAS_SCALE)
+        };
+        Self {
+            triangles,
+            lights,
+            bvh,
+            bias,
+            options: options.clone(),
+        }
+    }
+
+    // Closest hit as triangle and distance, or the first one found when any_hit is set
+    fn trace(
+        &self,
+        origin: Vector3,
+        direction: Vector3,
+        max_distance: f32,
+        any_hit: bool,
+    ) -> Option<(usize, f32)> {
+        let inv_direction = Vector3::new(1. / direction.x, 1. / direction.y, 1. / direction.z);
+        let mut hit = None;
+        let mut distance = max_distance;
+        // Nodes are depth first: children follow their parent, miss skips the subtree
+        let mut i = 0;
+        while i >= 0 && (i as usize) < self.bvh.len() {
+            let node = &self.bvh[i as usize];
+            if !intersect_aabb(node, origin, inv_direction, distance) {
+                i = node.miss;
+                continue;
+            }
+            if node.reference < 0 {
+                i += 1;
+                continue;
+            }
+            let triangle = node.reference as usize;
+            if let Some(t) = intersect_triangle(&self.triangles[triangle], origin, direction) {
+                if t < distance {
+                    distance = t;
+                    hit = Some((triangle, t));
+                    if any_hit {
+                        break;
+                    }
+                }
+            }
+            i = node.miss;
+        }
+        hit
+    }
+
+    pub fn intersect(&self, origin: Vector3, direction: Vector3) -> Option<(usize, f32)> {
+        self.trace(origin, direction, f32::MAX, false)
+    }
+
+    pub fn is_occluded(&self, origin: Vector3, direction: Vector3, max_distance: f32) -> bool {
+        self.trace(origin, direction, max_distance, true).is_some()
+    }
+
+    // Same attenuations of the pbr shader, with shadow rays
+    pub fn direct_irradiance(&self, position: Vector3, normal: Vector3) -> Vector3 {
+        let origin = position + normal * self.bias;
+        self.lights
+            .iter()
+            .fold(Vector3::default_zero(), |irradiance, light| {
+                let light_type = LightType::from(light.light_type);
+                let (direction, distance, attenuation) = if light_type == LightType::Directional {
+                    let direction = -Vector3::from(light.direction).normalized();
+                    (direction, f32::MAX, 1.)
+                } else {
+                    let to_light = Vector3::from(light.position) - position;
+                    let distance = to_light.length().max(f32::EPSILON);
+                    let direction = to_light / distance;
+                    let mut attenuation = if light.range <= 0. {
+                        1. / (distance * distance)
+                    } else {
+                        (1. - (distance / light.range).powi(4)).clamp(0., 1.)
+                            / (distance * distance)
+                    };
+                    if light_type == LightType::Spot {
+                        let cos = Vector3::from(light.direction)
+                            .normalized()
+                            .dot_product(-direction);
+                        attenuation *= if cos <= light.outer_cone_angle {
+                            0.
+                        } else if cos < light.inner_cone_angle {
+                            let t = (cos - light.outer_cone_angle)
+                                / (light.inner_cone_angle - light.outer_cone_angle);
+                            t * t
+                        } else {
+                            1.
+                        };
+                    }
+                    (direction, distance - self.bias, attenuation)
+                };
+                let n_dot_l = normal.dot_product(direction);
+                if light_type == LightType::None
+                    || n_dot_l <= 0.
+                    || attenuation <= 0.
+                    || self.is_occluded(origin, direction, distance)
+                {
+                    return irradiance;
+                }
+                irradiance + Vector3::from(light.color) * (light.intensity * attenuation * n_dot_l)
+            })
+    }
+
+    // Radiance coming back along a ray and the distance of its hit
+    fn radiance(
+        &self,
+        origin: Vector3,
+        direction: Vector3,
+        bounces: u32,
+        seed: &mut u32,
+    ) -> (Vector3, f32) {
+        let Some((triangle, distance)) = self.intersect(origin, direction) else {
+            return (Vector3::from(self.options.sky_color), f32::MAX);
+        };
+        let triangle = &self.triangles[triangle];
+        if bounces == 0 {
+            return (triangle.emissive, distance);
+        }
+        let position = origin + direction * distance;
+        let mut normal = triangle.normal();
+        if normal.dot_product(direction) > 0. {
+            normal = -normal;
+        }
+        let mut irradiance = self.direct_irradiance(position, normal);
+        if bounces > 1 {
+            // One cosine weighted sample estimates the incoming irradiance as PI * radiance
+            let bounce = cosine_weighted_direction(normal, seed);
+            let (incoming, _) =
+                self.radiance(position + normal * self.bias, bounce, bounces - 1, seed);
+            irradiance += incoming * PI;
+        }
+        (
+            triangle.emissive + triangle.albedo.mul(irradiance) / PI,
+            distance,
+        )
+    }
+
+    // Irradiance in rgb and ambient occlusion in alpha
+    pub fn bake_texel(&self, position: Vector3, normal: Vector3, seed: &mut u32) -> [f32; 4] {
+        let mut irradiance = if self.options.direct_light {
+            self.direct_irradiance(position, normal)
+        } else {
+            Vector3::default_zero()
+        };
+        let samples = self.options.samples.max(1);
+        let origin = position + normal * self.bias;
+        let mut indirect = Vector3::default_zero();
+        let mut occluded = 0;
+        (0..samples).for_each(|_| {
+            let direction = cosine_weighted_direction(normal, seed);
+            let (radiance, distance) = self.radiance(origin, direction, self.options.bounces, seed);
+            if distance < self.options.ao_distance {
+                occluded += 1;
+            }
+            indirect += radiance;
+        });
+        irradiance += indirect * (PI / samples as f32);
+        [
+            irradiance.x,
+            irradiance.y,
+            irradiance.z,
+            1. - occluded as f32 / samples as f32,
+        ]
+    }
+}
+
+// Bakes the texels covered by the triangles in the uv set, vertices are in world space.
+// Empty texels are filled from their neighbours to avoid bleeding when sampling.
+pub fn bake_lightmap(
+    scene: &LightmapScene,
+    vertices: &[MeshVertex],
+    indices: &[u32],
+    uv_set: usize,
+    size: u32,
+) -> Vec<[f32; 4]> {
+    let size = size.max(1) as usize;
+    let mut texels: Vec<Option<[f32; 4]>> = vec![None; size * size];
+    // Uvs outside of [0, 1] are wrapped by sampling
+    let origin = vertices
+        .iter()
+        .fold(Vector2::new(f32::MAX, f32::MAX), |min, v| {
+            min.min(vertex_uv(v, uv_set))
+        });
+    let origin = Vector2::new(origin.x.floor(), origin.y.floor());
+    (0..indices.len() / 3).for_each(|t| {
+        let v = [
+            &vertices[indices[t * 3] as usize],
+            &vertices[indices[t * 3 + 1] as usize],
+            &vertices[indices[t * 3 + 2] as usize],
+        ];
+        let uv = v.map(|v| (vertex_uv(v, uv_set) - origin) * size as f32);
+        let area = (uv[1] - uv[0]).perp_dot(uv[2] - uv[0]);
+        if area.abs() <= f32::EPSILON {
+            return;
+        }
+        let min = uv[0].min(uv[1]).min(uv[2]);
+        let max = uv[0].max(uv[1]).max(uv[2]);
+        let (x_start, x_end) = (
+            min.x.floor().max(0.) as usize,
+            (max.x.ceil() as usize).min(size),
+        );
+        let (y_start, y_end) = (
+            min.y.floor().max(0.) as usize,
+            (max.y.ceil() as usize).min(size),
+        );
+        (y_start..y_end).for_each(|y| {
+            (x_start..x_end).for_each(|x| {
+                let index = y * size + x;
+                if texels[index].is_some() {
+                    return;
+                }
+                let p = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = (uv[2] - uv[1]).perp_dot(p - uv[1]) / area;
+                let w1 = (uv[0] - uv[2]).perp_dot(p - uv[2]) / area;
+                let w2 = 1. - w0 - w1;
+                if w0 < -1e-4 || w1 < -1e-4 || w2 < -1e-4 {
+                    return;
+                }
+                let position = v[0].pos.xyz() * w0 + v[1].pos.xyz() * w1 + v[2].pos.xyz() * w2;
+                let normal =
+                    (v[0].normal.xyz() * w0 + v[1].normal.xyz() * w1 + v[2].normal.xyz() * w2)
+                        .normalized();
+                let mut seed = index as u32 ^ 0x9e3779b9;
+                texels[index] = Some(scene.bake_texel(position, normal, &mut seed));
+            });
+        });
+    });
+
+    (0..=scene.options.padding).for_each(|_| {
+        let source = texels.clone();
+        (0..size).for_each(|y| {
+            (0..size).for_each(|x| {
+                if source[y * size + x].is_some() {
+                    return;
+                }
+                let mut sum = [0.; 4];
+                let mut count = 0;
+                (y.saturating_sub(1)..(y + 2).min(size)).for_each(|ny| {
+                    (x.saturating_sub(1)..(x + 2).min(size)).for_each(|nx| {
+                        if let Some(texel) = source[ny * size + nx] {
+                            (0..4).for_each(|c| sum[c] += texel[c]);
+                            count += 1;
+                        }
+                    });
+                });
+                if count > 0 {
+                    texels[y * size + x] = Some(sum.map(|c| c / count as f32));
+                }
+            });
+        });
+    });
+    texels
+        .into_iter()
+        .map(|t| t.unwrap_or([0., 0., 0., 1.]))
+        .collect()
+}
+
+// Irradiance is stored as sqrt(E / (1 + E)) to keep precision in 8 bits
+pub fn encode_lightmap(texels: &[[f32; 4]]) -> Vec<u8> {
+    texels
+        .iter()
+        .flat_map(|t| {
+            let encode = |e: f32| {
+                let e = e.max(0.);
+                ((e / (1. + e)).sqrt() * 255.).round() as u8
+            };
+            [
+                encode(t[0]),
+                encode(t[1]),
+                encode(t[2]),
+                (t[3].clamp(0., 1.) * 255.).round() as u8,
+            ]
+        })
+        .collect()
+}
+
+// Same decoding of the shader
+pub fn decode_lightmap_irradiance(value: u8) -> f32 {
+    let c = value as f32 / 255.;
+    let c = c * c;
+    c / (1. - c).max(1. / 255.)
+}
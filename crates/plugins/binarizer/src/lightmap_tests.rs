@@ -0,0 +1,240 @@
+use std::f32::consts::PI;
+
+use inox_math::{VecBase, Vector2, Vector3, Vector4};
+use inox_render::{GPULight, LightType};
+
+use crate::{
+    lightmap::{
+        bake_lightmap, decode_lightmap_irradiance, encode_lightmap, generate_lightmap_uvs,
+        vertex_uv, LightmapScene, LightmapTriangle,
+    },
+    mesh::MeshVertex,
+    LightmapOptions,
+};
+
+fn create_vertex(pos: [f32; 3], normal: [f32; 3]) -> MeshVertex {
+    MeshVertex {
+        pos: Vector4::new(pos[0], pos[1], pos[2], 1.),
+        normal: Vector4::new(normal[0], normal[1], normal[2], 0.),
+        uv_0: Vector2::new(0., 0.),
+        ..Default::default()
+    }
+}
+
+// Cube with shared corners, so that charts have to split its vertices
+fn create_cube() -> (Vec<MeshVertex>, Vec<u32>) {
+    let vertices = (0..8)
+        .map(|i| {
+            let p = [(i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32];
+            create_vertex(p, [p[0] - 0.5, p[1] - 0.5, p[2] - 0.5])
+        })
+        .collect();
+    let indices = vec![
+        0, 2, 1, 1, 2, 3, // -z
+        4, 5, 6, 5, 7, 6, // +z
+        0, 1, 4, 1, 5, 4, // -y
+        2, 6, 3, 3, 6, 7, // +y
+        0, 4, 2, 2, 4, 6, // -x
+        1, 3, 5, 3, 7, 5, // +x
+    ];
+    (vertices, indices)
+}
+
+// Quad on the xz plane facing up
+fn create_floor(min: f32, max: f32) -> (Vec<MeshVertex>, Vec<u32>) {
+    let up = [0., 1., 0.];
+    let vertices = vec![
+        create_vertex([min, 0., min], up),
+        create_vertex([min, 0., max], up),
+        create_vertex([max, 0., max], up),
+        create_vertex([max, 0., min], up),
+    ];
+    (vertices, vec![0, 1, 2, 0, 2, 3])
+}
+
+fn create_triangles(
+    vertices: &[MeshVertex],
+    indices: &[u32],
+    albedo: f32,
+) -> Vec<LightmapTriangle> {
+    indices
+        .chunks(3)
+        .map(|t| LightmapTriangle {
+            positions: [
+                vertices[t[0] as usize].pos.xyz(),
+                vertices[t[1] as usize].pos.xyz(),
+                vertices[t[2] as usize].pos.xyz(),
+            ],
+            albedo: Vector3::new(albedo, albedo, albedo),
+            emissive: Vector3::default_zero(),
+        })
+        .collect()
+}
+
+// Floor from -2 to 2 with a roof at half unit over its negative x half
+fn create_scene(lights: Vec<GPULight>, options: &LightmapOptions) -> LightmapScene {
+    let (floor_vertices, floor_indices) = create_floor(-2., 2.);
+    let mut triangles = create_triangles(&floor_vertices, &floor_indices, 0.5);
+    let roof = [
+        Vector3::new(-2., 0.5, -2.),
+        Vector3::new(0., 0.5, -2.),
+        Vector3::new(0., 0.5, 2.),
+        Vector3::new(-2., 0.5, 2.),
+    ];
+    [[0, 1, 2], [0, 2, 3]].iter().for_each(|t| {
+        triangles.push(LightmapTriangle {
+            positions: [roof[t[0]], roof[t[1]], roof[t[2]]],
+            albedo: Vector3::new(0.5, 0.5, 0.5),
+            emissive: Vector3::default_zero(),
+        });
+    });
+    LightmapScene::new(triangles, lights, options)
+}
+
+fn create_sun() -> GPULight {
+    GPULight {
+        light_type: LightType::Directional.into(),
+        direction: [0., -1., 0.],
+        range: -1.,
+        ..Default::default()
+    }
+}
+
+#[allow(dead_code)]
+fn lightmap_uvs_test() {
+    let (vertices, indices) = create_cube();
+    let options = LightmapOptions::default();
+    let (new_vertices, new_indices, size) =
+        generate_lightmap_uvs(&vertices, &indices, 1, &options).unwrap();
+    assert!(size.is_power_of_two());
+    assert!(size >= options.min_size && size <= options.max_size);
+    // Each face is a chart with its own corners
+    assert_eq!(new_vertices.len(), 24);
+    assert_eq!(new_indices.len(), indices.len());
+    new_indices.iter().zip(indices.iter()).for_each(|(&n, &i)| {
+        assert_eq!(new_vertices[n as usize].pos, vertices[i as usize].pos);
+    });
+    new_vertices.iter().for_each(|v| {
+        let uv = vertex_uv(v, 1);
+        assert!(uv.x > 0. && uv.x < 1. && uv.y > 0. && uv.y < 1.);
+        assert_eq!(v.uv_0, Vector2::new(0., 0.));
+    });
+    // Faces don't overlap in the lightmap
+    let faces: Vec<(Vector2, Vector2)> = new_indices
+        .chunks(6)
+        .map(|face| {
+            face.iter().fold(
+                (Vector2::new(1., 1.), Vector2::new(0., 0.)),
+                |(min, max), &i| {
+                    let uv = vertex_uv(&new_vertices[i as usize], 1);
+                    (min.min(uv), max.max(uv))
+                },
+            )
+        })
+        .collect();
+    faces.iter().enumerate().for_each(|(i, a)| {
+        faces.iter().skip(i + 1).for_each(|b| {
+            assert!(
+                a.1.x < b.0.x || b.1.x < a.0.x || a.1.y < b.0.y || b.1.y < a.0.y,
+                "Charts overlap"
+            );
+        });
+    });
+
+    // Charts with padding can't fit in a tiny lightmap
+    let options = LightmapOptions {
+        max_size: 4,
+        ..Default::default()
+    };
+    assert!(generate_lightmap_uvs(&vertices, &indices, 1, &options).is_none());
+}
+
+#[allow(dead_code)]
+fn lightmap_scene_test() {
+    let options = LightmapOptions {
+        sky_color: [1.; 3],
+        bounces: 0,
+        ..Default::default()
+    };
+    let scene = create_scene(vec![create_sun()], &options);
+    let up = Vector3::new(0., 1., 0.);
+    let (triangle, distance) = scene.intersect(Vector3::new(-1., 0.1, 0.5), up).unwrap();
+    assert!(triangle >= 2);
+    assert!((distance - 0.4).abs() < 1e-4);
+    assert!(scene.intersect(Vector3::new(1., 0.1, 0.5), up).is_none());
+    assert!(scene.is_occluded(Vector3::new(-1., 0.1, 0.5), up, 1.));
+    assert!(!scene.is_occluded(Vector3::new(-1., 0.1, 0.5), up, 0.3));
+
+    // Direct light is shadowed by the roof
+    let lit = scene.direct_irradiance(Vector3::new(1.5, 0., 0.), up);
+    let shadowed = scene.direct_irradiance(Vector3::new(-1., 0., 0.), up);
+    assert!((lit.x - 1.).abs() < 1e-4);
+    assert_eq!(shadowed.x, 0.);
+
+    // Sky is seen less under the roof, where occlusion is higher
+    let mut seed = 0;
+    let open = scene.bake_texel(Vector3::new(1.8, 0., 0.), up, &mut seed);
+    let covered = scene.bake_texel(Vector3::new(-1., 0., 0.), up, &mut seed);
+    assert_eq!(open[3], 1.);
+    assert!(open[0] > 1. + 0.8 * PI);
+    assert!(covered[3] < 0.5);
+    assert!(covered[0] < 0.5 * PI);
+
+    // Light bounces from the lit floor to the bottom of the roof
+    let options = LightmapOptions {
+        bounces: 1,
+        ..Default::default()
+    };
+    let scene = create_scene(vec![create_sun()], &options);
+    let down = Vector3::new(0., -1., 0.);
+    let roof = scene.bake_texel(Vector3::new(-0.1, 0.5, 0.), down, &mut seed);
+    assert!(roof[0] > 0.05);
+    let options = LightmapOptions {
+        bounces: 0,
+        ..options
+    };
+    let scene = create_scene(vec![create_sun()], &options);
+    let roof = scene.bake_texel(Vector3::new(-0.1, 0.5, 0.), down, &mut seed);
+    assert_eq!(roof[0], 0.);
+}
+
+#[allow(dead_code)]
+fn lightmap_bake_test() {
+    let options = LightmapOptions {
+        texels_per_unit: 4.,
+        samples: 16,
+        bounces: 0,
+        ..Default::default()
+    };
+    let (vertices, indices) = create_floor(-2., 2.);
+    let (vertices, indices, size) =
+        generate_lightmap_uvs(&vertices, &indices, 1, &options).unwrap();
+    let scene = create_scene(vec![create_sun()], &options);
+    let texels = bake_lightmap(&scene, &vertices, &indices, 1, size);
+    assert_eq!(texels.len(), (size * size) as usize);
+    let lit = texels.iter().filter(|t| (t[0] - 1.).abs() < 1e-4).count();
+    let occluded = texels.iter().filter(|t| t[3] < 0.5).count();
+    assert!(lit > 0 && occluded > 0);
+    texels.iter().for_each(|t| {
+        assert!(t[0] >= 0. && t[3] >= 0. && t[3] <= 1.);
+    });
+
+    let encoded = encode_lightmap(&[[0., 0.1, 1., 0.25], [5., 100., -1., 2.]]);
+    assert_eq!(encoded.len(), 8);
+    assert_eq!(encoded[3], 64);
+    assert_eq!(encoded[6], 0);
+    assert_eq!(encoded[7], 255);
+    [0., 0.1, 1., 5.]
+        .iter()
+        .zip([encoded[0], encoded[1], encoded[2], encoded[4]])
+        .for_each(|(&e, c)| {
+            assert!((decode_lightmap_irradiance(c) - e).abs() <= e * 0.05 + 0.01);
+        });
+}
+
+#[test]
+fn tests() {
+    lightmap_uvs_test();
+    lightmap_scene_test();
+    lightmap_bake_test();
+}
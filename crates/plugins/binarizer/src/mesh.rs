@@ -1,12 +1,133 @@
-use std::mem::size_of;
+use std::{mem::size_of, path::Path};
 
+use crate::{
+    adjacency::{build_meshlets_adjacency, group_meshlets, group_meshlets_with_metis},
+    matches_glob,
+};
+
+use inox_bvh::{create_linearized_bvh, BVHTree, AABB};
 use inox_math::{VecBase, VecBaseFloat, Vector2, Vector3, Vector4};
-use inox_render::{MeshData, MeshletData, VertexAttributeLayout};
-use inox_resources::to_slice;
+use inox_render::{MeshData, MeshMorphData, MeshletData, VertexAttributeLayout, MAX_LOD_LEVELS};
+use inox_resources::{to_slice, MeshLodStats, MeshStats};
+use inox_serialize::{Deserialize, Serialize};
 use meshopt::DecodePosition;
 
-const MESHLET_MAX_VERTICES: usize = 192;
-const MESHLET_MAX_TRIANGLES: usize = 128;
+// Limits of meshopt for meshlets
+const MESHLET_MAX_VERTICES_LIMIT: usize = 255;
+const MESHLET_MAX_TRIANGLES_LIMIT: usize = 512;
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "inox_serialize")]
+pub enum MeshletGrouping {
+    // Partitions the graph of meshlets weighted by the edges they share
+    #[default]
+    Metis,
+    // Grows groups from a meshlet adding its most connected neighbours
+    Adjacency,
+}
+
+// Size of meshlets and how their LOD hierarchy is built
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(crate = "inox_serialize", default)]
+pub struct MeshletOptions {
+    pub max_vertices: usize,
+    pub max_triangles: usize,
+    // Levels including the one of full detail
+    pub lod_count: usize,
+    // Meshlets simplified together into the next level
+    pub group_size: usize,
+    // Triangles of a group kept by its simplification
+    pub reduction_ratio: f32,
+    // Weights of normals and uvs in the simplification error, only positions count when zero
+    pub normal_weight: f32,
+    pub uv_weight: f32,
+    pub grouping: MeshletGrouping,
+}
+
+impl Default for MeshletOptions {
+    fn default() -> Self {
+        Self {
+            max_vertices: 192,
+            max_triangles: 128,
+            lod_count: MAX_LOD_LEVELS,
+            group_size: 4,
+            reduction_ratio: 0.5,
+            normal_weight: 0.,
+            uv_weight: 0.,
+            grouping: MeshletGrouping::default(),
+        }
+    }
+}
+
+impl MeshletOptions {
+    // Values that meshopt and the renderer can handle
+    pub fn clamped(&self) -> Self {
+        Self {
+            max_vertices: self.max_vertices.clamp(3, MESHLET_MAX_VERTICES_LIMIT),
+            max_triangles: (self.max_triangles.clamp(4, MESHLET_MAX_TRIANGLES_LIMIT) / 4) * 4,
+            lod_count: self.lod_count.clamp(1, MAX_LOD_LEVELS),
+            group_size: self.group_size.max(2),
+            reduction_ratio: self.reduction_ratio.clamp(0.05, 0.95),
+            normal_weight: self.normal_weight.max(0.),
+            uv_weight: self.uv_weight.max(0.),
+            grouping: self.grouping,
+        }
+    }
+    fn has_attributes(&self) -> bool {
+        self.normal_weight > 0. || self.uv_weight > 0.
+    }
+}
+
+// Meshlet options of the assets matching the glob, local to the data raw folder.
+// Values that are not specified are the default ones, not the ones of the config
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "inox_serialize")]
+pub struct MeshletOptionsOverride {
+    pub path: String,
+    pub meshlets: MeshletOptions,
+}
+
+// How meshes are processed before being binarized
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshOptions {
+    // Reorders vertices and indices for vertex cache, overdraw and vertex fetch
+    pub optimize: bool,
+    // Builds the simplified LOD levels, otherwise only the meshlets of full detail are kept
+    pub lods: bool,
+    pub meshlets: MeshletOptions,
+    // The last one matching an asset wins
+    pub overrides: Vec<MeshletOptionsOverride>,
+}
+
+impl Default for MeshOptions {
+    fn default() -> Self {
+        Self {
+            optimize: true,
+            lods: true,
+            meshlets: MeshletOptions::default(),
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl MeshOptions {
+    // Options of an asset, with the meshlet options of its override if any
+    pub fn for_asset(&self, path: &Path, data_raw_folder: &Path) -> Self {
+        let local_path = path.strip_prefix(data_raw_folder).unwrap_or(path);
+        let meshlets = self
+            .overrides
+            .iter()
+            .rev()
+            .find(|o| matches_glob(&o.path, local_path))
+            .map_or(self.meshlets, |o| o.meshlets);
+        Self {
+            optimize: self.optimize,
+            lods: self.lods,
+            meshlets: meshlets.clamped(),
+            overrides: Vec::new(),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct MeshVertex {
@@ -18,6 +139,8 @@ pub struct MeshVertex {
     pub uv_1: Vector2,
     pub uv_2: Vector2,
     pub uv_3: Vector2,
+    pub joints: Vector4,
+    pub weights: Vector4,
 }
 
 impl MeshVertex {
@@ -30,6 +153,8 @@ impl MeshVertex {
             && self.uv_1 == other.uv_1
             && self.uv_2 == other.uv_2
             && self.uv_3 == other.uv_3
+            && self.joints == other.joints
+            && self.weights == other.weights
     }
 }
 
@@ -44,6 +169,8 @@ impl Default for MeshVertex {
             uv_1: Vector2::default_zero(),
             uv_2: Vector2::default_zero(),
             uv_3: Vector2::default_zero(),
+            joints: Vector4::default_zero(),
+            weights: Vector4::default_zero(),
         }
     }
 }
@@ -55,6 +182,16 @@ impl meshopt::DecodePosition for MeshVertex {
 }
 
 pub fn optimize_mesh<T>(vertices: &[T], indices: &[u32]) -> (Vec<T>, Vec<u32>)
+where
+    T: Clone + Default,
+{
+    let (new_vertices, new_indices, _) = optimize_mesh_with_remap(vertices, indices);
+    (new_vertices, new_indices)
+}
+
+// Source index of every optimized vertex is returned too,
+// to reorder data that is not part of the vertices like morph targets
+pub fn optimize_mesh_with_remap<T>(vertices: &[T], indices: &[u32]) -> (Vec<T>, Vec<u32>, Vec<u32>)
 where
     T: Clone + Default,
 {
@@ -73,25 +210,46 @@ where
         vertex_data_adapter.as_ref().unwrap(),
         threshold,
     );
-    let new_vertices = meshopt::optimize_vertex_fetch(&mut new_indices, vertices);
-
-    (new_vertices, new_indices)
+    // Unused vertices are removed and remapped to !0
+    let remap = meshopt::optimize_vertex_fetch_remap(&new_indices, vertices.len());
+    let mut source_indices = vec![0; remap.iter().filter(|&&i| i != !0).count()];
+    remap
+        .iter()
+        .enumerate()
+        .filter(|&(_, &i)| i != !0)
+        .for_each(|(source_index, &i)| source_indices[i as usize] = source_index as u32);
+    new_indices.iter_mut().for_each(|i| *i = remap[*i as usize]);
+    let new_vertices = source_indices
+        .iter()
+        .map(|&i| vertices[i as usize].clone())
+        .collect();
+
+    (new_vertices, new_indices, source_indices)
 }
 
 pub fn create_mesh_data(
     vertex_layout: VertexAttributeLayout,
     vertices: &[MeshVertex],
     indices: &[u32],
+    is_skinned: bool,
+    morph_targets: MeshMorphData,
 ) -> MeshData {
     let mut mesh_data = MeshData {
         vertex_layout: VertexAttributeLayout::HasPosition,
         aabb_max: Vector3::new(-f32::INFINITY, -f32::INFINITY, -f32::INFINITY),
         aabb_min: Vector3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        morph_targets,
         ..Default::default()
     };
-    vertices.iter().for_each(|v| {
-        mesh_data.aabb_max = mesh_data.aabb_max.max(v.pos.xyz());
-        mesh_data.aabb_min = mesh_data.aabb_min.min(v.pos.xyz());
+    // Bounds contain the vertices moved by morph targets too, so they're valid with any weights
+    let extents = vertices
+        .iter()
+        .enumerate()
+        .map(|(i, v)| mesh_data.morph_targets.vertex_extents(i, v.pos.xyz()))
+        .collect::<Vec<_>>();
+    extents.iter().for_each(|(min, max)| {
+        mesh_data.aabb_max = mesh_data.aabb_max.max(*max);
+        mesh_data.aabb_min = mesh_data.aabb_min.min(*min);
     });
     mesh_data.vertex_layout = vertex_layout;
     mesh_data.indices = indices.to_vec();
@@ -123,13 +281,44 @@ pub fn create_mesh_data(
             mesh_data.insert_uv(v.uv_3);
         }
     });
+    if is_skinned {
+        vertices.iter().zip(extents).for_each(|(v, extents)| {
+            let joints = [
+                v.joints.x as u32,
+                v.joints.y as u32,
+                v.joints.z as u32,
+                v.joints.w as u32,
+            ];
+            mesh_data.insert_skin(extents, joints, v.weights);
+        });
+    }
     mesh_data
 }
 
+// Grows meshlets bounds to contain their vertices moved by morph targets
+pub fn expand_meshlets_with_morph_targets(mesh_data: &mut MeshData, vertices: &[MeshVertex]) {
+    let MeshData {
+        meshlets,
+        indices,
+        morph_targets,
+        ..
+    } = mesh_data;
+    meshlets.iter_mut().flatten().for_each(|m| {
+        let start = m.indices_offset as usize;
+        let end = start + m.indices_count as usize;
+        indices[start..end].iter().for_each(|&i| {
+            let (min, max) = morph_targets.vertex_extents(i as _, vertices[i as usize].pos.xyz());
+            m.aabb_min = m.aabb_min.min(min);
+            m.aabb_max = m.aabb_max.max(max);
+        });
+    });
+}
+
 pub fn compute_meshlets<T>(
     vertices: &[T],
     indices: &[u32],
     starting_offset: u32,
+    options: &MeshletOptions,
 ) -> (Vec<MeshletData>, Vec<u32>)
 where
     T: DecodePosition,
@@ -147,8 +336,8 @@ where
     let meshlets = meshopt::build_meshlets(
         indices,
         vertex_data_adapter.as_ref().unwrap(),
-        MESHLET_MAX_VERTICES,
-        MESHLET_MAX_TRIANGLES,
+        options.max_vertices,
+        options.max_triangles,
         cone_weight,
     );
     debug_assert!(!meshlets.meshlets.is_empty());
@@ -229,11 +418,13 @@ pub fn compute_clusters(
     mesh_indices_offset: usize,
     vertices: &[MeshVertex],
     indices: &[u32],
-    _lod_level: i32,
-) -> (Vec<u32>, Vec<MeshletData>) {
+    options: &MeshletOptions,
+) -> (Vec<u32>, Vec<MeshletData>, MeshLodStats) {
     let mut indices_offset = mesh_indices_offset;
     let mut cluster_indices = Vec::new();
     let mut cluster_meshlets = Vec::new();
+    let mut locked_vertices = 0;
+    let mut group_vertices_count = 0;
 
     let vertex_locks = compute_vertex_locks(groups, global_meshlets, vertices, indices);
 
@@ -287,22 +478,61 @@ pub fn compute_clusters(
         );
         let vertex_data_adapter = meshopt::VertexDataAdapter::new(vertices_bytes, vertex_stride, 0);
 
-        let mut target_count = num_meshlet_per_group.div_ceil(2) * MESHLET_MAX_TRIANGLES * 3;
+        let mut target_count = ((num_meshlet_per_group * options.max_triangles) as f32
+            * options.reduction_ratio)
+            .ceil() as usize
+            * 3;
         if target_count >= group_indices.len() {
-            target_count = group_indices.len() / 2;
+            target_count =
+                ((group_indices.len() / 3) as f32 * options.reduction_ratio) as usize * 3;
         }
 
-        let mut simplification_error = 0.0;
+        // Locks are indexed by group vertex, as the indices given to meshopt
+        let group_locks = group_vertices
+            .iter()
+            .map(|v| vertex_locks[v.pos.w as usize])
+            .collect::<Vec<_>>();
+        locked_vertices += group_locks.iter().filter(|&&l| l).count();
+        group_vertices_count += group_vertices.len();
 
-        let mut simplified_indices = meshopt::simplify_with_locks(
-            &group_indices,
-            vertex_data_adapter.as_ref().unwrap(),
-            &vertex_locks,
-            target_count,
-            f32::MAX,
-            meshopt::SimplifyOptions::Sparse | meshopt::SimplifyOptions::ErrorAbsolute,
-            Some(&mut simplification_error),
-        );
+        let mut simplification_error = 0.0;
+        let simplify_options =
+            meshopt::SimplifyOptions::Sparse | meshopt::SimplifyOptions::ErrorAbsolute;
+        let mut simplified_indices = if options.has_attributes() {
+            let attributes = group_vertices
+                .iter()
+                .flat_map(|v| [v.normal.x, v.normal.y, v.normal.z, v.uv_0.x, v.uv_0.y])
+                .collect::<Vec<_>>();
+            let weights = [
+                options.normal_weight,
+                options.normal_weight,
+                options.normal_weight,
+                options.uv_weight,
+                options.uv_weight,
+            ];
+            meshopt::simplify_with_attributes_and_locks(
+                &group_indices,
+                vertex_data_adapter.as_ref().unwrap(),
+                &attributes,
+                &weights,
+                weights.len() * size_of::<f32>(),
+                &group_locks,
+                target_count,
+                f32::MAX,
+                simplify_options,
+                Some(&mut simplification_error),
+            )
+        } else {
+            meshopt::simplify_with_locks(
+                &group_indices,
+                vertex_data_adapter.as_ref().unwrap(),
+                &group_locks,
+                target_count,
+                f32::MAX,
+                simplify_options,
+                Some(&mut simplification_error),
+            )
+        };
         max_error += simplification_error;
 
         meshlets_indices.iter().for_each(|&meshlet_index| {
@@ -321,8 +551,12 @@ pub fn compute_clusters(
             simplified_indices = group_indices;
         }
 
-        let (mut new_meshlets, group_indices) =
-            compute_meshlets(&group_vertices, &simplified_indices, indices_offset as u32);
+        let (mut new_meshlets, group_indices) = compute_meshlets(
+            &group_vertices,
+            &simplified_indices,
+            indices_offset as u32,
+            options,
+        );
 
         let mut global_group_indices = Vec::with_capacity(group_indices.len());
         group_indices.iter().for_each(|&i| {
@@ -344,5 +578,221 @@ pub fn compute_clusters(
         cluster_indices.append(&mut global_group_indices);
         cluster_meshlets.append(&mut new_meshlets);
     });
-    (cluster_indices, cluster_meshlets)
+    let stats = MeshLodStats {
+        meshlets: cluster_meshlets.len() as _,
+        triangles: (cluster_indices.len() / 3) as _,
+        groups: groups.len() as _,
+        max_error: cluster_meshlets.iter().fold(0., |e, m| e.max(m.error)),
+        locked_border_ratio: if group_vertices_count > 0 {
+            locked_vertices as f32 / group_vertices_count as f32
+        } else {
+            0.
+        },
+    };
+    (cluster_indices, cluster_meshlets, stats)
+}
+
+// Builds the meshlets of every LOD level, grouping and simplifying the previous level
+// until a single group is left. Indices of all the levels are appended in the same buffer
+pub fn compute_meshlets_lods(
+    vertices: &[MeshVertex],
+    indices: &[u32],
+    options: &MeshOptions,
+) -> (Vec<Vec<MeshletData>>, Vec<u32>, Vec<MeshLodStats>) {
+    let meshlet_options = options.meshlets.clamped();
+    let mut mesh_indices_offset = 0;
+    let mut previous_meshlets_starting_offset = 0;
+    let mut meshlets_per_lod = Vec::new();
+    let (meshlets, mut mesh_indices) = compute_meshlets(vertices, indices, 0, &meshlet_options);
+    let mut lods_stats = vec![MeshLodStats {
+        meshlets: meshlets.len() as _,
+        triangles: (indices.len() / 3) as _,
+        ..Default::default()
+    }];
+
+    inox_log::debug_log!(
+        "LOD 0 has {} meshlets and {} triangles",
+        meshlets.len(),
+        indices.len() / 3
+    );
+
+    let mut is_meshlet_tree_created =
+        meshlets.len() <= 1 || !options.lods || meshlet_options.lod_count <= 1;
+    meshlets_per_lod.push(meshlets);
+    mesh_indices_offset += mesh_indices.len();
+
+    let mut level: usize = 0;
+    while !is_meshlet_tree_created {
+        let previous_lod_meshlets = meshlets_per_lod.last_mut().unwrap();
+        let meshlets_adjacency =
+            build_meshlets_adjacency(previous_lod_meshlets, vertices, &mesh_indices);
+        let groups = match meshlet_options.grouping {
+            MeshletGrouping::Metis => {
+                group_meshlets_with_metis(&meshlets_adjacency, meshlet_options.group_size)
+            }
+            MeshletGrouping::Adjacency => {
+                group_meshlets(&meshlets_adjacency, meshlet_options.group_size)
+            }
+        };
+
+        let (mut cluster_indices, cluster_meshlets, lod_stats) = compute_clusters(
+            &groups,
+            previous_lod_meshlets,
+            previous_meshlets_starting_offset,
+            mesh_indices_offset,
+            vertices,
+            &mesh_indices,
+            &meshlet_options,
+        );
+
+        inox_log::debug_log!(
+            "LOD {} has {} meshlets and {} triangles",
+            level,
+            cluster_meshlets.len(),
+            cluster_indices.len() / 3
+        );
+
+        mesh_indices_offset += cluster_indices.len();
+        mesh_indices.append(&mut cluster_indices);
+        previous_meshlets_starting_offset += meshlets_per_lod[level].len();
+        meshlets_per_lod.push(cluster_meshlets);
+        lods_stats.push(lod_stats);
+        level += 1;
+
+        is_meshlet_tree_created = groups.len() == 1 || level >= meshlet_options.lod_count - 1;
+    }
+    (meshlets_per_lod, mesh_indices, lods_stats)
+}
+
+// Replaces the meshlets BVH of every LOD level with a new one built from meshlets bounds
+pub fn compute_meshlets_bvh(mesh_data: &mut MeshData) {
+    mesh_data.meshlets_bvh.clear();
+    mesh_data.meshlets.iter_mut().for_each(|meshlets| {
+        let mut meshlets_aabbs = Vec::new();
+        meshlets_aabbs.resize_with(meshlets.len(), AABB::empty);
+        meshlets.iter_mut().enumerate().for_each(|(i, m)| {
+            meshlets_aabbs[i] = AABB::create(m.aabb_min, m.aabb_max, i as _);
+            m.bvh_offset = i as _;
+        });
+        let bvh = BVHTree::new(&meshlets_aabbs);
+        mesh_data.meshlets_bvh.push(create_linearized_bvh(&bvh));
+    });
+}
+
+// Optimizes a mesh without skin and morph targets and builds its meshlets LODs and BVHs
+pub fn build_mesh_data(
+    vertex_layout: VertexAttributeLayout,
+    vertices: &[MeshVertex],
+    indices: &[u32],
+    options: &MeshOptions,
+) -> (MeshData, Vec<MeshLodStats>) {
+    let (geometry_vertices, geometry_indices) = if options.optimize {
+        optimize_mesh(vertices, indices)
+    } else {
+        (vertices.to_vec(), indices.to_vec())
+    };
+    let (meshlets_per_lod, mesh_indices, lods_stats) =
+        compute_meshlets_lods(&geometry_vertices, &geometry_indices, options);
+    let mut mesh_data = create_mesh_data(
+        vertex_layout,
+        &geometry_vertices,
+        &mesh_indices,
+        false,
+        MeshMorphData::default(),
+    );
+    mesh_data.meshlets = meshlets_per_lod;
+    compute_meshlets_bvh(&mut mesh_data);
+    (mesh_data, lods_stats)
+}
+
+// Triangles are the ones of full detail, LODs are only counted as meshlets
+// and detailed in the stats of the meshlets hierarchy
+pub fn compute_mesh_stats(name: &str, mesh_data: &MeshData, lods: Vec<MeshLodStats>) -> MeshStats {
+    let triangles = mesh_data.meshlets.first().map_or(0, |meshlets| {
+        meshlets.iter().map(|m| m.indices_count / 3).sum()
+    });
+    MeshStats {
+        name: name.to_string(),
+        vertices: mesh_data.vertex_count() as _,
+        triangles,
+        meshlets_per_lod: mesh_data.meshlets.iter().map(|m| m.len() as _).collect(),
+        lods,
+    }
+}
+
+// Material of formats that don't have one: the color comes from vertex colors, if any
+pub fn default_material_data() -> MaterialData {
+    MaterialData {
+        flags: MaterialFlags::MetallicRoughness | MaterialFlags::AlphaModeOpaque,
+        metallic_factor: 0.,
+        emissive_color: Vector3::default_zero(),
+        ..Default::default()
+    }
+}
+
+// Smooth normals: every vertex averages the normals of its triangles, weighted by their area
+pub fn compute_normals(vertices: &mut [MeshVertex], indices: &[u32]) {
+    let mut normals = vec![Vector3::default_zero(); vertices.len()];
+    indices.chunks_exact(3).for_each(|t| {
+        let a = vertices[t[0] as usize].pos.xyz();
+        let b = vertices[t[1] as usize].pos.xyz();
+        let c = vertices[t[2] as usize].pos.xyz();
+        let n = (b - a).cross(c - a);
+        t.iter().for_each(|&i| normals[i as usize] += n);
+    });
+    vertices.iter_mut().zip(normals).for_each(|(v, n)| {
+        let n = if n.length() > 0. {
+            n.normalized()
+        } else {
+            Vector3::new(0., 0., 1.)
+        };
+        v.normal = n.extend(1.);
+    });
+}
+
+// Tangents follow the uv_0 directions. Without valid uvs any direction orthogonal
+// to the normal is used. Bitangent handedness is stored in w
+pub fn compute_tangents(vertices: &mut [MeshVertex], indices: &[u32]) {
+    let mut tangents = vec![Vector3::default_zero(); vertices.len()];
+    let mut bitangents = vec![Vector3::default_zero(); vertices.len()];
+    indices.chunks_exact(3).for_each(|t| {
+        let (v0, v1, v2) = (
+            &vertices[t[0] as usize],
+            &vertices[t[1] as usize],
+            &vertices[t[2] as usize],
+        );
+        let e1 = v1.pos.xyz() - v0.pos.xyz();
+        let e2 = v2.pos.xyz() - v0.pos.xyz();
+        let d1 = v1.uv_0 - v0.uv_0;
+        let d2 = v2.uv_0 - v0.uv_0;
+        let det = d1.x * d2.y - d2.x * d1.y;
+        if det.abs() <= f32::EPSILON {
+            return;
+        }
+        let tangent = (e1 * d2.y - e2 * d1.y) / det;
+        let bitangent = (e2 * d1.x - e1 * d2.x) / det;
+        t.iter().for_each(|&i| {
+            tangents[i as usize] += tangent;
+            bitangents[i as usize] += bitangent;
+        });
+    });
+    vertices.iter_mut().enumerate().for_each(|(i, v)| {
+        let n = v.normal.xyz();
+        let mut tangent = tangents[i] - n * n.dot_product(tangents[i]);
+        if tangent.length() <= f32::EPSILON {
+            let axis = if n.x.abs() < 0.9 {
+                Vector3::new(1., 0., 0.)
+            } else {
+                Vector3::new(0., 1., 0.)
+            };
+            tangent = axis - n * n.dot_product(axis);
+        }
+        let tangent = tangent.normalized();
+        let w = if n.cross(tangent).dot_product(bitangents[i]) < 0. {
+            -1.
+        } else {
+            1.
+        };
+        v.tangent = tangent.extend(w);
+    });
 }
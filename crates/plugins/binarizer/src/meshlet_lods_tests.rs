@@ -0,0 +1,203 @@
+use std::path::Path;
+
+use inox_math::{Vector2, Vector4};
+use inox_render::MAX_LOD_LEVELS;
+
+use crate::{
+    mesh::{compute_meshlets_lods, optimize_mesh_with_remap, MeshVertex},
+    MeshOptions, MeshletGrouping, MeshletOptions, MeshletOptionsOverride,
+};
+
+const GRID_SIZE: usize = 64;
+
+// Wavy grid, so that simplification has an error
+fn create_grid() -> (Vec<MeshVertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    (0..=GRID_SIZE).for_each(|y| {
+        (0..=GRID_SIZE).for_each(|x| {
+            let (fx, fy) = (x as f32 / GRID_SIZE as f32, y as f32 / GRID_SIZE as f32);
+            vertices.push(MeshVertex {
+                pos: Vector4::new(fx, (fx * 12.).sin() * (fy * 7.).cos() * 0.05, fy, 1.),
+                normal: Vector4::new(0., 1., 0., 0.),
+                uv_0: Vector2::new(fx, fy),
+                ..Default::default()
+            });
+        });
+    });
+    let mut indices = Vec::new();
+    (0..GRID_SIZE).for_each(|y| {
+        (0..GRID_SIZE).for_each(|x| {
+            let i = (y * (GRID_SIZE + 1) + x) as u32;
+            let below = i + GRID_SIZE as u32 + 1;
+            indices.extend_from_slice(&[i, below, i + 1, i + 1, below, below + 1]);
+        });
+    });
+    (vertices, indices)
+}
+
+#[allow(dead_code)]
+fn meshlet_options_test() {
+    let options = MeshletOptions {
+        max_vertices: 1000,
+        max_triangles: 1000,
+        lod_count: 100,
+        group_size: 1,
+        reduction_ratio: 2.,
+        normal_weight: -1.,
+        ..Default::default()
+    }
+    .clamped();
+    assert_eq!(options.max_vertices, 255);
+    assert_eq!(options.max_triangles, 512);
+    assert_eq!(options.lod_count, MAX_LOD_LEVELS);
+    assert_eq!(options.group_size, 2);
+    assert_eq!(options.reduction_ratio, 0.95);
+    assert_eq!(options.normal_weight, 0.);
+    // meshopt wants a multiple of 4 triangles
+    let options = MeshletOptions {
+        max_triangles: 126,
+        ..Default::default()
+    };
+    assert_eq!(options.clamped().max_triangles, 124);
+    assert_eq!(
+        MeshletOptions::default().clamped(),
+        MeshletOptions::default()
+    );
+
+    let data_raw_folder = Path::new("data_raw");
+    let mesh_options = MeshOptions {
+        meshlets: MeshletOptions {
+            lod_count: 4,
+            ..Default::default()
+        },
+        overrides: vec![
+            MeshletOptionsOverride {
+                path: "models/**".to_string(),
+                meshlets: MeshletOptions {
+                    max_triangles: 64,
+                    ..Default::default()
+                },
+            },
+            MeshletOptionsOverride {
+                path: "models/rocks/*.obj".to_string(),
+                meshlets: MeshletOptions {
+                    lod_count: 2,
+                    ..Default::default()
+                },
+            },
+        ],
+        ..Default::default()
+    };
+    let other = mesh_options.for_asset(Path::new("data_raw/other.stl"), data_raw_folder);
+    assert_eq!(other.meshlets, mesh_options.meshlets);
+    assert!(other.overrides.is_empty());
+    let tree = mesh_options.for_asset(Path::new("data_raw/models/tree.gltf"), data_raw_folder);
+    assert_eq!(tree.meshlets.max_triangles, 64);
+    assert_eq!(tree.meshlets.lod_count, MAX_LOD_LEVELS);
+    // The last matching override wins, values it doesn't have are the default ones
+    let rock = mesh_options.for_asset(Path::new("data_raw/models/rocks/rock.obj"), data_raw_folder);
+    assert_eq!(rock.meshlets.lod_count, 2);
+    assert_eq!(rock.meshlets.max_triangles, 128);
+    assert_eq!(rock.optimize, mesh_options.optimize);
+}
+
+#[allow(dead_code)]
+fn meshlet_lods_test() {
+    let (vertices, indices) = create_grid();
+    let options = MeshOptions {
+        meshlets: MeshletOptions {
+            max_triangles: 64,
+            lod_count: 4,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (meshlets_per_lod, mesh_indices, lods) =
+        compute_meshlets_lods(&vertices, &indices, &options);
+    assert_eq!(meshlets_per_lod.len(), lods.len());
+    assert!(lods.len() > 1 && lods.len() <= 4);
+    assert_eq!(lods[0].triangles as usize, GRID_SIZE * GRID_SIZE * 2);
+    assert_eq!(lods[0].groups, 0);
+    let mut indices_count = 0;
+    meshlets_per_lod
+        .iter()
+        .zip(lods.iter())
+        .for_each(|(meshlets, lod)| {
+            assert_eq!(meshlets.len(), lod.meshlets as usize);
+            meshlets.iter().for_each(|m| {
+                assert!(m.indices_count <= 64 * 3);
+                indices_count += m.indices_count as usize;
+            });
+        });
+    assert_eq!(indices_count, mesh_indices.len());
+    lods.windows(2).for_each(|l| {
+        assert!(l[1].triangles < l[0].triangles);
+        assert!(l[1].max_error >= l[0].max_error);
+        assert!(l[1].groups > 0 && l[1].groups <= l[0].meshlets);
+        assert!(l[1].locked_border_ratio > 0. && l[1].locked_border_ratio < 1.);
+    });
+
+    // Other grouping and attribute aware simplification build a hierarchy as well
+    let options = MeshOptions {
+        meshlets: MeshletOptions {
+            grouping: MeshletGrouping::Adjacency,
+            group_size: 8,
+            normal_weight: 1.,
+            uv_weight: 0.5,
+            ..options.meshlets
+        },
+        ..Default::default()
+    };
+    let (_, _, lods) = compute_meshlets_lods(&vertices, &indices, &options);
+    assert!(lods.len() > 1);
+    assert!(lods[1].triangles < lods[0].triangles);
+
+    let options = MeshOptions {
+        lods: false,
+        ..options
+    };
+    let (meshlets_per_lod, _, lods) = compute_meshlets_lods(&vertices, &indices, &options);
+    assert_eq!(meshlets_per_lod.len(), 1);
+    assert_eq!(lods.len(), 1);
+}
+
+#[allow(dead_code)]
+fn optimize_remap_test() {
+    let (mut vertices, indices) = create_grid();
+    // Not referenced by any triangle, so it's removed
+    vertices.push(MeshVertex::default());
+    let (new_vertices, new_indices, source_indices) = optimize_mesh_with_remap(&vertices, &indices);
+    assert_eq!(new_vertices.len(), vertices.len() - 1);
+    assert_eq!(source_indices.len(), new_vertices.len());
+    assert!(source_indices
+        .iter()
+        .all(|&i| (i as usize) < vertices.len() - 1));
+    new_vertices
+        .iter()
+        .zip(source_indices.iter())
+        .for_each(|(v, &i)| assert!(v.pos == vertices[i as usize].pos));
+    // Triangles are reordered, but they still reference the same source vertices
+    let triangles = |indices: &[u32], to_source: &dyn Fn(u32) -> u32| {
+        let mut triangles = indices
+            .chunks(3)
+            .map(|t| {
+                let mut t = [to_source(t[0]), to_source(t[1]), to_source(t[2])];
+                t.sort();
+                t
+            })
+            .collect::<Vec<_>>();
+        triangles.sort();
+        triangles
+    };
+    assert_eq!(
+        triangles(&indices, &|i| i),
+        triangles(&new_indices, &|i| source_indices[i as usize])
+    );
+}
+
+#[test]
+fn tests() {
+    meshlet_options_test();
+    meshlet_lods_test();
+    optimize_remap_test();
+}
@@ -0,0 +1,113 @@
+use std::fs::{self, create_dir_all};
+
+use inox_math::Vector4;
+use inox_platform::PLATFORM_TYPE_PC;
+use inox_render::{MaterialData, MaterialFlags, MeshData, TextureType};
+use inox_scene::{ObjectData, SceneData};
+use inox_serialize::deserialize;
+
+use crate::ObjCompiler;
+
+// A quad n-gon and a triangle with negative indices and no normals, in two groups:
+// the triangle one has no material and a name that isn't a valid filename
+const TEST_OBJ: &str = "# Test model
+mtllib quad.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+g Quad
+usemtl QuadMaterial
+f 1/1/1 2/2/1 3/3/1 4/4/1
+g ../Tri
+v 0 0 1
+v 1 0 1
+v 0 1 1
+f -3 -2 -1
+";
+
+const TEST_MTL: &str = "newmtl QuadMaterial
+Kd 0.5 0.25 1.0
+d 0.5
+Ns 0
+map_Kd quad.png
+";
+
+#[allow(dead_code)]
+fn obj_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_obj_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    let data_raw_folder = test_folder.join("data_raw");
+    let data_folder = test_folder.join("data");
+    let model_folder = data_raw_folder.join("models").join("Quad");
+    create_dir_all(model_folder.as_path()).unwrap();
+    create_dir_all(data_folder.as_path()).unwrap();
+
+    fs::write(model_folder.join("quad.obj"), TEST_OBJ).unwrap();
+    fs::write(model_folder.join("quad.mtl"), TEST_MTL).unwrap();
+    let model_path = model_folder.join("quad.obj").canonicalize().unwrap();
+
+    let compiler =
+        ObjCompiler::<PLATFORM_TYPE_PC>::new(data_raw_folder.as_path(), data_folder.as_path());
+    assert!(compiler.process_path(model_path.as_path()));
+
+    let output_folder = data_folder
+        .canonicalize()
+        .unwrap()
+        .join("models")
+        .join("Quad");
+
+    let quad = deserialize::<MeshData>(
+        &fs::read(output_folder.join("mesh").join("Quad_Primitive_0.mesh")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(quad.vertex_count(), 4);
+    assert_eq!(
+        quad.meshlets[0]
+            .iter()
+            .map(|m| m.indices_count)
+            .sum::<u32>(),
+        6
+    );
+    assert!(quad.material.ends_with("QuadMaterial.material"));
+
+    let triangle = deserialize::<MeshData>(
+        &fs::read(output_folder.join("mesh").join("___Tri_Primitive_0.mesh")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(triangle.vertex_count(), 3);
+    assert_eq!(triangle.aabb_min.z, 1.);
+    assert!(triangle.material.ends_with("quad_DefaultMaterial.material"));
+    assert!(output_folder.join("object").join("___Tri.object").exists());
+
+    let material = deserialize::<MaterialData>(
+        &fs::read(output_folder.join("material").join("QuadMaterial.material")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(material.base_color, Vector4::new(0.5, 0.25, 1., 0.5));
+    assert_eq!(material.roughness_factor, 1.);
+    assert!(material.flags.contains(MaterialFlags::AlphaModeBlend));
+    assert!(material.textures[TextureType::BaseColor as usize].ends_with("quad.png"));
+
+    let object = deserialize::<ObjectData>(
+        &fs::read(output_folder.join("object").join("Quad.object")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(object.components.len(), 1);
+
+    let scene =
+        deserialize::<SceneData>(&fs::read(output_folder.join("quad.scene")).unwrap()).unwrap();
+    assert_eq!(scene.objects.len(), 2);
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[test]
+fn tests() {
+    obj_test();
+}
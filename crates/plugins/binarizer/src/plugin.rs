@@ -46,6 +46,17 @@ impl Plugin for BinarizerPlugin {
             should_end_on_completion: AtomicBool::new(true),
             optimize_meshes: AtomicBool::new(true),
             preprocess_shaders_paths: shader_paths,
+            clean: command_parser.has("clean"),
+            verify: command_parser.has("verify"),
+            only: command_parser.get_values_of::<String>("only"),
+            jobs: command_parser
+                .get_values_of::<usize>("jobs")
+                .first()
+                .copied()
+                .unwrap_or_default(),
+            skip_mesh_lods: command_parser.has("no_mesh_lods"),
+            bake_lightmaps: command_parser.has("bake_lightmaps"),
+            ..Default::default()
         };
         for name in platform.iter() {
             let name = name.as_str();
@@ -0,0 +1,186 @@
+use std::{
+    fs::{self, create_dir_all},
+    path::{Path, PathBuf},
+};
+
+use inox_render::{MeshData, VertexAttributeLayout};
+use inox_scene::SceneData;
+use inox_serialize::deserialize;
+
+use crate::{PlyCompiler, StlCompiler};
+
+// A quad with an uchar color for each vertex and no normals
+const TEST_ASCII_PLY: &str = "ply
+format ascii 1.0
+comment test quad
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+";
+
+const TEST_ASCII_STL: &str = "solid quad
+facet normal 0 0 1
+outer loop
+vertex 0 0 0
+vertex 1 0 0
+vertex 1 1 0
+endloop
+endfacet
+facet normal 0 0 1
+outer loop
+vertex 0 0 0
+vertex 1 1 0
+vertex 0 1 0
+endloop
+endfacet
+endsolid quad
+";
+
+fn create_binary_ply(path: &Path) {
+    let mut data = b"ply
+format binary_big_endian 1.0
+element vertex 3
+property double x
+property double y
+property double z
+element face 1
+property list uchar uint vertex_indices
+end_header
+"
+    .to_vec();
+    [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]
+        .iter()
+        .flatten()
+        .for_each(|v: &f64| data.extend_from_slice(&v.to_be_bytes()));
+    data.push(3);
+    [0u32, 1, 2]
+        .iter()
+        .for_each(|i| data.extend_from_slice(&i.to_be_bytes()));
+    fs::write(path, data).unwrap();
+}
+
+// Two coplanar triangles and one on a different plane, then the extra bytes some exporters add
+fn create_binary_stl(path: &Path, trailing_bytes: usize) {
+    let mut data = vec![0u8; 80];
+    let triangles: [[f32; 9]; 3] = [
+        [0., 0., 0., 1., 0., 0., 1., 1., 0.],
+        [0., 0., 0., 1., 1., 0., 0., 1., 0.],
+        [0., 0., 0., 0., 1., 0., 0., 0., 1.],
+    ];
+    data.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+    triangles.iter().for_each(|t| {
+        data.extend_from_slice(&[0u8; 12]);
+        t.iter()
+            .for_each(|v| data.extend_from_slice(&v.to_le_bytes()));
+        data.extend_from_slice(&[0u8; 2]);
+    });
+    data.resize(data.len() + trailing_bytes, 0);
+    fs::write(path, data).unwrap();
+}
+
+fn compile_test_meshes(test_folder: &Path) -> PathBuf {
+    let data_raw_folder = test_folder.join("data_raw");
+    let data_folder = test_folder.join("data");
+    let model_folder = data_raw_folder.join("models").join("Scans");
+    create_dir_all(model_folder.as_path()).unwrap();
+    create_dir_all(data_folder.as_path()).unwrap();
+
+    fs::write(model_folder.join("ascii_quad.ply"), TEST_ASCII_PLY).unwrap();
+    create_binary_ply(model_folder.join("binary_triangle.ply").as_path());
+    fs::write(model_folder.join("ascii_square.stl"), TEST_ASCII_STL).unwrap();
+    create_binary_stl(model_folder.join("binary_corner.stl").as_path(), 0);
+    create_binary_stl(model_folder.join("padded_corner.stl").as_path(), 16);
+    // Vertex count much larger than the file
+    fs::write(
+        model_folder.join("wrong_count.ply"),
+        TEST_ASCII_PLY.replace("element vertex 4", "element vertex 4000000000000"),
+    )
+    .unwrap();
+
+    let ply_compiler = PlyCompiler::new(data_raw_folder.as_path(), data_folder.as_path());
+    let stl_compiler = StlCompiler::new(data_raw_folder.as_path(), data_folder.as_path());
+    ["ascii_quad.ply", "binary_triangle.ply"]
+        .iter()
+        .for_each(|f| {
+            assert!(ply_compiler.process_path(&model_folder.join(f).canonicalize().unwrap()))
+        });
+    assert!(
+        !ply_compiler.process_path(&model_folder.join("wrong_count.ply").canonicalize().unwrap())
+    );
+    ["ascii_square.stl", "binary_corner.stl", "padded_corner.stl"]
+        .iter()
+        .for_each(|f| {
+            assert!(stl_compiler.process_path(&model_folder.join(f).canonicalize().unwrap()))
+        });
+
+    data_folder
+        .canonicalize()
+        .unwrap()
+        .join("models")
+        .join("Scans")
+}
+
+fn read_mesh(output_folder: &Path, name: &str) -> MeshData {
+    let path = output_folder.join("mesh").join(format!("{name}.mesh"));
+    deserialize::<MeshData>(&fs::read(path).unwrap()).unwrap()
+}
+
+#[allow(dead_code)]
+fn ply_stl_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_ply_stl_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    let output_folder = compile_test_meshes(test_folder.as_path());
+
+    let quad = read_mesh(output_folder.as_path(), "ascii_quad");
+    assert_eq!(quad.vertex_count(), 4);
+    assert!(quad.vertex_layout.contains(
+        VertexAttributeLayout::HasColor
+            | VertexAttributeLayout::HasNormal
+            | VertexAttributeLayout::HasTangent
+    ));
+    assert_eq!(
+        quad.meshlets[0]
+            .iter()
+            .map(|m| m.indices_count)
+            .sum::<u32>(),
+        6
+    );
+
+    let triangle = read_mesh(output_folder.as_path(), "binary_triangle");
+    assert_eq!(triangle.vertex_count(), 3);
+    assert!(!triangle
+        .vertex_layout
+        .intersects(VertexAttributeLayout::HasColor));
+
+    // Coplanar triangles share their vertices, the third one has its own
+    let square = read_mesh(output_folder.as_path(), "ascii_square");
+    assert_eq!(square.vertex_count(), 4);
+    let corner = read_mesh(output_folder.as_path(), "binary_corner");
+    assert_eq!(corner.vertex_count(), 7);
+    let padded_corner = read_mesh(output_folder.as_path(), "padded_corner");
+    assert_eq!(padded_corner.vertex_count(), 7);
+
+    let scene =
+        deserialize::<SceneData>(&fs::read(output_folder.join("binary_corner.scene")).unwrap())
+            .unwrap();
+    assert_eq!(scene.objects.len(), 1);
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[test]
+fn tests() {
+    ply_stl_test();
+}
@@ -0,0 +1,583 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use inox_render::SHADER_NO_KEYWORD;
+
+// Where a line of the preprocessed code comes from, lines start from 1
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SourceLocation {
+    pub file: PathBuf,
+    pub line: u32,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file = self.file.file_name().unwrap_or(self.file.as_os_str());
+        write!(f, "{}:{}", file.to_string_lossy(), self.line)
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct SourceMap {
+    files: Vec<PathBuf>,
+    lines: Vec<(usize, u32)>,
+}
+
+impl SourceMap {
+    fn push(&mut self, file: &Path, line: u32) {
+        let index = match self.files.iter().position(|f| f == file) {
+            Some(index) => index,
+            None => {
+                self.files.push(file.to_path_buf());
+                self.files.len() - 1
+            }
+        };
+        self.lines.push((index, line));
+    }
+
+    // Original location of a line of the preprocessed code, as naga line numbers start from 1
+    pub fn location(&self, line: u32) -> Option<SourceLocation> {
+        let index = line.checked_sub(1)? as usize;
+        self.lines.get(index).map(|(file, line)| SourceLocation {
+            file: self.files[*file].clone(),
+            line: *line,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PreprocessorError {
+    pub location: SourceLocation,
+    pub message: String,
+}
+
+impl fmt::Display for PreprocessorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+// Code without comments and empty lines, with the imported files it has been built from
+pub struct PreprocessedShader {
+    pub code: String,
+    pub source_map: SourceMap,
+    pub dependencies: Vec<PathBuf>,
+    pub keyword_sets: Vec<Vec<String>>,
+}
+
+struct Conditional {
+    location: SourceLocation,
+    parent_active: bool,
+    active: bool,
+    taken: bool,
+    has_else: bool,
+}
+
+// Supports #import, #define, #undef, #ifdef, #ifndef, #if, #elif, #else, #endif, #error
+// and #keywords. Every file is imported only once, even when more files import it
+pub struct ShaderPreprocessor {
+    defines: HashMap<String, String>,
+    included: HashSet<PathBuf>,
+    root_folder: PathBuf,
+    dependencies: Vec<PathBuf>,
+    keyword_sets: Vec<Vec<String>>,
+    code: String,
+    source_map: SourceMap,
+}
+
+impl ShaderPreprocessor {
+    // Defines are NAME or NAME=value, the ones without a value are 1 in expressions
+    pub fn new(defines: &[String]) -> Self {
+        let mut preprocessor = Self {
+            defines: HashMap::new(),
+            included: HashSet::new(),
+            root_folder: PathBuf::new(),
+            dependencies: Vec::new(),
+            keyword_sets: Vec::new(),
+            code: String::new(),
+            source_map: SourceMap::default(),
+        };
+        defines.iter().for_each(|d| match d.split_once('=') {
+            Some((name, value)) => preprocessor.define(name.trim(), value.trim()),
+            None => preprocessor.define(d.trim(), ""),
+        });
+        preprocessor
+    }
+
+    pub fn define(&mut self, name: &str, value: &str) {
+        self.defines.insert(name.to_string(), value.to_string());
+    }
+
+    pub fn preprocess(mut self, path: &Path) -> Result<PreprocessedShader, PreprocessorError> {
+        let path = path.canonicalize().unwrap_or(path.to_path_buf());
+        let location = SourceLocation {
+            file: path.clone(),
+            line: 0,
+        };
+        let code = fs::read_to_string(path.as_path())
+            .map_err(|e| Self::error(&location, format!("Unable to read shader: {e}")))?;
+        self.root_folder = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        self.included.insert(path.clone());
+        self.process_file(path.as_path(), &code)?;
+        Ok(PreprocessedShader {
+            code: self.code,
+            source_map: self.source_map,
+            dependencies: self.dependencies,
+            keyword_sets: self.keyword_sets,
+        })
+    }
+
+    fn error(location: &SourceLocation, message: String) -> PreprocessorError {
+        PreprocessorError {
+            location: location.clone(),
+            message,
+        }
+    }
+
+    fn process_file(&mut self, path: &Path, code: &str) -> Result<(), PreprocessorError> {
+        let mut conditionals: Vec<Conditional> = Vec::new();
+        let mut comment_depth = 0;
+        for (i, line) in code.lines().enumerate() {
+            let location = SourceLocation {
+                file: path.to_path_buf(),
+                line: i as u32 + 1,
+            };
+            let line = strip_comments(line, &mut comment_depth);
+            let line = line.trim();
+            if let Some(directive) = line.strip_prefix('#') {
+                self.process_directive(directive.trim(), &location, &mut conditionals)?;
+            } else if !line.is_empty() && conditionals.last().is_none_or(|c| c.active) {
+                let line = self.expand(line, &mut Vec::new());
+                self.code.push_str(&line);
+                self.code.push('\n');
+                self.source_map.push(path, location.line);
+            }
+        }
+        // Conditionals can't be closed by the file that imported this one
+        if let Some(conditional) = conditionals.last() {
+            return Err(Self::error(
+                &conditional.location,
+                "Conditional without #endif".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn process_directive(
+        &mut self,
+        directive: &str,
+        location: &SourceLocation,
+        conditionals: &mut Vec<Conditional>,
+    ) -> Result<(), PreprocessorError> {
+        let (name, arguments) = split_identifier(directive);
+        let arguments = arguments.trim();
+        let active = conditionals.last().is_none_or(|c| c.active);
+        match name {
+            "ifdef" | "ifndef" | "if" => {
+                // Skipped branches are not evaluated, as they could use undefined syntax
+                let condition = active
+                    && match name {
+                        "ifdef" => self
+                            .defines
+                            .contains_key(Self::define_name(arguments, location)?),
+                        "ifndef" => !self
+                            .defines
+                            .contains_key(Self::define_name(arguments, location)?),
+                        _ => self.evaluate(arguments, location)? != 0,
+                    };
+                conditionals.push(Conditional {
+                    location: location.clone(),
+                    parent_active: active,
+                    active: condition,
+                    taken: condition,
+                    has_else: false,
+                });
+            }
+            "elif" => {
+                let Some(conditional) = conditionals.last() else {
+                    return Err(Self::error(location, "#elif without #if".to_string()));
+                };
+                if conditional.has_else {
+                    return Err(Self::error(location, "#elif after #else".to_string()));
+                }
+                let condition = conditional.parent_active
+                    && !conditional.taken
+                    && self.evaluate(arguments, location)? != 0;
+                let conditional = conditionals.last_mut().unwrap();
+                conditional.active = condition;
+                conditional.taken |= condition;
+            }
+            "else" => {
+                let Some(conditional) = conditionals.last_mut() else {
+                    return Err(Self::error(location, "#else without #if".to_string()));
+                };
+                if conditional.has_else {
+                    return Err(Self::error(location, "#else after #else".to_string()));
+                }
+                conditional.has_else = true;
+                conditional.active = conditional.parent_active && !conditional.taken;
+                conditional.taken = true;
+            }
+            "endif" => {
+                if conditionals.pop().is_none() {
+                    return Err(Self::error(location, "#endif without #if".to_string()));
+                }
+            }
+            _ if !active => {}
+            "define" => {
+                let (define, value) = split_identifier(arguments);
+                let define = Self::define_name(define, location)?;
+                self.define(define, value.trim());
+            }
+            "undef" => {
+                let define = Self::define_name(arguments, location)?;
+                self.defines.remove(define);
+            }
+            "import" => self.import(arguments, location)?,
+            // A variant is compiled for every keyword of the set, _ is for none of them
+            "keywords" => {
+                let keywords = arguments
+                    .split_whitespace()
+                    .map(|k| Self::define_name(k, location).map(str::to_string))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if keywords.is_empty() {
+                    return Err(Self::error(location, "Empty #keywords".to_string()));
+                }
+                if !self.keyword_sets.contains(&keywords) {
+                    self.keyword_sets.push(keywords);
+                }
+            }
+            "error" => return Err(Self::error(location, arguments.to_string())),
+            _ => return Err(Self::error(location, format!("Unknown directive #{name}"))),
+        }
+        Ok(())
+    }
+
+    fn define_name<'a>(
+        name: &'a str,
+        location: &SourceLocation,
+    ) -> Result<&'a str, PreprocessorError> {
+        let (define, rest) = split_identifier(name);
+        if define.is_empty() || !rest.trim().is_empty() {
+            return Err(Self::error(
+                location,
+                format!("Invalid define name {name:?}"),
+            ));
+        }
+        Ok(define)
+    }
+
+    // Imports are relative to the importing file or to the folder of the shader
+    fn import(
+        &mut self,
+        arguments: &str,
+        location: &SourceLocation,
+    ) -> Result<(), PreprocessorError> {
+        let Some(import) = arguments
+            .strip_prefix('"')
+            .and_then(|a| a.strip_suffix('"'))
+        else {
+            return Err(Self::error(location, format!("Invalid import {arguments}")));
+        };
+        let Some(import_path) = [location.file.parent(), Some(self.root_folder.as_path())]
+            .into_iter()
+            .flatten()
+            .map(|folder| folder.join(import))
+            .find(|p| p.is_file())
+            .and_then(|p| p.canonicalize().ok())
+        else {
+            return Err(Self::error(
+                location,
+                format!("Unable to find import {import:?}"),
+            ));
+        };
+        if !self.included.insert(import_path.clone()) {
+            return Ok(());
+        }
+        let code = fs::read_to_string(import_path.as_path())
+            .map_err(|e| Self::error(location, format!("Unable to read import {import:?}: {e}")))?;
+        self.dependencies.push(import_path.clone());
+        self.process_file(import_path.as_path(), &code)
+    }
+
+    // Defines being expanded are left as they are, to stop recursive ones
+    fn expand(&self, code: &str, expanding: &mut Vec<String>) -> String {
+        let mut result = String::with_capacity(code.len());
+        let mut chars = code.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            if !c.is_alphanumeric() && c != '_' {
+                result.push(c);
+                continue;
+            }
+            let mut end = start + c.len_utf8();
+            while let Some((i, c)) = chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_') {
+                end = i + c.len_utf8();
+            }
+            let token = &code[start..end];
+            // Numbers are copied as they are, like 0xFF or 1u
+            match self.defines.get(token) {
+                Some(value) if !c.is_ascii_digit() && !expanding.iter().any(|d| d == token) => {
+                    expanding.push(token.to_string());
+                    result.push_str(&self.expand(value, expanding));
+                    expanding.pop();
+                }
+                _ => result.push_str(token),
+            }
+        }
+        result
+    }
+
+    fn evaluate(
+        &self,
+        expression: &str,
+        location: &SourceLocation,
+    ) -> Result<i64, PreprocessorError> {
+        let tokens = self
+            .expression_tokens(expression, &mut Vec::new())
+            .map_err(|e| Self::error(location, e))?;
+        let mut parser = ExpressionParser {
+            tokens,
+            position: 0,
+        };
+        parser
+            .parse()
+            .map_err(|e| Self::error(location, format!("{e} in #if {expression}")))
+    }
+
+    // Expands defines and defined() into numbers, undefined names are 0
+    fn expression_tokens(
+        &self,
+        expression: &str,
+        expanding: &mut Vec<String>,
+    ) -> Result<Vec<Token>, String> {
+        let tokens = tokenize(expression)?;
+        let mut result = Vec::with_capacity(tokens.len());
+        let mut i = 0;
+        while i < tokens.len() {
+            let Token::Identifier(name) = &tokens[i] else {
+                result.push(tokens[i].clone());
+                i += 1;
+                continue;
+            };
+            i += 1;
+            if name == "defined" {
+                let define = match tokens.get(i..i + 3) {
+                    Some(
+                        [Token::Operator("("), Token::Identifier(define), Token::Operator(")")],
+                    ) => {
+                        i += 3;
+                        define
+                    }
+                    _ => match tokens.get(i) {
+                        Some(Token::Identifier(define)) => {
+                            i += 1;
+                            define
+                        }
+                        _ => return Err("Invalid defined()".to_string()),
+                    },
+                };
+                result.push(Token::Number(self.defines.contains_key(define) as i64));
+                continue;
+            }
+            match self.defines.get(name) {
+                Some(value) if value.trim().is_empty() => result.push(Token::Number(1)),
+                Some(value) if !expanding.contains(name) => {
+                    expanding.push(name.clone());
+                    result.extend(self.expression_tokens(value, expanding)?);
+                    expanding.pop();
+                }
+                _ => result.push(Token::Number((name == "true") as i64)),
+            }
+        }
+        Ok(result)
+    }
+}
+
+// Every combination of a keyword for each set, starting from the first keyword of every set.
+// Keywords for none of the set are left out
+pub fn keyword_permutations(keyword_sets: &[Vec<String>]) -> Vec<Vec<String>> {
+    keyword_sets
+        .iter()
+        .fold(vec![Vec::new()], |permutations, set| {
+            permutations
+                .iter()
+                .flat_map(|keywords| {
+                    set.iter().map(|k| {
+                        let mut keywords: Vec<String> = keywords.clone();
+                        if k != SHADER_NO_KEYWORD {
+                            keywords.push(k.clone());
+                        }
+                        keywords
+                    })
+                })
+                .collect()
+        })
+}
+
+// Nested block comments are allowed by wgsl
+fn strip_comments(line: &str, comment_depth: &mut u32) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('*')) => {
+                chars.next();
+                *comment_depth += 1;
+            }
+            ('*', Some('/')) if *comment_depth > 0 => {
+                chars.next();
+                *comment_depth -= 1;
+                // Code around a comment stays separated
+                result.push(' ');
+            }
+            ('/', Some('/')) if *comment_depth == 0 => break,
+            _ if *comment_depth == 0 => result.push(c),
+            _ => {}
+        }
+    }
+    result
+}
+
+fn split_identifier(text: &str) -> (&str, &str) {
+    let end = text
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(text.len());
+    text.split_at(end)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Token {
+    Number(i64),
+    Identifier(String),
+    Operator(&'static str),
+}
+
+const OPERATORS: [&str; 22] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<<", ">>", "(", ")", "!", "~", "+", "-", "*", "/", "%",
+    "<", ">", "&", "|", "^",
+];
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut text = expression.trim_start();
+    while !text.is_empty() {
+        if text.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            let (token, rest) = split_identifier(text);
+            text = rest;
+            if token.starts_with(|c: char| c.is_ascii_digit()) {
+                // Suffixes of wgsl literals are ignored
+                let digits = token.trim_end_matches(['u', 'i']);
+                let number = match digits
+                    .strip_prefix("0x")
+                    .or_else(|| digits.strip_prefix("0X"))
+                {
+                    Some(hex) => i64::from_str_radix(hex, 16),
+                    None => digits.parse::<i64>(),
+                };
+                let number = number.map_err(|_| format!("Invalid number {token}"))?;
+                tokens.push(Token::Number(number));
+            } else {
+                tokens.push(Token::Identifier(token.to_string()));
+            }
+        } else if let Some(operator) = OPERATORS.iter().find(|o| text.starts_with(**o)) {
+            tokens.push(Token::Operator(operator));
+            text = &text[operator.len()..];
+        } else {
+            return Err(format!("Unexpected {:?}", text.chars().next().unwrap()));
+        }
+        text = text.trim_start();
+    }
+    Ok(tokens)
+}
+
+struct ExpressionParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl ExpressionParser {
+    fn parse(&mut self) -> Result<i64, String> {
+        let value = self.binary(0)?;
+        match self.tokens.get(self.position) {
+            None => Ok(value),
+            Some(token) => Err(format!("Unexpected {token:?}")),
+        }
+    }
+
+    fn precedence(operator: &str) -> Option<u32> {
+        Some(match operator {
+            "||" => 1,
+            "&&" => 2,
+            "|" => 3,
+            "^" => 4,
+            "&" => 5,
+            "==" | "!=" => 6,
+            "<" | "<=" | ">" | ">=" => 7,
+            "<<" | ">>" => 8,
+            "+" | "-" => 9,
+            "*" | "/" | "%" => 10,
+            _ => return None,
+        })
+    }
+
+    fn binary(&mut self, min_precedence: u32) -> Result<i64, String> {
+        let mut left = self.unary()?;
+        while let Some(Token::Operator(operator)) = self.tokens.get(self.position).cloned() {
+            let Some(precedence) = Self::precedence(operator).filter(|p| *p > min_precedence)
+            else {
+                break;
+            };
+            self.position += 1;
+            let right = self.binary(precedence)?;
+            left = match operator {
+                "||" => (left != 0 || right != 0) as i64,
+                "&&" => (left != 0 && right != 0) as i64,
+                "|" => left | right,
+                "^" => left ^ right,
+                "&" => left & right,
+                "==" => (left == right) as i64,
+                "!=" => (left != right) as i64,
+                "<" => (left < right) as i64,
+                "<=" => (left <= right) as i64,
+                ">" => (left > right) as i64,
+                ">=" => (left >= right) as i64,
+                "<<" => left.wrapping_shl(right as u32),
+                ">>" => left.wrapping_shr(right as u32),
+                "+" => left.wrapping_add(right),
+                "-" => left.wrapping_sub(right),
+                "*" => left.wrapping_mul(right),
+                _ if right == 0 => return Err("Division by zero".to_string()),
+                "/" => left.wrapping_div(right),
+                _ => left.wrapping_rem(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<i64, String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        match token {
+            Some(Token::Number(value)) => Ok(value),
+            Some(Token::Operator("!")) => Ok((self.unary()? == 0) as i64),
+            Some(Token::Operator("~")) => Ok(!self.unary()?),
+            Some(Token::Operator("-")) => Ok(self.unary()?.wrapping_neg()),
+            Some(Token::Operator("+")) => self.unary(),
+            Some(Token::Operator("(")) => {
+                let value = self.binary(0)?;
+                match self.tokens.get(self.position) {
+                    Some(Token::Operator(")")) => {
+                        self.position += 1;
+                        Ok(value)
+                    }
+                    _ => Err("Missing )".to_string()),
+                }
+            }
+            Some(token) => Err(format!("Unexpected {token:?}")),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    fs::{self, create_dir_all},
+    path::{Path, PathBuf},
+};
+
+use inox_render::{ShaderData, ShaderVariantData, ShaderVariantKey};
+
+use crate::shader_preprocessor::{
+    keyword_permutations, PreprocessorError, ShaderPreprocessor, SourceLocation,
+};
+
+fn write_shader(folder: &Path, name: &str, code: &str) -> PathBuf {
+    let path = folder.join(name);
+    fs::write(path.as_path(), code).unwrap();
+    path.canonicalize().unwrap()
+}
+
+fn preprocess_error(folder: &Path, code: &str) -> PreprocessorError {
+    let path = write_shader(folder, "error.wgsl", code);
+    match ShaderPreprocessor::new(&[]).preprocess(path.as_path()) {
+        Ok(shader) => panic!("Preprocessed invalid shader:\n{}", shader.code),
+        Err(e) => e,
+    }
+}
+
+#[allow(dead_code)]
+fn shader_preprocessor_conditionals_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_shader_preprocessor_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    create_dir_all(test_folder.as_path()).unwrap();
+
+    let path = write_shader(
+        test_folder.as_path(),
+        "conditionals.wgsl",
+        "#define SIZE 4u
+#define HALF_SIZE (SIZE / 2u)
+#ifdef FEATURE_A
+    #if SIZE > 2 && !defined(FEATURE_B)
+const A: u32 = HALF_SIZE; // comment
+    #elif defined FEATURE_B
+const A: u32 = 0u;
+    #else
+const A: u32 = 1u;
+    #endif
+#else
+    #ifndef FEATURE_B
+const A: u32 = 2u;
+    #endif
+#endif
+#if VALUE == 0x10
+const B: u32 = 16u;
+#elif VALUE
+const B: u32 = VALUE;
+#endif
+#undef SIZE
+/* multi line
+   /* nested */ comment */
+var<private> size: u32 = SIZE;",
+    );
+    let shader = ShaderPreprocessor::new(&["FEATURE_A".to_string(), "VALUE=16".to_string()])
+        .preprocess(path.as_path())
+        .unwrap();
+    assert_eq!(
+        shader.code,
+        "const A: u32 = (4u / 2u);\nconst B: u32 = 16u;\nvar<private> size: u32 = SIZE;\n"
+    );
+    let shader = ShaderPreprocessor::new(&["FEATURE_B".to_string(), "VALUE=3".to_string()])
+        .preprocess(path.as_path())
+        .unwrap();
+    assert_eq!(
+        shader.code,
+        "const B: u32 = 3;\nvar<private> size: u32 = SIZE;\n"
+    );
+    let shader = ShaderPreprocessor::new(&[])
+        .preprocess(path.as_path())
+        .unwrap();
+    assert_eq!(
+        shader.code,
+        "const A: u32 = 2u;\nvar<private> size: u32 = SIZE;\n"
+    );
+
+    // Recursive defines stop expanding
+    let path = write_shader(
+        test_folder.as_path(),
+        "recursive.wgsl",
+        "#define A B + 1\n#define B A\nlet a = A;",
+    );
+    let shader = ShaderPreprocessor::new(&[])
+        .preprocess(path.as_path())
+        .unwrap();
+    assert_eq!(shader.code, "let a = A + 1;\n");
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[allow(dead_code)]
+fn shader_preprocessor_imports_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_shader_preprocessor_imports_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    create_dir_all(test_folder.join("utils")).unwrap();
+
+    let common = write_shader(
+        test_folder.as_path(),
+        "common.inc",
+        "#import \"utils/math.inc\"\n\nconst COMMON: u32 = PI_DEGREES;",
+    );
+    let math = write_shader(
+        test_folder.join("utils").as_path(),
+        "math.inc",
+        "// Imported only once\n#define PI_DEGREES 180u\nconst MATH: u32 = 1u;",
+    );
+    let path = write_shader(
+        test_folder.as_path(),
+        "main.wgsl",
+        "#import \"common.inc\"\n#import \"utils/math.inc\"\n\nconst MAIN: u32 = COMMON;",
+    );
+    let shader = ShaderPreprocessor::new(&[])
+        .preprocess(path.as_path())
+        .unwrap();
+    assert_eq!(
+        shader.code,
+        "const MATH: u32 = 1u;\nconst COMMON: u32 = 180u;\nconst MAIN: u32 = COMMON;\n"
+    );
+    assert_eq!(shader.dependencies, vec![common.clone(), math.clone()]);
+
+    // Lines of the preprocessed code point at the file they come from
+    let location = |file: &PathBuf, line| {
+        Some(SourceLocation {
+            file: file.clone(),
+            line,
+        })
+    };
+    assert_eq!(shader.source_map.location(1), location(&math, 3));
+    assert_eq!(shader.source_map.location(2), location(&common, 3));
+    assert_eq!(shader.source_map.location(3), location(&path, 4));
+    assert_eq!(shader.source_map.location(0), None);
+    assert_eq!(shader.source_map.location(4), None);
+    assert_eq!(
+        shader.source_map.location(1).unwrap().to_string(),
+        "math.inc:3"
+    );
+
+    let error = preprocess_error(test_folder.as_path(), "\n#import \"missing.inc\"");
+    assert_eq!(error.location.line, 2);
+    let error = preprocess_error(test_folder.as_path(), "#ifdef A\nconst A: u32 = 1u;");
+    assert_eq!(error.location.line, 1);
+    assert_eq!(error.message, "Conditional without #endif");
+    let error = preprocess_error(test_folder.as_path(), "#else");
+    assert_eq!(error.message, "#else without #if");
+    let error = preprocess_error(test_folder.as_path(), "#if 1\n#else\n#elif 1\n#endif");
+    assert_eq!(error.location.line, 3);
+    let error = preprocess_error(test_folder.as_path(), "#if (1 +\n#endif");
+    assert_eq!(error.location.line, 1);
+    let error = preprocess_error(test_folder.as_path(), "#if 1\n#error Not supported\n#endif");
+    assert_eq!(error.to_string(), "error.wgsl:2: Not supported");
+    // Errors in skipped branches are ignored
+    let path = write_shader(
+        test_folder.as_path(),
+        "skipped.wgsl",
+        "#if 0\n#if (\n#error Skipped\n#endif\n#endif",
+    );
+    assert!(ShaderPreprocessor::new(&[])
+        .preprocess(path.as_path())
+        .is_ok());
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[allow(dead_code)]
+fn shader_variants_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_shader_variants_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    create_dir_all(test_folder.as_path()).unwrap();
+
+    let path = write_shader(
+        test_folder.as_path(),
+        "variants.wgsl",
+        "#keywords _ USE_IBL
+#keywords DISPLAY_NORMALS DISPLAY_DEPTH DISPLAY_UV
+#keywords _ USE_IBL
+#if defined(USE_IBL) && defined(DISPLAY_DEPTH)
+const A: u32 = 1u;
+#endif",
+    );
+    let shader = ShaderPreprocessor::new(&[])
+        .preprocess(path.as_path())
+        .unwrap();
+    assert_eq!(shader.code, "");
+    assert_eq!(shader.keyword_sets.len(), 2);
+    let permutations = keyword_permutations(&shader.keyword_sets);
+    assert_eq!(permutations.len(), 6);
+    assert_eq!(permutations[0], vec!["DISPLAY_NORMALS".to_string()]);
+    assert_eq!(
+        permutations[4],
+        vec!["USE_IBL".to_string(), "DISPLAY_DEPTH".to_string()]
+    );
+    let shader = ShaderPreprocessor::new(&permutations[4])
+        .preprocess(path.as_path())
+        .unwrap();
+    assert_eq!(shader.code, "const A: u32 = 1u;\n");
+    assert_eq!(keyword_permutations(&[]), vec![Vec::<String>::new()]);
+
+    let error = preprocess_error(test_folder.as_path(), "#keywords A B-C");
+    assert_eq!(error.location.line, 1);
+
+    // Requested keywords select a variant, the first keyword of a set when none is requested
+    let shader_data = ShaderData {
+        wgsl_code: "default".to_string(),
+        keyword_sets: shader.keyword_sets.clone(),
+        variants: permutations[1..]
+            .iter()
+            .map(|keywords| ShaderVariantData {
+                key: ShaderVariantKey::new(keywords),
+                wgsl_code: keywords.join(" "),
+            })
+            .collect(),
+        ..Default::default()
+    };
+    assert_eq!(
+        shader_data.default_variant_key(),
+        ShaderVariantKey::new(&["DISPLAY_NORMALS"])
+    );
+    assert_eq!(
+        shader_data.variant_wgsl_code(&ShaderVariantKey::default()),
+        Some("default")
+    );
+    assert_eq!(
+        shader_data.variant_wgsl_code(&ShaderVariantKey::new(&["UNKNOWN", "_"])),
+        Some("default")
+    );
+    assert_eq!(
+        shader_data.variant_wgsl_code(&ShaderVariantKey::new(&["DISPLAY_UV", "USE_IBL"])),
+        Some("USE_IBL DISPLAY_UV")
+    );
+    assert_eq!(
+        shader_data.variant_key(&ShaderVariantKey::new(&["USE_IBL", "UNKNOWN"])),
+        ShaderVariantKey::new(&["DISPLAY_NORMALS", "USE_IBL"])
+    );
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[test]
+fn tests() {
+    shader_preprocessor_conditionals_test();
+    shader_preprocessor_imports_test();
+    shader_variants_test();
+}
@@ -0,0 +1,100 @@
+use inox_render::{ShaderBinding, ShaderBindingAccess, ShaderBindingType, ShaderStage};
+
+fn stage_of(stage: naga::ShaderStage) -> ShaderStage {
+    match stage {
+        naga::ShaderStage::Vertex => ShaderStage::Vertex,
+        naga::ShaderStage::Fragment => ShaderStage::Fragment,
+        naga::ShaderStage::Compute => ShaderStage::Compute,
+        _ => ShaderStage::None,
+    }
+}
+
+fn access_of(access: naga::StorageAccess) -> ShaderBindingAccess {
+    if access.contains(naga::StorageAccess::STORE) {
+        if access.contains(naga::StorageAccess::LOAD) {
+            ShaderBindingAccess::ReadWrite
+        } else {
+            ShaderBindingAccess::Write
+        }
+    } else {
+        ShaderBindingAccess::Read
+    }
+}
+
+// Bindings declared with @group and @binding, visible to the entry points that use them.
+// Bindings that no entry point uses are visible to all of them
+pub fn reflect_bindings(
+    module: &naga::Module,
+    info: &naga::valid::ModuleInfo,
+) -> Vec<ShaderBinding> {
+    let module_stage = module
+        .entry_points
+        .iter()
+        .fold(ShaderStage::None, |stage, ep| {
+            stage.combine(stage_of(ep.stage))
+        });
+    let mut bindings = module
+        .global_variables
+        .iter()
+        .filter_map(|(handle, global)| {
+            let binding = global.binding.as_ref()?;
+            let mut inner = &module.types[global.ty].inner;
+            let mut array_size = None;
+            if let naga::TypeInner::BindingArray { base, size } = inner {
+                if let naga::ArraySize::Constant(size) = size {
+                    array_size = Some(size.get());
+                }
+                inner = &module.types[*base].inner;
+            }
+            let (binding_type, access) = match (global.space, inner) {
+                (naga::AddressSpace::Uniform, _) => {
+                    (ShaderBindingType::Uniform, ShaderBindingAccess::Read)
+                }
+                (naga::AddressSpace::Storage { access }, _) => {
+                    (ShaderBindingType::Storage, access_of(access))
+                }
+                (
+                    _,
+                    naga::TypeInner::Image {
+                        class: naga::ImageClass::Storage { access, .. },
+                        ..
+                    },
+                ) => (ShaderBindingType::StorageTexture, access_of(*access)),
+                (_, naga::TypeInner::Image { .. }) => {
+                    (ShaderBindingType::Texture, ShaderBindingAccess::Read)
+                }
+                (_, naga::TypeInner::Sampler { .. }) => {
+                    (ShaderBindingType::Sampler, ShaderBindingAccess::Read)
+                }
+                (_, naga::TypeInner::AccelerationStructure { .. }) => (
+                    ShaderBindingType::AccelerationStructure,
+                    ShaderBindingAccess::Read,
+                ),
+                _ => return None,
+            };
+            let stage = module
+                .entry_points
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !info.get_entry_point(*i)[handle].is_empty())
+                .fold(ShaderStage::None, |stage, (_, ep)| {
+                    stage.combine(stage_of(ep.stage))
+                });
+            Some(ShaderBinding {
+                name: global.name.clone().unwrap_or_default(),
+                group: binding.group,
+                binding: binding.binding,
+                binding_type,
+                access,
+                array_size,
+                stage: if stage == ShaderStage::None {
+                    module_stage
+                } else {
+                    stage
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+    bindings.sort_by_key(|b| (b.group, b.binding));
+    bindings
+}
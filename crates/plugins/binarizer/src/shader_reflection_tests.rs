@@ -0,0 +1,105 @@
+use inox_render::{
+    merge_shader_bindings, ShaderBinding, ShaderBindingAccess, ShaderBindingType, ShaderStage,
+};
+
+use crate::shader_reflection::reflect_bindings;
+
+fn reflect(code: &str) -> Vec<ShaderBinding> {
+    let module = naga::front::wgsl::parse_str(code).unwrap();
+    let info = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::default(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .unwrap();
+    reflect_bindings(&module, &info)
+}
+
+#[allow(dead_code)]
+fn shader_reflection_test() {
+    let bindings = reflect(
+        "struct Data {
+    value: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> constant_data: Data;
+@group(0) @binding(1)
+var<storage, read_write> data_buffer: array<f32>;
+@group(1) @binding(0)
+var default_sampler: sampler;
+@group(1) @binding(1)
+var texture_array: binding_array<texture_2d_array<f32>, 8>;
+@group(0) @binding(2)
+var output_texture: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(3)
+var<storage, read> unused: Data;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(f32(constant_data.value + index));
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    data_buffer[0] = position.x;
+    textureStore(output_texture, vec2<u32>(0u), vec4<f32>(0.));
+    return textureSampleLevel(texture_array[0], default_sampler, vec2<f32>(0.), 0, 0.)
+        * f32(constant_data.value);
+}",
+    );
+    let names = bindings.iter().map(|b| b.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "constant_data",
+            "data_buffer",
+            "output_texture",
+            "unused",
+            "default_sampler",
+            "texture_array"
+        ]
+    );
+    assert_eq!(bindings[0].binding_type, ShaderBindingType::Uniform);
+    assert_eq!(bindings[0].stage, ShaderStage::VertexAndFragment);
+    assert_eq!(bindings[1].binding_type, ShaderBindingType::Storage);
+    assert_eq!(bindings[1].access, ShaderBindingAccess::ReadWrite);
+    assert_eq!(bindings[1].stage, ShaderStage::Fragment);
+    assert_eq!(bindings[2].binding_type, ShaderBindingType::StorageTexture);
+    assert_eq!(bindings[2].access, ShaderBindingAccess::Write);
+    assert_eq!(bindings[3].access, ShaderBindingAccess::Read);
+    // Bindings not used by any entry point are visible to all of them
+    assert_eq!(bindings[3].stage, ShaderStage::VertexAndFragment);
+    assert_eq!(bindings[4].binding_type, ShaderBindingType::Sampler);
+    assert_eq!((bindings[4].group, bindings[4].binding), (1, 0));
+    assert_eq!(bindings[5].binding_type, ShaderBindingType::Texture);
+    assert_eq!(bindings[5].array_size, Some(8));
+    assert_eq!(bindings[0].array_size, None);
+
+    // Bindings of other shaders are added, the same ones are visible to both
+    let mut merged = reflect(
+        "@group(0) @binding(1)
+var<storage, read_write> data_buffer: array<f32>;
+@group(2) @binding(0)
+var<storage, read> values: array<u32>;
+
+@compute @workgroup_size(1)
+fn main() {
+    data_buffer[0] = f32(values[0]);
+}",
+    );
+    assert_eq!(merged[0].stage, ShaderStage::Compute);
+    assert!(merge_shader_bindings(&mut merged, &bindings));
+    assert_eq!(merged.len(), 7);
+    assert_eq!(merged.last().unwrap().name, "values");
+    assert_eq!(merged[1].name, "data_buffer");
+    assert_eq!(merged[1].stage, ShaderStage::Compute);
+    let mut other = bindings.clone();
+    other[0].name = "other_data".to_string();
+    assert!(!merge_shader_bindings(&mut merged, &other));
+}
+
+#[test]
+fn tests() {
+    shader_reflection_test();
+}
@@ -3,22 +3,30 @@ use std::{
     path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        mpsc::{channel, Receiver, Sender},
+        Arc, RwLock,
     },
     thread::{self, JoinHandle},
 };
 
-use inox_core::{ContextRc, System, SystemId, SystemUID};
+use inox_core::{ContextRc, JobHandlerRw, System, SystemId, SystemUID};
 use inox_messenger::MessageHubRc;
 
-use inox_platform::PlatformType;
-use inox_resources::{ConfigBase, SharedDataRc};
+use inox_platform::{
+    PlatformType, PLATFORM_TYPE_ANDROID, PLATFORM_TYPE_ANDROID_NAME, PLATFORM_TYPE_IOS,
+    PLATFORM_TYPE_IOS_NAME, PLATFORM_TYPE_PC_NAME, PLATFORM_TYPE_WEB, PLATFORM_TYPE_WEB_NAME,
+};
+use inox_render::FontOptions;
+use inox_resources::{BuildReport, ConfigBase, SharedDataRc};
 use inox_serialize::{read_from_file, SerializationType};
 use inox_uid::generate_uid_from_string;
 
 use crate::{
-    config::Config, CopyCompiler, DataWatcher, FontCompiler, GltfCompiler, ImageCompiler,
-    ShaderCompiler,
+    clean_asset_database, config::Config, open_asset_database, read_build_report,
+    save_asset_database, stale_assets, take_asset_results, write_build_report, BinarizerJobs,
+    CopyCompiler, DataWatcher, EnvironmentCompiler, FontCompiler, GltfCompiler, ImageCompiler,
+    LightmapOptions, MeshOptions, MeshletOptions, MeshletOptionsOverride, ObjCompiler, PlyCompiler,
+    ShaderCompiler, StlCompiler,
 };
 
 #[derive(Default)]
@@ -26,6 +34,22 @@ pub struct BinarizerParameters {
     pub should_end_on_completion: AtomicBool,
     pub optimize_meshes: AtomicBool,
     pub preprocess_shaders_paths: Vec<String>,
+    // Removes everything compiled before binarizing again
+    pub clean: bool,
+    // Only reports the stale assets without compiling them
+    pub verify: bool,
+    // Globs of the files to binarize, all of them when empty
+    pub only: Vec<String>,
+    // Files and meshes binarized in parallel, as many as the cores when zero
+    pub jobs: usize,
+    // Meshes keep only the meshlets of full detail
+    pub skip_mesh_lods: bool,
+    pub font_options: RwLock<FontOptions>,
+    pub meshlet_options: RwLock<MeshletOptions>,
+    pub meshlet_options_overrides: RwLock<Vec<MeshletOptionsOverride>>,
+    // Bakes lightmaps of static meshes even when the config doesn't enable them
+    pub bake_lightmaps: bool,
+    pub lightmap_options: RwLock<LightmapOptions>,
 }
 
 pub struct Binarizer<const PLATFORM_TYPE: PlatformType> {
@@ -34,10 +58,12 @@ pub struct Binarizer<const PLATFORM_TYPE: PlatformType> {
     data_folder: PathBuf,
     shared_data: SharedDataRc,
     message_hub: MessageHubRc,
+    job_handler: JobHandlerRw,
     thread_handle: Option<JoinHandle<bool>>,
     is_running: Arc<AtomicBool>,
     is_ready: Arc<AtomicBool>,
     info: Arc<BinarizerParameters>,
+    report_sender: Option<Sender<BuildReport>>,
 }
 
 impl<const PLATFORM_TYPE: PlatformType> Binarizer<PLATFORM_TYPE> {
@@ -65,6 +91,7 @@ impl<const PLATFORM_TYPE: PlatformType> Binarizer<PLATFORM_TYPE> {
             config: Config::default(),
             shared_data: app_context.shared_data().clone(),
             message_hub: app_context.message_hub().clone(),
+            job_handler: app_context.job_handler().clone(),
             data_raw_folder,
             data_folder,
             thread_handle: None,
@@ -75,17 +102,47 @@ impl<const PLATFORM_TYPE: PlatformType> Binarizer<PLATFORM_TYPE> {
                 ),
                 optimize_meshes: AtomicBool::new(info.optimize_meshes.load(Ordering::SeqCst)),
                 preprocess_shaders_paths: info.preprocess_shaders_paths.clone(),
+                clean: info.clean,
+                verify: info.verify,
+                only: info.only.clone(),
+                jobs: info.jobs,
+                skip_mesh_lods: info.skip_mesh_lods,
+                font_options: RwLock::new(info.font_options.read().unwrap().clone()),
+                meshlet_options: RwLock::new(*info.meshlet_options.read().unwrap()),
+                meshlet_options_overrides: RwLock::new(
+                    info.meshlet_options_overrides.read().unwrap().clone(),
+                ),
+                bake_lightmaps: info.bake_lightmaps,
+                lightmap_options: RwLock::new(info.lightmap_options.read().unwrap().clone()),
             }),
             is_ready: Arc::new(AtomicBool::new(false)),
+            report_sender: None,
         }
     }
 
+    // Build report sent every time it changes, other than written in the data folder
+    pub fn report_receiver(&mut self) -> Receiver<BuildReport> {
+        let (sender, receiver) = channel();
+        self.report_sender = Some(sender);
+        receiver
+    }
+
     pub fn is_running(&self) -> bool {
         self.is_running.load(Ordering::SeqCst)
     }
 
+    fn platform_name() -> &'static str {
+        match PLATFORM_TYPE {
+            PLATFORM_TYPE_WEB => PLATFORM_TYPE_WEB_NAME,
+            PLATFORM_TYPE_ANDROID => PLATFORM_TYPE_ANDROID_NAME,
+            PLATFORM_TYPE_IOS => PLATFORM_TYPE_IOS_NAME,
+            _ => PLATFORM_TYPE_PC_NAME,
+        }
+    }
+
     fn init_binarizer(
         mut binarizer: DataWatcher,
+        jobs: &BinarizerJobs,
         shared_data: &SharedDataRc,
         message_hub: &MessageHubRc,
         data_raw_folder: &Path,
@@ -99,17 +156,45 @@ impl<const PLATFORM_TYPE: PlatformType> Binarizer<PLATFORM_TYPE> {
             data_folder,
         );
         if info.preprocess_shaders_paths.is_empty() {
-            let font_compiler =
+            let mut font_compiler =
                 FontCompiler::new(message_hub.clone(), data_raw_folder, data_folder);
-            let image_compiler =
-                ImageCompiler::new(message_hub.clone(), data_raw_folder, data_folder);
-            let gltf_compiler = GltfCompiler::new(data_raw_folder, data_folder);
+            font_compiler.set_font_options(&info.font_options.read().unwrap());
+            let image_compiler = ImageCompiler::<PLATFORM_TYPE>::new(
+                message_hub.clone(),
+                data_raw_folder,
+                data_folder,
+            );
+            let environment_compiler = EnvironmentCompiler::new(data_raw_folder, data_folder);
+            let mesh_options = MeshOptions {
+                optimize: info.optimize_meshes.load(Ordering::SeqCst),
+                lods: !info.skip_mesh_lods,
+                meshlets: *info.meshlet_options.read().unwrap(),
+                overrides: info.meshlet_options_overrides.read().unwrap().clone(),
+            };
+            let mut gltf_compiler =
+                GltfCompiler::<PLATFORM_TYPE>::new(data_raw_folder, data_folder);
+            let mut lightmap_options = info.lightmap_options.read().unwrap().clone();
+            lightmap_options.enabled |= info.bake_lightmaps;
+            gltf_compiler
+                .set_jobs(jobs)
+                .set_mesh_options(&mesh_options)
+                .set_lightmap_options(&lightmap_options);
+            let mut obj_compiler = ObjCompiler::<PLATFORM_TYPE>::new(data_raw_folder, data_folder);
+            obj_compiler.set_mesh_options(&mesh_options);
+            let mut ply_compiler = PlyCompiler::new(data_raw_folder, data_folder);
+            ply_compiler.set_mesh_options(&mesh_options);
+            let mut stl_compiler = StlCompiler::new(data_raw_folder, data_folder);
+            stl_compiler.set_mesh_options(&mesh_options);
             let config_compiler =
                 CopyCompiler::new(message_hub.clone(), data_raw_folder, data_folder);
             binarizer.add_handler(config_compiler);
             binarizer.add_handler(font_compiler);
             binarizer.add_handler(image_compiler);
+            binarizer.add_handler(environment_compiler);
             binarizer.add_handler(gltf_compiler);
+            binarizer.add_handler(obj_compiler);
+            binarizer.add_handler(ply_compiler);
+            binarizer.add_handler(stl_compiler);
             binarizer.add_handler(shader_compiler);
         } else {
             for path in info.preprocess_shaders_paths.iter() {
@@ -120,9 +205,15 @@ impl<const PLATFORM_TYPE: PlatformType> Binarizer<PLATFORM_TYPE> {
     }
 
     pub fn start(&mut self) {
-        let binarizer = DataWatcher::new(self.data_raw_folder.clone());
+        let mut jobs = BinarizerJobs::new(&self.job_handler);
+        if self.info.jobs > 0 {
+            jobs.set_max_pending_jobs(self.info.jobs);
+        }
+        let mut binarizer = DataWatcher::new(self.data_raw_folder.clone(), jobs.clone());
+        binarizer.set_filters(&self.info.only);
         let mut binarizer = Self::init_binarizer(
             binarizer,
+            &jobs,
             &self.shared_data,
             &self.message_hub,
             &self.data_raw_folder,
@@ -135,17 +226,53 @@ impl<const PLATFORM_TYPE: PlatformType> Binarizer<PLATFORM_TYPE> {
         }
         inox_log::debug_log!("Starting data binarizer");
 
+        if self.info.clean {
+            clean_asset_database(self.data_raw_folder.as_path(), self.data_folder.as_path());
+        }
+        open_asset_database(
+            self.data_raw_folder.as_path(),
+            self.data_folder.as_path(),
+            self.info.verify,
+        );
+
         self.is_running.store(true, Ordering::SeqCst);
         let can_continue = self.is_running.clone();
         let info = self.info.clone();
+        let data_raw_folder = self.data_raw_folder.clone();
+        let data_folder = self.data_folder.clone();
+        let report_sender = self.report_sender.clone();
+        let publish_report = move |report: &BuildReport| {
+            write_build_report(report);
+            if let Some(sender) = &report_sender {
+                sender.send(report.clone()).ok();
+            }
+        };
         let builder = thread::Builder::new().name("Data Binarizer".to_string());
 
         let t = builder
             .spawn(move || -> bool {
+                let mut report = BuildReport::new(Self::platform_name(), data_folder.as_path());
+                let previous_report = read_build_report(data_folder.as_path()).unwrap_or_default();
                 binarizer.binarize_all();
 
+                // Verify mode compiles nothing: the last report is kept with the stale assets
+                if info.verify {
+                    report.add_results(previous_report.results().cloned().collect());
+                    report.stale =
+                        Self::report_stale_assets(data_raw_folder.as_path(), data_folder.as_path());
+                    publish_report(&report);
+                    can_continue.store(false, Ordering::SeqCst);
+                    return false;
+                }
+                Self::update_report(&mut report, &previous_report, data_raw_folder.as_path());
+                publish_report(&report);
+
                 loop {
                     binarizer.update();
+                    if Self::update_report(&mut report, &previous_report, data_raw_folder.as_path())
+                    {
+                        publish_report(&report);
+                    }
 
                     if info.should_end_on_completion.load(Ordering::SeqCst) {
                         can_continue.store(false, Ordering::SeqCst);
@@ -159,6 +286,30 @@ impl<const PLATFORM_TYPE: PlatformType> Binarizer<PLATFORM_TYPE> {
             .unwrap();
         self.thread_handle = Some(t);
     }
+    fn report_stale_assets(data_raw_folder: &Path, data_folder: &Path) -> Vec<String> {
+        let stale = stale_assets(data_raw_folder, data_folder);
+        if stale.is_empty() {
+            println!("All assets in {data_folder:?} are up to date");
+        } else {
+            println!("{} stale assets in {data_folder:?}:", stale.len());
+            stale.iter().for_each(|s| println!("  {s}"));
+        }
+        stale
+    }
+    // Returns true when assets have been processed since the last update,
+    // the asset database is saved once for all of them
+    fn update_report(
+        report: &mut BuildReport,
+        previous_report: &BuildReport,
+        data_raw_folder: &Path,
+    ) -> bool {
+        save_asset_database(data_raw_folder, report.data_folder.as_path());
+        let results = take_asset_results(data_raw_folder, report.data_folder.as_path());
+        let has_results = !results.is_empty();
+        report.add_results(results);
+        report.restore_skipped(previous_report);
+        has_results
+    }
     pub fn stop(&mut self) {
         if self.thread_handle.is_some() {
             inox_log::debug_log!("Stopping data binarizer");
@@ -200,6 +351,10 @@ impl<const PLATFORM_TYPE: PlatformType> System for Binarizer<PLATFORM_TYPE> {
                     .store(data.optimize_meshes, Ordering::SeqCst);
                 info.should_end_on_completion
                     .store(data.end_on_completion, Ordering::SeqCst);
+                *info.font_options.write().unwrap() = data.font;
+                *info.meshlet_options.write().unwrap() = data.meshlets;
+                *info.meshlet_options_overrides.write().unwrap() = data.meshlets_overrides;
+                *info.lightmap_options.write().unwrap() = data.lightmaps;
                 is_ready.store(true, Ordering::SeqCst);
             }),
         );
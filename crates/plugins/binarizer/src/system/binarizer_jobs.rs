@@ -0,0 +1,178 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use inox_core::{JobHandlerRw, JobHandlerTrait, JobId, JobPriority};
+use inox_log::debug_log;
+
+pub const BINARIZER_JOB_CATEGORY: JobId = inox_uid::generate_static_uid_from_string("BinarizerJob");
+
+// Counted as pending until the job is executed or dropped without being executed
+struct PendingJob(Arc<AtomicUsize>);
+
+impl PendingJob {
+    fn new(pending_jobs: &Arc<AtomicUsize>) -> Self {
+        pending_jobs.fetch_add(1, Ordering::SeqCst);
+        Self(pending_jobs.clone())
+    }
+}
+
+impl Drop for PendingJob {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+type QueuedJob = Box<dyn FnOnce() + Send + Sync>;
+
+// Jobs of the binarizer, with a bounded amount of them in flight to limit memory use.
+// Without a job handler they're executed immediately on the calling thread
+#[derive(Default, Clone)]
+pub struct BinarizerJobs {
+    job_handler: Option<JobHandlerRw>,
+    max_pending_jobs: usize,
+    pending_jobs: Arc<AtomicUsize>,
+    // Jobs not started yet, taken by the job handler workers or by the thread waiting for them
+    queued_jobs: Arc<Mutex<VecDeque<QueuedJob>>>,
+    scheduled_jobs: Arc<AtomicUsize>,
+    completed_jobs: Arc<AtomicUsize>,
+}
+
+impl BinarizerJobs {
+    pub fn new(job_handler: &JobHandlerRw) -> Self {
+        let max_pending_jobs = thread::available_parallelism().map_or(4, |n| n.get());
+        Self {
+            job_handler: Some(job_handler.clone()),
+            max_pending_jobs,
+            ..Default::default()
+        }
+    }
+
+    // Jobs in flight, that is how many of them can run in parallel
+    pub fn set_max_pending_jobs(&mut self, max_pending_jobs: usize) -> &mut Self {
+        self.max_pending_jobs = max_pending_jobs.max(1);
+        self
+    }
+
+    // Jobs sharing the same job handler but waited and reported on their own
+    pub fn child(&self) -> Self {
+        Self {
+            job_handler: self.job_handler.clone(),
+            max_pending_jobs: self.max_pending_jobs,
+            ..Default::default()
+        }
+    }
+
+    pub fn add_job<F>(&self, job_name: &str, func: F)
+    where
+        F: FnOnce() + Send + Sync + 'static,
+    {
+        self.scheduled_jobs.fetch_add(1, Ordering::SeqCst);
+        let completed_jobs = self.completed_jobs.clone();
+        let scheduled_jobs = self.scheduled_jobs.clone();
+        let name = job_name.to_string();
+        let job = move || {
+            func();
+            let completed = completed_jobs.fetch_add(1, Ordering::SeqCst) + 1;
+            debug_log!(
+                "[{}/{}] {}",
+                completed,
+                scheduled_jobs.load(Ordering::SeqCst),
+                name
+            );
+        };
+        match &self.job_handler {
+            Some(job_handler) => {
+                self.wait_pending_jobs(self.max_pending_jobs - 1);
+                let pending_job = PendingJob::new(&self.pending_jobs);
+                self.queued_jobs
+                    .lock()
+                    .unwrap()
+                    .push_back(Box::new(move || {
+                        let _pending_job = pending_job;
+                        job();
+                    }));
+                let queued_jobs = self.queued_jobs.clone();
+                job_handler.add_job(
+                    &BINARIZER_JOB_CATEGORY,
+                    job_name,
+                    JobPriority::Low,
+                    move || {
+                        // Nothing to do when a thread waiting for them executed all of them
+                        Self::execute_queued_job(&queued_jobs);
+                    },
+                );
+            }
+            None => job(),
+        }
+    }
+
+    pub fn wait(&self) {
+        self.wait_pending_jobs(0);
+    }
+
+    // Completed and scheduled jobs
+    pub fn progress(&self) -> (usize, usize) {
+        (
+            self.completed_jobs.load(Ordering::SeqCst),
+            self.scheduled_jobs.load(Ordering::SeqCst),
+        )
+    }
+
+    fn execute_queued_job(queued_jobs: &Mutex<VecDeque<QueuedJob>>) -> bool {
+        let job = queued_jobs.lock().unwrap().pop_front();
+        match job {
+            Some(job) => {
+                job();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // While waiting the thread executes its own jobs too:
+    // a job waiting for the ones it scheduled can't starve the workers,
+    // and it's never blocked by a long job scheduled by someone else
+    fn wait_pending_jobs(&self, count: usize) {
+        if self.job_handler.is_none() {
+            return;
+        }
+        while self.pending_jobs.load(Ordering::SeqCst) > count {
+            if !Self::execute_queued_job(&self.queued_jobs) {
+                thread::yield_now();
+            }
+        }
+    }
+}
+
+// Threads executing the binarizer jobs when there are no job handler workers to do it,
+// as when binarizing outside of the engine
+pub fn spawn_binarizer_workers(
+    job_handler: &JobHandlerRw,
+    count: usize,
+    can_continue: &Arc<AtomicBool>,
+) -> Vec<JoinHandle<()>> {
+    (0..count)
+        .map(|i| {
+            let job_handler = job_handler.clone();
+            let can_continue = can_continue.clone();
+            thread::Builder::new()
+                .name(format!("Binarizer Worker {i}"))
+                .spawn(move || {
+                    while can_continue.load(Ordering::SeqCst) {
+                        match job_handler.get_job_with_priority(JobPriority::Low) {
+                            Some(job) => job.execute(),
+                            None => thread::park_timeout(Duration::from_millis(1)),
+                        }
+                    }
+                })
+                .unwrap()
+        })
+        .collect()
+}
@@ -1,66 +1,119 @@
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use inox_filesystem::convert_from_local_path;
 use inox_platform::FileWatcher;
 
-pub trait ExtensionHandler {
-    fn on_changed(&mut self, path: &Path);
+use crate::{asset_dependents, matches_glob, BinarizerJobs};
+
+pub trait ExtensionHandler: Send + Sync {
+    fn on_changed(&self, path: &Path);
 }
 
 pub struct DataWatcher {
     filewatcher: FileWatcher,
-    handlers: Vec<Box<dyn ExtensionHandler>>,
+    handlers: Vec<Arc<dyn ExtensionHandler>>,
     data_raw_folder: PathBuf,
+    jobs: BinarizerJobs,
+    // When not empty only the files matching one of the globs are binarized
+    filters: Vec<String>,
 }
 
 unsafe impl Send for DataWatcher {}
 unsafe impl Sync for DataWatcher {}
 
 impl DataWatcher {
-    pub fn new(data_raw_folder: PathBuf) -> Self {
+    pub fn new(data_raw_folder: PathBuf, jobs: BinarizerJobs) -> Self {
         Self {
             filewatcher: FileWatcher::new(data_raw_folder.clone()),
             handlers: Vec::new(),
             data_raw_folder,
+            jobs,
+            filters: Vec::new(),
         }
     }
+    pub fn set_filters(&mut self, filters: &[String]) -> &mut Self {
+        self.filters = filters.to_vec();
+        self
+    }
     pub fn add_handler<H>(&mut self, handler: H)
     where
         H: ExtensionHandler + 'static,
     {
-        self.handlers.push(Box::new(handler));
+        self.handlers.push(Arc::new(handler));
     }
 
     pub fn update(&mut self) {
+        let mut paths = Vec::new();
         while let Ok(path) = self.filewatcher.read_events().try_recv() {
             let path = std::path::PathBuf::from(path);
             if path.is_file() {
-                self.binarize_file(path.as_path());
+                // Sources importing or referencing the file are checked again as well
+                let dependents = asset_dependents(path.as_path());
+                std::iter::once(path).chain(dependents).for_each(|p| {
+                    if !paths.contains(&p) {
+                        paths.push(p);
+                    }
+                });
             }
         }
+        if !paths.is_empty() {
+            paths
+                .iter()
+                .for_each(|path| self.binarize_file(path.as_path()));
+            self.jobs.wait();
+        }
     }
 
+    // Every file is a job: they're sorted to be scheduled always in the same order
     pub fn binarize_all(&mut self) {
-        let path = self.data_raw_folder.clone();
-        self.binarize_folder(path.as_path());
+        let mut paths = Vec::new();
+        Self::collect_files(self.data_raw_folder.as_path(), &mut paths);
+        paths.sort();
+        paths
+            .iter()
+            .for_each(|path| self.binarize_file(path.as_path()));
+        self.jobs.wait();
+        let (completed, scheduled) = self.jobs.progress();
+        inox_log::debug_log!("Binarized {}/{} files", completed, scheduled);
     }
 
-    fn binarize_file(&mut self, path: &Path) {
+    fn is_filtered(&self, path: &Path) -> bool {
+        let local_path = path
+            .strip_prefix(self.data_raw_folder.as_path())
+            .unwrap_or(path);
+        !self.filters.is_empty()
+            && !self
+                .filters
+                .iter()
+                .any(|filter| matches_glob(filter, local_path))
+    }
+
+    fn binarize_file(&self, path: &Path) {
         let absolute_path = convert_from_local_path(self.data_raw_folder.as_path(), path);
-        for handler in self.handlers.iter_mut() {
-            handler.on_changed(absolute_path.as_path());
+        if self.is_filtered(absolute_path.as_path()) {
+            return;
         }
+        let handlers = self.handlers.clone();
+        let job_name = format!("Binarize {}", absolute_path.to_str().unwrap_or_default());
+        self.jobs.add_job(job_name.as_str(), move || {
+            for handler in handlers.iter() {
+                handler.on_changed(absolute_path.as_path());
+            }
+        });
     }
 
-    fn binarize_folder(&mut self, path: &Path) {
+    fn collect_files(path: &Path, paths: &mut Vec<PathBuf>) {
         if let Ok(dir) = std::fs::read_dir(path) {
             dir.for_each(|entry| {
                 if let Ok(dir_entry) = entry {
                     let path = dir_entry.path();
                     if !path.is_dir() {
-                        self.binarize_file(path.as_path());
+                        paths.push(path);
                     } else {
-                        self.binarize_folder(path.as_path());
+                        Self::collect_files(path.as_path(), paths);
                     }
                 }
             });
@@ -1,5 +1,7 @@
 pub use crate::binarizer::*;
+pub use crate::binarizer_jobs::*;
 pub use crate::data_watcher::*;
 
 pub mod binarizer;
+pub mod binarizer_jobs;
 pub mod data_watcher;
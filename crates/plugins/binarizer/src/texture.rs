@@ -0,0 +1,411 @@
+use std::path::{Path, PathBuf};
+
+use image::RgbaImage;
+use inox_platform::{PlatformType, PLATFORM_TYPE_ANDROID, PLATFORM_TYPE_IOS, PLATFORM_TYPE_PC};
+use inox_render::{AstcBlock, AstcChannel, CompiledTextureData, TextureFormat, TextureType};
+use inox_resources::{DiagnosticSeverity, TextureStats};
+use inox_serialize::{SerializationType, SerializeFile};
+
+use crate::{
+    add_asset_dependency, add_asset_texture_stats,
+    block_compression::{
+        encode_astc_4x4, encode_bc1, encode_bc3, encode_bc5, encode_bc7, PixelsBlock,
+    },
+    compute_compiled_path, need_to_binarize, report_asset_diagnostic, track_asset_output,
+};
+
+const ALPHA_CUTOFF: f32 = 0.5;
+
+// How an image is used decides how its mips are filtered and how it's compressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureKind {
+    // sRGB colors
+    Color,
+    // Linear values like metallic, roughness or occlusion
+    Data,
+    Normal,
+    // Alpha tested colors: coverage is preserved along the mip chain
+    Mask,
+}
+
+impl TextureKind {
+    // Lightmaps are baked with their own encoding and are used as they are
+    pub fn from_texture_type(texture_type: TextureType) -> Option<Self> {
+        match texture_type {
+            TextureType::BaseColor
+            | TextureType::Emissive
+            | TextureType::SpecularGlossiness
+            | TextureType::Diffuse
+            | TextureType::SpecularColor
+            | TextureType::SheenColor => Some(Self::Color),
+            TextureType::Normal | TextureType::ClearcoatNormal => Some(Self::Normal),
+            TextureType::Lightmap | TextureType::Count => None,
+            _ => Some(Self::Data),
+        }
+    }
+    // Images used outside of materials get their kind from their name, as the renderer
+    // looks for them: "*_normal" images are normal maps and LUTs are used as they are
+    pub fn from_image_path(path: &Path) -> Option<Self> {
+        let stem = path.file_stem()?.to_str()?;
+        if stem.ends_with("_LUT") {
+            None
+        } else if stem.to_lowercase().ends_with("_normal") {
+            Some(Self::Normal)
+        } else {
+            Some(Self::Color)
+        }
+    }
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Color => "color",
+            Self::Data => "data",
+            Self::Normal => "normal",
+            Self::Mask => "mask",
+        }
+    }
+    fn is_srgb(&self) -> bool {
+        matches!(self, Self::Color | Self::Mask)
+    }
+}
+
+struct Mip {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+}
+
+fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1. / 2.4) - 0.055
+    }
+}
+
+impl Mip {
+    // Values are filtered in linear space and normals in [-1, 1]
+    fn from_image(image: &RgbaImage, kind: TextureKind) -> Self {
+        let pixels = image
+            .pixels()
+            .map(|p| {
+                let mut v = p.0.map(|c| c as f32 / 255.);
+                if kind.is_srgb() {
+                    (0..3).for_each(|c| v[c] = srgb_to_linear(v[c]));
+                } else if kind == TextureKind::Normal {
+                    (0..3).for_each(|c| v[c] = v[c] * 2. - 1.);
+                }
+                v
+            })
+            .collect();
+        Self {
+            width: image.width(),
+            height: image.height(),
+            pixels,
+        }
+    }
+
+    fn to_rgba8(&self, kind: TextureKind) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|p| {
+                let mut v = *p;
+                if kind.is_srgb() {
+                    (0..3).for_each(|c| v[c] = linear_to_srgb(v[c]));
+                } else if kind == TextureKind::Normal {
+                    (0..3).for_each(|c| v[c] = v[c] * 0.5 + 0.5);
+                }
+                v.map(|c| (c.clamp(0., 1.) * 255.).round() as u8)
+            })
+            .collect()
+    }
+
+    fn pixel(&self, x: u32, y: u32) -> [f32; 4] {
+        let x = x.min(self.width - 1);
+        let y = y.min(self.height - 1);
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    fn downsample(&self, kind: TextureKind) -> Self {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let mut v = [0f32; 4];
+                [(0, 0), (1, 0), (0, 1), (1, 1)].iter().for_each(|(i, j)| {
+                    let p = self.pixel(x * 2 + i, y * 2 + j);
+                    (0..4).for_each(|c| v[c] += p[c] * 0.25);
+                });
+                if kind == TextureKind::Normal {
+                    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+                    if length > f32::EPSILON {
+                        (0..3).for_each(|c| v[c] /= length);
+                    } else {
+                        v[0] = 0.;
+                        v[1] = 0.;
+                        v[2] = 1.;
+                    }
+                }
+                pixels.push(v);
+            }
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn alpha_coverage(&self, scale: f32) -> f32 {
+        let count = self
+            .pixels
+            .iter()
+            .filter(|p| p[3] * scale >= ALPHA_CUTOFF)
+            .count();
+        count as f32 / self.pixels.len() as f32
+    }
+
+    // Scales alpha so that the same amount of pixels passes the alpha test as in the first mip
+    fn preserve_alpha_coverage(&mut self, coverage: f32) {
+        let (mut min_scale, mut max_scale) = (0f32, 4f32);
+        for _ in 0..16 {
+            let scale = (min_scale + max_scale) * 0.5;
+            if self.alpha_coverage(scale) < coverage {
+                min_scale = scale;
+            } else {
+                max_scale = scale;
+            }
+        }
+        self.pixels
+            .iter_mut()
+            .for_each(|p| p[3] = (p[3] * max_scale).min(1.));
+    }
+}
+
+// Cutout alpha has only fully transparent or fully opaque pixels
+fn is_alpha_tested(image: &RgbaImage) -> bool {
+    let mut has_transparent = false;
+    for p in image.pixels() {
+        match p.0[3] {
+            0..=8 => has_transparent = true,
+            247..=255 => {}
+            _ => return false,
+        }
+    }
+    has_transparent
+}
+
+fn select_format<const PLATFORM_TYPE: PlatformType>(
+    kind: TextureKind,
+    has_alpha: bool,
+    width: u32,
+    height: u32,
+) -> TextureFormat {
+    if width % 4 != 0 || height % 4 != 0 {
+        return TextureFormat::Rgba8Unorm;
+    }
+    // Shaders convert colors from sRGB by themselves: only Unorm formats are used
+    if PLATFORM_TYPE == PLATFORM_TYPE_PC {
+        match kind {
+            TextureKind::Normal => TextureFormat::Bc5RgUnorm,
+            TextureKind::Mask => TextureFormat::Bc3RgbaUnorm,
+            TextureKind::Data if !has_alpha => TextureFormat::Bc1RgbaUnorm,
+            _ => TextureFormat::Bc7RgbaUnorm,
+        }
+    } else if PLATFORM_TYPE == PLATFORM_TYPE_ANDROID || PLATFORM_TYPE == PLATFORM_TYPE_IOS {
+        TextureFormat::Astc {
+            block: AstcBlock::B4x4,
+            channel: AstcChannel::Unorm,
+        }
+    } else {
+        // Web can't rely on any block compression support
+        TextureFormat::Rgba8Unorm
+    }
+}
+
+fn compress<const N: usize>(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    encode: fn(&PixelsBlock) -> [u8; N],
+) -> Vec<u8> {
+    let mut data = Vec::new();
+    for by in 0..height.div_ceil(4) {
+        for bx in 0..width.div_ceil(4) {
+            let mut block: PixelsBlock = [[0; 4]; 16];
+            block.iter_mut().enumerate().for_each(|(i, p)| {
+                let x = (bx * 4 + i as u32 % 4).min(width - 1);
+                let y = (by * 4 + i as u32 / 4).min(height - 1);
+                let offset = ((y * width + x) * 4) as usize;
+                p.copy_from_slice(&rgba[offset..offset + 4]);
+            });
+            data.extend_from_slice(&encode(&block));
+        }
+    }
+    data
+}
+
+fn encode_mip(rgba: Vec<u8>, width: u32, height: u32, format: TextureFormat) -> Vec<u8> {
+    match format {
+        TextureFormat::Bc1RgbaUnorm => compress(&rgba, width, height, encode_bc1),
+        TextureFormat::Bc3RgbaUnorm => compress(&rgba, width, height, encode_bc3),
+        TextureFormat::Bc5RgUnorm => compress(&rgba, width, height, encode_bc5),
+        TextureFormat::Bc7RgbaUnorm => compress(&rgba, width, height, encode_bc7),
+        TextureFormat::Astc { .. } => compress(&rgba, width, height, encode_astc_4x4),
+        _ => rgba,
+    }
+}
+
+pub fn compute_texture_stats(name: &str, texture_data: &CompiledTextureData) -> TextureStats {
+    TextureStats {
+        name: name.to_string(),
+        width: texture_data.width,
+        height: texture_data.height,
+        mips: texture_data.mips.len() as _,
+        format: format!("{:?}", texture_data.format),
+        bytes: texture_data.mips.iter().map(|m| m.len() as u64).sum(),
+    }
+}
+
+pub fn compile_texture<const PLATFORM_TYPE: PlatformType>(
+    image: &RgbaImage,
+    mut kind: TextureKind,
+) -> CompiledTextureData {
+    let (width, height) = image.dimensions();
+    if kind == TextureKind::Color && is_alpha_tested(image) {
+        kind = TextureKind::Mask;
+    }
+    let has_alpha = image.pixels().any(|p| p.0[3] < 255);
+    let format = select_format::<PLATFORM_TYPE>(kind, has_alpha, width, height);
+    let mut texture_data = CompiledTextureData {
+        width,
+        height,
+        format,
+        mips: vec![encode_mip(image.to_vec(), width, height, format)],
+    };
+    let mut mip = Mip::from_image(image, kind);
+    let coverage = mip.alpha_coverage(1.);
+    // Block compressed mips have to be made of whole blocks
+    let (block_width, block_height) = format.block_dimensions();
+    while mip.width > 1 || mip.height > 1 {
+        mip = mip.downsample(kind);
+        if mip.width % block_width != 0 || mip.height % block_height != 0 {
+            break;
+        }
+        if kind == TextureKind::Mask {
+            mip.preserve_alpha_coverage(coverage);
+        }
+        texture_data.mips.push(encode_mip(
+            mip.to_rgba8(kind),
+            mip.width,
+            mip.height,
+            format,
+        ));
+    }
+    texture_data
+}
+
+// Textures of material slots are compiled next to their image, named after the image with its
+// extension and the kind of the slot, as the same image can be used by slots of different kinds.
+// Returns the compiled texture path or None when the slot uses the image as it is
+pub fn compile_material_texture<const PLATFORM_TYPE: PlatformType>(
+    path: &Path,
+    compiler: &str,
+    image_path: &Path,
+    texture_type: TextureType,
+    data_raw_folder: &Path,
+    data_folder: &Path,
+) -> Option<PathBuf> {
+    let kind = TextureKind::from_texture_type(texture_type)?;
+    let image_name = image_path.file_name()?.to_str()?;
+    let texture_path = compute_compiled_path(
+        image_path,
+        &format!("{image_name}.{}", kind.name()),
+        CompiledTextureData::extension(),
+        "",
+        data_raw_folder,
+        data_folder,
+    );
+    add_asset_dependency(path, compiler, image_path);
+    if !image_path.exists() || !need_to_binarize(path, texture_path.as_path()) {
+        return Some(texture_path).filter(|p| p.exists());
+    }
+    let image = match image::open(image_path) {
+        Ok(image) => image.into_rgba8(),
+        Err(e) => {
+            report_asset_diagnostic(
+                path,
+                compiler,
+                DiagnosticSeverity::Warning,
+                image_name,
+                &format!("Unable to read image: {e}"),
+            );
+            return None;
+        }
+    };
+    let texture_data = compile_texture::<PLATFORM_TYPE>(&image, kind);
+    add_asset_texture_stats(
+        path,
+        compiler,
+        compute_texture_stats(image_name, &texture_data),
+    );
+    if let Some(parent) = texture_path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    texture_data.save_to_file(texture_path.as_path(), SerializationType::Binary);
+    track_asset_output(path, compiler, texture_path.as_path());
+    Some(texture_path)
+}
+
+// Images are compiled next to their copy in the data folder, with the kind of their name.
+// Returns the compiled texture or None when the image is used as it is or can't be decoded
+pub fn compile_image_texture<const PLATFORM_TYPE: PlatformType>(
+    path: &Path,
+    compiler: &str,
+    data_raw_folder: &Path,
+    data_folder: &Path,
+) -> Option<CompiledTextureData> {
+    let kind = TextureKind::from_image_path(path)?;
+    let image_name = path.file_name()?.to_str()?;
+    let image = match image::open(path) {
+        Ok(image) => image.into_rgba8(),
+        Err(e) => {
+            report_asset_diagnostic(
+                path,
+                compiler,
+                DiagnosticSeverity::Info,
+                image_name,
+                &format!("Unable to decode image, it's used as it is: {e}"),
+            );
+            return None;
+        }
+    };
+    let texture_path = compute_compiled_path(
+        path,
+        &format!("{image_name}.{}", kind.name()),
+        CompiledTextureData::extension(),
+        "",
+        data_raw_folder,
+        data_folder,
+    );
+    let texture_data = compile_texture::<PLATFORM_TYPE>(&image, kind);
+    add_asset_texture_stats(
+        path,
+        compiler,
+        compute_texture_stats(image_name, &texture_data),
+    );
+    if let Some(parent) = texture_path.parent() {
+        std::fs::create_dir_all(parent).ok();
+    }
+    texture_data.save_to_file(texture_path.as_path(), SerializationType::Binary);
+    track_asset_output(path, compiler, texture_path.as_path());
+    Some(texture_data)
+}
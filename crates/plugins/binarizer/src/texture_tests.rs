@@ -0,0 +1,300 @@
+use std::{
+    fs::{self, create_dir_all},
+    path::Path,
+    sync::Arc,
+};
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use inox_messenger::MessageHub;
+use inox_platform::{PLATFORM_TYPE_ANDROID, PLATFORM_TYPE_PC, PLATFORM_TYPE_WEB};
+use inox_render::{AstcBlock, AstcChannel, CompiledTextureData, TextureFormat, TextureType};
+use inox_serialize::deserialize;
+
+use crate::{
+    block_compression::{encode_astc_4x4, encode_bc1, encode_bc4},
+    texture::{compile_material_texture, compile_texture, TextureKind},
+    ImageCompiler,
+};
+
+fn decode_565(value: u16) -> [i32; 3] {
+    let r = ((value >> 11) & 31) as i32;
+    let g = ((value >> 5) & 63) as i32;
+    let b = (value & 31) as i32;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+}
+
+fn read_texture(path: &Path) -> CompiledTextureData {
+    deserialize::<CompiledTextureData>(&fs::read(path).unwrap()).unwrap()
+}
+
+#[allow(dead_code)]
+fn block_compression_test() {
+    // Two colors block: both of them have to be exactly in the palette
+    let mut block = [[0u8; 4]; 16];
+    block.iter_mut().enumerate().for_each(|(i, p)| {
+        *p = if i % 2 == 0 {
+            [255, 0, 0, 255]
+        } else {
+            [0, 0, 255, 255]
+        }
+    });
+    let bc1 = encode_bc1(&block);
+    let c0 = decode_565(u16::from_le_bytes([bc1[0], bc1[1]]));
+    let c1 = decode_565(u16::from_le_bytes([bc1[2], bc1[3]]));
+    let indices = u32::from_le_bytes([bc1[4], bc1[5], bc1[6], bc1[7]]);
+    block.iter().enumerate().for_each(|(i, p)| {
+        let color = match (indices >> (2 * i)) & 3 {
+            0 => c0,
+            1 => c1,
+            _ => panic!("Interpolated color used for an endpoint"),
+        };
+        assert_eq!(color, [p[0] as i32, p[1] as i32, p[2] as i32]);
+    });
+
+    let values: [u8; 16] = std::array::from_fn(|i| (i * 17) as u8);
+    let bc4 = encode_bc4(&values);
+    let (a0, a1) = (bc4[0] as i32, bc4[1] as i32);
+    assert_eq!((a0, a1), (255, 0));
+    let mut bits = [0u8; 8];
+    bits[0..6].copy_from_slice(&bc4[2..8]);
+    let indices = u64::from_le_bytes(bits);
+    values.iter().enumerate().for_each(|(i, v)| {
+        let decoded = match (indices >> (3 * i)) & 7 {
+            0 => a0,
+            1 => a1,
+            index => ((8 - index as i32) * a0 + (index as i32 - 1) * a1) / 7,
+        };
+        assert!((decoded - *v as i32).abs() <= 19);
+    });
+
+    let astc = u128::from_le_bytes(encode_astc_4x4(&block));
+    assert_eq!(astc & 0x7FF, 0x42);
+    assert_eq!((astc >> 11) & 3, 0);
+    assert_eq!((astc >> 13) & 0xF, 12);
+}
+
+#[allow(dead_code)]
+fn mips_filtering_test() {
+    // Black and white pixels have to average to a linear grey
+    let image = RgbaImage::from_fn(4, 4, |x, _| {
+        if x % 2 == 0 {
+            Rgba([0, 0, 0, 255])
+        } else {
+            Rgba([255, 255, 255, 255])
+        }
+    });
+    let texture = compile_texture::<PLATFORM_TYPE_WEB>(&image, TextureKind::Color);
+    assert_eq!(texture.format, TextureFormat::Rgba8Unorm);
+    assert_eq!(texture.mips.len(), 3);
+    assert_eq!(texture.mips[1].len(), 2 * 2 * 4);
+    assert_eq!(texture.mips[1][0], 188);
+
+    // Opposite normals are averaged and renormalized
+    let image = RgbaImage::from_fn(4, 4, |x, _| {
+        if x % 2 == 0 {
+            Rgba([26, 128, 230, 255])
+        } else {
+            Rgba([230, 128, 230, 255])
+        }
+    });
+    let texture = compile_texture::<PLATFORM_TYPE_WEB>(&image, TextureKind::Normal);
+    assert_eq!(texture.mips[1][2], 255);
+
+    // Alpha tested pixels should not disappear in smaller mips
+    let image = RgbaImage::from_fn(8, 8, |x, y| {
+        if x < 4 && (x + y) % 2 == 0 {
+            Rgba([255, 255, 255, 153])
+        } else {
+            Rgba([255, 255, 255, 0])
+        }
+    });
+    let texture = compile_texture::<PLATFORM_TYPE_WEB>(&image, TextureKind::Mask);
+    let mip = &texture.mips[1];
+    let covered = mip.chunks(4).filter(|p| p[3] >= 127).count();
+    assert_eq!(covered, 8);
+}
+
+#[allow(dead_code)]
+fn material_texture_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_texture_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    let data_raw_folder = test_folder.join("data_raw");
+    let data_folder = test_folder.join("data");
+    let textures_folder = data_raw_folder.join("textures");
+    create_dir_all(textures_folder.as_path()).unwrap();
+    create_dir_all(data_folder.as_path()).unwrap();
+
+    let scene_path = data_raw_folder.join("scene.gltf");
+    fs::write(scene_path.as_path(), "{}").unwrap();
+    let color = RgbaImage::from_fn(8, 8, |x, y| Rgba([(x * 32) as u8, (y * 32) as u8, 64, 255]));
+    color.save(textures_folder.join("rock.png")).unwrap();
+    DynamicImage::ImageRgba8(color.clone())
+        .to_rgb8()
+        .save(textures_folder.join("rock.jpg"))
+        .unwrap();
+    let leaf = RgbaImage::from_fn(8, 8, |x, _| Rgba([0, 255, 0, if x < 4 { 255 } else { 0 }]));
+    leaf.save(textures_folder.join("leaf.png")).unwrap();
+    RgbaImage::from_pixel(6, 6, Rgba([255, 0, 0, 255]))
+        .save(textures_folder.join("odd.png"))
+        .unwrap();
+
+    let scene_path = scene_path.canonicalize().unwrap();
+    let compile = |name: &str, texture_type: TextureType| {
+        compile_material_texture::<PLATFORM_TYPE_PC>(
+            scene_path.as_path(),
+            "gltf",
+            textures_folder.join(name).canonicalize().unwrap().as_path(),
+            texture_type,
+            data_raw_folder.as_path(),
+            data_folder.as_path(),
+        )
+    };
+    let output_folder = data_folder.canonicalize().unwrap().join("textures");
+
+    // The format comes from the slot using the image, whatever its name is
+    let albedo = compile("rock.png", TextureType::BaseColor).unwrap();
+    assert_eq!(albedo, output_folder.join("rock.png.color.texture"));
+    let albedo = read_texture(albedo.as_path());
+    assert_eq!(albedo.format, TextureFormat::Bc7RgbaUnorm);
+    // 2x2 mip can't be made of whole blocks
+    assert_eq!(albedo.mips.len(), 2);
+    assert_eq!(albedo.mips[0].len(), 4 * 16);
+    assert_eq!(albedo.mips[1].len(), 16);
+
+    let normal = compile("rock.png", TextureType::Normal).unwrap();
+    assert_eq!(normal, output_folder.join("rock.png.normal.texture"));
+    assert_eq!(
+        read_texture(normal.as_path()).format,
+        TextureFormat::Bc5RgUnorm
+    );
+
+    // Images with the same name and a different extension don't overwrite each other
+    let occlusion = compile("rock.jpg", TextureType::Occlusion).unwrap();
+    assert_eq!(occlusion, output_folder.join("rock.jpg.data.texture"));
+    assert!(output_folder.join("rock.png.color.texture").exists());
+
+    let leaf = compile("leaf.png", TextureType::BaseColor).unwrap();
+    assert_eq!(
+        read_texture(leaf.as_path()).format,
+        TextureFormat::Bc3RgbaUnorm
+    );
+
+    let odd = read_texture(compile("odd.png", TextureType::Emissive).unwrap().as_path());
+    assert_eq!(odd.format, TextureFormat::Rgba8Unorm);
+    assert_eq!(odd.mips.len(), 3);
+    assert_eq!(odd.mip_size(1), (3, 3));
+    assert_eq!(odd.mips[2].len(), 4);
+
+    // Lightmaps are used as they are
+    assert!(compile("rock.png", TextureType::Lightmap).is_none());
+
+    // Already compiled textures are kept
+    let modified = || {
+        fs::metadata(output_folder.join("rock.png.color.texture"))
+            .unwrap()
+            .modified()
+            .unwrap()
+    };
+    let time = modified();
+    assert!(compile("rock.png", TextureType::BaseColor).is_some());
+    assert_eq!(modified(), time);
+
+    fs::remove_dir_all(data_folder.as_path()).ok();
+    create_dir_all(data_folder.as_path()).unwrap();
+    let albedo = compile_material_texture::<PLATFORM_TYPE_ANDROID>(
+        scene_path.as_path(),
+        "gltf",
+        textures_folder
+            .join("rock.png")
+            .canonicalize()
+            .unwrap()
+            .as_path(),
+        TextureType::BaseColor,
+        data_raw_folder.as_path(),
+        data_folder.as_path(),
+    )
+    .unwrap();
+    let albedo = read_texture(albedo.as_path());
+    assert_eq!(
+        albedo.format,
+        TextureFormat::Astc {
+            block: AstcBlock::B4x4,
+            channel: AstcChannel::Unorm
+        }
+    );
+    assert_eq!(albedo.mips[0].len(), 4 * 16);
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[allow(dead_code)]
+fn image_texture_test() {
+    let test_folder = std::env::temp_dir().join("inox_binarizer_image_test");
+    fs::remove_dir_all(test_folder.as_path()).ok();
+    let data_raw_folder = test_folder.join("data_raw");
+    let data_folder = test_folder.join("data");
+    let textures_folder = data_raw_folder.join("textures");
+    create_dir_all(textures_folder.as_path()).unwrap();
+    create_dir_all(data_folder.as_path()).unwrap();
+
+    let color = RgbaImage::from_fn(8, 8, |x, y| Rgba([(x * 32) as u8, (y * 32) as u8, 64, 255]));
+    color.save(textures_folder.join("icon.png")).unwrap();
+    color.save(textures_folder.join("n_normal.png")).unwrap();
+    color.save(textures_folder.join("grading_LUT.png")).unwrap();
+    RgbaImage::from_pixel(6, 6, Rgba([255, 0, 0, 255]))
+        .save(textures_folder.join("odd.png"))
+        .unwrap();
+    fs::write(textures_folder.join("broken.png"), "not an image").unwrap();
+
+    let compiler = ImageCompiler::<PLATFORM_TYPE_PC>::new(
+        Arc::new(MessageHub::default()),
+        data_raw_folder.as_path(),
+        data_folder.as_path(),
+    );
+    let output_folder = data_folder.canonicalize().unwrap().join("textures");
+    let compile = |name: &str| {
+        let path = textures_folder.join(name).canonicalize().unwrap();
+        compiler.process_path(path.as_path())
+    };
+
+    // Block compressed images are copied too, as fallback when the GPU doesn't support them
+    assert!(compile("icon.png"));
+    let icon = read_texture(output_folder.join("icon.png.color.texture").as_path());
+    assert_eq!(icon.format, TextureFormat::Bc7RgbaUnorm);
+    assert_eq!(icon.mips.len(), 2);
+    assert!(output_folder.join("icon.png").exists());
+
+    assert!(compile("n_normal.png"));
+    let normal = read_texture(output_folder.join("n_normal.png.normal.texture").as_path());
+    assert_eq!(normal.format, TextureFormat::Bc5RgUnorm);
+
+    // Uncompressed images are used only through their compiled texture
+    assert!(compile("odd.png"));
+    assert_eq!(
+        read_texture(output_folder.join("odd.png.color.texture").as_path()).format,
+        TextureFormat::Rgba8Unorm
+    );
+    assert!(!output_folder.join("odd.png").exists());
+
+    // LUTs and images that can't be decoded are copied as they are
+    assert!(compile("grading_LUT.png"));
+    assert!(output_folder.join("grading_LUT.png").exists());
+    assert!(!output_folder.join("grading_LUT.png.color.texture").exists());
+    assert!(compile("broken.png"));
+    assert!(output_folder.join("broken.png").exists());
+    assert!(!output_folder.join("broken.png.color.texture").exists());
+
+    fs::remove_dir_all(test_folder.as_path()).ok();
+}
+
+#[test]
+fn tests() {
+    block_compression_test();
+    mips_filtering_test();
+    material_texture_test();
+    image_texture_test();
+}
@@ -0,0 +1,582 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Instant, UNIX_EPOCH},
+};
+
+use inox_log::debug_log;
+use inox_resources::{
+    AssetDiagnostic, AssetResult, AssetStats, AssetStatus, DiagnosticSeverity, MeshStats,
+    TextureStats,
+};
+use inox_serialize::{deserialize_from_text, serialize_to_text, Deserialize, Serialize};
+use inox_uid::checksum;
+
+pub const ASSET_DATABASE_FILENAME: &str = "asset_database.json";
+// Databases written with a different version are discarded and everything is rebuilt
+const ASSET_DATABASE_VERSION: u32 = 1;
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+// Content of a file when an asset was compiled.
+// Path is local to the data_raw folder when the file is inside it
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct AssetFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: u64,
+    pub hash: u64,
+}
+
+// Outputs are local to the data folder
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct AssetEntry {
+    pub compiler_version: u32,
+    pub source: AssetFile,
+    pub dependencies: Vec<AssetFile>,
+    pub outputs: Vec<PathBuf>,
+}
+
+// Assets are stored by source local path and then by the compiler that processed them
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct AssetDatabaseData {
+    pub version: u32,
+    pub assets: BTreeMap<String, BTreeMap<String, AssetEntry>>,
+}
+
+struct AssetBuild {
+    compiler: String,
+    compiler_version: u32,
+    dependencies: Vec<PathBuf>,
+    outputs: Vec<PathBuf>,
+    diagnostics: Vec<AssetDiagnostic>,
+    stats: AssetStats,
+    start: Instant,
+}
+
+impl AssetBuild {
+    fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|d| d.severity == DiagnosticSeverity::Error)
+    }
+
+    fn into_result(mut self, source: String, status: AssetStatus) -> AssetResult {
+        self.stats.time_ms = self.start.elapsed().as_millis() as u64;
+        AssetResult {
+            diagnostics: self.diagnostics,
+            stats: self.stats,
+            ..AssetResult::new(&source, &self.compiler, status)
+        }
+    }
+}
+
+struct AssetDatabase {
+    data_raw_folder: PathBuf,
+    data_folder: PathBuf,
+    data: AssetDatabaseData,
+    // Keyed by source and compiler, as a source can be compiled by different compilers
+    building: HashMap<(PathBuf, String), AssetBuild>,
+    // Changes are written by save_asset_database and not after each asset
+    is_dirty: bool,
+    verify_only: bool,
+    stale: Vec<String>,
+    results: Vec<AssetResult>,
+}
+
+// One database for each data folder, shared by all the compilers writing into it
+static ASSET_DATABASES: OnceLock<Mutex<HashMap<PathBuf, AssetDatabase>>> = OnceLock::new();
+
+fn canonical_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn local_path(path: &Path, folder: &Path) -> PathBuf {
+    path.strip_prefix(folder).unwrap_or(path).to_path_buf()
+}
+
+pub fn compute_file_hash(path: &Path) -> Option<u64> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    let mut hash = 0;
+    loop {
+        let count = file.read(&mut buffer).ok()?;
+        if count == 0 {
+            return Some(hash);
+        }
+        hash = checksum(hash, &buffer[..count]);
+    }
+}
+
+// Content is hashed again only when size or modification time differ from the previous one
+fn read_asset_file(
+    path: &Path,
+    local_path: PathBuf,
+    previous: Option<&AssetFile>,
+) -> Option<AssetFile> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    let size = metadata.len();
+    let hash = match previous {
+        Some(p) if p.size == size && p.modified == modified => p.hash,
+        _ => compute_file_hash(path)?,
+    };
+    Some(AssetFile {
+        path: local_path,
+        size,
+        modified,
+        hash,
+    })
+}
+
+impl AssetDatabase {
+    fn load(data_raw_folder: &Path, data_folder: &Path) -> Self {
+        let data = fs::read(data_folder.join(ASSET_DATABASE_FILENAME))
+            .ok()
+            .and_then(|bytes| deserialize_from_text::<AssetDatabaseData>(&bytes))
+            .filter(|data| data.version == ASSET_DATABASE_VERSION)
+            .unwrap_or_else(|| AssetDatabaseData {
+                version: ASSET_DATABASE_VERSION,
+                ..Default::default()
+            });
+        Self {
+            data_raw_folder: data_raw_folder.to_path_buf(),
+            data_folder: data_folder.to_path_buf(),
+            data,
+            building: HashMap::new(),
+            is_dirty: false,
+            verify_only: false,
+            stale: Vec::new(),
+            results: Vec::new(),
+        }
+    }
+
+    fn save(&mut self) {
+        if !self.is_dirty {
+            return;
+        }
+        let path = self.data_folder.join(ASSET_DATABASE_FILENAME);
+        match fs::write(path.as_path(), serialize_to_text(&self.data)) {
+            Ok(()) => self.is_dirty = false,
+            Err(e) => eprintln!("Unable to write asset database {path:?}: {e}"),
+        }
+    }
+
+    fn source_key(&self, source: &Path) -> String {
+        local_path(source, self.data_raw_folder.as_path())
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    fn add_result(&mut self, source: &Path, compiler: &str, status: AssetStatus) {
+        let source = self.source_key(source);
+        self.results
+            .push(AssetResult::new(&source, compiler, status));
+    }
+
+    // The asset is forgotten so that it's compiled again next time
+    fn fail_build(&mut self, source: &Path, build: AssetBuild) {
+        let key = self.source_key(source);
+        if let Some(compilers) = self.data.assets.get_mut(&key) {
+            compilers.remove(&build.compiler);
+            if compilers.is_empty() {
+                self.data.assets.remove(&key);
+            }
+        }
+        self.results
+            .push(build.into_result(key, AssetStatus::Failed));
+        self.is_dirty = true;
+    }
+
+    fn entry(&self, source: &Path, compiler: &str) -> Option<&AssetEntry> {
+        self.data
+            .assets
+            .get(&self.source_key(source))
+            .and_then(|compilers| compilers.get(compiler))
+    }
+}
+
+fn has_changed(file: &AssetFile, data_raw_folder: &Path) -> bool {
+    let path = data_raw_folder.join(file.path.as_path());
+    read_asset_file(path.as_path(), file.path.clone(), Some(file))
+        .is_none_or(|current| current.hash != file.hash)
+}
+
+// Reads and hashes files: it's called without holding the asset databases lock
+fn stale_reason(
+    entry: Option<&AssetEntry>,
+    compiler_version: u32,
+    data_raw_folder: &Path,
+    data_folder: &Path,
+) -> Option<String> {
+    let Some(entry) = entry else {
+        return Some("never compiled".to_string());
+    };
+    if entry.compiler_version != compiler_version {
+        return Some(format!(
+            "compiler version changed from {} to {compiler_version}",
+            entry.compiler_version
+        ));
+    }
+    if has_changed(&entry.source, data_raw_folder) {
+        return Some("source changed".to_string());
+    }
+    if let Some(dependency) = entry
+        .dependencies
+        .iter()
+        .find(|d| has_changed(d, data_raw_folder))
+    {
+        return Some(format!("dependency {:?} changed", dependency.path));
+    }
+    if let Some(output) = entry.outputs.iter().find(|o| !data_folder.join(o).exists()) {
+        return Some(format!("output {output:?} missing"));
+    }
+    None
+}
+
+fn with_asset_database<F, R>(data_raw_folder: &Path, data_folder: &Path, f: F) -> R
+where
+    F: FnOnce(&mut AssetDatabase) -> R,
+{
+    let data_raw_folder = canonical_path(data_raw_folder);
+    let data_folder = canonical_path(data_folder);
+    let mut databases = ASSET_DATABASES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap();
+    let database = databases
+        .entry(data_folder.clone())
+        .or_insert_with(|| AssetDatabase::load(data_raw_folder.as_path(), data_folder.as_path()));
+    f(database)
+}
+
+fn for_each_asset_database<F>(mut f: F)
+where
+    F: FnMut(&mut AssetDatabase),
+{
+    if let Some(databases) = ASSET_DATABASES.get() {
+        for database in databases.lock().unwrap().values_mut() {
+            f(database);
+        }
+    }
+}
+
+// In verify mode nothing is compiled: stale assets are only collected to be reported
+pub fn open_asset_database(data_raw_folder: &Path, data_folder: &Path, verify_only: bool) {
+    with_asset_database(data_raw_folder, data_folder, |database| {
+        database.verify_only = verify_only;
+        database.stale.clear();
+        database.results.clear();
+    });
+}
+
+// Removes every output recorded in the database together with the database itself
+pub fn clean_asset_database(data_raw_folder: &Path, data_folder: &Path) {
+    with_asset_database(data_raw_folder, data_folder, |database| {
+        database.data.assets.values().for_each(|compilers| {
+            compilers.values().for_each(|entry| {
+                entry.outputs.iter().for_each(|output| {
+                    let path = database.data_folder.join(output);
+                    if path.exists() {
+                        debug_log!("Removing {:?}", path);
+                        fs::remove_file(path).ok();
+                    }
+                });
+            });
+        });
+        database.data.assets.clear();
+        database.is_dirty = false;
+        fs::remove_file(database.data_folder.join(ASSET_DATABASE_FILENAME)).ok();
+    });
+}
+
+// Returns true when the asset has to be compiled:
+// it's then considered in building until end_asset or abort_asset are called.
+// Files are read and hashed out of the lock, taken only to look up and update the records
+pub fn begin_asset(
+    path: &Path,
+    compiler: &str,
+    compiler_version: u32,
+    data_raw_folder: &Path,
+    data_folder: &Path,
+) -> bool {
+    let source = canonical_path(path);
+    let (entry, database_data_raw_folder, database_data_folder) =
+        with_asset_database(data_raw_folder, data_folder, |database| {
+            (
+                database.entry(source.as_path(), compiler).cloned(),
+                database.data_raw_folder.clone(),
+                database.data_folder.clone(),
+            )
+        });
+    let reason = stale_reason(
+        entry.as_ref(),
+        compiler_version,
+        database_data_raw_folder.as_path(),
+        database_data_folder.as_path(),
+    );
+    with_asset_database(data_raw_folder, data_folder, |database| {
+        let Some(reason) = reason else {
+            if !database.verify_only {
+                database.add_result(source.as_path(), compiler, AssetStatus::Skipped);
+            }
+            return false;
+        };
+        if database.verify_only {
+            let key = database.source_key(source.as_path());
+            database.stale.push(format!("{key} ({compiler}): {reason}"));
+            return false;
+        }
+        debug_log!("Compiling {:?} with {}: {}", source, compiler, reason);
+        database.building.insert(
+            (source, compiler.to_string()),
+            AssetBuild {
+                compiler: compiler.to_string(),
+                compiler_version,
+                dependencies: Vec::new(),
+                outputs: Vec::new(),
+                diagnostics: Vec::new(),
+                stats: AssetStats::default(),
+                start: Instant::now(),
+            },
+        );
+        true
+    })
+}
+
+// Records the asset with the current content of its source and dependencies,
+// unless an error has been reported while compiling it
+pub fn end_asset(path: &Path, compiler: &str, data_raw_folder: &Path, data_folder: &Path) {
+    let source = canonical_path(path);
+    let Some((build, key, previous, database_data_raw_folder, database_data_folder)) =
+        with_asset_database(data_raw_folder, data_folder, |database| {
+            let build = database
+                .building
+                .remove(&(source.clone(), compiler.to_string()))?;
+            if build.has_errors() {
+                database.fail_build(source.as_path(), build);
+                return None;
+            }
+            let previous = database.entry(source.as_path(), compiler).cloned();
+            Some((
+                build,
+                database.source_key(source.as_path()),
+                previous,
+                database.data_raw_folder.clone(),
+                database.data_folder.clone(),
+            ))
+        })
+    else {
+        return;
+    };
+
+    // Sources and dependencies are hashed without holding the lock
+    let read_file = |path: &Path| {
+        let local_path = local_path(path, database_data_raw_folder.as_path());
+        let previous = previous.as_ref().and_then(|entry| {
+            std::iter::once(&entry.source)
+                .chain(entry.dependencies.iter())
+                .find(|f| f.path == local_path)
+        });
+        read_asset_file(path, local_path, previous)
+    };
+    let Some(source_file) = read_file(source.as_path()) else {
+        eprintln!("Unable to read source asset {source:?}");
+        with_asset_database(data_raw_folder, data_folder, |database| {
+            database
+                .results
+                .push(build.into_result(key, AssetStatus::Failed));
+        });
+        return;
+    };
+    let mut dependencies_files: Vec<AssetFile> = Vec::new();
+    build.dependencies.iter().for_each(|dependency| {
+        let dependency = canonical_path(dependency);
+        if dependency == source
+            || dependencies_files
+                .iter()
+                .any(|d| database_data_raw_folder.join(d.path.as_path()) == dependency)
+        {
+            return;
+        }
+        match read_file(dependency.as_path()) {
+            Some(file) => dependencies_files.push(file),
+            None => debug_log!("Unable to find dependency {:?} of {:?}", dependency, source),
+        }
+    });
+    let mut outputs = build
+        .outputs
+        .iter()
+        .map(|o| local_path(o, database_data_folder.as_path()))
+        .collect::<Vec<_>>();
+    outputs.sort();
+    outputs.dedup();
+    let entry = AssetEntry {
+        compiler_version: build.compiler_version,
+        source: source_file,
+        dependencies: dependencies_files,
+        outputs: outputs.clone(),
+    };
+    with_asset_database(data_raw_folder, data_folder, |database| {
+        database
+            .data
+            .assets
+            .entry(key.clone())
+            .or_default()
+            .insert(build.compiler.clone(), entry);
+        database.results.push(AssetResult {
+            outputs,
+            ..build.into_result(key, AssetStatus::Produced)
+        });
+        database.is_dirty = true;
+    });
+}
+
+// Failed assets are forgotten so that they're compiled again next time
+pub fn abort_asset(path: &Path, compiler: &str, data_raw_folder: &Path, data_folder: &Path) {
+    let source = canonical_path(path);
+    with_asset_database(data_raw_folder, data_folder, |database| {
+        if let Some(build) = database
+            .building
+            .remove(&(source.clone(), compiler.to_string()))
+        {
+            database.fail_build(source.as_path(), build);
+        }
+    });
+}
+
+// Writes the database when assets have been recorded or forgotten since it was last saved
+pub fn save_asset_database(data_raw_folder: &Path, data_folder: &Path) {
+    with_asset_database(data_raw_folder, data_folder, |database| database.save());
+}
+
+// Returns true when the source is being compiled for the data folder of the output,
+// by any compiler
+pub fn is_asset_building(path: &Path, output_path: &Path) -> bool {
+    let source = canonical_path(path);
+    let mut is_building = false;
+    for_each_asset_database(|database| {
+        is_building |= output_path.starts_with(database.data_folder.as_path())
+            && database.building.keys().any(|(s, _)| *s == source);
+    });
+    is_building
+}
+
+// Files written while compiling the source, removed by clean_asset_database
+pub fn track_asset_output(path: &Path, compiler: &str, output_path: &Path) {
+    let key = (canonical_path(path), compiler.to_string());
+    for_each_asset_database(|database| {
+        if !output_path.starts_with(database.data_folder.as_path()) {
+            return;
+        }
+        if let Some(build) = database.building.get_mut(&key) {
+            build.outputs.push(output_path.to_path_buf());
+        }
+    });
+}
+
+// Files read while compiling the source other than the source itself
+pub fn add_asset_dependency(path: &Path, compiler: &str, dependency: &Path) {
+    let key = (canonical_path(path), compiler.to_string());
+    for_each_asset_database(|database| {
+        if let Some(build) = database.building.get_mut(&key) {
+            build.dependencies.push(dependency.to_path_buf());
+        }
+    });
+}
+
+// Problems found while compiling the source, errors make it fail when it ends
+pub fn report_asset_diagnostic(
+    path: &Path,
+    compiler: &str,
+    severity: DiagnosticSeverity,
+    location: &str,
+    message: &str,
+) {
+    match severity {
+        DiagnosticSeverity::Info => debug_log!("{:?} {}: {}", path, location, message),
+        _ => eprintln!("{severity:?} in {path:?} {location}: {message}"),
+    }
+    let key = (canonical_path(path), compiler.to_string());
+    for_each_asset_database(|database| {
+        let asset = database.source_key(key.0.as_path());
+        if let Some(build) = database.building.get_mut(&key) {
+            build.diagnostics.push(AssetDiagnostic {
+                severity,
+                asset,
+                location: location.to_string(),
+                message: message.to_string(),
+            });
+        }
+    });
+}
+
+pub fn add_asset_mesh_stats(path: &Path, compiler: &str, stats: MeshStats) {
+    let key = (canonical_path(path), compiler.to_string());
+    for_each_asset_database(|database| {
+        if let Some(build) = database.building.get_mut(&key) {
+            build.stats.meshes.push(stats.clone());
+        }
+    });
+}
+
+pub fn add_asset_texture_stats(path: &Path, compiler: &str, stats: TextureStats) {
+    let key = (canonical_path(path), compiler.to_string());
+    for_each_asset_database(|database| {
+        if let Some(build) = database.building.get_mut(&key) {
+            build.stats.textures.push(stats.clone());
+        }
+    });
+}
+
+// Sources that have to be compiled again when the file changes
+pub fn asset_dependents(path: &Path) -> Vec<PathBuf> {
+    let path = canonical_path(path);
+    let mut dependents = Vec::new();
+    for_each_asset_database(|database| {
+        let local_path = local_path(path.as_path(), database.data_raw_folder.as_path());
+        database.data.assets.values().for_each(|compilers| {
+            compilers.values().for_each(|entry| {
+                if entry.dependencies.iter().any(|d| d.path == local_path) {
+                    let source = database.data_raw_folder.join(entry.source.path.as_path());
+                    if !dependents.contains(&source) {
+                        dependents.push(source);
+                    }
+                }
+            });
+        });
+    });
+    dependents
+}
+
+// Stale assets found in verify mode, together with the ones whose source was removed
+pub fn stale_assets(data_raw_folder: &Path, data_folder: &Path) -> Vec<String> {
+    with_asset_database(data_raw_folder, data_folder, |database| {
+        let mut stale = database.stale.clone();
+        database.data.assets.iter().for_each(|(key, compilers)| {
+            if !database.data_raw_folder.join(key).exists() {
+                compilers.keys().for_each(|compiler| {
+                    stale.push(format!("{key} ({compiler}): source removed"));
+                });
+            }
+        });
+        stale
+    })
+}
+
+// Results of the assets processed since they were last taken
+pub fn take_asset_results(data_raw_folder: &Path, data_folder: &Path) -> Vec<AssetResult> {
+    with_asset_database(data_raw_folder, data_folder, |database| {
+        std::mem::take(&mut database.results)
+    })
+}
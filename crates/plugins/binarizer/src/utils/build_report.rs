@@ -0,0 +1,39 @@
+use std::{fs, path::Path};
+
+use inox_resources::{BuildReport, BUILD_REPORT_FILENAME};
+use inox_serialize::{deserialize_from_text, serialize_to_text, Serialize};
+
+fn write_report<T>(path: &Path, report: &T) -> bool
+where
+    T: Serialize,
+{
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).ok();
+    }
+    match fs::write(path, serialize_to_text(report)) {
+        Ok(_) => true,
+        Err(e) => {
+            eprintln!("Unable to write build report {path:?}: {e}");
+            false
+        }
+    }
+}
+
+// Reports of more platforms written together as json for build pipelines
+pub fn write_build_reports(path: &Path, reports: &[BuildReport]) -> bool {
+    write_report(path, &reports)
+}
+
+// Report kept in the data folder, the one shown by the viewer
+pub fn write_build_report(report: &BuildReport) -> bool {
+    write_report(
+        report.data_folder.join(BUILD_REPORT_FILENAME).as_path(),
+        report,
+    )
+}
+
+pub fn read_build_report(data_folder: &Path) -> Option<BuildReport> {
+    fs::read(data_folder.join(BUILD_REPORT_FILENAME))
+        .ok()
+        .and_then(|bytes| deserialize_from_text::<BuildReport>(&bytes))
+}
@@ -0,0 +1,65 @@
+const DATA_URI_PREFIX: &str = "data:";
+const BASE64_MARKER: &str = ";base64";
+
+pub fn is_data_uri(uri: &str) -> bool {
+    uri.starts_with(DATA_URI_PREFIX)
+}
+
+// Decodes a "data:[<mime type>][;base64],<data>" uri returning its mime type and content
+pub fn decode_data_uri(uri: &str) -> Option<(String, Vec<u8>)> {
+    let (header, data) = uri.strip_prefix(DATA_URI_PREFIX)?.split_once(',')?;
+    if let Some(mime_type) = header.strip_suffix(BASE64_MARKER) {
+        decode_base64(data).map(|bytes| (mime_type.to_string(), bytes))
+    } else {
+        Some((header.to_string(), data.as_bytes().to_vec()))
+    }
+}
+
+pub fn decode_base64(data: &str) -> Option<Vec<u8>> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a' + 26) as u32),
+            b'0'..=b'9' => Some((c - b'0' + 52) as u32),
+            b'+' | b'-' => Some(62),
+            b'/' | b'_' => Some(63),
+            _ => None,
+        }
+    }
+
+    let mut result = Vec::with_capacity(data.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut num_bits = 0;
+    for c in data.bytes() {
+        if c == b'=' {
+            break;
+        }
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        bits = (bits << 6) | value(c)?;
+        num_bits += 6;
+        if num_bits >= 8 {
+            num_bits -= 8;
+            result.push((bits >> num_bits) as u8);
+            bits &= (1 << num_bits) - 1;
+        }
+    }
+    Some(result)
+}
+
+#[test]
+fn test_decode_data_uri() {
+    assert_eq!(decode_base64("SU5PWA==").unwrap(), b"INOX");
+    assert_eq!(decode_base64("SU5PWCE").unwrap(), b"INOX!");
+    assert!(decode_base64("SU5P*A==").is_none());
+
+    let (mime_type, bytes) =
+        decode_data_uri("data:application/octet-stream;base64,AAECAw==").unwrap();
+    assert_eq!(mime_type, "application/octet-stream");
+    assert_eq!(bytes, vec![0, 1, 2, 3]);
+
+    assert!(is_data_uri("data:image/png;base64,"));
+    assert!(!is_data_uri("textures/image.png"));
+    assert!(decode_data_uri("textures/image.png").is_none());
+}
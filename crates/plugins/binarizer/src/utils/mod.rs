@@ -1,5 +1,11 @@
+pub use crate::asset_database::*;
+pub use crate::build_report::*;
+pub use crate::data_uri_utils::*;
 pub use crate::naga_utils::*;
 pub use crate::path_utils::*;
 
+pub mod asset_database;
+pub mod build_report;
+pub mod data_uri_utils;
 pub mod naga_utils;
 pub mod path_utils;
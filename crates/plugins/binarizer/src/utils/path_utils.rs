@@ -3,29 +3,31 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use crate::{is_asset_building, track_asset_output};
 use inox_filesystem::convert_in_local_path;
+use inox_log::debug_log;
 use inox_messenger::MessageHubRc;
 use inox_resources::ReloadEvent;
+use inox_serialize::{SerializationType, Serialize, SerializeFile};
 
+// Assets being compiled after a check of the asset database are always written,
+// their outputs are recorded by the functions writing them
 pub fn need_to_binarize(original_path: &Path, new_path: &Path) -> bool {
-    let mut need_copy = false;
-    if let Ok(raw_time) = std::fs::metadata(original_path).unwrap().modified() {
-        if !new_path.exists() {
-            need_copy = true;
-        } else if let Ok(data_time) = std::fs::metadata(new_path).unwrap().modified() {
-            if data_time < raw_time {
-                need_copy = true;
-            }
-        } else {
-            need_copy = true;
-        }
+    if is_asset_building(original_path, new_path) {
+        return true;
+    }
+    // Files deleted or not written yet can't be compared: they're binarized again
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(original_path), modified(new_path)) {
+        (Some(raw_time), Some(data_time)) => data_time < raw_time,
+        _ => true,
     }
-    need_copy
 }
 
 pub fn copy_into_data_folder(
     message_hub: &MessageHubRc,
     path: &Path,
+    compiler: &str,
     data_raw_folder: &Path,
     data_folder: &Path,
 ) -> bool {
@@ -42,6 +44,7 @@ pub fn copy_into_data_folder(
     if need_to_binarize(path, new_path.as_path()) {
         let result = copy(path, new_path.as_path());
         if result.is_ok() {
+            track_asset_output(path, compiler, new_path.as_path());
             send_reloaded_event(message_hub, new_path.as_path());
             return true;
         }
@@ -61,3 +64,97 @@ pub fn to_local_path(original_path: &Path, data_raw_folder: &Path, data_folder:
     let path = convert_in_local_path(base_path.as_path(), data_folder);
     path
 }
+
+// Path in the data folder of a file generated from the source path:
+// it's named new_name.extension and placed in folder next to where the source would be
+pub fn compute_compiled_path(
+    path: &Path,
+    new_name: &str,
+    extension: &str,
+    folder: &str,
+    data_raw_folder: &Path,
+    data_folder: &Path,
+) -> PathBuf {
+    let filename = path.file_name().unwrap().to_str().unwrap();
+    let destination_ext = format!("{new_name}.{extension}");
+    let mut filepath = path.parent().unwrap().to_path_buf();
+    if !folder.is_empty() {
+        filepath = filepath.join(folder);
+    }
+    filepath = filepath.join(filename);
+    let mut from_source_to_compiled = filepath.to_str().unwrap().to_string();
+    from_source_to_compiled = from_source_to_compiled.replace(
+        data_raw_folder.canonicalize().unwrap().to_str().unwrap(),
+        data_folder.canonicalize().unwrap().to_str().unwrap(),
+    );
+    from_source_to_compiled = from_source_to_compiled.replace(filename, destination_ext.as_str());
+
+    PathBuf::from(from_source_to_compiled)
+}
+
+pub fn create_compiled_file<T>(
+    path: &Path,
+    compiler: &str,
+    data: &T,
+    new_name: &str,
+    folder: &str,
+    serialization_type: SerializationType,
+    data_raw_folder: &Path,
+    data_folder: &Path,
+) -> PathBuf
+where
+    T: Serialize + SerializeFile + Clone + 'static,
+{
+    let new_path = compute_compiled_path(
+        path,
+        new_name,
+        T::extension(),
+        folder,
+        data_raw_folder,
+        data_folder,
+    );
+    if !new_path.exists() {
+        let result = create_dir_all(new_path.parent().unwrap());
+        debug_assert!(result.is_ok());
+    }
+    if need_to_binarize(path, new_path.as_path()) {
+        debug_log!("Serializing {:?}", new_path);
+        data.save_to_file(new_path.as_path(), serialization_type);
+        track_asset_output(path, compiler, new_path.as_path());
+    }
+    new_path
+}
+
+// Glob of a path local to the data_raw folder, with '/' as separator:
+// '**' matches any number of folders while '*' and '?' don't cross them.
+// Patterns without a '/' are matched against the file name only
+pub fn matches_glob(pattern: &str, local_path: &Path) -> bool {
+    let local_path = local_path.to_string_lossy().replace('\\', "/");
+    if pattern.contains('/') {
+        let pattern = pattern.trim_start_matches("./");
+        glob_match(pattern.as_bytes(), local_path.as_bytes())
+    } else {
+        let name = local_path.rsplit('/').next().unwrap_or_default();
+        glob_match(pattern.as_bytes(), name.as_bytes())
+    }
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => {
+            glob_match(rest, text)
+                || (0..text.len())
+                    .filter(|i| text[*i] == b'/')
+                    .any(|i| glob_match(rest, &text[i + 1..]))
+        }
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|i| *i == 0 || text[*i - 1] != b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'?', rest @ ..] => {
+            text.first().is_some_and(|c| *c != b'/') && glob_match(rest, &text[1..])
+        }
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
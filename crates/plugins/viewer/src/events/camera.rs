@@ -0,0 +1,93 @@
+use inox_commands::CommandParser;
+use inox_math::Matrix4;
+use inox_messenger::implement_message;
+
+// Sent by Blender while it's streaming its 3D viewport camera,
+// with the camera world transform and its projection
+pub enum CameraSyncEvent {
+    Update(Matrix4, Matrix4),
+    Stop,
+}
+
+implement_message!(
+    CameraSyncEvent,
+    message_from_command_parser,
+    compare_and_discard
+);
+
+impl CameraSyncEvent {
+    fn compare_and_discard(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::Update(..), Self::Update(..)) | (Self::Stop, Self::Stop)
+        )
+    }
+    fn message_from_command_parser(command_parser: CommandParser) -> Option<Self> {
+        if command_parser.has("camera_sync_stop") {
+            return Some(Self::Stop);
+        }
+        if command_parser.has("camera_transform") && command_parser.has("camera_proj") {
+            let transform = command_parser.get_values_of::<String>("camera_transform");
+            let proj = command_parser.get_values_of::<String>("camera_proj");
+            if let (Some(transform), Some(proj)) = (
+                transform.first().and_then(|t| matrix_from_string(t)),
+                proj.first().and_then(|p| matrix_from_string(p)),
+            ) {
+                return Some(Self::Update(transform, proj));
+            }
+        }
+        None
+    }
+}
+
+// Matrices are sent column major as "[m00,m01,...,m33]"
+fn matrix_from_string(s: &str) -> Option<Matrix4> {
+    let values = s
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|v| v.trim().parse::<f32>())
+        .collect::<Result<Vec<f32>, _>>()
+        .ok()?;
+    if values.len() != 16 {
+        return None;
+    }
+    let mut m = [[0.; 4]; 4];
+    values.chunks(4).enumerate().for_each(|(i, column)| {
+        m[i].copy_from_slice(column);
+    });
+    Some(Matrix4::from(m))
+}
+
+#[test]
+fn test_matrix_from_string() {
+    use inox_math::{MatBase, Vector4};
+
+    // Every 4 values are a column
+    let m = matrix_from_string("[1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16]").unwrap();
+    assert_eq!(m.x, Vector4::new(1., 2., 3., 4.));
+    assert_eq!(m.y, Vector4::new(5., 6., 7., 8.));
+    assert_eq!(m.w, Vector4::new(13., 14., 15., 16.));
+    let m = matrix_from_string("[-1.5, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1]").unwrap();
+    assert_eq!(m.x.x, -1.5);
+    assert!(matrix_from_string("[1,2,3]").is_none());
+    assert!(matrix_from_string("[1,0,0,0,0,1,0,0,0,0,1,0,0,0,0,x]").is_none());
+
+    // Brackets keep negative values from being parsed as commands
+    let command_parser = CommandParser::from_string(
+        "-camera_transform [1,0,0,0,0,1,0,0,0,0,1,0,-2,0,5,1] \
+         -camera_proj [1,0,0,0,0,1,0,0,0,0,1,0,0,0,0,1]",
+    );
+    match CameraSyncEvent::message_from_command_parser(command_parser) {
+        Some(CameraSyncEvent::Update(transform, proj)) => {
+            assert_eq!(transform.w, Vector4::new(-2., 0., 5., 1.));
+            assert_eq!(proj, Matrix4::default_identity());
+        }
+        _ => panic!("Camera sync update expected"),
+    }
+    let command_parser = CommandParser::from_string("-camera_sync_stop");
+    assert!(matches!(
+        CameraSyncEvent::message_from_command_parser(command_parser),
+        Some(CameraSyncEvent::Stop)
+    ));
+}
@@ -1,3 +1,5 @@
+pub mod camera;
 pub mod widgets;
 
+pub use camera::*;
 pub use widgets::*;
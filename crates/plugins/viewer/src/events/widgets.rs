@@ -6,6 +6,7 @@ use inox_uid::Uid;
 pub enum WidgetType {
     Hierarchy,
     Gfx,
+    BuildReport,
 }
 pub enum WidgetEvent {
     Selected(Uid),
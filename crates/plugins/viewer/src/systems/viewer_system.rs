@@ -16,8 +16,8 @@ use inox_uid::generate_random_uid;
 use std::path::PathBuf;
 
 use crate::{
-    events::{WidgetEvent, WidgetType},
-    widgets::{Gfx, Hierarchy, Info, InfoParams, View3D},
+    events::{CameraSyncEvent, WidgetEvent, WidgetType},
+    widgets::{BuildReportView, Gfx, Hierarchy, Info, InfoParams, View3D},
 };
 
 #[allow(dead_code)]
@@ -40,9 +40,12 @@ pub struct ViewerSystem {
     info: Option<Info>,
     hierarchy: Option<Hierarchy>,
     graphics: Option<Gfx>,
+    build_report: Option<BuildReportView>,
     last_frame: u64,
     camera_index: u32,
     camera_speed: f32,
+    is_camera_synced: bool,
+    camera_sync: Option<(Matrix4, Matrix4)>,
 }
 
 const FORCE_USE_DEFAULT_CAMERA: bool = false;
@@ -73,7 +76,8 @@ impl System for ViewerSystem {
             .register::<MouseEvent>()
             .register::<WindowEvent>()
             .register::<SerializableResourceEvent<Scene>>()
-            .register::<WidgetEvent>();
+            .register::<WidgetEvent>()
+            .register::<CameraSyncEvent>();
     }
 
     fn run(&mut self) -> bool {
@@ -116,6 +120,7 @@ impl System for ViewerSystem {
     }
     fn uninit(&mut self) {
         self.listener
+            .unregister::<CameraSyncEvent>()
             .unregister::<WidgetEvent>()
             .unregister::<KeyEvent>()
             .unregister::<MouseEvent>()
@@ -161,6 +166,7 @@ impl ViewerSystem {
             info,
             hierarchy: None,
             graphics: None,
+            build_report: None,
             context: context.clone(),
             render_context: render_context.clone(),
             listener,
@@ -168,6 +174,8 @@ impl ViewerSystem {
             camera_index: 0,
             last_mouse_pos: Vector2::default_zero(),
             camera_speed: 1.,
+            is_camera_synced: false,
+            camera_sync: None,
         }
     }
 
@@ -449,6 +457,12 @@ impl ViewerSystem {
                     WidgetType::Gfx => {
                         self.graphics = Some(Gfx::new(&self.context, &self.render_context))
                     }
+                    // Created only once, as the report is read from file
+                    WidgetType::BuildReport => {
+                        if self.build_report.is_none() {
+                            self.build_report = Some(BuildReportView::new(&self.context));
+                        }
+                    }
                 },
                 WidgetEvent::Destroy(t) => match t {
                     WidgetType::Hierarchy => {
@@ -457,8 +471,21 @@ impl ViewerSystem {
                     WidgetType::Gfx => {
                         self.graphics = None;
                     }
+                    WidgetType::BuildReport => {
+                        self.build_report = None;
+                    }
                 },
                 _ => {}
+            })
+            .process_messages(|e: &CameraSyncEvent| match e {
+                CameraSyncEvent::Update(transform, proj) => {
+                    self.is_camera_synced = true;
+                    self.camera_sync = Some((*transform, *proj));
+                }
+                CameraSyncEvent::Stop => {
+                    self.is_camera_synced = false;
+                    self.camera_sync = None;
+                }
             });
         self
     }
@@ -477,6 +504,7 @@ impl ViewerSystem {
             } else {
                 self.camera_index = 1;
             }
+            let camera_sync = self.camera_sync.take();
             let mut index = 0;
             self.context
                 .shared_data()
@@ -485,6 +513,12 @@ impl ViewerSystem {
                     if self.camera_index == index {
                         c.set_active(true);
 
+                        // Camera transforms are view matrices, as the ones of glTF cameras
+                        if let Some((transform, proj_matrix)) = camera_sync {
+                            c.set_transform(transform.inverse())
+                                .set_projection_from_matrix(proj_matrix);
+                        }
+
                         let view_matrix = c.transform();
                         let proj_matrix = c.proj_matrix();
 
@@ -509,6 +543,23 @@ impl ViewerSystem {
                     }
                 }
             }
+            if event.code == Key::O && event.state == InputState::Released {
+                self.context
+                    .shared_data()
+                    .for_each_resource_mut(|_, c: &mut Camera| {
+                        if !c.is_active() {
+                            return;
+                        }
+                        if c.is_orthographic() {
+                            c.set_perspective();
+                        } else {
+                            // Keeps the framing of what is at the distance of the origin
+                            let distance = c.transform().translation().length().max(1.);
+                            let ymag = distance * (c.fov_in_radians().0 * 0.5).tan();
+                            c.set_orthographic(ymag);
+                        }
+                    });
+            }
 
             let mut movement = Vector3::default_zero();
             if event.code == Key::W {
@@ -525,7 +576,7 @@ impl ViewerSystem {
                 movement.y -= self.camera_speed;
             }
             movement *= self.context.global_timer().dt().as_secs_f32();
-            if movement != Vector3::default_zero() {
+            if movement != Vector3::default_zero() && !self.is_camera_synced {
                 self.context
                     .shared_data()
                     .for_each_resource_mut(|_, c: &mut Camera| {
@@ -560,7 +611,7 @@ impl ViewerSystem {
                         }
                     });
             }
-            if self.is_on_view3d {
+            if self.is_on_view3d && !self.is_camera_synced {
                 let mut rotation_angle = Vector3::default_zero();
                 rotation_angle.x = event.normalized_y - self.last_mouse_pos.y;
                 rotation_angle.y = event.normalized_x - self.last_mouse_pos.x;
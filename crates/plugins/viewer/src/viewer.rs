@@ -3,9 +3,10 @@ use std::path::PathBuf;
 use inox_core::{define_plugin, ContextRc, Plugin, SystemUID, WindowSystem};
 
 use inox_graphics::{
-    BlitPass, CommandsPass, ComputeInstancesPass, ComputePathTracingDirectPass,
-    ComputePathTracingIndirectPass, CullingPass, DebugPass, DepthFirstPass, DepthPyramidPass,
-    FinalizePass, VisibilityBufferPass, WireframePass, WIREFRAME_PASS_NAME,
+    BlitPass, CommandsPass, ComputeInstancesPass, ComputeMorphTargetsPass,
+    ComputePathTracingDirectPass, ComputePathTracingIndirectPass, ComputeSkinningPass, CullingPass,
+    DebugPass, DepthFirstPass, DepthPyramidPass, FinalizePass, VisibilityBufferPass, WireframePass,
+    WIREFRAME_PASS_NAME,
 };
 use inox_platform::Window;
 use inox_render::{
@@ -14,7 +15,7 @@ use inox_render::{
     RenderPass, Renderer, RendererRw, TextureFormat, TextureUsage, DEFAULT_HEIGHT, DEFAULT_WIDTH,
 };
 use inox_resources::ConfigBase;
-use inox_scene::{ObjectSystem, ScriptSystem};
+use inox_scene::{AnimationSystem, ObjectSystem, ScriptSystem};
 use inox_serialize::{read_from_file, SerializationType};
 use inox_ui::{UIPass, UISystem, UI_PASS_NAME};
 
@@ -79,6 +80,7 @@ impl Plugin for Viewer {
             context.remove_system(inox_core::Phases::Update, &UISystem::system_id());
         }
 
+        context.remove_system(inox_core::Phases::Update, &AnimationSystem::system_id());
         context.remove_system(inox_core::Phases::Update, &ScriptSystem::system_id());
         context.remove_system(inox_core::Phases::Update, &ObjectSystem::system_id());
 
@@ -128,6 +130,7 @@ impl Viewer {
         let viewer_system = ViewerSystem::new(context, render_context, false);
         let object_system = ObjectSystem::new(context);
         let script_system = ScriptSystem::new(context);
+        let animation_system = AnimationSystem::new(context);
 
         context.add_system(
             inox_core::Phases::Render,
@@ -140,6 +143,11 @@ impl Viewer {
             Some(&[UpdateSystem::system_id()]),
         );
 
+        context.add_system(
+            inox_core::Phases::Update,
+            animation_system,
+            Some(&[RenderingSystem::system_id()]),
+        );
         context.add_system(
             inox_core::Phases::Update,
             object_system,
@@ -233,6 +241,8 @@ impl Viewer {
     }
     fn create_render_passes(context: &ContextRc, render_context: &RenderContextRc) {
         Self::create_depth_pyramid_pass(context, render_context);
+        Self::create_morph_targets_pass(context, render_context);
+        Self::create_skinning_pass(context, render_context);
         Self::create_instances_pass(context, render_context);
         Self::create_culling_pass(context, render_context);
 
@@ -246,6 +256,14 @@ impl Viewer {
         Self::create_wireframe_pass(context, render_context, has_wireframe_support());
         Self::create_ui_pass(context, render_context, ADD_UI_PASS);
     }
+    fn create_morph_targets_pass(context: &ContextRc, render_context: &RenderContextRc) {
+        let morph_targets_pass = ComputeMorphTargetsPass::create(context, render_context);
+        render_context.add_pass(morph_targets_pass, true);
+    }
+    fn create_skinning_pass(context: &ContextRc, render_context: &RenderContextRc) {
+        let skinning_pass = ComputeSkinningPass::create(context, render_context);
+        render_context.add_pass(skinning_pass, true);
+    }
     fn create_instances_pass(context: &ContextRc, render_context: &RenderContextRc) {
         let instances_pass = ComputeInstancesPass::create(context, render_context);
         render_context.add_pass(instances_pass, true);
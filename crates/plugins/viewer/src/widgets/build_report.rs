@@ -0,0 +1,199 @@
+use std::sync::{Arc, RwLock};
+
+use inox_core::ContextRc;
+use inox_log::debug_log;
+use inox_messenger::MessageHubRc;
+use inox_resources::{
+    AssetResult, AssetStatus, BuildReport, DiagnosticSeverity, Resource, SharedDataRc,
+};
+use inox_serialize::{read_from_file, SerializationType};
+use inox_ui::{implement_widget_data, CollapsingHeader, Color32, UIWidget, Ui, Window};
+
+#[derive(Clone)]
+struct BuildReportData {
+    report: Arc<RwLock<Option<BuildReport>>>,
+    show_infos: bool,
+}
+implement_widget_data!(BuildReportData);
+
+// Diagnostics and stats of the last binarization of the platform data folder
+#[derive(Clone)]
+pub struct BuildReportView {
+    ui_page: Resource<UIWidget>,
+}
+
+impl BuildReportView {
+    pub fn new(context: &ContextRc) -> Self {
+        let data = BuildReportData {
+            report: Arc::new(RwLock::new(None)),
+            show_infos: false,
+        };
+        Self::load(&data.report);
+        Self {
+            ui_page: Self::create(context.shared_data(), context.message_hub(), data),
+        }
+    }
+
+    fn load(report: &Arc<RwLock<Option<BuildReport>>>) {
+        let report = report.clone();
+        let path = BuildReport::path();
+        if !read_from_file(
+            path.as_path(),
+            SerializationType::Json,
+            Box::new(move |data: BuildReport| {
+                *report.write().unwrap() = Some(data);
+            }),
+        ) {
+            debug_log!("Build report {:?} not found", path);
+        }
+    }
+
+    fn severity_color(severity: DiagnosticSeverity) -> Color32 {
+        match severity {
+            DiagnosticSeverity::Error => Color32::RED,
+            DiagnosticSeverity::Warning => Color32::YELLOW,
+            DiagnosticSeverity::Info => Color32::GRAY,
+        }
+    }
+
+    fn show_summary(ui: &mut Ui, report: &BuildReport) {
+        ui.label(format!(
+            "Platform {}: {} produced, {} skipped, {} failed, {} stale",
+            report.platform,
+            report.produced.len(),
+            report.skipped.len(),
+            report.failed.len(),
+            report.stale.len()
+        ));
+        ui.horizontal_wrapped(|ui| {
+            ui.colored_label(
+                Self::severity_color(DiagnosticSeverity::Error),
+                format!(
+                    "{} errors",
+                    report.count_diagnostics(DiagnosticSeverity::Error)
+                ),
+            );
+            ui.colored_label(
+                Self::severity_color(DiagnosticSeverity::Warning),
+                format!(
+                    "{} warnings",
+                    report.count_diagnostics(DiagnosticSeverity::Warning)
+                ),
+            );
+        });
+    }
+
+    fn show_diagnostics(ui: &mut Ui, report: &BuildReport, show_infos: bool) {
+        report
+            .diagnostics()
+            .filter(|d| show_infos || d.severity != DiagnosticSeverity::Info)
+            .for_each(|d| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.colored_label(
+                        Self::severity_color(d.severity),
+                        format!("{:?}", d.severity),
+                    );
+                    ui.label(format!("{} {}: {}", d.asset, d.location, d.message));
+                });
+            });
+        report.stale.iter().for_each(|s| {
+            ui.colored_label(
+                Self::severity_color(DiagnosticSeverity::Warning),
+                format!("Stale {s}"),
+            );
+        });
+    }
+
+    fn show_asset(ui: &mut Ui, result: &AssetResult) {
+        let stats = &result.stats;
+        let title = format!(
+            "{} ({}) {:?} in {} ms",
+            result.source, result.compiler, result.status, stats.time_ms
+        );
+        CollapsingHeader::new(title)
+            .default_open(false)
+            .show(ui, |ui| {
+                stats.meshes.iter().for_each(|m| {
+                    ui.label(format!(
+                        "Mesh {}: {} vertices, {} triangles, meshlets per LOD {:?}",
+                        m.name, m.vertices, m.triangles, m.meshlets_per_lod
+                    ));
+                    m.lods.iter().enumerate().for_each(|(level, lod)| {
+                        ui.label(format!(
+                            "    LOD {level}: {} meshlets, {} triangles from {} groups, error {:.6}, locked border {:.1}%",
+                            lod.meshlets,
+                            lod.triangles,
+                            lod.groups,
+                            lod.max_error,
+                            lod.locked_border_ratio * 100.
+                        ));
+                    });
+                });
+                stats.textures.iter().for_each(|t| {
+                    ui.label(format!(
+                        "Texture {}: {}x{}, {} mips, {}, {} KB",
+                        t.name,
+                        t.width,
+                        t.height,
+                        t.mips,
+                        t.format,
+                        t.bytes / 1024
+                    ));
+                });
+                result.outputs.iter().for_each(|o| {
+                    ui.label(format!("Output {o:?}"));
+                });
+            });
+    }
+
+    fn create(
+        shared_data: &SharedDataRc,
+        message_hub: &MessageHubRc,
+        data: BuildReportData,
+    ) -> Resource<UIWidget> {
+        UIWidget::register(shared_data, message_hub, data, |ui_data, ui_context| {
+            if let Some(data) = ui_data.as_any_mut().downcast_mut::<BuildReportData>() {
+                if let Some(response) = Window::new("Build report")
+                    .vscroll(true)
+                    .title_bar(true)
+                    .resizable(true)
+                    .show(ui_context, |ui| {
+                        ui.horizontal_wrapped(|ui| {
+                            if ui.button("Reload").clicked() {
+                                Self::load(&data.report);
+                            }
+                            ui.checkbox(&mut data.show_infos, "Show infos");
+                        });
+                        ui.separator();
+                        let report = data.report.read().unwrap();
+                        let Some(report) = report.as_ref() else {
+                            ui.label("No build report found");
+                            return;
+                        };
+                        Self::show_summary(ui, report);
+                        CollapsingHeader::new("Diagnostics")
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                Self::show_diagnostics(ui, report, data.show_infos);
+                            });
+                        // Skipped assets are listed only when stats of their last compilation are known
+                        CollapsingHeader::new("Assets")
+                            .default_open(false)
+                            .show(ui, |ui| {
+                                report
+                                    .results()
+                                    .filter(|r| {
+                                        r.status != AssetStatus::Skipped
+                                            || r.stats != Default::default()
+                                    })
+                                    .for_each(|r| Self::show_asset(ui, r));
+                            });
+                    })
+                {
+                    return response.response.is_pointer_button_down_on();
+                }
+            }
+            false
+        })
+    }
+}
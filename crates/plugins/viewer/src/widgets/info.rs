@@ -4,8 +4,8 @@ use inox_bvh::GPUBVHNode;
 use inox_core::ContextRc;
 use inox_graphics::CullingEvent;
 use inox_math::{
-    compute_frustum, Degrees, Frustum, Mat4Ops, MatBase, Matrix4, NewAngle, VecBase, Vector2,
-    Vector3,
+    compute_frustum, compute_orthographic_frustum, Degrees, Frustum, Mat4Ops, MatBase, Matrix4,
+    NewAngle, VecBase, Vector2, Vector3,
 };
 use inox_messenger::Listener;
 use inox_platform::{MouseEvent, MouseState, WindowEvent};
@@ -71,6 +71,7 @@ struct Data {
     use_orbit_camera: bool,
     show_hierarchy: bool,
     show_graphics: bool,
+    show_build_report: bool,
     show_tlas: bool,
     show_blas: bool,
     show_frustum: bool,
@@ -89,6 +90,7 @@ struct Data {
     far: f32,
     fov: Degrees,
     aspect_ratio: f32,
+    orthographic_size: Option<Vector2>,
     selected_object_id: ObjectId,
     meshes: HashMap<MeshId, MeshInfo>,
 }
@@ -114,6 +116,7 @@ impl Info {
             use_orbit_camera: false,
             show_hierarchy: false,
             show_graphics: false,
+            show_build_report: false,
             show_tlas: false,
             show_blas: false,
             show_frustum: false,
@@ -155,6 +158,7 @@ impl Info {
             far: 0.,
             fov: Degrees::new(0.),
             aspect_ratio: 1.,
+            orthographic_size: None,
             selected_object_id: INVALID_UID,
             meshes: HashMap::new(),
         };
@@ -278,6 +282,15 @@ impl Info {
                     .message_hub()
                     .send_event(WidgetEvent::Destroy(WidgetType::Gfx));
             }
+            if data.show_build_report {
+                self.listener
+                    .message_hub()
+                    .send_event(WidgetEvent::Create(WidgetType::BuildReport));
+            } else {
+                self.listener
+                    .message_hub()
+                    .send_event(WidgetEvent::Destroy(WidgetType::BuildReport));
+            }
             if data.show_lights {
                 Self::show_lights(data);
             }
@@ -296,15 +309,30 @@ impl Info {
                         data.proj_matrix = c.proj_matrix();
                         data.fov = c.fov_in_degrees();
                         data.aspect_ratio = c.aspect_ratio();
+                        data.orthographic_size = if c.is_orthographic() {
+                            Some(Vector2::new(c.xmag(), c.ymag()))
+                        } else {
+                            None
+                        };
                     }
                 }
-                let frustum = compute_frustum(
-                    &data.cam_matrix,
-                    data.near,
-                    data.far,
-                    data.fov,
-                    data.aspect_ratio,
-                );
+                let frustum = if let Some(size) = data.orthographic_size {
+                    compute_orthographic_frustum(
+                        &data.cam_matrix,
+                        data.near,
+                        data.far,
+                        size.x,
+                        size.y,
+                    )
+                } else {
+                    compute_frustum(
+                        &data.cam_matrix,
+                        data.near,
+                        data.far,
+                        data.fov,
+                        data.aspect_ratio,
+                    )
+                };
                 Self::show_frustum(data, &frustum);
             }
             if data.show_tlas {
@@ -502,6 +530,7 @@ impl Info {
                         ));
                         ui.checkbox(&mut data.show_hierarchy, "Hierarchy");
                         ui.checkbox(&mut data.show_graphics, "Graphics");
+                        ui.checkbox(&mut data.show_build_report, "Build report");
                         ui.checkbox(&mut data.show_lights, "Show Lights");
                         ui.checkbox(&mut data.show_tlas, "Show TLAS BVH");
                         ui.checkbox(&mut data.show_blas, "Show BLAS BVHs");
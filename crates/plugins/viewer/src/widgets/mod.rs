@@ -1,8 +1,10 @@
+pub use self::build_report::*;
 pub use self::gfx::*;
 pub use self::hierarchy::*;
 pub use self::info::*;
 pub use self::view3d::*;
 
+pub mod build_report;
 pub mod gfx;
 pub mod hierarchy;
 pub mod info;
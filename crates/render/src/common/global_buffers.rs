@@ -1,22 +1,25 @@
 use std::{
     collections::HashMap,
     sync::{
-        atomic::{AtomicU32, Ordering},
+        atomic::{AtomicBool, AtomicU32, Ordering},
         Arc, RwLock,
     },
 };
 
 use inox_bvh::{create_linearized_bvh, BVHTree, GPUBVHNode, AABB};
-use inox_math::{quantize_half, Mat4Ops, Matrix4, VecBase, Vector2, Vector3};
+use inox_math::{matrix4_to_array, quantize_half, Mat4Ops, Matrix4, VecBase, Vector2, Vector3};
 use inox_resources::{to_slice, Buffer, ResourceId};
 use inox_uid::{generate_static_uid_from_string, generate_uid_from_type, Uid};
 
 use crate::{
-    platform::has_primitive_index_support, AsBinding, ConstantDataRw, GPUInstance, GPULight,
-    GPUMaterial, GPUMesh, GPUMeshlet, GPUPrimitiveIndices, GPUTexture, GPUTransform,
+    platform::has_primitive_index_support, AsBinding, ConstantDataRw, GPUInstance, GPUJointMatrix,
+    GPULight, GPUMaterial, GPUMesh, GPUMeshlet, GPUMorph, GPUMorphDelta, GPUMorphVertex,
+    GPUMorphWeight, GPUPrimitiveIndices, GPUSkin, GPUSkinVertex, GPUTexture, GPUTransform,
     GPUVertexAttributes, GPUVertexIndices, GPUVertexPosition, Light, LightId, Material,
-    MaterialData, MaterialFlags, MaterialId, Mesh, MeshData, MeshFlags, MeshId, RenderContext,
-    TextureId, TextureType, INVALID_INDEX, MAX_LOD_LEVELS,
+    MaterialData, MaterialFlags, MaterialId, Mesh, MeshData, MeshFlags, MeshId, MeshSkinData,
+    RenderContext, TextureId, TextureType, VertexAttributeLayout, INVALID_INDEX, MAX_LOD_LEVELS,
+    MAX_TEXTURE_TRANSFORMS, TEXTURE_SLOT_COORD_SET_SHIFT, TEXTURE_SLOT_INDEX_MASK,
+    TEXTURE_SLOT_TRANSFORM_SHIFT,
 };
 
 pub const INSTANCE_DATA_ID: Uid = generate_static_uid_from_string("INSTANCE_DATA_ID");
@@ -45,6 +48,16 @@ pub type DynGPUVector = Arc<RwLock<dyn GPUDataVector>>;
 pub type DynGPUBufferMap = HashMap<Uid, DynGPUBuffer>;
 pub type DynGPUVectorMap = HashMap<Uid, DynGPUVector>;
 
+// CPU side data used to update the bounds of a skinned mesh
+#[derive(Default)]
+pub struct SkinnedMeshData {
+    pub skin: MeshSkinData,
+    // Joints influencing each meshlet, in the same order of the GPUMeshlet buffer
+    pub meshlets_joints: Vec<Vec<u32>>,
+    // First meshlet of the last lod, that is the one used to build the mesh BLAS
+    pub blas_meshlets_offset: usize,
+}
+
 //Alignment should be 4, 8, 16 or 32 bytes
 #[derive(Default)]
 pub struct GlobalBuffers {
@@ -52,6 +65,9 @@ pub struct GlobalBuffers {
     pub tlas_start_index: AtomicCounter,
     pub buffers: Arc<RwLock<DynGPUBufferMap>>,
     pub vectors: Arc<RwLock<DynGPUVectorMap>>,
+    pub skinned_meshes: Arc<RwLock<HashMap<MeshId, SkinnedMeshData>>>,
+    // Set by animated skins, the TLAS is recreated once per frame for all of them
+    is_tlas_dirty: AtomicBool,
 }
 unsafe impl Send for GlobalBuffers {}
 unsafe impl Sync for GlobalBuffers {}
@@ -318,9 +334,451 @@ impl GlobalBuffers {
             .write()
             .unwrap()
             .mark_as_dirty(render_context);
+        if mesh_data.is_skinned() {
+            self.add_skin(render_context, mesh_id, mesh_index as _, mesh_data);
+        }
+        if mesh_data.has_morph_targets() {
+            self.add_morph_targets(render_context, mesh_id, mesh_index as _, mesh_data);
+        }
         self.recreate_tlas(render_context);
         mesh_index
     }
+    fn add_skin(
+        &self,
+        render_context: &RenderContext,
+        mesh_id: &MeshId,
+        mesh_index: u32,
+        mesh_data: &MeshData,
+    ) {
+        inox_profiler::scoped_profile!("render_buffers::add_skin");
+
+        let vertices_count = mesh_data.vertex_count();
+        if mesh_data.skin.vertex_joints.len() != vertices_count * 2
+            || mesh_data.skin.vertex_weights.len() != vertices_count
+        {
+            inox_log::debug_log!("Invalid skin data for mesh {:?}", mesh_id);
+            return;
+        }
+        let skin_index = self
+            .buffer::<GPUSkin>()
+            .write()
+            .unwrap()
+            .push(
+                mesh_id,
+                GPUSkin {
+                    bind_aabb_min: mesh_data.aabb_min.into(),
+                    bind_aabb_max: mesh_data.aabb_max.into(),
+                    aabb_min: mesh_data.aabb_min.into(),
+                    aabb_max: mesh_data.aabb_max.into(),
+                    mesh_index,
+                    vertices_count: vertices_count as _,
+                    joints_offset: INVALID_INDEX as _,
+                    ..Default::default()
+                },
+            )
+            .1
+            .start;
+
+        let layout = mesh_data.vertex_layout;
+        let stride = layout.stride_in_count();
+        // Same as vertex_attribute_offset in shaders: attributes are stored in bit order
+        let layout_bits: u32 = layout.into();
+        let normal_bit: u32 = VertexAttributeLayout::HasNormal.into();
+        let normal_offset = (layout_bits & (normal_bit - 1)).count_ones() as usize;
+        let skin_vertices = (0..vertices_count)
+            .map(|i| GPUSkinVertex {
+                position: mesh_data.vertex_positions[i],
+                normal: if layout.intersects(VertexAttributeLayout::HasNormal) {
+                    mesh_data.vertex_attributes[i * stride + normal_offset]
+                } else {
+                    0
+                },
+                joints: [
+                    mesh_data.skin.vertex_joints[i * 2],
+                    mesh_data.skin.vertex_joints[i * 2 + 1],
+                ],
+                weights: mesh_data.skin.vertex_weights[i],
+                skin_index: skin_index as _,
+            })
+            .collect::<Vec<_>>();
+        let skin_vertices_offset = self
+            .buffer::<GPUSkinVertex>()
+            .write()
+            .unwrap()
+            .allocate(mesh_id, &skin_vertices)
+            .1
+            .start;
+        if let Some(skin) = self
+            .buffer::<GPUSkin>()
+            .write()
+            .unwrap()
+            .get_first_mut(mesh_id)
+        {
+            skin.skin_vertices_offset = skin_vertices_offset as _;
+        }
+        self.buffer::<GPUSkin>()
+            .write()
+            .unwrap()
+            .mark_as_dirty(render_context);
+        self.buffer::<GPUSkinVertex>()
+            .write()
+            .unwrap()
+            .mark_as_dirty(render_context);
+
+        let mut meshlets_joints = Vec::new();
+        mesh_data.meshlets.iter().for_each(|meshlets| {
+            meshlets.iter().for_each(|meshlet| {
+                let start = meshlet.indices_offset as usize;
+                let end = start + meshlet.indices_count as usize;
+                let mut joints = Vec::new();
+                mesh_data.indices[start..end].iter().for_each(|&i| {
+                    let weights = mesh_data.skin.vertex_weights(i as _);
+                    mesh_data
+                        .skin
+                        .vertex_joints(i as _)
+                        .iter()
+                        .zip(weights.iter())
+                        .for_each(|(&j, &w)| {
+                            if w > 0. && !joints.contains(&j) {
+                                joints.push(j);
+                            }
+                        });
+                });
+                meshlets_joints.push(joints);
+            });
+        });
+        let blas_meshlets_offset =
+            meshlets_joints.len() - mesh_data.meshlets.last().map_or(0, |m| m.len());
+        self.skinned_meshes.write().unwrap().insert(
+            *mesh_id,
+            SkinnedMeshData {
+                skin: MeshSkinData {
+                    joints_aabb_min: mesh_data.skin.joints_aabb_min.clone(),
+                    joints_aabb_max: mesh_data.skin.joints_aabb_max.clone(),
+                    ..Default::default()
+                },
+                meshlets_joints,
+                blas_meshlets_offset,
+            },
+        );
+    }
+    // Morphed vertices are written in the skin vertices of the mesh,
+    // so that the skinning pass deforms the morphed bind pose
+    fn add_morph_targets(
+        &self,
+        render_context: &RenderContext,
+        mesh_id: &MeshId,
+        mesh_index: u32,
+        mesh_data: &MeshData,
+    ) {
+        inox_profiler::scoped_profile!("render_buffers::add_morph_targets");
+
+        let morph_targets = &mesh_data.morph_targets;
+        let vertices_count = mesh_data.vertex_count();
+        let targets_count = morph_targets.targets_count();
+        if morph_targets.vertex_count() != vertices_count
+            || (!morph_targets.normal_deltas.is_empty()
+                && morph_targets.normal_deltas.len() != morph_targets.position_deltas.len())
+        {
+            inox_log::debug_log!("Invalid morph targets for mesh {:?}", mesh_id);
+            return;
+        }
+        let layout = mesh_data.vertex_layout;
+        let stride = layout.stride_in_count();
+        let layout_bits: u32 = layout.into();
+        let normal_bit: u32 = VertexAttributeLayout::HasNormal.into();
+        let normal_offset = (layout_bits & (normal_bit - 1)).count_ones() as usize;
+        let vertex_normal = |i: usize| {
+            if layout.intersects(VertexAttributeLayout::HasNormal) {
+                mesh_data.vertex_attributes[i * stride + normal_offset]
+            } else {
+                0
+            }
+        };
+        let skin_index = self
+            .buffer::<GPUSkin>()
+            .read()
+            .unwrap()
+            .get_first_with_index(mesh_id)
+            .map(|(_, i)| i);
+        // Meshes that are not skinned get a skin without joints:
+        // morphed vertices are always written in skin vertices
+        let skin_index = skin_index.unwrap_or_else(|| {
+            let skin_index = self
+                .buffer::<GPUSkin>()
+                .write()
+                .unwrap()
+                .push(
+                    mesh_id,
+                    GPUSkin {
+                        bind_aabb_min: mesh_data.aabb_min.into(),
+                        bind_aabb_max: mesh_data.aabb_max.into(),
+                        aabb_min: mesh_data.aabb_min.into(),
+                        aabb_max: mesh_data.aabb_max.into(),
+                        mesh_index,
+                        vertices_count: vertices_count as _,
+                        joints_offset: INVALID_INDEX as _,
+                        ..Default::default()
+                    },
+                )
+                .1
+                .start;
+            let skin_vertices = (0..vertices_count)
+                .map(|i| GPUSkinVertex {
+                    position: mesh_data.vertex_positions[i],
+                    normal: vertex_normal(i),
+                    skin_index: skin_index as _,
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>();
+            let skin_vertices_offset = self
+                .buffer::<GPUSkinVertex>()
+                .write()
+                .unwrap()
+                .allocate(mesh_id, &skin_vertices)
+                .1
+                .start;
+            if let Some(skin) = self
+                .buffer::<GPUSkin>()
+                .write()
+                .unwrap()
+                .get_first_mut(mesh_id)
+            {
+                skin.skin_vertices_offset = skin_vertices_offset as _;
+            }
+            self.buffer::<GPUSkin>()
+                .write()
+                .unwrap()
+                .mark_as_dirty(render_context);
+            self.buffer::<GPUSkinVertex>()
+                .write()
+                .unwrap()
+                .mark_as_dirty(render_context);
+            skin_index as _
+        });
+        let morph_index = self
+            .buffer::<GPUMorph>()
+            .write()
+            .unwrap()
+            .push(
+                mesh_id,
+                GPUMorph {
+                    aabb_min: mesh_data.aabb_min.into(),
+                    aabb_max: mesh_data.aabb_max.into(),
+                    mesh_index,
+                    vertices_count: vertices_count as _,
+                    targets_count: targets_count as _,
+                    skin_index,
+                    ..Default::default()
+                },
+            )
+            .1
+            .start;
+
+        let morph_vertices = (0..vertices_count)
+            .map(|i| GPUMorphVertex {
+                position: mesh_data.vertex_positions[i],
+                normal: vertex_normal(i),
+                morph_index: morph_index as _,
+            })
+            .collect::<Vec<_>>();
+        let deltas = morph_targets
+            .position_deltas
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let n = morph_targets
+                    .normal_deltas
+                    .get(i)
+                    .copied()
+                    .unwrap_or(Vector3::default_zero());
+                GPUMorphDelta {
+                    position: [p.x, p.y, p.z, 0.],
+                    normal: [n.x, n.y, n.z, 0.],
+                }
+            })
+            .collect::<Vec<_>>();
+        let weights = morph_targets
+            .weights
+            .iter()
+            .map(|w| GPUMorphWeight(*w))
+            .collect::<Vec<_>>();
+        let morph_vertices_offset = self
+            .buffer::<GPUMorphVertex>()
+            .write()
+            .unwrap()
+            .allocate(mesh_id, &morph_vertices)
+            .1
+            .start;
+        let deltas_offset = self
+            .buffer::<GPUMorphDelta>()
+            .write()
+            .unwrap()
+            .allocate(mesh_id, &deltas)
+            .1
+            .start;
+        let weights_offset = self
+            .buffer::<GPUMorphWeight>()
+            .write()
+            .unwrap()
+            .allocate(mesh_id, &weights)
+            .1
+            .start;
+        if let Some(morph) = self
+            .buffer::<GPUMorph>()
+            .write()
+            .unwrap()
+            .get_first_mut(mesh_id)
+        {
+            morph.morph_vertices_offset = morph_vertices_offset as _;
+            morph.deltas_offset = deltas_offset as _;
+            morph.weights_offset = weights_offset as _;
+        }
+        self.buffer::<GPUMorph>()
+            .write()
+            .unwrap()
+            .mark_as_dirty(render_context);
+        self.buffer::<GPUMorphVertex>()
+            .write()
+            .unwrap()
+            .mark_as_dirty(render_context);
+        self.buffer::<GPUMorphDelta>()
+            .write()
+            .unwrap()
+            .mark_as_dirty(render_context);
+        self.buffer::<GPUMorphWeight>()
+            .write()
+            .unwrap()
+            .mark_as_dirty(render_context);
+    }
+    fn update_morph_weights(&self, render_context: &RenderContext, mesh_id: &MeshId, mesh: &Mesh) {
+        inox_profiler::scoped_profile!("render_buffers::update_morph_weights");
+
+        let weights = self.buffer::<GPUMorphWeight>();
+        let mut weights = weights.write().unwrap();
+        if let Some(data) = weights.get_mut(mesh_id) {
+            data.iter_mut()
+                .zip(mesh.morph_weights())
+                .for_each(|(d, w)| d.0 = *w);
+            weights.mark_as_dirty(render_context);
+        }
+    }
+    // Joint matrices are uploaded for the skinning pass while meshlets and BLAS bounds are
+    // conservatively updated on CPU. LOD bounding spheres are left in bind pose
+    // to keep the LOD selection consistent between parent and children meshlets
+    fn update_skin(&self, render_context: &RenderContext, mesh_id: &MeshId, mesh: &Mesh) {
+        inox_profiler::scoped_profile!("render_buffers::update_skin");
+
+        let skinned_meshes = self.skinned_meshes.read().unwrap();
+        let Some(skinned_mesh) = skinned_meshes.get(mesh_id) else {
+            return;
+        };
+        let joint_matrices = mesh.joint_matrices();
+        let joints_offset = self
+            .buffer::<GPUJointMatrix>()
+            .write()
+            .unwrap()
+            .allocate(
+                mesh_id,
+                joint_matrices
+                    .iter()
+                    .map(|m| GPUJointMatrix(matrix4_to_array(*m)))
+                    .collect::<Vec<_>>()
+                    .as_slice(),
+            )
+            .1
+            .start;
+        self.buffer::<GPUJointMatrix>()
+            .write()
+            .unwrap()
+            .mark_as_dirty(render_context);
+        if let Some(skin) = self
+            .buffer::<GPUSkin>()
+            .write()
+            .unwrap()
+            .get_first_mut(mesh_id)
+        {
+            skin.joints_offset = joints_offset as _;
+            skin.aabb_min = (*mesh.min()).into();
+            skin.aabb_max = (*mesh.max()).into();
+        }
+        self.buffer::<GPUSkin>()
+            .write()
+            .unwrap()
+            .mark_as_dirty(render_context);
+
+        let mut blas_aabbs = Vec::new();
+        if let Some(meshlets) = self
+            .buffer::<GPUMeshlet>()
+            .write()
+            .unwrap()
+            .get_mut(mesh_id)
+        {
+            meshlets.iter_mut().enumerate().for_each(|(i, meshlet)| {
+                let Some(joints) = skinned_mesh.meshlets_joints.get(i) else {
+                    return;
+                };
+                let (aabb_min, aabb_max) = skinned_mesh
+                    .skin
+                    .skinned_aabb(joints.iter().map(|&j| j as usize), joint_matrices);
+                if aabb_min.x > aabb_max.x {
+                    return;
+                }
+                meshlet.aabb_min = aabb_min.into();
+                meshlet.aabb_max = aabb_max.into();
+                if i >= skinned_mesh.blas_meshlets_offset {
+                    let index = i - skinned_mesh.blas_meshlets_offset;
+                    blas_aabbs.push(AABB::create(aabb_min, aabb_max, index as _));
+                }
+            });
+        }
+        self.buffer::<GPUMeshlet>()
+            .write()
+            .unwrap()
+            .mark_as_dirty(render_context);
+
+        if blas_aabbs.is_empty() {
+            return;
+        }
+        let bvh = BVHTree::new(&blas_aabbs);
+        let linearized_bvh = create_linearized_bvh(&bvh);
+        let blas_range = self
+            .buffer::<GPUBVHNode>()
+            .write()
+            .unwrap()
+            .allocate(mesh_id, &linearized_bvh)
+            .1;
+        let blas_index = blas_range.start;
+        self.buffer::<GPUBVHNode>().write().unwrap().data_mut()[blas_range]
+            .iter_mut()
+            .for_each(|n| {
+                if n.miss >= 0 {
+                    n.miss += blas_index as i32;
+                }
+            });
+        self.buffer::<GPUBVHNode>()
+            .write()
+            .unwrap()
+            .mark_as_dirty(render_context);
+        if let Some(m) = self
+            .buffer::<GPUMesh>()
+            .write()
+            .unwrap()
+            .get_first_mut(mesh_id)
+        {
+            m.blas_index = blas_index as _;
+        }
+        self.buffer::<GPUMesh>()
+            .write()
+            .unwrap()
+            .mark_as_dirty(render_context);
+        self.is_tlas_dirty.store(true, Ordering::SeqCst);
+    }
+    pub fn update_tlas(&self, render_context: &RenderContext) {
+        if self.is_tlas_dirty.swap(false, Ordering::SeqCst) {
+            self.recreate_tlas(render_context);
+        }
+    }
     pub fn recreate_tlas(&self, render_context: &RenderContext) {
         inox_profiler::scoped_profile!("render_buffers::recreate_tlas");
         let mut meshes_aabbs = Vec::new();
@@ -375,6 +833,12 @@ impl GlobalBuffers {
     }
     pub fn change_mesh(&self, render_context: &RenderContext, mesh_id: &MeshId, mesh: &mut Mesh) {
         inox_profiler::scoped_profile!("render_buffers::change_mesh");
+        if !mesh.joint_matrices().is_empty() {
+            self.update_skin(render_context, mesh_id, mesh);
+        }
+        if !mesh.morph_weights().is_empty() {
+            self.update_morph_weights(render_context, mesh_id, mesh);
+        }
         let meshes = self.buffer::<GPUMesh>();
         let mut meshes = meshes.write().unwrap();
         if let Some(m) = meshes.get_first_mut(mesh_id) {
@@ -457,6 +921,79 @@ impl GlobalBuffers {
                 .unwrap()
                 .mark_as_dirty(render_context);
         }
+        if self
+            .skinned_meshes
+            .write()
+            .unwrap()
+            .remove(mesh_id)
+            .is_some()
+        {
+            self.buffer::<GPUSkin>().write().unwrap().remove(mesh_id);
+            self.buffer::<GPUSkin>()
+                .write()
+                .unwrap()
+                .mark_as_dirty(render_context);
+            self.buffer::<GPUSkinVertex>()
+                .write()
+                .unwrap()
+                .remove(mesh_id);
+            self.buffer::<GPUSkinVertex>()
+                .write()
+                .unwrap()
+                .mark_as_dirty(render_context);
+            self.buffer::<GPUJointMatrix>()
+                .write()
+                .unwrap()
+                .remove(mesh_id);
+            self.buffer::<GPUJointMatrix>()
+                .write()
+                .unwrap()
+                .mark_as_dirty(render_context);
+        }
+        if self.buffer::<GPUMorph>().write().unwrap().remove(mesh_id) {
+            self.buffer::<GPUMorph>()
+                .write()
+                .unwrap()
+                .mark_as_dirty(render_context);
+            if self.buffer::<GPUSkin>().write().unwrap().remove(mesh_id) {
+                self.buffer::<GPUSkin>()
+                    .write()
+                    .unwrap()
+                    .mark_as_dirty(render_context);
+                self.buffer::<GPUSkinVertex>()
+                    .write()
+                    .unwrap()
+                    .remove(mesh_id);
+                self.buffer::<GPUSkinVertex>()
+                    .write()
+                    .unwrap()
+                    .mark_as_dirty(render_context);
+            }
+            self.buffer::<GPUMorphVertex>()
+                .write()
+                .unwrap()
+                .remove(mesh_id);
+            self.buffer::<GPUMorphVertex>()
+                .write()
+                .unwrap()
+                .mark_as_dirty(render_context);
+            self.buffer::<GPUMorphDelta>()
+                .write()
+                .unwrap()
+                .remove(mesh_id);
+            self.buffer::<GPUMorphDelta>()
+                .write()
+                .unwrap()
+                .mark_as_dirty(render_context);
+            self.buffer::<GPUMorphWeight>()
+                .write()
+                .unwrap()
+                .remove(mesh_id);
+            self.buffer::<GPUMorphWeight>()
+                .write()
+                .unwrap()
+                .mark_as_dirty(render_context);
+        }
         if recreate_tlas {
             self.recreate_tlas(render_context);
         }
@@ -485,14 +1022,18 @@ impl GlobalBuffers {
             for (v, item) in textures_index_and_coord_set.iter().enumerate() {
                 let i = v & 3; // Equivalent to index % 4 (faster than modulo on power of 2)
                 let j = v >> 2; // Equivalent to index / 4 (faster than division by power of 2)
-                m.textures_index_and_coord_set[j][i] = f32::from_bits(*item);
+                let slot = f32::to_bits(m.textures_index_and_coord_set[j][i]);
+                m.textures_index_and_coord_set[j][i] =
+                    f32::from_bits((slot & !TEXTURE_SLOT_INDEX_MASK) | *item);
             }
         } else {
             let mut m = GPUMaterial::default();
             for (v, item) in textures_index_and_coord_set.iter().enumerate() {
                 let i = v & 3; // Equivalent to index % 4 (faster than modulo on power of 2)
                 let j = v >> 2; // Equivalent to index / 4 (faster than division by power of 2)
-                m.textures_index_and_coord_set[j][i] = f32::from_bits(*item);
+                let slot = f32::to_bits(m.textures_index_and_coord_set[j][i]);
+                m.textures_index_and_coord_set[j][i] =
+                    f32::from_bits((slot & !TEXTURE_SLOT_INDEX_MASK) | *item);
             }
             let index = materials.push(material_id, m).1.start;
             material.set_material_index(index as _);
@@ -509,11 +1050,37 @@ impl GlobalBuffers {
         let materials = self.buffer::<GPUMaterial>();
         let mut materials = materials.write().unwrap();
         if let Some(material) = materials.get_first_mut(material_id) {
+            // Slots with the same KHR_texture_transform share the same entry
+            let mut transforms_count = 0;
             for (v, t) in material_data.texcoords_set.iter().enumerate() {
+                let offset_and_scale: [f32; 4] = material_data.texture_offset_and_scale[v].into();
+                let rotation = material_data.texture_rotation[v];
+                let mut transform = 0;
+                if offset_and_scale != [0., 0., 1., 1.] || rotation != 0. {
+                    let index = (0..transforms_count)
+                        .find(|&k| {
+                            material.texture_offset_and_scale[k] == offset_and_scale
+                                && material.texture_rotation[k] == rotation
+                        })
+                        .unwrap_or(transforms_count);
+                    if index < MAX_TEXTURE_TRANSFORMS {
+                        material.texture_offset_and_scale[index] = offset_and_scale;
+                        material.texture_rotation[index] = rotation;
+                        transforms_count = transforms_count.max(index + 1);
+                        transform = index as u32 + 1;
+                    } else {
+                        inox_log::debug_log!(
+                            "Too many texture transforms for material {:?}",
+                            material_id
+                        );
+                    }
+                }
                 let i = v & 3; // Equivalent to index % 4 (faster than modulo on power of 2)
                 let j = v >> 2; // Equivalent to index / 4 (faster than division by power of 2)
                 let mut v = f32::to_bits(material.textures_index_and_coord_set[j][i]);
-                v |= (*t << 28) as u32;
+                v &= TEXTURE_SLOT_INDEX_MASK;
+                v |= transform << TEXTURE_SLOT_TRANSFORM_SHIFT;
+                v |= (*t as u32) << TEXTURE_SLOT_COORD_SET_SHIFT;
                 material.textures_index_and_coord_set[j][i] = f32::from_bits(v);
             }
             material.roughness_factor = material_data.roughness_factor;
@@ -532,6 +1099,18 @@ impl GlobalBuffers {
                 as u32
                 | ((quantize_half(material_data.alpha_cutoff) as u32) << 16);
             material.emissive_strength = material_data.emissive_strength;
+            material.clearcoat_factor = material_data.clearcoat_factor;
+            material.clearcoat_roughness_factor = material_data.clearcoat_roughness_factor;
+            material.sheen_color_and_roughness_factor =
+                material_data.sheen_color_and_roughness_factor.into();
+            material.iridescence_factor_ior_and_thickness_range = [
+                material_data.iridescence_factor,
+                material_data.iridescence_ior,
+                material_data.iridescence_thickness_range.x,
+                material_data.iridescence_thickness_range.y,
+            ];
+            material.anisotropy_strength = material_data.anisotropy_strength;
+            material.anisotropy_rotation = material_data.anisotropy_rotation;
             material.flags = material_data.flags.into();
             materials.mark_as_dirty(render_context);
         }
@@ -9,7 +9,7 @@ use inox_platform::Handle;
 use inox_resources::{Resource, ResourceTrait, SharedDataRc};
 
 use crate::{
-    platform::{platform_limits, required_gpu_features, setup_env},
+    platform::{enable_gpu_features, platform_limits, setup_env},
     BindingDataBuffer, BindingDataBufferRc, BufferId, BufferRef, ComputePipeline, GlobalBuffers,
     Material, Pass, RenderPass, RenderPipeline, Texture, TextureFormat, TextureHandler,
     TextureHandlerRc, TextureId, TextureUsage, DEFAULT_HEIGHT, DEFAULT_WIDTH,
@@ -185,7 +185,7 @@ impl RenderContext {
             let (device, queue) = adapter
                 .request_device(&wgpu::DeviceDescriptor {
                     label: None,
-                    required_features: required_gpu_features(),
+                    required_features: enable_gpu_features(adapter.features()),
                     required_limits: platform_limits(),
                     memory_hints: wgpu::MemoryHints::Performance,
                     trace: wgpu::Trace::Off,
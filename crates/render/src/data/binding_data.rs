@@ -3,8 +3,9 @@ use std::num::NonZeroU32;
 use inox_bitmask::bitmask;
 
 use crate::{
-    platform::required_gpu_features, AsBinding, BufferId, RenderContextRc, SamplerType,
-    ShaderStage, TextureId, MAX_TEXTURE_ATLAS_COUNT,
+    platform::{material_textures_binding_name, required_gpu_features},
+    AsBinding, BufferId, RenderContextRc, SamplerType, ShaderBinding, ShaderBindingAccess,
+    ShaderBindingType, ShaderStage, TextureId, MAX_TEXTURE_ATLAS_COUNT,
 };
 
 const DEBUG_BINDINGS: bool = false;
@@ -99,6 +100,9 @@ pub struct BindingData {
     vertex_buffers: Vec<BufferId>,
     index_buffer: Option<BufferId>,
     pass_name: String,
+    shader_bindings: Vec<ShaderBinding>,
+    reported_errors: Vec<String>,
+    has_binding_errors: bool,
     is_layout_changed: bool,
     is_data_changed: bool,
 }
@@ -116,6 +120,9 @@ impl BindingData {
             is_layout_changed: false,
             is_data_changed: false,
             pass_name: pass_name.to_string(),
+            shader_bindings: Vec::default(),
+            reported_errors: Vec::default(),
+            has_binding_errors: false,
         }
     }
     pub fn bind_groups(&self) -> &[wgpu::BindGroup] {
@@ -124,6 +131,94 @@ impl BindingData {
     pub fn bind_group_layouts(&self) -> &[wgpu::BindGroupLayout] {
         self.bind_group_layout.as_slice()
     }
+    // Bindings reflected from the shaders of the pass, needed to bind resources by name.
+    // It's set before binding every frame, so that missing bindings are checked again
+    pub fn set_shader_bindings(&mut self, bindings: Vec<ShaderBinding>) -> &mut Self {
+        if self.shader_bindings != bindings {
+            self.shader_bindings = bindings;
+            self.reported_errors.clear();
+        }
+        self.has_binding_errors = false;
+        self
+    }
+    pub fn has_binding_errors(&self) -> bool {
+        self.has_binding_errors
+    }
+    // Every error is logged only once, until the shader bindings change
+    fn add_binding_error(&mut self, error: String) {
+        self.has_binding_errors = true;
+        if !self.reported_errors.contains(&error) {
+            inox_log::debug_log!("{}", error);
+            self.reported_errors.push(error);
+        }
+    }
+    pub fn binding_info(
+        &mut self,
+        name: &str,
+        binding_types: &[ShaderBindingType],
+    ) -> Option<BindingInfo> {
+        let Some(binding) = self.shader_bindings.iter().find(|b| b.name == name) else {
+            self.add_binding_error(format!(
+                "Binding {} of {} is not declared by its shaders",
+                name, self.pass_name
+            ));
+            return None;
+        };
+        if !binding_types.contains(&binding.binding_type) {
+            let error = format!(
+                "Binding {} [{}][{}] of {} is declared as {:?} while it's bound as {:?}",
+                name,
+                binding.group,
+                binding.binding,
+                self.pass_name,
+                binding.binding_type,
+                binding_types
+            );
+            self.add_binding_error(error);
+            return None;
+        }
+        let mut flags = match binding.access {
+            ShaderBindingAccess::Read => BindingFlags::Read,
+            ShaderBindingAccess::Write => BindingFlags::Write,
+            ShaderBindingAccess::ReadWrite => BindingFlags::ReadWrite,
+        };
+        match binding.binding_type {
+            ShaderBindingType::Uniform => flags |= BindingFlags::Uniform,
+            ShaderBindingType::Storage | ShaderBindingType::StorageTexture => {
+                flags |= BindingFlags::Storage
+            }
+            _ => {}
+        }
+        Some(BindingInfo {
+            group_index: binding.group as _,
+            binding_index: binding.binding as _,
+            stage: binding.stage,
+            flags,
+            ..Default::default()
+        })
+    }
+    // Shader bindings that are not bound would make the pipeline creation fail
+    pub fn validate_shader_bindings(&mut self) -> bool {
+        let unbound = self
+            .shader_bindings
+            .iter()
+            .filter(|b| {
+                !self
+                    .bind_group_layout_entries
+                    .get(b.group as usize)
+                    .is_some_and(|entries| entries.iter().any(|e| e.binding == b.binding))
+            })
+            .map(|b| format!("{} [{}][{}]", b.name, b.group, b.binding))
+            .collect::<Vec<_>>();
+        if !unbound.is_empty() {
+            self.add_binding_error(format!(
+                "Bindings {} of {} are declared by its shaders but not bound",
+                unbound.join(", "),
+                self.pass_name
+            ));
+        }
+        !self.has_binding_errors()
+    }
     fn create_group_and_binding_index(&mut self, group_index: usize) {
         inox_profiler::scoped_profile!("binding_data::create_group_and_binding_index");
         if group_index >= self.bind_group_layout_entries.len() {
@@ -284,6 +379,25 @@ impl BindingData {
         self.add_buffer_with_id(data.buffer_id(), data, label, info)
     }
 
+    // Uniform or storage flags and access come from the shader, flags only adds other usages
+    pub fn add_buffer_by_name<T>(
+        &mut self,
+        name: &str,
+        data: &mut T,
+        label: Option<&str>,
+        flags: BindingFlags,
+    ) -> &mut Self
+    where
+        T: AsBinding,
+    {
+        let binding_types = [ShaderBindingType::Uniform, ShaderBindingType::Storage];
+        if let Some(mut info) = self.binding_info(name, &binding_types) {
+            info.flags |= flags;
+            self.add_buffer(data, label, info);
+        }
+        self
+    }
+
     fn bind_buffer(&mut self, data_id: BufferId, info: BindingInfo) -> &mut Self {
         inox_profiler::scoped_profile!("binding_data::bind_buffer");
 
@@ -357,6 +471,44 @@ impl BindingData {
         self
     }
 
+    pub fn add_default_sampler_by_name(
+        &mut self,
+        name: &str,
+        sampler_type: SamplerType,
+    ) -> &mut Self {
+        if let Some(info) = self.binding_info(name, &[ShaderBindingType::Sampler]) {
+            self.add_default_sampler(info, sampler_type);
+        }
+        self
+    }
+
+    // Binds the texture atlases to the bindings declared in texture_utils.inc
+    pub fn add_material_textures_by_name(&mut self) -> &mut Self {
+        let name = material_textures_binding_name();
+        if let Some(info) = self.binding_info(name, &[ShaderBindingType::Texture]) {
+            let array_size = self
+                .shader_bindings
+                .iter()
+                .find(|b| b.name == name)
+                .and_then(|b| b.array_size);
+            let expected_size =
+                if required_gpu_features().contains(wgpu::Features::TEXTURE_BINDING_ARRAY) {
+                    Some(MAX_TEXTURE_ATLAS_COUNT)
+                } else {
+                    None
+                };
+            if array_size != expected_size {
+                self.add_binding_error(format!(
+                    "Binding {} of {} has array size {:?} while {:?} textures are bound",
+                    name, self.pass_name, array_size, expected_size
+                ));
+            } else {
+                self.add_material_textures(info);
+            }
+        }
+        self
+    }
+
     pub fn add_material_textures(&mut self, info: BindingInfo) -> &mut Self {
         inox_profiler::scoped_profile!("binding_data::add_material_textures");
 
@@ -528,6 +680,22 @@ impl BindingData {
         self
     }
 
+    pub fn add_texture_by_name(
+        &mut self,
+        name: &str,
+        texture_id: &TextureId,
+        view_index: u32,
+    ) -> &mut Self {
+        let binding_types = [
+            ShaderBindingType::Texture,
+            ShaderBindingType::StorageTexture,
+        ];
+        if let Some(info) = self.binding_info(name, &binding_types) {
+            self.add_texture(texture_id, view_index, info);
+        }
+        self
+    }
+
     pub fn set_bind_group_layout(&mut self) {
         inox_profiler::scoped_profile!("binding_data::bind_group_layout");
 
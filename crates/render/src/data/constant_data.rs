@@ -5,7 +5,7 @@ use inox_uid::Uid;
 
 use crate::{
     declare_as_binding, AsBinding, RenderContext, DEFAULT_FOV, DEFAULT_HEIGHT, DEFAULT_WIDTH,
-    ENV_MAP_UID, LUT_PBR_CHARLIE_UID, LUT_PBR_GGX_UID,
+    ENVIRONMENT_SH_COEFFICIENTS, ENV_MAP_UID, LUT_PBR_CHARLIE_UID, LUT_PBR_GGX_UID,
 };
 
 pub const CONSTANT_DATA_FLAGS_NONE: u32 = 0;
@@ -25,6 +25,16 @@ pub const CONSTANT_DATA_FLAGS_DISPLAY_UV_0: u32 = 1 << 12;
 pub const CONSTANT_DATA_FLAGS_DISPLAY_UV_1: u32 = 1 << 13;
 pub const CONSTANT_DATA_FLAGS_DISPLAY_UV_2: u32 = 1 << 14;
 pub const CONSTANT_DATA_FLAGS_DISPLAY_UV_3: u32 = 1 << 15;
+pub const CONSTANT_DATA_FLAGS_USE_ENVIRONMENT_CUBEMAP: u32 = 1 << 16;
+
+// Keywords of the shader variants that replace the runtime checks of the lighting flags
+pub const CONSTANT_DATA_LIGHTING_KEYWORDS: [(u32, &str); 2] = [
+    (CONSTANT_DATA_FLAGS_USE_IBL, "USE_IBL"),
+    (
+        CONSTANT_DATA_FLAGS_USE_ENVIRONMENT_CUBEMAP,
+        "USE_ENVIRONMENT_CUBEMAP",
+    ),
+];
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -48,6 +58,7 @@ pub struct ConstantData {
     camera_near: f32,
     camera_far: f32,
     camera_fov: f32,
+    irradiance_sh: [[f32; 4]; ENVIRONMENT_SH_COEFFICIENTS],
 }
 
 impl Default for ConstantData {
@@ -72,6 +83,7 @@ impl Default for ConstantData {
             camera_near: 0.,
             camera_far: 0.,
             camera_fov: DEFAULT_FOV,
+            irradiance_sh: [[0.; 4]; ENVIRONMENT_SH_COEFFICIENTS],
         }
     }
 }
@@ -83,6 +95,17 @@ impl ConstantData {
     pub fn tlas_starting_index(&self) -> u32 {
         self.tlas_starting_index
     }
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+    // Keywords of the flags that are set
+    pub fn keywords(&self, flag_keywords: &[(u32, &'static str)]) -> Vec<&'static str> {
+        flag_keywords
+            .iter()
+            .filter(|(flag, _)| self.flags & flag != 0)
+            .map(|(_, keyword)| *keyword)
+            .collect()
+    }
     pub fn add_flag(&mut self, render_context: &RenderContext, flag: u32) -> &mut Self {
         if self.flags & flag == 0 {
             self.flags |= flag;
@@ -170,6 +193,29 @@ impl ConstantData {
         }
         self
     }
+    // Environment map becomes a prefiltered cubemap and irradiance comes from spherical harmonics
+    pub fn set_environment(
+        &mut self,
+        render_context: &RenderContext,
+        irradiance_sh: &[[f32; 4]; ENVIRONMENT_SH_COEFFICIENTS],
+    ) -> &mut Self {
+        self.irradiance_sh = *irradiance_sh;
+        self.flags |= CONSTANT_DATA_FLAGS_USE_ENVIRONMENT_CUBEMAP;
+        self.frame_index = 0;
+        self.mark_as_dirty(render_context);
+        self
+    }
+    // Back to the environment map sampled as equirectangular, once its environment is gone
+    pub fn clear_environment(&mut self, render_context: &RenderContext) -> &mut Self {
+        if self.flags & CONSTANT_DATA_FLAGS_USE_ENVIRONMENT_CUBEMAP == 0 {
+            return self;
+        }
+        self.irradiance_sh = [[0.; 4]; ENVIRONMENT_SH_COEFFICIENTS];
+        self.flags &= !CONSTANT_DATA_FLAGS_USE_ENVIRONMENT_CUBEMAP;
+        self.frame_index = 0;
+        self.mark_as_dirty(render_context);
+        self
+    }
     pub fn update(
         &mut self,
         render_context: &RenderContext,
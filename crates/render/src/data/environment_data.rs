@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use inox_serialize::{Deserialize, Serialize, SerializeFile};
+
+// Roughness levels stored in the prefiltered specular map, from 0 to 1
+pub const ENVIRONMENT_ROUGHNESS_LEVELS: u32 = 6;
+pub const ENVIRONMENT_SH_COEFFICIENTS: usize = 9;
+
+// Image based lighting produced by the binarizer from an equirectangular hdr image.
+// specular_map contains the GGX prefiltered cubemap faces side by side for each roughness level,
+// with levels placed one under the other and halving their size
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct EnvironmentData {
+    pub specular_map: PathBuf,
+    pub lut_pbr_ggx: PathBuf,
+    pub lut_pbr_charlie: PathBuf,
+    // L2 spherical harmonics of the irradiance, already convolved with the cosine lobe
+    pub irradiance: [[f32; 4]; ENVIRONMENT_SH_COEFFICIENTS],
+}
+
+impl Default for EnvironmentData {
+    fn default() -> Self {
+        Self {
+            specular_map: PathBuf::new(),
+            lut_pbr_ggx: PathBuf::new(),
+            lut_pbr_charlie: PathBuf::new(),
+            irradiance: [[0.; 4]; ENVIRONMENT_SH_COEFFICIENTS],
+        }
+    }
+}
+
+impl SerializeFile for EnvironmentData {
+    fn extension() -> &'static str {
+        "environment"
+    }
+}
@@ -1,9 +1,9 @@
 use inox_bitmask::bitmask;
-use inox_math::{quantize_half, MatBase, Matrix4};
+use inox_math::quantize_half;
 
 use crate::{
-    AsBinding, BufferRef, MaterialFlags, RenderContext, VertexBufferLayoutBuilder, VertexFormat,
-    INVALID_INDEX,
+    AsBinding, BufferRef, MaterialFlags, RenderContext, TextureType, VertexBufferLayoutBuilder,
+    VertexFormat, INVALID_INDEX,
 };
 
 pub const MAX_LOD_LEVELS: usize = 8;
@@ -91,10 +91,19 @@ pub struct GPUMeshlet {
     pub parent_bounding_sphere: [f32; 4],
 }
 
+// Texture index and coord set of each TextureType, packed in vec4
+pub const TEXTURE_SLOTS_VEC4_COUNT: usize = TextureType::Count as usize / 4;
+// Each texture slot packs texture index + 1, the texture transform index + 1 and the coord set
+pub const TEXTURE_SLOT_INDEX_MASK: u32 = 0x00FFFFFF;
+pub const TEXTURE_SLOT_TRANSFORM_SHIFT: u32 = 24;
+pub const TEXTURE_SLOT_COORD_SET_SHIFT: u32 = 28;
+// Different KHR_texture_transform that texture slots of a material can reference
+pub const MAX_TEXTURE_TRANSFORMS: usize = 4;
+
 #[repr(C)]
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub struct GPUMaterial {
-    pub textures_index_and_coord_set: Matrix4,
+    pub textures_index_and_coord_set: [[f32; 4]; TEXTURE_SLOTS_VEC4_COUNT],
     pub roughness_factor: f32,
     pub metallic_factor: f32,
     pub ior: f32,
@@ -110,12 +119,20 @@ pub struct GPUMaterial {
     pub normal_scale_and_alpha_cutoff: u32,
     pub occlusion_strength: f32,
     pub flags: u32,
+    pub sheen_color_and_roughness_factor: [f32; 4],
+    pub iridescence_factor_ior_and_thickness_range: [f32; 4],
+    pub clearcoat_factor: f32,
+    pub clearcoat_roughness_factor: f32,
+    pub anisotropy_strength: f32,
+    pub anisotropy_rotation: f32,
+    pub texture_offset_and_scale: [[f32; 4]; MAX_TEXTURE_TRANSFORMS],
+    pub texture_rotation: [f32; MAX_TEXTURE_TRANSFORMS],
 }
 
 impl Default for GPUMaterial {
     fn default() -> Self {
         Self {
-            textures_index_and_coord_set: Matrix4::default_zero(),
+            textures_index_and_coord_set: [[0.; 4]; TEXTURE_SLOTS_VEC4_COUNT],
             roughness_factor: 1.0,
             metallic_factor: 1.0,
             ior: 1.5,
@@ -132,6 +149,14 @@ impl Default for GPUMaterial {
                 | ((quantize_half(1.) as u32) << 16),
             occlusion_strength: 0.0,
             flags: MaterialFlags::Unlit.into(),
+            sheen_color_and_roughness_factor: [0.; 4],
+            iridescence_factor_ior_and_thickness_range: [0., 1.3, 100., 400.],
+            clearcoat_factor: 0.,
+            clearcoat_roughness_factor: 0.,
+            anisotropy_strength: 0.,
+            anisotropy_rotation: 0.,
+            texture_offset_and_scale: [[0., 0., 1., 1.]; MAX_TEXTURE_TRANSFORMS],
+            texture_rotation: [0.; MAX_TEXTURE_TRANSFORMS],
         }
     }
 }
@@ -166,6 +191,72 @@ impl GPUVertexAttributes {
     }
 }
 
+// Skinned mesh: bind pose vertices are skinned into the mesh vertices every frame,
+// quantized in the current conservative bounds of the mesh
+#[repr(C)]
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub struct GPUSkin {
+    pub bind_aabb_min: [f32; 3],
+    pub mesh_index: u32,
+    pub bind_aabb_max: [f32; 3],
+    pub vertices_count: u32,
+    pub aabb_min: [f32; 3],
+    pub skin_vertices_offset: u32,
+    pub aabb_max: [f32; 3],
+    pub joints_offset: u32,
+}
+
+#[repr(C)]
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub struct GPUSkinVertex {
+    pub position: u32, // packed like GPUVertexPosition in the bind pose bounds
+    pub normal: u32,   // packed like the normal vertex attribute
+    pub joints: [u32; 2],
+    pub weights: u32,
+    pub skin_index: u32,
+}
+
+#[repr(C)]
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub struct GPUJointMatrix(pub [[f32; 4]; 4]);
+
+// Mesh with morph targets: base vertices are morphed every frame with the current weights
+// and written in the skin vertices, and in the mesh vertices while the skin is not animated.
+// Bounds already contain the morphed vertices
+#[repr(C)]
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub struct GPUMorph {
+    pub aabb_min: [f32; 3],
+    pub mesh_index: u32,
+    pub aabb_max: [f32; 3],
+    pub vertices_count: u32,
+    pub morph_vertices_offset: u32,
+    pub deltas_offset: u32,
+    pub weights_offset: u32,
+    pub targets_count: u32,
+    pub skin_index: u32,
+    pub _padding: [u32; 3],
+}
+
+#[repr(C)]
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub struct GPUMorphVertex {
+    pub position: u32, // packed like GPUVertexPosition in the mesh bounds
+    pub normal: u32,   // packed like the normal vertex attribute
+    pub morph_index: u32,
+}
+
+#[repr(C)]
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub struct GPUMorphDelta {
+    pub position: [f32; 4],
+    pub normal: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub struct GPUMorphWeight(pub f32);
+
 #[repr(C)]
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub struct GPUTransform {
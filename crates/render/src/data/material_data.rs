@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use inox_bitmask::bitmask;
-use inox_math::{Vector3, Vector4};
+use inox_math::{Vector2, Vector3, Vector4};
 use inox_serialize::{Deserialize, Serialize, SerializeFile};
 
 use crate::TextureType;
@@ -27,6 +27,7 @@ pub enum MaterialFlags {
     AlphaModeOpaque = 1 << 12,
     AlphaModeMask = 1 << 13,
     AlphaModeBlend = 1 << 14,
+    TextureTransform = 1 << 15,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -49,6 +50,17 @@ pub struct MaterialData {
     pub occlusion_strength: f32,
     pub normal_scale: f32,
     pub alpha_cutoff: f32,
+    pub clearcoat_factor: f32,
+    pub clearcoat_roughness_factor: f32,
+    pub sheen_color_and_roughness_factor: Vector4,
+    pub iridescence_factor: f32,
+    pub iridescence_ior: f32,
+    pub iridescence_thickness_range: Vector2,
+    pub anisotropy_strength: f32,
+    pub anisotropy_rotation: f32,
+    // KHR_texture_transform of each TextureType, the texCoord override is stored in texcoords_set
+    pub texture_offset_and_scale: [Vector4; TextureType::Count as _],
+    pub texture_rotation: [f32; TextureType::Count as _],
     pub flags: MaterialFlags,
 }
 
@@ -78,6 +90,16 @@ impl Default for MaterialData {
             specular_factors: Vector4::new(1., 1., 1., 0.),
             thickness_factor: 0.,
             attenuation_color_and_distance: Vector4::new(1., 1., 1., 0.),
+            clearcoat_factor: 0.,
+            clearcoat_roughness_factor: 0.,
+            sheen_color_and_roughness_factor: Vector4::new(0., 0., 0., 0.),
+            iridescence_factor: 0.,
+            iridescence_ior: 1.3,
+            iridescence_thickness_range: Vector2::new(100., 400.),
+            anisotropy_strength: 0.,
+            anisotropy_rotation: 0.,
+            texture_offset_and_scale: [Vector4::new(0., 0., 1., 1.); TextureType::Count as _],
+            texture_rotation: [0.; TextureType::Count as _],
             flags: MaterialFlags::Unlit,
         }
     }
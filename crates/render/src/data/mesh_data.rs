@@ -2,8 +2,8 @@ use std::path::PathBuf;
 
 use inox_bvh::{create_linearized_bvh, BVHTree, GPUBVHNode, AABB};
 use inox_math::{
-    decode_unorm, pack_4_f32_to_snorm, quantize_half, quantize_snorm, quantize_unorm, VecBase,
-    Vector2, Vector3, Vector4,
+    decode_unorm, pack_4_f32_to_snorm, quantize_half, quantize_snorm, quantize_unorm, Mat4Ops,
+    Matrix4, VecBase, Vector2, Vector3, Vector4,
 };
 
 use inox_serialize::{Deserialize, Serialize, SerializeFile};
@@ -44,6 +44,117 @@ impl Default for MeshletData {
     }
 }
 
+// Skinning data of a mesh, empty when the mesh is not skinned
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct MeshSkinData {
+    pub vertex_joints: Vec<u32>, // 2 packed u32 per vertex with 4 joint indices in 16bit
+    pub vertex_weights: Vec<u32>, // 1 packed u32 per vertex with 4 weights in 255bit
+    // Bind pose bounds of the vertices influenced by each joint
+    pub joints_aabb_min: Vec<Vector3>,
+    pub joints_aabb_max: Vec<Vector3>,
+}
+
+impl MeshSkinData {
+    pub fn joints_count(&self) -> usize {
+        self.joints_aabb_min.len()
+    }
+
+    pub fn vertex_joints(&self, i: usize) -> [u32; 4] {
+        let a = self.vertex_joints[i * 2];
+        let b = self.vertex_joints[i * 2 + 1];
+        [a & 0xFFFF, a >> 16, b & 0xFFFF, b >> 16]
+    }
+
+    pub fn vertex_weights(&self, i: usize) -> [f32; 4] {
+        let w = self.vertex_weights[i];
+        [
+            decode_unorm((w >> 24) & 0xFF, 8),
+            decode_unorm((w >> 16) & 0xFF, 8),
+            decode_unorm((w >> 8) & 0xFF, 8),
+            decode_unorm(w & 0xFF, 8),
+        ]
+    }
+
+    // Conservative bounds of the skinned vertices influenced by the given joints:
+    // a skinned vertex is a weighted average of its position transformed by each of its joints,
+    // so it's always inside the union of the joint bounds transformed by their matrices
+    pub fn skinned_aabb<I>(&self, joints: I, joint_matrices: &[Matrix4]) -> (Vector3, Vector3)
+    where
+        I: Iterator<Item = usize>,
+    {
+        let mut aabb_min = Vector3::new(f32::MAX, f32::MAX, f32::MAX);
+        let mut aabb_max = Vector3::new(-f32::MAX, -f32::MAX, -f32::MAX);
+        joints.for_each(|j| {
+            if j >= joint_matrices.len() || j >= self.joints_count() {
+                return;
+            }
+            let min = self.joints_aabb_min[j];
+            let max = self.joints_aabb_max[j];
+            if min.x > max.x {
+                return;
+            }
+            (0..8).for_each(|corner| {
+                let p = Vector3::new(
+                    if corner & 1 == 0 { min.x } else { max.x },
+                    if corner & 2 == 0 { min.y } else { max.y },
+                    if corner & 4 == 0 { min.z } else { max.z },
+                );
+                let p = joint_matrices[j].rotate_point(p);
+                aabb_min = aabb_min.min(p);
+                aabb_max = aabb_max.max(p);
+            });
+        });
+        (aabb_min, aabb_max)
+    }
+}
+
+// Morph targets of a mesh, empty when the mesh has none.
+// Deltas are grouped by target: delta of vertex i for target t is at t * vertex_count + i
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct MeshMorphData {
+    pub position_deltas: Vec<Vector3>,
+    pub normal_deltas: Vec<Vector3>, // empty if targets don't change normals
+    pub weights: Vec<f32>,           // default weights, one for each target
+    // Min and max weight of each target contained by the bounds, 0..1 when missing
+    #[serde(default)]
+    pub weights_range: Vec<[f32; 2]>,
+}
+
+impl MeshMorphData {
+    pub fn targets_count(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty() || self.position_deltas.is_empty()
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        if self.weights.is_empty() {
+            0
+        } else {
+            self.position_deltas.len() / self.weights.len()
+        }
+    }
+
+    pub fn weight_range(&self, target: usize) -> [f32; 2] {
+        self.weights_range.get(target).copied().unwrap_or([0., 1.])
+    }
+
+    // Bounds of a vertex for any combination of weights in the range of each target
+    pub fn vertex_extents(&self, i: usize, p: Vector3) -> (Vector3, Vector3) {
+        let vertex_count = self.vertex_count();
+        (0..self.targets_count()).fold((p, p), |(min, max), t| {
+            let delta = self.position_deltas[t * vertex_count + i];
+            let [min_weight, max_weight] = self.weight_range(t);
+            let (a, b) = (delta * min_weight, delta * max_weight);
+            (min + a.min(b), max + a.max(b))
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(crate = "inox_serialize")]
 pub struct MeshData {
@@ -56,6 +167,10 @@ pub struct MeshData {
     pub material: PathBuf,
     pub meshlets: Vec<Vec<MeshletData>>,
     pub meshlets_bvh: Vec<Vec<GPUBVHNode>>,
+    #[serde(default)]
+    pub skin: MeshSkinData,
+    #[serde(default)]
+    pub morph_targets: MeshMorphData,
 }
 
 impl Default for MeshData {
@@ -78,6 +193,8 @@ impl Default for MeshData {
             material: PathBuf::default(),
             meshlets: vec![Vec::new()],
             meshlets_bvh: vec![Vec::new()],
+            skin: MeshSkinData::default(),
+            morph_targets: MeshMorphData::default(),
         }
     }
 }
@@ -95,11 +212,19 @@ impl MeshData {
     pub fn index_count(&self) -> usize {
         self.indices.len()
     }
+    pub fn is_skinned(&self) -> bool {
+        !self.skin.vertex_joints.is_empty()
+    }
+    pub fn has_morph_targets(&self) -> bool {
+        !self.morph_targets.is_empty()
+    }
     pub fn clear(&mut self) -> &mut Self {
         self.vertex_positions.clear();
         self.vertex_attributes.clear();
         self.meshlets.clear();
         self.indices.clear();
+        self.skin = MeshSkinData::default();
+        self.morph_targets = MeshMorphData::default();
         self
     }
 
@@ -143,6 +268,51 @@ impl MeshData {
         self.vertex_attributes.push(u | v);
     }
 
+    // Weights are normalized, bind pose bounds of the vertex are used to grow the joint bounds:
+    // they are the vertex position itself unless the vertex is moved by morph targets
+    pub fn insert_skin(
+        &mut self,
+        (p_min, p_max): (Vector3, Vector3),
+        joints: [u32; 4],
+        weights: Vector4,
+    ) {
+        let total = weights.x + weights.y + weights.z + weights.w;
+        let weights = if total > 0. {
+            weights / total
+        } else {
+            Vector4::new(1., 0., 0., 0.)
+        };
+        self.skin
+            .vertex_joints
+            .push((joints[0] & 0xFFFF) | (joints[1] << 16));
+        self.skin
+            .vertex_joints
+            .push((joints[2] & 0xFFFF) | (joints[3] << 16));
+        let w = [weights.x, weights.y, weights.z, weights.w];
+        self.skin.vertex_weights.push(
+            (quantize_unorm(w[0], 8) << 24)
+                | (quantize_unorm(w[1], 8) << 16)
+                | (quantize_unorm(w[2], 8) << 8)
+                | quantize_unorm(w[3], 8),
+        );
+        joints.iter().zip(w.iter()).for_each(|(&j, &w)| {
+            if w <= 0. {
+                return;
+            }
+            let j = j as usize;
+            if self.skin.joints_count() <= j {
+                self.skin
+                    .joints_aabb_min
+                    .resize(j + 1, Vector3::new(f32::MAX, f32::MAX, f32::MAX));
+                self.skin
+                    .joints_aabb_max
+                    .resize(j + 1, Vector3::new(-f32::MAX, -f32::MAX, -f32::MAX));
+            }
+            self.skin.joints_aabb_min[j] = self.skin.joints_aabb_min[j].min(p_min);
+            self.skin.joints_aabb_max[j] = self.skin.joints_aabb_max[j].max(p_max);
+        });
+    }
+
     pub fn add_vertex_pos_color(&mut self, p: Vector3, c: Vector4) -> &mut Self {
         debug_assert!(
             self.vertex_layout == VertexAttributeLayout::pos_color(),
@@ -208,7 +378,7 @@ impl MeshData {
         }
 
         self.aabb_min = self.aabb_min.min(mesh_data.aabb_min);
-        self.aabb_max = self.aabb_min.max(mesh_data.aabb_max);
+        self.aabb_max = self.aabb_max.max(mesh_data.aabb_max);
         let size = mesh_data.aabb_max - mesh_data.aabb_min;
         self.vertex_positions
             .reserve(self.vertex_positions.len() + mesh_data.vertex_positions.len());
@@ -225,6 +395,17 @@ impl MeshData {
         });
         self.vertex_attributes
             .append(&mut mesh_data.vertex_attributes);
+        if mesh_data.is_skinned() {
+            (0..mesh_data.vertex_count()).for_each(|i| {
+                let w = mesh_data.skin.vertex_weights(i);
+                let p = mesh_data.position(i);
+                self.insert_skin(
+                    (p, p),
+                    mesh_data.skin.vertex_joints(i),
+                    Vector4::new(w[0], w[1], w[2], w[3]),
+                );
+            });
+        }
         self.indices
             .reserve(self.indices.len() + mesh_data.indices.len());
         mesh_data
@@ -299,3 +480,29 @@ impl MeshData {
         self.vertex_attributes[index]
     }
 }
+
+#[test]
+fn test_skinned_aabb() {
+    let mut mesh_data = MeshData::default();
+    let (p0, p1) = (Vector3::new(0., 0., 0.), Vector3::new(1., 2., 3.));
+    mesh_data.insert_skin((p0, p0), [0, 1, 0, 0], Vector4::new(3., 1., 0., 0.));
+    mesh_data.insert_skin((p1, p1), [1, 0, 0, 0], Vector4::new(1., 0., 0., 0.));
+    assert!(mesh_data.is_skinned());
+    assert_eq!(mesh_data.skin.vertex_joints(0), [0, 1, 0, 0]);
+    let weights = mesh_data.skin.vertex_weights(0);
+    assert!((weights[0] - 0.75).abs() < 0.01 && (weights[1] - 0.25).abs() < 0.01);
+
+    // Joint 1 influences both vertices, joint 0 only the first one
+    let skin = &mesh_data.skin;
+    assert_eq!(skin.joints_count(), 2);
+    assert_eq!(skin.joints_aabb_max[1], Vector3::new(1., 2., 3.));
+
+    let offset = Matrix4::from_translation(Vector3::new(10., 0., 0.));
+    let identity = Matrix4::from_scale(1.);
+    let (min, max) = skin.skinned_aabb(0..2, &[identity, offset]);
+    assert_eq!(min, Vector3::new(0., 0., 0.));
+    assert_eq!(max, Vector3::new(11., 2., 3.));
+    let (min, max) = skin.skinned_aabb([1].into_iter(), &[identity, offset]);
+    assert_eq!(min, Vector3::new(10., 0., 0.));
+    assert_eq!(max, Vector3::new(11., 2., 3.));
+}
@@ -4,6 +4,7 @@ pub use binding_data::*;
 pub use compute_pass_data::*;
 pub use compute_pipeline_data::*;
 pub use constant_data::*;
+pub use environment_data::*;
 pub use gpu_data::*;
 use inox_messenger::MessageHubRc;
 use inox_resources::SharedDataRc;
@@ -20,6 +21,7 @@ pub mod binding_data;
 pub mod compute_pass_data;
 pub mod compute_pipeline_data;
 pub mod constant_data;
+pub mod environment_data;
 pub mod gpu_data;
 pub mod light_data;
 pub mod material_data;
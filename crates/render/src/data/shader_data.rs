@@ -2,7 +2,8 @@ use inox_serialize::{Deserialize, Serialize, SerializeFile};
 
 pub const SHADER_EXTENSION: &str = "shader";
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(crate = "inox_serialize")]
 pub enum ShaderStage {
     #[default]
     None,
@@ -24,12 +25,117 @@ impl From<ShaderStage> for wgpu::ShaderStages {
         }
     }
 }
+impl ShaderStage {
+    // Stage that includes both, used when a binding is visible to several entry points
+    pub fn combine(self, other: ShaderStage) -> ShaderStage {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (ShaderStage::None, stage) | (stage, ShaderStage::None) => stage,
+            (ShaderStage::Compute, _) | (_, ShaderStage::Compute) => ShaderStage::Compute,
+            _ => ShaderStage::VertexAndFragment,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(crate = "inox_serialize")]
+pub enum ShaderBindingType {
+    #[default]
+    Uniform,
+    Storage,
+    Texture,
+    StorageTexture,
+    Sampler,
+    AccelerationStructure,
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(crate = "inox_serialize")]
+pub enum ShaderBindingAccess {
+    #[default]
+    Read,
+    Write,
+    ReadWrite,
+}
 
+// A resource declared with @group and @binding, reflected from the shader when it's binarized
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct ShaderBinding {
+    pub name: String,
+    pub group: u32,
+    pub binding: u32,
+    pub binding_type: ShaderBindingType,
+    pub access: ShaderBindingAccess,
+    pub array_size: Option<u32>,
+    pub stage: ShaderStage,
+}
+
+// Adds the bindings that are not already there, sorted by group and binding.
+// Returns false when the same group and binding is declared with a different resource
+pub fn merge_shader_bindings(bindings: &mut Vec<ShaderBinding>, other: &[ShaderBinding]) -> bool {
+    let mut is_compatible = true;
+    other.iter().for_each(|b| {
+        if let Some(existing) = bindings
+            .iter_mut()
+            .find(|e| e.group == b.group && e.binding == b.binding)
+        {
+            is_compatible &= existing.name == b.name
+                && existing.binding_type == b.binding_type
+                && existing.access == b.access
+                && existing.array_size == b.array_size;
+            existing.stage = existing.stage.combine(b.stage);
+        } else {
+            bindings.push(b.clone());
+        }
+    });
+    bindings.sort_by_key(|b| (b.group, b.binding));
+    is_compatible
+}
+
+// Keyword that leaves a keyword set without any keyword defined
+pub const SHADER_NO_KEYWORD: &str = "_";
+
+// Keywords enabled in a shader variant, sorted so that the same keywords give the same key
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct ShaderVariantKey(Vec<String>);
+
+impl ShaderVariantKey {
+    pub fn new<S: AsRef<str>>(keywords: &[S]) -> Self {
+        let mut keywords = keywords
+            .iter()
+            .map(|k| k.as_ref().to_string())
+            .filter(|k| !k.is_empty() && k != SHADER_NO_KEYWORD)
+            .collect::<Vec<_>>();
+        keywords.sort();
+        keywords.dedup();
+        Self(keywords)
+    }
+    pub fn keywords(&self) -> &[String] {
+        self.0.as_slice()
+    }
+    pub fn contains(&self, keyword: &str) -> bool {
+        self.0.iter().any(|k| k == keyword)
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct ShaderVariantData {
+    pub key: ShaderVariantKey,
+    pub wgsl_code: String,
+}
+
+// The wgsl code is the default variant, compiled with the first keyword of every set
 #[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(crate = "inox_serialize")]
 pub struct ShaderData {
     pub spirv_code: Vec<u32>,
     pub wgsl_code: String,
+    pub keyword_sets: Vec<Vec<String>>,
+    pub variants: Vec<ShaderVariantData>,
+    pub bindings: Vec<ShaderBinding>,
 }
 
 impl SerializeFile for ShaderData {
@@ -37,3 +143,36 @@ impl SerializeFile for ShaderData {
         SHADER_EXTENSION
     }
 }
+
+impl ShaderData {
+    // Keeps one keyword for every set, the first one when none of the set is requested.
+    // Keywords that are not declared by the shader are ignored
+    pub fn variant_key(&self, requested: &ShaderVariantKey) -> ShaderVariantKey {
+        let keywords = self
+            .keyword_sets
+            .iter()
+            .filter_map(|set| {
+                set.iter()
+                    .find(|k| requested.contains(k))
+                    .or_else(|| set.first())
+            })
+            .collect::<Vec<_>>();
+        ShaderVariantKey::new(&keywords)
+    }
+    pub fn default_variant_key(&self) -> ShaderVariantKey {
+        self.variant_key(&ShaderVariantKey::default())
+    }
+    pub fn binding(&self, name: &str) -> Option<&ShaderBinding> {
+        self.bindings.iter().find(|b| b.name == name)
+    }
+    pub fn variant_wgsl_code(&self, key: &ShaderVariantKey) -> Option<&str> {
+        let key = self.variant_key(key);
+        if key == self.default_variant_key() {
+            return Some(self.wgsl_code.as_str());
+        }
+        self.variants
+            .iter()
+            .find(|v| v.key == key)
+            .map(|v| v.wgsl_code.as_str())
+    }
+}
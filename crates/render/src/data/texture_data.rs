@@ -3,7 +3,7 @@ use crate::print_field_size;
 use inox_bitmask::bitmask;
 use inox_math::decode_half;
 use inox_resources::ResourceTrait;
-use inox_serialize::{Deserialize, Serialize};
+use inox_serialize::{Deserialize, Serialize, SerializeFile};
 
 #[bitmask]
 pub enum TextureUsage {
@@ -34,6 +34,31 @@ pub struct TextureData {
     pub data: Option<Vec<u8>>,
 }
 
+// Binary texture written by the binarizer with every mip already encoded in its final format
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct CompiledTextureData {
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+    pub mips: Vec<Vec<u8>>,
+}
+
+impl SerializeFile for CompiledTextureData {
+    fn extension() -> &'static str {
+        "texture"
+    }
+}
+
+impl CompiledTextureData {
+    pub fn mip_size(&self, mip_level: usize) -> (u32, u32) {
+        (
+            (self.width >> mip_level).max(1),
+            (self.height >> mip_level).max(1),
+        )
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(crate = "inox_serialize")]
 pub enum TextureType {
@@ -48,12 +73,17 @@ pub enum TextureType {
     SpecularColor = 8,
     Transmission = 9,
     Thickness = 10,
-    EmptyForPadding3 = 11,
-    EmptyForPadding4 = 12,
-    EmptyForPadding5 = 13,
-    EmptyForPadding6 = 14,
-    EmptyForPadding7 = 15,
-    Count = 16,
+    Clearcoat = 11,
+    ClearcoatRoughness = 12,
+    ClearcoatNormal = 13,
+    SheenColor = 14,
+    SheenRoughness = 15,
+    Iridescence = 16,
+    IridescenceThickness = 17,
+    Anisotropy = 18,
+    // Baked irradiance in rgb and ambient occlusion in alpha
+    Lightmap = 19,
+    Count = 20,
 }
 
 impl From<TextureType> for usize {
@@ -75,16 +105,27 @@ impl From<usize> for TextureType {
             8 => TextureType::SpecularColor,
             9 => TextureType::Transmission,
             10 => TextureType::Thickness,
-            16 => TextureType::Count,
+            11 => TextureType::Clearcoat,
+            12 => TextureType::ClearcoatRoughness,
+            13 => TextureType::ClearcoatNormal,
+            14 => TextureType::SheenColor,
+            15 => TextureType::SheenRoughness,
+            16 => TextureType::Iridescence,
+            17 => TextureType::IridescenceThickness,
+            18 => TextureType::Anisotropy,
+            19 => TextureType::Lightmap,
+            20 => TextureType::Count,
             _ => panic!("Invalid TextureType value: {value}"),
         }
     }
 }
 
+pub const TEXTURE_MIPS_COUNT_SHIFT: u32 = 24;
+
 #[repr(C)]
 #[derive(Default, Debug, PartialEq, Clone, Copy)]
 pub struct GPUTexture {
-    pub texture_and_layer_index: i32, //negatives are LUT textures - 29bit + 3bit
+    pub texture_and_layer_index: i32, //negatives are LUT textures - 4bit mips + 21bit + 3bit
     pub min: u32,
     pub max: u32,
     pub size: u32,
@@ -109,10 +150,13 @@ impl GPUTexture {
         self.texture_and_layer_index.is_negative()
     }
     pub fn texture_index(&self) -> u32 {
-        self.texture_and_layer_index.unsigned_abs() >> 3
+        (self.texture_and_layer_index.unsigned_abs() >> 3) & 0x001FFFFF
     }
     pub fn layer_index(&self) -> u32 {
-        (self.texture_and_layer_index & 0x00000007) as _
+        self.texture_and_layer_index.unsigned_abs() & 0x00000007
+    }
+    pub fn mips_count(&self) -> u32 {
+        (self.texture_and_layer_index.unsigned_abs() >> TEXTURE_MIPS_COUNT_SHIFT) & 0x0000000F
     }
     pub fn total_width(&self) -> u32 {
         decode_half((self.size & 0x0000FFFF) as _) as _
@@ -333,6 +377,17 @@ pub enum TextureFormat {
 }
 
 impl TextureFormat {
+    pub fn block_dimensions(&self) -> (u32, u32) {
+        let fmt: wgpu::TextureFormat = (*self).into();
+        fmt.block_dimensions()
+    }
+    // Size in bytes of an image of this format, compressed ones are rounded up to whole blocks
+    pub fn image_size(&self, width: u32, height: u32) -> usize {
+        let fmt: wgpu::TextureFormat = (*self).into();
+        let (block_width, block_height) = fmt.block_dimensions();
+        let block_size = fmt.block_copy_size(Some(self.aspect())).unwrap_or(1);
+        (width.div_ceil(block_width) * height.div_ceil(block_height) * block_size) as usize
+    }
     pub fn aspect(&self) -> wgpu::TextureAspect {
         let fmt: wgpu::TextureFormat = (*self).into();
         if fmt.has_depth_aspect() && !fmt.has_stencil_aspect() {
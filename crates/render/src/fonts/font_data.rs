@@ -1,22 +1,77 @@
-use image::*;
-use inox_math::{Vector2, Vector4};
+use inox_math::{VecBase, Vector2, Vector3, Vector4};
 use inox_platform::DEFAULT_DPI;
-use inox_serialize::{Deserialize, Serialize};
-use std::path::Path;
-use ttf_parser::*;
+use inox_serialize::{Deserialize, Serialize, SerializeFile};
+use std::{collections::HashMap, path::Path};
+use ttf_parser::{
+    gpos::{PairAdjustment, PositioningSubtable},
+    kern, Face, GlyphId, Tag,
+};
 
-use crate::{create_quad_with_texture, Glyph, MeshData, Metrics};
+use crate::{
+    create_quad_with_texture,
+    fonts::sdf::{median, GlyphOutline},
+    FontAtlasType, Glyph, MeshData, Metrics, VertexAttributeLayout,
+};
 
-const DEFAULT_FONT_COUNT: u8 = 255;
-pub const DEFAULT_FONT_TEXTURE_SIZE: usize = 1024;
+pub const FONT_EXTENSION: &str = "font";
 //12pt = 16px = 1em = 100%
 pub const FONT_PT_TO_PIXEL: f32 = DEFAULT_DPI / (72. * 2048.);
+// Shader atlas info of textures that are not font atlases, same as FONT_ATLAS_NONE in font_utils.inc
+pub const FONT_ATLAS_NONE: u32 = u32::MAX;
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "inox_serialize", default)]
+pub struct FontOptions {
+    pub atlas_type: FontAtlasType,
+    // Pixels of an em in the atlas
+    pub glyph_size: u32,
+    // Pixels around the outlines covered by the distance field
+    pub distance_range: u32,
+    // Inclusive ranges of unicode code points of the glyphs in the atlas
+    pub unicode_ranges: Vec<[u32; 2]>,
+    pub max_atlas_size: u32,
+}
+
+impl Default for FontOptions {
+    fn default() -> Self {
+        Self {
+            atlas_type: FontAtlasType::default(),
+            glyph_size: 48,
+            distance_range: 4,
+            unicode_ranges: vec![[0x20, 0x7E], [0xA0, 0xFF]],
+            max_atlas_size: 4096,
+        }
+    }
+}
+
+// Advance to add between two characters, in em
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(crate = "inox_serialize")]
+pub struct KerningPair {
+    pub left: u32,
+    pub right: u32,
+    pub advance: f32,
+}
+
+// Glyphs are sorted by character and kerning pairs by left and right characters
 #[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(crate = "inox_serialize")]
 pub struct FontData {
     metrics: Metrics,
+    atlas_type: FontAtlasType,
+    glyph_size: f32,
+    distance_range: f32,
     glyphs: Vec<Glyph>,
+    kerning: Vec<KerningPair>,
+    atlas_width: u32,
+    atlas_height: u32,
+    atlas: Vec<u8>,
+}
+
+impl SerializeFile for FontData {
+    fn extension() -> &'static str {
+        FONT_EXTENSION
+    }
 }
 
 #[derive(Clone)]
@@ -29,9 +84,11 @@ pub struct TextData {
 }
 
 impl FontData {
-    #[inline]
-    pub fn new(filepath: &Path) -> Self {
-        FontData::new_ttf_font(filepath)
+    pub fn new(filepath: &Path, options: &FontOptions) -> Result<Self, String> {
+        let font_data = std::fs::read(filepath).map_err(|e| format!("Unable to read font: {e}"))?;
+        let face = Face::parse(font_data.as_slice(), 0)
+            .map_err(|e| format!("Unable to parse font: {e}"))?;
+        Self::from_face(&face, options)
     }
 
     pub fn add_text(
@@ -56,116 +113,411 @@ impl FontData {
     pub fn get_metrics(&self) -> &Metrics {
         &self.metrics
     }
-
     #[inline]
-    pub fn get_glyph(&self, index: usize) -> &Glyph {
-        if index >= self.glyphs.len() {
-            &self.glyphs[0]
-        } else {
-            &self.glyphs[index]
+    pub fn atlas_type(&self) -> FontAtlasType {
+        self.atlas_type
+    }
+    #[inline]
+    pub fn distance_range(&self) -> f32 {
+        self.distance_range
+    }
+    // Atlas type in the low 16 bits and distance range in the high ones, as read by font_utils.inc
+    pub fn shader_atlas_info(&self) -> u32 {
+        self.atlas_type as u32 | (self.distance_range as u32) << 16
+    }
+    #[inline]
+    pub fn atlas_size(&self) -> (u32, u32) {
+        (self.atlas_width, self.atlas_height)
+    }
+    #[inline]
+    pub fn atlas(&self) -> &[u8] {
+        self.atlas.as_slice()
+    }
+    #[inline]
+    pub fn glyphs(&self) -> &[Glyph] {
+        self.glyphs.as_slice()
+    }
+    #[inline]
+    pub fn kerning_pairs(&self) -> &[KerningPair] {
+        self.kerning.as_slice()
+    }
+
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs
+            .binary_search_by_key(&(c as u32), |g| g.character)
+            .ok()
+            .map(|i| &self.glyphs[i])
+    }
+
+    // Advance to add between two characters, in em
+    pub fn kerning(&self, left: char, right: char) -> f32 {
+        self.kerning
+            .binary_search_by_key(&(left as u32, right as u32), |k| (k.left, k.right))
+            .map_or(0., |i| self.kerning[i].advance)
+    }
+
+    // Pixels of the distance range on screen when an em is drawn with em_pixels pixels
+    pub fn screen_px_range(&self, em_pixels: f32) -> f32 {
+        (self.distance_range * em_pixels / self.glyph_size).max(1.)
+    }
+
+    // Nearest texel of the atlas
+    pub fn sample(&self, uv: Vector2) -> [u8; 4] {
+        if self.atlas.is_empty() {
+            return [0; 4];
         }
+        let x = ((uv.x * self.atlas_width as f32) as u32).min(self.atlas_width - 1);
+        let y = ((uv.y * self.atlas_height as f32) as u32).min(self.atlas_height - 1);
+        let index = ((y * self.atlas_width + x) * 4) as usize;
+        [
+            self.atlas[index],
+            self.atlas[index + 1],
+            self.atlas[index + 2],
+            self.atlas[index + 3],
+        ]
     }
 
-    pub fn create_mesh_from_text(&self, text_data: &TextData) -> MeshData {
-        let mut text_mesh_data = MeshData::default();
+    // Same as font_coverage in font_utils.inc
+    pub fn coverage(&self, texel: [u8; 4], screen_px_range: f32) -> f32 {
+        let [r, g, b, a] = texel.map(|c| c as f32 / 255.);
+        let distance = match self.atlas_type {
+            FontAtlasType::Coverage => return a,
+            FontAtlasType::Sdf => a,
+            FontAtlasType::Msdf => median(r, g, b),
+        };
+        (screen_px_range * (distance - 0.5) + 0.5).clamp(0., 1.)
+    }
 
-        let mut prev_pos = text_data.position;
-        let size = FONT_PT_TO_PIXEL * text_data.scale;
+    // Quads are placed from the top left corner of the text, with y going down
+    pub fn create_mesh_from_text(&self, text_data: &TextData) -> MeshData {
+        let em = FONT_PT_TO_PIXEL * text_data.scale;
         let spacing_x = FONT_PT_TO_PIXEL * text_data.spacing.x;
         let spacing_y = FONT_PT_TO_PIXEL * text_data.spacing.y;
+        let mut pen = text_data.position;
+        let mut previous = None;
+        let mut quads = Vec::new();
 
-        for c in text_data.text.as_bytes().iter() {
-            let g = &self.glyphs[*c as usize];
-            let mesh_data = create_quad_with_texture(
-                Vector4::new(prev_pos.x, prev_pos.y, prev_pos.x + size, prev_pos.y + size),
-                0.0,
-                g.texture_coord,
-            );
-            text_mesh_data.append_mesh_data(mesh_data, 0, false);
-            if *c == b'\n' {
-                prev_pos.x = text_data.position.x;
-                prev_pos.y += size + spacing_y;
-            } else {
-                prev_pos.x += size * 0.5 + spacing_x;
+        for c in text_data.text.chars() {
+            if c == '\n' {
+                pen.x = text_data.position.x;
+                pen.y += self.metrics.line_height * em + spacing_y;
+                previous = None;
+                continue;
+            }
+            let Some(glyph) = self.glyph(c).or_else(|| self.glyph('?')) else {
+                previous = None;
+                continue;
+            };
+            if let Some(previous) = previous {
+                pen.x += self.kerning(previous, c) * em;
             }
+            if !glyph.is_empty() {
+                let baseline = pen.y + self.metrics.ascender * em;
+                let bounds = glyph.plane_bounds;
+                quads.push((
+                    Vector4::new(
+                        pen.x + bounds.x * em,
+                        baseline - bounds.w * em,
+                        pen.x + bounds.z * em,
+                        baseline - bounds.y * em,
+                    ),
+                    glyph.texture_coord,
+                ));
+            }
+            pen.x += glyph.advance * em + spacing_x;
+            previous = Some(c);
         }
 
+        // Positions are quantized in the bounds of the whole text
+        let mut text_mesh_data = MeshData {
+            vertex_layout: VertexAttributeLayout::pos_color_normal_uv1(),
+            ..Default::default()
+        };
+        quads.iter().for_each(|(rect, _)| {
+            text_mesh_data.aabb_min = text_mesh_data
+                .aabb_min
+                .min(Vector3::new(rect.x, rect.y, 0.));
+            text_mesh_data.aabb_max = text_mesh_data
+                .aabb_max
+                .max(Vector3::new(rect.z, rect.w, 0.));
+        });
+        quads.into_iter().for_each(|(rect, texture_coord)| {
+            let mesh_data = create_quad_with_texture(rect, 0.0, texture_coord);
+            text_mesh_data.append_mesh_data(mesh_data, 0, false);
+        });
+
         text_mesh_data.set_vertex_color(text_data.color);
         text_mesh_data
     }
 }
 
 impl FontData {
-    fn new_ttf_font(filepath: &Path) -> Self {
-        let font_data = ::std::fs::read(filepath).unwrap();
+    fn from_face(face: &Face, options: &FontOptions) -> Result<Self, String> {
+        let units_per_em = face.units_per_em() as f32;
+        let glyph_size = options.glyph_size.max(1) as f32;
+        let distance_range = options.distance_range.max(1) as f32;
+        // Pixels for a font unit
+        let scale = glyph_size / units_per_em;
+        let padding = (distance_range * 0.5).ceil() + 1.;
 
-        let face = Face::parse(font_data.as_slice(), 0).unwrap();
+        let mut characters = options
+            .unicode_ranges
+            .iter()
+            .flat_map(|r| r[0]..=r[1])
+            .filter_map(char::from_u32)
+            .collect::<Vec<_>>();
+        characters.sort();
+        characters.dedup();
 
-        let mut max_glyph_metrics = Metrics::default();
-        for character in 0..DEFAULT_FONT_COUNT {
-            let metrics = Glyph::compute_metrics(character as _, &face);
-            max_glyph_metrics = max_glyph_metrics.max(&metrics);
-        }
+        let mut glyphs = Vec::new();
+        let mut outlines = Vec::new();
+        characters.iter().for_each(|c| {
+            let Some(id) = face.glyph_index(*c) else {
+                return;
+            };
+            let mut outline = GlyphOutline::default();
+            let mut glyph = Glyph {
+                character: *c as u32,
+                id: id.0,
+                advance: face.glyph_hor_advance(id).unwrap_or_default() as f32 / units_per_em,
+                plane_bounds: Vector4::new(0., 0., 0., 0.),
+                texture_coord: Vector4::new(0., 0., 0., 0.),
+            };
+            if let Some(bounds) = face.outline_glyph(id, &mut outline) {
+                if !outline.is_empty() {
+                    // Bitmap bounds in pixels, on whole pixels so that texels stay aligned
+                    glyph.plane_bounds = Vector4::new(
+                        (bounds.x_min as f32 * scale).floor() - padding,
+                        (bounds.y_min as f32 * scale).floor() - padding,
+                        (bounds.x_max as f32 * scale).ceil() + padding,
+                        (bounds.y_max as f32 * scale).ceil() + padding,
+                    ) / glyph_size;
+                    outlines.push((glyphs.len(), outline));
+                }
+            }
+            glyphs.push(glyph);
+        });
 
-        let mut glyphs: Vec<Glyph> = Vec::new();
-        for character in 0..DEFAULT_FONT_COUNT {
-            glyphs.push(Glyph::create(
-                face.glyph_index(character as _).unwrap_or_default().0,
-                &face,
-                &max_glyph_metrics,
-            ));
-        }
+        let sizes = outlines
+            .iter()
+            .map(|(i, _)| {
+                let bounds = glyphs[*i].plane_bounds * glyph_size;
+                (
+                    (bounds.z - bounds.x).round() as u32,
+                    (bounds.w - bounds.y).round() as u32,
+                )
+            })
+            .collect::<Vec<_>>();
+        let area = sizes.iter().map(|(w, h)| w * h).sum::<u32>();
+        let mut atlas_width = ((area as f32).sqrt().ceil() as u32).next_power_of_two();
+        let (positions, used_height) = loop {
+            if atlas_width > options.max_atlas_size {
+                return Err(format!(
+                    "{} glyphs don't fit in an atlas of {}x{}",
+                    sizes.len(),
+                    options.max_atlas_size,
+                    options.max_atlas_size
+                ));
+            }
+            if let Some((positions, height)) = pack_glyphs(&sizes, atlas_width) {
+                if height <= atlas_width {
+                    break (positions, height);
+                }
+            }
+            atlas_width *= 2;
+        };
+        let atlas_height = used_height.next_power_of_two();
 
-        Self {
-            metrics: max_glyph_metrics,
+        let mut atlas = vec![0u8; (atlas_width * atlas_height * 4) as usize];
+        outlines
+            .iter()
+            .zip(positions.iter().zip(sizes.iter()))
+            .for_each(|((i, outline), (&(x, y), &(width, height)))| {
+                let glyph = &mut glyphs[*i];
+                let origin =
+                    Vector2::new(glyph.plane_bounds.x, glyph.plane_bounds.w) * (glyph_size / scale);
+                let pixels = outline.render(
+                    options.atlas_type,
+                    width,
+                    height,
+                    origin,
+                    scale,
+                    distance_range,
+                );
+                pixels
+                    .chunks(width as usize)
+                    .enumerate()
+                    .for_each(|(row, line)| {
+                        let start = (((y + row as u32) * atlas_width + x) * 4) as usize;
+                        atlas[start..start + line.len() * 4]
+                            .copy_from_slice(line.concat().as_slice());
+                    });
+                glyph.texture_coord = Vector4::new(
+                    x as f32 / atlas_width as f32,
+                    y as f32 / atlas_height as f32,
+                    (x + width) as f32 / atlas_width as f32,
+                    (y + height) as f32 / atlas_height as f32,
+                );
+            });
+
+        let kerning = kerning_pairs(face, &glyphs, units_per_em);
+
+        let ascender = face.ascender() as f32 / units_per_em;
+        let descender = face.descender() as f32 / units_per_em;
+        Ok(Self {
+            metrics: Metrics {
+                ascender,
+                descender,
+                line_height: ascender - descender + face.line_gap() as f32 / units_per_em,
+            },
+            atlas_type: options.atlas_type,
+            glyph_size,
+            distance_range,
             glyphs,
-        }
+            kerning,
+            atlas_width,
+            atlas_height,
+            atlas,
+        })
     }
+}
 
-    pub fn create_texture(&mut self) -> Vec<u8> {
-        let size = DEFAULT_FONT_TEXTURE_SIZE;
-
-        let mut image = DynamicImage::new_rgba8(size as _, size as _);
+// Shelves filled from the tallest glyph. Returns the positions and the height used
+fn pack_glyphs(sizes: &[(u32, u32)], width: u32) -> Option<(Vec<(u32, u32)>, u32)> {
+    let mut order = (0..sizes.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+    let mut positions = vec![(0, 0); sizes.len()];
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+    for i in order {
+        let (w, h) = sizes[i];
+        if w > width {
+            return None;
+        }
+        if x + w > width {
+            x = 0;
+            y += shelf_height;
+            shelf_height = 0;
+        }
+        positions[i] = (x, y);
+        x += w;
+        shelf_height = shelf_height.max(h);
+    }
+    Some((positions, y + shelf_height))
+}
 
-        let num_glyphs: u32 = self.glyphs.len() as _;
-        let cell_size: u32 = (((size * size) as u32 / num_glyphs) as f64).sqrt().ceil() as u32;
+// Horizontal kerning of the glyphs in em, from the kern table or from the kern feature of GPOS.
+// Pair tables are walked instead of searching every couple of glyphs
+fn kerning_pairs(face: &Face, glyphs: &[Glyph], units_per_em: f32) -> Vec<KerningPair> {
+    let mut characters = HashMap::<u16, Vec<u32>>::new();
+    glyphs
+        .iter()
+        .for_each(|g| characters.entry(g.id).or_default().push(g.character));
+    let ids = characters.keys().map(|id| GlyphId(*id)).collect::<Vec<_>>();
 
-        let mut row: u32 = 0;
-        let mut column: u32 = 0;
-        for g in self.glyphs.iter_mut() {
-            let mut starting_x = column * cell_size;
-            if (starting_x + cell_size) > size as _ {
-                starting_x = 0;
-                column = 0;
-                row += 1;
+    // The first table with a value for a pair wins, kern before GPOS
+    let mut values = HashMap::<(u16, u16), i16>::new();
+    let mut add = |left: GlyphId, right: GlyphId, value: i16| {
+        values.entry((left.0, right.0)).or_insert(value);
+    };
+    if let Some(kern) = face.tables().kern {
+        let subtables = kern
+            .subtables
+            .into_iter()
+            .filter(|s| s.horizontal && !s.has_cross_stream);
+        for subtable in subtables {
+            match &subtable.format {
+                kern::Format::Format0(table) => {
+                    for pair in table.pairs {
+                        if characters.contains_key(&pair.left().0)
+                            && characters.contains_key(&pair.right().0)
+                        {
+                            add(pair.left(), pair.right(), pair.value);
+                        }
+                    }
+                }
+                // Class and state machine formats are only found in old fonts and are searched
+                _ => {
+                    for &left in &ids {
+                        for &right in &ids {
+                            if let Some(value) = subtable.glyphs_kerning(left, right) {
+                                add(left, right, value);
+                            }
+                        }
+                    }
+                }
             }
-            let starting_y = row * cell_size;
-            if (starting_y + cell_size) > size as _ {
-                break;
+        }
+    }
+    if let Some(gpos) = face.tables().gpos {
+        let subtables = gpos
+            .features
+            .into_iter()
+            .filter(|f| f.tag == Tag::from_bytes(b"kern"))
+            .flat_map(|f| f.lookup_indices)
+            .filter_map(|i| gpos.lookups.get(i))
+            .flat_map(|lookup| lookup.subtables.into_iter::<PositioningSubtable>());
+        for subtable in subtables {
+            match subtable {
+                // Pair sets can only be searched, so just the first glyphs they cover are tried
+                PositioningSubtable::Pair(PairAdjustment::Format1 { coverage, sets }) => {
+                    for &left in &ids {
+                        let Some(set) = coverage.get(left).and_then(|i| sets.get(i)) else {
+                            continue;
+                        };
+                        for &right in &ids {
+                            if let Some((first, _)) = set.get(right) {
+                                add(left, right, first.x_advance);
+                            }
+                        }
+                    }
+                }
+                // Values are by class, so every class couple is read once for a first glyph
+                PositioningSubtable::Pair(PairAdjustment::Format2 {
+                    coverage,
+                    classes,
+                    matrix,
+                }) => {
+                    let mut right_classes = HashMap::<u16, Vec<GlyphId>>::new();
+                    ids.iter().for_each(|&right| {
+                        right_classes
+                            .entry(classes.1.get(right))
+                            .or_default()
+                            .push(right)
+                    });
+                    for &left in &ids {
+                        if coverage.get(left).is_none() {
+                            continue;
+                        }
+                        let left_class = classes.0.get(left);
+                        for (&right_class, rights) in &right_classes {
+                            if let Some((first, _)) = matrix.get((left_class, right_class)) {
+                                rights
+                                    .iter()
+                                    .for_each(|&right| add(left, right, first.x_advance));
+                            }
+                        }
+                    }
+                }
+                _ => {}
             }
-
-            let _offset = (cell_size as f32 * (g.metrics.width / self.metrics.horizontal_offset)
-                - cell_size as f32)
-                * 0.5;
-            let x_pos: i32 =
-                (starting_x as i32 - _offset as i32).clamp(0, size as i32 - cell_size as i32);
-
-            g.render(|x, y, alpha| {
-                let v = (alpha * 255.0).round() as u8;
-                image.put_pixel(x_pos as u32 + x, starting_y + y, Rgba([v; 4]))
-            });
-
-            g.texture_coord = [
-                (starting_x) as f32 / size as f32,
-                (starting_y) as f32 / size as f32,
-                (starting_x + cell_size) as f32 / size as f32,
-                (starting_y + cell_size) as f32 / size as f32,
-            ]
-            .into();
-
-            column += 1;
         }
-
-        image.into_bytes()
     }
+
+    let mut kerning = Vec::new();
+    values
+        .into_iter()
+        .filter(|(_, value)| *value != 0)
+        .for_each(|((left, right), value)| {
+            characters[&left].iter().for_each(|l| {
+                characters[&right].iter().for_each(|r| {
+                    kerning.push(KerningPair {
+                        left: *l,
+                        right: *r,
+                        advance: value as f32 / units_per_em,
+                    })
+                })
+            })
+        });
+    kerning.sort_by_key(|k| (k.left, k.right));
+    kerning
 }
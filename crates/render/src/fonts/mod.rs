@@ -1,7 +1,7 @@
 pub use crate::fonts::font_data::*;
 pub use crate::fonts::glyph::*;
+pub use crate::fonts::sdf::FontAtlasType;
 
 pub mod font_data;
-mod geometry;
 pub mod glyph;
-mod raster;
+mod sdf;
@@ -0,0 +1,427 @@
+use inox_math::{lerp_v2, Vector2};
+use inox_serialize::{Deserialize, Serialize};
+use ttf_parser::OutlineBuilder;
+
+const QUAD_SEGMENTS: usize = 8;
+const CUBIC_SEGMENTS: usize = 12;
+// Sine of the angle between two edges over which their joint is a corner
+const CORNER_CROSS_THRESHOLD: f32 = 0.141;
+
+const RED: u8 = 1;
+const GREEN: u8 = 1 << 1;
+const BLUE: u8 = 1 << 2;
+const WHITE: u8 = RED | GREEN | BLUE;
+const CORNER_COLORS: [u8; 3] = [GREEN | BLUE, RED | BLUE, RED | GREEN];
+
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(crate = "inox_serialize")]
+pub enum FontAtlasType {
+    // Antialiased coverage at the size of the atlas
+    Coverage,
+    // Signed distance in every channel
+    Sdf,
+    // Signed distance of differently colored edges in rgb, that keeps sharp corners,
+    // and the true signed distance in alpha
+    #[default]
+    Msdf,
+}
+
+fn dot(a: Vector2, b: Vector2) -> f32 {
+    a.x * b.x + a.y * b.y
+}
+
+fn cross(a: Vector2, b: Vector2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn normalize(v: Vector2) -> Vector2 {
+    let length = dot(v, v).sqrt();
+    if length > 0. {
+        v * (1. / length)
+    } else {
+        v
+    }
+}
+
+pub fn median(a: f32, b: f32, c: f32) -> f32 {
+    a.min(b).max(a.max(b).min(c))
+}
+
+fn to_unorm(value: f32) -> u8 {
+    (value.clamp(0., 1.) * 255.).round() as u8
+}
+
+// Curves are flattened, a whole curve is still a single edge with one color
+struct Edge {
+    points: Vec<Vector2>,
+    color: u8,
+}
+
+#[derive(Clone, Copy)]
+struct EdgeDistance {
+    distance: f32,
+    // Alignment of the direction to the point with the edge, the lowest wins among equal distances
+    dot: f32,
+    pseudo_distance: f32,
+}
+
+impl EdgeDistance {
+    fn is_closer(&self, other: &Option<EdgeDistance>) -> bool {
+        match other {
+            None => true,
+            Some(other) => {
+                self.distance.abs() < other.distance.abs()
+                    || (self.distance.abs() == other.distance.abs() && self.dot < other.dot)
+            }
+        }
+    }
+}
+
+impl Edge {
+    fn start_direction(&self) -> Vector2 {
+        self.points
+            .windows(2)
+            .map(|p| p[1] - p[0])
+            .find(|d| dot(*d, *d) > 0.)
+            .unwrap_or(Vector2::new(0., 0.))
+    }
+    fn end_direction(&self) -> Vector2 {
+        self.points
+            .windows(2)
+            .rev()
+            .map(|p| p[1] - p[0])
+            .find(|d| dot(*d, *d) > 0.)
+            .unwrap_or(Vector2::new(0., 0.))
+    }
+    // Positive on the left of the edge. Beyond its ends the distance from the line that
+    // extends it is used as pseudo distance, so that channels meet with sharp corners
+    fn distance(&self, p: Vector2) -> Option<EdgeDistance> {
+        let last = self.points.len() - 2;
+        let mut closest: Option<(f32, usize, f32, Vector2)> = None;
+        self.points.windows(2).enumerate().for_each(|(i, s)| {
+            let direction = s[1] - s[0];
+            let length_squared = dot(direction, direction);
+            if length_squared <= 0. {
+                return;
+            }
+            let t = dot(p - s[0], direction) / length_squared;
+            let q = s[0] + direction * t.clamp(0., 1.);
+            let distance_squared = dot(p - q, p - q);
+            if closest.is_none_or(|c| distance_squared < c.0) {
+                closest = Some((distance_squared, i, t, q));
+            }
+        });
+        let (distance_squared, i, t, q) = closest?;
+        let start = self.points[i];
+        let direction = normalize(self.points[i + 1] - start);
+        let distance = distance_squared.sqrt();
+        let sign = if cross(direction, p - q) >= 0. {
+            1.
+        } else {
+            -1.
+        };
+        let dot = if distance > 0. {
+            dot(direction, (p - q) * (1. / distance)).abs()
+        } else {
+            0.
+        };
+        let mut pseudo_distance = sign * distance;
+        if (i == 0 && t < 0.) || (i == last && t > 1.) {
+            let pseudo = cross(direction, p - q);
+            if pseudo.abs() <= distance {
+                pseudo_distance = pseudo;
+            }
+        }
+        Some(EdgeDistance {
+            distance: sign * distance,
+            dot,
+            pseudo_distance,
+        })
+    }
+}
+
+// Outline of a glyph in font units, with edges colored for multi-channel distance fields
+pub struct GlyphOutline {
+    contours: Vec<Vec<Edge>>,
+    start: Vector2,
+    current: Vector2,
+}
+
+impl Default for GlyphOutline {
+    fn default() -> Self {
+        Self {
+            contours: Vec::new(),
+            start: Vector2::new(0., 0.),
+            current: Vector2::new(0., 0.),
+        }
+    }
+}
+
+impl OutlineBuilder for GlyphOutline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.contours.push(Vec::new());
+        self.start = Vector2::new(x, y);
+        self.current = self.start;
+    }
+    fn line_to(&mut self, x: f32, y: f32) {
+        let p = Vector2::new(x, y);
+        self.add_edge(vec![self.current, p]);
+    }
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (p0, p1, p2) = (self.current, Vector2::new(x1, y1), Vector2::new(x, y));
+        let points = (0..=QUAD_SEGMENTS)
+            .map(|i| {
+                let t = i as f32 / QUAD_SEGMENTS as f32;
+                lerp_v2(t, lerp_v2(t, p0, p1), lerp_v2(t, p1, p2))
+            })
+            .collect();
+        self.add_edge(points);
+    }
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (p0, p1, p2, p3) = (
+            self.current,
+            Vector2::new(x1, y1),
+            Vector2::new(x2, y2),
+            Vector2::new(x, y),
+        );
+        let points = (0..=CUBIC_SEGMENTS)
+            .map(|i| {
+                let t = i as f32 / CUBIC_SEGMENTS as f32;
+                let a = lerp_v2(t, lerp_v2(t, p0, p1), lerp_v2(t, p1, p2));
+                let b = lerp_v2(t, lerp_v2(t, p1, p2), lerp_v2(t, p2, p3));
+                lerp_v2(t, a, b)
+            })
+            .collect();
+        self.add_edge(points);
+    }
+    fn close(&mut self) {
+        if self.current != self.start {
+            self.add_edge(vec![self.current, self.start]);
+        }
+        if let Some(contour) = self.contours.last_mut() {
+            Self::color_edges(contour);
+        }
+    }
+}
+
+impl GlyphOutline {
+    pub fn is_empty(&self) -> bool {
+        self.contours.iter().all(|c| c.is_empty())
+    }
+
+    fn add_edge(&mut self, points: Vec<Vector2>) {
+        let end = *points.last().unwrap();
+        if end != self.current {
+            if self.contours.is_empty() {
+                self.contours.push(Vec::new());
+            }
+            self.contours.last_mut().unwrap().push(Edge {
+                points,
+                color: WHITE,
+            });
+        }
+        self.current = end;
+    }
+
+    // Color changes at every corner, so that two channels at least meet there.
+    // Smooth contours stay white and behave as a plain distance field
+    fn color_edges(edges: &mut [Edge]) {
+        if edges.is_empty() {
+            return;
+        }
+        let corners = (0..edges.len())
+            .filter(|&i| {
+                let previous =
+                    normalize(edges[(i + edges.len() - 1) % edges.len()].end_direction());
+                let next = normalize(edges[i].start_direction());
+                dot(previous, next) <= 0. || cross(previous, next).abs() > CORNER_CROSS_THRESHOLD
+            })
+            .collect::<Vec<_>>();
+        match corners.len() {
+            0 => edges.iter_mut().for_each(|e| e.color = WHITE),
+            // A teardrop is split in three parts around its corner
+            1 => {
+                let count = edges.len();
+                let colors = [CORNER_COLORS[1], WHITE, CORNER_COLORS[2]];
+                (0..count).for_each(|i| {
+                    let part = if count < 3 { i * 2 } else { 3 * i / count };
+                    edges[(corners[0] + i) % count].color = colors[part.min(2)];
+                });
+            }
+            _ => {
+                let count = edges.len();
+                let mut spline = 0;
+                (0..count).for_each(|i| {
+                    let index = (corners[0] + i) % count;
+                    if i > 0 && corners.contains(&index) {
+                        spline += 1;
+                    }
+                    // Last spline meets the first one, so it can't share its color
+                    let color_index = if spline == corners.len() - 1 && spline % 3 == 0 {
+                        1
+                    } else {
+                        spline % 3
+                    };
+                    edges[index].color = CORNER_COLORS[color_index];
+                });
+            }
+        }
+    }
+
+    // Positive when the outline is counter clockwise, as inside is on the left of edges
+    fn orientation(&self) -> f32 {
+        let area = self
+            .contours
+            .iter()
+            .flatten()
+            .flat_map(|e| e.points.windows(2))
+            .map(|s| cross(s[0], s[1]))
+            .sum::<f32>();
+        if area >= 0. {
+            1.
+        } else {
+            -1.
+        }
+    }
+
+    // Non zero fill rule
+    fn is_inside(&self, p: Vector2) -> bool {
+        let mut winding = 0;
+        self.contours
+            .iter()
+            .flatten()
+            .flat_map(|e| e.points.windows(2))
+            .for_each(|s| {
+                let side = cross(s[1] - s[0], p - s[0]);
+                if s[0].y <= p.y && s[1].y > p.y && side > 0. {
+                    winding += 1;
+                } else if s[1].y <= p.y && s[0].y > p.y && side < 0. {
+                    winding -= 1;
+                }
+            });
+        winding != 0
+    }
+
+    // Signed distances in pixels, positive inside: the true one and the one of every channel
+    fn distances(&self, p: Vector2, orientation: f32) -> (f32, [f32; 3]) {
+        let mut closest = None;
+        let mut channels = [None; 3];
+        self.contours.iter().flatten().for_each(|edge| {
+            if let Some(d) = edge.distance(p) {
+                if d.is_closer(&closest) {
+                    closest = Some(d);
+                }
+                channels.iter_mut().enumerate().for_each(|(c, channel)| {
+                    if edge.color & (1 << c) != 0 && d.is_closer(channel) {
+                        *channel = Some(d);
+                    }
+                });
+            }
+        });
+        let distance = closest.map_or(f32::MAX, |d| d.distance.abs());
+        let distance = if self.is_inside(p) {
+            distance
+        } else {
+            -distance
+        };
+        let channels = channels.map(|c| c.map_or(distance, |d| d.pseudo_distance * orientation));
+        (distance, channels)
+    }
+
+    // Rgba pixels of a bitmap whose top left corner is at origin, in font units.
+    // Distances are mapped so that distance_range pixels cover the whole 0..1 range
+    pub fn render(
+        &self,
+        atlas_type: FontAtlasType,
+        width: u32,
+        height: u32,
+        origin: Vector2,
+        scale: f32,
+        distance_range: f32,
+    ) -> Vec<[u8; 4]> {
+        let orientation = self.orientation();
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let p = Vector2::new(
+                    origin.x + (x as f32 + 0.5) / scale,
+                    origin.y - (y as f32 + 0.5) / scale,
+                );
+                let (distance, channels) = self.distances(p, orientation);
+                let distance = distance * scale;
+                let value = |d: f32| to_unorm(0.5 + d / distance_range);
+                pixels.push(match atlas_type {
+                    FontAtlasType::Coverage => [to_unorm(distance + 0.5); 4],
+                    FontAtlasType::Sdf => [value(distance); 4],
+                    FontAtlasType::Msdf => {
+                        let [r, g, b] = channels.map(|c| c * scale);
+                        // Channels that would show the wrong side fall back to the true distance
+                        if (median(r, g, b) > 0.) != (distance > 0.) {
+                            [value(distance); 4]
+                        } else {
+                            [value(r), value(g), value(b), value(distance)]
+                        }
+                    }
+                });
+            }
+        }
+        pixels
+    }
+}
+
+#[test]
+fn test_glyph_distance_field() {
+    let square = |clockwise: bool| {
+        let mut outline = GlyphOutline::default();
+        let mut points = vec![(0., 0.), (100., 0.), (100., 100.), (0., 100.)];
+        if clockwise {
+            points.reverse();
+        }
+        outline.move_to(points[0].0, points[0].1);
+        points[1..].iter().for_each(|p| outline.line_to(p.0, p.1));
+        outline.close();
+        outline
+    };
+    // 100 units are 10 pixels, with 5 pixels around
+    let origin = Vector2::new(-50., 150.);
+    for clockwise in [false, true] {
+        let outline = square(clockwise);
+        assert!(!outline.is_empty());
+        for atlas_type in [
+            FontAtlasType::Coverage,
+            FontAtlasType::Sdf,
+            FontAtlasType::Msdf,
+        ] {
+            let pixels = outline.render(atlas_type, 20, 20, origin, 0.1, 4.);
+            let pixel = |x: usize, y: usize| pixels[y * 20 + x];
+            let inside = pixel(10, 10);
+            let outside = pixel(2, 2);
+            assert!(inside.iter().all(|c| *c > 128));
+            assert!(outside.iter().all(|c| *c < 128));
+            // Half a pixel from the left edge
+            assert!(
+                (pixel(5, 10)[3] as i32 - 159).abs() <= 1 || atlas_type == FontAtlasType::Coverage
+            );
+        }
+    }
+    // Distances outside a corner are rounded in the alpha channel, the median keeps it sharp
+    let pixels = square(false).render(FontAtlasType::Msdf, 20, 20, origin, 0.1, 4.);
+    let corner = pixels[3 * 20 + 16];
+    let along_edge = pixels[3 * 20 + 10];
+    assert!(corner[3] < along_edge[3]);
+    assert_eq!(
+        median(corner[0] as f32, corner[1] as f32, corner[2] as f32),
+        along_edge[3] as f32
+    );
+
+    // Holes are outside
+    let mut outline = square(false);
+    outline.move_to(25., 25.);
+    [(25., 75.), (75., 75.), (75., 25.)]
+        .iter()
+        .for_each(|p| outline.line_to(p.0, p.1));
+    outline.close();
+    let pixels = outline.render(FontAtlasType::Msdf, 20, 20, origin, 0.1, 4.);
+    assert!(pixels[10 * 20 + 10].iter().all(|c| *c < 128));
+    assert!(pixels[6 * 20 + 6].iter().all(|c| *c > 128));
+}
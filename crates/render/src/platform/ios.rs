@@ -9,6 +9,11 @@ pub fn required_gpu_features() -> wgpu::Features {
         | wgpu::Features::CLEAR_TEXTURE
 }
 
+// Enabled only when the adapter supports them
+pub fn optional_gpu_features() -> wgpu::Features {
+    wgpu::Features::TEXTURE_COMPRESSION_ASTC
+}
+
 pub fn platform_limits() -> wgpu::Limits {
     wgpu::Limits {
         max_binding_array_elements_per_shader_stage: 256,
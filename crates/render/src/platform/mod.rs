@@ -1,3 +1,5 @@
+use std::sync::OnceLock;
+
 use inox_platform::{PlatformType, PLATFORM_TYPE_PC};
 #[cfg(target_arch = "wasm32")]
 pub use wasm::*;
@@ -31,6 +33,10 @@ pub mod ios;
 
 pub const WGPU_FIXED_ALIGNMENT: u64 = 16; // 4 bytes is min alignment for wgpu
 
+static ENABLED_GPU_FEATURES: OnceLock<wgpu::Features> = OnceLock::new();
+
+// Device features are fixed for a platform and its shaders are binarized in their own data folder,
+// so these are defines of every shader compiled for the platform and not keywords of variants
 pub fn shader_preprocessor_defs<const PLATFORM_TYPE: PlatformType>() -> Vec<String> {
     if PLATFORM_TYPE == PLATFORM_TYPE_PC {
         vec![
@@ -49,6 +55,15 @@ pub fn shader_preprocessor_defs<const PLATFORM_TYPE: PlatformType>() -> Vec<Stri
     }
 }
 
+// First binding of the material textures declared in texture_utils.inc
+pub fn material_textures_binding_name() -> &'static str {
+    if required_gpu_features().contains(wgpu::Features::TEXTURE_BINDING_ARRAY) {
+        "texture_array"
+    } else {
+        "texture_1"
+    }
+}
+
 pub fn has_wireframe_support() -> bool {
     required_gpu_features().contains(wgpu::Features::POLYGON_MODE_LINE)
 }
@@ -61,3 +76,16 @@ pub fn is_indirect_mode_count_enabled() -> bool {
 pub fn has_timestamp_queries() -> bool {
     required_gpu_features().contains(wgpu::Features::TIMESTAMP_QUERY)
 }
+
+// Required features plus the optional ones supported by the adapter, enabled once on the device
+pub fn enable_gpu_features(adapter_features: wgpu::Features) -> wgpu::Features {
+    *ENABLED_GPU_FEATURES
+        .get_or_init(|| required_gpu_features() | (adapter_features & optional_gpu_features()))
+}
+pub fn has_texture_format_support(format: wgpu::TextureFormat) -> bool {
+    let features = ENABLED_GPU_FEATURES
+        .get()
+        .copied()
+        .unwrap_or_else(required_gpu_features);
+    features.contains(format.required_features())
+}
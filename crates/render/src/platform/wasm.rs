@@ -6,6 +6,10 @@ pub fn required_gpu_features() -> wgpu::Features {
         | wgpu::Features::DEPTH32FLOAT_STENCIL8
 }
 
+pub fn optional_gpu_features() -> wgpu::Features {
+    wgpu::Features::empty()
+}
+
 pub fn platform_limits() -> wgpu::Limits {
     wgpu::Limits::default()
 }
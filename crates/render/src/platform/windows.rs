@@ -23,6 +23,11 @@ pub fn required_gpu_features() -> wgpu::Features {
     features
 }
 
+// Enabled only when the adapter supports them
+pub fn optional_gpu_features() -> wgpu::Features {
+    wgpu::Features::TEXTURE_COMPRESSION_BC
+}
+
 pub fn platform_limits() -> wgpu::Limits {
     wgpu::Limits {
         max_binding_array_elements_per_shader_stage: 256,
@@ -92,6 +92,11 @@ impl ComputePass {
         entry_point_name: Option<&str>,
     ) {
         let mut is_initialized = false;
+        // Pipelines would fail to be created with bindings that don't match their shaders
+        if !binding_data.validate_shader_bindings() {
+            self.is_initialized = is_initialized;
+            return;
+        }
         binding_data.set_bind_group_layout();
         self.pipelines.iter().for_each(|pipeline| {
             is_initialized |=
@@ -1,4 +1,7 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use inox_log::debug_log;
 use inox_messenger::MessageHubRc;
@@ -8,7 +11,10 @@ use inox_resources::{
 };
 use inox_serialize::{read_from_file, SerializationType, SerializeFile};
 
-use crate::{BindingData, ComputePipelineData, RenderContext, Shader, SHADER_ENTRY_POINT};
+use crate::{
+    BindingData, ComputePipelineData, RenderContext, Shader, ShaderBinding, ShaderVariantKey,
+    SHADER_ENTRY_POINT,
+};
 
 pub type ComputePipelineId = ResourceId;
 
@@ -17,7 +23,8 @@ pub struct ComputePipeline {
     shared_data: SharedDataRc,
     message_hub: MessageHubRc,
     shader: Handle<Shader>,
-    compute_pipeline: Option<wgpu::ComputePipeline>,
+    variant: ShaderVariantKey,
+    compute_pipelines: HashMap<ShaderVariantKey, wgpu::ComputePipeline>,
 }
 
 impl Clone for ComputePipeline {
@@ -27,18 +34,19 @@ impl Clone for ComputePipeline {
             shared_data: self.shared_data.clone(),
             message_hub: self.message_hub.clone(),
             shader: self.shader.clone(),
-            compute_pipeline: None,
+            variant: self.variant.clone(),
+            compute_pipelines: HashMap::new(),
         }
     }
 }
 
 impl ResourceTrait for ComputePipeline {
     fn invalidate(&mut self) -> &mut Self {
-        self.compute_pipeline = None;
+        self.compute_pipelines.clear();
         self
     }
     fn is_initialized(&self) -> bool {
-        self.shader.is_some() && self.compute_pipeline.is_some()
+        self.shader.is_some() && self.compute_pipelines.contains_key(&self.pipeline_key())
     }
 }
 
@@ -68,7 +76,8 @@ impl DataTypeResource for ComputePipeline {
             shared_data: shared_data.clone(),
             message_hub: message_hub.clone(),
             shader: None,
-            compute_pipeline: None,
+            variant: ShaderVariantKey::default(),
+            compute_pipelines: HashMap::new(),
         }
     }
 
@@ -91,7 +100,30 @@ impl DataTypeResource for ComputePipeline {
 
 impl ComputePipeline {
     pub fn compute_pipeline(&self) -> &wgpu::ComputePipeline {
-        self.compute_pipeline.as_ref().unwrap()
+        self.compute_pipelines.get(&self.pipeline_key()).unwrap()
+    }
+    pub fn variant(&self) -> &ShaderVariantKey {
+        &self.variant
+    }
+    // Pipelines of the variants already used are kept until the shader changes
+    pub fn set_variant(&mut self, variant: ShaderVariantKey) -> &mut Self {
+        self.variant = variant;
+        self
+    }
+    // Requested keywords that the shader doesn't declare select the same pipeline
+    fn pipeline_key(&self) -> ShaderVariantKey {
+        self.shader
+            .as_ref()
+            .map(|shader| shader.get().variant_key(&self.variant))
+            .unwrap_or_default()
+    }
+    // None until the shader data is loaded
+    pub fn bindings(&self) -> Option<Vec<ShaderBinding>> {
+        let shader = self.shader.as_ref()?.get();
+        if !shader.has_code() {
+            return None;
+        }
+        Some(shader.bindings().to_vec())
     }
     fn load_shaders(
         data: &ComputePipelineData,
@@ -115,7 +147,10 @@ impl ComputePipeline {
                 if !shader.get_mut().init(context) {
                     return false;
                 }
-                self.compute_pipeline = None;
+                self.compute_pipelines.clear();
+            }
+            if !shader.get_mut().init_variant(context, &self.variant) {
+                return false;
             }
         }
         if self.is_initialized() {
@@ -154,12 +189,18 @@ impl ComputePipeline {
                     ),
                     layout: Some(&compute_pipeline_layout),
                     cache: None,
-                    module: self.shader.as_ref().unwrap().get().module(),
+                    module: self
+                        .shader
+                        .as_ref()
+                        .unwrap()
+                        .get()
+                        .variant_module(&self.variant),
                     compilation_options: wgpu::PipelineCompilationOptions::default(),
                     entry_point: Some(entry_point_name.unwrap_or(SHADER_ENTRY_POINT)),
                 })
         };
-        self.compute_pipeline = Some(compute_pipeline);
+        self.compute_pipelines
+            .insert(self.pipeline_key(), compute_pipeline);
         true
     }
 
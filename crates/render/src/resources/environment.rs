@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+
+use inox_messenger::MessageHubRc;
+use inox_resources::{
+    DataTypeResource, Handle, OnCreateData, ResourceId, ResourceTrait, SerializableResource,
+    SharedDataRc,
+};
+use inox_serialize::{read_from_file, SerializationType, SerializeFile};
+
+use crate::{
+    EnvironmentData, Texture, ENVIRONMENT_SH_COEFFICIENTS, ENV_MAP_UID, LUT_PBR_CHARLIE_UID,
+    LUT_PBR_GGX_UID,
+};
+
+pub type EnvironmentId = ResourceId;
+
+#[derive(Clone)]
+pub struct Environment {
+    filepath: PathBuf,
+    specular_map: Handle<Texture>,
+    lut_pbr_ggx: Handle<Texture>,
+    lut_pbr_charlie: Handle<Texture>,
+    irradiance: [[f32; 4]; ENVIRONMENT_SH_COEFFICIENTS],
+}
+
+impl ResourceTrait for Environment {
+    fn is_initialized(&self) -> bool {
+        self.specular_map.is_some()
+    }
+
+    fn invalidate(&mut self) -> &mut Self {
+        self.specular_map = None;
+        self.lut_pbr_ggx = None;
+        self.lut_pbr_charlie = None;
+        self
+    }
+}
+
+impl SerializableResource for Environment {
+    fn path(&self) -> &Path {
+        self.filepath.as_path()
+    }
+
+    fn set_path(&mut self, path: &Path) -> &mut Self {
+        self.filepath = path.to_path_buf();
+        self
+    }
+
+    fn extension() -> &'static str {
+        EnvironmentData::extension()
+    }
+
+    fn deserialize_data(path: &std::path::Path, f: Box<dyn FnMut(Self::DataType) + 'static>) {
+        read_from_file::<Self::DataType>(path, SerializationType::Binary, f);
+    }
+}
+
+impl DataTypeResource for Environment {
+    type DataType = EnvironmentData;
+
+    fn new(_id: ResourceId, _shared_data: &SharedDataRc, _message_hub: &MessageHubRc) -> Self {
+        Self {
+            filepath: PathBuf::new(),
+            specular_map: None,
+            lut_pbr_ggx: None,
+            lut_pbr_charlie: None,
+            irradiance: [[0.; 4]; ENVIRONMENT_SH_COEFFICIENTS],
+        }
+    }
+
+    fn create_from_data(
+        shared_data: &SharedDataRc,
+        message_hub: &MessageHubRc,
+        id: ResourceId,
+        data: &Self::DataType,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        let mut environment = Self::new(id, shared_data, message_hub);
+        environment.irradiance = data.irradiance;
+        // Textures replace the ones used as LUT by the rendering as soon as they are loaded
+        environment.specular_map = Some(Texture::request_load(
+            shared_data,
+            message_hub,
+            data.specular_map.as_path(),
+            OnCreateData::create(|t: &mut Texture| {
+                t.mark_as_LUT(&ENV_MAP_UID);
+            }),
+        ));
+        environment.lut_pbr_ggx = Some(Texture::request_load(
+            shared_data,
+            message_hub,
+            data.lut_pbr_ggx.as_path(),
+            OnCreateData::create(|t: &mut Texture| {
+                t.mark_as_LUT(&LUT_PBR_GGX_UID);
+            }),
+        ));
+        environment.lut_pbr_charlie = Some(Texture::request_load(
+            shared_data,
+            message_hub,
+            data.lut_pbr_charlie.as_path(),
+            OnCreateData::create(|t: &mut Texture| {
+                t.mark_as_LUT(&LUT_PBR_CHARLIE_UID);
+            }),
+        ));
+        environment
+    }
+}
+
+impl Environment {
+    pub fn irradiance(&self) -> &[[f32; 4]; ENVIRONMENT_SH_COEFFICIENTS] {
+        &self.irradiance
+    }
+    pub fn specular_map(&self) -> &Handle<Texture> {
+        &self.specular_map
+    }
+}
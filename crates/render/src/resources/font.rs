@@ -1,13 +1,12 @@
-use crate::{
-    FontData, Texture, TextureData, TextureFormat, TextureUsage, DEFAULT_FONT_TEXTURE_SIZE,
-};
+use crate::{FontData, Texture, TextureData, TextureFormat, TextureUsage, FONT_EXTENSION};
 
-use inox_math::Vector4;
+use inox_math::{VecBase, Vector4};
 use inox_messenger::MessageHubRc;
 use inox_resources::{
     DataTypeResource, Handle, ResourceId, ResourceTrait, SerializableResource, SharedData,
     SharedDataRc,
 };
+use inox_serialize::{read_from_file, SerializationType};
 use inox_uid::{generate_random_uid, INVALID_UID};
 use std::path::{Path, PathBuf};
 
@@ -50,11 +49,12 @@ impl DataTypeResource for Font {
     where
         Self: Sized,
     {
-        let mut font_data = data.clone();
+        let font_data = data.clone();
+        let (width, height) = font_data.atlas_size();
         let texture_data = TextureData {
-            width: DEFAULT_FONT_TEXTURE_SIZE as _,
-            height: DEFAULT_FONT_TEXTURE_SIZE as _,
-            data: Some(font_data.create_texture()),
+            width,
+            height,
+            data: Some(font_data.atlas().to_vec()),
             format: TextureFormat::Rgba8Unorm,
             usage: TextureUsage::TextureBinding | TextureUsage::CopyDst,
             sample_count: 1,
@@ -85,10 +85,10 @@ impl SerializableResource for Font {
         self.path.as_path()
     }
     fn extension() -> &'static str {
-        "ttf"
+        FONT_EXTENSION
     }
-    fn deserialize_data(path: &Path, mut f: Box<dyn FnMut(Self::DataType) + 'static>) {
-        f(FontData::new(path));
+    fn deserialize_data(path: &Path, f: Box<dyn FnMut(Self::DataType) + 'static>) {
+        read_from_file::<Self::DataType>(path, SerializationType::Binary, f);
     }
 }
 
@@ -107,7 +107,8 @@ impl Font {
         &self.texture
     }
     pub fn glyph_texture_coord(&self, c: char) -> Vector4 {
-        let texture_coord = self.font_data.get_glyph(c as _).texture_coord;
-        texture_coord
+        self.font_data
+            .glyph(c)
+            .map_or(Vector4::default_zero(), |g| g.texture_coord)
     }
 }
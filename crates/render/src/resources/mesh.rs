@@ -1,9 +1,9 @@
 use std::path::{Path, PathBuf};
 
-use crate::{Material, MeshData, INVALID_INDEX};
+use crate::{Material, MeshData, MeshSkinData, INVALID_INDEX};
 
 use inox_bitmask::bitmask;
-use inox_math::{VecBase, Vector3};
+use inox_math::{Matrix4, VecBase, Vector3};
 use inox_messenger::MessageHubRc;
 use inox_resources::{
     DataTypeResource, DataTypeResourceEvent, Handle, Resource, ResourceEvent, ResourceId,
@@ -43,6 +43,9 @@ pub struct Mesh {
     min: Vector3,
     max: Vector3,
     mesh_index: i32,
+    skin_bounds: MeshSkinData,
+    joint_matrices: Vec<Matrix4>,
+    morph_weights: Vec<f32>,
 }
 
 impl ResourceTrait for Mesh {
@@ -89,6 +92,9 @@ impl DataTypeResource for Mesh {
             flags: MeshFlags::Visible | MeshFlags::Opaque,
             min: Vector3::default_zero(),
             max: Vector3::default_zero(),
+            skin_bounds: MeshSkinData::default(),
+            joint_matrices: Vec::new(),
+            morph_weights: Vec::new(),
         }
     }
 
@@ -151,11 +157,55 @@ impl Mesh {
         }
         self.min = mesh_data.aabb_min;
         self.max = mesh_data.aabb_max;
+        self.skin_bounds = MeshSkinData {
+            joints_aabb_min: mesh_data.skin.joints_aabb_min.clone(),
+            joints_aabb_max: mesh_data.skin.joints_aabb_max.clone(),
+            ..Default::default()
+        };
+        self.morph_weights = mesh_data.morph_targets.weights.clone();
+        self.morph_weights
+            .resize(mesh_data.morph_targets.targets_count(), 0.);
         self.message_hub
             .send_event(DataTypeResourceEvent::<Self>::Loaded(self.id, mesh_data));
         self.mark_as_dirty();
         self
     }
+    pub fn is_skinned(&self) -> bool {
+        self.skin_bounds.joints_count() > 0
+    }
+    pub fn joint_matrices(&self) -> &[Matrix4] {
+        &self.joint_matrices
+    }
+    // Joint matrices are in mesh space: bounds are updated to contain the skinned vertices
+    pub fn set_joint_matrices(&mut self, joint_matrices: &[Matrix4]) -> &mut Self {
+        if !self.is_skinned() || self.joint_matrices == joint_matrices {
+            return self;
+        }
+        self.joint_matrices = joint_matrices.to_vec();
+        let (min, max) = self
+            .skin_bounds
+            .skinned_aabb(0..self.skin_bounds.joints_count(), joint_matrices);
+        if min.x <= max.x {
+            self.min = min;
+            self.max = max;
+        }
+        self.mark_as_dirty();
+        self
+    }
+    pub fn morph_weights(&self) -> &[f32] {
+        &self.morph_weights
+    }
+    // Bounds of the mesh contain every morph target with the weights range of its data:
+    // 0..1 widened by the default and animated weights
+    pub fn set_morph_weights(&mut self, weights: &[f32]) -> &mut Self {
+        let weights = &weights[..weights.len().min(self.morph_weights.len())];
+        if self.morph_weights.starts_with(weights) {
+            return self;
+        }
+        self.morph_weights[..weights.len()].copy_from_slice(weights);
+        self.mark_as_dirty();
+        self
+    }
     pub fn flags(&self) -> &MeshFlags {
         &self.flags
     }
@@ -213,6 +213,10 @@ impl RenderPass {
         vertex_layout: Option<VertexBufferLayoutBuilder>,
         instance_layout: Option<VertexBufferLayoutBuilder>,
     ) {
+        // Pipeline would fail to be created with bindings that don't match its shaders
+        if !binding_data.validate_shader_bindings() {
+            return;
+        }
         let render_targets = render_context.texture_handler().render_targets();
 
         let mut render_formats = Vec::new();
@@ -1,4 +1,7 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use inox_log::debug_log;
 use inox_messenger::MessageHubRc;
@@ -9,9 +12,9 @@ use inox_resources::{
 use inox_serialize::{read_from_file, SerializationType, SerializeFile};
 
 use crate::{
-    BindingData, BlendFactor, RenderContext, RenderPipelineData, Shader, TextureFormat,
-    VertexBufferLayoutBuilder, FRAGMENT_SHADER_ENTRY_POINT, SHADER_ENTRY_POINT,
-    VERTEX_SHADER_ENTRY_POINT,
+    merge_shader_bindings, BindingData, BlendFactor, RenderContext, RenderPipelineData, Shader,
+    ShaderBinding, ShaderVariantKey, TextureFormat, VertexBufferLayoutBuilder,
+    FRAGMENT_SHADER_ENTRY_POINT, SHADER_ENTRY_POINT, VERTEX_SHADER_ENTRY_POINT,
 };
 
 pub type RenderPipelineId = ResourceId;
@@ -24,7 +27,8 @@ pub struct RenderPipeline {
     formats: Vec<TextureFormat>,
     vertex_shader: Handle<Shader>,
     fragment_shader: Handle<Shader>,
-    render_pipeline: Option<wgpu::RenderPipeline>,
+    variant: ShaderVariantKey,
+    render_pipelines: HashMap<ShaderVariantKey, wgpu::RenderPipeline>,
 }
 
 impl Clone for RenderPipeline {
@@ -39,7 +43,8 @@ impl Clone for RenderPipeline {
             vertex_shader: Some(vertex_shader),
             fragment_shader: Some(fragment_shader),
             formats: Vec::new(),
-            render_pipeline: None,
+            variant: self.variant.clone(),
+            render_pipelines: HashMap::new(),
         }
     }
 }
@@ -52,7 +57,7 @@ impl ResourceTrait for RenderPipeline {
     fn is_initialized(&self) -> bool {
         self.vertex_shader.is_some()
             && self.fragment_shader.is_some()
-            && self.render_pipeline.is_some()
+            && self.render_pipelines.contains_key(&self.pipeline_key())
     }
 }
 
@@ -86,7 +91,8 @@ impl DataTypeResource for RenderPipeline {
             formats: Vec::new(),
             vertex_shader: None,
             fragment_shader: None,
-            render_pipeline: None,
+            variant: ShaderVariantKey::default(),
+            render_pipelines: HashMap::new(),
         }
     }
 
@@ -115,7 +121,37 @@ impl RenderPipeline {
         &self.data
     }
     pub fn render_pipeline(&self) -> &wgpu::RenderPipeline {
-        self.render_pipeline.as_ref().unwrap()
+        self.render_pipelines.get(&self.pipeline_key()).unwrap()
+    }
+    pub fn variant(&self) -> &ShaderVariantKey {
+        &self.variant
+    }
+    // Pipelines of the variants already used are kept until shaders or formats change
+    pub fn set_variant(&mut self, variant: ShaderVariantKey) -> &mut Self {
+        self.variant = variant;
+        self
+    }
+    // Keywords of the variants used by both shaders, requested keywords that they don't
+    // declare select the same pipeline
+    fn pipeline_key(&self) -> ShaderVariantKey {
+        let keywords = [self.vertex_shader.as_ref(), self.fragment_shader.as_ref()]
+            .into_iter()
+            .flatten()
+            .flat_map(|shader| shader.get().variant_key(&self.variant).keywords().to_vec())
+            .collect::<Vec<_>>();
+        ShaderVariantKey::new(&keywords)
+    }
+    // Bindings of both shaders, None until their data is loaded
+    pub fn bindings(&self) -> Option<Vec<ShaderBinding>> {
+        let mut bindings = Vec::new();
+        for shader in [self.vertex_shader.as_ref()?, self.fragment_shader.as_ref()?] {
+            let shader = shader.get();
+            if !shader.has_code() {
+                return None;
+            }
+            merge_shader_bindings(&mut bindings, shader.bindings());
+        }
+        Some(bindings)
     }
     fn load_shaders(
         data: &RenderPipelineData,
@@ -154,18 +190,24 @@ impl RenderPipeline {
                 if !shader.get_mut().init(context) {
                     return false;
                 }
-                self.render_pipeline = None;
+                self.render_pipelines.clear();
                 self.formats = Vec::new();
             }
+            if !shader.get_mut().init_variant(context, &self.variant) {
+                return false;
+            }
         }
         if let Some(shader) = self.fragment_shader.as_ref() {
             if !shader.get().is_initialized() {
                 if !shader.get_mut().init(context) {
                     return false;
                 }
-                self.render_pipeline = None;
+                self.render_pipelines.clear();
                 self.formats = Vec::new();
             }
+            if !shader.get_mut().init_variant(context, &self.variant) {
+                return false;
+            }
         }
         let is_same_format = if render_formats.is_empty() {
             !self.formats.is_empty()
@@ -179,9 +221,12 @@ impl RenderPipeline {
                 .count();
             count == self.formats.len() && count == render_formats.len()
         };
-        if is_same_format {
+        if is_same_format && self.render_pipelines.contains_key(&self.pipeline_key()) {
             return true;
         }
+        if !is_same_format {
+            self.render_pipelines.clear();
+        }
         let pipeline_render_formats = if render_formats.is_empty() {
             if self.data.sampling_count > 1 {
                 return false;
@@ -238,7 +283,12 @@ impl RenderPipeline {
                     cache: None,
                     layout: Some(&render_pipeline_layout),
                     vertex: wgpu::VertexState {
-                        module: self.vertex_shader.as_ref().unwrap().get().module(),
+                        module: self
+                            .vertex_shader
+                            .as_ref()
+                            .unwrap()
+                            .get()
+                            .variant_module(&self.variant),
                         compilation_options: wgpu::PipelineCompilationOptions::default(),
                         entry_point: if self.data.vertex_shader == self.data.fragment_shader {
                             Some(VERTEX_SHADER_ENTRY_POINT)
@@ -248,7 +298,12 @@ impl RenderPipeline {
                         buffers: vertex_state_buffers.as_slice(),
                     },
                     fragment: Some(wgpu::FragmentState {
-                        module: self.fragment_shader.as_ref().unwrap().get().module(),
+                        module: self
+                            .fragment_shader
+                            .as_ref()
+                            .unwrap()
+                            .get()
+                            .variant_module(&self.variant),
                         compilation_options: wgpu::PipelineCompilationOptions::default(),
                         entry_point: if self.data.vertex_shader == self.data.fragment_shader {
                             Some(FRAGMENT_SHADER_ENTRY_POINT)
@@ -313,7 +368,8 @@ impl RenderPipeline {
                 })
         };
         self.formats = pipeline_render_formats.iter().map(|&f| f.into()).collect();
-        self.render_pipeline = Some(render_pipeline);
+        self.render_pipelines
+            .insert(self.pipeline_key(), render_pipeline);
         true
     }
 
@@ -1,4 +1,7 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use inox_messenger::MessageHubRc;
 
@@ -8,7 +11,7 @@ use inox_resources::{
 use inox_serialize::{read_from_file, SerializationType};
 use wgpu::ShaderModule;
 
-use crate::{RenderContext, ShaderData, SHADER_EXTENSION};
+use crate::{RenderContext, ShaderBinding, ShaderData, ShaderVariantKey, SHADER_EXTENSION};
 
 pub type ShaderId = ResourceId;
 
@@ -69,10 +72,11 @@ pub fn read_spirv_from_bytes<Data: ::std::io::Read + ::std::io::Seek>(
     }
     result
 }
+// Modules of the variants are created the first time they are used
 pub struct Shader {
     path: PathBuf,
     data: ShaderData,
-    module: Option<ShaderModule>,
+    modules: HashMap<ShaderVariantKey, ShaderModule>,
 }
 
 impl Clone for Shader {
@@ -80,18 +84,18 @@ impl Clone for Shader {
         Self {
             path: self.path.clone(),
             data: self.data.clone(),
-            module: None,
+            modules: HashMap::new(),
         }
     }
 }
 
 impl ResourceTrait for Shader {
     fn invalidate(&mut self) -> &mut Self {
-        self.module = None;
+        self.modules.clear();
         self
     }
     fn is_initialized(&self) -> bool {
-        self.module.is_some()
+        self.modules.contains_key(&self.data.default_variant_key())
     }
 }
 
@@ -120,7 +124,7 @@ impl DataTypeResource for Shader {
         Self {
             path: PathBuf::new(),
             data: ShaderData::default(),
-            module: None,
+            modules: HashMap::new(),
         }
     }
 
@@ -141,9 +145,17 @@ impl DataTypeResource for Shader {
 
 impl Shader {
     pub fn init(&mut self, context: &RenderContext) -> bool {
-        if self.module.is_none() {
+        self.init_variant(context, &ShaderVariantKey::default())
+    }
+    // Key of the variant really compiled for the requested keywords
+    pub fn variant_key(&self, key: &ShaderVariantKey) -> ShaderVariantKey {
+        self.data.variant_key(key)
+    }
+    pub fn init_variant(&mut self, context: &RenderContext, key: &ShaderVariantKey) -> bool {
+        let key = self.variant_key(key);
+        if !self.modules.contains_key(&key) {
             inox_profiler::scoped_profile!("shader::init({:?})", self.path);
-            let shader_name = format!(
+            let mut shader_name = format!(
                 "Shader {}",
                 self.path
                     .file_stem()
@@ -151,33 +163,44 @@ impl Shader {
                     .to_str()
                     .unwrap_or_default()
             );
-            if !self.data.spirv_code.is_empty() {
-                let module =
-                    context
-                        .webgpu
-                        .device
-                        .create_shader_module(wgpu::ShaderModuleDescriptor {
-                            label: Some(shader_name.as_str()),
-                            source: wgpu::ShaderSource::SpirV(std::borrow::Cow::Borrowed(
-                                self.data.spirv_code.as_slice(),
-                            )),
-                        });
-                self.module = Some(module);
-            } else if !self.data.wgsl_code.is_empty() {
-                let module =
-                    context
-                        .webgpu
-                        .device
-                        .create_shader_module(wgpu::ShaderModuleDescriptor {
-                            label: Some(shader_name.as_str()),
-                            source: wgpu::ShaderSource::Wgsl(self.data.wgsl_code.clone().into()),
-                        });
-                self.module = Some(module);
+            if !key.keywords().is_empty() {
+                shader_name = format!("{shader_name} {:?}", key.keywords());
             }
+            let source = if !self.data.spirv_code.is_empty() {
+                Some(wgpu::ShaderSource::SpirV(std::borrow::Cow::Borrowed(
+                    self.data.spirv_code.as_slice(),
+                )))
+            } else {
+                self.data
+                    .variant_wgsl_code(&key)
+                    .filter(|code| !code.is_empty())
+                    .map(|code| wgpu::ShaderSource::Wgsl(code.into()))
+            };
+            let Some(source) = source else {
+                return false;
+            };
+            let module = context
+                .webgpu
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some(shader_name.as_str()),
+                    source,
+                });
+            self.modules.insert(key, module);
         }
-        self.module.is_some()
+        true
+    }
+    // False until the shader data is loaded
+    pub fn has_code(&self) -> bool {
+        !self.data.spirv_code.is_empty() || !self.data.wgsl_code.is_empty()
+    }
+    pub fn bindings(&self) -> &[ShaderBinding] {
+        self.data.bindings.as_slice()
     }
     pub fn module(&self) -> &ShaderModule {
-        self.module.as_ref().unwrap()
+        self.variant_module(&ShaderVariantKey::default())
+    }
+    pub fn variant_module(&self, key: &ShaderVariantKey) -> &ShaderModule {
+        self.modules.get(&self.variant_key(key)).unwrap()
     }
 }
@@ -9,10 +9,12 @@ use inox_resources::{
     Data, DataTypeResource, Handle, Resource, ResourceEvent, ResourceId, ResourceTrait,
     SerializableResource, SharedData, SharedDataRc,
 };
+use inox_serialize::{read_from_file, SerializationType, SerializeFile};
 use inox_uid::{generate_random_uid, Uid, INVALID_UID};
 
 use crate::{
-    platform::has_multisampling_support, TextureData, TextureFormat, TextureUsage, INVALID_INDEX,
+    platform::{has_multisampling_support, has_texture_format_support},
+    CompiledTextureData, TextureData, TextureFormat, TextureUsage, INVALID_INDEX,
 };
 
 pub type TextureId = ResourceId;
@@ -23,6 +25,7 @@ pub struct TextureBlock {
     pub y: u32,
     pub width: u32,
     pub height: u32,
+    pub mip_level: u32,
     pub data: Vec<u8>,
 }
 
@@ -97,13 +100,29 @@ impl DataTypeResource for Texture {
         };
         texture.mips_count = data.mips_count;
         if let Some(image_data) = &data.data {
-            texture.blocks_to_update = vec![TextureBlock {
-                x: 0,
-                y: 0,
-                width: data.width,
-                height: data.height,
-                data: image_data.clone(),
-            }];
+            // Mips are stored one after the other, starting from the biggest one
+            let mut offset = 0;
+            for mip_level in 0..data.mips_count.max(1) {
+                let width = (data.width >> mip_level).max(1);
+                let height = (data.height >> mip_level).max(1);
+                let size = if data.mips_count > 1 {
+                    data.format.image_size(width, height)
+                } else {
+                    image_data.len()
+                };
+                if offset + size > image_data.len() {
+                    break;
+                }
+                texture.blocks_to_update.push(TextureBlock {
+                    x: 0,
+                    y: 0,
+                    width,
+                    height,
+                    mip_level,
+                    data: image_data[offset..offset + size].to_vec(),
+                });
+                offset += size;
+            }
         }
         texture
     }
@@ -122,41 +141,48 @@ impl SerializableResource for Texture {
         "png"
     }
 
-    fn deserialize_data(path: &Path, mut f: Box<dyn FnMut(Self::DataType) + 'static>) {
-        let file = File::new(path);
-        let filepath = path.to_path_buf();
-        file.load(move |bytes| {
-            let image_format = ImageFormat::from_path(filepath.as_path()).unwrap();
-            #[allow(non_snake_case)]
-            let is_LUT = filepath
-                .file_stem()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .ends_with("_LUT");
-            match image::load_from_memory_with_format(bytes, image_format) {
-                Ok(image_data) => {
-                    f(TextureData {
-                        width: image_data.width(),
-                        height: image_data.height(),
-                        format: TextureFormat::Rgba8Unorm,
-                        data: Some(image_data.into_rgba8().to_vec()),
-                        usage: TextureUsage::TextureBinding | TextureUsage::CopyDst,
-                        sample_count: if has_multisampling_support() { 8 } else { 1 },
-                        layer_count: 1,
-                        is_LUT,
-                        mips_count: 1,
-                    });
-                }
-                Err(e) => {
+    fn deserialize_data(path: &Path, f: Box<dyn FnMut(Self::DataType) + 'static>) {
+        let compiled_texture_extension = CompiledTextureData::extension();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(compiled_texture_extension) {
+            match Self::compiled_image_path(path).filter(|p| File::new(p).exists()) {
+                Some(compiled_path) => Self::deserialize_data(compiled_path.as_path(), f),
+                None => Self::load_image(path, f),
+            }
+            return;
+        }
+        // Compiled textures are named after their image: "image.png.kind.texture"
+        let image_path = path.with_extension("").with_extension("");
+        let mut f = Some(f);
+        read_from_file::<CompiledTextureData>(
+            path,
+            SerializationType::Binary,
+            Box::new(move |data| {
+                let Some(mut f) = f.take() else {
+                    return;
+                };
+                // Block compressed formats are optional: their image is used when not supported
+                if !has_texture_format_support(data.format.into()) {
                     inox_log::debug_log!(
-                        "Failed to load image {:?} due to error: {:?}",
-                        &filepath,
-                        e
-                    )
+                        "Format {:?} of {:?} not supported, using its image",
+                        data.format,
+                        image_path
+                    );
+                    Self::load_image(image_path.as_path(), f);
+                    return;
                 }
-            }
-        });
+                f(TextureData {
+                    width: data.width,
+                    height: data.height,
+                    format: data.format,
+                    usage: TextureUsage::TextureBinding | TextureUsage::CopyDst,
+                    sample_count: 1,
+                    layer_count: 1,
+                    mips_count: data.mips.len() as _,
+                    is_LUT: Self::is_LUT(image_path.as_path()),
+                    data: Some(data.mips.concat()),
+                });
+            }),
+        );
     }
 
     fn is_matching_extension(path: &Path) -> bool {
@@ -167,9 +193,10 @@ impl SerializableResource for Texture {
         const IMAGE_TGA_EXTENSION: &str = "tga";
         const IMAGE_DDS_EXTENSION: &str = "dds";
         const IMAGE_TIFF_EXTENSION: &str = "tiff";
-        const IMAGE_GIF_EXTENSION: &str = "bmp";
+        const IMAGE_GIF_EXTENSION: &str = "gif";
         const IMAGE_ICO_EXTENSION: &str = "ico";
         const IMAGE_EXR_EXTENSION: &str = "exr";
+        let compiled_texture_extension = CompiledTextureData::extension();
 
         if let Some(ext) = path.extension().unwrap().to_str() {
             return ext == IMAGE_PNG_EXTENSION
@@ -181,13 +208,71 @@ impl SerializableResource for Texture {
                 || ext == IMAGE_TIFF_EXTENSION
                 || ext == IMAGE_GIF_EXTENSION
                 || ext == IMAGE_ICO_EXTENSION
-                || ext == IMAGE_EXR_EXTENSION;
+                || ext == IMAGE_EXR_EXTENSION
+                || ext == compiled_texture_extension;
         }
         false
     }
 }
 
 impl Texture {
+    #[allow(non_snake_case)]
+    fn is_LUT(path: &Path) -> bool {
+        path.file_stem()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .ends_with("_LUT")
+    }
+    // Images are compiled by the binarizer next to them, with mips and block compression:
+    // "*_normal" images as normal maps, LUTs aren't compiled and the others are colors
+    fn compiled_image_path(path: &Path) -> Option<PathBuf> {
+        if Self::is_LUT(path) {
+            return None;
+        }
+        let name = path.file_name()?.to_str()?;
+        let stem = path.file_stem()?.to_str()?.to_lowercase();
+        let kind = if stem.ends_with("_normal") {
+            "normal"
+        } else {
+            "color"
+        };
+        Some(path.with_file_name(format!(
+            "{name}.{kind}.{}",
+            CompiledTextureData::extension()
+        )))
+    }
+    fn load_image(path: &Path, mut f: Box<dyn FnMut(TextureData) + 'static>) {
+        #[allow(non_snake_case)]
+        let is_LUT = Self::is_LUT(path);
+        let file = File::new(path);
+        let filepath = path.to_path_buf();
+        file.load(move |bytes| {
+            let image_format = ImageFormat::from_path(filepath.as_path()).unwrap();
+            match image::load_from_memory_with_format(bytes, image_format) {
+                Ok(image_data) => {
+                    f(TextureData {
+                        width: image_data.width(),
+                        height: image_data.height(),
+                        format: TextureFormat::Rgba8Unorm,
+                        data: Some(image_data.into_rgba8().to_vec()),
+                        usage: TextureUsage::TextureBinding | TextureUsage::CopyDst,
+                        sample_count: if has_multisampling_support() { 8 } else { 1 },
+                        layer_count: 1,
+                        is_LUT,
+                        mips_count: 1,
+                    });
+                }
+                Err(e) => {
+                    inox_log::debug_log!(
+                        "Failed to load image {:?} due to error: {:?}",
+                        &filepath,
+                        e
+                    )
+                }
+            }
+        });
+    }
     fn mark_as_dirty(&self) -> &Self {
         self.message_hub
             .send_event(ResourceEvent::<Self>::Changed(self.id));
@@ -289,6 +374,7 @@ impl Texture {
             y: origin.y,
             width: size.x,
             height: size.y,
+            mip_level: 0,
             data: data.to_vec(),
         });
         self.mark_as_dirty();
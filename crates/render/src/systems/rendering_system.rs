@@ -6,10 +6,11 @@ use inox_messenger::Listener;
 use inox_resources::{
     ConfigBase, ConfigEvent, Handle, OnCreateData, SerializableResource, SharedDataRc,
 };
-use inox_serialize::{read_from_file, SerializationType};
+use inox_serialize::{read_from_file, SerializationType, SerializeFile};
 
 use crate::{
-    RenderContextRc, RendererState, Texture, ENV_MAP_UID, LUT_PBR_CHARLIE_UID, LUT_PBR_GGX_UID,
+    Environment, EnvironmentData, RenderContextRc, RendererState, Texture, ENV_MAP_UID,
+    LUT_PBR_CHARLIE_UID, LUT_PBR_GGX_UID,
 };
 
 use super::config::Config;
@@ -25,6 +26,7 @@ pub struct RenderingSystem {
     pbr_lut_charlie: Handle<Texture>,
     pbr_lut_ggx: Handle<Texture>,
     env_map: Handle<Texture>,
+    environment: Handle<Environment>,
 }
 
 impl RenderingSystem {
@@ -39,6 +41,7 @@ impl RenderingSystem {
             pbr_lut_charlie: None,
             pbr_lut_ggx: None,
             env_map: None,
+            environment: None,
         }
     }
 }
@@ -92,14 +95,29 @@ impl System for RenderingSystem {
                                 t.mark_as_LUT(&LUT_PBR_GGX_UID);
                             }),
                         ));
-                        self.env_map = Some(Texture::request_load(
-                            &self.shared_data,
-                            self.listener.message_hub(),
-                            &config.env_map,
-                            OnCreateData::create(|t: &mut Texture| {
-                                t.mark_as_LUT(&ENV_MAP_UID);
-                            }),
-                        ));
+                        // A compiled environment brings its own prefiltered map and LUTs
+                        if config.env_map.extension().unwrap_or_default()
+                            == EnvironmentData::extension()
+                        {
+                            self.env_map = None;
+                            self.environment = Some(Environment::request_load(
+                                &self.shared_data,
+                                self.listener.message_hub(),
+                                &config.env_map,
+                                None,
+                            ));
+                        } else {
+                            // The previous environment, once destroyed, stops using its cubemap
+                            self.environment = None;
+                            self.env_map = Some(Texture::request_load(
+                                &self.shared_data,
+                                self.listener.message_hub(),
+                                &config.env_map,
+                                OnCreateData::create(|t: &mut Texture| {
+                                    t.mark_as_LUT(&ENV_MAP_UID);
+                                }),
+                            ));
+                        }
                     }
                 }
             });
@@ -10,8 +10,8 @@ use inox_resources::{
 use inox_uid::generate_random_uid;
 
 use crate::{
-    is_shader, CommandBuffer, ComputePipeline, Light, Material, Mesh, RenderContextRc,
-    RenderPipeline, RendererState, Texture, View, DEFAULT_HEIGHT, DEFAULT_WIDTH,
+    is_shader, CommandBuffer, ComputePipeline, Environment, EnvironmentId, Light, Material, Mesh,
+    RenderContextRc, RenderPipeline, RendererState, Texture, View, DEFAULT_HEIGHT, DEFAULT_WIDTH,
 };
 
 pub const RENDERING_UPDATE: &str = "RENDERING_UPDATE";
@@ -25,6 +25,8 @@ pub struct UpdateSystem {
     width: u32,
     height: u32,
     resolution_changed: bool,
+    // Environment whose cubemap and irradiance are in use, replaced by the last one loaded
+    environment_id: Option<EnvironmentId>,
 }
 
 impl UpdateSystem {
@@ -46,6 +48,7 @@ impl UpdateSystem {
             mouse_coords: Vector2::default_zero(),
             width: DEFAULT_WIDTH,
             height: DEFAULT_HEIGHT,
+            environment_id: None,
         }
     }
 
@@ -97,6 +100,29 @@ impl UpdateSystem {
                         .remove_texture(&self.render_context, id);
                 }
             })
+            .process_messages(|e: &DataTypeResourceEvent<Environment>| {
+                let DataTypeResourceEvent::Loaded(id, environment_data) = e;
+                self.environment_id = Some(*id);
+                self.render_context
+                    .global_buffers()
+                    .constant_data
+                    .write()
+                    .unwrap()
+                    .set_environment(&self.render_context, &environment_data.irradiance);
+            })
+            .process_messages(|e: &ResourceEvent<Environment>| {
+                if let ResourceEvent::Destroyed(id) = e {
+                    if self.environment_id == Some(*id) {
+                        self.environment_id = None;
+                        self.render_context
+                            .global_buffers()
+                            .constant_data
+                            .write()
+                            .unwrap()
+                            .clear_environment(&self.render_context);
+                    }
+                }
+            })
             .process_messages(|e: &DataTypeResourceEvent<Light>| {
                 let DataTypeResourceEvent::Loaded(id, light_data) = e;
                 self.render_context.global_buffers().update_light(
@@ -208,12 +234,14 @@ impl System for UpdateSystem {
             .register::<WindowEvent>()
             .register::<MouseEvent>()
             .register::<ReloadEvent>()
+            .register::<DataTypeResourceEvent<Environment>>()
             .register::<DataTypeResourceEvent<Light>>()
             .register::<DataTypeResourceEvent<Material>>()
             .register::<DataTypeResourceEvent<Mesh>>()
             .register::<SerializableResourceEvent<RenderPipeline>>()
             .register::<SerializableResourceEvent<ComputePipeline>>()
             .register::<SerializableResourceEvent<Texture>>()
+            .register::<ResourceEvent<Environment>>()
             .register::<ResourceEvent<Material>>()
             .register::<ResourceEvent<Texture>>()
             .register::<ResourceEvent<Light>>()
@@ -242,6 +270,9 @@ impl System for UpdateSystem {
         let mut command_buffer = self.render_context.new_command_buffer();
 
         self.handle_events(&mut command_buffer);
+        self.render_context
+            .global_buffers()
+            .update_tlas(&self.render_context);
 
         {
             let screen_size = Vector2::new(self.width as _, self.height as _);
@@ -271,12 +302,14 @@ impl System for UpdateSystem {
             .unregister::<WindowEvent>()
             .unregister::<MouseEvent>()
             .unregister::<ReloadEvent>()
+            .unregister::<DataTypeResourceEvent<Environment>>()
             .unregister::<DataTypeResourceEvent<Light>>()
             .unregister::<DataTypeResourceEvent<Material>>()
             .unregister::<DataTypeResourceEvent<Mesh>>()
             .unregister::<SerializableResourceEvent<RenderPipeline>>()
             .unregister::<SerializableResourceEvent<ComputePipeline>>()
             .unregister::<SerializableResourceEvent<Texture>>()
+            .unregister::<ResourceEvent<Environment>>()
             .unregister::<ResourceEvent<Light>>()
             .unregister::<ResourceEvent<Texture>>()
             .unregister::<ResourceEvent<Material>>()
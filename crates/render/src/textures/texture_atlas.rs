@@ -1,8 +1,12 @@
+use std::collections::HashMap;
+
 use inox_log::debug_log;
 use inox_math::quantize_half;
 use inox_uid::generate_random_uid;
 
-use crate::{GPUTexture, TextureBlock, TextureFormat, TextureId, TextureView};
+use crate::{
+    GPUTexture, TextureBlock, TextureFormat, TextureId, TextureView, TEXTURE_MIPS_COUNT_SHIFT,
+};
 
 use super::{
     area::{Area, AreaAllocator, DEFAULT_AREA_SIZE},
@@ -11,10 +15,25 @@ use super::{
 
 pub const DEFAULT_LAYER_COUNT: u32 = 8u32;
 pub const MAX_TEXTURE_ATLAS_COUNT: u32 = 8u32;
+pub const DEFAULT_MIPS_COUNT: u32 = 8u32;
 
 pub struct TextureAtlas {
     texture: TextureRef,
     allocators: Vec<AreaAllocator>,
+    // Mips uploaded for each texture, shaders can't sample the ones after them
+    mips_count: HashMap<TextureId, u32>,
+}
+
+// Negatives are LUT textures
+fn pack_texture_and_layer_index(texture_index: i32, layer_index: usize, mips_count: u32) -> i32 {
+    let value = ((mips_count << TEXTURE_MIPS_COUNT_SHIFT)
+        | (texture_index.unsigned_abs() << 3)
+        | layer_index as u32) as i32;
+    if texture_index < 0 {
+        -value
+    } else {
+        value
+    }
 }
 
 impl TextureAtlas {
@@ -34,12 +53,13 @@ impl TextureAtlas {
                 1,
                 format,
                 wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                1,
+                DEFAULT_MIPS_COUNT,
             ),
         );
         Self {
             texture,
             allocators,
+            mips_count: HashMap::new(),
         }
     }
 
@@ -84,11 +104,14 @@ impl TextureAtlas {
         for (layer_index, area_allocator) in self.allocators.iter_mut().enumerate() {
             if let Some(area) = area_allocator.allocate(id, dimensions.0, dimensions.1) {
                 self.texture
-                    .send_to_gpu(device, encoder, layer_index as _, area, image_data);
+                    .send_to_gpu(device, encoder, layer_index as _, 0, area, image_data);
+                self.mips_count.insert(*id, 1);
                 return Some(GPUTexture {
-                    texture_and_layer_index: (((texture_index as u32) << 3) | layer_index as u32)
-                        as i32
-                        * texture_index.signum(),
+                    texture_and_layer_index: pack_texture_and_layer_index(
+                        texture_index,
+                        layer_index,
+                        1,
+                    ),
                     min: quantize_half(area.x as f32) as u32
                         | ((quantize_half(area.y as f32) as u32) << 16),
                     max: quantize_half(area.width as f32) as u32
@@ -110,6 +133,47 @@ impl TextureAtlas {
         texture_block: &TextureBlock,
     ) {
         if let Some(area) = self.allocators[layer_index].get_area(texture_id) {
+            let mip_level = texture_block.mip_level;
+            if mip_level > 0 {
+                // Mips are sampled up to the first one missing: they have to come in order and
+                // a mip can be copied only if the area is still aligned to the blocks at its level
+                let (block_width, block_height) = self.texture.format().block_dimensions();
+                let mips_count = self.mips_count.get(texture_id).copied().unwrap_or(1);
+                if mip_level != mips_count
+                    || mip_level >= DEFAULT_MIPS_COUNT
+                    || area.x % (block_width << mip_level) != 0
+                    || area.y % (block_height << mip_level) != 0
+                    || texture_block.width % block_width != 0
+                    || texture_block.height % block_height != 0
+                {
+                    debug_log!(
+                        "Skipping mip {} of texture {} at ({},{}): only {} mips can be sampled",
+                        mip_level,
+                        texture_id,
+                        area.x,
+                        area.y,
+                        mips_count
+                    );
+                    return;
+                }
+                let mip_area = Area {
+                    x: area.x >> mip_level,
+                    y: area.y >> mip_level,
+                    width: texture_block.width,
+                    height: texture_block.height,
+                    ..*area
+                };
+                self.texture.send_to_gpu(
+                    device,
+                    encoder,
+                    layer_index as _,
+                    mip_level,
+                    &mip_area,
+                    &texture_block.data,
+                );
+                self.mips_count.insert(*texture_id, mips_count + 1);
+                return;
+            }
             let mut real_area = *area;
             debug_assert!(texture_block.x < area.width);
             debug_assert!(texture_block.y < area.height);
@@ -123,6 +187,7 @@ impl TextureAtlas {
                 device,
                 encoder,
                 layer_index as _,
+                0,
                 &real_area,
                 &texture_block.data,
             );
@@ -132,8 +197,13 @@ impl TextureAtlas {
     pub fn texture_info(&self, texture_index: u32, texture_id: &TextureId) -> Option<GPUTexture> {
         for (layer_index, area_allocator) in self.allocators.iter().enumerate() {
             if let Some(area) = area_allocator.get_area(texture_id) {
+                let mips_count = self.mips_count.get(texture_id).copied().unwrap_or(1);
                 return Some(GPUTexture {
-                    texture_and_layer_index: ((texture_index << 3) | layer_index as u32) as i32,
+                    texture_and_layer_index: pack_texture_and_layer_index(
+                        texture_index as _,
+                        layer_index,
+                        mips_count,
+                    ),
                     min: quantize_half(area.x as f32) as u32
                         | ((quantize_half(area.y as f32) as u32) << 16),
                     max: quantize_half(area.width as f32) as u32
@@ -147,6 +217,7 @@ impl TextureAtlas {
     }
 
     pub fn remove(&mut self, texture_id: &TextureId) -> bool {
+        self.mips_count.remove(texture_id);
         for (layer_index, allocator) in self.allocators.iter_mut().enumerate() {
             if allocator.remove_texture(texture_id) {
                 //todo remove the real texture from device memory
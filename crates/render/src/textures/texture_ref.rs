@@ -93,9 +93,22 @@ impl TextureRef {
             sample_count,
             format,
         };
-        //create_mips
-        for _ in 0..mips_count {
-            texture.add_view(format, Some(usage), dimension.unwrap(), layers_count);
+        if usage.intersects(
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::STORAGE_BINDING,
+        ) {
+            //create_mips
+            for _ in 0..mips_count {
+                texture.add_view(
+                    format,
+                    Some(usage),
+                    dimension.unwrap(),
+                    layers_count,
+                    Some(1),
+                );
+            }
+        } else {
+            // Sampled only textures have a single view on the whole mip chain
+            texture.add_view(format, Some(usage), dimension.unwrap(), layers_count, None);
         }
         texture
     }
@@ -105,6 +118,7 @@ impl TextureRef {
         usage: Option<wgpu::TextureUsages>,
         dimension: wgpu::TextureViewDimension,
         layers_count: u32,
+        mip_level_count: Option<u32>,
     ) {
         let view = self.texture.create_view(&wgpu::TextureViewDescriptor {
             label: Some(format!("TextureView[{}]_mip[{}]", self.id, self.views.len()).as_str()),
@@ -113,7 +127,7 @@ impl TextureRef {
             aspect: format.aspect(),
             usage,
             base_mip_level: self.views.len() as _,
-            mip_level_count: Some(1),
+            mip_level_count,
             base_array_layer: 0,
             array_layer_count: Some(layers_count),
         });
@@ -146,6 +160,7 @@ impl TextureRef {
         device: &wgpu::Device,
         encoder: &mut wgpu::CommandEncoder,
         layer_index: u32,
+        mip_level: u32,
         area: &Area,
         data: &[u8],
     ) {
@@ -155,7 +170,10 @@ impl TextureRef {
         // multiple of wgpu::COPY_BYTES_PER_ROW_ALIGNMENT.
         let format: wgpu::TextureFormat = self.format.into();
         let (block_width, block_height) = format.block_dimensions();
-        let (area_width, area_height) = (area.width / block_width, area.height / block_height);
+        let (area_width, area_height) = (
+            area.width.div_ceil(block_width),
+            area.height.div_ceil(block_height),
+        );
         let block_copy_size = format
             .block_copy_size(Some(self.format.aspect()))
             .unwrap_or(1);
@@ -190,7 +208,7 @@ impl TextureRef {
             },
             wgpu::TexelCopyTextureInfo {
                 texture: &self.texture,
-                mip_level: 0,
+                mip_level,
                 origin: wgpu::Origin3d {
                     x: area.x,
                     y: area.y,
@@ -199,8 +217,8 @@ impl TextureRef {
                 aspect: self.format.aspect(),
             },
             wgpu::Extent3d {
-                width: area_width,
-                height: area_height,
+                width: area_width * block_width,
+                height: area_height * block_height,
                 depth_or_array_layers: 1,
             },
         );
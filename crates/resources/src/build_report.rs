@@ -0,0 +1,215 @@
+use std::path::{Path, PathBuf};
+
+use inox_filesystem::convert_from_local_path;
+use inox_serialize::{Deserialize, Serialize, SerializeFile};
+
+use crate::Data;
+
+// Written by the binarizer in every platform data folder
+pub const BUILD_REPORT_FILENAME: &str = "build_report.json";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(crate = "inox_serialize")]
+pub enum AssetStatus {
+    Produced,
+    Skipped,
+    Failed,
+}
+
+// Errors make the asset fail, warnings are about content skipped or imported differently
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+#[serde(crate = "inox_serialize")]
+pub enum DiagnosticSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+// Location is where the problem is inside the asset, like a mesh primitive or a line
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct AssetDiagnostic {
+    pub severity: DiagnosticSeverity,
+    pub asset: String,
+    pub location: String,
+    pub message: String,
+}
+
+// A level of the meshlets hierarchy, made simplifying groups of meshlets of the previous one
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct MeshLodStats {
+    pub meshlets: u32,
+    pub triangles: u32,
+    // Groups of the previous level simplified into this one
+    pub groups: u32,
+    // Highest simplification error of the meshlets, in mesh units
+    pub max_error: f32,
+    // Vertices locked on the borders of the groups over all the vertices of the groups
+    pub locked_border_ratio: f32,
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct MeshStats {
+    pub name: String,
+    pub vertices: u32,
+    pub triangles: u32,
+    pub meshlets_per_lod: Vec<u32>,
+    #[serde(default)]
+    pub lods: Vec<MeshLodStats>,
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct TextureStats {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub mips: u32,
+    pub format: String,
+    pub bytes: u64,
+}
+
+// Only the meshes and textures produced while compiling the asset are counted
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct AssetStats {
+    pub meshes: Vec<MeshStats>,
+    pub textures: Vec<TextureStats>,
+    pub time_ms: u64,
+}
+
+// What happened to an asset since results were last taken
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct AssetResult {
+    pub source: String,
+    pub compiler: String,
+    pub status: AssetStatus,
+    pub outputs: Vec<PathBuf>,
+    #[serde(default)]
+    pub diagnostics: Vec<AssetDiagnostic>,
+    #[serde(default)]
+    pub stats: AssetStats,
+}
+
+impl AssetResult {
+    pub fn new(source: &str, compiler: &str, status: AssetStatus) -> Self {
+        Self {
+            source: source.to_string(),
+            compiler: compiler.to_string(),
+            status,
+            outputs: Vec::new(),
+            diagnostics: Vec::new(),
+            stats: AssetStats::default(),
+        }
+    }
+
+    fn restore_from(&mut self, previous: AssetResult) {
+        if self.status == AssetStatus::Skipped
+            && previous.status != AssetStatus::Failed
+            && self.diagnostics.is_empty()
+            && self.stats == AssetStats::default()
+        {
+            self.diagnostics = previous.diagnostics;
+            self.stats = previous.stats;
+        }
+    }
+}
+
+// What a binarization of a data folder produced, skipped or failed to compile
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct BuildReport {
+    pub platform: String,
+    pub data_folder: PathBuf,
+    pub produced: Vec<AssetResult>,
+    pub skipped: Vec<AssetResult>,
+    pub failed: Vec<AssetResult>,
+    // Assets found stale in verify mode
+    pub stale: Vec<String>,
+}
+
+impl SerializeFile for BuildReport {
+    fn extension() -> &'static str {
+        "json"
+    }
+}
+
+impl BuildReport {
+    pub fn new(platform: &str, data_folder: &Path) -> Self {
+        Self {
+            platform: platform.to_string(),
+            data_folder: data_folder.to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    // Report of the platform the application is running on
+    pub fn path() -> PathBuf {
+        convert_from_local_path(
+            Data::platform_data_folder().as_path(),
+            Path::new(BUILD_REPORT_FILENAME),
+        )
+    }
+
+    // An asset compiled more times, as while watching, keeps only its last result.
+    // When skipped it keeps diagnostics and stats of when it was compiled
+    pub fn add_results(&mut self, results: Vec<AssetResult>) {
+        results.into_iter().for_each(|mut result| {
+            let mut previous = None;
+            [&mut self.produced, &mut self.skipped, &mut self.failed]
+                .into_iter()
+                .for_each(|list| {
+                    if let Some(i) = list
+                        .iter()
+                        .position(|r| r.source == result.source && r.compiler == result.compiler)
+                    {
+                        previous = Some(list.remove(i));
+                    }
+                });
+            if let Some(previous) = previous {
+                result.restore_from(previous);
+            }
+            match result.status {
+                AssetStatus::Produced => self.produced.push(result),
+                AssetStatus::Skipped => self.skipped.push(result),
+                AssetStatus::Failed => self.failed.push(result),
+            }
+        });
+    }
+
+    // Skipped assets take diagnostics and stats from a report of a previous binarization
+    pub fn restore_skipped(&mut self, previous: &BuildReport) {
+        self.skipped.iter_mut().for_each(|result| {
+            if let Some(previous) = previous
+                .results()
+                .find(|r| r.source == result.source && r.compiler == result.compiler)
+            {
+                result.restore_from(previous.clone());
+            }
+        });
+    }
+
+    pub fn is_successful(&self) -> bool {
+        self.failed.is_empty() && self.stale.is_empty()
+    }
+
+    pub fn results(&self) -> impl Iterator<Item = &AssetResult> {
+        self.produced
+            .iter()
+            .chain(self.skipped.iter())
+            .chain(self.failed.iter())
+    }
+
+    pub fn diagnostics(&self) -> impl Iterator<Item = &AssetDiagnostic> {
+        self.results().flat_map(|r| r.diagnostics.iter())
+    }
+
+    pub fn count_diagnostics(&self, severity: DiagnosticSeverity) -> usize {
+        self.diagnostics()
+            .filter(|d| d.severity == severity)
+            .count()
+    }
+}
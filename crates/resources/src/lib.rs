@@ -1,6 +1,7 @@
 #![warn(clippy::all)]
 
 pub use crate::buffer::*;
+pub use crate::build_report::*;
 
 pub use crate::config::*;
 pub use crate::data::*;
@@ -12,6 +13,7 @@ pub use crate::singleton::*;
 pub use crate::storage::*;
 
 pub mod buffer;
+pub mod build_report;
 
 pub mod config;
 pub mod data;
@@ -0,0 +1,106 @@
+use std::path::{Path, PathBuf};
+
+use inox_math::{Quaternion, Vector3};
+use inox_messenger::MessageHubRc;
+use inox_resources::{
+    DataTypeResource, ResourceId, ResourceTrait, SerializableResource, SharedDataRc,
+};
+use inox_serialize::{read_from_file, SerializationType, SerializeFile};
+
+use crate::{to_quaternion, AnimationData, AnimationProperty};
+
+pub type AnimationId = ResourceId;
+
+// Local transform values of an animated object, None if not animated by the clip.
+// Morph weights are the ones animated by the clip, with their target index
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct AnimationPose {
+    pub translation: Option<Vector3>,
+    pub rotation: Option<Quaternion>,
+    pub scale: Option<Vector3>,
+    pub morph_weights: Vec<(usize, f32)>,
+}
+
+#[derive(Clone)]
+pub struct Animation {
+    filepath: PathBuf,
+    data: AnimationData,
+    is_initialized: bool,
+}
+
+impl SerializableResource for Animation {
+    fn path(&self) -> &Path {
+        self.filepath.as_path()
+    }
+
+    fn set_path(&mut self, path: &Path) -> &mut Self {
+        self.filepath = path.to_path_buf();
+        self
+    }
+
+    fn extension() -> &'static str {
+        AnimationData::extension()
+    }
+
+    fn deserialize_data(path: &std::path::Path, f: Box<dyn FnMut(Self::DataType) + 'static>) {
+        read_from_file::<Self::DataType>(path, SerializationType::Binary, f);
+    }
+}
+
+impl ResourceTrait for Animation {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+    fn invalidate(&mut self) -> &mut Self {
+        self.is_initialized = false;
+        self
+    }
+}
+
+impl DataTypeResource for Animation {
+    type DataType = AnimationData;
+
+    fn new(_id: ResourceId, _shared_data: &SharedDataRc, _message_hub: &MessageHubRc) -> Self {
+        Self {
+            filepath: PathBuf::new(),
+            data: AnimationData::default(),
+            is_initialized: false,
+        }
+    }
+
+    fn create_from_data(
+        shared_data: &SharedDataRc,
+        message_hub: &MessageHubRc,
+        id: AnimationId,
+        data: &Self::DataType,
+    ) -> Self {
+        let mut animation = Self::new(id, shared_data, message_hub);
+        animation.data = data.clone();
+        animation.is_initialized = true;
+        animation
+    }
+}
+
+impl Animation {
+    #[inline]
+    pub fn animation_name(&self) -> &str {
+        &self.data.name
+    }
+    #[inline]
+    pub fn duration(&self) -> f32 {
+        self.data.duration
+    }
+    pub fn sample(&self, time: f32) -> AnimationPose {
+        let mut pose = AnimationPose::default();
+        self.data.channels.iter().for_each(|c| {
+            let v = c.sample(time);
+            match c.property {
+                AnimationProperty::Translation => pose.translation = Some(v.truncate()),
+                AnimationProperty::Rotation => pose.rotation = Some(to_quaternion(v)),
+                AnimationProperty::Scale => pose.scale = Some(v.truncate()),
+                AnimationProperty::MorphWeight(i) => pose.morph_weights.push((i as _, v.x)),
+            }
+        });
+        pose
+    }
+}
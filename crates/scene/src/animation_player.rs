@@ -0,0 +1,362 @@
+use inox_math::{Mat4Ops, Matrix4, Quaternion, VecBase, Vector3, Vector4};
+use inox_messenger::MessageHubRc;
+use inox_resources::{DataTypeResource, Resource, ResourceId, ResourceTrait, SharedDataRc};
+
+use crate::{from_quaternion, to_quaternion, Animation, ObjectId};
+
+pub type AnimationPlayerId = ResourceId;
+
+#[derive(Clone)]
+pub struct AnimationState {
+    animation: Resource<Animation>,
+    time: f32,
+    weight: f32,
+    target_weight: f32,
+    fade_speed: f32,
+    pub speed: f32,
+    pub is_looping: bool,
+    pub is_playing: bool,
+}
+
+impl AnimationState {
+    fn new(animation: Resource<Animation>) -> Self {
+        Self {
+            animation,
+            time: 0.,
+            weight: 0.,
+            target_weight: 0.,
+            fade_speed: 0.,
+            speed: 1.,
+            is_looping: true,
+            is_playing: false,
+        }
+    }
+    #[inline]
+    pub fn animation(&self) -> &Resource<Animation> {
+        &self.animation
+    }
+    #[inline]
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+    #[inline]
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+    fn advance(&mut self, dt: f32) {
+        if self.fade_speed > 0. {
+            let delta = self.target_weight - self.weight;
+            let step = self.fade_speed * dt;
+            self.weight = if delta.abs() <= step {
+                self.target_weight
+            } else {
+                self.weight + step.copysign(delta)
+            };
+        }
+        if !self.is_playing {
+            return;
+        }
+        let duration = self.animation.get().duration();
+        self.time += dt * self.speed;
+        if self.is_looping && duration > 0. {
+            self.time = self.time.rem_euclid(duration);
+        } else if self.time >= duration || self.time <= 0. {
+            self.time = self.time.clamp(0., duration);
+            self.is_playing = false;
+        }
+    }
+}
+
+// Plays animations of an object: every clip with a weight greater than zero
+// is sampled and blended, then the result is written in the object transform
+#[derive(Clone)]
+pub struct AnimationPlayer {
+    object_id: ObjectId,
+    rest_translation: Vector3,
+    rest_rotation: Quaternion,
+    rest_scale: Vector3,
+    states: Vec<AnimationState>,
+    morph_weights: Vec<(usize, f32)>,
+}
+
+impl ResourceTrait for AnimationPlayer {
+    fn is_initialized(&self) -> bool {
+        !self.states.is_empty()
+    }
+    fn invalidate(&mut self) -> &mut Self {
+        self
+    }
+}
+
+impl DataTypeResource for AnimationPlayer {
+    type DataType = ();
+
+    fn new(_id: ResourceId, _shared_data: &SharedDataRc, _message_hub: &MessageHubRc) -> Self {
+        Self {
+            object_id: ObjectId::default(),
+            rest_translation: Vector3::default_zero(),
+            rest_rotation: Quaternion::new(1., 0., 0., 0.),
+            rest_scale: Vector3::default_one(),
+            states: Vec::new(),
+            morph_weights: Vec::new(),
+        }
+    }
+
+    fn create_from_data(
+        shared_data: &SharedDataRc,
+        message_hub: &MessageHubRc,
+        id: AnimationPlayerId,
+        _data: &Self::DataType,
+    ) -> Self {
+        Self::new(id, shared_data, message_hub)
+    }
+}
+
+impl AnimationPlayer {
+    // Rest transform is used for the properties not animated by the playing clips
+    pub fn set_object(&mut self, object_id: &ObjectId, rest_transform: Matrix4) -> &mut Self {
+        self.object_id = *object_id;
+        self.rest_translation = rest_transform.translation();
+        self.rest_rotation = rest_transform.orientation();
+        self.rest_scale = rest_transform.scale();
+        self
+    }
+    #[inline]
+    pub fn object_id(&self) -> &ObjectId {
+        &self.object_id
+    }
+    #[inline]
+    pub fn states(&self) -> &[AnimationState] {
+        &self.states
+    }
+    // Blended morph target weights computed by the last update, with their target index
+    #[inline]
+    pub fn morph_weights(&self) -> &[(usize, f32)] {
+        &self.morph_weights
+    }
+    // The first animation added starts playing right away
+    pub fn add_animation(&mut self, animation: Resource<Animation>) -> &mut Self {
+        let mut state = AnimationState::new(animation);
+        if self.states.is_empty() {
+            state.weight = 1.;
+            state.target_weight = 1.;
+            state.is_playing = true;
+        }
+        self.states.push(state);
+        self
+    }
+    fn find_state(&mut self, name: &str) -> Option<&mut AnimationState> {
+        self.states
+            .iter_mut()
+            .find(|s| s.animation.get().animation_name() == name)
+    }
+    // Plays only the given animation from the beginning
+    pub fn play(&mut self, name: &str) -> &mut Self {
+        self.cross_fade(name, 0.)
+    }
+    // Starts the given animation and fades out all the others in fade_time seconds
+    pub fn cross_fade(&mut self, name: &str, fade_time: f32) -> &mut Self {
+        let fade_speed = if fade_time > 0. { 1. / fade_time } else { 0. };
+        self.states.iter_mut().for_each(|s| {
+            let is_target = s.animation.get().animation_name() == name;
+            s.target_weight = if is_target { 1. } else { 0. };
+            s.fade_speed = fade_speed;
+            if fade_speed <= 0. {
+                s.weight = s.target_weight;
+            }
+            if is_target {
+                s.time = 0.;
+                s.is_playing = true;
+            }
+        });
+        self
+    }
+    pub fn stop(&mut self, name: &str) -> &mut Self {
+        if let Some(s) = self.find_state(name) {
+            s.is_playing = false;
+        }
+        self
+    }
+    pub fn resume(&mut self, name: &str) -> &mut Self {
+        if let Some(s) = self.find_state(name) {
+            s.is_playing = true;
+        }
+        self
+    }
+    pub fn set_weight(&mut self, name: &str, weight: f32) -> &mut Self {
+        if let Some(s) = self.find_state(name) {
+            s.weight = weight.max(0.);
+            s.target_weight = s.weight;
+            s.fade_speed = 0.;
+        }
+        self
+    }
+    pub fn set_speed(&mut self, name: &str, speed: f32) -> &mut Self {
+        if let Some(s) = self.find_state(name) {
+            s.speed = speed;
+        }
+        self
+    }
+    pub fn set_looping(&mut self, name: &str, is_looping: bool) -> &mut Self {
+        if let Some(s) = self.find_state(name) {
+            s.is_looping = is_looping;
+        }
+        self
+    }
+
+    // Returns the new local transform of the object, if any animation contributes to it
+    pub fn update(&mut self, dt: f32) -> Option<Matrix4> {
+        let mut total_weight = 0.;
+        let mut translation = Vector3::default_zero();
+        let mut scale = Vector3::default_zero();
+        let mut rotation = Vector4::default_zero();
+        let mut first_rotation = None;
+        let mut morph_weights: Vec<(usize, f32, f32)> = Vec::new();
+
+        self.states.iter_mut().for_each(|s| {
+            if !s.animation.get().is_initialized() {
+                return;
+            }
+            s.advance(dt);
+            if s.weight <= 0. {
+                return;
+            }
+            let pose = s.animation.get().sample(s.time);
+            let r = from_quaternion(pose.rotation.unwrap_or(self.rest_rotation));
+            // Quaternions q and -q are the same rotation: blend on the same hemisphere
+            let reference = *first_rotation.get_or_insert(r);
+            let sign = if dot(reference, r) < 0. { -1. } else { 1. };
+            translation += pose.translation.unwrap_or(self.rest_translation) * s.weight;
+            scale += pose.scale.unwrap_or(self.rest_scale) * s.weight;
+            rotation += r * (s.weight * sign);
+            total_weight += s.weight;
+            pose.morph_weights.iter().for_each(|&(i, w)| {
+                if let Some(m) = morph_weights.iter_mut().find(|m| m.0 == i) {
+                    m.1 += w * s.weight;
+                    m.2 += s.weight;
+                } else {
+                    morph_weights.push((i, w * s.weight, s.weight));
+                }
+            });
+        });
+        self.morph_weights = morph_weights
+            .into_iter()
+            .map(|(i, w, total)| (i, w / total))
+            .collect();
+        if total_weight <= 0. {
+            return None;
+        }
+        let length = dot(rotation, rotation).sqrt();
+        let rotation = if length > 0. {
+            to_quaternion(rotation / length)
+        } else {
+            self.rest_rotation
+        };
+        Some(Matrix4::from_translation_orientation_scale(
+            translation / total_weight,
+            rotation,
+            scale / total_weight,
+        ))
+    }
+}
+
+#[inline]
+fn dot(a: Vector4, b: Vector4) -> f32 {
+    a.x * b.x + a.y * b.y + a.z * b.z + a.w * b.w
+}
+
+#[test]
+fn test_animation_hierarchy() {
+    use inox_math::MatBase;
+    use inox_messenger::MessageHub;
+    use inox_resources::SharedData;
+    use inox_uid::generate_random_uid;
+    use std::sync::Arc;
+
+    use crate::{AnimationChannelData, AnimationData, AnimationProperty, Object};
+
+    let message_hub = Arc::new(MessageHub::default());
+    let shared_data = Arc::new(SharedData::default());
+    shared_data.register_type::<Object>(&message_hub);
+    shared_data.register_type::<Animation>(&message_hub);
+    shared_data.register_type::<AnimationPlayer>(&message_hub);
+
+    let create_object = |translation: Vector3| {
+        let id = generate_random_uid();
+        let mut object = Object::new(id, &shared_data, &message_hub);
+        object.set_transform(Matrix4::from_translation(translation));
+        shared_data.add_resource(&message_hub, id, object)
+    };
+    // Moves along x by one unit each second, for 4 seconds
+    let create_player = |object: &Resource<Object>, axis: Vector3| {
+        let data = AnimationData {
+            name: "Move".to_string(),
+            duration: 4.,
+            channels: vec![AnimationChannelData {
+                property: AnimationProperty::Translation,
+                times: vec![0., 4.],
+                values: vec![Vector4::new(0., 0., 0., 0.), (axis * 4.).extend(0.)],
+                ..Default::default()
+            }],
+        };
+        let id = generate_random_uid();
+        let animation = Animation::create_from_data(&shared_data, &message_hub, id, &data);
+        let animation = shared_data.add_resource(&message_hub, id, animation);
+        let id = generate_random_uid();
+        let mut player = AnimationPlayer::new(id, &shared_data, &message_hub);
+        player
+            .set_object(object.id(), object.get().local_transform())
+            .add_animation(animation);
+        shared_data.add_resource(&message_hub, id, player)
+    };
+
+    // Animated root and joint, with a child that is not animated
+    let root = create_object(Vector3::default_zero());
+    let joint = create_object(Vector3::new(0., 1., 0.));
+    let child = create_object(Vector3::new(0., 0., 1.));
+    joint.get_mut().set_parent(Some(root.clone()));
+    root.get_mut().add_child(joint.clone());
+    child.get_mut().set_parent(Some(joint.clone()));
+    joint.get_mut().add_child(child.clone());
+    let players = [
+        create_player(&root, Vector3::new(1., 0., 0.)),
+        create_player(&joint, Vector3::new(0., 1., 0.)),
+    ];
+
+    for frame in 1..=3 {
+        players.iter().for_each(|p| {
+            let transform = p.get_mut().update(1.).unwrap();
+            let object = shared_data.get_resource::<Object>(p.get().object_id());
+            object.unwrap().get_mut().set_transform(transform);
+        });
+        // Children first: parents are updated before them anyway
+        [&child, &joint, &root].iter().for_each(|o| {
+            Object::update_world_transform(o);
+        });
+        let t = frame as f32;
+        assert_eq!(
+            root.get().local_transform().translation(),
+            Vector3::new(t, 0., 0.)
+        );
+        assert_eq!(
+            joint.get().local_transform().translation(),
+            Vector3::new(0., t, 0.)
+        );
+        assert_eq!(
+            root.get().transform().translation(),
+            Vector3::new(t, 0., 0.)
+        );
+        assert_eq!(
+            joint.get().transform().translation(),
+            Vector3::new(t, t, 0.)
+        );
+        assert_eq!(
+            child.get().local_transform().translation(),
+            Vector3::new(0., 0., 1.)
+        );
+        assert_eq!(
+            child.get().transform().translation(),
+            Vector3::new(t, t, 1.)
+        );
+    }
+}
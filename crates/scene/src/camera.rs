@@ -24,6 +24,8 @@ pub struct Camera {
     aspect_ratio: f32,
     near_plane: f32,
     far_plane: f32,
+    is_orthographic: bool,
+    ymag: f32, // half height of the orthographic view volume
 }
 
 impl UIProperties for Camera {
@@ -38,10 +40,17 @@ impl UIProperties for Camera {
             .show_background(true)
             .default_open(!collapsed)
             .show(ui, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label("FOV: ");
-                    self.fov_in_degrees.show(id, ui_registry, ui, collapsed);
-                });
+                if self.is_orthographic {
+                    ui.horizontal(|ui| {
+                        ui.label("Orthographic height: ");
+                        self.ymag.show(id, ui_registry, ui, collapsed);
+                    });
+                } else {
+                    ui.horizontal(|ui| {
+                        ui.label("FOV: ");
+                        self.fov_in_degrees.show(id, ui_registry, ui, collapsed);
+                    });
+                }
                 ui.horizontal(|ui| {
                     ui.label("Near plane: ");
                     self.near_plane.show(id, ui_registry, ui, collapsed);
@@ -101,6 +110,8 @@ impl DataTypeResource for Camera {
             near_plane: DEFAULT_NEAR,
             far_plane: DEFAULT_FAR,
             aspect_ratio: DEFAULT_ASPECT_RATIO,
+            is_orthographic: false,
+            ymag: 1.,
         }
     }
 
@@ -111,12 +122,20 @@ impl DataTypeResource for Camera {
         data: &Self::DataType,
     ) -> Self {
         let mut camera = Self::new(id, shared_data, message_hub);
-        camera.set_projection(data.fov, data.aspect_ratio, 1., data.near, data.far);
+        if data.is_orthographic {
+            camera.is_orthographic = true;
+            camera.ymag = data.ymag;
+            camera.set_projection(data.fov, data.xmag, data.ymag, data.near, data.far);
+        } else {
+            camera.set_projection(data.fov, data.aspect_ratio, 1., data.near, data.far);
+        }
         camera
     }
 }
 
 impl Camera {
+    // When orthographic the fov is kept for switching back to perspective
+    // and the view volume width follows the aspect ratio
     #[inline]
     pub fn set_projection(
         &mut self,
@@ -126,17 +145,67 @@ impl Camera {
         near: f32,
         far: f32,
     ) -> &mut Self {
-        let proj = inox_math::perspective(fov_in_degrees, screen_width / screen_height, near, far);
+        let aspect_ratio = screen_width / screen_height;
+        let proj = if self.is_orthographic {
+            inox_math::orthographic(self.ymag * aspect_ratio, self.ymag, near, far)
+        } else {
+            inox_math::perspective(fov_in_degrees, aspect_ratio, near, far)
+        };
 
         self.proj = proj;
 
         self.fov_in_degrees = fov_in_degrees;
-        self.aspect_ratio = screen_width / screen_height;
+        self.aspect_ratio = aspect_ratio;
         self.near_plane = near;
         self.far_plane = far;
 
         self
     }
+    // Takes fov or height and planes from a projection while keeping current aspect ratio
+    #[inline]
+    pub fn set_projection_from_matrix(&mut self, proj: Matrix4) -> &mut Self {
+        if proj.w.w == 1. {
+            self.is_orthographic = true;
+            self.ymag = 1. / proj.y.y;
+            let near = (proj.w.z + 1.) / proj.z.z;
+            let far = (proj.w.z - 1.) / proj.z.z;
+            return self.set_projection(self.fov_in_degrees, self.aspect_ratio, 1., near, far);
+        }
+        if proj.w.w != 0. {
+            // Neither a perspective nor an orthographic projection
+            self.proj = proj;
+            return self;
+        }
+        self.is_orthographic = false;
+        let fov_in_degrees: Degrees = Radians::new(2. * (1. / proj.y.y).atan()).into();
+        let near = proj.w.z / (proj.z.z - 1.);
+        let far = proj.w.z / (proj.z.z + 1.);
+        self.set_projection(fov_in_degrees, self.aspect_ratio, 1., near, far)
+    }
+    // ymag is half height of the view volume
+    #[inline]
+    pub fn set_orthographic(&mut self, ymag: f32) -> &mut Self {
+        self.is_orthographic = true;
+        self.ymag = ymag;
+        self.set_projection(
+            self.fov_in_degrees,
+            self.aspect_ratio,
+            1.,
+            self.near_plane,
+            self.far_plane,
+        )
+    }
+    #[inline]
+    pub fn set_perspective(&mut self) -> &mut Self {
+        self.is_orthographic = false;
+        self.set_projection(
+            self.fov_in_degrees,
+            self.aspect_ratio,
+            1.,
+            self.near_plane,
+            self.far_plane,
+        )
+    }
     #[inline]
     pub fn set_transform(&mut self, transform: Matrix4) -> &mut Self {
         if let Some(parent) = &self.parent {
@@ -229,7 +298,72 @@ impl Camera {
         self.far_plane
     }
 
+    #[inline]
+    pub fn is_orthographic(&self) -> bool {
+        self.is_orthographic
+    }
+
+    #[inline]
+    pub fn xmag(&self) -> f32 {
+        self.ymag * self.aspect_ratio
+    }
+
+    #[inline]
+    pub fn ymag(&self) -> f32 {
+        self.ymag
+    }
+
     pub fn convert_in_3d(&self, normalized_pos: Vector2) -> (Vector3, Vector3) {
         convert_in_3d(normalized_pos, &self.transform(), &self.proj_matrix())
     }
 }
+
+#[test]
+fn test_projection_from_matrix() {
+    use inox_messenger::MessageHub;
+    use inox_resources::SharedData;
+    use inox_uid::generate_random_uid;
+    use std::sync::Arc;
+
+    let message_hub = Arc::new(MessageHub::default());
+    let shared_data = Arc::new(SharedData::default());
+    let mut camera = Camera::new(generate_random_uid(), &shared_data, &message_hub);
+    camera.set_projection(Degrees::new(45.), 16., 9., 0.1, 100.);
+
+    let is_near = |a: f32, b: f32| (a - b).abs() <= 0.001 * b.abs().max(1.);
+    let is_same_matrix = |a: Matrix4, b: Matrix4| {
+        let (a, b): ([[f32; 4]; 4], [[f32; 4]; 4]) = (a.into(), b.into());
+        a.iter()
+            .flatten()
+            .zip(b.iter().flatten())
+            .all(|(a, b)| is_near(*a, *b))
+    };
+
+    // Aspect ratio is kept, so the width of the view volume follows the height
+    let orthographic = inox_math::orthographic(8., 4.5, 1., 50.);
+    camera.set_projection_from_matrix(orthographic);
+    assert!(camera.is_orthographic());
+    assert!(is_near(camera.ymag(), 4.5));
+    assert!(is_near(camera.xmag(), 8.));
+    assert!(is_near(camera.near_plane(), 1.));
+    assert!(is_near(camera.far_plane(), 50.));
+    assert!(is_same_matrix(camera.proj_matrix(), orthographic));
+    assert!(is_near(camera.fov_in_degrees().0, 45.));
+
+    let perspective = inox_math::perspective(Degrees::new(60.), 16. / 9., 0.5, 200.);
+    camera.set_projection_from_matrix(perspective);
+    assert!(!camera.is_orthographic());
+    assert!(is_near(camera.fov_in_degrees().0, 60.));
+    assert!(is_near(camera.near_plane(), 0.5));
+    assert!(is_near(camera.far_plane(), 200.));
+    assert!(is_same_matrix(camera.proj_matrix(), perspective));
+    assert!(is_near(camera.ymag(), 4.5));
+
+    // Neither perspective nor orthographic: only the matrix is kept
+    let mut other = Matrix4::default_identity();
+    other.w.w = 2.;
+    camera.set_projection_from_matrix(other);
+    assert!(!camera.is_orthographic());
+    assert!(is_same_matrix(camera.proj_matrix(), other));
+    assert!(is_near(camera.near_plane(), 0.5));
+}
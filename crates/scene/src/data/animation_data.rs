@@ -0,0 +1,186 @@
+use inox_math::{Quaternion, Vector4};
+use inox_serialize::{Deserialize, Serialize, SerializeFile};
+
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(crate = "inox_serialize")]
+pub enum AnimationInterpolation {
+    #[default]
+    Linear,
+    Step,
+    CubicSpline,
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(crate = "inox_serialize")]
+pub enum AnimationProperty {
+    #[default]
+    Translation,
+    Rotation,
+    Scale,
+    MorphWeight(u32), // weight of the morph target with the given index, stored in x
+}
+
+// Translation and scale use xyz, rotation is a quaternion stored as xyzw.
+// With CubicSpline interpolation every key has 3 values: in tangent, value, out tangent
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct AnimationChannelData {
+    pub property: AnimationProperty,
+    pub interpolation: AnimationInterpolation,
+    pub times: Vec<f32>,
+    pub values: Vec<Vector4>,
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct AnimationData {
+    pub name: String,
+    pub duration: f32,
+    pub channels: Vec<AnimationChannelData>,
+}
+
+impl SerializeFile for AnimationData {
+    fn extension() -> &'static str {
+        "animation"
+    }
+}
+
+impl AnimationChannelData {
+    fn value(&self, key: usize) -> Vector4 {
+        match self.interpolation {
+            AnimationInterpolation::CubicSpline => self.values[key * 3 + 1],
+            _ => self.values[key],
+        }
+    }
+
+    pub fn sample(&self, time: f32) -> Vector4 {
+        if self.times.is_empty() {
+            return Vector4::new(0., 0., 0., 1.);
+        }
+        let last = self.times.len() - 1;
+        if time <= self.times[0] {
+            return self.value(0);
+        }
+        if time >= self.times[last] {
+            return self.value(last);
+        }
+        let next = self.times.partition_point(|t| *t <= time);
+        let prev = next - 1;
+        let dt = self.times[next] - self.times[prev];
+        let t = if dt > 0. {
+            (time - self.times[prev]) / dt
+        } else {
+            0.
+        };
+        match self.interpolation {
+            AnimationInterpolation::Step => self.value(prev),
+            AnimationInterpolation::Linear => {
+                if self.property == AnimationProperty::Rotation {
+                    let a = to_quaternion(self.value(prev));
+                    let b = to_quaternion(self.value(next));
+                    from_quaternion(a.slerp(b, t))
+                } else {
+                    self.value(prev) * (1. - t) + self.value(next) * t
+                }
+            }
+            AnimationInterpolation::CubicSpline => {
+                let t2 = t * t;
+                let t3 = t2 * t;
+                let v0 = self.values[prev * 3 + 1];
+                let b0 = self.values[prev * 3 + 2];
+                let a1 = self.values[next * 3];
+                let v1 = self.values[next * 3 + 1];
+                let v = v0 * (2. * t3 - 3. * t2 + 1.)
+                    + b0 * (dt * (t3 - 2. * t2 + t))
+                    + v1 * (-2. * t3 + 3. * t2)
+                    + a1 * (dt * (t3 - t2));
+                // Hermite interpolation doesn't keep quaternions unit length
+                if self.property == AnimationProperty::Rotation {
+                    let length = (v.x * v.x + v.y * v.y + v.z * v.z + v.w * v.w).sqrt();
+                    if length > 0. {
+                        return v / length;
+                    }
+                }
+                v
+            }
+        }
+    }
+}
+
+pub fn to_quaternion(v: Vector4) -> Quaternion {
+    Quaternion::new(v.w, v.x, v.y, v.z)
+}
+
+pub fn from_quaternion(q: Quaternion) -> Vector4 {
+    Vector4::new(q.v.x, q.v.y, q.v.z, q.s)
+}
+
+#[test]
+fn test_animation_sampling() {
+    let channel = |property, interpolation, values: Vec<Vector4>| AnimationChannelData {
+        property,
+        interpolation,
+        times: vec![0., 1., 3.],
+        values,
+    };
+    let keys = vec![
+        Vector4::new(0., 0., 0., 0.),
+        Vector4::new(2., 0., 0., 0.),
+        Vector4::new(2., 4., 0., 0.),
+    ];
+
+    let linear = channel(
+        AnimationProperty::Translation,
+        AnimationInterpolation::Linear,
+        keys.clone(),
+    );
+    assert_eq!(linear.sample(-1.), keys[0]);
+    assert_eq!(linear.sample(0.5), Vector4::new(1., 0., 0., 0.));
+    assert_eq!(linear.sample(2.), Vector4::new(2., 2., 0., 0.));
+    assert_eq!(linear.sample(5.), keys[2]);
+
+    let step = channel(
+        AnimationProperty::Scale,
+        AnimationInterpolation::Step,
+        keys.clone(),
+    );
+    assert_eq!(step.sample(0.99), keys[0]);
+    assert_eq!(step.sample(1.5), keys[1]);
+
+    // With zero tangents a cubic spline passes through keys with zero velocity
+    let zero = Vector4::new(0., 0., 0., 0.);
+    let cubic = channel(
+        AnimationProperty::Translation,
+        AnimationInterpolation::CubicSpline,
+        keys.iter().flat_map(|k| [zero, *k, zero]).collect(),
+    );
+    assert_eq!(cubic.sample(1.), keys[1]);
+    assert_eq!(cubic.sample(0.5), Vector4::new(1., 0., 0., 0.));
+
+    let half_turn = std::f32::consts::FRAC_1_SQRT_2;
+    let rotation = AnimationChannelData {
+        property: AnimationProperty::Rotation,
+        interpolation: AnimationInterpolation::Linear,
+        times: vec![0., 1.],
+        values: vec![
+            Vector4::new(0., 0., 0., 1.),
+            Vector4::new(0., half_turn, 0., half_turn),
+        ],
+    };
+    let q = rotation.sample(0.5);
+    let quarter_turn = (std::f32::consts::PI / 8.).sin();
+    assert!((q.y - quarter_turn).abs() < 1e-5);
+    assert!((q.w - (std::f32::consts::PI / 8.).cos()).abs() < 1e-5);
+
+    let cubic_rotation = AnimationChannelData {
+        interpolation: AnimationInterpolation::CubicSpline,
+        values: rotation
+            .values
+            .iter()
+            .flat_map(|k| [zero, *k, zero])
+            .collect(),
+        ..rotation
+    };
+    let q = cubic_rotation.sample(0.5);
+    assert!(((q.x * q.x + q.y * q.y + q.z * q.z + q.w * q.w) - 1.).abs() < 1e-5);
+}
@@ -10,6 +10,10 @@ pub struct CameraData {
     pub near: f32,
     pub far: f32,
     pub fov: Degrees,
+    // When orthographic, xmag and ymag are half width and half height of the view volume
+    pub is_orthographic: bool,
+    pub xmag: f32,
+    pub ymag: f32,
 }
 
 impl SerializeFile for CameraData {
@@ -25,6 +29,9 @@ impl Default for CameraData {
             near: DEFAULT_NEAR,
             far: DEFAULT_FAR,
             fov: Degrees::new(DEFAULT_FOV),
+            is_orthographic: false,
+            xmag: 1.,
+            ymag: 1.,
         }
     }
 }
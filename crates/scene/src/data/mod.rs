@@ -1,7 +1,11 @@
+pub use animation_data::*;
 pub use camera_data::*;
 pub use object_data::*;
 pub use scene_data::*;
+pub use skin_data::*;
 
+pub mod animation_data;
 pub mod camera_data;
 pub mod object_data;
 pub mod scene_data;
+pub mod skin_data;
@@ -7,6 +7,8 @@ pub struct SceneData {
     pub objects: Vec<PathBuf>,
     pub cameras: Vec<PathBuf>,
     pub lights: Vec<PathBuf>,
+    // Compiled environment used for image based lighting, render config one when empty
+    pub environment: PathBuf,
 }
 
 impl SerializeFile for SceneData {
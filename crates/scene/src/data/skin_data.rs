@@ -0,0 +1,18 @@
+use std::path::PathBuf;
+
+use inox_math::Matrix4;
+use inox_serialize::{Deserialize, Serialize, SerializeFile};
+
+// Joints are object paths, in the same order of the joint indices of skinned meshes
+#[derive(Default, Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(crate = "inox_serialize")]
+pub struct SkinData {
+    pub inverse_bind_matrices: Vec<Matrix4>,
+    pub joints: Vec<PathBuf>,
+}
+
+impl SerializeFile for SkinData {
+    fn extension() -> &'static str {
+        "skin"
+    }
+}
@@ -5,17 +5,23 @@ use inox_resources::SharedDataRc;
 
 pub use crate::data::*;
 
+pub use crate::animation::*;
+pub use crate::animation_player::*;
 pub use crate::camera::*;
 pub use crate::object::*;
 pub use crate::scene::*;
 pub use crate::script::*;
+pub use crate::skin::*;
 pub use crate::systems::*;
 
+pub mod animation;
+pub mod animation_player;
 pub mod camera;
 pub mod data;
 pub mod object;
 pub mod scene;
 pub mod script;
+pub mod skin;
 pub mod systems;
 
 pub fn register_resource_types(shared_data: &SharedDataRc, message_hub: &MessageHubRc) {
@@ -23,6 +29,9 @@ pub fn register_resource_types(shared_data: &SharedDataRc, message_hub: &Message
     shared_data.register_type_serializable::<Camera>(message_hub);
     shared_data.register_type_serializable::<Script>(message_hub);
     shared_data.register_type_serializable::<Scene>(message_hub);
+    shared_data.register_type_serializable::<Animation>(message_hub);
+    shared_data.register_type::<AnimationPlayer>(message_hub);
+    shared_data.register_type_serializable::<Skin>(message_hub);
 }
 
 pub fn unregister_resource_types(shared_data: &SharedDataRc, message_hub: &MessageHubRc) {
@@ -30,4 +39,7 @@ pub fn unregister_resource_types(shared_data: &SharedDataRc, message_hub: &Messa
     shared_data.unregister_type_serializable::<Camera>(message_hub);
     shared_data.unregister_type_serializable::<Script>(message_hub);
     shared_data.unregister_type_serializable::<Scene>(message_hub);
+    shared_data.unregister_type_serializable::<Animation>(message_hub);
+    shared_data.unregister_type::<AnimationPlayer>(message_hub);
+    shared_data.unregister_type_serializable::<Skin>(message_hub);
 }
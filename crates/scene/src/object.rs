@@ -15,7 +15,7 @@ use inox_serialize::{read_from_file, SerializationType, SerializeFile};
 use inox_ui::{CollapsingHeader, UIProperties, UIPropertiesRegistry, Ui};
 use inox_uid::generate_random_uid;
 
-use crate::{Camera, ObjectData, Script};
+use crate::{Animation, AnimationPlayer, Camera, ObjectData, Script, Skin};
 
 pub type ComponentId = ResourceId;
 pub type ObjectId = ResourceId;
@@ -25,6 +25,9 @@ pub struct Object {
     id: ObjectId,
     filepath: PathBuf,
     message_hub: MessageHubRc,
+    // Transform relative to the parent, the one that is moved or animated
+    local_transform: Matrix4,
+    // World transform, computed from the parent one when dirty
     transform: Matrix4,
     parent: Handle<Object>,
     is_transform_dirty: bool,
@@ -107,6 +110,7 @@ impl DataTypeResource for Object {
             id,
             filepath: PathBuf::new(),
             message_hub: message_hub.clone(),
+            local_transform: Matrix4::default_identity(),
             transform: Matrix4::default_identity(),
             parent: None,
             is_transform_dirty: true,
@@ -122,6 +126,7 @@ impl DataTypeResource for Object {
         object_data: &Self::DataType,
     ) -> Self {
         let mut object = Self::new(id, shared_data, message_hub);
+        object.local_transform = object_data.transform;
         object.transform = object_data.transform;
 
         object_data.components.iter().for_each(|component_path| {
@@ -172,6 +177,29 @@ impl DataTypeResource for Object {
                     }),
                 );
                 object.add_component::<Script>(script);
+            } else if <Animation as SerializableResource>::is_matching_extension(path) {
+                let animation = Animation::request_load(shared_data, message_hub, path, None);
+                let player =
+                    if let Some(player) = object.components_of_type::<AnimationPlayer>().pop() {
+                        player
+                    } else {
+                        let player = object
+                            .add_default_component::<AnimationPlayer>(shared_data, message_hub);
+                        player.get_mut().set_object(&id, object.local_transform);
+                        player
+                    };
+                player.get_mut().add_animation(animation);
+            } else if <Skin as SerializableResource>::is_matching_extension(path) {
+                let object_id = id;
+                let skin = Skin::request_load(
+                    shared_data,
+                    message_hub,
+                    path,
+                    OnCreateData::create(move |skin: &mut Skin| {
+                        skin.set_object(&object_id);
+                    }),
+                );
+                object.add_component::<Skin>(skin);
             }
         });
 
@@ -195,49 +223,55 @@ impl DataTypeResource for Object {
 }
 
 impl Object {
+    // Sets the transform relative to the parent
     #[inline]
     pub fn set_transform(&mut self, transform: Matrix4) -> &mut Self {
-        self.transform = transform;
+        self.local_transform = transform;
         self.mark_as_dirty();
         self
     }
     #[inline]
+    pub fn local_transform(&self) -> Matrix4 {
+        self.local_transform
+    }
+    // World transform resolved by the last update
+    #[inline]
     pub fn transform(&self) -> Matrix4 {
         self.transform
     }
     #[inline]
     pub fn set_position(&mut self, position: Vector3) -> &mut Self {
-        self.transform.set_translation(position);
+        self.local_transform.set_translation(position);
         self.mark_as_dirty();
         self
     }
     #[inline]
     pub fn translate(&mut self, translation: Vector3) -> &mut Self {
-        self.transform.add_translation(translation);
+        self.local_transform.add_translation(translation);
         self.mark_as_dirty();
         self
     }
     #[inline]
     pub fn rotate(&mut self, roll_yaw_pitch: Vector3) -> &mut Self {
-        self.transform.add_rotation(roll_yaw_pitch);
+        self.local_transform.add_rotation(roll_yaw_pitch);
         self.mark_as_dirty();
         self
     }
     #[inline]
     pub fn scale(&mut self, scale: Vector3) -> &mut Self {
-        self.transform.add_scale(scale);
+        self.local_transform.add_scale(scale);
         self.mark_as_dirty();
         self
     }
     #[inline]
     pub fn look_at(&mut self, position: Vector3) -> &mut Self {
-        self.transform.look_at(position);
+        self.local_transform.look_at(position);
         self.mark_as_dirty();
         self
     }
     #[inline]
     pub fn look_towards(&mut self, direction: Vector3) -> &mut Self {
-        self.transform.look_towards(direction);
+        self.local_transform.look_towards(direction);
         self.mark_as_dirty();
         self
     }
@@ -274,7 +308,7 @@ impl Object {
     }
 
     #[inline]
-    fn set_parent(&mut self, parent: Handle<Object>) {
+    pub(crate) fn set_parent(&mut self, parent: Handle<Object>) {
         self.parent = parent;
         self.mark_as_dirty();
     }
@@ -386,13 +420,25 @@ impl Object {
     pub fn update_transform(&mut self, parent_transform: Option<Matrix4>) -> Matrix4 {
         if self.is_dirty() {
             self.is_transform_dirty = false;
-            if let Some(parent_transform) = parent_transform {
-                self.transform = parent_transform * self.transform;
-            }
+            self.transform = parent_transform.map_or(self.local_transform, |parent_transform| {
+                parent_transform * self.local_transform
+            });
             self.components_of_type::<Light>().iter().for_each(|light| {
                 light.get_mut().set_position(self.position());
             });
         }
         self.transform
     }
+
+    // Dirty parents are updated first, so that the world transform uses their current one
+    pub fn update_world_transform(object: &Resource<Object>) -> Matrix4 {
+        let parent_transform = object.get().parent().map(|parent| {
+            if parent.get().is_dirty() {
+                Self::update_world_transform(&parent)
+            } else {
+                parent.get().transform()
+            }
+        });
+        object.get_mut().update_transform(parent_transform)
+    }
 }
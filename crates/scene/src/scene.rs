@@ -1,8 +1,10 @@
 use std::path::{Path, PathBuf};
 
 use inox_messenger::MessageHubRc;
+use inox_render::Environment;
 use inox_resources::{
-    DataTypeResource, Resource, ResourceId, ResourceTrait, SerializableResource, SharedDataRc,
+    DataTypeResource, Handle, Resource, ResourceId, ResourceTrait, SerializableResource,
+    SharedDataRc,
 };
 use inox_serialize::{read_from_file, SerializationType, SerializeFile};
 use inox_ui::{CollapsingHeader, UIProperties, UIPropertiesRegistry, Ui};
@@ -16,6 +18,7 @@ pub struct Scene {
     filepath: PathBuf,
     objects: Vec<Resource<Object>>,
     cameras: Vec<Resource<Camera>>,
+    environment: Handle<Environment>,
 }
 
 impl UIProperties for Scene {
@@ -76,6 +79,7 @@ impl DataTypeResource for Scene {
             filepath: PathBuf::new(),
             objects: Vec::new(),
             cameras: Vec::new(),
+            environment: None,
         }
     }
 
@@ -97,6 +101,16 @@ impl DataTypeResource for Scene {
             scene.add_camera(c);
         }
 
+        if !scene_data.environment.as_os_str().is_empty() {
+            let e = Environment::request_load(
+                shared_data,
+                message_hub,
+                scene_data.environment.as_path(),
+                None,
+            );
+            scene.set_environment(e);
+        }
+
         scene
     }
 }
@@ -109,6 +123,15 @@ impl Scene {
     pub fn clear(&mut self) {
         self.objects.clear();
         self.cameras.clear();
+        self.environment = None;
+    }
+
+    pub fn set_environment(&mut self, environment: Resource<Environment>) {
+        self.environment = Some(environment);
+    }
+
+    pub fn environment(&self) -> &Handle<Environment> {
+        &self.environment
     }
 
     pub fn add_camera(&mut self, camera: Resource<Camera>) {
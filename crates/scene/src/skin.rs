@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+use inox_filesystem::convert_from_local_path;
+use inox_math::{Mat4Ops, MatBase, Matrix4};
+use inox_messenger::MessageHubRc;
+use inox_render::Mesh;
+use inox_resources::{
+    Data, DataTypeResource, ResourceId, ResourceTrait, SerializableResource, SharedDataRc,
+};
+use inox_serialize::{read_from_file, SerializationType, SerializeFile};
+use inox_uid::generate_uid_from_string;
+
+use crate::{Object, ObjectId, SkinData};
+
+pub type SkinId = ResourceId;
+
+// Joint objects are loaded with the scene hierarchy:
+// the skin only looks them up through the ids generated from their paths
+#[derive(Clone)]
+pub struct Skin {
+    filepath: PathBuf,
+    object_id: ObjectId,
+    joints: Vec<ObjectId>,
+    inverse_bind_matrices: Vec<Matrix4>,
+    is_initialized: bool,
+}
+
+impl SerializableResource for Skin {
+    fn path(&self) -> &Path {
+        self.filepath.as_path()
+    }
+
+    fn set_path(&mut self, path: &Path) -> &mut Self {
+        self.filepath = path.to_path_buf();
+        self
+    }
+
+    fn extension() -> &'static str {
+        SkinData::extension()
+    }
+
+    fn deserialize_data(path: &std::path::Path, f: Box<dyn FnMut(Self::DataType) + 'static>) {
+        read_from_file::<Self::DataType>(path, SerializationType::Binary, f);
+    }
+}
+
+impl ResourceTrait for Skin {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+    fn invalidate(&mut self) -> &mut Self {
+        self.is_initialized = false;
+        self
+    }
+}
+
+impl DataTypeResource for Skin {
+    type DataType = SkinData;
+
+    fn new(_id: ResourceId, _shared_data: &SharedDataRc, _message_hub: &MessageHubRc) -> Self {
+        Self {
+            filepath: PathBuf::new(),
+            object_id: ObjectId::default(),
+            joints: Vec::new(),
+            inverse_bind_matrices: Vec::new(),
+            is_initialized: false,
+        }
+    }
+
+    fn create_from_data(
+        shared_data: &SharedDataRc,
+        message_hub: &MessageHubRc,
+        id: SkinId,
+        data: &Self::DataType,
+    ) -> Self {
+        let mut skin = Self::new(id, shared_data, message_hub);
+        let data_folder = Data::platform_data_folder();
+        skin.joints = data
+            .joints
+            .iter()
+            .map(|p| {
+                let path = convert_from_local_path(data_folder.as_path(), p.as_path());
+                generate_uid_from_string(path.to_str().unwrap())
+            })
+            .collect();
+        skin.inverse_bind_matrices = data.inverse_bind_matrices.clone();
+        skin.is_initialized = true;
+        skin
+    }
+}
+
+impl Skin {
+    #[inline]
+    pub fn set_object(&mut self, object_id: &ObjectId) -> &mut Self {
+        self.object_id = *object_id;
+        self
+    }
+    #[inline]
+    pub fn object_id(&self) -> &ObjectId {
+        &self.object_id
+    }
+    #[inline]
+    pub fn joints(&self) -> &[ObjectId] {
+        &self.joints
+    }
+
+    // Joint matrices move vertices from the bind pose to the current pose in mesh space
+    pub fn update(&self, shared_data: &SharedDataRc) {
+        if !self.is_initialized {
+            return;
+        }
+        let Some(object) = shared_data.get_resource::<Object>(&self.object_id) else {
+            return;
+        };
+        let inverse_object_transform = object.get().transform().inverse();
+        let mut joint_matrices = Vec::with_capacity(self.joints.len());
+        for (i, joint_id) in self.joints.iter().enumerate() {
+            let Some(joint) = shared_data.get_resource::<Object>(joint_id) else {
+                return;
+            };
+            let inverse_bind_matrix = self
+                .inverse_bind_matrices
+                .get(i)
+                .copied()
+                .unwrap_or(Matrix4::default_identity());
+            joint_matrices
+                .push(inverse_object_transform * joint.get().transform() * inverse_bind_matrix);
+        }
+        object
+            .get()
+            .components_of_type::<Mesh>()
+            .iter()
+            .for_each(|mesh| {
+                mesh.get_mut().set_joint_matrices(&joint_matrices);
+            });
+    }
+}
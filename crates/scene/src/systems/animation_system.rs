@@ -0,0 +1,62 @@
+use inox_core::{implement_unique_system_uid, ContextRc, System};
+use inox_render::Mesh;
+
+use crate::{AnimationPlayer, Object, Skin};
+
+pub struct AnimationSystem {
+    context: ContextRc,
+}
+
+implement_unique_system_uid!(AnimationSystem);
+
+impl System for AnimationSystem {
+    fn read_config(&mut self, _plugin_name: &str) {}
+    fn should_run_when_not_focused(&self) -> bool {
+        false
+    }
+
+    fn init(&mut self) {}
+
+    fn run(&mut self) -> bool {
+        inox_profiler::scoped_profile!("animation_system::run");
+
+        let dt = self.context.global_timer().dt().as_secs_f32();
+        let shared_data = self.context.shared_data();
+        // Skins use the world transforms resolved in the previous frame
+        shared_data.for_each_resource(|_, s: &Skin| {
+            s.update(shared_data);
+        });
+        shared_data.for_each_resource_mut(|_, p: &mut AnimationPlayer| {
+            if let Some(transform) = p.update(dt) {
+                if let Some(object) = shared_data.get_resource::<Object>(p.object_id()) {
+                    object.get_mut().set_transform(transform);
+                    if !p.morph_weights().is_empty() {
+                        object
+                            .get()
+                            .components_of_type::<Mesh>()
+                            .iter()
+                            .for_each(|mesh| {
+                                let mut weights = mesh.get().morph_weights().to_vec();
+                                p.morph_weights().iter().for_each(|&(i, w)| {
+                                    if let Some(weight) = weights.get_mut(i) {
+                                        *weight = w;
+                                    }
+                                });
+                                mesh.get_mut().set_morph_weights(&weights);
+                            });
+                    }
+                }
+            }
+        });
+        true
+    }
+    fn uninit(&mut self) {}
+}
+
+impl AnimationSystem {
+    pub fn new(context: &ContextRc) -> Self {
+        Self {
+            context: context.clone(),
+        }
+    }
+}
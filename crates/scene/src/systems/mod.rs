@@ -1,5 +1,7 @@
+pub use animation_system::*;
 pub use object_system::*;
 pub use script_system::*;
 
+pub mod animation_system;
 pub mod object_system;
 pub mod script_system;
@@ -1,7 +1,6 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 
 use inox_core::{implement_unique_system_uid, ContextRc, System};
-use inox_math::{MatBase, Matrix4};
 use inox_messenger::Listener;
 use inox_resources::{ResourceEvent, SharedDataRc};
 
@@ -10,7 +9,7 @@ use crate::{Object, ObjectId};
 pub struct ObjectSystem {
     shared_data: SharedDataRc,
     listener: Listener,
-    map: HashMap<ObjectId, Matrix4>,
+    changed: HashSet<ObjectId>,
 }
 
 implement_unique_system_uid!(ObjectSystem);
@@ -30,11 +29,10 @@ impl System for ObjectSystem {
 
         self.update_events();
 
-        self.map.retain(|id, m| {
-            if let Some(o) = self.shared_data.get_resource::<Object>(id) {
-                o.get_mut().update_transform(Some(*m));
+        self.changed.drain().for_each(|id| {
+            if let Some(o) = self.shared_data.get_resource::<Object>(&id) {
+                Object::update_world_transform(&o);
             }
-            false
         });
 
         true
@@ -49,21 +47,14 @@ impl ObjectSystem {
         Self {
             shared_data: context.shared_data().clone(),
             listener: Listener::new(context.message_hub()),
-            map: HashMap::new(),
+            changed: HashSet::new(),
         }
     }
     fn update_events(&mut self) {
         inox_profiler::scoped_profile!("object_system::update_events");
         self.listener.process_messages(|e: &ResourceEvent<Object>| {
             if let ResourceEvent::Changed(id) = e {
-                if let Some(object) = self.shared_data.get_resource::<Object>(id) {
-                    let parent_transform = object
-                        .get()
-                        .parent()
-                        .map(|parent| parent.get().transform())
-                        .unwrap_or_else(Matrix4::default_identity);
-                    self.map.insert(*id, parent_transform);
-                }
+                self.changed.insert(*id);
             }
         });
     }
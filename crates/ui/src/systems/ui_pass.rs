@@ -47,6 +47,8 @@ pub struct UIInstance {
     pub index_count: u32,
     pub vertex_start: u32,
     pub texture_index: u32,
+    // FONT_ATLAS_NONE when the texture is not a font atlas
+    pub font_atlas: u32,
 }
 
 impl UIInstance {
@@ -57,6 +59,7 @@ impl UIInstance {
         layout_builder.add_attribute::<u32>(VertexFormat::Uint32.into());
         layout_builder.add_attribute::<u32>(VertexFormat::Uint32.into());
         layout_builder.add_attribute::<u32>(VertexFormat::Uint32.into());
+        layout_builder.add_attribute::<u32>(VertexFormat::Uint32.into());
         layout_builder
     }
 }
@@ -17,7 +17,7 @@ use inox_core::{
     SystemUID,
 };
 
-use inox_render::{Texture, TextureData, TextureFormat, TextureUsage};
+use inox_render::{Font, Texture, TextureData, TextureFormat, TextureUsage, FONT_ATLAS_NONE};
 
 use inox_log::debug_log;
 use inox_messenger::{Listener, MessageHubRc};
@@ -73,6 +73,17 @@ impl UISystem {
         let mut indices: Vec<u32> = Vec::new();
         let mut instances: Vec<UIInstance> = Vec::new();
 
+        // Font atlases drawn as user textures are decoded by the shader
+        let mut font_atlases = HashMap::new();
+        self.shared_data.for_each_resource(|_, font: &Font| {
+            if let Some(texture) = font.texture() {
+                font_atlases.insert(
+                    texture.get().texture_index(),
+                    font.font_data().shader_atlas_info(),
+                );
+            }
+        });
+
         for primitive in primitives.into_iter() {
             if let Primitive::Mesh(mesh) = primitive.primitive {
                 if mesh.vertices.is_empty() || mesh.indices.is_empty() {
@@ -92,6 +103,10 @@ impl UISystem {
                     index_count: mesh.indices.len() as _,
                     vertex_start: vertices.len() as _,
                     texture_index: texture_index as _,
+                    font_atlas: font_atlases
+                        .get(&texture_index)
+                        .copied()
+                        .unwrap_or(FONT_ATLAS_NONE),
                 });
                 vertices.extend_from_slice(to_slice(mesh.vertices.as_slice()));
                 indices.extend_from_slice(&mesh.indices);
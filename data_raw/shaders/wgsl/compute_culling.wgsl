@@ -91,6 +91,11 @@ fn compute_bounds_error(sphere: vec4<f32>, error: f32, position: vec3<f32>, orie
     let sphere_world_space = (model_transform * vec4(sphere.xyz, 1.0)).xyz;
     let radius_world_space = world_scale * sphere.w;
 
+    // Orthographic projection: projected size does not depend on distance, only on scale
+    if (constant_data.proj[3][3] == 1.) {
+        return error * world_scale * constant_data.proj[1][1] * 0.5 * constant_data.screen_height;
+    }
+
     var view_pos = culling_data.inverse_view_proj * vec4<f32>(0., 0., 0.,1.);
     view_pos /= view_pos.w;
     let dir = sphere_world_space - view_pos.xyz;
@@ -0,0 +1,72 @@
+#import "common.inc"
+#import "utils.inc"
+
+@group(0) @binding(0)
+var<storage, read> morphs: Morphs;
+@group(0) @binding(1)
+var<storage, read> morph_vertices: MorphVertices;
+@group(0) @binding(2)
+var<storage, read> morph_deltas: MorphDeltas;
+@group(0) @binding(3)
+var<storage, read> morph_weights: MorphWeights;
+@group(0) @binding(4)
+var<storage, read> skins: Skins;
+@group(0) @binding(5)
+var<storage, read_write> skin_vertices: SkinVertices;
+@group(0) @binding(6)
+var<storage, read> meshes: Meshes;
+@group(0) @binding(7)
+var<storage, read_write> vertices_positions: VerticesPositions;
+@group(0) @binding(8)
+var<storage, read_write> vertices_attributes: VerticesAttributes;
+
+
+@compute
+@workgroup_size(256, 1, 1)
+fn main(
+    @builtin(local_invocation_id) local_invocation_id: vec3<u32>, 
+    @builtin(local_invocation_index) local_invocation_index: u32, 
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>, 
+    @builtin(workgroup_id) workgroup_id: vec3<u32>
+) {
+    let id = global_invocation_id.x;
+    if (id >= arrayLength(&morph_vertices.data)) {
+        return;
+    }
+    let morph_vertex = morph_vertices.data[id];
+    let morph = morphs.data[morph_vertex.morph_index];
+    let mesh = meshes.data[morph.mesh_index];
+    let local_index = id - morph.morph_vertices_offset;
+
+    let size = max(morph.aabb_max - morph.aabb_min, vec3<f32>(1e-6));
+    var p = morph.aabb_min + unpack_unorm_to_3_f32(morph_vertex.position) * size;
+    var n = unpack_snorm_to_3_f32(morph_vertex.normal);
+    for (var t = 0u; t < morph.targets_count; t++) {
+        let w = morph_weights.data[morph.weights_offset + t];
+        if (w != 0.) {
+            let delta = morph_deltas.data[morph.deltas_offset + t * morph.vertices_count + local_index];
+            p += delta.position.xyz * w;
+            n += delta.normal.xyz * w;
+        }
+    }
+    // Mesh bounds contain the vertices morphed with weights in the range of each target,
+    // others are clamped to them: the same bounds are the bind pose ones of skins
+    let position = pack_3_f32_to_unorm(clamp((p - morph.aabb_min) / size, vec3<f32>(0.), vec3<f32>(1.)));
+    let normal = pack_3_f32_to_snorm(normalize(n));
+
+    let skin = skins.data[morph.skin_index];
+    skin_vertices.data[skin.skin_vertices_offset + local_index].position = position;
+    skin_vertices.data[skin.skin_vertices_offset + local_index].normal = normal;
+    // Until the skin is animated the skinning pass keeps the mesh vertices untouched
+    if (i32(skin.joints_offset) >= 0) {
+        return;
+    }
+    vertices_positions.data[mesh.vertices_position_offset + local_index] = position;
+
+    let vertex_layout = mesh.flags_and_vertices_attribute_layout & 0x0000FFFFu;
+    let normal_offset = vertex_attribute_offset(vertex_layout, VERTEX_ATTRIBUTE_HAS_NORMAL);
+    if (normal_offset >= 0) {
+        let index = mesh.vertices_attribute_offset + local_index * vertex_layout_stride(vertex_layout) + u32(normal_offset);
+        vertices_attributes.data[index] = normal;
+    }
+}
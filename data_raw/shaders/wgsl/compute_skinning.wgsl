@@ -0,0 +1,72 @@
+#import "common.inc"
+#import "utils.inc"
+
+@group(0) @binding(0)
+var<storage, read> skins: Skins;
+@group(0) @binding(1)
+var<storage, read> skin_vertices: SkinVertices;
+@group(0) @binding(2)
+var<storage, read> joint_matrices: JointMatrices;
+@group(0) @binding(3)
+var<storage, read> meshes: Meshes;
+@group(0) @binding(4)
+var<storage, read_write> vertices_positions: VerticesPositions;
+@group(0) @binding(5)
+var<storage, read_write> vertices_attributes: VerticesAttributes;
+
+
+@compute
+@workgroup_size(256, 1, 1)
+fn main(
+    @builtin(local_invocation_id) local_invocation_id: vec3<u32>, 
+    @builtin(local_invocation_index) local_invocation_index: u32, 
+    @builtin(global_invocation_id) global_invocation_id: vec3<u32>, 
+    @builtin(workgroup_id) workgroup_id: vec3<u32>
+) {
+    let id = global_invocation_id.x;
+    if (id >= arrayLength(&skin_vertices.data)) {
+        return;
+    }
+    let skin_vertex = skin_vertices.data[id];
+    let skin = skins.data[skin_vertex.skin_index];
+    // Joint matrices are not uploaded until the skin is animated: keep the bind pose
+    if (i32(skin.joints_offset) < 0) {
+        return;
+    }
+    let mesh = meshes.data[skin.mesh_index];
+    let local_index = id - skin.skin_vertices_offset;
+
+    let joints = vec4<u32>(
+        skin_vertex.joints.x & 0xFFFFu, 
+        skin_vertex.joints.x >> 16u, 
+        skin_vertex.joints.y & 0xFFFFu, 
+        skin_vertex.joints.y >> 16u
+    );
+    var weights = unpack_unorm_to_4_f32(skin_vertex.weights);
+    let total_weight = weights.x + weights.y + weights.z + weights.w;
+    if (total_weight <= 0.) {
+        return;
+    }
+    weights /= total_weight;
+    let m = joint_matrices.data[skin.joints_offset + joints.x] * weights.x
+          + joint_matrices.data[skin.joints_offset + joints.y] * weights.y
+          + joint_matrices.data[skin.joints_offset + joints.z] * weights.z
+          + joint_matrices.data[skin.joints_offset + joints.w] * weights.w;
+
+    let bind_size = skin.bind_aabb_max - skin.bind_aabb_min;
+    let bind_position = skin.bind_aabb_min + unpack_unorm_to_3_f32(skin_vertex.position) * bind_size;
+    let p = (m * vec4<f32>(bind_position, 1.)).xyz;
+
+    // Positions are quantized in the current skinned bounds, the same used by the mesh transforms
+    let size = max(skin.aabb_max - skin.aabb_min, vec3<f32>(1e-6));
+    let position = clamp((p - skin.aabb_min) / size, vec3<f32>(0.), vec3<f32>(1.));
+    vertices_positions.data[mesh.vertices_position_offset + local_index] = pack_3_f32_to_unorm(position);
+
+    let vertex_layout = mesh.flags_and_vertices_attribute_layout & 0x0000FFFFu;
+    let normal_offset = vertex_attribute_offset(vertex_layout, VERTEX_ATTRIBUTE_HAS_NORMAL);
+    if (normal_offset >= 0) {
+        let n = normalize((m * vec4<f32>(unpack_snorm_to_3_f32(skin_vertex.normal), 0.)).xyz);
+        let index = mesh.vertices_attribute_offset + local_index * vertex_layout_stride(vertex_layout) + u32(normal_offset);
+        vertices_attributes.data[index] = pack_3_f32_to_snorm(n);
+    }
+}
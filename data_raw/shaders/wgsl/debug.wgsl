@@ -1,6 +1,9 @@
 #import "common.inc"
 #import "utils.inc"
 
+// A variant for every display mode, selected by the debug pass from the constant data flags
+#keywords _ DISPLAY_MESHLETS DISPLAY_MESHLETS_LOD_LEVEL DISPLAY_UV_0 DISPLAY_UV_1 DISPLAY_UV_2 DISPLAY_UV_3 DISPLAY_NORMALS DISPLAY_TANGENT DISPLAY_BITANGENT DISPLAY_BASE_COLOR DISPLAY_METALLIC DISPLAY_ROUGHNESS DISPLAY_RADIANCE_BUFFER DISPLAY_DEPTH_BUFFER DISPLAY_PATHTRACE
+
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) uv: vec2<f32>,
@@ -130,335 +133,321 @@ fn fs_main(v_in: VertexOutput) -> @location(0) vec4<f32> {
     var out_color = vec4<f32>(0.);
     let pixel = vec2<f32>(screen_pixel);
     
-    if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_MESHLETS) != 0) {
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        var meshlet_id = 0xFFFFFFFFu;
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            let instance_id = (visibility_id >> 8u) - 1u; 
-            meshlet_id = instances.data[instance_id].meshlet_id;
-            let meshlet = meshlets.data[meshlet_id];
-            let meshlet_color = get_color_for_int(meshlet_id);
-            out_color = vec4<f32>(meshlet_color, 1.);
+#if defined(DISPLAY_MESHLETS)
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    var meshlet_id = 0xFFFFFFFFu;
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        let instance_id = (visibility_id >> 8u) - 1u; 
+        meshlet_id = instances.data[instance_id].meshlet_id;
+        let meshlet = meshlets.data[meshlet_id];
+        let meshlet_color = get_color_for_int(meshlet_id);
+        out_color = vec4<f32>(meshlet_color, 1.);
+    }
+    
+    let debug_pixel = vec2<u32>(constant_data.debug_uv_coords * vec2<f32>(visibility_dimensions));
+    let debug_visibility_value = textureLoad(visibility_texture, debug_pixel, 0);
+    let debug_visibility_id = debug_visibility_value.r;
+    if (debug_visibility_id != 0u && (debug_visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        let debug_instance_id = (debug_visibility_id >> 8u) - 1u; 
+        let debug_instance = instances.data[debug_instance_id];
+        let debug_meshlet_id = debug_instance.meshlet_id;
+        let debug_meshlet = meshlets.data[debug_meshlet_id];
+        if(debug_meshlet_id == meshlet_id) {
+            out_color = vec4<f32>(0.2, 0.2, 0.2, 0.1);
         }
-        
-        let debug_pixel = vec2<u32>(constant_data.debug_uv_coords * vec2<f32>(visibility_dimensions));
-        let debug_visibility_value = textureLoad(visibility_texture, debug_pixel, 0);
-        let debug_visibility_id = debug_visibility_value.r;
-        if (debug_visibility_id != 0u && (debug_visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            let debug_instance_id = (debug_visibility_id >> 8u) - 1u; 
-            let debug_instance = instances.data[debug_instance_id];
-            let debug_meshlet_id = debug_instance.meshlet_id;
-            let debug_meshlet = meshlets.data[debug_meshlet_id];
-            if(debug_meshlet_id == meshlet_id) {
-                out_color = vec4<f32>(0.2, 0.2, 0.2, 0.1);
-            }
-                
-            let transform = transforms.data[debug_instance.transform_id];
-            let orientation = transform.orientation;
-            let position = transform.position_scale_x.xyz;
-            let scale = vec3<f32>(transform.position_scale_x.w, transform.bb_min_scale_y.w, transform.bb_min_scale_y.w);
-            let cube_min = transform_vector(debug_meshlet.aabb_min, position, orientation, scale);
-            let cube_max = transform_vector(debug_meshlet.aabb_max, position, orientation, scale);
+            
+        let transform = transforms.data[debug_instance.transform_id];
+        let orientation = transform.orientation;
+        let position = transform.position_scale_x.xyz;
+        let scale = vec3<f32>(transform.position_scale_x.w, transform.bb_min_scale_y.w, transform.bb_min_scale_y.w);
+        let cube_min = transform_vector(debug_meshlet.aabb_min, position, orientation, scale);
+        let cube_max = transform_vector(debug_meshlet.aabb_max, position, orientation, scale);
 
-            out_color += vec4<f32>(draw_cube_from_min_max(cube_min, cube_max, screen_pixel, dimensions), 1.);
+        out_color += vec4<f32>(draw_cube_from_min_max(cube_min, cube_max, screen_pixel, dimensions), 1.);
+    }
+#elif defined(DISPLAY_MESHLETS_LOD_LEVEL)
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        let instance_id = (visibility_id >> 8u) - 1u; 
+        let meshlet_id = instances.data[instance_id].meshlet_id;
+        let meshlet = meshlets.data[meshlet_id];
+        if (meshlet.lod_level == MAX_LOD_LEVELS - 1) {
+            out_color = vec4<f32>(vec3<f32>(1., 0., 0.), 1.);
         }
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_MESHLETS_LOD_LEVEL) != 0) {
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            let instance_id = (visibility_id >> 8u) - 1u; 
-            let meshlet_id = instances.data[instance_id].meshlet_id;
-            let meshlet = meshlets.data[meshlet_id];
-            if (meshlet.lod_level == MAX_LOD_LEVELS - 1) {
-                out_color = vec4<f32>(vec3<f32>(1., 0., 0.), 1.);
-            }
-            else if (meshlet.lod_level == MAX_LOD_LEVELS - 2) {
-                out_color = vec4<f32>(vec3<f32>(1., 1., 0.), 1.);
-            }
-            else if (meshlet.lod_level == MAX_LOD_LEVELS - 3) {
-                out_color = vec4<f32>(vec3<f32>(0., 1., 0.), 1.);
-            }
-            else if (meshlet.lod_level == MAX_LOD_LEVELS - 4) {
-                out_color = vec4<f32>(vec3<f32>(0., 1., 1.), 1.);
-            }
-            else if (meshlet.lod_level == MAX_LOD_LEVELS - 5) {
-                out_color = vec4<f32>(vec3<f32>(0., 0., 1.), 1.);
-            }
-            else if (meshlet.lod_level == MAX_LOD_LEVELS - 6) {
-                out_color = vec4<f32>(vec3<f32>(0.3, 0.3, 0.3), 1.);
-            }
-            else if (meshlet.lod_level == MAX_LOD_LEVELS - 7) {
-                out_color = vec4<f32>(vec3<f32>(0.5, 0.5, 0.5), 1.);
-            }
-            else if (meshlet.lod_level == 0) {
-                out_color = vec4<f32>(vec3<f32>(1., 1., 1.), 1.);
-            } else {
-                out_color = vec4<f32>(vec3<f32>(0.1, 0.1, 0.1), 1.);
-            }
+        else if (meshlet.lod_level == MAX_LOD_LEVELS - 2) {
+            out_color = vec4<f32>(vec3<f32>(1., 1., 0.), 1.);
         }
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_UV_0) != 0) {
-        let depth_dimensions = textureDimensions(depth_texture);
-        let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
-        let depth_pixel = vec2<u32>(pixel * depth_scale);
-        let depth = textureLoad(depth_texture, depth_pixel, 0);
-        let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
-
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
-            out_color = vec4<f32>(vec3<f32>(pixel_data.uv_set[0].xy, 0.), 1.);
+        else if (meshlet.lod_level == MAX_LOD_LEVELS - 3) {
+            out_color = vec4<f32>(vec3<f32>(0., 1., 0.), 1.);
         }
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_UV_1) != 0) {
-        let depth_dimensions = textureDimensions(depth_texture);
-        let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
-        let depth_pixel = vec2<u32>(pixel * depth_scale);
-        let depth = textureLoad(depth_texture, depth_pixel, 0);
-        let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
-
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
-            out_color = vec4<f32>(vec3<f32>(pixel_data.uv_set[1].xy, 0.), 1.);
+        else if (meshlet.lod_level == MAX_LOD_LEVELS - 4) {
+            out_color = vec4<f32>(vec3<f32>(0., 1., 1.), 1.);
         }
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_UV_2) != 0) {
-        let depth_dimensions = textureDimensions(depth_texture);
-        let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
-        let depth_pixel = vec2<u32>(pixel * depth_scale);
-        let depth = textureLoad(depth_texture, depth_pixel, 0);
-        let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
-
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
-            out_color = vec4<f32>(vec3<f32>(pixel_data.uv_set[2].xy, 0.), 1.);
+        else if (meshlet.lod_level == MAX_LOD_LEVELS - 5) {
+            out_color = vec4<f32>(vec3<f32>(0., 0., 1.), 1.);
         }
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_UV_3) != 0) {
-        let depth_dimensions = textureDimensions(depth_texture);
-        let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
-        let depth_pixel = vec2<u32>(pixel * depth_scale);
-        let depth = textureLoad(depth_texture, depth_pixel, 0);
-        let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
-
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
-            out_color = vec4<f32>(vec3<f32>(pixel_data.uv_set[3].xy, 0.), 1.);
+        else if (meshlet.lod_level == MAX_LOD_LEVELS - 6) {
+            out_color = vec4<f32>(vec3<f32>(0.3, 0.3, 0.3), 1.);
         }
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_NORMALS) != 0) {
-        let depth_dimensions = textureDimensions(depth_texture);
-        let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
-        let depth_pixel = vec2<u32>(pixel * depth_scale);
-        let depth = textureLoad(depth_texture, depth_pixel, 0);
-        let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
-
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
-            var material = materials.data[pixel_data.material_id];
-            let tbn = compute_tbn(&material, &pixel_data);
-            out_color = vec4<f32>((vec3<f32>(1.) + tbn.normal) / vec3<f32>(2.), 1.);
+        else if (meshlet.lod_level == MAX_LOD_LEVELS - 7) {
+            out_color = vec4<f32>(vec3<f32>(0.5, 0.5, 0.5), 1.);
         }
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_TANGENT) != 0) {
-        let depth_dimensions = textureDimensions(depth_texture);
-        let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
-        let depth_pixel = vec2<u32>(pixel * depth_scale);
-        let depth = textureLoad(depth_texture, depth_pixel, 0);
-        let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
-
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
-            var material = materials.data[pixel_data.material_id];
-            let tbn = compute_tbn(&material, &pixel_data);
-            out_color = vec4<f32>((vec3<f32>(1.) + tbn.tangent) / vec3<f32>(2.), 1.);
+        else if (meshlet.lod_level == 0) {
+            out_color = vec4<f32>(vec3<f32>(1., 1., 1.), 1.);
+        } else {
+            out_color = vec4<f32>(vec3<f32>(0.1, 0.1, 0.1), 1.);
         }
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_BITANGENT) != 0) {
-        let depth_dimensions = textureDimensions(depth_texture);
-        let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
-        let depth_pixel = vec2<u32>(pixel * depth_scale);
-        let depth = textureLoad(depth_texture, depth_pixel, 0);
-        let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
+    }
+#elif defined(DISPLAY_UV_0)
+    let depth_dimensions = textureDimensions(depth_texture);
+    let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
+    let depth_pixel = vec2<u32>(pixel * depth_scale);
+    let depth = textureLoad(depth_texture, depth_pixel, 0);
+    let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
 
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
-            var material = materials.data[pixel_data.material_id];
-            let tbn = compute_tbn(&material, &pixel_data);
-            out_color = vec4<f32>((vec3<f32>(1.) + tbn.binormal) / vec3<f32>(2.), 1.);
-        }
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_BASE_COLOR) != 0) {
-        let depth_dimensions = textureDimensions(depth_texture);
-        let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
-        let depth_pixel = vec2<u32>(pixel * depth_scale);
-        let depth = textureLoad(depth_texture, depth_pixel, 0);
-        let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
+        out_color = vec4<f32>(vec3<f32>(pixel_data.uv_set[0].xy, 0.), 1.);
+    }
+#elif defined(DISPLAY_UV_1)
+    let depth_dimensions = textureDimensions(depth_texture);
+    let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
+    let depth_pixel = vec2<u32>(pixel * depth_scale);
+    let depth = textureLoad(depth_texture, depth_pixel, 0);
+    let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
 
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
-            let material_info = compute_color_from_material(pixel_data.material_id, &pixel_data);
-            out_color = vec4<f32>(vec3<f32>(material_info.base_color.rgb), 1.);
-        }
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_METALLIC) != 0) {
-        let depth_dimensions = textureDimensions(depth_texture);
-        let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
-        let depth_pixel = vec2<u32>(pixel * depth_scale);
-        let depth = textureLoad(depth_texture, depth_pixel, 0);
-        let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
+        out_color = vec4<f32>(vec3<f32>(pixel_data.uv_set[1].xy, 0.), 1.);
+    }
+#elif defined(DISPLAY_UV_2)
+    let depth_dimensions = textureDimensions(depth_texture);
+    let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
+    let depth_pixel = vec2<u32>(pixel * depth_scale);
+    let depth = textureLoad(depth_texture, depth_pixel, 0);
+    let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
 
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
-            let material_info = compute_color_from_material(pixel_data.material_id, &pixel_data);
-            out_color = vec4<f32>(vec3<f32>(material_info.metallic), 1.);
-        }
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_ROUGHNESS) != 0) {
-        let depth_dimensions = textureDimensions(depth_texture);
-        let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
-        let depth_pixel = vec2<u32>(pixel * depth_scale);
-        let depth = textureLoad(depth_texture, depth_pixel, 0);
-        let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
+        out_color = vec4<f32>(vec3<f32>(pixel_data.uv_set[2].xy, 0.), 1.);
+    }
+#elif defined(DISPLAY_UV_3)
+    let depth_dimensions = textureDimensions(depth_texture);
+    let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
+    let depth_pixel = vec2<u32>(pixel * depth_scale);
+    let depth = textureLoad(depth_texture, depth_pixel, 0);
+    let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
 
-        let visibility_dimensions = textureDimensions(visibility_texture);
-        let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
-        let visibility_pixel = vec2<u32>(pixel * visibility_scale);
-        let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
-        let visibility_id = visibility_value.r;
-        
-        if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
-            var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
-            let material_info = compute_color_from_material(pixel_data.material_id, &pixel_data);
-            out_color = vec4<f32>(vec3<f32>(material_info.perceptual_roughness), 1.);
-        }
-    } 
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
+        out_color = vec4<f32>(vec3<f32>(pixel_data.uv_set[3].xy, 0.), 1.);
+    }
+#elif defined(DISPLAY_NORMALS)
+    let depth_dimensions = textureDimensions(depth_texture);
+    let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
+    let depth_pixel = vec2<u32>(pixel * depth_scale);
+    let depth = textureLoad(depth_texture, depth_pixel, 0);
+    let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
+
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
+        var material = materials.data[pixel_data.material_id];
+        let tbn = compute_tbn(&material, &pixel_data);
+        out_color = vec4<f32>((vec3<f32>(1.) + tbn.normal) / vec3<f32>(2.), 1.);
+    }
+#elif defined(DISPLAY_TANGENT)
+    let depth_dimensions = textureDimensions(depth_texture);
+    let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
+    let depth_pixel = vec2<u32>(pixel * depth_scale);
+    let depth = textureLoad(depth_texture, depth_pixel, 0);
+    let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
+
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
+        var material = materials.data[pixel_data.material_id];
+        let tbn = compute_tbn(&material, &pixel_data);
+        out_color = vec4<f32>((vec3<f32>(1.) + tbn.tangent) / vec3<f32>(2.), 1.);
+    }
+#elif defined(DISPLAY_BITANGENT)
+    let depth_dimensions = textureDimensions(depth_texture);
+    let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
+    let depth_pixel = vec2<u32>(pixel * depth_scale);
+    let depth = textureLoad(depth_texture, depth_pixel, 0);
+    let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
+
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
+        var material = materials.data[pixel_data.material_id];
+        let tbn = compute_tbn(&material, &pixel_data);
+        out_color = vec4<f32>((vec3<f32>(1.) + tbn.binormal) / vec3<f32>(2.), 1.);
+    }
+#elif defined(DISPLAY_BASE_COLOR)
+    let depth_dimensions = textureDimensions(depth_texture);
+    let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
+    let depth_pixel = vec2<u32>(pixel * depth_scale);
+    let depth = textureLoad(depth_texture, depth_pixel, 0);
+    let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
+
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
+        let material_info = compute_color_from_material(pixel_data.material_id, &pixel_data);
+        out_color = vec4<f32>(vec3<f32>(material_info.base_color.rgb), 1.);
+    }
+#elif defined(DISPLAY_METALLIC)
+    let depth_dimensions = textureDimensions(depth_texture);
+    let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
+    let depth_pixel = vec2<u32>(pixel * depth_scale);
+    let depth = textureLoad(depth_texture, depth_pixel, 0);
+    let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
+
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
+        let material_info = compute_color_from_material(pixel_data.material_id, &pixel_data);
+        out_color = vec4<f32>(vec3<f32>(material_info.metallic), 1.);
+    }
+#elif defined(DISPLAY_ROUGHNESS)
+    let depth_dimensions = textureDimensions(depth_texture);
+    let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
+    let depth_pixel = vec2<u32>(pixel * depth_scale);
+    let depth = textureLoad(depth_texture, depth_pixel, 0);
+    let hit_point = pixel_to_world(depth_pixel, depth_dimensions, depth); 
+
+    let visibility_dimensions = textureDimensions(visibility_texture);
+    let visibility_scale = vec2<f32>(visibility_dimensions) / vec2<f32>(dimensions);
+    let visibility_pixel = vec2<u32>(pixel * visibility_scale);
+    let visibility_value = textureLoad(visibility_texture, visibility_pixel, 0);
+    let visibility_id = visibility_value.r;
+    
+    if (visibility_id != 0u && (visibility_id & 0xFFFFFFFFu) != 0xFF000000u) {
+        var pixel_data = visibility_to_gbuffer(visibility_id, hit_point);
+        let material_info = compute_color_from_material(pixel_data.material_id, &pixel_data);
+        out_color = vec4<f32>(vec3<f32>(material_info.perceptual_roughness), 1.);
+    }
     /*
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_RADIANCE_BUFFER) != 0) {
-        let data_dimensions = vec2<u32>(DEFAULT_WIDTH, DEFAULT_HEIGHT);
-        let data_scale = vec2<f32>(data_dimensions) / vec2<f32>(dimensions);
-        let data_pixel = vec2<u32>(pixel * data_scale);
-        let data_index = (data_pixel.y * data_dimensions.x + data_pixel.x) * SIZE_OF_DATA_BUFFER_ELEMENT;
-        let radiance = vec3<f32>(data_buffer_1[data_index], data_buffer_1[data_index + 1u], data_buffer_1[data_index + 2u]);
-        out_color = vec4<f32>(radiance, 1.);
-    } 
+#elif defined(DISPLAY_RADIANCE_BUFFER)
+    let data_dimensions = vec2<u32>(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+    let data_scale = vec2<f32>(data_dimensions) / vec2<f32>(dimensions);
+    let data_pixel = vec2<u32>(pixel * data_scale);
+    let data_index = (data_pixel.y * data_dimensions.x + data_pixel.x) * SIZE_OF_DATA_BUFFER_ELEMENT;
+    let radiance = vec3<f32>(data_buffer_1[data_index], data_buffer_1[data_index + 1u], data_buffer_1[data_index + 2u]);
+    out_color = vec4<f32>(radiance, 1.);
     */
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_DEPTH_BUFFER) != 0) {
-        let depth_dimensions = textureDimensions(depth_texture);
-        let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
-        let depth_pixel = vec2<u32>(pixel * depth_scale);
-        let depth = textureLoad(depth_texture, depth_pixel, 0);
-        let v = vec3<f32>(1. - depth) * 10.;
-        out_color = vec4<f32>(v, 1.);
-    } 
-    else if ((constant_data.flags & CONSTANT_DATA_FLAGS_DISPLAY_PATHTRACE) != 0) {
-        var origin = vec3<f32>(0.);
-        var direction = vec3<f32>(0.);
-        let line_color = vec3<f32>(0., 1., 0.);
-        let line_size = 0.003;
-        var bounce_index = 0u;
-        
-        let data_dimensions = vec2<u32>(DEFAULT_WIDTH, DEFAULT_HEIGHT);
-        let data_scale = vec2<f32>(data_dimensions) / vec2<f32>(dimensions);
-        let data_pixel = vec2<u32>(pixel * data_scale);
-        let data_index = (data_pixel.y * data_dimensions.x + data_pixel.x) * SIZE_OF_DATA_BUFFER_ELEMENT;
-        let radiance = vec3<f32>(data_buffer_1[data_index], data_buffer_1[data_index + 1u], data_buffer_1[data_index + 2u]);
-        var color = radiance.rgb;        
-        /*
-        var debug_bvh_index = 100u;
-        let max_bvh_index = u32(read_value_from_data_buffer(&data_buffer_debug, &debug_bvh_index));
-        while(debug_bvh_index < max_bvh_index) 
-        {
-            var min = read_vec3_from_data_buffer(&data_buffer_debug, &debug_bvh_index);
-            var max = read_vec3_from_data_buffer(&data_buffer_debug, &debug_bvh_index);
-            color += draw_cube_from_min_max(min, max, screen_pixel, dimensions);
-            //color += draw_line_3d(screen_pixel, dimensions, min, max, vec3<f32>(0.,0.,1.), line_size);
-        }
-        */
-        
-        var debug_index = 0u;        
-        let max_index = u32(data_buffer_debug[debug_index]);
-        debug_index = debug_index + 1u;
-        if(max_index > 8u) {
-            while(debug_index < max_index) {
-                let visibility_id = u32(data_buffer_debug[debug_index]);
-                color += draw_triangle_from_visibility(visibility_id, screen_pixel, dimensions);
-                
-                var previous = origin;
-                origin = vec3<f32>(data_buffer_debug[debug_index + 1u], data_buffer_debug[debug_index + 2u], data_buffer_debug[debug_index + 3u]);
-                direction = vec3<f32>(data_buffer_debug[debug_index + 4u], data_buffer_debug[debug_index + 5u], data_buffer_debug[debug_index + 6u]);
-                
-                if (bounce_index > 0u) {
-                    color += draw_line_3d(screen_pixel, dimensions, previous, origin, line_color, line_size);
-                }
-                bounce_index += 1u;
-                debug_index = debug_index + 7u;
+#elif defined(DISPLAY_DEPTH_BUFFER)
+    let depth_dimensions = textureDimensions(depth_texture);
+    let depth_scale = vec2<f32>(depth_dimensions) / vec2<f32>(dimensions);
+    let depth_pixel = vec2<u32>(pixel * depth_scale);
+    let depth = textureLoad(depth_texture, depth_pixel, 0);
+    let v = vec3<f32>(1. - depth) * 10.;
+    out_color = vec4<f32>(v, 1.);
+#elif defined(DISPLAY_PATHTRACE)
+    var origin = vec3<f32>(0.);
+    var direction = vec3<f32>(0.);
+    let line_color = vec3<f32>(0., 1., 0.);
+    let line_size = 0.003;
+    var bounce_index = 0u;
+    
+    let data_dimensions = vec2<u32>(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+    let data_scale = vec2<f32>(data_dimensions) / vec2<f32>(dimensions);
+    let data_pixel = vec2<u32>(pixel * data_scale);
+    let data_index = (data_pixel.y * data_dimensions.x + data_pixel.x) * SIZE_OF_DATA_BUFFER_ELEMENT;
+    let radiance = vec3<f32>(data_buffer_1[data_index], data_buffer_1[data_index + 1u], data_buffer_1[data_index + 2u]);
+    var color = radiance.rgb;        
+    /*
+    var debug_bvh_index = 100u;
+    let max_bvh_index = u32(read_value_from_data_buffer(&data_buffer_debug, &debug_bvh_index));
+    while(debug_bvh_index < max_bvh_index) 
+    {
+        var min = read_vec3_from_data_buffer(&data_buffer_debug, &debug_bvh_index);
+        var max = read_vec3_from_data_buffer(&data_buffer_debug, &debug_bvh_index);
+        color += draw_cube_from_min_max(min, max, screen_pixel, dimensions);
+        //color += draw_line_3d(screen_pixel, dimensions, min, max, vec3<f32>(0.,0.,1.), line_size);
+    }
+    */
+    
+    var debug_index = 0u;        
+    let max_index = u32(data_buffer_debug[debug_index]);
+    debug_index = debug_index + 1u;
+    if(max_index > 8u) {
+        while(debug_index < max_index) {
+            let visibility_id = u32(data_buffer_debug[debug_index]);
+            color += draw_triangle_from_visibility(visibility_id, screen_pixel, dimensions);
+            
+            var previous = origin;
+            origin = vec3<f32>(data_buffer_debug[debug_index + 1u], data_buffer_debug[debug_index + 2u], data_buffer_debug[debug_index + 3u]);
+            direction = vec3<f32>(data_buffer_debug[debug_index + 4u], data_buffer_debug[debug_index + 5u], data_buffer_debug[debug_index + 6u]);
+            
+            if (bounce_index > 0u) {
+                color += draw_line_3d(screen_pixel, dimensions, previous, origin, line_color, line_size);
             }
-            color += draw_line_3d(screen_pixel, dimensions, origin, origin + direction * 5., line_color, line_size);
-            out_color = vec4<f32>(color, 1.);
+            bounce_index += 1u;
+            debug_index = debug_index + 7u;
         }
+        color += draw_line_3d(screen_pixel, dimensions, origin, origin + direction * 5., line_color, line_size);
+        out_color = vec4<f32>(color, 1.);
     }
+#endif
     
     return select(vec4<f32>(0.), out_color, out_color.a > 0.);
 }
\ No newline at end of file
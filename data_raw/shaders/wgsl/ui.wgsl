@@ -21,12 +21,14 @@ struct UIInstance {
     @location(4) index_count: u32,
     @location(5) vertex_start: u32,
     @location(6) texture_index: u32,
+    @location(7) font_atlas: u32,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) color: vec4<f32>,
     @location(1) tex_coords: vec3<f32>,
+    @location(2) @interpolate(flat) font_atlas: u32,
 };
 
 
@@ -39,6 +41,7 @@ var<uniform> textures: Textures;
 
 #import "texture_utils.inc"
 #import "color_utils.inc"
+#import "font_utils.inc"
 
 
 @vertex
@@ -58,6 +61,7 @@ fn vs_main(
     );    
     vertex_out.color = unpack_color(u32(v_in.color));
     vertex_out.tex_coords = vec3<f32>(v_in.uv.xy, f32(i_in.texture_index));
+    vertex_out.font_atlas = i_in.font_atlas;
 
     return vertex_out;
 }
@@ -66,6 +70,14 @@ fn vs_main(
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let tex_linear = sample_texture(in.tex_coords);
 
+    // Derivatives must be computed outside of non uniform branches
+    let atlas_size = unpack2x16float(textures.data[max(i32(in.tex_coords.z), 0)].max);
+    let screen_px_range = font_screen_px_range(in.tex_coords.xy, font_distance_range(in.font_atlas), atlas_size);
+    if (in.font_atlas != FONT_ATLAS_NONE) {
+        let coverage = font_coverage(tex_linear, font_atlas_type(in.font_atlas), screen_px_range);
+        return vec4<f32>(in.color.rgb, in.color.a * coverage);
+    }
+
     //let tex_gamma = gamma_from_linear_rgba(tex_linear);
     //let out_color_gamma = in.color * tex_gamma;
     //return vec4<f32>(linear_from_gamma_rgb(out_color_gamma.rgb), out_color_gamma.a);